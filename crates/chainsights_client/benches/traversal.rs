@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Throughput of traversal, predicate parsing and signature verification over synthetic
+//! catalogs signed with an ephemeral key and served in-process.
+//!
+//! Catalog sizes come from `CHAINSIGHTS_BENCH_SIZES`, a comma-separated list of
+//! `COMPONENTSxRELEASES` or `COMPONENTSxRELEASESxPAGES` (default `10x5,50x10,50x10x5`):
+//!
+//! ```text
+//! cargo bench -p chainsights_client --features bench --bench traversal
+//! CHAINSIGHTS_BENCH_SIZES=500x20x10 cargo bench -p chainsights_client --features bench --bench traversal -- traversal/
+//! ```
+
+use std::fmt;
+use std::hint::black_box;
+
+use chainsights_client::client::Client;
+use chainsights_core::identity::SignerIdentity;
+use chainsights_core::statement::InTotoStatement;
+use chainsights_core::timestamp::TimeSource;
+use chainsights_core::verify::verify_signature_with_pae;
+use chainsights_predicates::parse_predicate;
+use chainsights_testing::{FixtureSpec, FixtureTree, MockEnvironment};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+
+const DEFAULT_SIZES: &str = "10x5,50x10,50x10x5";
+
+/// Base URL for trees that are only signed and parsed, never served.
+const UNSERVED_BASE_URL: &str = "http://bench.invalid";
+
+/// The shape of a synthetic catalog.
+#[derive(Debug, Clone, Copy)]
+struct Size {
+    components: usize,
+    releases: usize,
+    pages: usize,
+}
+
+impl Size {
+    fn parse(text: &str) -> Option<Self> {
+        let parts: Vec<usize> = text.trim().split('x').map(|n| n.parse().ok()).collect::<Option<_>>()?;
+        match parts[..] {
+            [components, releases] => Some(Self { components, releases, pages: 1 }),
+            [components, releases, pages] if pages > 0 => Some(Self { components, releases, pages }),
+            _ => None,
+        }
+    }
+
+    fn spec(self) -> FixtureSpec {
+        FixtureSpec::synthetic(self.components, self.releases).pages(self.pages)
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}x{}", self.components, self.releases, self.pages)
+    }
+}
+
+fn sizes() -> Vec<Size> {
+    let sizes = std::env::var("CHAINSIGHTS_BENCH_SIZES").unwrap_or_else(|_| DEFAULT_SIZES.to_string());
+    sizes
+        .split(',')
+        .map(|size| Size::parse(size).unwrap_or_else(|| panic!("Invalid CHAINSIGHTS_BENCH_SIZES entry '{}'", size)))
+        .collect()
+}
+
+/// The signed bundles of a tree: catalog pages, components and releases, but not artifacts.
+fn bundles(tree: &FixtureTree) -> Vec<String> {
+    tree.documents
+        .iter()
+        .filter(|(path, _)| !path.starts_with("/artifacts/"))
+        .map(|(_, bytes)| String::from_utf8(bytes.clone()).expect("bundles are UTF-8"))
+        .collect()
+}
+
+fn verify(bundle: &str, identities: &[SignerIdentity]) -> Vec<u8> {
+    verify_signature_with_pae(bundle, identities, TimeSource::Local, chrono::Duration::seconds(300))
+        .expect("fixture bundles verify")
+        .payload
+}
+
+/// Fetching, verifying and aggregating a whole tree from the local HTTP server.
+fn traversal(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let client = Client::builder().quiet(true).build();
+    let mut group = c.benchmark_group("traversal");
+    group.sample_size(10);
+    for size in sizes() {
+        let env = runtime.block_on(MockEnvironment::start(size.spec())).unwrap();
+        let statements = bundles(&FixtureTree::generate(&size.spec(), UNSERVED_BASE_URL).unwrap()).len();
+        group.throughput(Throughput::Elements(statements as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &env, |b, env| {
+            b.iter(|| {
+                let data = runtime.block_on(client.traverse(env.root_uri(), env.identity())).unwrap();
+                assert!(data.root_error.is_none(), "{:?}", data.root_error);
+                data
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Verifying the signature and signer identity of every bundle in a tree.
+fn verification(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verification");
+    for size in sizes() {
+        let spec = size.spec();
+        let identities = [SignerIdentity::new(&spec.identity)];
+        let bundles = bundles(&FixtureTree::generate(&spec, UNSERVED_BASE_URL).unwrap());
+        group.throughput(Throughput::Elements(bundles.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &bundles, |b, bundles| {
+            b.iter(|| {
+                for bundle in bundles {
+                    black_box(verify(black_box(bundle), &identities));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Parsing the verified statements of a tree into predicates.
+fn parsing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parsing");
+    for size in sizes() {
+        let spec = size.spec();
+        let identities = [SignerIdentity::new(&spec.identity)];
+        let payloads: Vec<Vec<u8>> = bundles(&FixtureTree::generate(&spec, UNSERVED_BASE_URL).unwrap())
+            .iter()
+            .map(|bundle| verify(bundle, &identities))
+            .collect();
+        group.throughput(Throughput::Elements(payloads.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &payloads, |b, payloads| {
+            b.iter(|| {
+                for payload in payloads {
+                    let statement: InTotoStatement = serde_json::from_slice(black_box(payload)).unwrap();
+                    black_box(parse_predicate(&statement).unwrap());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, traversal, verification, parsing);
+criterion_main!(benches);
@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generates the gRPC service of the `grpc` feature. The protobuf definitions are compiled with
+//! protox, so building does not need `protoc` installed.
+
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        const PROTO: &str = "proto/chainsights/v1/query.proto";
+        println!("cargo:rerun-if-changed={}", PROTO);
+        let descriptors = protox::compile([PROTO], ["proto"]).expect("Failed to compile the gRPC service definition");
+        tonic_build::configure()
+            .compile_fds(descriptors)
+            .expect("Failed to generate the gRPC service");
+    }
+}
@@ -0,0 +1,323 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Admission decisions for container images, for Kubernetes admission controllers that gate
+//! deployments on Chainsights attestations.
+//!
+//! An [`AdmissionVerifier`] traverses a fixed set of supplier domains and indexes every image
+//! their verified releases list by manifest digest, so that each decision is a lookup:
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! use chainsights_client::admission::AdmissionVerifier;
+//! use chainsights_client::client::Client;
+//!
+//! let verifier = AdmissionVerifier::new(Client::builder().quiet(true).build(), &["example.com"])?;
+//! verifier.warm().await?;
+//! let verdict = verifier.verify_image_digest("sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae").await;
+//! println!("{}", if verdict.is_allowed() { "admit" } else { "reject" });
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+use chainsights_core::identity::normalize_domain;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use tokio::sync::{Mutex, RwLock};
+
+use crate::client::Client;
+use crate::discovery::Discovery;
+use crate::fetch::Fetchers;
+use crate::image::attested_images;
+use crate::output::{self, Status};
+
+pub use crate::image::ImageRelease;
+
+/// How long the image index is used before it is rebuilt by default.
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+
+/// How long a domain that fails to traverse keeps its images by default.
+const DEFAULT_MAX_STALENESS: Duration = Duration::from_secs(3600);
+
+/// Whether an image may be admitted.
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "decision", rename_all = "kebab-case")]
+pub enum Verdict {
+    /// Verified releases that have not been revoked attest to the image. Pulled releases are
+    /// included, with their status, so that callers can warn about them.
+    Allowed {
+        releases: Vec<ImageRelease>,
+        /// When the releases were last verified, if that was longer ago than the refresh
+        /// interval because their domains have since failed to traverse
+        #[serde(skip_serializing_if = "Option::is_none")]
+        stale_since: Option<String>,
+    },
+    /// No release attests to the image, or every one that does has been revoked.
+    Denied { reason: String },
+}
+
+impl Verdict {
+    /// Returns true if the image may be admitted.
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, Verdict::Allowed { .. })
+    }
+}
+
+/// The images each domain's releases attest to.
+#[derive(Default)]
+struct ImageIndex {
+    built: Option<Instant>,
+    domains: HashMap<String, DomainImages>,
+}
+
+/// The images a domain's releases attest to, by lowercase hex sha256 digest, as of its last
+/// successful traversal.
+struct DomainImages {
+    traversed: Instant,
+    traversed_at: DateTime<Utc>,
+    images: HashMap<String, Vec<ImageRelease>>,
+}
+
+/// Answers admission decisions from an index of the images configured supplier domains attest to.
+/// The index is built on first use, or ahead of it with [`warm`](Self::warm), and rebuilt every
+/// refresh interval by [`keep_warm`](Self::keep_warm), or by calling `warm` again. A domain that
+/// fails to traverse keeps its images until they are older than the maximum staleness, after which
+/// they are denied.
+pub struct AdmissionVerifier {
+    discovery: Box<dyn Discovery>,
+    fetchers: Fetchers,
+    domains: Vec<String>,
+    refresh_interval: Duration,
+    max_staleness: Duration,
+    index: RwLock<ImageIndex>,
+    /// Held while the index is rebuilt, so that only one rebuild runs at a time.
+    rebuilding: Mutex<()>,
+}
+
+impl AdmissionVerifier {
+    /// A verifier trusting the releases of `domains`, traversed with `client`.
+    pub fn new(client: Client, domains: &[&str]) -> Result<Self> {
+        let domains = domains.iter().map(|domain| normalize_domain(domain)).collect::<Result<Vec<_>>>()?;
+        let (discovery, fetchers) = client.into_discovery();
+        Ok(Self::with_discovery(discovery, fetchers, domains))
+    }
+
+    pub(crate) fn with_discovery(discovery: Box<dyn Discovery>, fetchers: Fetchers, domains: Vec<String>) -> Self {
+        Self {
+            discovery,
+            fetchers,
+            domains,
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            max_staleness: DEFAULT_MAX_STALENESS,
+            index: RwLock::new(ImageIndex::default()),
+            rebuilding: Mutex::new(()),
+        }
+    }
+
+    /// Sets how long the index is used before it is rebuilt. Defaults to five minutes.
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Sets how long a domain that fails to traverse keeps the images it attested to before. Once
+    /// its last successful traversal is older, its images are denied. Defaults to one hour.
+    pub fn max_staleness(mut self, max_staleness: Duration) -> Self {
+        self.max_staleness = max_staleness;
+        self
+    }
+
+    /// Builds the index now, e.g. before a webhook starts answering. Returns the number of image
+    /// digests indexed. Fails only if no domain could be traversed.
+    pub async fn warm(&self) -> Result<usize> {
+        let _rebuilding = self.rebuilding.lock().await;
+        self.rebuild().await
+    }
+
+    /// Rebuilds the index every refresh interval, so that decisions never wait for a rebuild.
+    /// Runs until the future is dropped.
+    pub async fn keep_warm(&self) {
+        loop {
+            tokio::time::sleep(self.refresh_interval).await;
+            // Failures are already reported per domain, and the previous index stays in use.
+            let _ = self.warm().await;
+        }
+    }
+
+    /// Decides whether the image with manifest digest `digest` (`sha256:<hex>`, or an image
+    /// reference pinned by digest) may be admitted.
+    pub async fn verify_image_digest(&self, digest: &str) -> Verdict {
+        let Some(hex) = digest
+            .rsplit_once("sha256:")
+            .map(|(_, hex)| hex.to_ascii_lowercase())
+            .filter(|hex| hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+        else {
+            return Verdict::Denied { reason: format!("'{}' is not a sha256 image digest", digest) };
+        };
+        // Only the first decision waits for the index; after that keep_warm refreshes it in the
+        // background and decisions are answered from the index as it is.
+        if self.index.read().await.built.is_none() {
+            let _rebuilding = self.rebuilding.lock().await;
+            // Another decision may have built the index while this one waited.
+            if self.index.read().await.built.is_none() {
+                let _ = self.rebuild().await;
+            }
+        }
+        let index = self.index.read().await;
+        let mut releases = Vec::new();
+        let mut stale_since: Option<DateTime<Utc>> = None;
+        let mut expired = Vec::new();
+        for (domain, indexed) in &index.domains {
+            let Some(attesting) = indexed.images.get(&hex) else {
+                continue;
+            };
+            let age = indexed.traversed.elapsed();
+            if age > self.max_staleness {
+                expired.push(domain.as_str());
+                continue;
+            }
+            if age > self.refresh_interval {
+                stale_since = Some(stale_since.map_or(indexed.traversed_at, |since| since.min(indexed.traversed_at)));
+            }
+            releases.extend(attesting.iter().cloned());
+        }
+        if releases.is_empty() {
+            let mut reason = format!("No verified release of {} attests to image sha256:{}", self.domains.join(", "), hex);
+            if !expired.is_empty() {
+                expired.sort_unstable();
+                reason.push_str(&format!(
+                    "; releases of {} did, but were last verified more than {}s ago",
+                    expired.join(", "),
+                    self.max_staleness.as_secs()
+                ));
+            }
+            return Verdict::Denied { reason };
+        }
+        if releases.iter().all(|release| release.revoked.is_some()) {
+            return Verdict::Denied { reason: format!("Every release attesting to image sha256:{} has been revoked", hex) };
+        }
+        Verdict::Allowed {
+            releases,
+            stale_since: stale_since.map(|since| since.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        }
+    }
+
+    /// Traverses every domain and replaces its part of the index. Domains that fail keep the
+    /// images they attested to before, so that an outage does not block deployments, until those
+    /// are older than the maximum staleness.
+    async fn rebuild(&self) -> Result<usize> {
+        let mut traversed = HashMap::new();
+        let mut failures = Vec::new();
+        for domain in &self.domains {
+            match self.discovery.discover(domain, &self.fetchers).await {
+                Ok(data) => {
+                    let mut images: HashMap<String, Vec<ImageRelease>> = HashMap::new();
+                    for (digest, release) in attested_images(domain, &data) {
+                        images.entry(digest).or_default().push(release);
+                    }
+                    traversed.insert(domain.clone(), DomainImages { traversed: Instant::now(), traversed_at: Utc::now(), images });
+                }
+                Err(e) => {
+                    output::eprint(0, Status::Warning, &format!("Keeping the previous images of '{}': {:#}", domain, e));
+                    failures.push(format!("{}: {:#}", domain, e));
+                }
+            }
+        }
+        if traversed.is_empty() && !self.domains.is_empty() {
+            bail!("No supplier domain could be traversed: {}", failures.join("; "));
+        }
+        let mut index = self.index.write().await;
+        index.domains.retain(|domain, indexed| {
+            let expired = !traversed.contains_key(domain) && indexed.traversed.elapsed() > self.max_staleness;
+            if expired {
+                output::eprint(0, Status::Warning, &format!(
+                    "Dropping the images of '{}', last verified at {}",
+                    domain,
+                    indexed.traversed_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+                ));
+            }
+            !expired
+        });
+        index.domains.extend(traversed);
+        index.built = Some(Instant::now());
+        Ok(index.domains.values().map(|indexed| indexed.images.len()).sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use futures::future::BoxFuture;
+    use serde_json::json;
+
+    use super::*;
+    use crate::models::aggregation::AggregatedCatalogData;
+
+    /// Discovers a release of the image `sha256:aaaa...` until an outage starts.
+    struct FlakyDiscovery(Arc<AtomicBool>);
+
+    impl Discovery for FlakyDiscovery {
+        fn discover<'a>(&'a self, _: &'a str, _: &'a Fetchers) -> BoxFuture<'a, Result<AggregatedCatalogData>> {
+            Box::pin(async move {
+                if self.0.load(Ordering::SeqCst) {
+                    bail!("connection refused");
+                }
+                Ok(serde_json::from_value(json!({
+                    "catalog_predicate": null,
+                    "root_error": null,
+                    "component_errors": [],
+                    "components": [{
+                        "component_predicate": null,
+                        "component_link_uri": "https://example.com/app.json",
+                        "release_errors": [],
+                        "releases": [{
+                            "release_predicate": {
+                                "timestamp": "2025-01-01T00:00:00Z",
+                                "purl": "pkg:chainsights/example.com/app@1.0.0",
+                                "name": "1.0.0",
+                                "metadataLinks": [],
+                                "artifacts": [{ "uri": format!("oci://ghcr.io/example/app@sha256:{}", "a".repeat(64)) }],
+                            },
+                            "metadata_artifacts": [],
+                            "release_link_uri": "https://example.com/app/1.0.0.json",
+                            "verification": { "method": "sigstore-keyless", "identity": "release@example.com" },
+                            "artifact_fetch_errors": [],
+                        }],
+                    }],
+                }))?)
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn failing_domains_keep_their_images_until_they_are_too_stale() {
+        let digest = format!("sha256:{}", "a".repeat(64));
+        let outage = Arc::new(AtomicBool::new(false));
+        let verifier = |max_staleness| {
+            AdmissionVerifier::with_discovery(Box::new(FlakyDiscovery(outage.clone())), Fetchers::new(reqwest::Client::new()), vec!["example.com".to_string()])
+                .refresh_interval(Duration::ZERO)
+                .max_staleness(max_staleness)
+        };
+        let tolerant = verifier(Duration::from_secs(3600));
+        let strict = verifier(Duration::ZERO);
+        // The first decision builds the index.
+        assert!(tolerant.verify_image_digest(&digest).await.is_allowed());
+        strict.warm().await.unwrap();
+
+        outage.store(true, Ordering::SeqCst);
+        assert!(tolerant.warm().await.is_err());
+        let Verdict::Allowed { stale_since, .. } = tolerant.verify_image_digest(&digest).await else {
+            panic!("the images of a recently verified domain are kept");
+        };
+        assert!(stale_since.is_some());
+        let Verdict::Denied { reason } = strict.verify_image_digest(&digest).await else {
+            panic!("the images of a domain verified too long ago are denied");
+        };
+        assert!(reason.contains("releases of example.com did, but were last verified more than 0s ago"), "{}", reason);
+    }
+}
@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chainsights_core::bundle::{
+    CertificateData, DsseEnvelope, SignatureData, SigstoreBundleData, VerificationMaterial,
+};
+use futures::future::BoxFuture;
+use serde::Deserialize;
+
+use crate::fetch::Fetcher;
+use crate::output::{self, Status};
+
+const ARCHIVISTA_SCHEME: &str = "archivista://";
+
+/// Fetches attestations stored in an Archivista instance.
+///
+/// Supported URI forms:
+/// - `archivista://<host>/<gitoid>` downloads the envelope with the given gitoid.
+/// - `archivista://<host>/subject/<algorithm>:<digest>` searches for the first envelope
+///   whose statement has a subject with the given digest.
+///
+/// Archivista stores bare DSSE envelopes (witness format), so downloaded envelopes are
+/// repackaged as Sigstore bundles for the regular verification path.
+pub(crate) struct ArchivistaFetcher {
+    client: reqwest::Client,
+}
+
+/// Where in an Archivista instance an attestation should be looked up.
+#[derive(Debug)]
+enum ArchivistaTarget {
+    Gitoid(String),
+    SubjectDigest { algorithm: String, value: String },
+}
+
+/// A DSSE envelope as stored by Archivista.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ArchivistaEnvelope {
+    payload: String,
+    payload_type: String,
+    signatures: Vec<ArchivistaSignature>,
+}
+
+/// A signature within an Archivista envelope. The certificate is base64 encoded PEM.
+#[derive(Deserialize, Debug)]
+struct ArchivistaSignature {
+    sig: String,
+    certificate: Option<String>,
+}
+
+impl ArchivistaFetcher {
+    pub(crate) fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+
+    /// Looks up the gitoid of the first envelope with a subject matching the given digest.
+    async fn search_by_subject_digest(
+        &self,
+        base_url: &str,
+        algorithm: &str,
+        value: &str,
+    ) -> Result<String> {
+        let query = r#"query ($algorithm: String!, $value: String!) {
+  dsses(where: {hasStatementWith: {hasSubjectsWith: {hasSubjectDigestsWith: {algorithm: $algorithm, value: $value}}}}) {
+    edges { node { gitoidSha256 } }
+  }
+}"#;
+        let body = serde_json::json!({
+            "query": query,
+            "variables": { "algorithm": algorithm, "value": value },
+        });
+        let response: serde_json::Value = self
+            .client
+            .post(format!("{}/query", base_url))
+            .json(&body)
+            .send()
+            .await
+            .with_context(|| format!("Failed to query Archivista at '{}'", base_url))?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse Archivista query response")?;
+
+        if let Some(errors) = response.get("errors") {
+            bail!("Archivista query returned errors: {}", errors);
+        }
+
+        response
+            .pointer("/data/dsses/edges/0/node/gitoidSha256")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                anyhow!(
+                    "No attestation found in Archivista for subject digest {}:{}",
+                    algorithm,
+                    value
+                )
+            })
+    }
+
+    /// Downloads the envelope with the given gitoid and repackages it as a bundle.
+    async fn download(&self, base_url: &str, gitoid: &str) -> Result<Vec<u8>> {
+        let envelope: ArchivistaEnvelope = self
+            .client
+            .get(format!("{}/download/{}", base_url, gitoid))
+            .send()
+            .await
+            .with_context(|| format!("Failed to download gitoid '{}' from Archivista", gitoid))?
+            .error_for_status()?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse Archivista envelope '{}'", gitoid))?;
+
+        let bundle = envelope_to_bundle(envelope)?;
+        serde_json::to_vec(&bundle).context("Failed to serialize bundle")
+    }
+}
+
+impl Fetcher for ArchivistaFetcher {
+    fn supports(&self, uri: &str) -> bool {
+        uri.starts_with(ARCHIVISTA_SCHEME)
+    }
+
+    fn fetch<'a>(&'a self, uri: &'a str) -> BoxFuture<'a, Result<Vec<u8>>> {
+        Box::pin(async move {
+            let (host, target) = parse_archivista_uri(uri)?;
+            let base_url = format!("https://{}", host);
+            let gitoid = match target {
+                ArchivistaTarget::Gitoid(gitoid) => gitoid,
+                ArchivistaTarget::SubjectDigest { algorithm, value } => {
+                    self.search_by_subject_digest(&base_url, &algorithm, &value)
+                        .await?
+                }
+            };
+            output::print(2, Status::Info, &format!("Fetching gitoid {} from Archivista at {}", gitoid, host));
+            self.download(&base_url, &gitoid).await
+        })
+    }
+}
+
+/// Splits an `archivista://` URI into its host and lookup target.
+fn parse_archivista_uri(uri: &str) -> Result<(String, ArchivistaTarget)> {
+    let rest = uri
+        .strip_prefix(ARCHIVISTA_SCHEME)
+        .ok_or_else(|| anyhow!("Not an archivista URI: '{}'", uri))?;
+    let (host, path) = rest
+        .split_once('/')
+        .ok_or_else(|| anyhow!("Archivista URI '{}' is missing a path", uri))?;
+    if host.is_empty() {
+        bail!("Archivista URI '{}' is missing a host", uri);
+    }
+
+    let target = match path.strip_prefix("subject/") {
+        Some(digest) => {
+            let (algorithm, value) = digest.split_once(':').ok_or_else(|| {
+                anyhow!(
+                    "Archivista subject lookup '{}' must be of the form <algorithm>:<digest>",
+                    digest
+                )
+            })?;
+            if algorithm.is_empty() || value.is_empty() {
+                bail!("Archivista subject lookup '{}' has an empty algorithm or digest", digest);
+            }
+            ArchivistaTarget::SubjectDigest {
+                algorithm: algorithm.to_string(),
+                value: value.to_string(),
+            }
+        }
+        None if !path.is_empty() && !path.contains('/') => ArchivistaTarget::Gitoid(path.to_string()),
+        None => bail!("Archivista URI '{}' must reference a gitoid or subject digest", uri),
+    };
+
+    Ok((host.to_string(), target))
+}
+
+/// Converts an Archivista envelope into the Sigstore bundle layout used for verification.
+fn envelope_to_bundle(envelope: ArchivistaEnvelope) -> Result<SigstoreBundleData> {
+    let signature = envelope
+        .signatures
+        .into_iter()
+        .next()
+        .context("Archivista envelope contains no signatures")?;
+    let cert_b64 = signature.certificate.context(
+        "Archivista envelope signature carries no certificate; only certificate-based signatures are supported",
+    )?;
+    let cert_pem = STANDARD
+        .decode(cert_b64)
+        .context("Failed to decode Archivista signature certificate")?;
+    let cert = pem::parse(&cert_pem).context("Archivista signature certificate is not valid PEM")?;
+
+    Ok(SigstoreBundleData {
+        verification_material: VerificationMaterial {
+            certificate: CertificateData {
+                raw_bytes: STANDARD.encode(cert.contents()),
+            },
+            tlog_entries: Vec::new(),
+            timestamp_verification_data: None,
+        },
+        dsse_envelope: DsseEnvelope {
+            payload: envelope.payload,
+            payload_type: envelope.payload_type,
+            signatures: vec![SignatureData { sig: signature.sig }],
+        },
+    })
+}
@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! A blocking version of [`crate::client`], for code that does not run an async runtime.
+//!
+//! Each client owns a single-threaded Tokio runtime that its calls block on, so like
+//! `reqwest::blocking` it must not be used from within an async runtime.
+
+use anyhow::{Context, Result};
+use tokio::runtime::Runtime;
+
+pub use crate::client::TimeSource;
+use crate::models::aggregation::AggregatedCatalogData;
+
+/// A blocking client that discovers, verifies and aggregates a domain's attestations.
+///
+/// ```no_run
+/// let client = chainsights_client::blocking::Client::new()?;
+/// let data = client.discover("example.com")?;
+/// println!("{} components", data.components.len());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct Client {
+    inner: crate::client::Client,
+    runtime: Runtime,
+}
+
+impl Client {
+    /// A client with the default options.
+    pub fn new() -> Result<Self> {
+        Self::builder().build()
+    }
+
+    /// Starts configuring a client.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// Looks up the domain's `_chainsights` DNS record and traverses everything it publishes.
+    #[cfg(feature = "dns-discovery")]
+    pub fn discover(&self, domain: &str) -> Result<AggregatedCatalogData> {
+        self.runtime.block_on(self.inner.discover(domain))
+    }
+
+    /// Traverses from a known root catalog URI, which must be signed by `identity`.
+    pub fn traverse(&self, root_uri: &str, identity: &str) -> Result<AggregatedCatalogData> {
+        self.runtime.block_on(self.inner.traverse(root_uri, identity))
+    }
+}
+
+/// Configures a blocking [`Client`].
+#[derive(Default)]
+pub struct ClientBuilder {
+    inner: crate::client::ClientBuilder,
+}
+
+impl ClientBuilder {
+    /// Searches this Rekor instance for attestations whose links fail to fetch.
+    pub fn rekor_fallback(self, rekor_url: &str) -> Self {
+        Self { inner: self.inner.rekor_fallback(rekor_url) }
+    }
+
+    /// Leaves revoked components and releases out of results instead of marking them.
+    pub fn exclude_revoked(self, exclude: bool) -> Self {
+        Self { inner: self.inner.exclude_revoked(exclude) }
+    }
+
+    /// Sets the tolerated clock difference for expiry, freshness and certificate validity checks.
+    pub fn clock_skew(self, skew: chrono::Duration) -> Self {
+        Self { inner: self.inner.clock_skew(skew) }
+    }
+
+    /// Sets where signing times come from.
+    pub fn time_source(self, time_source: TimeSource) -> Self {
+        Self { inner: self.inner.time_source(time_source) }
+    }
+
+    /// Stops traversals from printing a line for each attestation they verify or reject.
+    pub fn quiet(self, quiet: bool) -> Self {
+        Self { inner: self.inner.quiet(quiet) }
+    }
+
+    /// Reuses `_chainsights` TXT answers and root catalog bundles across queries for up to `ttl`.
+    pub fn cache_ttl(self, ttl: std::time::Duration) -> Self {
+        Self { inner: self.inner.cache_ttl(ttl) }
+    }
+
+    /// Reports attestation links outside the queried domain and `allowed_hosts` as errors.
+    pub fn scope_links(self, allowed_hosts: &[&str]) -> Self {
+        Self { inner: self.inner.scope_links(allowed_hosts) }
+    }
+
+    /// Only accepts release statements whose signing certificate satisfies `policy`.
+    pub fn release_build_policy(self, policy: chainsights_core::identity::BuildPolicy) -> Self {
+        Self { inner: self.inner.release_build_policy(policy) }
+    }
+
+    /// Verifies bundles against the trust material of a Sigstore instance.
+    pub fn trusted_root(self, trusted_root: chainsights_core::trust::TrustedRoot) -> Self {
+        Self { inner: self.inner.trusted_root(trusted_root) }
+    }
+
+    /// Looks up the keys of PGP-signed attestations in `keyring`.
+    pub fn pgp_keyring(self, keyring: chainsights_core::pgp::PgpKeyring) -> Self {
+        Self { inner: self.inner.pgp_keyring(keyring) }
+    }
+
+    /// Requires HTTPS hosts whose TLS certificates match their DANE TLSA records.
+    pub fn dane(self, resolver: impl crate::dane::TlsaResolver + 'static) -> Self {
+        Self { inner: self.inner.dane(resolver) }
+    }
+
+    /// Answers `_chainsights` TXT lookups with `resolver` instead of the system's DNS servers.
+    #[cfg(feature = "dns-discovery")]
+    pub fn txt_resolver(self, resolver: impl crate::dns::TxtResolver + 'static) -> Self {
+        Self { inner: self.inner.txt_resolver(resolver) }
+    }
+
+    /// Builds the client and its runtime.
+    pub fn build(self) -> Result<Client> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .context("Failed to start the blocking client's runtime")?;
+        // The HTTP client is created inside the runtime so its connection pool belongs to it.
+        let inner = runtime.block_on(async { self.inner.build() });
+        Ok(Client { inner, runtime })
+    }
+}
@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Time-limited caches for results that repeated queries would otherwise fetch again, such as
+//! `_chainsights` TXT answers and root catalog bundles.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A map whose entries expire a fixed time after they were inserted.
+pub(crate) struct TtlCache<V> {
+    entries: Mutex<HashMap<String, (Instant, V)>>,
+}
+
+impl<V: Clone> TtlCache<V> {
+    pub(crate) fn new() -> Self {
+        Self { entries: Mutex::new(HashMap::new()) }
+    }
+
+    /// Returns the value cached under `key`, unless it has expired.
+    pub(crate) fn get(&self, key: &str) -> Option<V> {
+        self.get_at(key, Instant::now())
+    }
+
+    /// Caches `value` under `key` for `ttl`. A zero `ttl` caches nothing.
+    pub(crate) fn insert(&self, key: &str, value: V, ttl: Duration) {
+        self.insert_at(key, value, ttl, Instant::now());
+    }
+
+    fn get_at(&self, key: &str, now: Instant) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(key) {
+            Some((expires, value)) if now < *expires => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert_at(&self, key: &str, value: V, ttl: Duration, now: Instant) {
+        if ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, (expires, _)| now < *expires);
+        entries.insert(key.to_string(), (now + ttl, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_expire() {
+        let cache = TtlCache::new();
+        let inserted = Instant::now();
+        cache.insert_at("fresh", 1, Duration::from_secs(60), inserted);
+        cache.insert_at("stale", 2, Duration::from_secs(1), inserted);
+        cache.insert_at("uncached", 3, Duration::ZERO, inserted);
+
+        let later = inserted + Duration::from_secs(1);
+        assert_eq!(cache.get_at("fresh", later), Some(1));
+        assert_eq!(cache.get_at("stale", inserted), Some(2));
+        assert_eq!(cache.get_at("stale", later), None);
+        assert_eq!(cache.get_at("uncached", inserted), None);
+    }
+}
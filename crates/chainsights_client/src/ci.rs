@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Describing a release from inside the CI job that built it, for `ci emit-release`: the
+//! repository and tag come from the job's environment, and the artifacts from the build outputs.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+
+use crate::generate::build::file_artifact;
+use crate::generate::{ReleaseDraft, metadata_media_type};
+use crate::models::chainsights::ArtifactLink;
+
+/// What the CI job's environment says about the build, from GitHub Actions or GitLab CI variables.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct BuildEnvironment {
+    /// Web URL of the repository being built.
+    pub(crate) repository_url: Option<String>,
+    /// The tag or branch being built.
+    pub(crate) ref_name: Option<String>,
+    /// URL of the release page of the tag being built.
+    pub(crate) release_page: Option<String>,
+    /// Base URL the tag's release assets download from, where the forge has one.
+    pub(crate) download_base: Option<String>,
+}
+
+impl BuildEnvironment {
+    pub(crate) fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        if let Some(repository) = var("GITHUB_REPOSITORY") {
+            let server = var("GITHUB_SERVER_URL").unwrap_or_else(|| "https://github.com".to_string());
+            let repository_url = format!("{}/{}", server.trim_end_matches('/'), repository);
+            let tag = var("GITHUB_REF_NAME").filter(|_| var("GITHUB_REF_TYPE").as_deref() == Some("tag"));
+            return Self {
+                release_page: tag.as_ref().map(|tag| format!("{}/releases/tag/{}", repository_url, tag)),
+                download_base: tag.as_ref().map(|tag| format!("{}/releases/download/{}/", repository_url, tag)),
+                ref_name: var("GITHUB_REF_NAME"),
+                repository_url: Some(repository_url),
+            };
+        }
+        if let Some(repository_url) = var("CI_PROJECT_URL") {
+            // GitLab release assets are links to wherever they were uploaded, so there is no download base.
+            let tag = var("CI_COMMIT_TAG");
+            return Self {
+                release_page: tag.as_ref().map(|tag| format!("{}/-/releases/{}", repository_url, tag)),
+                download_base: None,
+                ref_name: tag.or_else(|| var("CI_COMMIT_REF_NAME")),
+                repository_url: Some(repository_url),
+            };
+        }
+        Self::default()
+    }
+
+    /// The name of the repository being built, the last segment of its URL.
+    pub(crate) fn repository_name(&self) -> Option<&str> {
+        self.repository_url.as_deref().and_then(|url| url.rsplit('/').next()).filter(|name| !name.is_empty())
+    }
+}
+
+/// Links build outputs served at `base_uri` followed by their file names, with their digests.
+pub(crate) fn file_artifacts(files: &[PathBuf], base_uri: &str) -> Result<Vec<ArtifactLink>> {
+    files
+        .iter()
+        .map(|file| {
+            let file_name = file
+                .file_name()
+                .and_then(|name| name.to_str())
+                .with_context(|| format!("'{}' has no file name", file.display()))?;
+            file_artifact(format!("{}{}", base_uri, file_name), file)
+        })
+        .collect()
+}
+
+/// Describes release `version` with its build outputs. Those recognized as supply chain metadata
+/// (SBOMs, provenance) become metadata links; every other output is a released artifact.
+pub(crate) fn release_draft(
+    version: &str,
+    name: &str,
+    links: Vec<ArtifactLink>,
+    release_notes_uri: Option<String>,
+    lifecycle_phase: Option<String>,
+) -> ReleaseDraft {
+    let (metadata_links, artifacts) =
+        links.into_iter().partition(|link| metadata_media_type(link.uri.rsplit('/').next().unwrap_or_default()).is_some());
+    ReleaseDraft {
+        version: version.to_string(),
+        name: name.to_string(),
+        cpe: None,
+        release_date: Some(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)),
+        release_notes_uri,
+        lifecycle_phase,
+        metadata_links,
+        artifacts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn describes_tagged_github_builds() {
+        let vars = HashMap::from([
+            ("GITHUB_REPOSITORY", "example/app"),
+            ("GITHUB_REF_NAME", "v1.2.0"),
+            ("GITHUB_REF_TYPE", "tag"),
+        ]);
+        let environment = BuildEnvironment::from_vars(|name| vars.get(name).map(|value| value.to_string()));
+        assert_eq!(environment.repository_name(), Some("app"));
+        assert_eq!(environment.release_page.as_deref(), Some("https://github.com/example/app/releases/tag/v1.2.0"));
+        assert_eq!(environment.download_base.as_deref(), Some("https://github.com/example/app/releases/download/v1.2.0/"));
+    }
+
+    #[test]
+    fn describes_nothing_outside_ci() {
+        assert_eq!(BuildEnvironment::from_vars(|_| None), BuildEnvironment::default());
+    }
+
+    #[test]
+    fn drafts_releases_of_built_files() {
+        let base = "https://github.com/example/app/releases/download/v1.2.0/";
+        let dir = TempDir::new().unwrap();
+        let binary = dir.path().join("app-linux-amd64.tar.gz");
+        let sbom = dir.path().join("app.spdx.json");
+        std::fs::write(&binary, b"binary").unwrap();
+        std::fs::write(&sbom, b"{}").unwrap();
+        let links = file_artifacts(&[binary, sbom], base).unwrap();
+        let draft = release_draft("1.2.0", "v1.2.0", links, None, None);
+        assert_eq!(draft.artifacts.len(), 1);
+        assert_eq!(draft.artifacts[0].uri, format!("{}app-linux-amd64.tar.gz", base));
+        assert_eq!(draft.artifacts[0].media_type.as_deref(), Some("application/gzip"));
+        assert_eq!(draft.metadata_links[0].media_type.as_deref(), Some("application/spdx+json"));
+    }
+}
@@ -0,0 +1,234 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use anyhow::{Context, Result, anyhow, bail};
+use packageurl::PackageUrl;
+use serde::Deserialize;
+
+use crate::depsdev::{component_alias_purls, release_ecosystem_purls};
+use crate::fetch::Fetchers;
+use crate::models::aggregation::{AggregatedCatalogData, AggregatedComponentData, ClearlyDefinedEnrichment};
+use crate::output::{self, Status};
+
+/// The public ClearlyDefined API.
+pub(crate) const DEFAULT_CLEARLY_DEFINED_URL: &str = "https://api.clearlydefined.io";
+
+/// A definition as returned by `GET /definitions/{type}/{provider}/{namespace}/{name}/{revision}`.
+#[derive(Deserialize, Debug, Default)]
+struct Definition {
+    #[serde(default)]
+    described: Described,
+    #[serde(default)]
+    licensed: Licensed,
+    #[serde(default)]
+    scores: Scores,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct Described {
+    source_location: Option<SourceLocation>,
+    /// The tools that harvested the package; empty when ClearlyDefined knows nothing about it.
+    #[serde(default)]
+    tools: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SourceLocation {
+    url: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Licensed {
+    declared: Option<String>,
+    score: Option<Score>,
+    #[serde(default)]
+    facets: Facets,
+}
+
+#[derive(Deserialize, Debug)]
+struct Score {
+    total: Option<u32>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Facets {
+    core: Option<Facet>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Facet {
+    discovered: Option<Discovered>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Discovered {
+    #[serde(default)]
+    expressions: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Scores {
+    effective: Option<u32>,
+}
+
+/// Looks up curated license data on ClearlyDefined.
+pub(crate) struct ClearlyDefinedClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl ClearlyDefinedClient {
+    pub(crate) fn new(client: reqwest::Client, base_url: &str) -> Self {
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Collects the declared and discovered licenses and scores of an ecosystem PURL.
+    pub(crate) async fn lookup(&self, purl: &str) -> Result<ClearlyDefinedEnrichment> {
+        let coordinates = coordinates(purl)?;
+        let mut url = reqwest::Url::parse(&self.base_url)
+            .with_context(|| format!("Invalid ClearlyDefined URL '{}'", self.base_url))?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("Invalid ClearlyDefined URL '{}'", self.base_url))?
+            .pop_if_empty()
+            .push("definitions")
+            .extend(&coordinates);
+        let definition: Definition = self
+            .client
+            .get(url.clone())
+            .send()
+            .await
+            .with_context(|| format!("Failed to send request to '{}'", url))?
+            .error_for_status()
+            .with_context(|| format!("ClearlyDefined request to '{}' failed", url))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse ClearlyDefined response from '{}'", url))?;
+
+        let coordinates = coordinates.join("/");
+        if definition.described.tools.is_empty() {
+            bail!("ClearlyDefined has not harvested {}", coordinates);
+        }
+        Ok(ClearlyDefinedEnrichment {
+            purl: purl.to_string(),
+            coordinates,
+            declared_license: definition.licensed.declared,
+            discovered_licenses: definition
+                .licensed
+                .facets
+                .core
+                .and_then(|core| core.discovered)
+                .map(|discovered| discovered.expressions)
+                .unwrap_or_default(),
+            license_score: definition.licensed.score.and_then(|score| score.total),
+            effective_score: definition.scores.effective,
+            source_location: definition.described.source_location.and_then(|location| location.url),
+        })
+    }
+}
+
+/// Enriches every release in the aggregate with ClearlyDefined license data.
+pub(crate) async fn enrich_with_clearly_defined(
+    aggregated_data: &mut AggregatedCatalogData,
+    clearly_defined: &ClearlyDefinedClient,
+    fetchers: &Fetchers,
+) {
+    for component in &mut aggregated_data.components {
+        enrich_component_licenses(component, clearly_defined, fetchers).await;
+    }
+}
+
+/// Enriches a component's releases with ClearlyDefined license data for the same ecosystem PURLs
+/// deps.dev enrichment uses.
+pub(crate) async fn enrich_component_licenses(
+    component: &mut AggregatedComponentData,
+    clearly_defined: &ClearlyDefinedClient,
+    fetchers: &Fetchers,
+) {
+    let alias_purls = component_alias_purls(component);
+    for release in &mut component.releases {
+        for purl in release_ecosystem_purls(&alias_purls, release, fetchers).await {
+            if coordinates(&purl).is_err() {
+                continue;
+            }
+            output::print(1, Status::Info, &format!("Looking up {} on ClearlyDefined", purl));
+            match clearly_defined.lookup(&purl).await {
+                Ok(enrichment) => release.clearly_defined.push(enrichment),
+                Err(e) => release.enrichment_errors.push((purl, format!("{:#}", e))),
+            }
+        }
+    }
+}
+
+/// Maps an ecosystem PURL onto ClearlyDefined's (type, provider, namespace, name, revision)
+/// coordinates, with `-` for a missing namespace.
+fn coordinates(purl: &str) -> Result<[String; 5]> {
+    let parsed =
+        PackageUrl::from_str(purl).with_context(|| format!("Invalid PURL syntax: '{}'", purl))?;
+    let version = parsed
+        .version()
+        .ok_or_else(|| anyhow!("PURL '{}' has no version", purl))?
+        .to_string();
+    let (kind, provider) = match parsed.ty() {
+        "npm" => ("npm", "npmjs"),
+        "cargo" => ("crate", "cratesio"),
+        "pypi" => ("pypi", "pypi"),
+        "gem" => ("gem", "rubygems"),
+        "nuget" => ("nuget", "nuget"),
+        "maven" => ("maven", "mavencentral"),
+        "golang" => ("go", "golang"),
+        "composer" => ("composer", "packagist"),
+        "cocoapods" => ("pod", "cocoapods"),
+        "github" => ("git", "github"),
+        other => bail!("ClearlyDefined does not curate '{}' packages", other),
+    };
+    Ok([
+        kind.to_string(),
+        provider.to_string(),
+        parsed.namespace().unwrap_or("-").to_string(),
+        parsed.name().to_string(),
+        version,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn looks_up_curated_licenses() {
+        let server = MockServer::start().await;
+        let definition = json!({
+            "described": { "tools": ["clearlydefined/1.5.0"], "sourceLocation": { "url": "https://github.com/gorilla/mux/tree/v1.8.0" } },
+            "licensed": { "declared": "BSD-3-Clause", "score": { "total": 75 }, "facets": { "core": { "discovered": { "expressions": ["BSD-3-Clause"] } } } },
+            "scores": { "effective": 83, "tool": 80 },
+        });
+        // Go namespaces contain slashes, which stay in a single, encoded path segment.
+        Mock::given(path("/definitions/go/golang/github.com%2Fgorilla/mux/v1.8.0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(definition))
+            .mount(&server)
+            .await;
+        Mock::given(path("/definitions/npm/npmjs/-/unknown/1.0.0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "described": { "tools": [] } })))
+            .mount(&server)
+            .await;
+        let clearly_defined = ClearlyDefinedClient::new(reqwest::Client::new(), &server.uri());
+
+        let enrichment = clearly_defined.lookup("pkg:golang/github.com/gorilla/mux@v1.8.0").await.unwrap();
+        assert_eq!(enrichment.coordinates, "go/golang/github.com/gorilla/mux/v1.8.0");
+        assert_eq!(enrichment.declared_license.as_deref(), Some("BSD-3-Clause"));
+        assert_eq!(enrichment.discovered_licenses, ["BSD-3-Clause"]);
+        assert_eq!((enrichment.license_score, enrichment.effective_score), (Some(75), Some(83)));
+
+        assert!(clearly_defined.lookup("pkg:npm/unknown@1.0.0").await.is_err());
+        assert!(coordinates("pkg:chainsights/example.com/app@1.0.0").is_err());
+    }
+}
@@ -0,0 +1,4333 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+#[cfg(feature = "admission")]
+use crate::admission::AdmissionVerifier;
+use crate::aws::AwsCredentials;
+use crate::csaf::{AdvisoryCache, catalog_advisory_links, correlate_advisories, correlate_component};
+use crate::depsdev::{DEFAULT_DEPS_DEV_URL, DepsDevClient, enrich_component, enrich_with_deps_dev};
+use crate::discovery::{ChainsightsDiscovery, Discovery, FirstDiscovery};
+use crate::dns::SystemResolver;
+use crate::graph::Graph;
+use crate::generate::github::{DEFAULT_GITHUB_API_URL, GithubForge};
+use crate::generate::gitlab::{DEFAULT_GITLAB_API_URL, GitlabForge};
+use crate::generate::sbom::component_draft_from_sbom;
+use crate::generate::baseline::{assess, control_specs, read_previous, write_baseline_draft};
+use crate::generate::edit::{
+    Draft, MergeMode, add_component, add_release, attestation_link, common_identity, link_metadata, merge_catalogs,
+    remove_component, remove_release,
+};
+use crate::generate::{ComponentDraft, DraftOptions, Forge, ReleaseDraft, tag_to_version, write_component_drafts, write_freshness_draft, write_release_draft};
+use crate::generate::build::BuildSource;
+use crate::ci::{BuildEnvironment, file_artifacts, release_draft};
+use crate::clearlydefined::{ClearlyDefinedClient, DEFAULT_CLEARLY_DEFINED_URL, enrich_component_licenses, enrich_with_clearly_defined};
+use crate::consistency::check_domain_consistency;
+use crate::exec_hooks::ExecHooks;
+use crate::fetch::{Fetchers, fetch_and_verify_artifact, fetch_chainsights_info, fetch_document_text, fetch_manifest_text};
+#[cfg(feature = "grpc")]
+use crate::grpc;
+#[cfg(feature = "grpc")]
+use futures::future::BoxFuture;
+use crate::history::{HistoryStore, Snapshot, Trend};
+use crate::idn::parse_domain_arg;
+use crate::image::find_image_releases;
+use crate::init::{Prompter, gather, write_init_tree};
+use crate::keyless::{DEFAULT_FULCIO_URL, FulcioClient, KeylessSigner, ambient_identity_token};
+use crate::keys::{KeySource, KeyStore, passphrase};
+use crate::layout::{Layout, check_component_layout, check_layout};
+use crate::lifecycle::{SupportStatus, parse_lifecycle_date, support_answer};
+use crate::local_artifact::{check_release_trusted, match_release_artifacts};
+use crate::lockfile::lockfile_purls;
+use crate::models::aggregation::{AggregatedCatalogData, AggregatedComponentData, AggregatedReleaseData, NodeVerification};
+use crate::models::baseline::BaselineVerifier;
+use crate::metrics::{self, FailureKind, Metrics};
+use crate::monitor::{RekorMonitor, published_digests};
+use crate::models::chainsights::{
+    ArtifactLink, CatalogComponentEntry, ChainsightsComponentPredicate, ChainsightsPolicyPredicate, ChainsightsPredicate,
+    ChainsightsTrustSnapshotPredicate, Platform, ReleaseStatus, RequiredArtifact, parse_predicate,
+};
+use packageurl::PackageUrl;
+use chainsights_core::bundle::SigstoreBundleData;
+use crate::oci::{attach_bundle, resolve_image_digest};
+use crate::pins::{OnRootChange, RootPins};
+#[cfg(feature = "plugins")]
+use crate::plugins::Plugins;
+use crate::policy::{PolicyStore, open_policy, policy_statement};
+use crate::publish::{AttestationTree, TargetOptions, UploadTarget, UriTemplate, plan_publish, publish_tree};
+use oci_client::secrets::RegistryAuth;
+use crate::rekor::{DEFAULT_REKOR_URL, RekorClient, embed_tlog_entry};
+use crate::relink::{Outcome, UriMapping, relink_tree, write_tree};
+use crate::resolve::{ResolveOptions, resolve_ecosystem_purl};
+use crate::scan::scan_dependencies;
+use crate::score::{SupplierScore, score_supplier};
+use crate::scope::{CrossDomainTrust, LinkScope};
+use crate::scorecard::{ScorecardRequirement, check_scorecard_requirements, collect_component_scorecard, collect_scorecards, parse_scorecard_requirement};
+use crate::selftest::run_selftest;
+use crate::serials::SerialStore;
+use crate::setup::cloudflare::{CloudflareProvider, DEFAULT_CLOUDFLARE_API_URL};
+use crate::setup::gcloud::{CloudDnsProvider, DEFAULT_CLOUD_DNS_API_URL};
+use crate::setup::rfc2136::{Rfc2136Provider, TsigKey};
+use crate::setup::route53::{DEFAULT_ROUTE53_API_URL, Route53Provider};
+use crate::sign::{DidKeySigner, Ed25519Key, StatementSigner, sign_draft, write_private};
+use crate::setup::{DnsProvider, apply_records, planned_records, presentation};
+use crate::stats::StatsRecorder;
+use crate::swid::{collect_component_swid_tags, collect_swid_tags};
+use crate::tea::TeaDiscovery;
+use crate::meta::MetaTagDiscovery;
+use crate::relay::RelayDiscovery;
+use chainsights_core::identity::{BuildPolicy, FulcioExtensions, SignerIdentity, normalize_domain};
+use chainsights_core::pgp::PgpKeyring;
+use chainsights_core::statement::InTotoStatement;
+use chainsights_core::timestamp::TimeSource;
+use chainsights_core::trust::TrustedRoot;
+use chainsights_core::txt::ChainsightsRecord;
+use chainsights_core::verify::decode_bundle_unverified;
+use chainsights_core::verifier::key_verifier;
+use crate::output::{self, Status};
+use crate::progress::TraversalProgress;
+use crate::telemetry::Tracer;
+use crate::purl::{ChainsightsPurl, parse_chainsights_purl, slugify};
+use crate::threshold::{FailThreshold, parse_error_rate};
+use crate::trust::{ExpectedSigner, open_trust_snapshot, snapshot_statement};
+#[cfg(feature = "tui")]
+use crate::tui;
+#[cfg(feature = "admission")]
+use crate::webhook;
+use crate::traversal::{
+    InsecureMode, ParsedAttestation, PredicateParser, TraversalHooks, TraversalOptions, traverse_and_aggregate, traverse_catalog_path, verify_and_parse,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::BufWriter;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use std::str::FromStr;
+
+#[derive(Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    /// Print without colors. Colors are also off when NO_COLOR is set or output is not a terminal.
+    #[arg(long, global = true, default_value_t = false)]
+    no_color: bool,
+}
+
+// Parsed once per run, so the size of the larger variants doesn't matter.
+#[allow(clippy::large_enum_variant)]
+#[derive(Subcommand)]
+enum Commands {
+    /// Query and traverse starting from a domain's root attestation via DNS lookup.
+    Domain {
+        /// The domain name to query for Chainsights info (e.g., example.com)
+        #[arg(long, value_parser = parse_domain_arg)]
+        domain: String,
+
+        /// Only output releases whose license expression includes one of these SPDX license IDs (repeatable or comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        filter_license: Vec<String>,
+
+        /// Write newline-delimited JSON: a catalog record, then one record per component
+        #[arg(long, default_value_t = false)]
+        ndjson: bool,
+
+        #[command(flatten)]
+        traversal: TraversalArgs,
+    },
+    /// Query for a specific component/release using a PURL. Traversal starts from the domain's root.
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Purl {
+        #[command(subcommand)]
+        action: Option<PurlAction>,
+
+        /// The Package URL (PURL) to query (e.g., pkg:chainsights/example.com/my-component@1.2.0).
+        /// Ecosystem PURLs (e.g., pkg:npm/my-component@1.2.0) are matched against component alias PURLs.
+        #[arg(long, required = true)]
+        purl: Option<String>,
+
+        /// Domain to search when the PURL is not a pkg:chainsights PURL
+        #[arg(long, value_parser = parse_domain_arg)]
+        domain: Option<String>,
+
+        #[command(flatten)]
+        output: PurlOutputArgs,
+
+        #[command(flatten)]
+        traversal: TraversalArgs,
+    },
+    /// Export the catalog, component, release, and component relationship graph of a domain.
+    Graph {
+        /// The domain name to query for Chainsights info (e.g., example.com)
+        #[arg(long, value_parser = parse_domain_arg)]
+        domain: String,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+
+        /// Write the graph to this file instead of standard output
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        #[command(flatten)]
+        traversal: TraversalArgs,
+    },
+    /// Check that a domain's TXT record, catalog, components and releases agree with each other.
+    VerifyDomain {
+        /// The domain name to check (e.g., example.com)
+        #[arg(long, value_parser = parse_domain_arg)]
+        domain: String,
+
+        #[command(flatten)]
+        traversal: TraversalArgs,
+    },
+    /// Check whether a pinned version is still within its publisher's declared support window.
+    Eol {
+        /// The PURL of the pinned version (e.g., pkg:chainsights/example.com/my-component@1.2.0)
+        #[arg(long)]
+        purl: String,
+
+        /// Domain to search when the PURL is not a pkg:chainsights PURL
+        #[arg(long, value_parser = parse_domain_arg)]
+        domain: Option<String>,
+
+        /// Evaluate support as of this date (RFC 3339 or YYYY-MM-DD) instead of now
+        #[arg(long)]
+        at: Option<String>,
+
+        /// Exit with an error if the version has reached end of life
+        #[arg(long, default_value_t = false)]
+        fail_on_eol: bool,
+
+        #[command(flatten)]
+        traversal: TraversalArgs,
+    },
+    /// Interactively set up a publisher: gather components and releases, then generate, sign and lay out the attestation tree for hosting.
+    Init {
+        /// The domain to publish under. Asked for when omitted.
+        #[arg(long, value_parser = parse_domain_arg)]
+        domain: Option<String>,
+
+        /// Directory to write the drafts, signed files and signing key to
+        #[arg(long, default_value = "chainsights")]
+        output_dir: PathBuf,
+
+        /// Key to sign with: a PKCS#8 PEM file, env:<variable>, store:<name>, a KMS or pkcs11: key URI, or command:<program>.
+        /// Defaults to signing-key.pem in the output directory, which is generated if it doesn't exist.
+        #[arg(long, value_parser = parse_key_arg)]
+        key: Option<KeySource>,
+
+        /// GitHub token, used when importing components from private repositories
+        #[arg(long, env = "GITHUB_TOKEN", hide_env_values = true)]
+        github_token: Option<String>,
+    },
+    /// Generate draft (unsigned) component and release statements from existing data.
+    Generate {
+        #[command(subcommand)]
+        source: GenerateSource,
+    },
+    /// Author attestations about a component.
+    Attest {
+        #[command(subcommand)]
+        kind: AttestKind,
+    },
+    /// Generate, list and export the signing keys of key-signed attestations.
+    Key {
+        /// Directory of the key store. Defaults to CHAINSIGHTS_KEY_DIR, or chainsights/keys in the user's config directory.
+        #[arg(long, global = true)]
+        key_dir: Option<PathBuf>,
+
+        #[command(subcommand)]
+        action: KeyAction,
+    },
+    /// Add or remove components in a draft (unsigned) catalog statement, or merge several.
+    Catalog {
+        #[command(subcommand)]
+        action: CatalogAction,
+    },
+    /// Add or remove releases in a draft (unsigned) component statement.
+    Component {
+        #[command(subcommand)]
+        action: ComponentAction,
+    },
+    /// Publish a directory of signed attestations to S3, WebDAV or an OCI registry, or a single bundle with a subcommand.
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Publish {
+        #[command(subcommand)]
+        action: Option<PublishTarget>,
+
+        #[command(flatten)]
+        tree: PublishTreeArgs,
+    },
+    /// Describe, sign and publish releases from the CI job that builds them.
+    Ci {
+        #[command(subcommand)]
+        action: CiAction,
+    },
+    /// Rewrite the links of a local attestation tree for new hosting, recomputing the digests that pin rewritten statements.
+    Relink {
+        /// Directory of statements and the documents they link to, laid out as they are served
+        #[arg(long)]
+        dir: PathBuf,
+
+        /// How link URIs move, as FROM=TO URI prefixes or templates with {path} or {tag}
+        /// (e.g., https://example.github.io/attestations/=https://cdn.example.com/) (repeatable; the first match applies)
+        #[arg(long = "map", required = true, value_parser = parse_mapping_arg)]
+        mappings: Vec<UriMapping>,
+
+        /// Directory to write the relinked tree to
+        #[arg(long, required_unless_present = "dry_run")]
+        output_dir: Option<PathBuf>,
+
+        /// Report what would change without writing anything
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Set up a publisher's infrastructure.
+    Setup {
+        #[command(subcommand)]
+        target: SetupTarget,
+    },
+    /// Run discovery, traversal and verification against a generated fixture tree served locally, to validate the install.
+    Selftest,
+    /// Check the signature, signer identity and predicate of a local bundle, and print its statement.
+    VerifyBundle {
+        /// Path to the signed bundle (first non-empty line of a .jsonl file)
+        #[arg(long)]
+        file: PathBuf,
+
+        /// Identity expected to have signed the bundle (repeatable)
+        #[arg(long, required = true)]
+        identity: Vec<String>,
+
+        /// OIDC issuer expected in the signing certificate (e.g., https://token.actions.githubusercontent.com)
+        #[arg(long)]
+        issuer: Option<String>,
+
+        #[command(flatten)]
+        build_policy: BuildPolicyArgs,
+
+        #[command(flatten)]
+        trust: TrustArgs,
+
+        #[command(flatten)]
+        plugins: PluginArgs,
+
+        /// Where the signing time used for certificate validity comes from
+        #[arg(long, value_enum, default_value_t = TimeSource::Local)]
+        time_source: TimeSource,
+
+        /// Seconds of clock difference tolerated when checking certificate validity
+        #[arg(long, default_value_t = 300)]
+        clock_skew: i64,
+    },
+    /// Watch external sources for signs that a publisher's identity is being misused.
+    Monitor {
+        #[command(subcommand)]
+        action: MonitorAction,
+    },
+    /// Serve the query API over gRPC (chainsights.v1.ChainsightsQuery in proto/chainsights/v1/query.proto).
+    #[cfg(feature = "grpc")]
+    ServeGrpc {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:50051")]
+        listen: SocketAddr,
+
+        #[command(flatten)]
+        traversal: TraversalArgs,
+    },
+    /// Serve a Kubernetes validating admission webhook that admits pods only if supplier releases attest to every image digest.
+    #[cfg(feature = "admission")]
+    ServeAdmission {
+        /// Address to listen on
+        #[arg(long, default_value = "0.0.0.0:8443")]
+        listen: SocketAddr,
+
+        /// Supplier domains whose releases are trusted (repeatable or comma-separated)
+        #[arg(long = "domain", env = "CHAINSIGHTS_DOMAINS", required = true, value_delimiter = ',', value_parser = parse_domain_arg)]
+        domains: Vec<String>,
+
+        /// PEM certificate chain to serve HTTPS with. Without it, plain HTTP is served for use behind a TLS proxy.
+        #[arg(long, requires = "tls_key")]
+        tls_cert: Option<PathBuf>,
+
+        /// PEM private key of --tls-cert
+        #[arg(long, requires = "tls_cert")]
+        tls_key: Option<PathBuf>,
+
+        /// Seconds between traversals of the domains to refresh the attested images
+        #[arg(long, default_value_t = 300, value_parser = clap::value_parser!(u64).range(1..))]
+        refresh_interval: u64,
+
+        /// Seconds a domain that fails to traverse keeps its attested images before they are denied
+        #[arg(long, default_value_t = 3600, value_parser = clap::value_parser!(u64).range(1..))]
+        max_staleness: u64,
+
+        #[command(flatten)]
+        traversal: TraversalArgs,
+    },
+    /// Browse a domain's attestations interactively: components, releases and their artifacts.
+    #[cfg(feature = "tui")]
+    Tui {
+        /// The domain name to browse (e.g., example.com)
+        #[arg(long, value_parser = parse_domain_arg)]
+        domain: String,
+
+        #[command(flatten)]
+        traversal: TraversalArgs,
+    },
+    /// Snapshot Sigstore trust material for offline verification, or unpack a signed snapshot.
+    Trust {
+        #[command(subcommand)]
+        action: TrustAction,
+    },
+    /// Write an organization's client policy as a statement to sign, or verify and show a signed policy.
+    Policy {
+        #[command(subcommand)]
+        action: PolicyAction,
+    },
+    /// Decode a bundle's statement and certificate WITHOUT verifying them, to debug malformed output.
+    Inspect {
+        /// Path to a bundle file, or a URI to fetch it from
+        source: String,
+    },
+    /// Check artifacts against the digests suppliers publish for them.
+    Artifacts {
+        #[command(subcommand)]
+        action: ArtifactsAction,
+    },
+    /// Find the supplier releases that attest to a container image, by its manifest digest.
+    VerifyImage {
+        /// The image, pinned by digest (e.g., ghcr.io/vendor/app@sha256:...) or by a tag to resolve
+        image: String,
+
+        /// Supplier domains whose releases to search (repeatable or comma-separated)
+        #[arg(long = "domain", env = "CHAINSIGHTS_DOMAINS", required = true, value_delimiter = ',', value_parser = parse_domain_arg)]
+        domains: Vec<String>,
+
+        /// Registry username for resolving a tag. Anonymous access is used if not provided.
+        #[arg(long, env = "CHAINSIGHTS_REGISTRY_USERNAME")]
+        username: Option<String>,
+
+        /// Registry password or token
+        #[arg(long, env = "CHAINSIGHTS_REGISTRY_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+
+        #[command(flatten)]
+        traversal: TraversalArgs,
+    },
+    /// Report which dependencies of a lockfile (Cargo.lock, package-lock.json or go.sum) have discoverable transparency data.
+    Scan {
+        /// The lockfile to scan
+        #[arg(long)]
+        lockfile: PathBuf,
+
+        /// Supplier domains to search, in order (repeatable or comma-separated)
+        #[arg(long = "domain", env = "CHAINSIGHTS_DOMAINS", value_delimiter = ',', value_parser = parse_domain_arg)]
+        domains: Vec<String>,
+
+        /// JSON file mapping versionless PURLs or PURL prefixes to the domain that publishes them
+        #[arg(long)]
+        mapping: Option<PathBuf>,
+
+        /// Exit with an error when less than this percentage of dependencies is covered (e.g., 80%)
+        #[arg(long, value_parser = parse_error_rate)]
+        fail_under: Option<f64>,
+
+        #[command(flatten)]
+        traversal: TraversalArgs,
+    },
+    /// Grade suppliers on the completeness of their attestations (SBOM, provenance, VEX, signed artifacts, freshness).
+    Score {
+        /// Supplier domains to score (repeatable or comma-separated)
+        #[arg(long = "domain", env = "CHAINSIGHTS_DOMAINS", required = true, value_delimiter = ',', value_parser = parse_domain_arg)]
+        domains: Vec<String>,
+
+        /// Release attestations signed longer ago than this many days do not count as fresh
+        #[arg(long, default_value_t = 365)]
+        fresh_within_days: i64,
+
+        /// Record each supplier's score in the history that `trends` reports on
+        #[arg(long, default_value_t = false)]
+        record: bool,
+
+        /// History file (default: $XDG_STATE_HOME/chainsights/history.json)
+        #[arg(long, requires = "record")]
+        history: Option<PathBuf>,
+
+        #[command(flatten)]
+        traversal: TraversalArgs,
+    },
+    /// Record a supplier's scores, release cadence and verification failures, and report how they changed over a period.
+    Trends {
+        /// The supplier domain
+        #[arg(long, value_parser = parse_domain_arg)]
+        domain: String,
+
+        /// Compare against the snapshot recorded this many days ago, or the oldest one since
+        #[arg(long, default_value_t = 90)]
+        period_days: i64,
+
+        /// Release attestations signed longer ago than this many days do not count as fresh
+        #[arg(long, default_value_t = 365)]
+        fresh_within_days: i64,
+
+        /// History file (default: $XDG_STATE_HOME/chainsights/history.json)
+        #[arg(long)]
+        history: Option<PathBuf>,
+
+        #[command(flatten)]
+        traversal: TraversalArgs,
+    },
+    /// Print a shell completion script (e.g., `chainsights_client completions bash > /etc/bash_completion.d/chainsights_client`).
+    Completions {
+        /// The shell to complete in
+        shell: clap_complete::Shell,
+    },
+    /// Write man pages for the command and each subcommand, for packaging.
+    Manpages {
+        /// Directory to write the pages to
+        #[arg(long, default_value = "man")]
+        out_dir: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum PurlAction {
+    /// Find the Chainsights component publishing an ecosystem package (e.g., pkg:npm/left-pad@1.3.0).
+    Resolve {
+        /// The ecosystem PURL to resolve
+        purl: String,
+
+        /// Domains to search, in order (repeatable or comma-separated)
+        #[arg(long = "domain", env = "CHAINSIGHTS_DOMAINS", value_delimiter = ',', value_parser = parse_domain_arg)]
+        domains: Vec<String>,
+
+        /// JSON file mapping versionless PURLs or PURL prefixes to the domain that publishes them
+        #[arg(long)]
+        mapping: Option<PathBuf>,
+
+        #[command(flatten)]
+        traversal: TraversalArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum MonitorAction {
+    /// Watch Rekor for entries signed by the publisher's identities that its catalog does not publish.
+    Rekor {
+        /// The domain whose catalog accounts for what the identities sign
+        #[arg(long, value_parser = parse_domain_arg)]
+        domain: String,
+
+        /// Email identity to watch (repeatable); Rekor indexes signers by email address only
+        #[arg(long = "identity", required = true)]
+        identities: Vec<String>,
+
+        /// Ignore entries added to the log before this RFC 3339 time (e.g., when the domain started publishing)
+        #[arg(long)]
+        since: Option<DateTime<Utc>>,
+
+        /// File recording the entries already checked, so that each is reported once across runs
+        #[arg(long)]
+        state: Option<PathBuf>,
+
+        /// Seconds between checks
+        #[arg(long, default_value_t = 300, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+
+        /// Check once and exit with an error if any entry is unpublished, e.g. from a scheduled CI job
+        #[arg(long, default_value_t = false)]
+        once: bool,
+
+        /// Serve Prometheus metrics at http://ADDR/metrics (e.g., 0.0.0.0:9464)
+        #[arg(long, value_name = "ADDR", conflicts_with = "once")]
+        metrics_addr: Option<SocketAddr>,
+
+        #[command(flatten)]
+        traversal: TraversalArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum TrustAction {
+    /// Write an unsigned trust snapshot statement of a trusted_root.json, to be signed and carried into an air-gapped environment.
+    Export {
+        /// Path or URL of the trusted_root.json to snapshot (e.g., from the instance's TUF repository)
+        source: String,
+
+        /// Days until the snapshot expires and must be refreshed
+        #[arg(long, default_value_t = 90)]
+        expires_in_days: i64,
+
+        /// Where to write the statement. Defaults to stdout.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Verify a signed trust snapshot and write its trusted_root.json, for use with --trusted-root.
+    Import {
+        /// Path to the signed snapshot bundle (first non-empty line of a .jsonl file), or to the key-signed statement
+        bundle: PathBuf,
+
+        /// Identity expected to have signed the snapshot, verified against --trusted-root (repeatable)
+        #[arg(long, required_unless_present = "key", requires = "trusted_root")]
+        identity: Vec<String>,
+
+        /// Trusted root the snapshot's signing certificate must chain to, e.g. the one it replaces
+        #[arg(long, value_parser = parse_trusted_root_arg)]
+        trusted_root: Option<Arc<TrustedRoot>>,
+
+        /// Public key (SSH or minisign) pinned to sign the snapshot, whose detached signature is next to it (repeatable)
+        #[arg(long, conflicts_with = "identity")]
+        key: Vec<String>,
+
+        /// Where to write the trusted root
+        #[arg(long, default_value = "trusted_root.json")]
+        output: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum PolicyAction {
+    /// Write the unsigned statement of a policy document, to be signed and served to every machine applying it with --policy-bundle.
+    Export {
+        /// Path to the policy document: a JSON object with a name and the policy's rules
+        document: PathBuf,
+
+        /// Days until the policy expires and must be reissued
+        #[arg(long, default_value_t = 30)]
+        expires_in_days: i64,
+
+        /// Where to write the statement. Defaults to stdout.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Verify a signed policy bundle and show the rules it applies.
+    Show {
+        /// Path to the signed policy bundle, or a URL to fetch it from; for a key-signed policy, the statement, whose detached signature is next to it
+        bundle: String,
+
+        /// Identity expected to have signed the policy (repeatable)
+        #[arg(long, required_unless_present = "key")]
+        identity: Vec<String>,
+
+        /// Trusted root the policy's signing certificate must chain to
+        #[arg(long, value_parser = parse_trusted_root_arg, conflicts_with = "key")]
+        trusted_root: Option<Arc<TrustedRoot>>,
+
+        /// Public key (SSH or minisign) pinned to sign the policy (repeatable)
+        #[arg(long, conflicts_with = "identity")]
+        key: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ArtifactsAction {
+    /// Verify a file obtained out-of-band (e.g., from a mirror or a vendor portal) against the digests in its supplier's signed release statement.
+    VerifyLocal {
+        /// Path to the downloaded file
+        file: PathBuf,
+
+        /// PURL of the release the file belongs to, with its version (e.g., pkg:chainsights/example.com/my-component@1.2.0)
+        #[arg(long)]
+        purl: String,
+
+        /// Domain to search when the PURL is not a pkg:chainsights PURL
+        #[arg(long, value_parser = parse_domain_arg)]
+        domain: Option<String>,
+
+        #[command(flatten)]
+        traversal: TraversalArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum PublishTarget {
+    /// Upload a bundle's DSSE envelope to Rekor and embed the resulting tlog entry in the bundle.
+    Rekor {
+        /// Path to the signed bundle (first non-empty line of a .jsonl file)
+        #[arg(long)]
+        bundle: PathBuf,
+
+        /// Rekor instance to upload to (public good instance or a private deployment)
+        #[arg(long, default_value = DEFAULT_REKOR_URL)]
+        rekor_url: String,
+
+        /// Where to write the updated bundle. Defaults to stdout.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Attach a signed release bundle to a container image via the OCI referrers API (cosign-compatible).
+    Oci {
+        /// The image the release attestation describes (e.g., ghcr.io/example/app:1.0.0)
+        #[arg(long)]
+        image: String,
+
+        /// Path to the signed release bundle (first non-empty line of a .jsonl file)
+        #[arg(long)]
+        bundle: PathBuf,
+
+        /// Registry username. Anonymous access is used if not provided.
+        #[arg(long, env = "CHAINSIGHTS_REGISTRY_USERNAME")]
+        username: Option<String>,
+
+        /// Registry password or token
+        #[arg(long, env = "CHAINSIGHTS_REGISTRY_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CiAction {
+    /// Write the release statement of the build's outputs, sign it (keylessly with the job's OIDC identity unless --key is given) and upload it.
+    EmitRelease {
+        /// Domain the component is published under (e.g., example.com)
+        #[arg(long, env = "CHAINSIGHTS_DOMAIN", value_parser = parse_domain_arg)]
+        domain: String,
+
+        /// Component slug. Defaults to the name of the repository being built.
+        #[arg(long, env = "CHAINSIGHTS_COMPONENT")]
+        component: Option<String>,
+
+        /// Release version. Defaults to the tag being built, without a leading "v".
+        #[arg(long, env = "CHAINSIGHTS_VERSION")]
+        version: Option<String>,
+
+        /// Human-readable release name. Defaults to the tag being built.
+        #[arg(long)]
+        name: Option<String>,
+
+        /// A build output to list with its digests (repeatable). SBOMs and provenance become metadata links.
+        #[arg(long = "artifact")]
+        artifacts: Vec<PathBuf>,
+
+        /// Build outputs to list with their digests (repeatable): dist:<dir>, cargo[:<dir>], npm[:<dir>],
+        /// maven[:<dir>], image:<reference>@sha256:<digest> or image-metadata:<buildx metadata file>
+        #[arg(long = "from", value_parser = parse_build_source_arg)]
+        sources: Vec<BuildSource>,
+
+        /// Base URI the --artifact and dist: files are downloaded from, followed by their paths. Defaults
+        /// to the tag's GitHub release downloads.
+        #[arg(long)]
+        artifact_base_uri: Option<String>,
+
+        /// Lifecycle phase of the release (e.g., stable, beta)
+        #[arg(long)]
+        lifecycle_phase: Option<String>,
+
+        /// Key to sign with: a PKCS#8 PEM file, env:<variable>, store:<name>, a KMS or pkcs11: key URI, or command:<program>.
+        /// Defaults to keyless signing with the job's OIDC token.
+        #[arg(long, value_parser = parse_key_arg)]
+        key: Option<KeySource>,
+
+        /// OIDC token to sign keylessly with. Defaults to SIGSTORE_ID_TOKEN, or one requested from GitHub Actions.
+        #[arg(long, hide_env_values = true)]
+        oidc_token: Option<String>,
+
+        /// Fulcio instance to certify the keyless signing key
+        #[arg(long, default_value = DEFAULT_FULCIO_URL)]
+        fulcio_url: String,
+
+        /// Rekor instance to log keyless signatures in
+        #[arg(long, default_value = DEFAULT_REKOR_URL)]
+        rekor_url: String,
+
+        /// Directory to write the draft and signed statements to, as components/<component>/<version>.json[l]
+        #[arg(long, default_value = "chainsights-release")]
+        output_dir: PathBuf,
+
+        /// Where to upload the signed statement: s3://bucket[/prefix], webdav://host/path or oci://registry/repository.
+        /// It is only written locally when omitted.
+        #[arg(long)]
+        target: Option<String>,
+
+        /// The URI the statement is served at, with {path} for its path (components/<component>/<version>.jsonl)
+        /// or {tag} for its OCI tag. Defaults to serving straight from the target.
+        #[arg(long)]
+        uri_template: Option<String>,
+
+        #[command(flatten)]
+        target_options: TargetArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum AttestKind {
+    /// Walk through the OpenSSF Baseline controls and sign a Baseline attestation, linked from the component's metadata links.
+    Baseline {
+        /// Draft component statement the attestation is about. Its metadata links are updated in place.
+        #[arg(long)]
+        component: PathBuf,
+
+        /// Key to sign the attestation with: a PKCS#8 PEM file, env:<variable>, store:<name>, a KMS or pkcs11: key URI, or command:<program>
+        #[arg(long, value_parser = parse_key_arg)]
+        key: KeySource,
+
+        /// Where to write the signed attestation
+        #[arg(long)]
+        output: PathBuf,
+
+        /// Where to write the draft attestation, whose answers are the defaults when the
+        /// assessment is repeated. Defaults to the output path with a .json extension.
+        #[arg(long)]
+        draft: Option<PathBuf>,
+
+        /// URI the signed attestation will be hosted at. Defaults to baseline.jsonl next to the
+        /// component's most recent release.
+        #[arg(long)]
+        uri: Option<String>,
+
+        /// ID of whoever assessed the controls (e.g., email@example.com). Asked for when omitted.
+        #[arg(long)]
+        verifier: Option<String>,
+
+        /// JSON file of the controls to assess, as {"control", "title"} objects. Defaults to the
+        /// Baseline's Maturity Level 1 controls.
+        #[arg(long)]
+        controls: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum KeyAction {
+    /// Generate an Ed25519 key, encrypted at rest with a passphrase (scrypt), and print its did:key identity.
+    Generate {
+        /// Name to refer to the key by, as store:<name>
+        name: String,
+
+        /// Store the key unencrypted, for keys protected by other means
+        #[arg(long, default_value_t = false)]
+        no_passphrase: bool,
+    },
+    /// List the stored keys and their did:key identities.
+    List,
+    /// Print a key's public key as PEM, or with --private write its private key as stored (encrypted unless generated without a passphrase).
+    Export {
+        /// Name of the key
+        name: String,
+
+        /// Export the private key, to move it to another machine or into a CI secret
+        #[arg(long, default_value_t = false, requires = "output")]
+        private: bool,
+
+        /// Where to write the key. Defaults to stdout.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CatalogAction {
+    /// Add a component to the catalog.
+    AddComponent {
+        #[command(flatten)]
+        catalog: CatalogDraftArgs,
+
+        /// URI of the component's signed statement
+        #[arg(long)]
+        uri: String,
+
+        /// Draft statement of the component, to take its name, description, PURL and labels from
+        #[arg(long)]
+        from: Option<PathBuf>,
+
+        /// Human-readable component name
+        #[arg(long, required_unless_present = "from")]
+        name: Option<String>,
+
+        /// Versionless PURL of the component
+        #[arg(long, required_unless_present = "from")]
+        purl: Option<String>,
+
+        /// Brief description of the component
+        #[arg(long)]
+        description: Option<String>,
+
+        /// A label to categorize the component by, as KEY=VALUE (repeatable)
+        #[arg(long, value_parser = parse_label_arg)]
+        label: Vec<(String, String)>,
+
+        #[command(flatten)]
+        link: LinkArgs,
+    },
+    /// Remove a component from the catalog.
+    RemoveComponent {
+        #[command(flatten)]
+        catalog: CatalogDraftArgs,
+
+        /// PURL or name of the component to remove
+        component: String,
+    },
+    /// Combine the draft catalogs of several teams into the organization's root catalog, listing
+    /// their components or linking each as a sub-catalog.
+    Merge {
+        /// Draft team catalog statements or bare catalog predicates, each named after its file stem
+        #[arg(required = true, num_args = 2..)]
+        catalogs: Vec<PathBuf>,
+
+        /// Where to write the merged draft catalog
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Subject URI of the merged statement. Defaults to the first catalog's.
+        #[arg(long)]
+        subject: Option<String>,
+
+        /// Link each team catalog as a sub-catalog published at <BASE_URI>/<name>.jsonl, rather than listing its components
+        #[arg(long = "sub-catalogs", value_name = "BASE_URI")]
+        sub_catalogs_base_uri: Option<String>,
+
+        /// Identity expected to sign the team catalogs. Defaults to the one each catalog's component links expect.
+        #[arg(long, requires = "sub_catalogs_base_uri")]
+        identity: Option<String>,
+    },
+    /// Write a draft freshness statement confirming that a signed root catalog is current, for
+    /// the publisher or a witness to sign and publish at the catalog's freshnessLink.
+    Freshness {
+        /// The signed root catalog as it is served
+        #[arg(long)]
+        signed: PathBuf,
+
+        /// Where to write the draft freshness statement
+        #[arg(long)]
+        output: PathBuf,
+
+        /// Hours until the freshness statement expires; publish a new one well before then
+        #[arg(long, default_value_t = 48, value_parser = clap::value_parser!(i64).range(1..))]
+        valid_for: i64,
+    },
+}
+
+#[derive(Subcommand)]
+enum ComponentAction {
+    /// Add a release to the component, ahead of its existing releases.
+    AddRelease {
+        #[command(flatten)]
+        component: ComponentDraftArgs,
+
+        /// URI of the release's signed statement
+        #[arg(long)]
+        uri: String,
+
+        #[command(flatten)]
+        link: LinkArgs,
+    },
+    /// Remove a release from the component.
+    RemoveRelease {
+        #[command(flatten)]
+        component: ComponentDraftArgs,
+
+        /// URI of the release's signed statement
+        uri: String,
+    },
+}
+
+/// The draft catalog statement a `catalog` subcommand edits.
+#[derive(Args)]
+struct CatalogDraftArgs {
+    /// Draft catalog statement to edit, or a bare catalog predicate
+    #[arg(long)]
+    catalog: PathBuf,
+
+    #[command(flatten)]
+    draft: EditArgs,
+}
+
+/// The draft component statement a `component` subcommand edits.
+#[derive(Args)]
+struct ComponentDraftArgs {
+    /// Draft component statement to edit, or a bare component predicate
+    #[arg(long)]
+    component: PathBuf,
+
+    #[command(flatten)]
+    draft: EditArgs,
+}
+
+/// Options shared by the commands that edit draft statements.
+#[derive(Args)]
+struct EditArgs {
+    /// Subject URI of the statement, required when editing a bare predicate
+    #[arg(long)]
+    subject: Option<String>,
+
+    /// Where to write the updated statement. Defaults to the file being edited.
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+/// How a new link is pinned.
+#[derive(Args)]
+struct LinkArgs {
+    /// Identity expected to sign the linked statement. Defaults to the one every existing link expects.
+    #[arg(long)]
+    identity: Option<String>,
+
+    /// Digest pinning the linked statement, as ALGORITHM:VALUE (e.g., sha256:4a5b...)
+    #[arg(long)]
+    digest: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum SetupTarget {
+    /// Create or update the domain's `_chainsights` TXT record, and any challenge records, through a DNS provider's API.
+    Dns {
+        #[command(flatten)]
+        dns: SetupDnsArgs,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum DnsProviderKind {
+    /// Amazon Route53, with the AWS credentials in the environment
+    Route53,
+    /// Cloudflare, with an API token that can edit the zone's DNS
+    Cloudflare,
+    /// Google Cloud DNS, with an OAuth access token
+    GoogleCloudDns,
+    /// RFC 2136 dynamic updates to the zone's primary server, signed with a TSIG key
+    Rfc2136,
+}
+
+/// The records `setup dns` sets and the provider it sets them through.
+#[derive(Args)]
+struct SetupDnsArgs {
+    /// The DNS provider hosting the domain's zone
+    #[arg(long, value_enum)]
+    provider: DnsProviderKind,
+
+    /// The domain to set records for (e.g., example.com)
+    #[arg(long, value_parser = parse_domain_arg)]
+    domain: String,
+
+    /// URI of the domain's root catalog
+    #[arg(long, requires = "identity")]
+    uri: Option<String>,
+
+    /// Identity the root catalog is signed by
+    #[arg(long, requires = "uri")]
+    identity: Option<String>,
+
+    /// Fingerprint or URI of the key the root catalog must be signed with
+    #[arg(long, requires = "uri")]
+    key: Option<String>,
+
+    /// An additional TXT record to set, such as a domain verification challenge, as NAME=VALUE with NAME relative to the domain (repeatable)
+    #[arg(long, value_parser = parse_challenge_arg)]
+    challenge: Vec<(String, String)>,
+
+    /// TTL of the records, in seconds
+    #[arg(long, default_value_t = 300)]
+    ttl: u32,
+
+    /// The zone: a Route53 hosted zone ID, a Cloud DNS managed zone name, a Cloudflare zone ID
+    /// (looked up when omitted) or, for RFC 2136, the zone's name (the domain when omitted)
+    #[arg(long)]
+    zone: Option<String>,
+
+    /// Cloudflare API token
+    #[arg(long, env = "CLOUDFLARE_API_TOKEN", hide_env_values = true)]
+    cloudflare_token: Option<String>,
+
+    /// Google Cloud project of the managed zone
+    #[arg(long, env = "GOOGLE_CLOUD_PROJECT")]
+    gcp_project: Option<String>,
+
+    /// Google OAuth access token (e.g., from `gcloud auth print-access-token`)
+    #[arg(long, env = "GOOGLE_OAUTH_ACCESS_TOKEN", hide_env_values = true)]
+    gcp_access_token: Option<String>,
+
+    /// The primary server to send RFC 2136 updates to, as host or host:port
+    #[arg(long)]
+    server: Option<String>,
+
+    /// Name of the TSIG key RFC 2136 updates are signed with
+    #[arg(long, requires = "tsig_secret")]
+    tsig_key_name: Option<String>,
+
+    /// Base64 HMAC-SHA256 secret of the TSIG key
+    #[arg(long, env = "CHAINSIGHTS_TSIG_SECRET", hide_env_values = true)]
+    tsig_secret: Option<String>,
+
+    /// Print the records that would be set without changing anything
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+}
+
+// The subcommands read as `generate from-github`, `generate from-sbom`, and so on.
+#[allow(clippy::enum_variant_names)]
+#[derive(Subcommand)]
+enum GenerateSource {
+    /// Bootstrap a component and its releases from a GitHub repository.
+    FromGithub {
+        /// The repository to read (e.g., example/my-component)
+        #[arg(long)]
+        repo: String,
+
+        /// GitHub token, used for private repositories and higher rate limits
+        #[arg(long, env = "GITHUB_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+
+        /// GitHub API URL (change for GitHub Enterprise Server)
+        #[arg(long, default_value = DEFAULT_GITHUB_API_URL)]
+        api_url: String,
+
+        /// Maximum number of most recent releases to include
+        #[arg(long, default_value_t = 10)]
+        max_releases: usize,
+
+        #[command(flatten)]
+        draft: DraftArgs,
+    },
+    /// Bootstrap a component and its releases from a GitLab project (gitlab.com or self-hosted).
+    FromGitlab {
+        /// The project path to read (e.g., example/group/my-component)
+        #[arg(long)]
+        project: String,
+
+        /// GitLab token, used for private projects
+        #[arg(long, env = "GITLAB_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+
+        /// GitLab API URL (e.g., https://gitlab.example.com/api/v4 for self-hosted instances)
+        #[arg(long, default_value = DEFAULT_GITLAB_API_URL)]
+        api_url: String,
+
+        /// Maximum number of most recent releases to include
+        #[arg(long, default_value_t = 10)]
+        max_releases: usize,
+
+        #[command(flatten)]
+        draft: DraftArgs,
+    },
+    /// Derive a component and release from an existing SPDX or CycloneDX JSON SBOM.
+    FromSbom {
+        /// Path to the SBOM describing the release
+        sbom: PathBuf,
+
+        /// Release version, if the SBOM does not record one
+        #[arg(long)]
+        version: Option<String>,
+
+        #[command(flatten)]
+        draft: DraftArgs,
+    },
+}
+
+/// Options shared by the draft generators.
+#[derive(Args)]
+struct DraftArgs {
+    /// Domain the component is published under (e.g., example.com)
+    #[arg(long, value_parser = parse_domain_arg)]
+    domain: String,
+
+    /// Identity expected to sign the generated statements
+    #[arg(long)]
+    identity: String,
+
+    /// Base URI the signed .jsonl files will be hosted at
+    #[arg(long)]
+    base_uri: String,
+
+    /// Directory to write the draft statements to
+    #[arg(long, default_value = ".")]
+    output_dir: PathBuf,
+}
+
+impl From<DraftArgs> for DraftOptions {
+    fn from(args: DraftArgs) -> Self {
+        DraftOptions {
+            domain: args.domain,
+            identity: args.identity,
+            base_uri: args.base_uri,
+            output_dir: args.output_dir,
+        }
+    }
+}
+
+/// Options shared by every command that traverses a domain's attestations.
+#[derive(Args)]
+struct TraversalArgs {
+    /// When an attestation link fails to fetch, search Rekor for an entry matching the link's sha256 digest
+    #[arg(long, default_value_t = false)]
+    rekor_fallback: bool,
+
+    /// Rekor instance used for the fallback search
+    #[arg(long, default_value = DEFAULT_REKOR_URL)]
+    rekor_url: String,
+
+    /// Seconds of clock difference tolerated when checking expiry, freshness and certificate validity
+    #[arg(long, default_value_t = 300)]
+    clock_skew: i64,
+
+    /// Where signing times come from when checking expiry, freshness and certificate validity
+    #[arg(long, value_enum, default_value_t = TimeSource::Local)]
+    time_source: TimeSource,
+
+    /// Leave revoked components and releases out of the output instead of marking them
+    #[arg(long, default_value_t = false)]
+    exclude_revoked: bool,
+
+    /// Look up ecosystem PURLs from component aliases and release SBOMs on deps.dev
+    #[arg(long, default_value_t = false)]
+    enrich_deps_dev: bool,
+
+    /// deps.dev API used for enrichment
+    #[arg(long, default_value = DEFAULT_DEPS_DEV_URL)]
+    deps_dev_url: String,
+
+    /// Look up curated license data for ecosystem PURLs from component aliases and release SBOMs on ClearlyDefined
+    #[arg(long, default_value_t = false)]
+    enrich_clearly_defined: bool,
+
+    /// ClearlyDefined API used for enrichment
+    #[arg(long, default_value = DEFAULT_CLEARLY_DEFINED_URL)]
+    clearly_defined_url: String,
+
+    /// Check the in-toto attestations each release links against this in-toto layout (JSON), reporting compliance per release
+    #[arg(long)]
+    intoto_layout: Option<PathBuf>,
+
+    /// Fetch the CSAF advisories linked from the catalog, components and releases, and report those naming each release
+    #[arg(long, default_value_t = false)]
+    csaf: bool,
+
+    /// Fetch and parse the SWID and CoSWID tags releases link, checking their digests and reporting their tag IDs
+    #[arg(long, default_value_t = false)]
+    swid_tags: bool,
+
+    /// Verify the OpenSSF Scorecard results components link as in-toto attestations, reporting their check scores
+    #[arg(long, default_value_t = false)]
+    scorecard: bool,
+
+    /// Fail unless every component has a verified Scorecard result meeting this minimum, as CHECK>=SCORE (e.g., pinned-dependencies>=8, or score>=7 for the aggregate score); implies --scorecard
+    #[arg(long, value_parser = parse_scorecard_requirement)]
+    require_scorecard: Vec<ScorecardRequirement>,
+
+    /// File recording the last root catalog serial seen from each domain, to detect older catalogs being replayed [default: $XDG_STATE_HOME/chainsights/serials.json]
+    #[arg(long, env = "CHAINSIGHTS_SERIAL_STATE")]
+    serial_state: Option<PathBuf>,
+
+    /// Neither check root catalog serials against the last ones seen nor record them
+    #[arg(long, default_value_t = false, conflicts_with_all = ["serial_state", "fail_on_rollback"])]
+    no_serial_check: bool,
+
+    /// Exit with an error instead of warning when a root catalog's serial is lower than the last one seen from its domain
+    #[arg(long, default_value_t = false)]
+    fail_on_rollback: bool,
+
+    /// File recording the root identity and URI each domain's _chainsights record advertised on first use, to detect it advertising others [default: $XDG_STATE_HOME/chainsights/root-pins.json]
+    #[arg(long, env = "CHAINSIGHTS_ROOT_PIN_STATE")]
+    root_pin_state: Option<PathBuf>,
+
+    /// Neither check roots against those pinned on first use nor pin them; --pin-root pins still apply
+    #[arg(long, default_value_t = false, conflicts_with_all = ["root_pin_state", "fail_on_root_change", "accept_root_change"])]
+    no_root_pinning: bool,
+
+    /// Pin a domain's root to an identity, as DOMAIN=IDENTITY; a _chainsights record advertising another identity fails discovery (repeatable, pinning a domain again allows either identity)
+    #[arg(long, value_parser = parse_root_pin_arg)]
+    pin_root: Vec<(String, String)>,
+
+    /// Exit with an error instead of warning when a domain's _chainsights record advertises another root identity or URI than the one pinned on first use
+    #[arg(long, default_value_t = false, conflicts_with = "accept_root_change")]
+    fail_on_root_change: bool,
+
+    /// Pin the root identity and URI each domain's _chainsights record now advertises in place of the ones pinned before, e.g. after an announced key rotation
+    #[arg(long, default_value_t = false)]
+    accept_root_change: bool,
+
+    /// Protocol used to discover what the domain publishes
+    #[arg(long, value_enum, default_value_t = DiscoveryProtocol::Chainsights)]
+    discovery: DiscoveryProtocol,
+
+    /// Also traverse the subdomains the domain delegates its namespace to with `delegate=` TXT records
+    #[arg(long, default_value_t = false)]
+    follow_delegations: bool,
+
+    /// Accept the results of a trusted verifier's signed verification statement at this URI instead of traversing
+    #[arg(long, requires = "relay_identity")]
+    relay: Option<String>,
+
+    /// Identity expected to sign the verification statement given with --relay
+    #[arg(long, requires = "relay")]
+    relay_identity: Option<String>,
+
+    /// Only accept verification results evaluated under this policy URI
+    #[arg(long, requires = "relay")]
+    relay_policy: Option<String>,
+
+    /// Reject verification results given with --relay that were verified more than this many hours ago
+    #[arg(long, requires = "relay", value_parser = clap::value_parser!(i64).range(1..))]
+    relay_max_age: Option<i64>,
+
+    /// Exit with an error when more than this percentage of statements failed to verify or had expired (e.g., 10%)
+    #[arg(long, value_parser = parse_error_rate)]
+    fail_on_error_rate: Option<f64>,
+
+    /// Exit with an error unless the root catalog and every component and release verified
+    #[arg(long, default_value_t = false)]
+    require_all_releases: bool,
+
+    /// Exit with an error unless the root catalog links a freshness statement confirming it was current within this many hours
+    #[arg(long, value_parser = clap::value_parser!(i64).range(1..))]
+    max_catalog_age: Option<i64>,
+
+    /// Exit with an error unless every verified release links or satisfies this: sbom, provenance, vex or signed-artifacts (repeatable or comma-separated)
+    #[arg(long, value_delimiter = ',', value_parser = parse_required_artifact_arg)]
+    require_artifact: Vec<RequiredArtifact>,
+
+    /// Apply the organization policy in this signed bundle from `policy export`, given as a path or URL, where it is stricter than the command line
+    #[arg(long, env = "CHAINSIGHTS_POLICY_BUNDLE")]
+    policy_bundle: Option<String>,
+
+    /// Identity expected to have signed the --policy-bundle policy, verified against --trusted-root or --trust-bundle if given (repeatable or comma-separated)
+    #[arg(long, env = "CHAINSIGHTS_POLICY_IDENTITY", value_delimiter = ',', requires = "policy_bundle")]
+    policy_identity: Vec<String>,
+
+    /// Public key (SSH or minisign) pinned to sign the --policy-bundle policy, whose detached signature is next to it (repeatable or comma-separated)
+    #[arg(long, env = "CHAINSIGHTS_POLICY_KEY", value_delimiter = ',', requires = "policy_bundle", conflicts_with = "policy_identity")]
+    policy_key: Vec<String>,
+
+    /// File recording when the policy last applied from each --policy-bundle source was issued, to refuse older policies being replayed [default: $XDG_STATE_HOME/chainsights/policies.json]
+    #[arg(long, env = "CHAINSIGHTS_POLICY_STATE", requires = "policy_bundle")]
+    policy_state: Option<PathBuf>,
+
+    /// Report attestation links outside the queried domain and the --allow-link-host hosts as errors instead of following them
+    #[arg(long, default_value_t = false)]
+    scope_links: bool,
+
+    /// Host that attestation links may also point at with --scope-links, including its subdomains (e.g., a CDN; repeatable or comma-separated)
+    #[arg(long, value_delimiter = ',', value_parser = parse_domain_arg, requires = "scope_links")]
+    allow_link_host: Vec<String>,
+
+    /// Another organization's domain (e.g., the vendor of a resold product) that component and release links may point at with --scope-links, once its own _chainsights record names the identity each link expects; such nodes are labeled CROSS-DOMAIN (repeatable or comma-separated)
+    #[arg(long, value_delimiter = ',', value_parser = parse_domain_arg, requires = "scope_links")]
+    trust_cross_domain: Vec<String>,
+
+    /// Endorser whose endorsements are attributed to NAME when signed by IDENTITY, as NAME=IDENTITY; endorsements by other signers are shown by signer identity only (repeatable)
+    #[arg(long, value_parser = parse_endorser_arg, value_name = "NAME=IDENTITY")]
+    trust_endorser: Vec<(String, String)>,
+
+    /// Require hosts serving attestations to use HTTPS with a TLS certificate matching their DANE TLSA records; hosts without TLSA records fail
+    #[arg(long, default_value_t = false)]
+    dane: bool,
+
+    #[command(flatten)]
+    trust: TrustArgs,
+
+    #[command(flatten)]
+    plugins: PluginArgs,
+
+    /// Armored PGP public keys (e.g., a distribution keyring or an Apache KEYS file) for attestations whose links pin PGP key fingerprints
+    #[arg(long, value_parser = parse_pgp_keyring_arg)]
+    pgp_keyring: Option<Arc<PgpKeyring>>,
+
+    /// Only accept release statements signed from a CI build in this repository (e.g., https://github.com/example/app)
+    #[arg(long)]
+    release_source_repository: Option<String>,
+
+    /// Only accept release statements signed from a CI build of a git ref matching this pattern, where `*` matches anything (e.g., "refs/tags/*")
+    #[arg(long)]
+    release_source_ref: Option<String>,
+
+    /// Only accept release statements signed from a CI build triggered by this event (e.g., push)
+    #[arg(long)]
+    release_build_trigger: Option<String>,
+
+    /// Export OpenTelemetry traces of traversals to this OTLP/HTTP collector (e.g., http://localhost:4318)
+    #[arg(long, env = "OTEL_EXPORTER_OTLP_ENDPOINT", value_name = "URL")]
+    otlp_endpoint: Option<String>,
+
+    /// Shell command to run for each verified, unrevoked catalog, component and release, with the node as JSON on stdin
+    #[arg(long, env = "CHAINSIGHTS_ON_NODE_VERIFIED", value_name = "CMD")]
+    on_node_verified: Option<String>,
+
+    /// Shell command to run when a traversal fails the command's thresholds or policy, with the violation as JSON on stdin
+    #[arg(long, env = "CHAINSIGHTS_ON_POLICY_VIOLATION", value_name = "CMD")]
+    on_policy_violation: Option<String>,
+
+    /// Shell command to run when a root changes, a catalog is rolled back or `monitor` finds an unpublished Rekor entry, with the change as JSON on stdin
+    #[arg(long, env = "CHAINSIGHTS_ON_CHANGE", value_name = "CMD")]
+    on_change: Option<String>,
+
+    /// INSECURE, for local development only: accept statements without checking their signatures, reporting every one as UNVERIFIED
+    #[arg(long, default_value_t = false)]
+    insecure_skip_signature: bool,
+
+    /// INSECURE, for local development only: accept statements signed by any identity, reporting those not signed by an expected identity as UNVERIFIED
+    #[arg(long, default_value_t = false)]
+    insecure_skip_identity: bool,
+
+    /// Include the traversal's cost in the aggregate: requests, bytes, cache hits and latency per host, and verification times
+    #[arg(long, default_value_t = false)]
+    stats: bool,
+
+    /// Progress display for this run's traversals, set by `start_progress`
+    #[arg(skip)]
+    progress: Option<Arc<TraversalProgress>>,
+
+    /// Trace of this run's traversals with --otlp-endpoint, set by `start_progress`
+    #[arg(skip)]
+    tracer: Option<Arc<Tracer>>,
+
+    /// Statistics of this run's traversals with --stats, set by `start_progress`
+    #[arg(skip)]
+    stats_recorder: Option<Arc<StatsRecorder>>,
+}
+
+/// Where `publish` uploads an attestation tree, and the URIs its files are served at.
+#[derive(Args)]
+struct PublishTreeArgs {
+    /// Directory of signed bundles and the documents they link to, laid out as they are served
+    #[arg(long, required = true)]
+    dir: Option<PathBuf>,
+
+    /// Where to upload: s3://bucket[/prefix], webdav://host/path (webdav+http:// for plain HTTP) or oci://registry/repository
+    #[arg(long, required = true)]
+    target: Option<String>,
+
+    /// The URI each file is served at, with {path} for its path within --dir or {tag} for its OCI tag
+    /// (e.g., https://example.com/chainsights/{path}). Defaults to serving straight from the target.
+    #[arg(long)]
+    uri_template: Option<String>,
+
+    /// Path of the root catalog within --dir, if it can't be told from the links between statements
+    #[arg(long)]
+    root: Option<String>,
+
+    #[command(flatten)]
+    target_options: TargetArgs,
+
+    /// Check the tree and print where each file would be published, without uploading
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+}
+
+/// Options of the upload targets that need them.
+#[derive(Args)]
+struct TargetArgs {
+    /// S3-compatible endpoint to upload to instead of AWS (e.g., http://localhost:9000 for MinIO)
+    #[arg(long)]
+    s3_endpoint: Option<String>,
+
+    /// S3 region
+    #[arg(long, env = "AWS_REGION", default_value = "us-east-1")]
+    s3_region: String,
+
+    /// WebDAV or registry username
+    #[arg(long, env = "CHAINSIGHTS_PUBLISH_USERNAME")]
+    username: Option<String>,
+
+    /// WebDAV or registry password or token
+    #[arg(long, env = "CHAINSIGHTS_PUBLISH_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+}
+
+impl From<TargetArgs> for TargetOptions {
+    fn from(args: TargetArgs) -> Self {
+        TargetOptions {
+            s3_endpoint: args.s3_endpoint,
+            s3_region: Some(args.s3_region),
+            username: args.username,
+            password: args.password,
+        }
+    }
+}
+
+/// Options selecting which releases and artifacts `purl` reports.
+#[derive(Args)]
+struct PurlOutputArgs {
+    /// Fetch all releases for the specified component (ignores version in PURL)
+    #[arg(long, default_value_t = false)]
+    all_releases: bool,
+
+    /// Fetch and verify SBOM/artifact of the specified media type (e.g., application/spdx+json)
+    #[arg(long = "fetch-sbom")]
+    fetch_sbom_media_type: Option<String>,
+
+    /// Exit with an error if the queried version has been yanked or withdrawn by its publisher
+    #[arg(long, default_value_t = false)]
+    fail_on_yanked: bool,
+
+    /// Only match releases whose license expression includes one of these SPDX license IDs (repeatable or comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    filter_license: Vec<String>,
+
+    /// Only report and fetch platform-specific artifacts for this platform (os/arch[/variant], e.g., linux/arm64)
+    #[arg(long, value_parser = parse_platform_arg)]
+    platform: Option<Platform>,
+}
+
+/// The Sigstore trust material bundles are verified against. Without any, a certificate is
+/// trusted whenever the signature and identity match.
+#[derive(Args)]
+struct TrustArgs {
+    /// Verify bundles against this Sigstore trusted root (trusted_root.json), e.g. a private instance's. With --trust-bundle, it verifies the snapshot, whose root then replaces it.
+    #[arg(long, value_parser = parse_trusted_root_arg)]
+    trusted_root: Option<Arc<TrustedRoot>>,
+
+    /// Verify bundles against the trust material in this signed snapshot from `trust export`, for offline use
+    #[arg(long)]
+    trust_bundle: Option<PathBuf>,
+
+    /// Identity expected to have signed the --trust-bundle snapshot, verified against --trusted-root (repeatable)
+    #[arg(long, requires_all = ["trust_bundle", "trusted_root"])]
+    trust_bundle_identity: Vec<String>,
+
+    /// Public key (SSH or minisign) pinned to sign the --trust-bundle snapshot, whose detached signature is next to it (repeatable)
+    #[arg(long, requires = "trust_bundle", conflicts_with = "trust_bundle_identity")]
+    trust_bundle_key: Vec<String>,
+}
+
+impl TrustArgs {
+    /// Verifies the --trust-bundle snapshot, if there is one, and trusts its trusted root.
+    fn open_trust_bundle(&mut self) -> Result<()> {
+        let Some(path) = &self.trust_bundle else {
+            return Ok(());
+        };
+        if self.trust_bundle_key.is_empty() && self.trust_bundle_identity.is_empty() {
+            bail!("--trust-bundle needs --trust-bundle-key, or --trust-bundle-identity and --trusted-root");
+        }
+        let snapshot = read_trust_snapshot(path, &self.trust_bundle_key, &self.trust_bundle_identity, self.trusted_root.as_deref())
+            .with_context(|| format!("Failed to open trust bundle '{}'", path.display()))?;
+        self.trusted_root = Some(Arc::new(snapshot.trusted_root));
+        Ok(())
+    }
+}
+
+/// Opens the signed trust snapshot at `path`: a statement signed by one of the pinned `keys`, with
+/// its detached signature next to it, or else a bundle signed by one of `identities` whose
+/// certificate chains to `trusted_root`.
+fn read_trust_snapshot(
+    path: &Path,
+    keys: &[String],
+    identities: &[String],
+    trusted_root: Option<&TrustedRoot>,
+) -> Result<ChainsightsTrustSnapshotPredicate> {
+    let name = path.display().to_string();
+    if !keys.is_empty() {
+        let verifier = key_verifier(keys)?;
+        let document = std::fs::read_to_string(path).with_context(|| format!("Failed to read '{}'", name))?;
+        let signature = verifier
+            .signature_uri(&name, &document)
+            .map(|signature| std::fs::read_to_string(&signature).with_context(|| format!("Failed to read signature '{}'", signature)))
+            .transpose()?;
+        let signer = ExpectedSigner::Key { verifier: &*verifier, signature: signature.as_deref() };
+        return open_trust_snapshot(&name, &document, &signer);
+    }
+    let identities: Vec<SignerIdentity> = identities.iter().map(|i| SignerIdentity::new(i)).collect();
+    open_trust_snapshot(&name, &read_bundle_file(path)?, &ExpectedSigner::Sigstore { identities: &identities, trusted_root })
+}
+
+/// WebAssembly plugins that parse proprietary predicate types and check bespoke policies. Without
+/// the `plugins` feature there are none.
+#[derive(Args)]
+struct PluginArgs {
+    /// WebAssembly plugin (.wasm or .wat) that parses proprietary predicate types or checks a bespoke policy (repeatable or comma-separated)
+    #[cfg(feature = "plugins")]
+    #[arg(long = "plugin", env = "CHAINSIGHTS_PLUGINS", value_delimiter = ',', value_name = "PATH")]
+    paths: Vec<PathBuf>,
+
+    /// The compiled plugins, set by `load`
+    #[cfg(feature = "plugins")]
+    #[arg(skip)]
+    loaded: Arc<Plugins>,
+}
+
+#[cfg(feature = "plugins")]
+impl PluginArgs {
+    /// Compiles the --plugin plugins.
+    fn load(&mut self) -> Result<()> {
+        self.loaded = Arc::new(Plugins::load(&self.paths)?);
+        Ok(())
+    }
+
+    /// Fails with the violations the policy-checking plugins find in a traversal.
+    fn check_policies(&self, data: &AggregatedCatalogData) -> Result<()> {
+        self.loaded.check_policies(data)
+    }
+
+    /// Parses statements with the predicate-parsing plugins, if any are loaded.
+    fn parser(&self) -> Option<Arc<dyn PredicateParser>> {
+        self.loaded.parses_predicates().then(|| self.loaded.clone() as Arc<dyn PredicateParser>)
+    }
+}
+
+#[cfg(not(feature = "plugins"))]
+impl PluginArgs {
+    fn load(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn check_policies(&self, _data: &AggregatedCatalogData) -> Result<()> {
+        Ok(())
+    }
+
+    fn parser(&self) -> Option<Arc<dyn PredicateParser>> {
+        None
+    }
+}
+
+/// Constraints on the CI build context recorded in a signing certificate.
+#[derive(Args)]
+struct BuildPolicyArgs {
+    /// Require a certificate issued to a CI build in this repository (e.g., https://github.com/example/app)
+    #[arg(long)]
+    source_repository: Option<String>,
+
+    /// Require a certificate issued to a CI build of a git ref matching this pattern, where `*` matches anything (e.g., "refs/tags/*")
+    #[arg(long)]
+    source_ref: Option<String>,
+
+    /// Require a certificate issued to a CI build triggered by this event (e.g., push)
+    #[arg(long)]
+    build_trigger: Option<String>,
+}
+
+impl BuildPolicyArgs {
+    fn to_policy(&self) -> BuildPolicy {
+        BuildPolicy {
+            source_repository: self.source_repository.clone(),
+            source_ref: self.source_ref.clone(),
+            build_trigger: self.build_trigger.clone(),
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// JSON with `nodes` and `edges` arrays
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum DiscoveryProtocol {
+    /// `_chainsights` DNS TXT record and signed in-toto catalogs
+    Chainsights,
+    /// CycloneDX Transparency Exchange API, located via /.well-known/tea
+    Tea,
+    /// A `<meta name="chainsights">` tag on the domain's homepage, for publishers without DNS access
+    Meta,
+    /// Chainsights first, falling back to TEA, then to a homepage meta tag
+    Auto,
+}
+
+impl TraversalArgs {
+    fn to_options(&self, client: &reqwest::Client) -> TraversalOptions {
+        TraversalOptions {
+            rekor_fallback: self
+                .rekor_fallback
+                .then(|| RekorClient::new(client.clone(), &self.rekor_url)),
+            exclude_revoked: self.exclude_revoked,
+            clock_skew: chrono::Duration::seconds(self.clock_skew),
+            time_source: self.time_source,
+            hooks: self.progress.clone().map(|progress| progress as Arc<dyn TraversalHooks>),
+            link_scope: self.scope_links.then(|| {
+                let scope = LinkScope::new(self.allow_link_host.clone());
+                match self.trust_cross_domain.is_empty() {
+                    true => scope,
+                    false => scope.trusting(CrossDomainTrust::new(self.trust_cross_domain.clone(), Arc::new(SystemResolver))),
+                }
+            }),
+            release_build_policy: Some(BuildPolicy {
+                source_repository: self.release_source_repository.clone(),
+                source_ref: self.release_source_ref.clone(),
+                build_trigger: self.release_build_trigger.clone(),
+            })
+            .filter(|policy| !policy.is_empty()),
+            trusted_root: self.trust.trusted_root.clone(),
+            pgp_keyring: self.pgp_keyring.clone(),
+            root_key_pin: None,
+            tracer: self.tracer.clone(),
+            insecure: InsecureMode {
+                skip_signature: self.insecure_skip_signature,
+                skip_identity: self.insecure_skip_identity,
+            },
+            stats: self.stats_recorder.clone(),
+            trusted_endorsers: self.trust_endorser.clone(),
+            predicate_parser: self.plugins.parser(),
+        }
+    }
+
+    /// Checks a traversal of `domain` against --fail-on-error-rate, --require-all-releases,
+    /// --require-artifact and the policy-checking --plugin plugins, running the --on-* hooks for it.
+    async fn check_failures(&self, domain: &str, data: &AggregatedCatalogData) -> Result<()> {
+        self.report_unverified(data);
+        let hooks = self.exec_hooks();
+        hooks.traversed(domain, data).await;
+        let checked = FailThreshold {
+            max_error_rate: self.fail_on_error_rate,
+            require_all: self.require_all_releases,
+            max_catalog_age: self.max_catalog_age.map(chrono::Duration::hours),
+            required_artifacts: self.require_artifact.clone(),
+        }
+        .check(data)
+        .and_then(|()| self.plugins.check_policies(data));
+        if let Err(e) = &checked {
+            hooks.policy_violation(domain, e).await;
+        }
+        checked
+    }
+
+    /// Checks the Scorecard results of `components` against --require-scorecard, running the
+    /// --on-policy-violation hook if they fall short.
+    async fn check_scorecards<'a>(&self, domain: &str, components: impl IntoIterator<Item = &'a AggregatedComponentData>) -> Result<()> {
+        let checked = check_scorecard_requirements(components, &self.require_scorecard);
+        if let Err(e) = &checked {
+            self.exec_hooks().policy_violation(domain, e).await;
+        }
+        checked
+    }
+
+    fn exec_hooks(&self) -> ExecHooks {
+        ExecHooks {
+            on_node_verified: self.on_node_verified.clone(),
+            on_policy_violation: self.on_policy_violation.clone(),
+            on_change: self.on_change.clone(),
+        }
+    }
+
+    /// Verifies the --policy-bundle policy, if there is one, and applies it. Its requirements add
+    /// to those of the command line and the lower error rate wins, while its root identities,
+    /// release build policy and trusted root apply only where the command line sets none.
+    async fn apply_policy_bundle(&mut self) -> Result<()> {
+        let Some(source) = &self.policy_bundle else {
+            return Ok(());
+        };
+        if self.policy_key.is_empty() && self.policy_identity.is_empty() {
+            bail!("--policy-bundle needs --policy-key or --policy-identity");
+        }
+        let policy = read_policy(source, &self.policy_key, &self.policy_identity, self.trust.trusted_root.as_deref())
+            .await
+            .with_context(|| format!("Failed to open policy bundle '{}'", source))?;
+        let state = match &self.policy_state {
+            Some(path) => Some(path.clone()),
+            None => match PolicyStore::default_path() {
+                Ok(path) => Some(path),
+                Err(e) => {
+                    output::eprint(1, Status::Warning, &format!("Not checking the policy against the last one applied: {:#}", e));
+                    None
+                }
+            },
+        };
+        if let Some(path) = state {
+            PolicyStore::new(path).observe(source, &policy)?;
+        }
+        output::eprint(0, Status::Verified, &format!("Applying policy '{}' issued at {}", policy.name, policy.timestamp));
+
+        self.require_all_releases |= policy.require_all_releases;
+        self.fail_on_error_rate = match (self.fail_on_error_rate, policy.max_error_rate) {
+            (Some(ours), Some(theirs)) => Some(ours.min(theirs)),
+            (ours, theirs) => ours.or(theirs),
+        };
+        for artifact in policy.required_artifacts {
+            if !self.require_artifact.contains(&artifact) {
+                self.require_artifact.push(artifact);
+            }
+        }
+        let pinned: Vec<String> = self.pin_root.iter().map(|(domain, _)| domain.clone()).collect();
+        for (domain, identities) in policy.root_identities {
+            let domain = normalize_domain(&domain)?;
+            if !pinned.contains(&domain) {
+                self.pin_root.extend(identities.into_iter().map(|identity| (domain.clone(), identity)));
+            }
+        }
+        if let Some(build) = policy.release_build_policy
+            && self.release_source_repository.is_none()
+            && self.release_source_ref.is_none()
+            && self.release_build_trigger.is_none()
+        {
+            self.release_source_repository = build.source_repository;
+            self.release_source_ref = build.source_ref;
+            self.release_build_trigger = build.build_trigger;
+        }
+        if let Some(trusted_root) = policy.trusted_root
+            && self.trust.trusted_root.is_none()
+        {
+            self.trust.trusted_root = Some(Arc::new(trusted_root));
+        }
+        Ok(())
+    }
+
+    /// Checks the serial of a domain's verified root catalog against the last one seen from it,
+    /// warning of a rollback, or failing with --fail-on-rollback.
+    async fn check_serial(&self, domain: &str, data: &AggregatedCatalogData) -> Result<()> {
+        let Some(catalog) = data.catalog_predicate.as_deref().filter(|_| !self.no_serial_check && data.verification.as_ref().is_some_and(NodeVerification::is_verified)) else {
+            return Ok(());
+        };
+        let path = match &self.serial_state {
+            Some(path) => path.clone(),
+            None => match SerialStore::default_path() {
+                Ok(path) => path,
+                Err(e) => {
+                    output::print(1, Status::Warning, &format!("Not checking the root catalog's serial: {:#}", e));
+                    return Ok(());
+                }
+            },
+        };
+        let Some(rollback) = SerialStore::new(path).observe(domain, catalog)? else {
+            return Ok(());
+        };
+        let details = serde_json::json!({
+            "serial": rollback.serial,
+            "last_seen_serial": rollback.last_seen.serial,
+            "last_seen_timestamp": rollback.last_seen.timestamp,
+        });
+        self.exec_hooks().changed(domain, "catalog-rollback", &rollback.to_string(), details).await;
+        match self.fail_on_rollback {
+            true => Err(anyhow!(rollback.to_string())),
+            false => {
+                output::print(1, Status::Warning, &rollback.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    /// The root pins discovery checks each domain's record against, unless disabled.
+    fn root_pins(&self) -> RootPins {
+        let path = match (&self.root_pin_state, self.no_root_pinning) {
+            (_, true) => None,
+            (Some(path), false) => Some(path.clone()),
+            (None, false) => match RootPins::default_path() {
+                Ok(path) => Some(path),
+                Err(e) => {
+                    output::print(1, Status::Warning, &format!("Not pinning root identities: {:#}", e));
+                    None
+                }
+            },
+        };
+        let on_change = match (self.fail_on_root_change, self.accept_root_change) {
+            (true, _) => OnRootChange::Fail,
+            (_, true) => OnRootChange::Accept,
+            _ => OnRootChange::Warn,
+        };
+        self.pin_root
+            .iter()
+            .fold(RootPins::new(path, on_change), |pins, (domain, identity)| pins.pinning(domain, identity))
+    }
+
+    /// Shows a progress bar for the traversals that follow, if standard output is a terminal, and
+    /// traces them with --otlp-endpoint. Fetchers and options must be made after this to be traced
+    /// and counted by --stats.
+    fn start_progress(&mut self) {
+        if self.insecure_skip_signature || self.insecure_skip_identity {
+            let skipped = match (self.insecure_skip_signature, self.insecure_skip_identity) {
+                (true, true) => "Signatures and signer identities are",
+                (true, false) => "Signatures are",
+                _ => "Signer identities are",
+            };
+            output::eprint(0, Status::Warning, &format!(
+                "INSECURE MODE: {} not checked. Statements accepted because of it are reported as UNVERIFIED.",
+                skipped
+            ));
+        }
+        self.progress = TraversalProgress::start().map(Arc::new);
+        self.stats_recorder = self.stats.then(|| Arc::new(StatsRecorder::new()));
+        self.start_trace();
+    }
+
+    /// Warns that an insecure mode let statements through unverified, so that a downgraded run
+    /// cannot be taken for a verified one.
+    fn report_unverified(&self, data: &AggregatedCatalogData) {
+        let unverified = data.unverified_count();
+        if unverified > 0 {
+            output::eprint(0, Status::Warning, &format!(
+                "INSECURE MODE: {} statement(s) were accepted UNVERIFIED; do not rely on these results",
+                unverified
+            ));
+        }
+    }
+
+    fn finish_progress(&self) {
+        if let Some(progress) = &self.progress {
+            progress.finish();
+        }
+    }
+
+    fn start_trace(&mut self) {
+        self.tracer = self.otlp_endpoint.is_some().then(|| Arc::new(Tracer::new("traversal")));
+    }
+
+    /// Exports the trace of the traversals so far, if there is one, as a trace about `query`
+    /// (e.g., a domain or PURL). A collector that cannot be reached only causes a warning.
+    async fn export_trace(&self, client: &reqwest::Client, query: &str) {
+        let (Some(tracer), Some(endpoint)) = (&self.tracer, &self.otlp_endpoint) else {
+            return;
+        };
+        if let Err(e) = tracer.export(client, endpoint, vec![("chainsights.query", query.into())]).await {
+            output::print(0, Status::Warning, &format!("{:#}", e));
+        }
+    }
+
+    /// The HTTP client for a traversal, which with --dane exposes the certificates servers present.
+    fn http_client(&self) -> Result<reqwest::Client> {
+        reqwest::Client::builder()
+            .tls_info(self.dane)
+            .build()
+            .context("Failed to build the HTTP client")
+    }
+
+    fn fetchers(&self, client: &reqwest::Client) -> Fetchers {
+        match self.dane {
+            true => Fetchers::with_dane(client.clone(), Arc::new(SystemResolver)),
+            false => Fetchers::new(client.clone()),
+        }
+        .with_tracer(self.tracer.clone())
+        .with_stats(self.stats_recorder.clone())
+    }
+
+    fn deps_dev(&self, client: &reqwest::Client) -> Option<DepsDevClient> {
+        self.enrich_deps_dev
+            .then(|| DepsDevClient::new(client.clone(), &self.deps_dev_url))
+    }
+
+    fn scorecard(&self) -> bool {
+        self.scorecard || !self.require_scorecard.is_empty()
+    }
+
+    fn clearly_defined(&self, client: &reqwest::Client) -> Option<ClearlyDefinedClient> {
+        self.enrich_clearly_defined
+            .then(|| ClearlyDefinedClient::new(client.clone(), &self.clearly_defined_url))
+    }
+
+    fn layout(&self) -> Result<Option<Layout>> {
+        self.intoto_layout.as_deref().map(Layout::read).transpose()
+    }
+
+    fn to_discovery(&self, client: &reqwest::Client) -> Box<dyn Discovery> {
+        self.discovery_with(self.to_options(client))
+    }
+
+    /// The discovery protocol of these arguments, traversing with `options`.
+    fn discovery_with(&self, options: TraversalOptions) -> Box<dyn Discovery> {
+        if let (Some(uri), Some(identity)) = (&self.relay, &self.relay_identity) {
+            return Box::new(RelayDiscovery::new(
+                uri,
+                identity,
+                self.relay_policy.clone(),
+                self.relay_max_age.map(chrono::Duration::hours),
+                options,
+            ));
+        }
+        let root_pins = self.root_pins();
+        let chainsights = ChainsightsDiscovery::new(options.clone(), Arc::new(SystemResolver))
+            .following_delegations(self.follow_delegations)
+            .pinning_roots(Some(root_pins.clone()));
+        let meta = MetaTagDiscovery::new(options).pinning_roots(Some(root_pins));
+        match self.discovery {
+            DiscoveryProtocol::Chainsights => Box::new(chainsights),
+            DiscoveryProtocol::Tea => Box::new(TeaDiscovery),
+            DiscoveryProtocol::Meta => Box::new(meta),
+            DiscoveryProtocol::Auto => Box::new(FirstDiscovery::new(vec![Box::new(chainsights), Box::new(TeaDiscovery), Box::new(meta)])),
+        }
+    }
+}
+
+impl Commands {
+    /// The traversal options of commands that traverse a domain's attestations.
+    fn traversal_args_mut(&mut self) -> Option<&mut TraversalArgs> {
+        match self {
+            Commands::Purl { action: Some(PurlAction::Resolve { traversal, .. }), .. } => Some(traversal),
+            Commands::Domain { traversal, .. }
+            | Commands::Purl { traversal, .. }
+            | Commands::Graph { traversal, .. }
+            | Commands::Eol { traversal, .. }
+            | Commands::Monitor { action: MonitorAction::Rekor { traversal, .. } }
+            | Commands::Artifacts { action: ArtifactsAction::VerifyLocal { traversal, .. } }
+            | Commands::VerifyImage { traversal, .. }
+            | Commands::Scan { traversal, .. }
+            | Commands::Score { traversal, .. }
+            | Commands::Trends { traversal, .. }
+            | Commands::VerifyDomain { traversal, .. } => Some(traversal),
+            #[cfg(feature = "grpc")]
+            Commands::ServeGrpc { traversal, .. } => Some(traversal),
+            #[cfg(feature = "admission")]
+            Commands::ServeAdmission { traversal, .. } => Some(traversal),
+            #[cfg(feature = "tui")]
+            Commands::Tui { traversal, .. } => Some(traversal),
+            _ => None,
+        }
+    }
+
+    /// The trust material options of commands that verify bundles.
+    fn trust_args_mut(&mut self) -> Option<&mut TrustArgs> {
+        match self {
+            Commands::VerifyBundle { trust, .. } => Some(trust),
+            command => command.traversal_args_mut().map(|traversal| &mut traversal.trust),
+        }
+    }
+
+    /// The plugin options of commands that parse statements or check traversals.
+    fn plugin_args_mut(&mut self) -> Option<&mut PluginArgs> {
+        match self {
+            Commands::VerifyBundle { plugins, .. } => Some(plugins),
+            command => command.traversal_args_mut().map(|traversal| &mut traversal.plugins),
+        }
+    }
+}
+
+/// Parses the command line and runs the selected command.
+pub async fn run() -> Result<()> {
+    let mut cli = Cli::parse();
+    output::init_color(cli.no_color);
+    if let Some(trust) = cli.command.trust_args_mut() {
+        trust.open_trust_bundle()?;
+    }
+    if let Some(traversal) = cli.command.traversal_args_mut() {
+        traversal.apply_policy_bundle().await?;
+        if traversal.time_source != TimeSource::Local && traversal.trust.trusted_root.is_none() {
+            bail!(
+                "--time-source {:?} needs --trusted-root or --trust-bundle to verify the bundles' timestamps against",
+                traversal.time_source
+            );
+        }
+    }
+    if let Some(plugins) = cli.command.plugin_args_mut() {
+        plugins.load()?;
+    }
+
+    match cli.command {
+        Commands::Domain {
+            domain,
+            filter_license,
+            ndjson,
+            traversal,
+        } => handle_commands_domain(domain, filter_license, ndjson, traversal).await?,
+
+        Commands::Purl {
+            action: Some(action),
+            ..
+        } => handle_commands_purl_action(action).await?,
+
+        Commands::Purl {
+            action: None,
+            purl,
+            domain,
+            output,
+            traversal,
+        } => {
+            let purl = purl.context("--purl is required")?;
+            handle_commands_purl(purl, domain, output, traversal).await?
+        }
+
+        Commands::Graph {
+            domain,
+            format,
+            output,
+            traversal,
+        } => handle_commands_graph(domain, format, output, traversal).await?,
+
+        Commands::Eol {
+            purl,
+            domain,
+            at,
+            fail_on_eol,
+            traversal,
+        } => handle_commands_eol(purl, domain, at, fail_on_eol, traversal).await?,
+
+        Commands::Init {
+            domain,
+            output_dir,
+            key,
+            github_token,
+        } => handle_commands_init(domain, &output_dir, key, github_token).await?,
+
+        Commands::Generate { source } => handle_commands_generate(source).await?,
+
+        Commands::Attest {
+            kind:
+                AttestKind::Baseline {
+                    component,
+                    key,
+                    output,
+                    draft,
+                    uri,
+                    verifier,
+                    controls,
+                },
+        } => {
+            let draft = draft.unwrap_or_else(|| output.with_extension("json"));
+            handle_commands_attest_baseline(&component, &key, &output, &draft, uri, verifier, controls.as_deref()).await?
+        }
+
+        Commands::Key { key_dir, action } => handle_commands_key(key_dir, action)?,
+
+        Commands::Catalog { action } => handle_commands_catalog(action)?,
+
+        Commands::Component { action } => handle_commands_component(action)?,
+
+        Commands::Publish {
+            action: Some(target),
+            ..
+        } => handle_commands_publish(target).await?,
+
+        Commands::Publish { action: None, tree } => handle_commands_publish_tree(tree).await?,
+
+        Commands::Ci { action } => handle_commands_ci(action).await?,
+
+        Commands::VerifyDomain { domain, traversal } => handle_commands_verify_domain(domain, traversal).await?,
+
+        Commands::Relink {
+            dir,
+            mappings,
+            output_dir,
+            dry_run,
+        } => handle_commands_relink(&dir, &mappings, output_dir.filter(|_| !dry_run).as_deref())?,
+
+        Commands::Setup {
+            target: SetupTarget::Dns { dns },
+        } => handle_commands_setup_dns(dns).await?,
+
+        Commands::Selftest => handle_commands_selftest().await?,
+
+        Commands::VerifyBundle {
+            file,
+            identity,
+            issuer,
+            build_policy,
+            trust,
+            plugins,
+            time_source,
+            clock_skew,
+        } => handle_commands_verify_bundle(
+            &file,
+            &identity,
+            issuer.as_deref(),
+            &build_policy.to_policy(),
+            trust.trusted_root.as_deref(),
+            &plugins,
+            time_source,
+            clock_skew,
+        )?,
+
+        Commands::Monitor { action } => handle_commands_monitor(action).await?,
+
+        #[cfg(feature = "grpc")]
+        Commands::ServeGrpc { listen, traversal } => {
+            let client = traversal.http_client()?;
+            let fetchers = traversal.fetchers(&client);
+            grpc::serve(listen, Arc::new(TraversalResolver { traversal, client, fetchers })).await?
+        }
+
+        #[cfg(feature = "admission")]
+        Commands::ServeAdmission {
+            listen,
+            domains,
+            tls_cert,
+            tls_key,
+            refresh_interval,
+            max_staleness,
+            traversal,
+        } => {
+            let tls = tls_cert.as_deref().zip(tls_key.as_deref());
+            let refresh_interval = std::time::Duration::from_secs(refresh_interval);
+            let max_staleness = std::time::Duration::from_secs(max_staleness);
+            handle_commands_serve_admission(listen, domains, tls, refresh_interval, max_staleness, traversal).await?
+        }
+
+        #[cfg(feature = "tui")]
+        Commands::Tui { domain, traversal } => handle_commands_tui(domain, traversal).await?,
+
+        Commands::Trust { action } => handle_commands_trust(action).await?,
+
+        Commands::Policy { action } => handle_commands_policy(action).await?,
+
+        Commands::Inspect { source } => handle_commands_inspect(&source).await?,
+
+        Commands::Artifacts { action } => handle_commands_artifacts(action).await?,
+
+        Commands::VerifyImage {
+            image,
+            domains,
+            username,
+            password,
+            traversal,
+        } => {
+            let auth = match (username, password) {
+                (Some(username), Some(password)) => RegistryAuth::Basic(username, password),
+                (None, None) => RegistryAuth::Anonymous,
+                _ => return Err(anyhow!("Both --username and --password are required for registry authentication")),
+            };
+            handle_commands_verify_image(&image, &domains, auth, traversal).await?
+        }
+
+        Commands::Scan {
+            lockfile,
+            domains,
+            mapping,
+            fail_under,
+            traversal,
+        } => handle_commands_scan(&lockfile, domains, mapping.as_deref(), fail_under, traversal).await?,
+
+        Commands::Score {
+            domains,
+            fresh_within_days,
+            record,
+            history,
+            traversal,
+        } => {
+            let history = match record {
+                true => Some(HistoryStore::new(match history {
+                    Some(path) => path,
+                    None => HistoryStore::default_path()?,
+                })),
+                false => None,
+            };
+            handle_commands_score(&domains, chrono::Duration::days(fresh_within_days), history, traversal).await?
+        }
+
+        Commands::Trends {
+            domain,
+            period_days,
+            fresh_within_days,
+            history,
+            traversal,
+        } => {
+            let history = HistoryStore::new(match history {
+                Some(path) => path,
+                None => HistoryStore::default_path()?,
+            });
+            handle_commands_trends(&domain, chrono::Duration::days(period_days), chrono::Duration::days(fresh_within_days), history, traversal).await?
+        }
+
+        Commands::Completions { shell } => {
+            let mut command = Cli::command();
+            let name = command.get_name().to_string();
+            clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+        }
+
+        Commands::Manpages { out_dir } => handle_commands_manpages(&out_dir)?,
+    }
+
+    Ok(())
+}
+
+/// Clap value parser for trusted root arguments: reads and parses the file.
+fn parse_trusted_root_arg(path: &str) -> std::result::Result<Arc<TrustedRoot>, String> {
+    std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read trusted root '{}'", path))
+        .and_then(|text| TrustedRoot::from_json(&text))
+        .map(Arc::new)
+        .map_err(|e| format!("{:#}", e))
+}
+
+/// Clap value parser for PGP keyring arguments: reads and parses the armored keys.
+fn parse_pgp_keyring_arg(path: &str) -> std::result::Result<Arc<PgpKeyring>, String> {
+    std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read PGP keyring '{}'", path))
+        .and_then(|text| PgpKeyring::from_armored(&text))
+        .map(Arc::new)
+        .map_err(|e| format!("{:#}", e))
+}
+
+fn parse_mapping_arg(rule: &str) -> std::result::Result<UriMapping, String> {
+    UriMapping::parse(rule).map_err(|e| e.to_string())
+}
+
+fn parse_challenge_arg(challenge: &str) -> std::result::Result<(String, String), String> {
+    match challenge.split_once('=') {
+        Some((name, value)) if !name.is_empty() && !value.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("'{}' is not a NAME=VALUE record", challenge)),
+    }
+}
+
+fn parse_required_artifact_arg(artifact: &str) -> std::result::Result<RequiredArtifact, String> {
+    artifact.parse().map_err(|e: anyhow::Error| e.to_string())
+}
+
+fn parse_root_pin_arg(pin: &str) -> std::result::Result<(String, String), String> {
+    match pin.split_once('=') {
+        Some((domain, identity)) if !identity.is_empty() => Ok((parse_domain_arg(domain)?, identity.to_string())),
+        _ => Err(format!("'{}' is not a DOMAIN=IDENTITY pin", pin)),
+    }
+}
+
+fn parse_endorser_arg(endorser: &str) -> std::result::Result<(String, String), String> {
+    match endorser.split_once('=') {
+        Some((name, identity)) if !name.is_empty() && !identity.is_empty() => Ok((name.to_string(), identity.to_string())),
+        _ => Err(format!("'{}' is not a NAME=IDENTITY endorser", endorser)),
+    }
+}
+
+fn parse_key_arg(spec: &str) -> std::result::Result<KeySource, String> {
+    KeySource::parse(spec).map_err(|e| e.to_string())
+}
+
+fn parse_build_source_arg(spec: &str) -> std::result::Result<BuildSource, String> {
+    BuildSource::parse(spec).map_err(|e| e.to_string())
+}
+
+fn parse_label_arg(label: &str) -> std::result::Result<(String, String), String> {
+    match label.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("'{}' is not a KEY=VALUE label", label)),
+    }
+}
+
+/// Clap value parser for platform arguments.
+fn parse_platform_arg(platform: &str) -> std::result::Result<Platform, String> {
+    platform.parse().map_err(|e: anyhow::Error| e.to_string())
+}
+
+async fn handle_commands_domain(
+    domain: String,
+    filter_license: Vec<String>,
+    ndjson: bool,
+    mut traversal: TraversalArgs,
+) -> Result<()> {
+    let client = traversal.http_client()?;
+    output::print(0, Status::Info, &format!("Querying domain: {}", domain));
+    traversal.start_progress();
+    let discovery = traversal.to_discovery(&client);
+    let fetchers = traversal.fetchers(&client);
+    let discovered = discovery.discover(&domain, &fetchers).await;
+    traversal.finish_progress();
+    traversal.export_trace(&client, &domain).await;
+    let mut aggregated_data = discovered?;
+    traversal.check_serial(&domain, &aggregated_data).await?;
+    let failures = traversal.check_failures(&domain, &aggregated_data).await;
+    if !filter_license.is_empty() {
+        aggregated_data.retain_licenses(&filter_license);
+    }
+    if let Some(deps_dev) = traversal.deps_dev(&client) {
+        enrich_with_deps_dev(&mut aggregated_data, &deps_dev, &fetchers).await;
+    }
+    if let Some(clearly_defined) = traversal.clearly_defined(&client) {
+        enrich_with_clearly_defined(&mut aggregated_data, &clearly_defined, &fetchers).await;
+    }
+    if let Some(layout) = traversal.layout()? {
+        check_layout(&mut aggregated_data, &layout, &fetchers, &traversal.to_options(&client)).await;
+    }
+    if traversal.csaf {
+        correlate_advisories(&mut aggregated_data, &fetchers).await;
+    }
+    if traversal.swid_tags {
+        collect_swid_tags(&mut aggregated_data, &fetchers).await;
+    }
+    let mut failures = failures;
+    if traversal.scorecard() {
+        collect_scorecards(&mut aggregated_data, &fetchers, &traversal.to_options(&client)).await;
+        failures = failures.and(traversal.check_scorecards(&domain, &aggregated_data.components).await);
+    }
+
+    // Print the full aggregated data as JSON
+    if ndjson {
+        aggregated_data
+            .write_ndjson(BufWriter::new(std::io::stdout().lock()))
+            .context("Failed to write results as NDJSON")?;
+    } else {
+        output::json(&aggregated_data).context("Failed to serialize results to JSON")?;
+    }
+
+    failures
+}
+
+async fn handle_commands_graph(
+    domain: String,
+    format: GraphFormat,
+    output: Option<PathBuf>,
+    mut traversal: TraversalArgs,
+) -> Result<()> {
+    let client = traversal.http_client()?;
+    output::print(0, Status::Info, &format!("Querying domain: {}", domain));
+    traversal.start_progress();
+    let discovery = traversal.to_discovery(&client);
+    let fetchers = traversal.fetchers(&client);
+    let discovered = discovery.discover(&domain, &fetchers).await;
+    traversal.finish_progress();
+    traversal.export_trace(&client, &domain).await;
+    let aggregated_data = discovered?;
+    traversal.check_serial(&domain, &aggregated_data).await?;
+    let failures = traversal.check_failures(&domain, &aggregated_data).await;
+
+    let graph = Graph::from_aggregate(&format!("pkg:chainsights/{}", domain), &aggregated_data);
+    let rendered = match format {
+        GraphFormat::Dot => graph.to_dot(),
+        GraphFormat::Json => serde_json::to_string_pretty(&graph).context("Failed to serialize graph to JSON")?,
+    };
+    match output {
+        Some(path) => {
+            std::fs::write(&path, rendered)
+                .with_context(|| format!("Failed to write graph to '{}'", path.display()))?;
+            println!("Wrote graph to {}", path.display());
+        }
+        None => println!("{}", rendered),
+    }
+    failures
+}
+
+/// `purl` output for a whole component: its aggregated data and the releases that matched.
+#[derive(Serialize)]
+struct FilteredComponent<'a> {
+    component: Option<&'a AggregatedComponentData>,
+    matching_releases: &'a [Cow<'a, AggregatedReleaseData>],
+}
+
+/// The component a PURL query selects.
+struct PurlTarget {
+    purl: ChainsightsPurl,
+    /// Set when the query was an ecosystem PURL, which is matched against component PURLs and aliases.
+    ecosystem_package: Option<PackageUrl<'static>>,
+}
+
+impl PurlTarget {
+    fn is_match(&self, component: &ChainsightsComponentPredicate) -> bool {
+        match &self.ecosystem_package {
+            None => component.name == self.purl.component,
+            Some(package) => component.matching_purl(package).is_some(),
+        }
+    }
+}
+
+/// Parses a `pkg:chainsights` or ecosystem PURL, discovers its domain, and descends into any
+/// sub-catalogs the PURL names.
+async fn discover_purl_target(
+    purl: &str,
+    domain: Option<String>,
+    traversal: &TraversalArgs,
+    options: TraversalOptions,
+    fetchers: &Fetchers,
+) -> Result<(PurlTarget, AggregatedCatalogData)> {
+    let package = PackageUrl::from_str(purl)
+        .with_context(|| format!("Invalid PURL syntax: '{}'", purl))?;
+    // Ecosystem PURLs are matched against component alias PURLs under an explicit domain.
+    let ecosystem_package = (package.ty() != "chainsights").then_some(package);
+    let chainsights_purl = match &ecosystem_package {
+        None => parse_chainsights_purl(purl)
+            .with_context(|| format!("Failed to parse PURL '{}'", purl))?,
+        Some(package) => ChainsightsPurl {
+            domain: domain
+                .context("--domain is required when the PURL is not a pkg:chainsights PURL")?,
+            catalog_path: Vec::new(),
+            component: package.name().to_string(),
+            version: package.version().map(str::to_string),
+        },
+    };
+    let ChainsightsPurl {
+        domain,
+        catalog_path,
+        component,
+        version,
+    } = &chainsights_purl;
+
+    output::print(
+        1,
+        Status::Info,
+        &format!(
+            "Extracted Domain: {}, Sub-catalogs: {:?}, Component: {}, Version: {:?}",
+            domain, catalog_path, component, version
+        ),
+    );
+
+    let discovery = traversal.discovery_with(options.clone());
+    let mut aggregated_data = discovery.discover(domain, fetchers).await?;
+    traversal.check_serial(domain, &aggregated_data).await?;
+    if !catalog_path.is_empty() {
+        let options = options.scoped_to(domain);
+        aggregated_data = traverse_catalog_path(aggregated_data, catalog_path, fetchers, &options)
+            .await
+            .with_context(|| format!("Failed to select sub-catalog {} of '{}'", catalog_path.join("/"), domain))?;
+    }
+    let target = PurlTarget {
+        purl: chainsights_purl,
+        ecosystem_package,
+    };
+    Ok((target, aggregated_data))
+}
+
+async fn handle_commands_purl(
+    purl: String,
+    domain: Option<String>,
+    output: PurlOutputArgs,
+    mut traversal: TraversalArgs,
+) -> Result<()> {
+    let PurlOutputArgs {
+        all_releases,
+        fetch_sbom_media_type,
+        fail_on_yanked,
+        filter_license,
+        platform,
+    } = output;
+    let client = traversal.http_client()?;
+    output::print(0, Status::Info, &format!("Querying PURL: {}", purl));
+    traversal.start_progress();
+    let fetchers = traversal.fetchers(&client);
+    let discovered = discover_purl_target(&purl, domain, &traversal, traversal.to_options(&client), &fetchers).await;
+    traversal.finish_progress();
+    traversal.export_trace(&client, &purl).await;
+    let (target, mut aggregated_data) = discovered?;
+    let failures = traversal.check_failures(&target.purl.domain, &aggregated_data).await;
+    let component_name = target.purl.component.clone();
+    let purl_version_opt = target.purl.version.clone();
+    let is_match = |component: &ChainsightsComponentPredicate| target.is_match(component);
+    if !filter_license.is_empty() {
+        aggregated_data.retain_licenses(&filter_license);
+    }
+
+    if let Some(deps_dev) = traversal.deps_dev(&client) {
+        for component in aggregated_data
+            .components
+            .iter_mut()
+            .filter(|c| c.component_predicate.as_deref().is_some_and(is_match))
+        {
+            enrich_component(component, &deps_dev, &fetchers).await;
+        }
+    }
+    if let Some(clearly_defined) = traversal.clearly_defined(&client) {
+        for component in aggregated_data
+            .components
+            .iter_mut()
+            .filter(|c| c.component_predicate.as_deref().is_some_and(is_match))
+        {
+            enrich_component_licenses(component, &clearly_defined, &fetchers).await;
+        }
+    }
+    if let Some(layout) = traversal.layout()? {
+        let options = traversal.to_options(&client);
+        for component in aggregated_data
+            .components
+            .iter_mut()
+            .filter(|c| c.component_predicate.as_deref().is_some_and(is_match))
+        {
+            check_component_layout(component, &layout, &fetchers, &options).await;
+        }
+    }
+    if traversal.csaf {
+        let catalog_links = catalog_advisory_links(&aggregated_data);
+        let mut cache = AdvisoryCache::default();
+        for component in aggregated_data
+            .components
+            .iter_mut()
+            .filter(|c| c.component_predicate.as_deref().is_some_and(is_match))
+        {
+            correlate_component(component, &catalog_links, &mut cache, &fetchers).await;
+        }
+    }
+    if traversal.swid_tags {
+        for component in aggregated_data
+            .components
+            .iter_mut()
+            .filter(|c| c.component_predicate.as_deref().is_some_and(is_match))
+        {
+            collect_component_swid_tags(component, &fetchers).await;
+        }
+    }
+    let mut failures = failures;
+    if traversal.scorecard() {
+        let options = traversal.to_options(&client);
+        for component in aggregated_data
+            .components
+            .iter_mut()
+            .filter(|c| c.component_predicate.as_deref().is_some_and(is_match))
+        {
+            collect_component_scorecard(component, &fetchers, &options).await;
+        }
+        failures = failures.and(traversal.check_scorecards(
+            &target.purl.domain,
+            aggregated_data.components.iter().filter(|c| c.component_predicate.as_deref().is_some_and(is_match)),
+        ).await);
+    }
+
+    // --- Filtering Logic ---
+    // Matching releases are borrowed from the aggregate; only platform selection below copies them.
+    let mut found_releases: Vec<Cow<'_, AggregatedReleaseData>> = Vec::new();
+    let mut found_component_data: Option<&AggregatedComponentData> = None;
+
+    if let Some(_catalog) = &aggregated_data.catalog_predicate {
+        // Check if catalog was loaded
+        for comp_data in &aggregated_data.components {
+            if let Some(comp_pred) = &comp_data.component_predicate
+                && is_match(comp_pred)
+            {
+                found_component_data = Some(comp_data);
+                if all_releases {
+                    // Keep all releases for this component
+                    found_releases.extend(comp_data.releases.iter().map(Cow::Borrowed));
+                } else {
+                    // Filter by PURL version (if provided)
+                    if let Some(purl_version) = &purl_version_opt {
+                        for rel_data in &comp_data.releases {
+                            if let Some(rel_pred) = &rel_data.release_predicate {
+                                // TODO: Adjust field access for version
+                                // Assuming release_predicate has a 'version' field
+                                let purl = PackageUrl::from_str(&rel_pred.purl)
+                                    .context("Failed to parse PURL from release predicate")?;
+                                let release_version_field =
+                                    purl.version().context("Expected version in purl")?;
+                                if release_version_field == purl_version {
+                                    found_releases.push(Cow::Borrowed(rel_data));
+                                }
+                            }
+                        }
+                    } else {
+                        // PURL had no version, and --all-releases is false.
+                        // Behavior is undefined: error, return latest, return none?
+                        // Let's print a warning and return none for now.
+                        output::eprint(
+                            0,
+                            Status::Warning,
+                            "PURL has no version, and --all-releases is not specified. No specific release selected.",
+                        );
+                    }
+                }
+                break; // Found the matching component, stop searching components
+            }
+        }
+    } else if let Some(root_error) = &aggregated_data.root_error {
+        output::eprint(
+            0,
+            Status::Failed,
+            &format!("Cannot filter results as the root catalog failed to load: {}", root_error),
+        );
+        return failures; // Exit after reporting root error
+    }
+
+    // --- Security Contact ---
+    if let Some(contact) = found_component_data
+        .and_then(|c| c.component_predicate.as_ref())
+        .and_then(|c| c.security_contact.as_ref())
+        .or_else(|| aggregated_data.catalog_predicate.as_ref().and_then(|c| c.security_contact.as_ref()))
+    {
+        println!("Security contact for '{}': {}", component_name, contact);
+    }
+
+    // --- Hosted Service ---
+    if let Some(service) = found_component_data
+        .and_then(|c| c.component_predicate.as_ref())
+        .and_then(|c| c.service.as_ref())
+    {
+        println!("'{}' is a hosted service", component_name);
+        for endpoint in &service.endpoints {
+            println!(
+                "  Endpoint: {}{}{}",
+                endpoint.uri,
+                endpoint.description.as_ref().map(|d| format!(" ({})", d)).unwrap_or_default(),
+                endpoint.regions.as_ref().map(|r| format!(" in {}", r.join(", "))).unwrap_or_default()
+            );
+        }
+        if let Some(regions) = &service.regions {
+            println!("  Regions: {}", regions.join(", "));
+        }
+        if let Some(data_residency) = &service.data_residency {
+            println!("  Data residency: {}", data_residency.join(", "));
+        }
+        if let Some(status_page) = &service.status_page_uri {
+            println!("  Status page: {}", status_page);
+        }
+        for report in service.compliance_reports.iter().flatten() {
+            println!("  Compliance report: {}", report.uri);
+        }
+    }
+
+    // --- Access-Controlled Documents ---
+    // Gated links (e.g., SOC 2 reports under NDA) are never fetched; say how to obtain them instead.
+    let component_predicate = found_component_data.and_then(|c| c.component_predicate.as_deref());
+    let gated_links = aggregated_data
+        .catalog_predicate
+        .as_ref()
+        .and_then(|c| c.metadata_links.as_ref())
+        .into_iter()
+        .chain(component_predicate.and_then(|c| c.metadata_links.as_ref()))
+        .chain(component_predicate.and_then(|c| c.service.as_ref()).and_then(|s| s.compliance_reports.as_ref()))
+        .flatten()
+        .chain(found_releases.iter().flat_map(|r| &r.metadata_artifacts))
+        .filter(|link| link.is_gated());
+    for link in gated_links {
+        output::print(0, Status::Info, &link.access_instructions());
+    }
+
+    // --- Scorecard ---
+    if let Some(result) = found_component_data.and_then(|c| c.scorecard.as_ref()) {
+        output::print(0, Status::Verified, &format!(
+            "Scorecard for '{}'{}{} (signed by {})",
+            component_name,
+            result.score.map(|score| format!(": {}/10", score)).unwrap_or_default(),
+            result.date.as_ref().map(|date| format!(" as of {}", date)).unwrap_or_default(),
+            result.signer_identity
+        ));
+        for check in &result.checks {
+            let score = if check.score < 0 { "?".to_string() } else { check.score.to_string() };
+            output::print(1, Status::Info, &format!("{}: {}/10", check.name, score));
+        }
+    }
+
+    // --- Endorsements ---
+    let component_endorsements = found_component_data
+        .into_iter()
+        .flat_map(|c| &c.endorsements)
+        .map(|e| (component_name.as_str(), e));
+    let release_endorsements = found_releases.iter().flat_map(|r| {
+        let release_purl = r.release_predicate.as_ref().map_or(r.release_link_uri.as_str(), |p| p.purl.as_str());
+        r.endorsements.iter().map(move |e| (release_purl, e))
+    });
+    for (subject, endorsement) in component_endorsements.chain(release_endorsements) {
+        match &endorsement.trusted_endorser {
+            Some(name) => output::print(0, Status::Verified, &format!(
+                "'{}' is {} by {} (signed by {})",
+                subject, endorsement.predicate.assertion, name, endorsement.signer_identity
+            )),
+            None => output::print(0, Status::Info, &format!(
+                "'{}' is {} according to {}, which is not a trusted endorser (see --trust-endorser)",
+                subject, endorsement.predicate.assertion, endorsement.signer_identity
+            )),
+        }
+    }
+
+    // --- Layout Compliance ---
+    for release in &found_releases {
+        let Some(compliance) = &release.layout_compliance else { continue };
+        let release_purl = release.release_predicate.as_ref().map_or(release.release_link_uri.as_str(), |p| p.purl.as_str());
+        for step in &compliance.steps {
+            let status = if step.satisfied { Status::Verified } else { Status::Failed };
+            output::print(0, status, &format!(
+                "'{}' step '{}': attested by {} of {} required functionaries{}",
+                release_purl,
+                step.name,
+                step.functionaries.len(),
+                step.threshold,
+                if step.functionaries.is_empty() { String::new() } else { format!(" ({})", step.functionaries.join(", ")) }
+            ));
+        }
+        for (uri, error) in &compliance.errors {
+            output::print(1, Status::Warning, &format!("{}: {}", uri, error));
+        }
+    }
+
+    // --- Security Advisories ---
+    for release in &found_releases {
+        let release_purl = release.release_predicate.as_ref().map_or(release.release_link_uri.as_str(), |p| p.purl.as_str());
+        for advisory in &release.csaf_advisories {
+            let affected = advisory.vulnerabilities.iter().any(|v| v.is_affected());
+            output::print(0, if affected { Status::Warning } else { Status::Info }, &format!(
+                "'{}' is named in advisory {}: {}{}",
+                release_purl,
+                advisory.id,
+                advisory.title,
+                advisory.severity.as_ref().map(|s| format!(" ({})", s)).unwrap_or_default()
+            ));
+            for vulnerability in &advisory.vulnerabilities {
+                output::print(1, Status::Info, &format!("{}: {}", vulnerability.id, vulnerability.status.replace('_', " ")));
+            }
+        }
+    }
+
+    // --- SWID Tags ---
+    for release in &found_releases {
+        let release_purl = release.release_predicate.as_ref().map_or(release.release_link_uri.as_str(), |p| p.purl.as_str());
+        for tag in &release.swid_tags {
+            let status = if tag.digest_verified { Status::Verified } else { Status::Info };
+            output::print(0, status, &format!(
+                "'{}' has {} {} tag {} ({}{})",
+                release_purl,
+                tag.tag_type,
+                if tag.format == "coswid" { "CoSWID" } else { "SWID" },
+                tag.tag_id,
+                tag.name,
+                tag.version.as_ref().map(|v| format!(" {}", v)).unwrap_or_default()
+            ));
+        }
+    }
+
+    // --- Platform Selection ---
+    if let Some(platform) = &platform {
+        for release in &mut found_releases {
+            if release.release_predicate.as_ref().is_some_and(|r| r.artifact_groups.is_some())
+                && let Some(groups) = release
+                    .to_mut()
+                    .release_predicate
+                    .as_mut()
+                    .and_then(|r| Arc::make_mut(r).artifact_groups.as_mut())
+            {
+                groups.retain(|g| g.platform.matches(platform));
+            }
+        }
+    } else if found_releases.iter().any(|r| {
+        r.release_predicate
+            .as_ref()
+            .is_some_and(|p| p.artifact_groups.as_ref().is_some_and(|g| !g.is_empty()))
+    }) {
+        output::print(
+            0,
+            Status::Warning,
+            "Releases have platform-specific artifacts; pass --platform (e.g., linux/arm64) to select one platform",
+        );
+    }
+
+    // --- Release Status ---
+    let pulled_releases: Vec<(&str, &ReleaseStatus)> = found_releases
+        .iter()
+        .filter_map(|r| r.release_predicate.as_ref())
+        .filter_map(|r| r.status.as_ref().filter(|s| s.is_pulled()).map(|s| (r.purl.as_str(), s)))
+        .collect();
+    for (release_purl, status) in &pulled_releases {
+        output::eprint(0, Status::Warning, &format!("Release {} is {}", release_purl, status));
+    }
+
+    // --- Printing Filtered Data ---
+    if !found_releases.is_empty() {
+        println!();
+        output::print(0, Status::Info, &format!("Filtered releases for component '{}'", component_name));
+        // Decide what to print: just releases or the component + filtered releases
+        if all_releases || purl_version_opt.is_none() {
+            // If all releases or no specific version, maybe print the component context too
+            output::json(&FilteredComponent {
+                component: found_component_data, // Contains original URIs and errors
+                matching_releases: &found_releases,
+            })
+        } else {
+            // If specific version, just print those releases
+            output::json(&found_releases)
+        }
+        .context("Failed to serialize filtered results to JSON")?;
+
+        if fail_on_yanked && purl_version_opt.is_some() && !all_releases && !pulled_releases.is_empty() {
+            bail!("Queried version of '{}' has been pulled by its publisher", purl);
+        }
+
+        // TODO: Should I just have this handle fetching of any artifacts?
+        // --- Conditional SBOM Fetching ---
+        if let Some(media_type) = fetch_sbom_media_type {
+            println!();
+            output::print(0, Status::Info, &format!("Fetching artifacts with media type '{}'", media_type));
+            let mut fetch_futures = Vec::new();
+
+            for release_data in &found_releases {
+                // Platform groups were narrowed to --platform above; without it they are skipped.
+                let platform_artifacts = release_data
+                    .release_predicate
+                    .as_ref()
+                    .and_then(|r| r.artifact_groups.as_ref())
+                    .filter(|_| platform.is_some())
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|g| &g.artifacts);
+                for artifact_link in release_data.metadata_artifacts.iter().chain(platform_artifacts) {
+                    if artifact_link.media_type.as_deref() != Some(media_type.as_str()) {
+                        continue;
+                    }
+                    if artifact_link.is_gated() {
+                        output::print(1, Status::Info, &artifact_link.access_instructions());
+                    } else {
+                        output::print(1, Status::Info, &format!("Attempting to fetch: {}", artifact_link.uri));
+                        let fetchers = &fetchers;
+                        fetch_futures.push(async move {
+                            fetch_and_verify_artifact(artifact_link, fetchers).await
+                        });
+                    }
+                }
+            }
+
+            let fetch_results = futures::future::join_all(fetch_futures).await; // Execute fetches concurrently [10]
+
+            for result in fetch_results {
+                match result {
+                    Ok(bytes) => {
+                        // Attempt to print as UTF-8, fallback for binary
+                        match String::from_utf8(bytes.clone()) {
+                            Ok(s) => println!("Fetched Artifact Content:\n---\n{}\n---", s),
+                            Err(_) => println!(
+                                "Fetched Artifact Content: (Binary data, {} bytes)",
+                                bytes.len()
+                            ),
+                        }
+                    }
+                    Err(e) => {
+                        output::eprint(1, Status::Failed, &format!("Failed to fetch or verify artifact: {}", e));
+                        // Error context (URI) is lost here, ideally return (URI, Result) from future
+                    }
+                }
+            }
+        }
+    } else {
+        println!();
+        output::print(
+            0,
+            Status::Failed,
+            &format!("No matching component or release found for PURL '{}' in the traversed data.", purl),
+        );
+        // Optionally print component/release errors from aggregated_data for context
+        if let Some(comp_data) = found_component_data {
+            if !comp_data.release_errors.is_empty() {
+                output::eprint(
+                    0,
+                    Status::Failed,
+                    &format!("Errors encountered while processing releases for component '{}':", component_name),
+                );
+                for (uri, err) in &comp_data.release_errors {
+                    output::eprint(1, Status::Failed, &format!("{}: {}", uri, err));
+                }
+            }
+            for (uri, expires) in &comp_data.expired_releases {
+                output::eprint(1, Status::Warning, &format!("{}: expired {}", uri, expires));
+            }
+            for error in &comp_data.integrity_errors {
+                output::eprint(1, Status::Failed, &error.to_string());
+            }
+        } else {
+            if !aggregated_data.component_errors.is_empty() {
+                output::eprint(0, Status::Failed, "Errors encountered while processing components:");
+                for (uri, err) in &aggregated_data.component_errors {
+                    output::eprint(1, Status::Failed, &format!("{}: {}", uri, err));
+                }
+            }
+            for (uri, expires) in &aggregated_data.expired_components {
+                output::eprint(1, Status::Warning, &format!("{}: expired {}", uri, expires));
+            }
+            for error in &aggregated_data.integrity_errors {
+                output::eprint(1, Status::Failed, &error.to_string());
+            }
+        }
+    }
+
+    failures
+}
+
+async fn handle_commands_eol(
+    purl: String,
+    domain: Option<String>,
+    at: Option<String>,
+    fail_on_eol: bool,
+    mut traversal: TraversalArgs,
+) -> Result<()> {
+    let client = traversal.http_client()?;
+    output::print(0, Status::Info, &format!("Checking support lifecycle for PURL: {}", purl));
+    let at = match at {
+        Some(at) => parse_lifecycle_date(&at)?,
+        None => chrono::Utc::now(),
+    };
+    traversal.start_progress();
+    let fetchers = traversal.fetchers(&client);
+    let discovered = discover_purl_target(&purl, domain, &traversal, traversal.to_options(&client), &fetchers).await;
+    traversal.finish_progress();
+    traversal.export_trace(&client, &purl).await;
+    let (target, aggregated_data) = discovered?;
+    traversal.check_failures(&target.purl.domain, &aggregated_data).await?;
+    let version = target
+        .purl
+        .version
+        .as_deref()
+        .with_context(|| format!("PURL '{}' must include the pinned version", purl))?;
+
+    let component = aggregated_data
+        .components
+        .iter()
+        .filter_map(|c| c.component_predicate.as_ref())
+        .find(|c| target.is_match(c))
+        .ok_or_else(|| match &aggregated_data.root_error {
+            Some(root_error) => anyhow!("Root catalog failed to load: {}", root_error),
+            None => anyhow!("No component matching '{}' found", purl),
+        })?;
+    let answer = support_answer(&purl, version, component.support_streams.as_deref().unwrap_or_default(), at)?;
+
+    output::json(&answer).context("Failed to serialize support status to JSON")?;
+
+    if fail_on_eol && answer.status == SupportStatus::EndOfLife {
+        bail!("'{}' reached end of life on {}", purl, answer.eol.as_deref().unwrap_or("an unknown date"));
+    }
+    Ok(())
+}
+
+async fn handle_commands_purl_action(action: PurlAction) -> Result<()> {
+    match action {
+        PurlAction::Resolve {
+            purl,
+            domains,
+            mapping,
+            traversal,
+        } => {
+            let client = traversal.http_client()?;
+            let discovery = traversal.to_discovery(&client);
+            let options = ResolveOptions {
+                domains,
+                mapping: mapping.as_deref(),
+                deps_dev: traversal.deps_dev(&client),
+            };
+            let fetchers = traversal.fetchers(&client);
+            output::print(0, Status::Info, &format!("Resolving ecosystem PURL: {}", purl));
+            let resolved = resolve_ecosystem_purl(&purl, &options, discovery.as_ref(), &fetchers)
+                .await
+                .with_context(|| format!("Failed to resolve '{}'", purl))?;
+            if resolved.is_empty() {
+                bail!("No Chainsights component found for '{}'", purl);
+            }
+
+            output::json(&resolved).context("Failed to serialize results to JSON")?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_commands_init(
+    domain: Option<String>,
+    output_dir: &Path,
+    key: Option<KeySource>,
+    github_token: Option<String>,
+) -> Result<()> {
+    let forge = GithubForge::new(reqwest::Client::new(), DEFAULT_GITHUB_API_URL, github_token);
+    let mut prompter = Prompter::new(std::io::stdin().lock(), std::io::stdout());
+    let answers = gather(&mut prompter, domain, &forge).await?;
+
+    std::fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create directory '{}'", output_dir.display()))?;
+    let store = KeyStore::new(KeyStore::default_dir()?);
+    let default_key = output_dir.join("signing-key.pem");
+    let key = match key {
+        Some(key) => key,
+        None if default_key.exists() => KeySource::File(default_key),
+        None if prompter.confirm(&format!("No signing key at {}. Generate one", default_key.display()), true)? => {
+            write_private(&default_key, &Ed25519Key::generate().to_pem(None)?)?;
+            output::print(0, Status::Info, &format!("Generated a signing key at {}; keep it private and backed up", default_key.display()));
+            KeySource::File(default_key)
+        }
+        None => bail!("A signing key is needed; give one with --key"),
+    };
+    let signer = DidKeySigner::new(key.open(&store)?).await?;
+
+    let tree = write_init_tree(&answers, &signer, output_dir).await?;
+    println!("\nWrote {} draft statements under {}", tree.drafts.len(), output_dir.join("drafts").display());
+    println!("Signed {} statements as {}:", tree.signed.len(), signer.identity());
+    for path in &tree.signed {
+        println!("  - {}", path.display());
+    }
+    let record = ChainsightsRecord {
+        uri: tree.root_uri,
+        identity: signer.identity(),
+        key: None,
+    };
+    println!("\nHost the contents of {} at {}", output_dir.join("public").display(), answers.base_uri);
+    println!("(for example with `chainsights_client publish`), then publish the TXT record of _chainsights.{}:", answers.domain);
+    println!("  {}", record);
+    println!("`chainsights_client setup dns` can set it through your DNS provider.");
+    Ok(())
+}
+
+async fn handle_commands_generate(source: GenerateSource) -> Result<()> {
+    let client = reqwest::Client::new();
+    let (component, options) = match source {
+        GenerateSource::FromGithub {
+            repo,
+            token,
+            api_url,
+            max_releases,
+            draft,
+        } => {
+            let forge = GithubForge::new(client, &api_url, token);
+            (forge_draft(&forge, &repo, max_releases).await?, DraftOptions::from(draft))
+        }
+        GenerateSource::FromGitlab {
+            project,
+            token,
+            api_url,
+            max_releases,
+            draft,
+        } => {
+            let forge = GitlabForge::new(client, &api_url, token);
+            (forge_draft(&forge, &project, max_releases).await?, DraftOptions::from(draft))
+        }
+        GenerateSource::FromSbom {
+            sbom,
+            version,
+            draft,
+        } => {
+            let options = DraftOptions::from(draft);
+            let component = component_draft_from_sbom(&sbom, &options, version)
+                .with_context(|| format!("Failed to derive a component from SBOM '{}'", sbom.display()))?;
+            (component, options)
+        }
+    };
+
+    let written = write_component_drafts(&component, &options)?;
+    println!("\nWrote {} draft statements:", written.len());
+    for path in &written {
+        println!("  - {}", path.display());
+    }
+    println!("Review the drafts, then sign each one and host it as <name>.jsonl under {}", options.base_uri);
+
+    Ok(())
+}
+
+async fn forge_draft(forge: &dyn Forge, repo: &str, max_releases: usize) -> Result<ComponentDraft> {
+    forge
+        .component_draft(repo, max_releases)
+        .await
+        .with_context(|| format!("Failed to read repository '{}'", repo))
+}
+
+async fn handle_commands_publish(target: PublishTarget) -> Result<()> {
+    match target {
+        PublishTarget::Rekor {
+            bundle,
+            rekor_url,
+            output,
+        } => {
+            let bundle_line = read_bundle_file(&bundle)?;
+            let bundle_line = bundle_line.as_str();
+            let bundle_data: SigstoreBundleData =
+                serde_json::from_str(bundle_line).context("Failed to parse bundle JSON")?;
+            let mut bundle_json: serde_json::Value =
+                serde_json::from_str(bundle_line).context("Failed to parse bundle JSON")?;
+
+            eprintln!("Uploading {} to Rekor at {}", bundle.display(), rekor_url);
+            let rekor = RekorClient::new(reqwest::Client::new(), &rekor_url);
+            let entry = rekor.upload_bundle(&bundle_data).await?;
+            embed_tlog_entry(&mut bundle_json, &entry)?;
+
+            let updated = serde_json::to_string(&bundle_json)
+                .context("Failed to serialize updated bundle")?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, format!("{}\n", updated))
+                        .with_context(|| format!("Failed to write bundle '{}'", path.display()))?;
+                    eprintln!("Wrote bundle with tlog entry (log index {}) to {}", entry.log_index, path.display());
+                }
+                None => println!("{}", updated),
+            }
+        }
+        PublishTarget::Oci {
+            image,
+            bundle,
+            username,
+            password,
+        } => {
+            let bundle_line = read_bundle_file(&bundle)?;
+            let auth = match (username, password) {
+                (Some(username), Some(password)) => RegistryAuth::Basic(username, password),
+                (None, None) => RegistryAuth::Anonymous,
+                _ => return Err(anyhow!("Both --username and --password are required for registry authentication")),
+            };
+
+            println!("Attaching {} to image {}", bundle.display(), image);
+            let digest = attach_bundle(&image, &bundle_line, &auth).await?;
+            println!("Attached release attestation as referrer {}", digest);
+            println!("Link it from the component as: oci://{}", image);
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_commands_publish_tree(args: PublishTreeArgs) -> Result<()> {
+    let dir = args.dir.context("--dir is required")?;
+    let target = args.target.context("--target is required")?;
+    let options = TargetOptions::from(args.target_options);
+    let target = UploadTarget::parse(&target, &options)?;
+    let template = args.uri_template.unwrap_or_else(|| target.default_template());
+    let template = UriTemplate::parse(&template)?;
+
+    let tree = AttestationTree::read(&dir)?;
+    let plan = plan_publish(&tree, &template, args.root.as_deref())?;
+    if args.dry_run {
+        for (path, uri) in &plan.uris {
+            println!("{} -> {}", path, uri);
+        }
+    } else {
+        let uploader = target.uploader(reqwest::Client::new(), &options)?;
+        publish_tree(&tree, &plan, uploader.as_ref()).await?;
+        output::print(0, Status::Info, &format!("Published {} files", plan.uris.len()));
+    }
+
+    println!("Root catalog: {}", plan.root_uri);
+    let record = ChainsightsRecord {
+        uri: plan.root_uri,
+        identity: plan.root_identity.unwrap_or_else(|| "<signer identity>".to_string()),
+        key: None,
+    };
+    println!("Publish it in DNS as the TXT record of _chainsights.<your domain>:");
+    println!("  {}", record);
+    Ok(())
+}
+
+async fn handle_commands_ci(action: CiAction) -> Result<()> {
+    let CiAction::EmitRelease {
+        domain,
+        component,
+        version,
+        name,
+        artifacts,
+        sources,
+        artifact_base_uri,
+        lifecycle_phase,
+        key,
+        oidc_token,
+        fulcio_url,
+        rekor_url,
+        output_dir,
+        target,
+        uri_template,
+        target_options,
+    } = action;
+    let environment = BuildEnvironment::from_env();
+    let slug = match component.or_else(|| environment.repository_name().map(str::to_string)) {
+        Some(component) => slugify(&component),
+        None => bail!("No repository in the CI environment to name the component after; give it with --component"),
+    };
+    let version = version
+        .or_else(|| environment.ref_name.as_deref().map(tag_to_version))
+        .context("No tag in the CI environment to take the version from; give it with --version")?;
+    let artifact_base_uri = artifact_base_uri.or_else(|| environment.download_base.clone());
+    let mut links = match (&artifact_base_uri, artifacts.is_empty()) {
+        (_, true) => Vec::new(),
+        (Some(base_uri), false) => file_artifacts(&artifacts, base_uri)?,
+        (None, false) => bail!("No GitHub release to download the artifacts from; give their base URI with --artifact-base-uri"),
+    };
+    for source in &sources {
+        let artifacts = source
+            .artifacts(artifact_base_uri.as_deref())
+            .with_context(|| format!("Failed to read the build outputs of {}", source))?;
+        output::print(0, Status::Info, &format!("Read {} artifacts from {}", artifacts.len(), source));
+        links.extend(artifacts);
+    }
+    let name = name.or_else(|| environment.ref_name.clone()).unwrap_or_else(|| version.clone());
+    let release = release_draft(&version, &name, links, environment.release_page.clone(), lifecycle_phase);
+    let signer: Box<dyn StatementSigner> = match key {
+        Some(key) => Box::new(DidKeySigner::new(key.open(&KeyStore::new(KeyStore::default_dir()?))?).await?),
+        None => {
+            let client = reqwest::Client::new();
+            let token = match oidc_token {
+                Some(token) => token,
+                None => ambient_identity_token(&client).await?,
+            };
+            let rekor = RekorClient::new(client.clone(), &rekor_url);
+            Box::new(KeylessSigner::new(&FulcioClient::new(client, &fulcio_url), &token, Some(rekor)).await?)
+        }
+    };
+    let upload = target.map(|target| (target, uri_template, TargetOptions::from(target_options)));
+    emit_release(&domain, &slug, &release, signer.as_ref(), &output_dir, upload).await
+}
+
+/// Writes, signs and optionally uploads the release statement, and reports it for linking.
+async fn emit_release(
+    domain: &str,
+    slug: &str,
+    release: &ReleaseDraft,
+    signer: &dyn StatementSigner,
+    output_dir: &Path,
+    upload: Option<(String, Option<String>, TargetOptions)>,
+) -> Result<()> {
+    let path = format!("components/{}/{}.jsonl", slug, release.version);
+    let signed = output_dir.join(&path);
+    let draft = signed.with_extension("json");
+    if let Some(parent) = draft.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("Failed to create directory '{}'", parent.display()))?;
+    }
+    let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    write_release_draft(release, domain, slug, &timestamp, &draft)?;
+    let digest = sign_draft(signer, &draft, &signed).await?;
+    output::print(0, Status::Info, &format!("Signed {} as {}", signed.display(), signer.identity()));
+
+    let uri = match upload {
+        Some((target, template, options)) => {
+            let target = UploadTarget::parse(&target, &options)?;
+            let template = UriTemplate::parse(&template.unwrap_or_else(|| target.default_template()))?;
+            let document = std::fs::read(&signed).with_context(|| format!("Failed to read '{}'", signed.display()))?;
+            target
+                .uploader(reqwest::Client::new(), &options)?
+                .upload(&path, &document)
+                .await
+                .with_context(|| format!("Failed to upload '{}'", path))?;
+            let uri = template.expand(&path);
+            output::print(0, Status::Info, &format!("Published it at {}", uri));
+            Some(uri)
+        }
+        None => None,
+    };
+
+    let digest = format!("sha256:{}", digest);
+    let outputs = [("uri", uri.clone().unwrap_or_default()), ("digest", digest.clone()), ("identity", signer.identity())];
+    if let Ok(github_output) = std::env::var("GITHUB_OUTPUT") {
+        let lines: String = outputs.iter().map(|(name, value)| format!("{}={}\n", name, value)).collect();
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&github_output)
+            .with_context(|| format!("Failed to open GITHUB_OUTPUT '{}'", github_output))?;
+        std::io::Write::write_all(&mut file, lines.as_bytes()).context("Failed to write step outputs")?;
+    }
+    println!("Link the release from the component statement with:");
+    println!(
+        "  chainsights_client component add-release --component <component draft> --uri {} --identity {} --digest {}",
+        uri.as_deref().unwrap_or("<URI it is hosted at>"),
+        signer.identity(),
+        digest
+    );
+    Ok(())
+}
+
+async fn handle_commands_attest_baseline(
+    component_path: &Path,
+    key: &KeySource,
+    output: &Path,
+    draft_path: &Path,
+    uri: Option<String>,
+    verifier: Option<String>,
+    controls: Option<&Path>,
+) -> Result<()> {
+    let mut component = Draft::read_component(component_path, None)?;
+    let uri = match uri {
+        Some(uri) => uri,
+        None => component
+            .predicate
+            .release_attestations
+            .first()
+            .and_then(|release| release.uri.rsplit_once('/'))
+            .map(|(dir, _)| format!("{}/baseline.jsonl", dir))
+            .context("The component links no releases to host the attestation next to; give its URI with --uri")?,
+    };
+    let signer = DidKeySigner::new(key.open(&KeyStore::new(KeyStore::default_dir()?))?).await?;
+    let previous = draft_path.exists().then(|| read_previous(draft_path)).transpose()?;
+    let specs = control_specs(controls)?;
+
+    let mut prompter = Prompter::new(std::io::stdin().lock(), std::io::stdout());
+    let assessed = assess(&mut prompter, &specs, previous.as_ref())?;
+    let verifier = match verifier {
+        Some(verifier) => verifier,
+        None => prompter.ask("\nWho assessed the controls", previous.as_ref().map(|previous| previous.verifier.id.as_str()))?,
+    };
+    let assessment_comment = match previous.as_ref().map(|previous| previous.assessment_comment.as_str()).filter(|c| !c.is_empty()) {
+        Some(comment) => prompter.ask("Assessment comment", Some(comment))?,
+        None => prompter.ask_optional("Assessment comment")?.unwrap_or_default(),
+    };
+
+    let implemented = assessed.iter().filter(|control| control.implemented).count();
+    let total = assessed.len();
+    write_baseline_draft(
+        draft_path,
+        &component.subject_uri,
+        BaselineVerifier { id: verifier, comment: None },
+        assessment_comment,
+        assessed,
+    )?;
+    let digest = sign_draft(&signer, draft_path, output).await?;
+    link_metadata(&mut component.predicate, ArtifactLink {
+        uri: uri.clone(),
+        digest: Some(HashMap::from([("sha256".to_string(), digest)])),
+        media_type: Some("application/vnd.in-toto+json".to_string()),
+        expected_signer_identity: Some(signer.identity()),
+        access: None,
+        request_url: None,
+    });
+    component.write(component_path)?;
+
+    output::print(0, Status::Info, &format!("{} of {} controls implemented", implemented, total));
+    output::print(0, Status::Info, &format!("Wrote {} (draft {}), to be hosted at {}", output.display(), draft_path.display(), uri));
+    output::print(0, Status::Info, &format!("Linked it from {}; sign it and publish it in place of the previous statement", component_path.display()));
+    Ok(())
+}
+
+fn handle_commands_key(key_dir: Option<PathBuf>, action: KeyAction) -> Result<()> {
+    let store = KeyStore::new(match key_dir {
+        Some(dir) => dir,
+        None => KeyStore::default_dir()?,
+    });
+    match action {
+        KeyAction::Generate { name, no_passphrase } => {
+            let passphrase = match no_passphrase {
+                true => None,
+                false => Some(passphrase(&format!("Passphrase to encrypt '{}' with: ", name), true)?),
+            };
+            let key = store.generate(&name, passphrase.as_deref())?;
+            output::print(0, Status::Info, &format!("Generated store:{}", key.name));
+            println!("{}", key.did);
+        }
+        KeyAction::List => {
+            for key in store.list()? {
+                let encrypted = if key.encrypted { "encrypted" } else { "unencrypted" };
+                println!("{}\t{}\t{}", key.name, encrypted, key.did);
+            }
+        }
+        KeyAction::Export { name, private, output } => {
+            let pem = match private {
+                true => store.private_key_pem(&name)?,
+                false => store.public_key_pem(&name)?,
+            };
+            match (output, private) {
+                (Some(path), true) => write_private(&path, &pem)?,
+                (Some(path), false) => std::fs::write(&path, pem).with_context(|| format!("Failed to write '{}'", path.display()))?,
+                (None, _) => print!("{}", pem),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_commands_catalog(action: CatalogAction) -> Result<()> {
+    let (args, summary) = match action {
+        CatalogAction::Freshness { signed, output, valid_for } => {
+            write_freshness_draft(&signed, chrono::Duration::hours(valid_for), &output)?;
+            output::print(0, Status::Info, &format!("Confirmed {} as current for {} hour(s)", signed.display(), valid_for));
+            output::print(0, Status::Info, &format!("Wrote {}; sign it and publish it at the catalog's freshnessLink", output.display()));
+            return Ok(());
+        }
+        CatalogAction::Merge { catalogs, output, subject, sub_catalogs_base_uri, identity } => {
+            let mut subject_uri = subject;
+            let mut teams = Vec::new();
+            for path in &catalogs {
+                let draft = Draft::read_catalog(path, subject_uri.as_deref())?;
+                let name = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .map(slugify)
+                    .with_context(|| format!("Cannot name the catalog '{}' after its file", path.display()))?;
+                subject_uri.get_or_insert(draft.subject_uri);
+                teams.push((name, draft.predicate));
+            }
+            let mode = match sub_catalogs_base_uri {
+                Some(base_uri) => MergeMode::SubCatalogs { base_uri, identity },
+                None => MergeMode::Components,
+            };
+            let mut merged = Draft {
+                subject_uri: subject_uri.context("No catalogs to merge")?,
+                predicate: merge_catalogs(teams, mode)?,
+            };
+            merged.write(&output)?;
+            output::print(
+                0,
+                Status::Info,
+                &format!(
+                    "Merged {} catalogs: {} component(s), {} sub-catalog(s)",
+                    catalogs.len(),
+                    merged.predicate.components.len(),
+                    merged.predicate.sub_catalogs.as_ref().map_or(0, Vec::len)
+                ),
+            );
+            output::print(0, Status::Info, &format!("Wrote {}; sign it and publish it as the root catalog", output.display()));
+            return Ok(());
+        }
+        CatalogAction::AddComponent { catalog: args, uri, from, name, purl, description, label, link } => {
+            let mut draft = Draft::read_catalog(&args.catalog, args.draft.subject.as_deref())?;
+            let component = from
+                .map(|path| Draft::read_component(&path, None))
+                .transpose()?
+                .map(|draft| draft.predicate);
+            let identity = match link.identity {
+                Some(identity) => identity,
+                None => common_identity(draft.predicate.components.iter().map(|entry| &entry.component_attestation_link))
+                    .context("The catalog's components expect different identities; give the new one's with --identity")?,
+            };
+            let mut labels = component.as_ref().and_then(|component| component.labels.clone()).unwrap_or_default();
+            labels.extend(label);
+            let entry = CatalogComponentEntry {
+                name: name
+                    .or_else(|| component.as_ref().map(|component| component.name.clone()))
+                    .context("--name is required")?,
+                description: description.or_else(|| component.as_ref().and_then(|component| component.description.clone())),
+                component_purl: purl
+                    .or_else(|| component.as_ref().map(|component| component.purl.clone()))
+                    .context("--purl is required")?,
+                component_attestation_link: attestation_link(&uri, identity, link.digest.as_deref())?,
+                labels: (!labels.is_empty()).then_some(labels),
+            };
+            let summary = format!("Added {} to the catalog", entry.component_purl);
+            add_component(&mut draft.predicate, entry)?;
+            draft.write(args.draft.output.as_deref().unwrap_or(&args.catalog))?;
+            (args, summary)
+        }
+        CatalogAction::RemoveComponent { catalog: args, component } => {
+            let mut draft = Draft::read_catalog(&args.catalog, args.draft.subject.as_deref())?;
+            let removed = remove_component(&mut draft.predicate, &component)?;
+            draft.write(args.draft.output.as_deref().unwrap_or(&args.catalog))?;
+            (args, format!("Removed {} from the catalog", removed.component_purl))
+        }
+    };
+    print_edited(&summary, args.draft.output.as_deref().unwrap_or(&args.catalog));
+    Ok(())
+}
+
+fn handle_commands_component(action: ComponentAction) -> Result<()> {
+    let (args, summary) = match action {
+        ComponentAction::AddRelease { component: args, uri, link } => {
+            let mut draft = Draft::read_component(&args.component, args.draft.subject.as_deref())?;
+            let identity = match link.identity {
+                Some(identity) => identity,
+                None => common_identity(&draft.predicate.release_attestations)
+                    .context("The component's releases expect different identities; give the new one's with --identity")?,
+            };
+            add_release(&mut draft.predicate, attestation_link(&uri, identity, link.digest.as_deref())?)?;
+            draft.write(args.draft.output.as_deref().unwrap_or(&args.component))?;
+            (args, format!("Added release {} to {}", uri, draft.predicate.purl))
+        }
+        ComponentAction::RemoveRelease { component: args, uri } => {
+            let mut draft = Draft::read_component(&args.component, args.draft.subject.as_deref())?;
+            remove_release(&mut draft.predicate, &uri)?;
+            draft.write(args.draft.output.as_deref().unwrap_or(&args.component))?;
+            (args, format!("Removed release {} from {}", uri, draft.predicate.purl))
+        }
+    };
+    print_edited(&summary, args.draft.output.as_deref().unwrap_or(&args.component));
+    Ok(())
+}
+
+fn print_edited(summary: &str, path: &Path) {
+    output::print(0, Status::Info, summary);
+    output::print(0, Status::Info, &format!("Wrote {}; sign it and publish it in place of the previous statement", path.display()));
+}
+
+fn handle_commands_relink(dir: &Path, mappings: &[UriMapping], output_dir: Option<&Path>) -> Result<()> {
+    let tree = AttestationTree::read(dir)?;
+    let relinked = relink_tree(&tree, mappings)?;
+    for (path, outcome) in &relinked.outcomes {
+        match outcome {
+            Outcome::Resign(signing) => {
+                output::print(0, Status::Warning, &format!("{}: rewritten, must be signed again ({})", path, signing))
+            }
+            Outcome::Rewritten => output::print(0, Status::Info, &format!("{}: rewritten", path)),
+            Outcome::StaleSignature => output::print(0, Status::Warning, &format!("{}: stale signature, left out", path)),
+        }
+    }
+    for (path, algorithm) in &relinked.dropped_digests {
+        output::print(0, Status::Warning, &format!("{}: dropped '{}' digests of rewritten statements, which can't be recomputed", path, algorithm));
+    }
+    let resign = relinked.outcomes.values().filter(|outcome| matches!(outcome, Outcome::Resign(_))).count();
+    output::print(0, Status::Info, &format!(
+        "{} statements rewritten, {} of which must be signed again",
+        relinked.outcomes.values().filter(|outcome| **outcome != Outcome::StaleSignature).count(),
+        resign
+    ));
+
+    if let Some(output_dir) = output_dir {
+        if output_dir.exists()
+            && std::fs::canonicalize(output_dir).ok() == std::fs::canonicalize(dir).ok()
+        {
+            bail!("--output-dir must not be the tree being relinked");
+        }
+        write_tree(&relinked.documents, output_dir)?;
+        output::print(0, Status::Info, &format!("Wrote the relinked tree to {}", output_dir.display()));
+    }
+    Ok(())
+}
+
+async fn handle_commands_setup_dns(args: SetupDnsArgs) -> Result<()> {
+    let record = match (args.uri, args.identity) {
+        (Some(uri), Some(identity)) => Some(ChainsightsRecord { uri, identity, key: args.key }),
+        _ => None,
+    };
+    let records = planned_records(&args.domain, record.as_ref(), &args.challenge)?;
+    if args.dry_run {
+        for record in &records {
+            println!("{} {} IN TXT {}", record.name, args.ttl, presentation(&record.value));
+        }
+        return Ok(());
+    }
+
+    let client = reqwest::Client::new();
+    let provider: Box<dyn DnsProvider> = match args.provider {
+        DnsProviderKind::Route53 => Box::new(Route53Provider::new(
+            client,
+            DEFAULT_ROUTE53_API_URL,
+            AwsCredentials::from_env()?,
+            &args.zone.context("Route53 needs the hosted zone ID (--zone)")?,
+        )),
+        DnsProviderKind::Cloudflare => Box::new(CloudflareProvider::new(
+            client,
+            DEFAULT_CLOUDFLARE_API_URL,
+            args.cloudflare_token.context("Cloudflare needs an API token (--cloudflare-token or CLOUDFLARE_API_TOKEN)")?,
+            args.zone,
+        )),
+        DnsProviderKind::GoogleCloudDns => Box::new(CloudDnsProvider::new(
+            client,
+            DEFAULT_CLOUD_DNS_API_URL,
+            args.gcp_access_token
+                .context("Cloud DNS needs an access token (--gcp-access-token or GOOGLE_OAUTH_ACCESS_TOKEN)")?,
+            &args.gcp_project.context("Cloud DNS needs the project (--gcp-project or GOOGLE_CLOUD_PROJECT)")?,
+            &args.zone.context("Cloud DNS needs the managed zone name (--zone)")?,
+        )),
+        DnsProviderKind::Rfc2136 => {
+            let key = match (args.tsig_key_name, args.tsig_secret) {
+                (Some(name), Some(secret)) => Some(TsigKey {
+                    name,
+                    secret: STANDARD.decode(secret.trim()).context("The TSIG secret is not base64")?,
+                }),
+                (None, None) => None,
+                _ => return Err(anyhow!("Both --tsig-key-name and --tsig-secret are required for TSIG")),
+            };
+            Box::new(Rfc2136Provider::new(
+                &args.server.context("RFC 2136 needs the primary server (--server)")?,
+                args.zone.as_deref().unwrap_or(&args.domain),
+                key,
+            ))
+        }
+    };
+    apply_records(provider.as_ref(), &records, args.ttl).await?;
+    output::print(0, Status::Info, &format!(
+        "Records set; once they propagate, check the domain with: chainsights_client verify-domain --domain {}",
+        args.domain
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "admission")]
+async fn handle_commands_serve_admission(
+    listen: SocketAddr,
+    domains: Vec<String>,
+    tls: Option<(&Path, &Path)>,
+    refresh_interval: std::time::Duration,
+    max_staleness: std::time::Duration,
+    traversal: TraversalArgs,
+) -> Result<()> {
+    let client = traversal.http_client()?;
+    let verifier = AdmissionVerifier::with_discovery(traversal.to_discovery(&client), traversal.fetchers(&client), domains)
+        .refresh_interval(refresh_interval)
+        .max_staleness(max_staleness);
+    let verifier = Arc::new(verifier);
+    let images = verifier.warm().await?;
+    output::print(0, Status::Verified, &format!("Indexed {} attested image digest(s)", images));
+    let refresher = verifier.clone();
+    tokio::spawn(async move { refresher.keep_warm().await });
+    webhook::serve(listen, verifier, tls).await
+}
+
+#[cfg(feature = "tui")]
+async fn handle_commands_tui(domain: String, mut traversal: TraversalArgs) -> Result<()> {
+    if !std::io::IsTerminal::is_terminal(&std::io::stdout()) {
+        bail!("The tui command needs a terminal; use the domain command for JSON output");
+    }
+    let client = traversal.http_client()?;
+    output::print(0, Status::Info, &format!("Querying domain: {}", domain));
+    traversal.start_progress();
+    let discovery = traversal.to_discovery(&client);
+    let fetchers = traversal.fetchers(&client);
+    let discovered = discovery.discover(&domain, &fetchers).await;
+    traversal.finish_progress();
+    traversal.export_trace(&client, &domain).await;
+    let aggregated_data = discovered?;
+    traversal.check_serial(&domain, &aggregated_data).await?;
+    traversal.report_unverified(&aggregated_data);
+    tui::browse(&domain, &aggregated_data, &fetchers).await
+}
+
+async fn handle_commands_verify_domain(domain: String, mut traversal: TraversalArgs) -> Result<()> {
+    let client = traversal.http_client()?;
+    output::print(0, Status::Info, &format!("Checking consistency of domain: {}", domain));
+    let ChainsightsRecord { uri: root_uri, identity: root_identity, key } = fetch_chainsights_info(&SystemResolver, &domain)
+        .await
+        .with_context(|| format!("Failed to fetch root info for domain '{}'", domain))?;
+    traversal.start_progress();
+    let options = traversal.to_options(&client).scoped_to(&domain).pinned_to(key);
+    let fetchers = traversal.fetchers(&client);
+    let traversed = traverse_and_aggregate(&root_uri, &[SignerIdentity::new(&root_identity)], &fetchers, &options).await;
+    traversal.finish_progress();
+    traversal.export_trace(&client, &domain).await;
+    let data = traversed.with_context(|| format!("Traversal failed starting from {}", root_uri))?;
+    if let Some(e) = &data.root_error {
+        bail!("Root catalog at {} could not be verified: {}", root_uri, e);
+    }
+
+    let mut problems = 0;
+    for check in check_domain_consistency(&domain, &root_identity, &data) {
+        if check.problems.is_empty() {
+            output::print(0, Status::Verified, check.name);
+            continue;
+        }
+        output::print(0, Status::Failed, check.name);
+        for problem in &check.problems {
+            output::print(1, Status::Failed, problem);
+        }
+        problems += check.problems.len();
+    }
+
+    let unverified = data.component_errors.len()
+        + data.components.iter().map(|c| c.release_errors.len()).sum::<usize>();
+    if unverified > 0 {
+        output::print(0, Status::Warning, &format!(
+            "{} statement(s) failed to verify and were not checked",
+            unverified
+        ));
+    }
+    if problems > 0 {
+        bail!("Found {} consistency problem(s) in domain '{}'", problems, domain);
+    }
+    traversal.check_failures(&domain, &data).await
+}
+
+async fn handle_commands_selftest() -> Result<()> {
+    output::print(0, Status::Info, "Running self-test against a local fixture tree (no network access)");
+    let options = TraversalOptions {
+        clock_skew: chrono::Duration::seconds(300),
+        ..TraversalOptions::default()
+    };
+    let report = run_selftest(options).await;
+    let failed = report.failures();
+    if failed > 0 {
+        bail!("Self-test failed: {} of {} checks failed", failed, report.checks.len());
+    }
+    output::print(0, Status::Verified, &format!("Self-test passed: {} checks", report.checks.len()));
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_commands_verify_bundle(
+    file: &Path,
+    identities: &[String],
+    issuer: Option<&str>,
+    build_policy: &BuildPolicy,
+    trusted_root: Option<&TrustedRoot>,
+    plugins: &PluginArgs,
+    time_source: TimeSource,
+    clock_skew: i64,
+) -> Result<()> {
+    let bundle = read_bundle_file(file)?;
+    let identities: Vec<SignerIdentity> = identities.iter().map(|i| SignerIdentity::new(i)).collect();
+    let ParsedAttestation { verified, predicate, payload_sha256 } = verify_and_parse(
+        &file.display().to_string(),
+        &bundle,
+        &identities,
+        time_source,
+        chrono::Duration::seconds(clock_skew),
+        trusted_root,
+        plugins.parser().as_deref(),
+    )?;
+
+    output::print(0, Status::Verified, &format!("Signature verified, signed by {}", verified.signer_identity));
+    if trusted_root.is_some() {
+        output::print(0, Status::Verified, "Certificate and Rekor entries verified against the trusted root");
+    }
+    match (issuer, verified.extensions.issuer.as_deref()) {
+        (Some(expected), Some(actual)) if expected == actual => {
+            output::print(0, Status::Verified, &format!("Certificate issued by {}", actual));
+        }
+        (Some(expected), actual) => bail!(
+            "Certificate issuer '{}' does not match the expected issuer '{}'",
+            actual.unwrap_or("none"),
+            expected
+        ),
+        (None, Some(actual)) => output::print(0, Status::Info, &format!("Certificate issued by {}", actual)),
+        (None, None) => {}
+    }
+    print_build_context(&verified.extensions, "");
+    if !build_policy.is_empty() {
+        build_policy.check(&verified.extensions)?;
+        output::print(0, Status::Verified, "Build context satisfies the build policy");
+    }
+    if let Some(signed_at) = verified.trusted_time {
+        output::print(0, Status::Info, &format!("Signed at {} ({:?} time source)", signed_at.to_rfc3339(), time_source));
+    }
+    match &predicate {
+        ChainsightsPredicate::Custom(custom) => {
+            output::print(0, Status::Verified, &format!("Parsed {} predicate with plugin '{}'", custom.kind, custom.plugin));
+            for detail in &custom.details {
+                output::print(1, Status::Info, detail);
+            }
+        }
+        ChainsightsPredicate::Unknown { predicate_type, .. } => output::print(
+            0,
+            Status::Warning,
+            &format!("Predicate type '{}' is not a Chainsights predicate", predicate_type),
+        ),
+        known => output::print(0, Status::Verified, &format!("Parsed {} predicate", known.kind())),
+    }
+    output::print(0, Status::Info, &format!("Payload sha256:{}", payload_sha256));
+
+    let statement: serde_json::Value =
+        serde_json::from_slice(&verified.payload).context("Failed to decode the statement")?;
+    println!();
+    output::json(&statement)
+}
+
+async fn handle_commands_monitor(action: MonitorAction) -> Result<()> {
+    let MonitorAction::Rekor { domain, identities, since, state, interval, once, metrics_addr, mut traversal } = action;
+    let client = traversal.http_client()?;
+    traversal.start_trace();
+    let discovery = traversal.to_discovery(&client);
+    let fetchers = traversal.fetchers(&client);
+    let rekor = RekorClient::new(client.clone(), &traversal.rekor_url);
+    let mut monitor = RekorMonitor::new(rekor, identities, since, state)?;
+    let hooks = traversal.exec_hooks();
+    let metrics = Arc::new(Metrics::default());
+    metrics.monitor(&domain);
+    if let Some(addr) = metrics_addr {
+        metrics::serve(addr, metrics.clone()).await?;
+    }
+    output::print(0, Status::Info, &format!("Watching {} for entries that {} does not publish", traversal.rekor_url, domain));
+    loop {
+        let check = async {
+            let started = Instant::now();
+            let data = discovery.discover(&domain, &fetchers).await.inspect_err(|_| metrics.record_fetch_error(&domain))?;
+            metrics.record_traversal(&domain, started.elapsed(), &data);
+            hooks.root_changes(&domain, &data).await;
+            if let Some(error) = &data.root_error {
+                bail!("The root catalog of '{}' could not be verified: {}", domain, error);
+            }
+            monitor
+                .unpublished_entries(&published_digests(&data)?)
+                .await
+                .inspect_err(|_| metrics.record_fetch_error(&domain))
+        };
+        let checked = check.await;
+        traversal.export_trace(&client, &domain).await;
+        match checked {
+            Ok(unpublished) => {
+                match unpublished.is_empty() {
+                    true => metrics.record_success(&domain),
+                    false => metrics.record_failures(&domain, FailureKind::UnpublishedRekorEntry, unpublished.len()),
+                }
+                for entry in &unpublished {
+                    let time = entry.integrated_time.map_or("unknown time".to_string(), |time| time.to_rfc3339_opts(SecondsFormat::Secs, true));
+                    output::print(
+                        0,
+                        Status::Failed,
+                        &format!(
+                            "Rekor entry {} (log index {}, {}) signed by {} is not published by {}",
+                            entry.uuid,
+                            entry.log_index.map_or("unknown".to_string(), |index| index.to_string()),
+                            time,
+                            entry.signer_identities.join(", "),
+                            domain
+                        ),
+                    );
+                    let predicate = entry.predicate_type.as_deref().map(|t| format!(", predicate {}", t)).unwrap_or_default();
+                    let sha256 = entry.sha256.as_deref().unwrap_or("unknown");
+                    output::print(1, Status::Info, &format!("{} entry, sha256 {}{}", entry.kind, sha256, predicate));
+                    let details = serde_json::json!({
+                        "uuid": entry.uuid,
+                        "log_index": entry.log_index,
+                        "integrated_time": entry.integrated_time.map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true)),
+                        "kind": entry.kind,
+                        "sha256": entry.sha256,
+                        "predicate_type": entry.predicate_type,
+                        "signer_identities": entry.signer_identities,
+                    });
+                    let summary = format!("Rekor entry {} signed by {} is not published by {}", entry.uuid, entry.signer_identities.join(", "), domain);
+                    hooks.changed(&domain, "unpublished-rekor-entry", &summary, details).await;
+                }
+                if once {
+                    if !unpublished.is_empty() {
+                        bail!("{} Rekor entry(ies) signed by the publisher not published in its catalog", unpublished.len());
+                    }
+                    output::print(0, Status::Verified, "Every new Rekor entry is published in the catalog");
+                    return Ok(());
+                }
+            }
+            Err(e) if once => return Err(e),
+            Err(e) => output::print(0, Status::Warning, &format!("Check failed, retrying in {}s: {:#}", interval, e)),
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+    }
+}
+
+/// Runs the traversals of `serve-grpc` with the options it was started with.
+#[cfg(feature = "grpc")]
+struct TraversalResolver {
+    traversal: TraversalArgs,
+    client: reqwest::Client,
+    fetchers: Fetchers,
+}
+
+#[cfg(feature = "grpc")]
+impl grpc::Resolver for TraversalResolver {
+    fn resolve_domain<'a>(&'a self, domain: &'a str, hooks: Arc<dyn TraversalHooks>) -> BoxFuture<'a, Result<AggregatedCatalogData>> {
+        Box::pin(async move {
+            let options = TraversalOptions { hooks: Some(hooks), ..self.traversal.to_options(&self.client) };
+            let data = self.traversal.discovery_with(options).discover(domain, &self.fetchers).await?;
+            self.traversal.check_serial(domain, &data).await?;
+            Ok(data)
+        })
+    }
+
+    fn resolve_purl<'a>(
+        &'a self,
+        purl: &'a str,
+        domain: Option<String>,
+        hooks: Arc<dyn TraversalHooks>,
+    ) -> BoxFuture<'a, Result<AggregatedCatalogData>> {
+        Box::pin(async move {
+            let options = TraversalOptions { hooks: Some(hooks), ..self.traversal.to_options(&self.client) };
+            let (_, data) = discover_purl_target(purl, domain, &self.traversal, options, &self.fetchers).await?;
+            Ok(data)
+        })
+    }
+}
+
+async fn handle_commands_trust(action: TrustAction) -> Result<()> {
+    match action {
+        TrustAction::Export {
+            source,
+            expires_in_days,
+            output,
+        } => {
+            let text = if Path::new(&source).exists() {
+                std::fs::read_to_string(&source).with_context(|| format!("Failed to read '{}'", source))?
+            } else {
+                fetch_manifest_text(&Fetchers::new(reqwest::Client::new()), &source)
+                    .await
+                    .with_context(|| format!("Failed to fetch trusted root from '{}'", source))?
+            };
+            let trusted_root = TrustedRoot::from_json(&text)?;
+            let expires = chrono::Utc::now() + chrono::Duration::days(expires_in_days);
+            let statement = snapshot_statement(trusted_root, &source, Some(expires))?;
+            let json = serde_json::to_string_pretty(&statement)?;
+            match &output {
+                Some(path) => {
+                    std::fs::write(path, json).with_context(|| format!("Failed to write '{}'", path.display()))?;
+                    output::eprint(0, Status::Info, &format!("Wrote unsigned trust snapshot to {}", path.display()));
+                }
+                None => println!("{}", json),
+            }
+            output::eprint(0, Status::Info, &format!(
+                "Sign the snapshot, then use the bundle with --trust-bundle or `trust import` until {}",
+                expires.to_rfc3339()
+            ));
+        }
+        TrustAction::Import {
+            bundle,
+            identity,
+            trusted_root,
+            key,
+            output,
+        } => {
+            let snapshot = read_trust_snapshot(&bundle, &key, &identity, trusted_root.as_deref())?;
+            output::print(0, Status::Verified, &format!("Trust snapshot of {} taken at {}", snapshot.source, snapshot.timestamp));
+            if let Some(expires) = &snapshot.expires {
+                output::print(0, Status::Info, &format!("Expires at {}", expires));
+            }
+            let root = &snapshot.trusted_root;
+            output::print(0, Status::Info, &format!(
+                "{} certificate authorities, {} Rekor logs, {} CT logs",
+                root.certificate_authorities.len(),
+                root.tlogs.len(),
+                root.ctlogs.len()
+            ));
+            std::fs::write(&output, serde_json::to_string_pretty(root)?)
+                .with_context(|| format!("Failed to write '{}'", output.display()))?;
+            output::print(0, Status::Info, &format!("Wrote {}, use it with --trusted-root", output.display()));
+        }
+    }
+    Ok(())
+}
+
+async fn handle_commands_policy(action: PolicyAction) -> Result<()> {
+    match action {
+        PolicyAction::Export {
+            document,
+            expires_in_days,
+            output,
+        } => {
+            let text = std::fs::read_to_string(&document).with_context(|| format!("Failed to read '{}'", document.display()))?;
+            let expires = chrono::Utc::now() + chrono::Duration::days(expires_in_days);
+            let statement = policy_statement(&text, Some(expires))
+                .with_context(|| format!("Failed to read policy document '{}'", document.display()))?;
+            let json = serde_json::to_string_pretty(&statement)?;
+            match &output {
+                Some(path) => {
+                    std::fs::write(path, json).with_context(|| format!("Failed to write '{}'", path.display()))?;
+                    output::eprint(0, Status::Info, &format!("Wrote unsigned policy statement to {}", path.display()));
+                }
+                None => println!("{}", json),
+            }
+            output::eprint(0, Status::Info, &format!(
+                "Sign the statement, then serve the bundle for --policy-bundle until {}",
+                expires.to_rfc3339()
+            ));
+        }
+        PolicyAction::Show { bundle, identity, trusted_root, key } => {
+            let policy = read_policy(&bundle, &key, &identity, trusted_root.as_deref()).await?;
+            print_policy(&policy);
+        }
+    }
+    Ok(())
+}
+
+fn print_policy(policy: &ChainsightsPolicyPredicate) {
+    output::print(0, Status::Verified, &format!("Policy '{}' issued at {}", policy.name, policy.timestamp));
+    if let Some(expires) = &policy.expires {
+        output::print(0, Status::Info, &format!("Expires at {}", expires));
+    }
+    for (domain, identities) in &policy.root_identities {
+        output::print(1, Status::Info, &format!("Root of {} signed by: {}", domain, identities.join(", ")));
+    }
+    if !policy.required_artifacts.is_empty() {
+        let required: Vec<String> = policy.required_artifacts.iter().map(ToString::to_string).collect();
+        output::print(1, Status::Info, &format!("Every verified release requires: {}", required.join(", ")));
+    }
+    if policy.require_all_releases {
+        output::print(1, Status::Info, "The root catalog and every component and release must verify");
+    }
+    if let Some(rate) = policy.max_error_rate {
+        output::print(1, Status::Info, &format!("At most {:.1}% of statements may fail to verify", rate * 100.0));
+    }
+    if let Some(build) = &policy.release_build_policy {
+        let fields = [("repository", &build.source_repository), ("ref", &build.source_ref), ("trigger", &build.build_trigger)];
+        let constraints: Vec<String> =
+            fields.iter().filter_map(|(name, value)| value.as_ref().map(|value| format!("{} {}", name, value))).collect();
+        output::print(1, Status::Info, &format!("Releases must be signed from a CI build with {}", constraints.join(", ")));
+    }
+    if let Some(root) = &policy.trusted_root {
+        output::print(1, Status::Info, &format!(
+            "Trusted root: {} certificate authorities, {} Rekor logs, {} CT logs",
+            root.certificate_authorities.len(),
+            root.tlogs.len(),
+            root.ctlogs.len()
+        ));
+    }
+}
+
+/// Opens the signed policy at `source`, a path or URL: a statement signed by one of the pinned
+/// `keys`, with its detached signature next to it, or else a bundle signed by one of `identities`
+/// whose certificate chains to `trusted_root`, if there is one.
+async fn read_policy(
+    source: &str,
+    keys: &[String],
+    identities: &[String],
+    trusted_root: Option<&TrustedRoot>,
+) -> Result<ChainsightsPolicyPredicate> {
+    let fetchers = Fetchers::new(reqwest::Client::new());
+    if !keys.is_empty() {
+        let verifier = key_verifier(keys)?;
+        let document = read_policy_document(&fetchers, source).await?;
+        let signature = match verifier.signature_uri(source, &document) {
+            Some(signature) => Some(
+                read_policy_document(&fetchers, &signature)
+                    .await
+                    .with_context(|| format!("Failed to read signature '{}'", signature))?,
+            ),
+            None => None,
+        };
+        return open_policy(source, &document, &ExpectedSigner::Key { verifier: &*verifier, signature: signature.as_deref() });
+    }
+    let identities: Vec<SignerIdentity> = identities.iter().map(|i| SignerIdentity::new(i)).collect();
+    let bundle = match Path::new(source).exists() {
+        true => read_bundle_file(Path::new(source))?,
+        false => fetch_manifest_text(&fetchers, source)
+            .await
+            .with_context(|| format!("Failed to fetch policy bundle from '{}'", source))?,
+    };
+    open_policy(source, &bundle, &ExpectedSigner::Sigstore { identities: &identities, trusted_root })
+}
+
+/// Reads a whole document, such as a key-signed policy or its signature, from a file, or fetches
+/// it from a URL.
+async fn read_policy_document(fetchers: &Fetchers, source: &str) -> Result<String> {
+    if Path::new(source).exists() {
+        return std::fs::read_to_string(source).with_context(|| format!("Failed to read '{}'", source));
+    }
+    fetch_document_text(fetchers, source).await.with_context(|| format!("Failed to fetch '{}'", source))
+}
+
+/// Prints the CI build context recorded in a signing certificate, if any.
+fn print_build_context(extensions: &FulcioExtensions, suffix: &str) {
+    let fields = [
+        ("Source repository", &extensions.source_repository_uri),
+        ("Source ref", &extensions.source_repository_ref),
+        ("Source digest", &extensions.source_repository_digest),
+        ("Build trigger", &extensions.build_trigger),
+        ("Build signer", &extensions.build_signer_uri),
+        ("Runner environment", &extensions.runner_environment),
+    ];
+    for (label, value) in fields {
+        if let Some(value) = value {
+            output::print(0, Status::Info, &format!("{}{}: {}", label, suffix, value));
+        }
+    }
+}
+
+fn handle_commands_manpages(out_dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(out_dir).with_context(|| format!("Failed to create directory '{}'", out_dir.display()))?;
+    clap_mangen::generate_to(Cli::command(), out_dir)
+        .with_context(|| format!("Failed to write man pages to '{}'", out_dir.display()))?;
+    output::print(0, Status::Verified, &format!("Wrote man pages to {}", out_dir.display()));
+    Ok(())
+}
+
+async fn handle_commands_artifacts(action: ArtifactsAction) -> Result<()> {
+    match action {
+        ArtifactsAction::VerifyLocal { file, purl, domain, traversal } => handle_artifacts_verify_local(&file, &purl, domain, traversal).await,
+    }
+}
+
+async fn handle_artifacts_verify_local(file: &Path, purl: &str, domain: Option<String>, mut traversal: TraversalArgs) -> Result<()> {
+    let version = PackageUrl::from_str(purl)
+        .with_context(|| format!("Invalid PURL syntax: '{}'", purl))?
+        .version()
+        .map(str::to_string)
+        .with_context(|| format!("'{}' has no version; name the release the file belongs to (e.g., @1.2.0)", purl))?;
+    let bytes = std::fs::read(file).with_context(|| format!("Failed to read '{}'", file.display()))?;
+    let client = traversal.http_client()?;
+    output::print(0, Status::Info, &format!("Verifying {} against {}", file.display(), purl));
+    traversal.start_progress();
+    let fetchers = traversal.fetchers(&client);
+    let discovered = discover_purl_target(purl, domain, &traversal, traversal.to_options(&client), &fetchers).await;
+    traversal.finish_progress();
+    traversal.export_trace(&client, purl).await;
+    let (target, aggregated_data) = discovered?;
+    traversal.report_unverified(&aggregated_data);
+    if let Some(root_error) = &aggregated_data.root_error {
+        bail!("Root catalog failed to verify: {}", root_error);
+    }
+
+    let component = aggregated_data
+        .components
+        .iter()
+        .find(|c| c.component_predicate.as_deref().is_some_and(|p| target.is_match(p)))
+        .with_context(|| format!("No verified component of the catalog matches '{}'", purl))?;
+    let release_version = |release: &&AggregatedReleaseData| {
+        let predicate = release.release_predicate.as_deref()?;
+        PackageUrl::from_str(&predicate.purl).ok()?.version().map(str::to_string)
+    };
+    let Some(release) = component.releases.iter().find(|r| release_version(r).as_deref() == Some(version.as_str())) else {
+        for (uri, e) in &component.release_errors {
+            output::eprint(1, Status::Failed, &format!("{}: {}", uri, e));
+        }
+        bail!("No verified release {} of '{}' was found", version, target.purl.component);
+    };
+    let predicate = check_release_trusted(release)?;
+    if let Some(status) = predicate.status.as_ref().filter(|s| s.is_pulled()) {
+        output::eprint(0, Status::Warning, &format!("Release {} is {}", predicate.purl, status));
+    }
+
+    let matched = match_release_artifacts(&bytes, predicate)?;
+    if matched.is_empty() {
+        bail!("{} does not match the digest of any artifact published in release {}", file.display(), predicate.purl);
+    }
+    for artifact in &matched {
+        output::print(0, Status::Verified, &format!(
+            "{} matches {}{} ({})",
+            file.display(),
+            artifact.link.uri,
+            artifact.platform.map(|p| format!(" for {}", p)).unwrap_or_default(),
+            artifact.algorithms.join(", ")
+        ));
+    }
+    let signer = release.verification.as_ref().and_then(|v| v.identity.as_deref()).unwrap_or("a pinned key");
+    output::print(0, Status::Verified, &format!("Published in release {}, signed by {}", predicate.purl, signer));
+    Ok(())
+}
+
+async fn handle_commands_verify_image(image: &str, domains: &[String], auth: RegistryAuth, mut traversal: TraversalArgs) -> Result<()> {
+    let digest = resolve_image_digest(image, auth).await?;
+    output::print(0, Status::Info, &format!("Image {} has digest {}", image, digest));
+    let client = traversal.http_client()?;
+    traversal.start_progress();
+    let discovery = traversal.to_discovery(&client);
+    let fetchers = traversal.fetchers(&client);
+    let mut found = Vec::new();
+    for domain in domains {
+        output::print(0, Status::Info, &format!("Searching domain: {}", domain));
+        match discovery.discover(domain, &fetchers).await {
+            Ok(data) => {
+                traversal.report_unverified(&data);
+                found.extend(find_image_releases(domain, &data, &digest));
+            }
+            Err(e) => output::print(1, Status::Warning, &format!("Skipping domain '{}': {:#}", domain, e)),
+        }
+    }
+    traversal.finish_progress();
+    traversal.export_trace(&client, image).await;
+    if found.is_empty() {
+        bail!("No verified release of {} attests to image {}", domains.join(", "), digest);
+    }
+    output::json(&found).context("Failed to serialize results to JSON")?;
+    if found.iter().all(|release| release.revoked.is_some()) {
+        bail!("Every release attesting to image {} has been revoked", digest);
+    }
+    Ok(())
+}
+
+async fn handle_commands_scan(
+    lockfile: &Path,
+    domains: Vec<String>,
+    mapping: Option<&Path>,
+    fail_under: Option<f64>,
+    mut traversal: TraversalArgs,
+) -> Result<()> {
+    let purls = lockfile_purls(lockfile)?;
+    output::print(0, Status::Info, &format!("Scanning {} dependencies from {}", purls.len(), lockfile.display()));
+    let client = traversal.http_client()?;
+    traversal.start_progress();
+    let discovery = traversal.to_discovery(&client);
+    let options = ResolveOptions {
+        domains,
+        mapping,
+        deps_dev: traversal.deps_dev(&client),
+    };
+    let fetchers = traversal.fetchers(&client);
+    let report = scan_dependencies(&purls, &options, discovery.as_ref(), &fetchers).await?;
+    traversal.finish_progress();
+    traversal.export_trace(&client, &lockfile.display().to_string()).await;
+
+    output::json(&report).context("Failed to serialize coverage report to JSON")?;
+    output::print(
+        0,
+        Status::Info,
+        &format!(
+            "Coverage: {} of {} dependencies ({:.1}%), {} with a verified release of the pinned version",
+            report.covered,
+            report.total,
+            report.coverage() * 100.0,
+            report.covered_releases
+        ),
+    );
+    if let Some(fail_under) = fail_under
+        && report.coverage() < fail_under
+    {
+        bail!("Coverage of {:.1}% is below the required {:.1}%", report.coverage() * 100.0, fail_under * 100.0);
+    }
+    Ok(())
+}
+
+async fn handle_commands_score(
+    domains: &[String],
+    fresh_within: chrono::Duration,
+    history: Option<HistoryStore>,
+    mut traversal: TraversalArgs,
+) -> Result<()> {
+    let client = traversal.http_client()?;
+    traversal.start_progress();
+    let discovery = traversal.to_discovery(&client);
+    let fetchers = traversal.fetchers(&client);
+    let now = Utc::now();
+    let mut scores = Vec::new();
+    for domain in domains {
+        output::print(0, Status::Info, &format!("Scoring domain: {}", domain));
+        match discovery.discover(domain, &fetchers).await {
+            Ok(data) => {
+                traversal.report_unverified(&data);
+                let score = score_supplier(domain, &data, fresh_within, now);
+                if let Some(history) = &history {
+                    history.record(domain, Snapshot::new(&score, &data, now))?;
+                }
+                scores.push(score);
+            }
+            Err(e) => {
+                output::print(1, Status::Warning, &format!("Failed to traverse '{}': {:#}", domain, e));
+                scores.push(SupplierScore::failed(domain, format!("{:#}", e)));
+            }
+        }
+    }
+    traversal.finish_progress();
+    traversal.export_trace(&client, &domains.join(",")).await;
+
+    scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+    output::json(&scores).context("Failed to serialize scores to JSON")?;
+    for supplier in &scores {
+        output::print(
+            0,
+            Status::Info,
+            &format!(
+                "{}: {:.1} / 100 ({} components, {} releases)",
+                supplier.domain,
+                supplier.score,
+                supplier.components.len(),
+                supplier.release_count()
+            ),
+        );
+    }
+    Ok(())
+}
+
+async fn handle_commands_trends(
+    domain: &str,
+    period: chrono::Duration,
+    fresh_within: chrono::Duration,
+    history: HistoryStore,
+    mut traversal: TraversalArgs,
+) -> Result<()> {
+    let client = traversal.http_client()?;
+    traversal.start_progress();
+    let discovery = traversal.to_discovery(&client);
+    let fetchers = traversal.fetchers(&client);
+    output::print(0, Status::Info, &format!("Scoring domain: {}", domain));
+    let discovered = discovery.discover(domain, &fetchers).await;
+    traversal.finish_progress();
+    traversal.export_trace(&client, domain).await;
+    let data = discovered.with_context(|| format!("Failed to traverse '{}'", domain))?;
+    traversal.report_unverified(&data);
+
+    let now = Utc::now();
+    let current = Snapshot::new(&score_supplier(domain, &data, fresh_within, now), &data, now);
+    let baseline = history.baseline(domain, now - period, now)?;
+    history.record(domain, current.clone())?;
+    let Some(baseline) = baseline else {
+        output::json(&current).context("Failed to serialize snapshot to JSON")?;
+        output::print(0, Status::Info, &format!("Recorded the first snapshot of {}; run again later to see trends", domain));
+        return Ok(());
+    };
+
+    let trend = Trend::new(domain, baseline, current);
+    output::json(&trend).context("Failed to serialize trends to JSON")?;
+    if trend.deltas.is_empty() {
+        output::print(0, Status::Info, &format!("No change since {}", trend.baseline.recorded_at));
+    }
+    for delta in &trend.deltas {
+        output::print(0, Status::Info, &delta.summary);
+    }
+    Ok(())
+}
+
+async fn handle_commands_inspect(source: &str) -> Result<()> {
+    let bundle = if Path::new(source).exists() {
+        read_bundle_file(Path::new(source))?
+    } else {
+        fetch_manifest_text(&Fetchers::new(reqwest::Client::new()), source)
+            .await
+            .with_context(|| format!("Failed to fetch bundle from '{}'", source))?
+    };
+    let decoded = decode_bundle_unverified(&bundle)?;
+
+    output::print(0, Status::Warning, "Signature NOT verified: nothing below can be trusted");
+    output::print(0, Status::Info, &format!("Payload type: {}", decoded.payload_type));
+    output::print(0, Status::Info, &format!("Signatures: {}", decoded.signatures));
+    if let Some(error) = &decoded.certificate_error {
+        output::print(0, Status::Failed, error);
+    }
+    for identity in &decoded.identities {
+        output::print(0, Status::Info, &format!("Certificate identity (unverified): {}", identity));
+    }
+    if let Some(issuer) = &decoded.extensions.issuer {
+        output::print(0, Status::Info, &format!("Certificate issuer (unverified): {}", issuer));
+    }
+    print_build_context(&decoded.extensions, " (unverified)");
+    if let Some((not_before, not_after)) = decoded.validity {
+        output::print(0, Status::Info, &format!(
+            "Certificate valid from {} to {}",
+            not_before.to_rfc3339(),
+            not_after.to_rfc3339()
+        ));
+    }
+    if let Some(integrated_time) = decoded.integrated_time {
+        output::print(0, Status::Info, &format!("Rekor integrated time (unverified): {}", integrated_time.to_rfc3339()));
+    }
+
+    let statement: serde_json::Value = match serde_json::from_slice(&decoded.payload) {
+        Ok(statement) => statement,
+        Err(e) => {
+            output::print(0, Status::Failed, &format!("Payload is not JSON: {}", e));
+            println!();
+            println!("{}", String::from_utf8_lossy(&decoded.payload));
+            return Ok(());
+        }
+    };
+    match serde_json::from_value::<InTotoStatement>(statement.clone()) {
+        Ok(parsed) => match parse_predicate(&parsed) {
+            Ok(ChainsightsPredicate::Unknown { predicate_type, .. }) => output::print(
+                0,
+                Status::Warning,
+                &format!("Predicate type '{}' is not a Chainsights predicate", predicate_type),
+            ),
+            Ok(known) => output::print(0, Status::Info, &format!("Detected {} predicate", known.kind())),
+            Err(e) => output::print(0, Status::Failed, &format!("{:#}", e)),
+        },
+        Err(e) => output::print(0, Status::Failed, &format!("Payload is not an in-toto statement: {}", e)),
+    }
+    println!();
+    output::json(&statement)
+}
+
+/// Reads the first non-empty line of a bundle (.jsonl) file.
+fn read_bundle_file(path: &Path) -> Result<String> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read bundle '{}'", path.display()))?;
+    contents
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(str::to_string)
+        .with_context(|| format!("Bundle file '{}' is empty", path.display()))
+}
@@ -0,0 +1,320 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chainsights_core::identity::{BuildPolicy, SignerIdentity};
+use chainsights_core::pgp::PgpKeyring;
+use chainsights_core::trust::TrustedRoot;
+#[cfg(feature = "dns-discovery")]
+use chainsights_core::identity::normalize_domain;
+
+pub use chainsights_core::timestamp::TimeSource;
+
+use crate::dane::TlsaResolver;
+#[cfg(feature = "dns-discovery")]
+use crate::discovery::{ChainsightsDiscovery, Discovery};
+#[cfg(feature = "dns-discovery")]
+use crate::dns::{CachingResolver, SystemResolver, TxtResolver};
+use crate::fetch::Fetchers;
+use crate::models::aggregation::AggregatedCatalogData;
+use crate::rekor::RekorClient;
+#[cfg(feature = "dns-discovery")]
+use crate::scope::CrossDomainTrust;
+use crate::scope::LinkScope;
+use crate::traversal::{InsecureMode, SilentHooks, TraversalHooks, TraversalOptions, traverse_and_aggregate};
+
+/// Default tolerance for clock differences when checking expiry, freshness and certificate validity.
+const DEFAULT_CLOCK_SKEW_SECONDS: i64 = 300;
+
+/// An asynchronous client that discovers, verifies and aggregates a domain's attestations.
+///
+/// ```no_run
+/// # async fn run() -> anyhow::Result<()> {
+/// let client = chainsights_client::client::Client::new();
+/// let data = client.discover("example.com").await?;
+/// println!("{} components", data.components.len());
+/// # Ok(())
+/// # }
+/// ```
+pub struct Client {
+    fetchers: Fetchers,
+    options: TraversalOptions,
+    #[cfg(feature = "dns-discovery")]
+    resolver: Arc<dyn TxtResolver>,
+    #[cfg(feature = "dns-discovery")]
+    follow_delegations: bool,
+}
+
+impl Client {
+    /// A client with the default options.
+    pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    /// Starts configuring a client.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// Looks up the domain's `_chainsights` DNS record and traverses everything it publishes.
+    #[cfg(feature = "dns-discovery")]
+    pub async fn discover(&self, domain: &str) -> Result<AggregatedCatalogData> {
+        let domain = normalize_domain(domain)?;
+        ChainsightsDiscovery::new(self.options.clone(), self.resolver.clone())
+            .following_delegations(self.follow_delegations)
+            .discover(&domain, &self.fetchers)
+            .await
+    }
+
+    /// Splits the client into its discovery protocol and fetchers, for users that keep them
+    /// beyond single queries.
+    #[cfg(feature = "dns-discovery")]
+    pub(crate) fn into_discovery(self) -> (Box<dyn Discovery>, Fetchers) {
+        let discovery = ChainsightsDiscovery::new(self.options, self.resolver).following_delegations(self.follow_delegations);
+        (Box::new(discovery), self.fetchers)
+    }
+
+    /// Traverses from a known root catalog URI, which must be signed by `identity`.
+    pub async fn traverse(&self, root_uri: &str, identity: &str) -> Result<AggregatedCatalogData> {
+        traverse_and_aggregate(root_uri, &[SignerIdentity::new(identity)], &self.fetchers, &self.options)
+            .await
+            .with_context(|| format!("Traversal failed starting from {}", root_uri))
+    }
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Configures a [`Client`].
+pub struct ClientBuilder {
+    http: Option<reqwest::Client>,
+    rekor_fallback_url: Option<String>,
+    exclude_revoked: bool,
+    clock_skew: chrono::Duration,
+    time_source: TimeSource,
+    quiet: bool,
+    cache_ttl: Option<Duration>,
+    link_scope: Option<LinkScope>,
+    release_build_policy: Option<BuildPolicy>,
+    trusted_root: Option<Arc<TrustedRoot>>,
+    pgp_keyring: Option<Arc<PgpKeyring>>,
+    trusted_endorsers: Vec<(String, String)>,
+    dane: Option<Arc<dyn TlsaResolver>>,
+    #[cfg(feature = "dns-discovery")]
+    resolver: Option<Arc<dyn TxtResolver>>,
+    #[cfg(feature = "dns-discovery")]
+    follow_delegations: bool,
+    #[cfg(feature = "dns-discovery")]
+    cross_domains: Vec<String>,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self {
+            http: None,
+            rekor_fallback_url: None,
+            exclude_revoked: false,
+            clock_skew: chrono::Duration::seconds(DEFAULT_CLOCK_SKEW_SECONDS),
+            time_source: TimeSource::default(),
+            quiet: false,
+            cache_ttl: None,
+            link_scope: None,
+            release_build_policy: None,
+            trusted_root: None,
+            pgp_keyring: None,
+            trusted_endorsers: Vec::new(),
+            dane: None,
+            #[cfg(feature = "dns-discovery")]
+            resolver: None,
+            #[cfg(feature = "dns-discovery")]
+            follow_delegations: false,
+            #[cfg(feature = "dns-discovery")]
+            cross_domains: Vec::new(),
+        }
+    }
+}
+
+impl ClientBuilder {
+    /// Uses an existing HTTP client, e.g. one with a proxy or custom root certificates.
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http = Some(client);
+        self
+    }
+
+    /// Searches this Rekor instance for attestations whose links fail to fetch.
+    pub fn rekor_fallback(mut self, rekor_url: &str) -> Self {
+        self.rekor_fallback_url = Some(rekor_url.to_string());
+        self
+    }
+
+    /// Leaves revoked components and releases out of results instead of marking them.
+    pub fn exclude_revoked(mut self, exclude: bool) -> Self {
+        self.exclude_revoked = exclude;
+        self
+    }
+
+    /// Sets the tolerated clock difference for expiry, freshness and certificate validity checks.
+    pub fn clock_skew(mut self, skew: chrono::Duration) -> Self {
+        self.clock_skew = skew;
+        self
+    }
+
+    /// Sets where signing times come from. Time sources other than the local clock need a
+    /// [`trusted_root`](Self::trusted_root) to verify the bundles' timestamps against.
+    pub fn time_source(mut self, time_source: TimeSource) -> Self {
+        self.time_source = time_source;
+        self
+    }
+
+    /// Stops traversals from printing a line for each attestation they verify or reject.
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Reuses `_chainsights` TXT answers and root catalog bundles across queries for up to `ttl`,
+    /// for clients that query the same domains repeatedly. TXT answers are kept no longer than
+    /// their DNS TTL. Cached bundles are verified again each time they are used.
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Reports attestation links that point outside the queried domain and `allowed_hosts` as
+    /// errors instead of following them, so that a catalog cannot splice in statements signed by
+    /// another party. [`Client::traverse`] scopes links to the root catalog's host. Allowed hosts
+    /// (e.g., a CDN) include their subdomains.
+    pub fn scope_links(mut self, allowed_hosts: &[&str]) -> Self {
+        self.link_scope = Some(LinkScope::new(allowed_hosts.iter().map(|h| h.to_string()).collect()));
+        self
+    }
+
+    /// Only accepts release statements whose signing certificate records a CI build context
+    /// satisfying `policy`, e.g. a build of a tag in the publisher's repository. Releases that
+    /// fail it are reported as release errors.
+    pub fn release_build_policy(mut self, policy: BuildPolicy) -> Self {
+        self.release_build_policy = Some(policy);
+        self
+    }
+
+    /// Verifies bundles against the trust material of a Sigstore instance, such as a private
+    /// deployment's `trusted_root.json`: certificates must chain to its Fulcio roots and carry a
+    /// timestamp from one of its CT logs, and Rekor entries must be signed by one of its logs.
+    pub fn trusted_root(mut self, trusted_root: TrustedRoot) -> Self {
+        self.trusted_root = Some(Arc::new(trusted_root));
+        self
+    }
+
+    /// Looks up the keys of PGP-signed attestations in `keyring`. Links pin the keys they accept
+    /// by fingerprint; without a keyring, PGP-signed attestations fail to verify.
+    pub fn pgp_keyring(mut self, keyring: PgpKeyring) -> Self {
+        self.pgp_keyring = Some(Arc::new(keyring));
+        self
+    }
+
+    /// Attributes endorsements signed by `identity` to `name`. Endorsements by other signers are
+    /// still verified and listed, but without a trusted endorser name: the endorser a statement
+    /// names, and the identity its link expects, are chosen by the publisher.
+    pub fn trust_endorser(mut self, name: &str, identity: &str) -> Self {
+        self.trusted_endorsers.push((name.to_string(), identity.to_string()));
+        self
+    }
+
+    /// Requires attestations fetched over HTTP(S) to be served over HTTPS by hosts whose TLS
+    /// certificate matches their DANE TLSA records, looked up with `resolver`. Hosts without
+    /// TLSA records fail. A client passed to [`http_client`](Self::http_client) must be built
+    /// with `tls_info(true)`.
+    pub fn dane(mut self, resolver: impl TlsaResolver + 'static) -> Self {
+        self.dane = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Answers `_chainsights` TXT lookups with `resolver` instead of the system's DNS servers.
+    #[cfg(feature = "dns-discovery")]
+    pub fn txt_resolver(mut self, resolver: impl TxtResolver + 'static) -> Self {
+        self.resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Also discovers the subdomains a domain delegates its namespace to with `delegate=` TXT
+    /// records, and includes their components in its results. Each delegated root must be signed
+    /// by an identity within its subdomain, or by the identity the delegation pins.
+    #[cfg(feature = "dns-discovery")]
+    pub fn follow_delegations(mut self, follow: bool) -> Self {
+        self.follow_delegations = follow;
+        self
+    }
+
+    /// With [`scope_links`](Self::scope_links), also follows component and release links into
+    /// another organization's `domains` (e.g., the vendor of a resold product), including their
+    /// subdomains, once the domain's own `_chainsights` record names the identity each link
+    /// expects. Such nodes carry the domain in their `cross_domain` label.
+    #[cfg(feature = "dns-discovery")]
+    pub fn trust_cross_domain(mut self, domains: &[&str]) -> Self {
+        self.cross_domains = domains.iter().map(|d| d.to_string()).collect();
+        self
+    }
+
+    /// Builds the client.
+    pub fn build(self) -> Client {
+        let http = self.http.unwrap_or_else(|| {
+            reqwest::Client::builder().tls_info(self.dane.is_some()).build().unwrap_or_default()
+        });
+        let fetchers = match self.dane {
+            Some(resolver) => Fetchers::with_dane(http.clone(), resolver),
+            None => Fetchers::new(http.clone()),
+        };
+        #[cfg(feature = "dns-discovery")]
+        let resolver: Arc<dyn TxtResolver> = {
+            let resolver = self.resolver.unwrap_or_else(|| Arc::new(SystemResolver));
+            match self.cache_ttl {
+                Some(ttl) => Arc::new(CachingResolver::new(resolver, ttl)),
+                None => resolver,
+            }
+        };
+        #[cfg(feature = "dns-discovery")]
+        let link_scope = match self.link_scope {
+            Some(scope) if !self.cross_domains.is_empty() => {
+                Some(scope.trusting(CrossDomainTrust::new(self.cross_domains, Arc::new(resolver.clone()))))
+            }
+            scope => scope,
+        };
+        #[cfg(not(feature = "dns-discovery"))]
+        let link_scope = self.link_scope;
+        Client {
+            options: TraversalOptions {
+                rekor_fallback: self
+                    .rekor_fallback_url
+                    .map(|url| RekorClient::new(http.clone(), &url)),
+                exclude_revoked: self.exclude_revoked,
+                clock_skew: self.clock_skew,
+                time_source: self.time_source,
+                hooks: self.quiet.then(|| Arc::new(SilentHooks) as Arc<dyn TraversalHooks>),
+                link_scope,
+                release_build_policy: self.release_build_policy,
+                trusted_root: self.trusted_root,
+                pgp_keyring: self.pgp_keyring,
+                root_key_pin: None,
+                #[cfg(feature = "cli")]
+                tracer: None,
+                insecure: InsecureMode::default(),
+                stats: None,
+                trusted_endorsers: self.trusted_endorsers,
+                predicate_parser: None,
+            },
+            fetchers: match self.cache_ttl {
+                Some(ttl) => fetchers.with_catalog_cache(ttl),
+                None => fetchers,
+            },
+            #[cfg(feature = "dns-discovery")]
+            resolver,
+            #[cfg(feature = "dns-discovery")]
+            follow_delegations: self.follow_delegations,
+        }
+    }
+}
@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+#[cfg(feature = "cli")]
+use chainsights_core::identity::{normalize_domain, same_identity};
+use packageurl::PackageUrl;
+
+#[cfg(feature = "cli")]
+use crate::models::aggregation::{AggregatedCatalogData, IntegrityErrorKind};
+
+/// The outcome of one cross-link check over a traversed domain.
+#[cfg(feature = "cli")]
+pub(crate) struct ConsistencyCheck {
+    /// What was checked (e.g., "Release PURLs match their components")
+    pub(crate) name: &'static str,
+    /// Each inconsistency found; empty when the check passed
+    pub(crate) problems: Vec<String>,
+}
+
+/// Checks that the statements a domain publishes agree with each other and with its TXT record:
+/// the root catalog is signed by the TXT record identity, root components are named under the
+/// domain, catalog entries name the components they link to, and releases are versions of their
+/// component. The last two are checked during traversal, which reports them as integrity errors.
+/// Statements that failed to verify are not checked.
+#[cfg(feature = "cli")]
+pub(crate) fn check_domain_consistency(
+    domain: &str,
+    txt_identity: &str,
+    data: &AggregatedCatalogData,
+) -> Vec<ConsistencyCheck> {
+    vec![
+        check_root_identity(txt_identity, data),
+        check_root_namespace(domain, data),
+        check_component_entries(data),
+        check_release_purls(data),
+    ]
+}
+
+#[cfg(feature = "cli")]
+fn check_root_identity(txt_identity: &str, data: &AggregatedCatalogData) -> ConsistencyCheck {
+    let mut problems = Vec::new();
+    match data.verification.as_ref().and_then(|v| v.identity.as_deref()) {
+        Some(signer) if same_identity(signer, txt_identity) => {}
+        Some(signer) => problems.push(format!(
+            "Root catalog is signed by '{}', but the TXT record names '{}'",
+            signer, txt_identity
+        )),
+        None => problems.push("Root catalog has no verified signer identity".to_string()),
+    }
+    ConsistencyCheck { name: "Root catalog is signed by the TXT record identity", problems }
+}
+
+#[cfg(feature = "cli")]
+fn check_root_namespace(domain: &str, data: &AggregatedCatalogData) -> ConsistencyCheck {
+    let mut problems = Vec::new();
+    for entry in data.catalog_predicate.iter().flat_map(|c| &c.components) {
+        if !purl_in_domain(&entry.component_purl, domain) {
+            problems.push(format!(
+                "Catalog entry '{}' has PURL '{}' outside the {} namespace",
+                entry.name, entry.component_purl, domain
+            ));
+        }
+    }
+    ConsistencyCheck { name: "Root component PURLs are in the domain namespace", problems }
+}
+
+#[cfg(feature = "cli")]
+fn check_component_entries(data: &AggregatedCatalogData) -> ConsistencyCheck {
+    ConsistencyCheck {
+        name: "Catalog entries match their component statements",
+        problems: data
+            .integrity_errors
+            .iter()
+            .filter(|e| e.kind == IntegrityErrorKind::ComponentPurl)
+            .map(ToString::to_string)
+            .collect(),
+    }
+}
+
+#[cfg(feature = "cli")]
+fn check_release_purls(data: &AggregatedCatalogData) -> ConsistencyCheck {
+    ConsistencyCheck {
+        name: "Release PURLs are versions of their components",
+        problems: data
+            .components
+            .iter()
+            .flat_map(|c| &c.integrity_errors)
+            .filter(|e| e.kind == IntegrityErrorKind::ReleasePurl)
+            .map(ToString::to_string)
+            .collect(),
+    }
+}
+
+/// True if the first namespace segment of `purl` is `domain`.
+#[cfg(feature = "cli")]
+pub(crate) fn purl_in_domain(purl: &str, domain: &str) -> bool {
+    let Ok(purl) = PackageUrl::from_str(purl) else {
+        return false;
+    };
+    let namespace_domain = purl.namespace().and_then(|n| n.split('/').next()).map(normalize_domain);
+    matches!((namespace_domain, normalize_domain(domain)), (Some(Ok(a)), Ok(b)) if a == b)
+}
+
+/// True if two PURLs name the same package, ignoring versions, qualifiers and subpaths.
+pub(crate) fn same_package(a: &str, b: &str) -> bool {
+    match (PackageUrl::from_str(a), PackageUrl::from_str(b)) {
+        (Ok(a), Ok(b)) => a.ty() == b.ty() && a.namespace() == b.namespace() && a.name() == b.name(),
+        _ => false,
+    }
+}
+
+/// True if `release` is a versioned PURL of the package `component` names.
+pub(crate) fn is_version_of(release: &str, component: &str) -> bool {
+    PackageUrl::from_str(release).is_ok_and(|purl| purl.version().is_some()) && same_package(release, component)
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use chainsights_core::identity::SignerIdentity;
+    use chainsights_testing::{FixtureSpec, MockEnvironment};
+
+    use super::*;
+    use crate::fetch::Fetchers;
+    use crate::models::aggregation::IntegrityError;
+    use crate::traversal::{TraversalOptions, traverse_and_aggregate};
+
+    #[tokio::test]
+    async fn reports_each_failed_check() {
+        let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+        let identities = [SignerIdentity::new(env.identity())];
+        let fetchers = Fetchers::new(reqwest::Client::new());
+        let mut data = traverse_and_aggregate(env.root_uri(), &identities, &fetchers, &TraversalOptions::default())
+            .await
+            .unwrap();
+        for check in check_domain_consistency(env.domain(), env.identity(), &data) {
+            assert!(check.problems.is_empty(), "{}: {:?}", check.name, check.problems);
+        }
+
+        data.integrity_errors.push(IntegrityError {
+            uri: env.uri("/components/app.json"),
+            kind: IntegrityErrorKind::ComponentPurl,
+            expected_purl: "pkg:generic/example.test/app".to_string(),
+            found_purl: "pkg:generic/example.test/renamed".to_string(),
+        });
+        let checks = check_domain_consistency("example.org", "other@example.test", &data);
+        let problems: Vec<usize> = checks.iter().map(|c| c.problems.len()).collect();
+        assert_eq!(problems, [1, 2, 1, 0]);
+    }
+
+    #[test]
+    fn purls_belong_to_their_namespace_domain() {
+        assert!(purl_in_domain("pkg:chainsights/example.com/app", "example.com"));
+        assert!(purl_in_domain("pkg:chainsights/Example.COM/team-a/app", "example.com"));
+        assert!(purl_in_domain("pkg:generic/example.com/app@1.0", "example.com"));
+        assert!(!purl_in_domain("pkg:chainsights/example.org/app", "example.com"));
+        assert!(!purl_in_domain("pkg:chainsights/evil.example.com.attacker.test/app", "example.com"));
+        assert!(!purl_in_domain("pkg:npm/app", "example.com"));
+    }
+
+    #[test]
+    fn releases_are_versions_of_their_component() {
+        let component = "pkg:chainsights/example.com/app";
+        assert!(is_version_of("pkg:chainsights/example.com/app@1.0", component));
+        assert!(is_version_of("pkg:chainsights/example.com/app@1.0?arch=x86_64", component));
+        assert!(!is_version_of("pkg:chainsights/example.com/app", component));
+        assert!(!is_version_of("pkg:chainsights/example.com/other@1.0", component));
+        assert!(!is_version_of("pkg:chainsights/example.org/app@1.0", component));
+    }
+}
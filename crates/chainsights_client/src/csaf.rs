@@ -0,0 +1,505 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Security advisories in the Common Security Advisory Framework (CSAF) format, linked from the
+//! catalog, a component or a release as `application/csaf+json`. A link may point at a single
+//! advisory or at a provider's `provider-metadata.json`, whose directory and ROLIE distributions
+//! list its advisories. Advisories are matched to releases by the PURLs and CPEs of their
+//! products, so discovery can report the ones that apply to each release.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use packageurl::PackageUrl;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::fetch::{Fetchers, fetch_and_verify_artifact, fetch_document_text};
+use crate::models::aggregation::{AggregatedCatalogData, AggregatedComponentData, CsafAdvisory, CsafVulnerability};
+use crate::models::chainsights::{ArtifactLink, CSAF_MEDIA_TYPE};
+use crate::output::{self, Status};
+
+/// The most advisories read from one provider; larger providers should be linked advisory by advisory.
+const MAX_PROVIDER_ADVISORIES: usize = 500;
+
+/// A CSAF advisory, reduced to what is needed to match it to releases.
+#[derive(Deserialize, Debug)]
+struct CsafDocument {
+    document: DocumentMeta,
+    #[serde(default)]
+    product_tree: ProductTree,
+    #[serde(default)]
+    vulnerabilities: Vec<Vulnerability>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DocumentMeta {
+    title: String,
+    tracking: Tracking,
+    aggregate_severity: Option<AggregateSeverity>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Tracking {
+    id: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct AggregateSeverity {
+    text: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct ProductTree {
+    #[serde(default)]
+    branches: Vec<Branch>,
+    #[serde(default)]
+    full_product_names: Vec<FullProductName>,
+    #[serde(default)]
+    relationships: Vec<Relationship>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Branch {
+    #[serde(default)]
+    branches: Vec<Branch>,
+    product: Option<FullProductName>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FullProductName {
+    product_id: String,
+    product_identification_helper: Option<ProductIdentificationHelper>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ProductIdentificationHelper {
+    cpe: Option<String>,
+    /// CSAF 2.0 names a single PURL.
+    purl: Option<String>,
+    /// CSAF 2.1 names a list of them.
+    #[serde(default)]
+    purls: Vec<String>,
+}
+
+/// A product made of another, such as a package installed on a platform.
+#[derive(Deserialize, Debug)]
+struct Relationship {
+    product_reference: String,
+    full_product_name: FullProductName,
+}
+
+#[derive(Deserialize, Debug)]
+struct Vulnerability {
+    cve: Option<String>,
+    #[serde(default)]
+    ids: Vec<VulnerabilityId>,
+    title: Option<String>,
+    /// Product IDs by status (`known_affected`, `fixed`, ...).
+    #[serde(default)]
+    product_status: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct VulnerabilityId {
+    text: String,
+}
+
+/// A CSAF provider's `provider-metadata.json`.
+#[derive(Deserialize, Debug)]
+struct ProviderMetadata {
+    #[serde(default)]
+    distributions: Vec<Distribution>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Distribution {
+    directory_url: Option<String>,
+    rolie: Option<Rolie>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Rolie {
+    #[serde(default)]
+    feeds: Vec<RolieFeedLink>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RolieFeedLink {
+    url: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct RolieFeed {
+    feed: Feed,
+}
+
+#[derive(Deserialize, Debug)]
+struct Feed {
+    #[serde(default)]
+    entry: Vec<FeedEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FeedEntry {
+    content: FeedContent,
+}
+
+#[derive(Deserialize, Debug)]
+struct FeedContent {
+    src: String,
+}
+
+/// The identifiers a release is known by.
+struct ReleaseIdentity {
+    purls: Vec<PackageUrl<'static>>,
+    cpe: Option<String>,
+}
+
+impl ReleaseIdentity {
+    fn matches(&self, product: &FullProductName) -> bool {
+        let Some(helper) = &product.product_identification_helper else {
+            return false;
+        };
+        if helper.cpe.is_some() && helper.cpe == self.cpe {
+            return true;
+        }
+        helper.purl.iter().chain(&helper.purls).filter_map(|purl| PackageUrl::from_str(purl).ok()).any(|purl| {
+            self.purls.iter().any(|own| {
+                own.ty() == purl.ty()
+                    && own.namespace() == purl.namespace()
+                    && own.name() == purl.name()
+                    && own.version().is_some()
+                    && own.version() == purl.version()
+            })
+        })
+    }
+}
+
+impl ProductTree {
+    fn products(&self) -> Vec<&FullProductName> {
+        fn walk<'a>(branches: &'a [Branch], products: &mut Vec<&'a FullProductName>) {
+            for branch in branches {
+                products.extend(&branch.product);
+                walk(&branch.branches, products);
+            }
+        }
+        let mut products: Vec<&FullProductName> = self.full_product_names.iter().collect();
+        walk(&self.branches, &mut products);
+        products.extend(self.relationships.iter().map(|relationship| &relationship.full_product_name));
+        products
+    }
+}
+
+impl CsafDocument {
+    /// IDs of the products that are the release, or are built from it.
+    fn release_products(&self, release: &ReleaseIdentity) -> HashSet<&str> {
+        let mut products: HashSet<&str> = self
+            .product_tree
+            .products()
+            .into_iter()
+            .filter(|product| release.matches(product))
+            .map(|product| product.product_id.as_str())
+            .collect();
+        for relationship in &self.product_tree.relationships {
+            if products.contains(relationship.product_reference.as_str()) {
+                products.insert(relationship.full_product_name.product_id.as_str());
+            }
+        }
+        products
+    }
+
+    /// Describes the advisory as it applies to the release, if it names the release at all.
+    fn advisory_for(&self, uri: &str, release: &ReleaseIdentity) -> Option<CsafAdvisory> {
+        let products = self.release_products(release);
+        if products.is_empty() {
+            return None;
+        }
+        let mut vulnerabilities = Vec::new();
+        for vulnerability in &self.vulnerabilities {
+            let id = vulnerability
+                .cve
+                .clone()
+                .or_else(|| vulnerability.ids.first().map(|id| id.text.clone()))
+                .or_else(|| vulnerability.title.clone())
+                .unwrap_or_default();
+            for (status, product_ids) in &vulnerability.product_status {
+                if product_ids.iter().any(|product_id| products.contains(product_id.as_str())) {
+                    vulnerabilities.push(CsafVulnerability { id: id.clone(), status: status.clone() });
+                }
+            }
+        }
+        Some(CsafAdvisory {
+            id: self.document.tracking.id.clone(),
+            title: self.document.title.clone(),
+            uri: uri.to_string(),
+            severity: self.document.aggregate_severity.as_ref().map(|severity| severity.text.clone()),
+            vulnerabilities,
+        })
+    }
+}
+
+/// The advisories behind one link, and those of a provider's advisories that could not be read.
+#[derive(Default)]
+struct LinkedAdvisories {
+    documents: Vec<(String, CsafDocument)>,
+    errors: Vec<(String, String)>,
+}
+
+/// Advisories fetched so far by link URI, so links shared by several components and releases are
+/// only fetched once.
+#[derive(Default)]
+pub(crate) struct AdvisoryCache(HashMap<String, Arc<LinkedAdvisories>>);
+
+impl AdvisoryCache {
+    async fn get(&mut self, link: &ArtifactLink, fetchers: &Fetchers) -> Arc<LinkedAdvisories> {
+        if let Some(advisories) = self.0.get(&link.uri) {
+            return advisories.clone();
+        }
+        output::print(1, Status::Info, &format!("Fetching CSAF advisories from {}", link.uri));
+        let advisories = Arc::new(match load(link, fetchers).await {
+            Ok(advisories) => advisories,
+            Err(e) => LinkedAdvisories { errors: vec![(link.uri.clone(), format!("{:#}", e))], ..Default::default() },
+        });
+        self.0.insert(link.uri.clone(), advisories.clone());
+        advisories
+    }
+}
+
+/// Fetches a linked advisory, or every advisory a linked provider distributes.
+async fn load(link: &ArtifactLink, fetchers: &Fetchers) -> Result<LinkedAdvisories> {
+    let bytes = fetch_and_verify_artifact(link, fetchers).await?;
+    let document: Value = serde_json::from_slice(&bytes).context("The CSAF document is not JSON")?;
+    if document.get("document").is_some() {
+        let advisory = serde_json::from_value(document).context("Invalid CSAF advisory")?;
+        return Ok(LinkedAdvisories { documents: vec![(link.uri.clone(), advisory)], errors: Vec::new() });
+    }
+    if document.get("distributions").is_none() {
+        bail!("The document is neither a CSAF advisory nor CSAF provider metadata");
+    }
+    let metadata: ProviderMetadata = serde_json::from_value(document).context("Invalid CSAF provider metadata")?;
+
+    let mut advisories = LinkedAdvisories::default();
+    let mut uris = Vec::new();
+    for distribution in &metadata.distributions {
+        if let Some(directory_url) = &distribution.directory_url {
+            let index_url = format!("{}/index.txt", directory_url.trim_end_matches('/'));
+            match fetch_document_text(fetchers, &index_url).await {
+                Ok(index) => uris.extend(
+                    index
+                        .lines()
+                        .map(str::trim)
+                        .filter(|line| !line.is_empty())
+                        .map(|line| format!("{}/{}", directory_url.trim_end_matches('/'), line)),
+                ),
+                Err(e) => advisories.errors.push((index_url, format!("{:#}", e))),
+            }
+        }
+        for feed_link in distribution.rolie.iter().flat_map(|rolie| &rolie.feeds) {
+            let feed = fetch_document_text(fetchers, &feed_link.url)
+                .await
+                .and_then(|text| serde_json::from_str::<RolieFeed>(&text).context("Invalid ROLIE feed"));
+            match feed {
+                Ok(feed) => uris.extend(feed.feed.entry.into_iter().map(|entry| entry.content.src)),
+                Err(e) => advisories.errors.push((feed_link.url.clone(), format!("{:#}", e))),
+            }
+        }
+    }
+    uris.sort();
+    uris.dedup();
+    if uris.len() > MAX_PROVIDER_ADVISORIES {
+        output::eprint(
+            1,
+            Status::Warning,
+            &format!(
+                "{} lists {} advisories; only the first {} are read",
+                link.uri,
+                uris.len(),
+                MAX_PROVIDER_ADVISORIES
+            ),
+        );
+        uris.truncate(MAX_PROVIDER_ADVISORIES);
+    }
+
+    for uri in uris {
+        let advisory = fetch_document_text(fetchers, &uri)
+            .await
+            .and_then(|text| serde_json::from_str::<CsafDocument>(&text).context("Invalid CSAF advisory"));
+        match advisory {
+            Ok(advisory) => advisories.documents.push((uri, advisory)),
+            Err(e) => advisories.errors.push((uri, format!("{:#}", e))),
+        }
+    }
+    Ok(advisories)
+}
+
+fn csaf_links(links: Option<&Vec<ArtifactLink>>) -> impl Iterator<Item = &ArtifactLink> {
+    links.into_iter().flatten().filter(|link| link.media_type.as_deref() == Some(CSAF_MEDIA_TYPE) && !link.is_gated())
+}
+
+/// The CSAF links of a catalog, which apply to every release of every component.
+pub(crate) fn catalog_advisory_links(aggregated_data: &AggregatedCatalogData) -> Vec<ArtifactLink> {
+    csaf_links(aggregated_data.catalog_predicate.as_ref().and_then(|c| c.metadata_links.as_ref())).cloned().collect()
+}
+
+/// Correlates the advisories linked anywhere in a catalog with the releases of its components.
+pub(crate) async fn correlate_advisories(aggregated_data: &mut AggregatedCatalogData, fetchers: &Fetchers) {
+    let catalog_links = catalog_advisory_links(aggregated_data);
+    let mut cache = AdvisoryCache::default();
+    for component in &mut aggregated_data.components {
+        correlate_component(component, &catalog_links, &mut cache, fetchers).await;
+    }
+}
+
+/// Records in each release of a component the advisories that name it, from those linked from the
+/// catalog (`catalog_links`), the component, and the release itself. Releases are matched by
+/// their PURL, the component's alias PURLs combined with the release version, and their CPE.
+pub(crate) async fn correlate_component(
+    component: &mut AggregatedComponentData,
+    catalog_links: &[ArtifactLink],
+    cache: &mut AdvisoryCache,
+    fetchers: &Fetchers,
+) {
+    let component_links: Vec<ArtifactLink> = catalog_links
+        .iter()
+        .chain(csaf_links(component.component_predicate.as_ref().and_then(|c| c.metadata_links.as_ref())))
+        .cloned()
+        .collect();
+    let alias_purls: Vec<String> = component
+        .component_predicate
+        .as_ref()
+        .map(|c| c.ecosystem_purls().cloned().collect())
+        .unwrap_or_default();
+
+    for release in &mut component.releases {
+        let Some(release_predicate) = release.release_predicate.clone() else {
+            continue;
+        };
+        let mut purls: Vec<PackageUrl<'static>> = PackageUrl::from_str(&release_predicate.purl).into_iter().collect();
+        if let Some(version) = purls.first().and_then(|purl| purl.version()).map(str::to_string) {
+            purls.extend(alias_purls.iter().filter_map(|alias| {
+                let mut purl = PackageUrl::from_str(alias).ok()?;
+                if purl.version().is_some() {
+                    return None;
+                }
+                purl.with_version(version.clone());
+                Some(purl)
+            }));
+        }
+        let identity = ReleaseIdentity { purls, cpe: release_predicate.cpe.clone() };
+
+        let mut seen = HashSet::new();
+        let links: Vec<ArtifactLink> = component_links
+            .iter()
+            .chain(csaf_links(Some(&release.metadata_artifacts)))
+            .filter(|link| seen.insert(link.uri.clone()))
+            .cloned()
+            .collect();
+        for link in &links {
+            let linked = cache.get(link, fetchers).await;
+            release.enrichment_errors.extend(linked.errors.iter().cloned());
+            for (uri, document) in &linked.documents {
+                if let Some(advisory) = document.advisory_for(uri, &identity)
+                    && !release.csaf_advisories.iter().any(|a| a.id == advisory.id)
+                {
+                    release.csaf_advisories.push(advisory);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    fn advisory(id: &str, purl: &str) -> Value {
+        json!({
+            "document": {
+                "category": "csaf_security_advisory",
+                "title": format!("{} advisory", id),
+                "tracking": { "id": id },
+                "aggregate_severity": { "text": "Important" },
+            },
+            "product_tree": {
+                "branches": [{
+                    "category": "vendor",
+                    "name": "Example",
+                    "branches": [{
+                        "category": "product_version",
+                        "name": "1.0.0",
+                        "product": {
+                            "product_id": "APP-1.0.0",
+                            "name": "app 1.0.0",
+                            "product_identification_helper": { "purl": purl },
+                        },
+                    }],
+                }],
+                "relationships": [{
+                    "category": "default_component_of",
+                    "product_reference": "APP-1.0.0",
+                    "relates_to_product_reference": "PLATFORM",
+                    "full_product_name": { "product_id": "APP-1.0.0:PLATFORM", "name": "app 1.0.0 on the platform" },
+                }],
+            },
+            "vulnerabilities": [
+                { "cve": "CVE-2025-0001", "product_status": { "known_affected": ["APP-1.0.0:PLATFORM"] } },
+                { "cve": "CVE-2025-0002", "product_status": { "fixed": ["APP-1.0.0"], "known_affected": ["OTHER"] } },
+            ],
+        })
+    }
+
+    #[tokio::test]
+    async fn reads_provider_distributions_and_matches_releases() {
+        let server = MockServer::start().await;
+        let provider = json!({
+            "canonical_url": format!("{}/provider-metadata.json", server.uri()),
+            "distributions": [
+                { "directory_url": format!("{}/advisories/", server.uri()) },
+                { "rolie": { "feeds": [{ "url": format!("{}/feed.json", server.uri()) }] } },
+            ],
+        });
+        let feed = json!({ "feed": { "entry": [{ "content": { "src": format!("{}/rolie/sa-2.json", server.uri()) } }] } });
+        let responses = [
+            ("/provider-metadata.json", provider.to_string()),
+            ("/advisories/index.txt", "2025/sa-1.json\n2025/missing.json\n".to_string()),
+            ("/advisories/2025/sa-1.json", advisory("SA-1", "pkg:npm/app@1.0.0").to_string()),
+            ("/feed.json", feed.to_string()),
+            ("/rolie/sa-2.json", advisory("SA-2", "pkg:npm/app@2.0.0").to_string()),
+        ];
+        for (route, body) in responses {
+            Mock::given(path(route)).respond_with(ResponseTemplate::new(200).set_body_string(body)).mount(&server).await;
+        }
+
+        let link = ArtifactLink {
+            uri: format!("{}/provider-metadata.json", server.uri()),
+            digest: None,
+            media_type: Some(CSAF_MEDIA_TYPE.to_string()),
+            expected_signer_identity: None,
+            access: None,
+            request_url: None,
+        };
+        let linked = load(&link, &Fetchers::new(reqwest::Client::new())).await.unwrap();
+        assert_eq!(linked.documents.len(), 2);
+        assert_eq!(linked.errors.len(), 1, "the missing advisory");
+
+        let release = ReleaseIdentity { purls: vec![PackageUrl::from_str("pkg:npm/app@1.0.0").unwrap()], cpe: None };
+        let advisories: Vec<CsafAdvisory> =
+            linked.documents.iter().filter_map(|(uri, document)| document.advisory_for(uri, &release)).collect();
+        assert_eq!(advisories.len(), 1);
+        assert_eq!(advisories[0].id, "SA-1");
+        assert_eq!(advisories[0].severity.as_deref(), Some("Important"));
+        assert_eq!(
+            advisories[0].vulnerabilities,
+            [
+                CsafVulnerability { id: "CVE-2025-0001".to_string(), status: "known_affected".to_string() },
+                CsafVulnerability { id: "CVE-2025-0002".to_string(), status: "fixed".to_string() },
+            ]
+        );
+    }
+}
@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! DANE: checking the TLS certificates of attestation hosts against their TLSA records.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use chainsights_core::tlsa::TlsaRecord;
+use futures::future::BoxFuture;
+
+use crate::cache::TtlCache;
+
+/// How long a host's TLSA records are reused before they are looked up again.
+const TLSA_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Looks up DNS TLSA records. `SystemResolver` asks the system's configured DNS servers, which
+/// should validate DNSSEC for the answers to mean anything.
+pub trait TlsaResolver: Send + Sync {
+    /// Returns the TLSA records of `name`, e.g. `_443._tcp.example.com`.
+    fn tlsa_lookup<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<TlsaRecord>>>;
+}
+
+/// Requires every HTTPS response to come from a server whose certificate matches the TLSA
+/// records of its host. Hosts without usable TLSA records, and plain HTTP, are rejected.
+pub(crate) struct DaneCheck {
+    resolver: Arc<dyn TlsaResolver>,
+    records: TtlCache<Arc<Vec<TlsaRecord>>>,
+}
+
+impl DaneCheck {
+    pub(crate) fn new(resolver: Arc<dyn TlsaResolver>) -> Self {
+        Self { resolver, records: TtlCache::new() }
+    }
+
+    /// Checks the certificate a response for `url` was served with against the host's records.
+    pub(crate) async fn check(&self, url: &reqwest::Url, peer_certificate: Option<&[u8]>) -> Result<()> {
+        if url.scheme() != "https" {
+            bail!("'{}' is not served over HTTPS, which DANE requires", url);
+        }
+        let host = url.host_str().context("URL has no host")?;
+        let name = format!("_{}._tcp.{}", url.port_or_known_default().unwrap_or(443), host);
+        let records = match self.records.get(&name) {
+            Some(records) => records,
+            None => {
+                let records = Arc::new(
+                    self.resolver
+                        .tlsa_lookup(&name)
+                        .await
+                        .with_context(|| format!("TLSA lookup for {} failed", name))?,
+                );
+                self.records.insert(&name, records.clone(), TLSA_CACHE_TTL);
+                records
+            }
+        };
+        if records.is_empty() {
+            bail!("{} publishes no TLSA records", name);
+        }
+        let end_entity: Vec<&TlsaRecord> = records.iter().filter(|record| record.is_end_entity()).collect();
+        if end_entity.is_empty() {
+            bail!("{} only has trust anchor TLSA records (usage 0 or 2), which are not supported", name);
+        }
+        let certificate = peer_certificate.with_context(|| format!("No TLS certificate was presented by {}", host))?;
+        for record in end_entity {
+            if record.matches(certificate)? {
+                return Ok(());
+            }
+        }
+        bail!("The TLS certificate of {} does not match its TLSA records at {}", host, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chainsights_testing::EphemeralSigner;
+    use sha2::{Digest, Sha256, Sha512};
+
+    use super::*;
+
+    struct StaticTlsa(HashMap<String, Vec<TlsaRecord>>);
+
+    impl TlsaResolver for StaticTlsa {
+        fn tlsa_lookup<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<TlsaRecord>>> {
+            Box::pin(async move { Ok(self.0.get(name).cloned().unwrap_or_default()) })
+        }
+    }
+
+    fn certificate() -> Vec<u8> {
+        let pem = EphemeralSigner::new("host@example.test").unwrap().certificate_pem();
+        pem::parse(pem).unwrap().into_contents()
+    }
+
+    #[tokio::test]
+    async fn certificates_must_match_end_entity_records() {
+        let (served, other) = (certificate(), certificate());
+        let record = |usage, matching_type, data: &[u8]| TlsaRecord { usage, selector: 0, matching_type, data: data.to_vec() };
+        let dane = DaneCheck::new(Arc::new(StaticTlsa(HashMap::from([
+            ("_443._tcp.a.example.test".to_string(), vec![record(3, 1, &Sha256::digest(&served))]),
+            ("_8443._tcp.a.example.test".to_string(), vec![record(1, 2, &Sha512::digest(&served))]),
+            ("_443._tcp.b.example.test".to_string(), vec![record(2, 1, &Sha256::digest(&served))]),
+        ]))));
+        let url = |url: &str| reqwest::Url::parse(url).unwrap();
+
+        assert!(dane.check(&url("https://a.example.test/x.json"), Some(&served)).await.is_ok());
+        assert!(dane.check(&url("https://a.example.test:8443/x.json"), Some(&served)).await.is_ok());
+        assert!(dane.check(&url("https://a.example.test/x.json"), Some(&other)).await.is_err());
+        assert!(dane.check(&url("https://a.example.test/x.json"), None).await.is_err());
+        assert!(dane.check(&url("http://a.example.test/x.json"), Some(&served)).await.is_err());
+        assert!(dane.check(&url("https://b.example.test/x.json"), Some(&served)).await.is_err());
+        assert!(dane.check(&url("https://c.example.test/x.json"), Some(&served)).await.is_err());
+    }
+
+    #[test]
+    fn parses_presentation_format() {
+        let record: TlsaRecord = "3 1 1 0c72ac70b745ac19998811b131d662c9 ac69dbdbe7cb23e5b514b56664c5d3d6".parse().unwrap();
+        assert_eq!((record.usage, record.selector, record.matching_type, record.data.len()), (3, 1, 1, 32));
+        assert!("3 1 1 zz".parse::<TlsaRecord>().is_err());
+        assert!("3 1".parse::<TlsaRecord>().is_err());
+    }
+}
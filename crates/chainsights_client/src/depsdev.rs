@@ -0,0 +1,304 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use anyhow::{Context, Result, anyhow};
+use packageurl::PackageUrl;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+
+use crate::fetch::{Fetchers, fetch_and_verify_artifact};
+use crate::generate::sbom::sbom_purl;
+use crate::models::aggregation::{AggregatedCatalogData, AggregatedComponentData, AggregatedReleaseData, DepsDevEnrichment};
+use crate::output::{self, Status};
+
+/// The public deps.dev API.
+pub(crate) const DEFAULT_DEPS_DEV_URL: &str = "https://api.deps.dev/v3";
+
+pub(crate) const SBOM_MEDIA_TYPES: &[&str] = &["application/spdx+json", "application/vnd.cyclonedx+json"];
+
+/// A package version as returned by `GET /systems/{system}/packages/{name}/versions/{version}`.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct DepsDevVersion {
+    #[serde(default)]
+    licenses: Vec<String>,
+    #[serde(default)]
+    advisory_keys: Vec<DepsDevKey>,
+    #[serde(default)]
+    related_projects: Vec<DepsDevRelatedProject>,
+}
+
+impl DepsDevVersion {
+    fn source_repository(&self) -> Option<String> {
+        self.related_projects
+            .iter()
+            .find(|p| p.relation_type == "SOURCE_REPO")
+            .map(|p| p.project_key.id.clone())
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct DepsDevKey {
+    id: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct DepsDevRelatedProject {
+    project_key: DepsDevKey,
+    relation_type: String,
+}
+
+/// A project as returned by `GET /projects/{id}`.
+#[derive(Deserialize, Debug)]
+struct DepsDevProject {
+    scorecard: Option<DepsDevScorecard>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct DepsDevScorecard {
+    date: Option<String>,
+    overall_score: Option<f64>,
+}
+
+/// A resolved dependency graph as returned by `GET .../versions/{version}:dependencies`.
+#[derive(Deserialize, Debug)]
+struct DepsDevDependencies {
+    #[serde(default)]
+    nodes: Vec<DepsDevNode>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct DepsDevNode {
+    version_key: DepsDevVersionKey,
+    relation: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct DepsDevVersionKey {
+    name: String,
+    version: String,
+}
+
+/// Looks up ecosystem packages on deps.dev.
+pub(crate) struct DepsDevClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl DepsDevClient {
+    pub(crate) fn new(client: reqwest::Client, base_url: &str) -> Self {
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Collects licenses, advisories, Scorecard, and dependency data for an ecosystem PURL.
+    pub(crate) async fn lookup(&self, purl: &str) -> Result<DepsDevEnrichment> {
+        let (system, name, version) = deps_dev_key(purl)?;
+        let package = self.version(system, &name, &version).await?;
+        let source_repository = package.source_repository();
+        let scorecard = match &source_repository {
+            Some(project) => {
+                let project: DepsDevProject = self.get_json(&self.url(&["projects", project])?).await?;
+                project.scorecard
+            }
+            None => None,
+        };
+
+        // Dependency graphs are only resolved for some ecosystems; their absence is not an error.
+        let dependencies_url = self.url(&[
+            "systems",
+            system,
+            "packages",
+            &name,
+            "versions",
+            &format!("{}:dependencies", version),
+        ])?;
+        let nodes = self
+            .get_json::<DepsDevDependencies>(&dependencies_url)
+            .await
+            .map(|d| d.nodes)
+            .unwrap_or_default();
+
+        Ok(DepsDevEnrichment {
+            purl: purl.to_string(),
+            licenses: package.licenses,
+            advisories: package.advisory_keys.into_iter().map(|k| k.id).collect(),
+            source_repository,
+            scorecard_score: scorecard.as_ref().and_then(|s| s.overall_score),
+            scorecard_date: scorecard.and_then(|s| s.date),
+            direct_dependencies: nodes
+                .iter()
+                .filter(|n| n.relation == "DIRECT")
+                .map(|n| format!("{}@{}", n.version_key.name, n.version_key.version))
+                .collect(),
+            indirect_dependency_count: nodes.iter().filter(|n| n.relation == "INDIRECT").count(),
+        })
+    }
+
+    /// Returns the source repository deps.dev links to an ecosystem PURL (e.g., github.com/owner/repo).
+    pub(crate) async fn source_repository(&self, purl: &str) -> Result<Option<String>> {
+        let (system, name, version) = deps_dev_key(purl)?;
+        Ok(self.version(system, &name, &version).await?.source_repository())
+    }
+
+    async fn version(&self, system: &str, name: &str, version: &str) -> Result<DepsDevVersion> {
+        let url = self.url(&["systems", system, "packages", name, "versions", version])?;
+        self.get_json(&url).await
+    }
+
+    /// Builds an API URL, percent-encoding each path segment.
+    fn url(&self, segments: &[&str]) -> Result<reqwest::Url> {
+        let mut url = reqwest::Url::parse(&self.base_url)
+            .with_context(|| format!("Invalid deps.dev URL '{}'", self.base_url))?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("Invalid deps.dev URL '{}'", self.base_url))?
+            .extend(segments);
+        Ok(url)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &reqwest::Url) -> Result<T> {
+        self.client
+            .get(url.clone())
+            .send()
+            .await
+            .with_context(|| format!("Failed to send request to '{}'", url))?
+            .error_for_status()
+            .with_context(|| format!("deps.dev request to '{}' failed", url))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse deps.dev response from '{}'", url))
+    }
+}
+
+/// Enriches every release in the aggregate with deps.dev data.
+pub(crate) async fn enrich_with_deps_dev(
+    aggregated_data: &mut AggregatedCatalogData,
+    deps_dev: &DepsDevClient,
+    fetchers: &Fetchers,
+) {
+    for component in &mut aggregated_data.components {
+        enrich_component(component, deps_dev, fetchers).await;
+    }
+}
+
+/// Enriches a component's releases with deps.dev data for their ecosystem PURLs (see
+/// [`release_ecosystem_purls`]).
+pub(crate) async fn enrich_component(
+    component: &mut AggregatedComponentData,
+    deps_dev: &DepsDevClient,
+    fetchers: &Fetchers,
+) {
+    let alias_purls = component_alias_purls(component);
+    for release in &mut component.releases {
+        for purl in release_ecosystem_purls(&alias_purls, release, fetchers).await {
+            if deps_dev_key(&purl).is_err() {
+                continue;
+            }
+            output::print(1, Status::Info, &format!("Looking up {} on deps.dev", purl));
+            match deps_dev.lookup(&purl).await {
+                Ok(enrichment) => release.deps_dev.push(enrichment),
+                Err(e) => release.enrichment_errors.push((purl, format!("{:#}", e))),
+            }
+        }
+    }
+}
+
+/// The component's alias PURLs that name ecosystem packages.
+pub(crate) fn component_alias_purls(component: &AggregatedComponentData) -> Vec<String> {
+    component
+        .component_predicate
+        .as_ref()
+        .map(|c| c.ecosystem_purls().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// The PURLs a release may be known by in package ecosystems: the release PURL, the component's
+/// alias PURLs (combined with the release version), and the primary package of any SBOM linked
+/// from the release. SBOMs that cannot be read are recorded as enrichment errors.
+pub(crate) async fn release_ecosystem_purls(
+    alias_purls: &[String],
+    release: &mut AggregatedReleaseData,
+    fetchers: &Fetchers,
+) -> Vec<String> {
+    let Some(release_purl) = release.release_predicate.as_ref().map(|r| r.purl.clone()) else {
+        return Vec::new();
+    };
+    let Some(version) = PackageUrl::from_str(&release_purl)
+        .ok()
+        .and_then(|p| p.version().map(str::to_string))
+    else {
+        return Vec::new();
+    };
+
+    // The release PURL itself counts when it is an ecosystem PURL rather than pkg:chainsights.
+    let mut purls = vec![release_purl];
+    purls.extend(alias_purls
+        .iter()
+        .filter_map(|alias| {
+            let mut purl = PackageUrl::from_str(alias).ok()?;
+            if purl.version().is_some() {
+                return None;
+            }
+            purl.with_version(version.clone());
+            Some(purl.to_string())
+        }));
+    for link in &release.metadata_artifacts {
+        if !link
+            .media_type
+            .as_deref()
+            .is_some_and(|t| SBOM_MEDIA_TYPES.contains(&t))
+            || link.is_gated()
+        {
+            continue;
+        }
+        match fetch_and_verify_artifact(link, fetchers)
+            .await
+            .and_then(|bytes| sbom_purl(&bytes))
+        {
+            Ok(Some(purl)) => purls.push(purl),
+            Ok(None) => {}
+            // Each enrichment reads the SBOMs again, but their errors need only be recorded once.
+            Err(_) if release.enrichment_errors.iter().any(|(uri, _)| *uri == link.uri) => {}
+            Err(e) => release.enrichment_errors.push((link.uri.clone(), format!("{:#}", e))),
+        }
+    }
+    purls.sort();
+    purls.dedup();
+    purls
+}
+
+/// Maps an ecosystem PURL onto the deps.dev (system, package name, version) triple.
+fn deps_dev_key(purl: &str) -> Result<(&'static str, String, String)> {
+    let parsed =
+        PackageUrl::from_str(purl).with_context(|| format!("Invalid PURL syntax: '{}'", purl))?;
+    let version = parsed
+        .version()
+        .ok_or_else(|| anyhow!("PURL '{}' has no version", purl))?
+        .to_string();
+    let namespace = parsed.namespace();
+    let name = parsed.name();
+    let (system, name) = match parsed.ty() {
+        "npm" => ("npm", join_namespace(namespace, name, "/")),
+        "pypi" => ("pypi", name.to_string()),
+        "cargo" => ("cargo", name.to_string()),
+        "gem" => ("rubygems", name.to_string()),
+        "nuget" => ("nuget", name.to_string()),
+        "maven" => ("maven", join_namespace(namespace, name, ":")),
+        "golang" => ("go", join_namespace(namespace, name, "/")),
+        other => return Err(anyhow!("deps.dev does not index '{}' packages", other)),
+    };
+    Ok((system, name, version))
+}
+
+fn join_namespace(namespace: Option<&str>, name: &str, separator: &str) -> String {
+    match namespace {
+        Some(namespace) => format!("{}{}{}", namespace, separator, name),
+        None => name.to_string(),
+    }
+}
@@ -0,0 +1,328 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow};
+use chainsights_core::identity::{SignerIdentity, normalize_domain};
+use chainsights_core::txt::{ChainsightsRecord, DelegationRecord};
+use futures::future::BoxFuture;
+
+use crate::dns::TxtResolver;
+use crate::fetch::{Fetchers, fetch_domain_records};
+#[cfg(feature = "cli")]
+use crate::output;
+use crate::output::Status;
+use crate::models::aggregation::{AggregatedCatalogData, NamespaceDelegation, RootDiscovery};
+#[cfg(feature = "cli")]
+use crate::pins::RootPins;
+use crate::traversal::{TraversalOptions, traverse_and_aggregate};
+
+/// A protocol for finding and walking a supplier's published component and release data.
+pub(crate) trait Discovery: Send + Sync {
+    /// Discovers everything the domain publishes and aggregates it into the common output shape.
+    fn discover<'a>(
+        &'a self,
+        domain: &'a str,
+        fetchers: &'a Fetchers,
+    ) -> BoxFuture<'a, Result<AggregatedCatalogData>>;
+}
+
+/// The error for a root that must not be traversed: one outside its delegated namespace, or
+/// contradicting a pin. Other protocols must not be tried instead, since whoever could change the
+/// domain's record may well control its homepage too.
+#[derive(Debug)]
+pub(crate) struct RefusedRoot(pub String);
+
+impl std::fmt::Display for RefusedRoot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for RefusedRoot {}
+
+/// Delegations are followed at most this many subdomain levels below the queried domain.
+const MAX_DELEGATION_DEPTH: usize = 4;
+
+/// The Chainsights scheme: a `_chainsights` TXT record pointing at a signed root catalog.
+pub(crate) struct ChainsightsDiscovery {
+    options: TraversalOptions,
+    resolver: Arc<dyn TxtResolver>,
+    follow_delegations: bool,
+    #[cfg(feature = "cli")]
+    root_pins: Option<RootPins>,
+}
+
+impl ChainsightsDiscovery {
+    pub(crate) fn new(options: TraversalOptions, resolver: Arc<dyn TxtResolver>) -> Self {
+        Self {
+            options,
+            resolver,
+            follow_delegations: false,
+            #[cfg(feature = "cli")]
+            root_pins: None,
+        }
+    }
+
+    /// Checks the root each domain's record advertises against `root_pins` before traversing it,
+    /// and pins it once its root catalog verified.
+    #[cfg(feature = "cli")]
+    pub(crate) fn pinning_roots(self, root_pins: Option<RootPins>) -> Self {
+        Self { root_pins, ..self }
+    }
+
+    /// Also traverses the roots of the subdomains a domain delegates its namespace to, and
+    /// includes their components in the domain's aggregate.
+    pub(crate) fn following_delegations(self, follow_delegations: bool) -> Self {
+        Self { follow_delegations, ..self }
+    }
+
+    /// Discovers `domain`, whose root must stay within `delegation` if it was delegated to, and
+    /// the subdomains it delegates to. Returns the aggregate and the domain's root record.
+    fn discover_domain<'a>(
+        &'a self,
+        domain: &'a str,
+        delegation: Option<&'a DelegationRecord>,
+        depth: usize,
+        fetchers: &'a Fetchers,
+    ) -> BoxFuture<'a, Result<(AggregatedCatalogData, Option<ChainsightsRecord>)>> {
+        Box::pin(async move {
+            let records = fetch_domain_records(self.resolver.as_ref(), domain)
+                .await
+                .with_context(|| format!("Failed to fetch root info for domain '{}'", domain))?;
+            let source = format!("_chainsights.{}", normalize_domain(domain)?);
+            let (mut aggregate, root) = match records.root {
+                Ok(root) => {
+                    if let Some(delegation) = delegation
+                        && !delegation.permits(&root.identity)
+                    {
+                        return Err(RefusedRoot(format!(
+                            "The root of '{}' is signed by '{}', which is outside the delegated namespace{}",
+                            domain,
+                            root.identity,
+                            delegation.identity.as_ref().map(|pinned| format!(" (the delegation pins '{}')", pinned)).unwrap_or_default()
+                        ))
+                        .into());
+                    }
+                    #[cfg(feature = "cli")]
+                    let change = match &self.root_pins {
+                        Some(pins) => pins.check(domain, &root)?,
+                        None => None,
+                    };
+                    // Roots are only pinned by the CLI, which keeps the pins in a state file.
+                    #[cfg(not(feature = "cli"))]
+                    let change: Option<crate::models::aggregation::RootChange> = None;
+                    if let Some(change) = &change {
+                        self.options.hooks().message(0, Status::Failed, &change.to_string());
+                    }
+                    let mut aggregate = self.traverse_root(domain, &root, fetchers).await?;
+                    #[cfg(feature = "cli")]
+                    if let Some(pins) = &self.root_pins
+                        && aggregate.verification.as_ref().is_some_and(|v| v.is_verified())
+                        && pins.record(domain, &root)?
+                    {
+                        let pinned = match change.is_some() {
+                            true => "Accepted the changed root",
+                            false => "Pinned the root",
+                        };
+                        self.options.hooks().message(0, Status::Info, &format!("{} of '{}': {} at {}", pinned, domain, root.identity, root.uri));
+                    }
+                    aggregate.root_changes.extend(change);
+                    (aggregate, Some(root))
+                }
+                Err(e) if self.follow_delegations && !records.delegations.is_empty() => {
+                    self.options.hooks().message(0, Status::Info, &format!("'{}' has no root catalog of its own ({:#}); following its delegations", domain, e));
+                    (AggregatedCatalogData::default(), None)
+                }
+                Err(e) if !records.delegations.is_empty() => {
+                    return Err(e.context(format!(
+                        "'{}' only delegates to subdomains ({}); pass --follow-delegations to traverse them",
+                        domain,
+                        delegated_namespaces(&records.delegations)
+                    )));
+                }
+                Err(e) => return Err(e).with_context(|| format!("Failed to fetch root info for domain '{}'", domain)),
+            };
+            aggregate.discovery = Some(RootDiscovery::dns_txt(&source));
+
+            if !self.follow_delegations {
+                if !records.delegations.is_empty() {
+                    self.options.hooks().message(0, Status::Info, &format!(
+                        "'{}' delegates {}; pass --follow-delegations to include them",
+                        domain,
+                        delegated_namespaces(&records.delegations)
+                    ));
+                }
+                return Ok((aggregate, root));
+            }
+            for delegation in &records.delegations {
+                self.follow_delegation(domain, &source, delegation, depth, fetchers, &mut aggregate).await;
+            }
+            Ok((aggregate, root))
+        })
+    }
+
+    async fn traverse_root(&self, domain: &str, root: &ChainsightsRecord, fetchers: &Fetchers) -> Result<AggregatedCatalogData> {
+        self.options.hooks().message(0, Status::Info, &format!(
+            "Traversing from root URI: {} with expected identity: {}",
+            root.uri, root.identity
+        ));
+        let root_identities = [SignerIdentity::new(&root.identity)];
+        let options = self.options.scoped_to(domain).pinned_to(root.key.clone());
+        traverse_and_aggregate(&root.uri, &root_identities, fetchers, &options)
+            .await
+            .with_context(|| format!("Traversal failed starting from {}", root.uri))
+    }
+
+    /// Discovers a delegated subdomain and adds its components and errors to the delegating
+    /// domain's aggregate, recording the delegation and why it was not followed, if it was not.
+    async fn follow_delegation(
+        &self,
+        domain: &str,
+        source: &str,
+        delegation: &DelegationRecord,
+        depth: usize,
+        fetchers: &Fetchers,
+        aggregate: &mut AggregatedCatalogData,
+    ) {
+        let mut entry = NamespaceDelegation {
+            namespace: delegation.namespace.clone(),
+            source: source.to_string(),
+            root_uri: None,
+            identity: None,
+            error: None,
+        };
+        self.options.hooks().message(0, Status::Info, &format!("Following delegation of '{}' from '{}'", delegation.namespace, domain));
+        let delegated = match normalize_domain(&delegation.namespace) {
+            Ok(namespace) if !namespace.ends_with(&format!(".{}", normalize_domain(domain).unwrap_or_default())) => {
+                Err(anyhow!("'{}' is not a subdomain of '{}'", namespace, domain))
+            }
+            Ok(_) if depth >= MAX_DELEGATION_DEPTH => Err(anyhow!("Delegations nest more than {} levels deep", MAX_DELEGATION_DEPTH)),
+            Ok(namespace) => {
+                let delegation = DelegationRecord { namespace: namespace.clone(), identity: delegation.identity.clone() };
+                self.discover_domain(&namespace, Some(&delegation), depth + 1, fetchers).await
+            }
+            Err(e) => Err(e),
+        };
+        match delegated {
+            Ok((delegated, root)) => {
+                entry.root_uri = root.as_ref().map(|root| root.uri.clone());
+                entry.identity = root.map(|root| root.identity);
+                match delegated.root_error {
+                    Some(root_error) => entry.error = Some(root_error),
+                    None => {
+                        aggregate.components.extend(delegated.components);
+                        aggregate.component_errors.extend(delegated.component_errors);
+                        aggregate.expired_components.extend(delegated.expired_components);
+                        aggregate.integrity_errors.extend(delegated.integrity_errors);
+                        aggregate.relationships.extend(delegated.relationships);
+                        aggregate.delegations.push(entry);
+                        aggregate.delegations.extend(delegated.delegations);
+                        aggregate.root_changes.extend(delegated.root_changes);
+                        return;
+                    }
+                }
+            }
+            Err(e) => entry.error = Some(format!("{:#}", e)),
+        }
+        if let Some(error) = &entry.error {
+            self.options.hooks().message(1, Status::Failed, &format!("Delegation of '{}' not followed: {}", entry.namespace, error));
+        }
+        aggregate.delegations.push(entry);
+    }
+}
+
+fn delegated_namespaces(delegations: &[DelegationRecord]) -> String {
+    delegations.iter().map(|delegation| delegation.namespace.as_str()).collect::<Vec<_>>().join(", ")
+}
+
+impl Discovery for ChainsightsDiscovery {
+    fn discover<'a>(
+        &'a self,
+        domain: &'a str,
+        fetchers: &'a Fetchers,
+    ) -> BoxFuture<'a, Result<AggregatedCatalogData>> {
+        Box::pin(async move { Ok(self.discover_domain(domain, None, 0, fetchers).await?.0) })
+    }
+}
+
+/// Tries each protocol in order, returning the result of the first one that can discover the domain.
+#[cfg(feature = "cli")]
+pub(crate) struct FirstDiscovery {
+    protocols: Vec<Box<dyn Discovery>>,
+}
+
+#[cfg(feature = "cli")]
+impl FirstDiscovery {
+    pub(crate) fn new(protocols: Vec<Box<dyn Discovery>>) -> Self {
+        Self { protocols }
+    }
+}
+
+#[cfg(feature = "cli")]
+impl Discovery for FirstDiscovery {
+    fn discover<'a>(
+        &'a self,
+        domain: &'a str,
+        fetchers: &'a Fetchers,
+    ) -> BoxFuture<'a, Result<AggregatedCatalogData>> {
+        Box::pin(async move {
+            for protocol in &self.protocols {
+                match protocol.discover(domain, fetchers).await {
+                    Ok(data) => return Ok(data),
+                    Err(e) if e.downcast_ref::<RefusedRoot>().is_some() => return Err(e),
+                    Err(e) => output::print(1, Status::Failed, &format!("Discovery failed: {:#}", e)),
+                }
+            }
+            Err(anyhow!("No discovery protocol succeeded for domain '{}'", domain))
+        })
+    }
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::*;
+
+    /// A protocol that fails with `error`, or finds an empty aggregate if there is none, and
+    /// records that it was tried.
+    struct Stub {
+        error: Option<fn() -> anyhow::Error>,
+        tried: Arc<AtomicBool>,
+    }
+
+    impl Discovery for Stub {
+        fn discover<'a>(&'a self, _: &'a str, _: &'a Fetchers) -> BoxFuture<'a, Result<AggregatedCatalogData>> {
+            Box::pin(async move {
+                self.tried.store(true, Ordering::SeqCst);
+                match self.error {
+                    Some(error) => Err(error()),
+                    None => Ok(AggregatedCatalogData::default()),
+                }
+            })
+        }
+    }
+
+    /// Discovers example.com with a protocol failing with `error`, then one that succeeds.
+    /// Returns whether discovery succeeded and whether the second protocol was tried.
+    async fn discover_after(error: fn() -> anyhow::Error) -> (bool, bool) {
+        let fallback = Arc::new(AtomicBool::new(false));
+        let first = Stub { error: Some(error), tried: Arc::new(AtomicBool::new(false)) };
+        let second = Stub { error: None, tried: fallback.clone() };
+        let discovery = FirstDiscovery::new(vec![Box::new(first), Box::new(second)]);
+        let result = discovery.discover("example.com", &Fetchers::new(reqwest::Client::new())).await;
+        (result.is_ok(), fallback.load(Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_the_next_protocol_on_failure() {
+        assert_eq!(discover_after(|| anyhow!("no _chainsights record")).await, (true, true));
+    }
+
+    #[tokio::test]
+    async fn refused_roots_are_not_discovered_another_way() {
+        let refused = || anyhow::Error::from(RefusedRoot("pinned to another identity".to_string())).context("Failed to discover");
+        assert_eq!(discover_after(refused).await, (false, false));
+    }
+}
@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! DNS TXT lookups for discovering a domain's root catalog, and TLSA lookups for DANE.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use chainsights_core::tlsa::TlsaRecord;
+use futures::future::BoxFuture;
+use hickory_resolver::TokioResolver;
+
+use crate::cache::TtlCache;
+use crate::dane::TlsaResolver;
+
+/// Looks up DNS TXT records. [`SystemResolver`] asks the system's configured DNS servers; other
+/// implementations let tests and offline environments answer `_chainsights` lookups themselves.
+pub trait TxtResolver: Send + Sync {
+    /// Returns the TXT records of `name`, each with its character strings joined.
+    fn txt_lookup<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<String>>>;
+
+    /// Like [`txt_lookup`](Self::txt_lookup), also returning how long the answer may be cached.
+    /// Resolvers that do not know return `None`.
+    fn txt_lookup_with_ttl<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<(Vec<String>, Option<Duration>)>> {
+        Box::pin(async move { Ok((self.txt_lookup(name).await?, None)) })
+    }
+}
+
+impl<R: TxtResolver + ?Sized> TxtResolver for Arc<R> {
+    fn txt_lookup<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<String>>> {
+        (**self).txt_lookup(name)
+    }
+
+    fn txt_lookup_with_ttl<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<(Vec<String>, Option<Duration>)>> {
+        (**self).txt_lookup_with_ttl(name)
+    }
+}
+
+/// Resolves TXT records with the system's DNS configuration.
+#[derive(Default)]
+pub struct SystemResolver;
+
+impl SystemResolver {
+    /// Reads the system's DNS configuration (e.g., `/etc/resolv.conf`) without sending a query.
+    pub fn check_configuration(&self) -> Result<()> {
+        TokioResolver::builder_tokio()?;
+        Ok(())
+    }
+}
+
+impl TxtResolver for SystemResolver {
+    fn txt_lookup<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<String>>> {
+        Box::pin(async move { Ok(self.txt_lookup_with_ttl(name).await?.0) })
+    }
+
+    fn txt_lookup_with_ttl<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<(Vec<String>, Option<Duration>)>> {
+        Box::pin(async move {
+            let resolver = TokioResolver::builder_tokio()?.build();
+            let txt_lookup = resolver.txt_lookup(name).await?;
+            let ttl = txt_lookup.valid_until().saturating_duration_since(Instant::now());
+            let records = txt_lookup
+                .iter()
+                .map(|txt_record| {
+                    txt_record
+                        .txt_data()
+                        .iter()
+                        .map(|b| String::from_utf8_lossy(b))
+                        .collect::<Vec<_>>()
+                        .join("")
+                })
+                .collect();
+            Ok((records, Some(ttl)))
+        })
+    }
+}
+
+impl TlsaResolver for SystemResolver {
+    fn tlsa_lookup<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<TlsaRecord>>> {
+        Box::pin(async move {
+            let resolver = TokioResolver::builder_tokio()?.build();
+            let records = match resolver.tlsa_lookup(name).await {
+                Ok(lookup) => lookup
+                    .iter()
+                    .map(|tlsa| TlsaRecord {
+                        usage: tlsa.cert_usage().into(),
+                        selector: tlsa.selector().into(),
+                        matching_type: tlsa.matching().into(),
+                        data: tlsa.cert_data().to_vec(),
+                    })
+                    .collect(),
+                Err(e) if e.is_no_records_found() => Vec::new(),
+                Err(e) => return Err(e.into()),
+            };
+            Ok(records)
+        })
+    }
+}
+
+/// Answers TXT lookups from a fixed table instead of DNS, for tests and offline use.
+#[derive(Default)]
+pub struct StaticResolver {
+    records: HashMap<String, Vec<String>>,
+}
+
+impl StaticResolver {
+    /// A resolver that knows the given records, keyed by the full name
+    /// (e.g. `_chainsights.example.com`).
+    pub fn new(records: HashMap<String, Vec<String>>) -> Self {
+        Self { records }
+    }
+}
+
+impl TxtResolver for StaticResolver {
+    fn txt_lookup<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<String>>> {
+        let name = name.trim_end_matches('.').to_ascii_lowercase();
+        let records = self.records.get(&name).cloned().unwrap_or_default();
+        Box::pin(async move { Ok(records) })
+    }
+}
+
+/// Remembers another resolver's answers for as long as their DNS TTL allows, up to a maximum, so
+/// that repeated queries of the same domains do not repeat identical lookups. Failed lookups are
+/// not cached.
+pub struct CachingResolver {
+    inner: Arc<dyn TxtResolver>,
+    max_ttl: Duration,
+    answers: TtlCache<Vec<String>>,
+}
+
+impl CachingResolver {
+    /// Caches the answers of `inner` for at most `max_ttl`. Answers from resolvers that do not
+    /// report a TTL are kept for `max_ttl`.
+    pub fn new(inner: impl TxtResolver + 'static, max_ttl: Duration) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            max_ttl,
+            answers: TtlCache::new(),
+        }
+    }
+}
+
+impl TxtResolver for CachingResolver {
+    fn txt_lookup<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<String>>> {
+        Box::pin(async move { Ok(self.txt_lookup_with_ttl(name).await?.0) })
+    }
+
+    fn txt_lookup_with_ttl<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<(Vec<String>, Option<Duration>)>> {
+        Box::pin(async move {
+            let key = name.trim_end_matches('.').to_ascii_lowercase();
+            if let Some(records) = self.answers.get(&key) {
+                return Ok((records, None));
+            }
+            let (records, ttl) = self.inner.txt_lookup_with_ttl(name).await?;
+            let ttl = ttl.map_or(self.max_ttl, |ttl| ttl.min(self.max_ttl));
+            self.answers.insert(&key, records.clone(), ttl);
+            Ok((records, Some(ttl)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Answers every lookup with one record and counts the lookups.
+    struct CountingResolver {
+        lookups: Arc<AtomicUsize>,
+        ttl: Option<Duration>,
+    }
+
+    impl TxtResolver for CountingResolver {
+        fn txt_lookup<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<String>>> {
+            Box::pin(async move { Ok(self.txt_lookup_with_ttl(name).await?.0) })
+        }
+
+        fn txt_lookup_with_ttl<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<(Vec<String>, Option<Duration>)>> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move { Ok((vec![format!("uri=https://{}/catalog.json", name)], self.ttl)) })
+        }
+    }
+
+    #[tokio::test]
+    async fn caching_resolver_respects_record_ttl() {
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let resolver = CachingResolver::new(
+            CountingResolver { lookups: lookups.clone(), ttl: Some(Duration::from_secs(300)) },
+            Duration::from_secs(3600),
+        );
+        resolver.txt_lookup("_chainsights.example.com").await.unwrap();
+        resolver.txt_lookup("_chainsights.EXAMPLE.com.").await.unwrap();
+        assert_eq!(lookups.load(Ordering::SeqCst), 1);
+        let (_, ttl) = resolver.txt_lookup_with_ttl("_chainsights.other.com").await.unwrap();
+        assert_eq!(ttl, Some(Duration::from_secs(300)));
+
+        let expired = CachingResolver::new(
+            CountingResolver { lookups: lookups.clone(), ttl: Some(Duration::ZERO) },
+            Duration::from_secs(3600),
+        );
+        expired.txt_lookup("_chainsights.example.com").await.unwrap();
+        expired.txt_lookup("_chainsights.example.com").await.unwrap();
+        assert_eq!(lookups.load(Ordering::SeqCst), 4);
+    }
+}
@@ -0,0 +1,250 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! User-provided commands run when a traversal verifies a node, fails its policy, or finds that
+//! something changed, for quick integrations with ticketing systems and chat alerts. Each command
+//! runs through the shell with the event as JSON on standard input.
+
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, Command};
+
+use crate::models::aggregation::{AggregatedCatalogData, NodeVerification};
+use crate::output::{self, Status};
+
+/// How long a hook may run before it is killed.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The events hooks run on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Hook {
+    /// The root catalog, a component or a release verified
+    NodeVerified,
+    /// A traversal fell short of the command's thresholds or policy
+    PolicyViolation,
+    /// A root changed, a catalog was rolled back, or the log shows an unpublished entry
+    Change,
+}
+
+impl Hook {
+    fn name(self) -> &'static str {
+        match self {
+            Hook::NodeVerified => "on-node-verified",
+            Hook::PolicyViolation => "on-policy-violation",
+            Hook::Change => "on-change",
+        }
+    }
+}
+
+/// The command to run for each hook, if any.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ExecHooks {
+    pub on_node_verified: Option<String>,
+    pub on_policy_violation: Option<String>,
+    pub on_change: Option<String>,
+}
+
+impl ExecHooks {
+    fn command(&self, hook: Hook) -> Option<&str> {
+        match hook {
+            Hook::NodeVerified => self.on_node_verified.as_deref(),
+            Hook::PolicyViolation => self.on_policy_violation.as_deref(),
+            Hook::Change => self.on_change.as_deref(),
+        }
+    }
+
+    /// Runs the command of `hook`, if it has one, with `event` on standard input. A hook that
+    /// fails or times out only causes a warning.
+    pub(crate) async fn run(&self, hook: Hook, event: &Value) {
+        let Some(command) = self.command(hook) else {
+            return;
+        };
+        if let Err(e) = exec(command, hook, event.to_string().into_bytes(), HOOK_TIMEOUT).await {
+            output::eprint(0, Status::Warning, &format!("Hook {} failed: {:#}", hook.name(), e));
+        }
+    }
+
+    /// Runs on-node-verified for every verified node of a traversal of `domain`, and on-change
+    /// for the root changes it found.
+    pub(crate) async fn traversed(&self, domain: &str, data: &AggregatedCatalogData) {
+        if self.on_node_verified.is_some() {
+            for event in node_events(domain, data) {
+                self.run(Hook::NodeVerified, &event).await;
+            }
+        }
+        self.root_changes(domain, data).await;
+    }
+
+    /// Runs on-change for the root changes a traversal of `domain` found.
+    pub(crate) async fn root_changes(&self, domain: &str, data: &AggregatedCatalogData) {
+        for change in &data.root_changes {
+            self.changed(domain, "root-changed", &change.to_string(), json!(change)).await;
+        }
+    }
+
+    /// Runs on-policy-violation for a traversal of `domain` that failed its checks.
+    pub(crate) async fn policy_violation(&self, domain: &str, error: &anyhow::Error) {
+        self.run(Hook::PolicyViolation, &json!({
+            "hook": Hook::PolicyViolation.name(),
+            "domain": domain,
+            "violation": format!("{:#}", error),
+        }))
+        .await;
+    }
+
+    /// Runs on-change for a change of `kind` (e.g., "catalog-rollback") found for `domain`.
+    pub(crate) async fn changed(&self, domain: &str, kind: &str, summary: &str, details: Value) {
+        self.run(Hook::Change, &json!({
+            "hook": Hook::Change.name(),
+            "domain": domain,
+            "change": kind,
+            "summary": summary,
+            "details": details,
+        }))
+        .await;
+    }
+}
+
+/// The on-node-verified events of a traversal: the root catalog, then each component followed
+/// by its releases, for the nodes that verified and were not revoked.
+pub(crate) fn node_events(domain: &str, data: &AggregatedCatalogData) -> Vec<Value> {
+    let verified = |verification: &Option<NodeVerification>| verification.as_ref().is_some_and(NodeVerification::is_verified);
+    let event = |kind: &str, uri: &str, node: Value| {
+        json!({ "hook": Hook::NodeVerified.name(), "domain": domain, "kind": kind, "uri": uri, "node": node })
+    };
+    let mut events = Vec::new();
+    if let Some(catalog) = data.catalog_predicate.as_deref().filter(|_| verified(&data.verification)) {
+        let uri = data.provenance.as_ref().map(|provenance| provenance.statement.uri.as_str()).unwrap_or_default();
+        events.push(event("catalog", uri, json!({ "predicate": catalog, "verification": data.verification })));
+    }
+    for component in &data.components {
+        if verified(&component.verification) && component.revocation.is_none() {
+            let mut node = json!(component);
+            if let Some(node) = node.as_object_mut() {
+                node.remove("releases");
+            }
+            events.push(event("component", &component.component_link_uri, node));
+        }
+        for release in component.releases.iter().filter(|release| verified(&release.verification) && release.revocation.is_none()) {
+            events.push(event("release", &release.release_link_uri, json!(release)));
+        }
+    }
+    events
+}
+
+/// Runs `command` through the shell with `input` on standard input and `CHAINSIGHTS_HOOK` set to
+/// the hook's name, killing it if it runs longer than `timeout`. Its output goes to standard
+/// error, so that it cannot corrupt JSON output.
+async fn exec(command: &str, hook: Hook, input: Vec<u8>, timeout: Duration) -> Result<()> {
+    let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    let mut shell = Command::new(shell);
+    shell
+        .args([flag, command])
+        .env("CHAINSIGHTS_HOOK", hook.name())
+        .stdin(Stdio::piped())
+        .stdout(std::io::stderr())
+        .kill_on_drop(true);
+    // Its own process group, so that a timeout kills whatever the command started too.
+    #[cfg(unix)]
+    shell.process_group(0);
+    let mut child = shell.spawn().with_context(|| format!("Failed to run '{}'", command))?;
+    // Written from another task so that a command that does not read its input cannot block
+    // the traversal; it then just sees a closed pipe.
+    let mut stdin = child.stdin.take().context("The hook's standard input is not a pipe")?;
+    tokio::spawn(async move {
+        let _ = stdin.write_all(&input).await;
+    });
+
+    match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(status) => {
+            let status = status?;
+            if !status.success() {
+                bail!("'{}' exited with {}", command, status);
+            }
+            Ok(())
+        }
+        Err(_) => {
+            kill(&mut child).await;
+            bail!("'{}' did not finish within {}s and was killed", command, timeout.as_secs());
+        }
+    }
+}
+
+/// Kills a hook's shell and, on Unix, every process in its process group.
+async fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        let _ = Command::new("kill").args(["-s", "KILL", "--", &format!("-{}", pid)]).status().await;
+    }
+    let _ = child.kill().await;
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(unix)]
+    use tempfile::TempDir;
+
+    use crate::models::aggregation::{AggregatedComponentData, AggregatedReleaseData};
+
+    use super::*;
+
+    #[test]
+    fn verified_nodes_are_handed_to_the_hook_command() {
+        let verification: NodeVerification = serde_json::from_value(json!({ "method": "sigstore-keyless" })).unwrap();
+        let release = |uri: &str, verified: bool| AggregatedReleaseData {
+            release_link_uri: uri.to_string(),
+            verification: verified.then(|| verification.clone()),
+            ..Default::default()
+        };
+        let data = AggregatedCatalogData {
+            components: vec![AggregatedComponentData {
+                component_link_uri: "https://example.com/components/app.jsonl".to_string(),
+                verification: Some(verification.clone()),
+                releases: vec![release("https://example.com/releases/app/1.0.0.jsonl", true), release("https://example.com/releases/app/2.0.0.jsonl", false)],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let events = node_events("example.com", &data);
+        let nodes: Vec<(&str, &str)> = events.iter().map(|event| (event["kind"].as_str().unwrap(), event["uri"].as_str().unwrap())).collect();
+        assert_eq!(nodes, [("component", "https://example.com/components/app.jsonl"), ("release", "https://example.com/releases/app/1.0.0.jsonl")]);
+        assert!(events[0]["node"].get("releases").is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn hook_commands_receive_the_event_on_standard_input() {
+        let dir = TempDir::new().unwrap();
+        let received = dir.path().join("event.json");
+        let command = format!("printf '%s ' \"$CHAINSIGHTS_HOOK\" > '{}'; cat >> '{}'", received.display(), received.display());
+        let event = json!({ "kind": "release", "uri": "https://example.com/releases/app/1.0.0.jsonl" });
+        exec(&command, Hook::NodeVerified, event.to_string().into_bytes(), HOOK_TIMEOUT).await.unwrap();
+        let text = std::fs::read_to_string(&received).unwrap();
+        let (hook, received) = text.split_once(' ').unwrap();
+        assert_eq!(hook, "on-node-verified");
+        assert_eq!(serde_json::from_str::<Value>(received).unwrap(), event);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn failing_hook_commands_are_errors() {
+        assert!(exec("exit 3", Hook::Change, Vec::new(), HOOK_TIMEOUT).await.unwrap_err().to_string().contains("exited with"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn hook_commands_that_time_out_are_killed_with_what_they_started() {
+        let dir = TempDir::new().unwrap();
+        let pid_file = dir.path().join("pid");
+        let command = format!("sleep 30 & echo $! > '{}'; wait", pid_file.display());
+        let error = exec(&command, Hook::Change, Vec::new(), Duration::from_millis(500)).await.unwrap_err();
+        assert!(error.to_string().contains("was killed"), "{}", error);
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        // Killed, or a zombie nothing has reaped yet.
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid.trim())).unwrap_or_default();
+        assert!(stat.is_empty() || stat.contains(") Z "), "the command's background process was killed too: {}", stat);
+    }
+}
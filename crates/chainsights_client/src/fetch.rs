@@ -1,15 +1,208 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{anyhow, bail, Context, Result};
-use hickory_resolver::TokioResolver;
+use futures::future::BoxFuture;
+#[cfg(feature = "oci")]
+use oci_client::secrets::RegistryAuth;
+#[cfg(feature = "cli")]
 use sha2::{Digest, Sha256};
 
-use crate::ArtifactLink;
+#[cfg(feature = "cli")]
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 
-//// Fetches the first line of a json lines (jsonl) Chainsights manifest from a given URL.
-pub(crate) async fn fetch_manifest_text(url: &str) -> Result<String> {
-    let resp = reqwest::get(url).await?.error_for_status()?;
-    let body_text = resp.text().await?;
+use reqwest::tls::TlsInfo;
+
+use crate::archivista::ArchivistaFetcher;
+use crate::cache::TtlCache;
+use crate::dane::{DaneCheck, TlsaResolver};
+#[cfg(feature = "cli")]
+use crate::gitoid::{GITOID_DIGEST_KEY, GIT_BLOB_DIGEST_KEY, GitoidAlgorithm, gitoid_blob_hex, gitoid_uri, parse_gitoid_uri};
+#[cfg(feature = "dns-discovery")]
+use chainsights_core::identity::normalize_domain;
+#[cfg(feature = "dns-discovery")]
+use chainsights_core::txt::{ChainsightsRecord, DelegationRecord};
+#[cfg(feature = "dns-discovery")]
+use crate::dns::TxtResolver;
+#[cfg(feature = "oci")]
+use crate::oci::OciFetcher;
+#[cfg(feature = "cli")]
+use crate::models::chainsights::ArtifactLink;
+#[cfg(feature = "dns-discovery")]
+use crate::output::{self, Status};
+#[cfg(feature = "dns-discovery")]
+use crate::scope::RecordLookup;
+use crate::stats::StatsRecorder;
+#[cfg(feature = "cli")]
+use crate::telemetry::{Tracer, traced};
+
+/// A backend capable of retrieving the raw bytes behind a URI.
+pub(crate) trait Fetcher: Send + Sync {
+    /// Returns true if this backend knows how to retrieve the given URI.
+    fn supports(&self, uri: &str) -> bool;
+    /// Fetches the raw bytes behind the given URI.
+    fn fetch<'a>(&'a self, uri: &'a str) -> BoxFuture<'a, Result<Vec<u8>>>;
+}
+
+/// Fetches documents over plain HTTP(S).
+pub(crate) struct HttpFetcher {
+    client: reqwest::Client,
+    dane: Option<DaneCheck>,
+}
+
+impl HttpFetcher {
+    pub(crate) fn new(client: reqwest::Client) -> Self {
+        Self { client, dane: None }
+    }
+}
+
+impl Fetcher for HttpFetcher {
+    fn supports(&self, uri: &str) -> bool {
+        uri.starts_with("https://") || uri.starts_with("http://")
+    }
+
+    fn fetch<'a>(&'a self, uri: &'a str) -> BoxFuture<'a, Result<Vec<u8>>> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .get(uri)
+                .send()
+                .await
+                .with_context(|| format!("Failed to send request to URI '{}'", uri))?;
+
+            if let Some(dane) = &self.dane {
+                let peer_certificate = response.extensions().get::<TlsInfo>().and_then(TlsInfo::peer_certificate);
+                dane.check(response.url(), peer_certificate)
+                    .await
+                    .with_context(|| format!("DANE check failed for URI '{}'", uri))?;
+            }
+
+            if !response.status().is_success() {
+                return Err(anyhow!(
+                    "Failed to fetch from URI '{}': HTTP Status {}",
+                    uri,
+                    response.status()
+                ));
+            }
+
+            let bytes = response
+                .bytes()
+                .await
+                .with_context(|| format!("Failed to read bytes from URI '{}'", uri))?
+                .to_vec();
+            Ok(bytes)
+        })
+    }
+}
+
+/// Ordered set of fetch backends. The first backend that supports a URI handles it.
+pub(crate) struct Fetchers {
+    backends: Vec<Box<dyn Fetcher>>,
+    catalog_cache: Option<CatalogCache>,
+    did_documents: TtlCache<String>,
+    #[cfg(feature = "cli")]
+    tracer: Option<Arc<Tracer>>,
+    stats: Option<Arc<StatsRecorder>>,
+}
+
+/// How long a `did:web` DID document is reused before it is fetched again.
+const DID_DOCUMENT_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Catalog bundles kept between traversals. Cached bundles are verified again on every use.
+struct CatalogCache {
+    bundles: TtlCache<String>,
+    ttl: Duration,
+}
+
+impl Fetchers {
+    /// Creates the default set of backends (Archivista, OCI referrers when the `oci` feature is
+    /// enabled, then HTTP(S)).
+    pub(crate) fn new(client: reqwest::Client) -> Self {
+        Self::with_http_fetcher(client.clone(), HttpFetcher::new(client))
+    }
+
+    /// Like [`Fetchers::new`], with HTTPS responses required to match their host's TLSA records.
+    /// `client` must be built with `tls_info(true)` so that responses carry the peer certificate.
+    pub(crate) fn with_dane(client: reqwest::Client, resolver: Arc<dyn TlsaResolver>) -> Self {
+        let http = HttpFetcher { client: client.clone(), dane: Some(DaneCheck::new(resolver)) };
+        Self::with_http_fetcher(client, http)
+    }
+
+    fn with_http_fetcher(client: reqwest::Client, http: HttpFetcher) -> Self {
+        let mut backends: Vec<Box<dyn Fetcher>> = vec![Box::new(ArchivistaFetcher::new(client))];
+        #[cfg(feature = "oci")]
+        backends.push(Box::new(OciFetcher::new(RegistryAuth::Anonymous)));
+        backends.push(Box::new(http));
+        Self {
+            backends,
+            catalog_cache: None,
+            did_documents: TtlCache::new(),
+            #[cfg(feature = "cli")]
+            tracer: None,
+            stats: None,
+        }
+    }
+
+    /// Keeps the catalog bundles fetched through [`fetch_catalog_text`] for `ttl`.
+    pub(crate) fn with_catalog_cache(mut self, ttl: Duration) -> Self {
+        self.catalog_cache = Some(CatalogCache { bundles: TtlCache::new(), ttl });
+        self
+    }
+
+    /// Records a span for every fetch in `tracer`'s trace.
+    #[cfg(feature = "cli")]
+    pub(crate) fn with_tracer(mut self, tracer: Option<Arc<Tracer>>) -> Self {
+        self.tracer = tracer;
+        self
+    }
+
+    /// Counts every fetch and cache hit in `stats`.
+    #[cfg(any(feature = "cli", test))]
+    pub(crate) fn with_stats(mut self, stats: Option<Arc<StatsRecorder>>) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    fn record_cache_hit(&self, uri: &str) {
+        if let Some(stats) = &self.stats {
+            stats.record_cache_hit(uri);
+        }
+    }
+
+    /// Fetches the raw bytes behind a URI using the first backend that supports its scheme.
+    pub(crate) async fn fetch(&self, uri: &str) -> Result<Vec<u8>> {
+        let backend = self
+            .backends
+            .iter()
+            .find(|b| b.supports(uri))
+            .ok_or_else(|| anyhow!("No fetch backend supports URI '{}'", uri))?;
+        let started = std::time::Instant::now();
+        let fetch = backend.fetch(uri);
+        #[cfg(feature = "cli")]
+        let fetch = {
+            let attributes = || {
+                let host = reqwest::Url::parse(uri).ok().and_then(|url| url.host_str().map(str::to_string));
+                let mut attributes = vec![("url.full", uri.into())];
+                attributes.extend(host.map(|host| ("server.address", host.into())));
+                attributes
+            };
+            traced(self.tracer.as_deref(), "fetch", attributes, fetch, |bytes| {
+                vec![("http.response.body.size", bytes.len().into())]
+            })
+        };
+        let result = fetch.await;
+        if let Some(stats) = &self.stats {
+            stats.record_request(uri, started.elapsed(), result.as_ref().ok().map(Vec::len));
+        }
+        result
+    }
+}
+
+/// Fetches the first line of a json lines (jsonl) Chainsights manifest from a given URL.
+pub(crate) async fn fetch_manifest_text(fetchers: &Fetchers, url: &str) -> Result<String> {
+    let bytes = fetchers.fetch(url).await?;
+    let body_text = String::from_utf8(bytes).context("Manifest is not valid UTF-8")?;
     let first_line = body_text
         .lines()
         .find(|l| !l.trim().is_empty())
@@ -21,103 +214,252 @@ pub(crate) async fn fetch_manifest_text(url: &str) -> Result<String> {
     Ok(first_line.to_string())
 }
 
-/// Parses a Chainsights PURL and extracts the domain, component name, and optional version.
-pub(crate) async fn fetch_chainsights_info(domain_name: &str) -> Result<(String, String)> {
-    let chainsights_domain = format!("_chainsights.{}", domain_name);
-    println!("  Querying TXT record for: {}", chainsights_domain);
-    let resolver = TokioResolver::builder_tokio()?.build();
-    let txt_lookup = resolver.txt_lookup(&chainsights_domain).await?;
-    for txt_record in txt_lookup.iter() {
-        let combined_data = txt_record
-            .txt_data()
-            .iter()
-            .map(|b| String::from_utf8_lossy(b))
-            .collect::<Vec<_>>()
-            .join("");
-        if combined_data.contains("uri=") && combined_data.contains("identity=") {
-            let mut uri = None;
-            let mut identity = None;
-            for part in combined_data.split_whitespace() {
-                if let Some(u) = part.strip_prefix("uri=") {
-                    uri = Some(u.trim_matches('"').to_string());
-                } else if let Some(id) = part.strip_prefix("identity=") {
-                    identity = Some(id.trim_matches('"').to_string());
-                }
+/// Fetches a whole document as text, for PGP-signed statements and signatures, whose signatures
+/// cover every line rather than the first.
+pub(crate) async fn fetch_document_text(fetchers: &Fetchers, url: &str) -> Result<String> {
+    let bytes = fetchers.fetch(url).await?;
+    String::from_utf8(bytes).context("Document is not valid UTF-8")
+}
+
+/// Like [`fetch_manifest_text`], but answered from the catalog cache when one is configured.
+pub(crate) async fn fetch_catalog_text(fetchers: &Fetchers, url: &str) -> Result<String> {
+    let Some(cache) = &fetchers.catalog_cache else {
+        return fetch_manifest_text(fetchers, url).await;
+    };
+    if let Some(text) = cache.bundles.get(url) {
+        fetchers.record_cache_hit(url);
+        return Ok(text);
+    }
+    let text = fetch_manifest_text(fetchers, url).await?;
+    cache.bundles.insert(url, text.clone(), cache.ttl);
+    Ok(text)
+}
+
+/// Fetches the DID document at `url`, reusing it for a few minutes since every statement a DID
+/// signs needs it.
+pub(crate) async fn fetch_did_document(fetchers: &Fetchers, url: &str) -> Result<String> {
+    if let Some(document) = fetchers.did_documents.get(url) {
+        fetchers.record_cache_hit(url);
+        return Ok(document);
+    }
+    let document = fetch_document_text(fetchers, url).await?;
+    fetchers.did_documents.insert(url, document.clone(), DID_DOCUMENT_CACHE_TTL);
+    Ok(document)
+}
+
+/// Looks up and parses a domain's `_chainsights` TXT record. Other TXT records at the name are
+/// ignored; a malformed Chainsights record is an error unless another one is valid.
+#[cfg(feature = "dns-discovery")]
+pub(crate) async fn fetch_chainsights_info(resolver: &dyn TxtResolver, domain_name: &str) -> Result<ChainsightsRecord> {
+    fetch_domain_records(resolver, domain_name).await?.root
+}
+
+#[cfg(feature = "dns-discovery")]
+impl<R: TxtResolver> RecordLookup for R {
+    fn chainsights_record<'a>(&'a self, domain: &'a str) -> BoxFuture<'a, Result<ChainsightsRecord>> {
+        Box::pin(fetch_chainsights_info(self, domain))
+    }
+}
+
+/// A domain's `_chainsights` TXT records: its root record, or why it has no valid one, and the
+/// subdomains it delegates to.
+#[cfg(feature = "dns-discovery")]
+pub(crate) struct DomainRecords {
+    pub root: Result<ChainsightsRecord>,
+    pub delegations: Vec<DelegationRecord>,
+}
+
+/// Looks up a domain's `_chainsights` TXT records. Malformed delegation records are reported and
+/// skipped.
+#[cfg(feature = "dns-discovery")]
+pub(crate) async fn fetch_domain_records(resolver: &dyn TxtResolver, domain_name: &str) -> Result<DomainRecords> {
+    // TXT records for internationalized domains live under the punycode name.
+    let chainsights_domain = format!("_chainsights.{}", normalize_domain(domain_name)?);
+    output::print(1, Status::Info, &format!("Querying TXT record for: {}", chainsights_domain));
+    let txt_records = resolver.txt_lookup(&chainsights_domain).await?;
+    let mut root = None;
+    let mut malformed = None;
+    let mut delegations = Vec::new();
+    for record in txt_records {
+        match DelegationRecord::parse(&[&record]) {
+            Ok(Some(delegation)) => {
+                delegations.push(delegation);
+                continue;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                output::print(1, Status::Warning, &format!("Skipping malformed delegation record for {}: '{}': {:#}", chainsights_domain, record, e));
+                continue;
+            }
+        }
+        match ChainsightsRecord::parse(&[&record]) {
+            Ok(Some(record)) => {
+                root.get_or_insert(record);
             }
-            if let (Some(uri_val), Some(identity_val)) = (uri, identity) {
-                if !uri_val.is_empty() && !identity_val.is_empty() {
-                    return Ok((uri_val, identity_val));
-                }
+            Ok(None) => {}
+            Err(e) => {
+                malformed.get_or_insert(e.context(format!("Malformed TXT record for {}: '{}'", chainsights_domain, record)));
             }
         }
     }
-    bail!("No valid chainsights TXT record for {}", chainsights_domain)
+    let root = match (root, malformed) {
+        (Some(record), _) => Ok(record),
+        (None, Some(e)) => Err(e),
+        (None, None) => Err(anyhow!("No valid chainsights TXT record for {}", chainsights_domain)),
+    };
+    Ok(DomainRecords { root, delegations })
 }
 
+#[cfg(feature = "cli")]
 pub(crate) async fn fetch_and_verify_artifact(
     link: &ArtifactLink,
-    client: &reqwest::Client,
+    fetchers: &Fetchers,
 ) -> Result<Vec<u8>> {
     // (i) Fetch Artifact Content
-    let response = client
-        .get(&link.uri)
-        .send()
-        .await
-        .with_context(|| format!("Failed to send request to artifact URI '{}'", link.uri))?;
-
-    if !response.status().is_success() {
-        return Err(anyhow!(
-            "Failed to fetch artifact from URI '{}': HTTP Status {}",
-            link.uri,
-            response.status()
-        ));
-    }
-
-    let bytes = response
-        .bytes()
+    let bytes = fetchers
+        .fetch(&link.uri)
         .await
-        .with_context(|| format!("Failed to read artifact bytes from URI '{}'", link.uri))?
-        .to_vec(); // Collect bytes into a Vec<u8>
+        .with_context(|| format!("Failed to fetch artifact from URI '{}'", link.uri))?;
 
     // (ii) Calculate Hash & (iii) Compare Hashes
-    // Currently supports only sha256, but extensible via the HashMap
     // This is mostly only useful for non-signed artifacts.
-    if let Some(expected_sha256_hex) = link.digest.as_ref().and_then(|digest| digest.get("sha256"))
-    {
-        if expected_sha256_hex.is_empty() {
-            return Err(anyhow!(
-                "Empty expected sha256 digest provided for URI '{}'",
-                link.uri
-            ));
-        }
-
-        let mut hasher = Sha256::new(); // [16]
-        hasher.update(&bytes); // [16]
-        let calculated_hash = hasher.finalize(); // [16]
-
-        // Convert calculated hash to lowercase hex string [18]
-        let calculated_sha256_hex = hex::encode(calculated_hash);
-
-        // Compare (case-insensitive recommended for robustness)
-        if !calculated_sha256_hex.eq_ignore_ascii_case(expected_sha256_hex) {
-            return Err(anyhow!(
-                "Digest mismatch for URI '{}'. Expected sha256: {}, Calculated: {}",
-                link.uri,
-                expected_sha256_hex,
-                calculated_sha256_hex
-            ));
-        }
-        println!("SHA256 verified for: {}", link.uri); // Log success
-    } else {
-        // Behavior if no sha256 digest is provided:
+    let verified = match &link.digest {
+        Some(digest) => verify_digests(&link.uri, &bytes, digest)?,
+        None => false,
+    };
+    if !verified {
+        // Behavior if no supported digest is provided:
         // Option 1: Fail - require at least one known digest
-        // return Err(anyhow!("No 'sha256' digest found in MetadataLink for URI '{}'. Cannot verify integrity.", link.uri));
+        // return Err(anyhow!("No supported digest found in MetadataLink for URI '{}'. Cannot verify integrity.", link.uri));
         // Option 2: Warn and proceed (less secure)
-        eprintln!(
-            "Warning: No sha256 digest provided for URI '{}'. Skipping integrity check.",
-            link.uri
+        output::eprint(
+            1,
+            Status::Warning,
+            &format!("No sha256 or gitoid digest provided for URI '{}'. Skipping integrity check.", link.uri),
         );
     }
 
     Ok(bytes)
-}
\ No newline at end of file
+}
+
+/// Checks fetched bytes against every supported entry of a digest map: `sha256`, `gitBlob` (the
+/// SHA-1 gitoid), and `gitoid` (an OmniBOR identifier such as `gitoid:blob:sha256:<hex>`). Other
+/// algorithms are ignored. Returns false if no supported digest was present.
+#[cfg(feature = "cli")]
+pub(crate) fn verify_digests(uri: &str, bytes: &[u8], digest: &HashMap<String, String>) -> Result<bool> {
+    let mut verified = false;
+    for (algorithm, expected) in digest {
+        let Some(calculated) = calculate_digest(uri, algorithm, expected, bytes)? else {
+            continue;
+        };
+        if expected.is_empty() {
+            bail!("Empty expected {} digest provided for URI '{}'", algorithm, uri);
+        }
+        if !calculated.eq_ignore_ascii_case(expected) {
+            bail!(
+                "Digest mismatch for URI '{}'. Expected {}: {}, Calculated: {}",
+                uri,
+                algorithm,
+                expected,
+                calculated
+            );
+        }
+        output::print(1, Status::Verified, &format!("{} verified for: {}", algorithm, uri));
+        verified = true;
+    }
+    Ok(verified)
+}
+
+/// Calculates the `algorithm` digest of `bytes` in the form `expected` is written in, or None if
+/// the algorithm is not supported.
+#[cfg(feature = "cli")]
+pub(crate) fn calculate_digest(uri: &str, algorithm: &str, expected: &str, bytes: &[u8]) -> Result<Option<String>> {
+    Ok(Some(match algorithm {
+        "sha256" => hex::encode(Sha256::digest(bytes)),
+        GIT_BLOB_DIGEST_KEY => gitoid_blob_hex(bytes, GitoidAlgorithm::Sha1),
+        GITOID_DIGEST_KEY => {
+            let (gitoid_algorithm, _) = parse_gitoid_uri(expected)
+                .with_context(|| format!("Invalid gitoid digest for URI '{}'", uri))?;
+            gitoid_uri(bytes, gitoid_algorithm)
+        }
+        _ => return Ok(None),
+    }))
+}
+
+#[cfg(all(test, feature = "dns-discovery"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::dns::StaticResolver;
+
+    use super::*;
+
+    async fn lookup(records: &[&str]) -> Result<ChainsightsRecord> {
+        let records = records.iter().map(|r| r.to_string()).collect();
+        let resolver = StaticResolver::new(HashMap::from([("_chainsights.example.com".to_string(), records)]));
+        fetch_chainsights_info(&resolver, "example.com").await
+    }
+
+    #[tokio::test]
+    async fn parses_versioned_escaped_and_legacy_records() {
+        let record = lookup(&["v=spf1 -all", "v=chainsights1 identity=Release` Team` ``QA`` key=SHA256:abc= uri=https://example.com/c.json?a=b"])
+            .await
+            .unwrap();
+        assert_eq!(record.uri, "https://example.com/c.json?a=b");
+        assert_eq!(record.identity, "Release Team `QA`");
+        assert_eq!(record.key.as_deref(), Some("SHA256:abc="));
+        assert_eq!(ChainsightsRecord::parse(&[record.to_string()]).unwrap(), Some(record));
+
+        // Strings of a multi-string record are concatenated before parsing.
+        let split = ChainsightsRecord::parse(&["v=chainsights1 uri=https://exam", "ple.com/c.json identity=a@example.com"]);
+        assert_eq!(split.unwrap().unwrap().uri, "https://example.com/c.json");
+
+        let legacy = lookup(&["identity=a@example.com uri=https://example.com/c.json"]).await.unwrap();
+        assert_eq!(legacy.identity, "a@example.com");
+    }
+
+    #[tokio::test]
+    async fn malformed_records_are_reported() {
+        for (record, error) in [
+            ("v=chainsights2 uri=https://example.com/c.json identity=a@example.com", "Unsupported record version"),
+            ("v=chainsights1 uri=https://example.com/c.json", "no 'identity'"),
+            ("v=chainsights1 uri=a uri=b identity=a@example.com", "more than once"),
+            ("v=chainsights1 uri= identity=a@example.com", "empty value"),
+            ("v=chainsights1 uri=https://example.com/c.json identity", "not an attribute=value pair"),
+            ("v=chainsights1 uri=https://example.com/c.json identity=a`", "unfinished backquote"),
+            ("uri=https://example.com/c.json v=chainsights1 identity=a@example.com", "must come first"),
+        ] {
+            let e = lookup(&[record]).await.unwrap_err();
+            assert!(format!("{:#}", e).contains(error), "{}: {:#}", record, e);
+        }
+        assert!(lookup(&["google-site-verification=abc", "not a key value record"]).await.unwrap_err().to_string().contains("No valid"));
+        // A valid record wins over a malformed one.
+        assert!(lookup(&["v=chainsights1 uri=a", "v=chainsights1 uri=b identity=a@example.com"]).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn separates_delegations_from_the_root_record() {
+        let records = [
+            "v=chainsights1 uri=https://example.com/c.json identity=a@example.com",
+            "v=chainsights1 delegate=cloud.example.com",
+            "v=chainsights1 delegate=store.example.com identity=https://github.com/acme/store/.github/workflows/release.yml@refs/heads/main",
+            "v=chainsights1 delegate=bad.example.com uri=https://example.com/other.json",
+        ];
+        let resolver = StaticResolver::new(HashMap::from([(
+            "_chainsights.example.com".to_string(),
+            records.iter().map(|r| r.to_string()).collect(),
+        )]));
+        let DomainRecords { root, delegations } = fetch_domain_records(&resolver, "example.com").await.unwrap();
+        assert_eq!(root.unwrap().identity, "a@example.com");
+        let [cloud, store] = &delegations[..] else {
+            panic!("expected two valid delegations, got {:?}", delegations);
+        };
+        assert!(cloud.permits("security@cloud.example.com"));
+        assert!(cloud.permits("https://ci.eu.cloud.example.com/workflows/release"));
+        assert!(!cloud.permits("security@example.com"));
+        assert!(!cloud.permits("security@evilcloud.example.com"));
+        assert!(store.permits("https://github.com/acme/store/.github/workflows/release.yml@refs/heads/main"));
+        assert!(!store.permits("security@store.example.com"));
+        assert_eq!(store.to_string(), records[2]);
+    }
+}
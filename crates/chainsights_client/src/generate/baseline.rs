@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Authoring OpenSSF Baseline attestations by walking through the Baseline controls.
+
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use serde::Deserialize;
+
+use crate::generate::write_statement;
+use crate::init::Prompter;
+use crate::models::baseline::{BASELINE_MANUAL, BaselineControl, BaselineEvidence, BaselinePredicate, BaselineVerifier};
+
+/// A control to assess: its ID and what it requires.
+#[derive(Deserialize)]
+pub(crate) struct ControlSpec {
+    pub(crate) control: String,
+    pub(crate) title: String,
+}
+
+/// The Maturity Level 1 controls of the OpenSSF Baseline (OSPS Baseline 2025-02-25).
+const LEVEL_1_CONTROLS: &[(&str, &str)] = &[
+    ("OSPS-AC-01.01", "Multi-factor authentication is required to access sensitive resources"),
+    ("OSPS-AC-02.01", "New collaborators are given the lowest available privileges by default"),
+    ("OSPS-AC-03.01", "Direct commits to the primary branch are prevented"),
+    ("OSPS-AC-03.02", "The primary branch cannot be deleted"),
+    ("OSPS-BR-01.01", "CI/CD pipelines sanitize and validate untrusted input"),
+    ("OSPS-BR-01.02", "CI/CD pipelines sanitize and validate branch names"),
+    ("OSPS-BR-03.01", "Official project URIs are only served over encrypted channels"),
+    ("OSPS-BR-03.02", "Distribution channels only serve over encrypted channels"),
+    ("OSPS-DO-01.01", "User guides describe basic functionality"),
+    ("OSPS-DO-02.01", "There is a documented guide for reporting defects"),
+    ("OSPS-GV-02.01", "There are public mechanisms for discussing changes and obstacles"),
+    ("OSPS-GV-03.01", "The contribution process is documented"),
+    ("OSPS-LE-02.01", "The source code is under an OSI or FSF approved license"),
+    ("OSPS-LE-02.02", "Released software assets are under an OSI or FSF approved license"),
+    ("OSPS-LE-03.01", "The repository includes its license in a well-known location"),
+    ("OSPS-LE-03.02", "Released software assets include their license"),
+    ("OSPS-QA-01.01", "The source code is publicly readable at a static URL"),
+    ("OSPS-QA-01.02", "The repository keeps a public record of every change, its author and time"),
+    ("OSPS-QA-02.01", "Direct dependencies are listed in a standard dependency file"),
+    ("OSPS-QA-04.01", "Any subprojects are listed in the project documentation"),
+    ("OSPS-QA-05.01", "No generated executable artifacts are kept in the repository"),
+    ("OSPS-QA-05.02", "No unreviewable binary artifacts are kept in the repository"),
+    ("OSPS-VM-02.01", "The project documents security contacts"),
+];
+
+/// The controls to assess: those in a JSON file of `{"control", "title"}` objects, or the
+/// Baseline's Maturity Level 1 controls.
+pub(crate) fn control_specs(path: Option<&Path>) -> Result<Vec<ControlSpec>> {
+    match path {
+        Some(path) => {
+            let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read controls '{}'", path.display()))?;
+            serde_json::from_str(&text).with_context(|| format!("Failed to parse controls '{}'", path.display()))
+        }
+        None => Ok(LEVEL_1_CONTROLS
+            .iter()
+            .map(|(control, title)| ControlSpec { control: control.to_string(), title: title.to_string() })
+            .collect()),
+    }
+}
+
+/// Reads the predicate of a previous Baseline draft statement, whose answers become the defaults.
+pub(crate) fn read_previous(path: &Path) -> Result<BaselinePredicate> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read '{}'", path.display()))?;
+    let statement: serde_json::Value = serde_json::from_str(&text).with_context(|| format!("'{}' is not JSON", path.display()))?;
+    serde_json::from_value(statement["predicate"].clone())
+        .with_context(|| format!("'{}' is not a Baseline statement", path.display()))
+}
+
+/// Asks whether each control is implemented and for the evidence that it is.
+pub(crate) fn assess<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    specs: &[ControlSpec],
+    previous: Option<&BaselinePredicate>,
+) -> Result<Vec<BaselineControl>> {
+    let mut controls = Vec::new();
+    for spec in specs {
+        let before = previous.and_then(|previous| previous.controls.iter().find(|control| control.control == spec.control));
+        prompter.say(&format!("\n{}: {}", spec.control, spec.title))?;
+        let implemented = prompter.confirm("Implemented", before.is_some_and(|control| control.implemented))?;
+
+        let mut evidence = Vec::new();
+        let kept = before.and_then(|control| control.evidence.clone()).unwrap_or_default();
+        if !kept.is_empty() && prompter.confirm(&format!("Keep the {} pieces of evidence recorded before", kept.len()), true)? {
+            evidence = kept;
+        }
+        while let Some(uri) = prompter.ask_optional("Evidence URL (blank when done)")? {
+            let description = prompter.ask_optional("What it shows")?;
+            evidence.push(BaselineEvidence { description, uri: Some(uri), media_type: None });
+        }
+        controls.push(BaselineControl {
+            control: spec.control.clone(),
+            implemented,
+            evidence: (!evidence.is_empty()).then_some(evidence),
+        });
+    }
+    Ok(controls)
+}
+
+/// Writes a Baseline draft statement about `subject_uri`.
+pub(crate) fn write_baseline_draft(
+    path: &Path,
+    subject_uri: &str,
+    verifier: BaselineVerifier,
+    assessment_comment: String,
+    controls: Vec<BaselineControl>,
+) -> Result<()> {
+    let predicate = BaselinePredicate {
+        verified_timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        verifier,
+        assessment_comment,
+        controls,
+    };
+    write_statement(path, subject_uri, BASELINE_MANUAL, &predicate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previous_answers_are_the_defaults() {
+        let specs = control_specs(None).unwrap();
+        let previous = BaselinePredicate {
+            verified_timestamp: "2025-01-01T00:00:00Z".to_string(),
+            verifier: BaselineVerifier { id: "auditor@example.com".to_string(), comment: None },
+            assessment_comment: String::new(),
+            controls: vec![BaselineControl {
+                control: "OSPS-AC-01.01".to_string(),
+                implemented: true,
+                evidence: Some(vec![BaselineEvidence { description: None, uri: Some("https://example.com/mfa".to_string()), media_type: None }]),
+            }],
+        };
+        // Keep the first control's answer and evidence; the second is implemented with new evidence.
+        let mut input = "\n\n\ny\nhttps://example.com/roles\nRole defaults\n\n".to_string();
+        input.push_str(&"\n\n".repeat(specs.len() - 2));
+        let mut prompter = Prompter::new(input.as_bytes(), Vec::new());
+        let controls = assess(&mut prompter, &specs, Some(&previous)).unwrap();
+
+        assert_eq!(controls.len(), specs.len());
+        assert!(controls[0].implemented);
+        assert_eq!(controls[0].evidence.as_ref().unwrap()[0].uri.as_deref(), Some("https://example.com/mfa"));
+        assert!(controls[1].implemented);
+        assert_eq!(controls[1].evidence.as_ref().unwrap()[0].description.as_deref(), Some("Role defaults"));
+        assert!(controls[2..].iter().all(|control| !control.implemented && control.evidence.is_none()));
+    }
+}
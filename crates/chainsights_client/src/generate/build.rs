@@ -0,0 +1,333 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Release artifacts read from what a build leaves behind, so their digests need not be computed
+//! by hand: a directory of release files, the package `cargo package`, `npm pack` or `mvn package`
+//! wrote, or a container image pushed by digest.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+
+use crate::generate::{artifact_digests, metadata_media_type};
+use crate::models::chainsights::ArtifactLink;
+use crate::publish::AttestationTree;
+
+const CRATES_IO_DOWNLOAD_URL: &str = "https://static.crates.io/crates";
+const NPM_REGISTRY_URL: &str = "https://registry.npmjs.org";
+const MAVEN_CENTRAL_URL: &str = "https://repo1.maven.org/maven2";
+
+/// Where a build's release artifacts are read from.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum BuildSource {
+    /// `dist:<dir>`: every file in a directory of release files, served under the artifact base URI
+    /// by its path within the directory.
+    Dist(PathBuf),
+    /// `cargo[:<dir>]`: the `.crate` files under `target/package`, as crates.io serves them.
+    Cargo(PathBuf),
+    /// `npm[:<dir>]`: the tarball `npm pack` wrote for `package.json`, as the npm registry serves it.
+    Npm(PathBuf),
+    /// `maven[:<dir>]`: the jars `mvn package` wrote to `target`, as Maven Central serves them.
+    Maven(PathBuf),
+    /// `image:<reference>@sha256:<digest>`: a container image pushed by digest.
+    Image(String),
+    /// `image-metadata:<file>`: the images in the file `docker buildx build --metadata-file` wrote.
+    ImageMetadata(PathBuf),
+}
+
+impl BuildSource {
+    pub(crate) fn parse(spec: &str) -> Result<Self> {
+        let (kind, location) = spec.split_once(':').unwrap_or((spec, ""));
+        let dir = || PathBuf::from(if location.is_empty() { "." } else { location });
+        Ok(match kind {
+            "dist" if !location.is_empty() => Self::Dist(dir()),
+            "cargo" => Self::Cargo(dir()),
+            "npm" => Self::Npm(dir()),
+            "maven" => Self::Maven(dir()),
+            "image" if location.contains("@sha256:") => Self::Image(location.to_string()),
+            "image" => bail!("Image '{}' is not pinned by digest; give it as <reference>@sha256:<digest>", location),
+            "image-metadata" if !location.is_empty() => Self::ImageMetadata(dir()),
+            _ => bail!(
+                "'{}' is not a build source; use dist:<dir>, cargo[:<dir>], npm[:<dir>], maven[:<dir>], \
+                 image:<reference>@sha256:<digest> or image-metadata:<file>",
+                spec
+            ),
+        })
+    }
+
+    /// The artifacts the build left behind. Files in a `dist` directory are linked under `base_uri`.
+    pub(crate) fn artifacts(&self, base_uri: Option<&str>) -> Result<Vec<ArtifactLink>> {
+        match self {
+            Self::Dist(dir) => {
+                let base_uri = base_uri.context("Give the URI the dist files are downloaded from with --artifact-base-uri")?;
+                let tree = AttestationTree::read(dir)?;
+                Ok(tree
+                    .documents
+                    .iter()
+                    .map(|(path, bytes)| artifact_link(format!("{}{}", base_uri, path), path, bytes))
+                    .collect())
+            }
+            Self::Cargo(dir) => cargo_artifacts(dir),
+            Self::Npm(dir) => npm_artifacts(dir),
+            Self::Maven(dir) => maven_artifacts(dir),
+            Self::Image(reference) => Ok(vec![image_artifact(reference, None)?]),
+            Self::ImageMetadata(path) => image_metadata_artifacts(path),
+        }
+    }
+}
+
+impl fmt::Display for BuildSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dist(dir) => write!(f, "dist:{}", dir.display()),
+            Self::Cargo(dir) => write!(f, "cargo:{}", dir.display()),
+            Self::Npm(dir) => write!(f, "npm:{}", dir.display()),
+            Self::Maven(dir) => write!(f, "maven:{}", dir.display()),
+            Self::Image(reference) => write!(f, "image:{}", reference),
+            Self::ImageMetadata(path) => write!(f, "image-metadata:{}", path.display()),
+        }
+    }
+}
+
+/// Links a local file served at `uri`, with its digests and the media type its name implies.
+pub(crate) fn file_artifact(uri: String, path: &Path) -> Result<ArtifactLink> {
+    let bytes = std::fs::read(path).with_context(|| format!("Failed to read artifact '{}'", path.display()))?;
+    let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    Ok(artifact_link(uri, file_name, &bytes))
+}
+
+fn artifact_link(uri: String, file_name: &str, bytes: &[u8]) -> ArtifactLink {
+    ArtifactLink {
+        uri,
+        digest: Some(artifact_digests(bytes)),
+        media_type: metadata_media_type(file_name).or_else(|| artifact_media_type(file_name)).map(str::to_string),
+        expected_signer_identity: None,
+        access: None,
+        request_url: None,
+    }
+}
+
+/// Guesses the media type of a release artifact from its file name.
+fn artifact_media_type(file_name: &str) -> Option<&'static str> {
+    let lower = file_name.to_ascii_lowercase();
+    let types = [
+        (".tar.gz", "application/gzip"),
+        (".tgz", "application/gzip"),
+        (".crate", "application/gzip"),
+        (".tar.xz", "application/x-xz"),
+        (".tar.zst", "application/zstd"),
+        (".tar", "application/x-tar"),
+        (".zip", "application/zip"),
+        (".whl", "application/zip"),
+        (".jar", "application/java-archive"),
+        (".pom", "application/xml"),
+        (".deb", "application/vnd.debian.binary-package"),
+        (".rpm", "application/x-rpm"),
+        (".apk", "application/vnd.android.package-archive"),
+        (".dmg", "application/x-apple-diskimage"),
+        (".msi", "application/x-msi"),
+        (".exe", "application/vnd.microsoft.portable-executable"),
+    ];
+    types.iter().find(|(extension, _)| lower.ends_with(extension)).map(|(_, media_type)| *media_type)
+}
+
+/// Splits a `.crate` file name (`my-crate-1.2.0-beta.1.crate`) into the crate's name and version.
+fn split_crate_file(file_name: &str) -> Option<(&str, &str)> {
+    let stem = file_name.strip_suffix(".crate")?;
+    stem.match_indices('-').map(|(index, _)| (&stem[..index], &stem[index + 1..])).find(|(name, version)| {
+        let core = version.split(['-', '+']).next().unwrap_or_default();
+        !name.is_empty() && core.split('.').count() == 3 && core.split('.').all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+    })
+}
+
+fn cargo_artifacts(dir: &Path) -> Result<Vec<ArtifactLink>> {
+    let package_dir = dir.join("target").join("package");
+    let entries = std::fs::read_dir(&package_dir)
+        .with_context(|| format!("Failed to read '{}'; run `cargo package` first", package_dir.display()))?;
+    let mut artifacts = Vec::new();
+    for entry in entries {
+        let path = entry.with_context(|| format!("Failed to read '{}'", package_dir.display()))?.path();
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else { continue };
+        if let Some((name, version)) = split_crate_file(file_name) {
+            artifacts.push(file_artifact(format!("{}/{}/{}-{}.crate", CRATES_IO_DOWNLOAD_URL, name, name, version), &path)?);
+        }
+    }
+    if artifacts.is_empty() {
+        bail!("'{}' holds no .crate files; run `cargo package` first", package_dir.display());
+    }
+    artifacts.sort_by(|a, b| a.uri.cmp(&b.uri));
+    Ok(artifacts)
+}
+
+#[derive(Deserialize)]
+struct PackageJson {
+    name: String,
+    version: String,
+}
+
+fn npm_artifacts(dir: &Path) -> Result<Vec<ArtifactLink>> {
+    let manifest = dir.join("package.json");
+    let text = std::fs::read_to_string(&manifest).with_context(|| format!("Failed to read '{}'", manifest.display()))?;
+    let package: PackageJson = serde_json::from_str(&text).with_context(|| format!("'{}' has no name and version", manifest.display()))?;
+    // `npm pack` names the tarball of @scope/name as scope-name-<version>.tgz; the registry serves
+    // it as @scope/name/-/name-<version>.tgz.
+    let packed = dir.join(format!("{}-{}.tgz", package.name.trim_start_matches('@').replace('/', "-"), package.version));
+    if !packed.exists() {
+        bail!("'{}' does not exist; run `npm pack` first", packed.display());
+    }
+    let unscoped = package.name.rsplit('/').next().unwrap_or(&package.name);
+    let uri = format!("{}/{}/-/{}-{}.tgz", NPM_REGISTRY_URL, package.name, unscoped, package.version);
+    Ok(vec![file_artifact(uri, &packed)?])
+}
+
+fn maven_artifacts(dir: &Path) -> Result<Vec<ArtifactLink>> {
+    let target = dir.join("target");
+    let properties_path = target.join("maven-archiver").join("pom.properties");
+    let text = std::fs::read_to_string(&properties_path)
+        .with_context(|| format!("Failed to read '{}'; run `mvn package` first", properties_path.display()))?;
+    let properties: HashMap<&str, &str> = text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect();
+    let property = |key: &str| properties.get(key).copied().with_context(|| format!("'{}' has no {}", properties_path.display(), key));
+    let (group_id, artifact_id, version) = (property("groupId")?, property("artifactId")?, property("version")?);
+    let base_uri = format!("{}/{}/{}/{}", MAVEN_CENTRAL_URL, group_id.replace('.', "/"), artifact_id, version);
+    let prefix = format!("{}-{}", artifact_id, version);
+
+    let mut artifacts = Vec::new();
+    for entry in std::fs::read_dir(&target).with_context(|| format!("Failed to read '{}'", target.display()))? {
+        let path = entry.with_context(|| format!("Failed to read '{}'", target.display()))?.path();
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else { continue };
+        if file_name.starts_with(&prefix) && file_name.ends_with(".jar") {
+            artifacts.push(file_artifact(format!("{}/{}", base_uri, file_name), &path)?);
+        }
+    }
+    let pom = dir.join("pom.xml");
+    if pom.exists() {
+        artifacts.push(file_artifact(format!("{}/{}.pom", base_uri, prefix), &pom)?);
+    }
+    if !artifacts.iter().any(|artifact| artifact.uri.ends_with(".jar")) {
+        bail!("'{}' holds no {}*.jar; run `mvn package` first", target.display(), prefix);
+    }
+    artifacts.sort_by(|a, b| a.uri.cmp(&b.uri));
+    Ok(artifacts)
+}
+
+/// Links an image pushed by digest as `oci://<reference>@sha256:<digest>`.
+fn image_artifact(reference: &str, media_type: Option<&str>) -> Result<ArtifactLink> {
+    let (_, digest) = reference
+        .rsplit_once("@sha256:")
+        .with_context(|| format!("Image '{}' is not pinned by digest", reference))?;
+    if digest.len() != 64 || !digest.bytes().all(|b| b.is_ascii_hexdigit()) {
+        bail!("Image '{}' has an invalid sha256 digest", reference);
+    }
+    Ok(ArtifactLink {
+        uri: format!("oci://{}", reference.trim_start_matches("oci://")),
+        digest: Some(HashMap::from([("sha256".to_string(), digest.to_ascii_lowercase())])),
+        media_type: media_type.map(str::to_string),
+        expected_signer_identity: None,
+        access: None,
+        request_url: None,
+    })
+}
+
+/// The pushed images a buildx metadata file records: one per name in `image.name`, each pinned
+/// by `containerimage.digest`.
+fn image_metadata_artifacts(path: &Path) -> Result<Vec<ArtifactLink>> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read '{}'", path.display()))?;
+    let metadata: serde_json::Value = serde_json::from_str(&text).with_context(|| format!("'{}' is not JSON", path.display()))?;
+    let digest = metadata["containerimage.digest"]
+        .as_str()
+        .with_context(|| format!("'{}' records no containerimage.digest; was the image pushed?", path.display()))?;
+    let names = metadata["image.name"].as_str().with_context(|| format!("'{}' records no image.name", path.display()))?;
+    let media_type = metadata["containerimage.descriptor"]["mediaType"].as_str();
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            // Link the repository, dropping the tag: the digest names the image.
+            let repository = match name.rsplit_once(':') {
+                Some((repository, tag)) if !tag.contains('/') => repository,
+                _ => name,
+            };
+            image_artifact(&format!("{}@{}", repository, digest), media_type)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// A build output directory holding `files`, given as paths and contents.
+    fn build_dir(files: &[(&str, &str)]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for (path, contents) in files {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
+    fn artifacts(spec: &str) -> Vec<ArtifactLink> {
+        BuildSource::parse(spec).unwrap().artifacts(Some("https://example.com/releases/1.2.0/")).unwrap()
+    }
+
+    #[test]
+    fn reads_dist_directories() {
+        let dir = build_dir(&[("dist/app-linux-amd64.tar.gz", "binary"), ("dist/sbom/app.cdx.json", "{}")]);
+        let dist = artifacts(&format!("dist:{}", dir.path().join("dist").display()));
+        assert_eq!(dist[0].uri, "https://example.com/releases/1.2.0/app-linux-amd64.tar.gz");
+        assert_eq!(dist[0].media_type.as_deref(), Some("application/gzip"));
+        assert_eq!(dist[0].digest, Some(artifact_digests(b"binary")));
+        assert_eq!(dist[1].media_type.as_deref(), Some("application/vnd.cyclonedx+json"));
+    }
+
+    #[test]
+    fn reads_package_registry_builds() {
+        let dir = build_dir(&[
+            ("target/package/my-crate-1.2.0-beta.1.crate", "crate"),
+            ("package.json", r#"{"name": "@example/app", "version": "1.2.0"}"#),
+            ("example-app-1.2.0.tgz", "tarball"),
+            ("target/maven-archiver/pom.properties", "#Created by Apache Maven\ngroupId=com.example\nartifactId=app\nversion=1.2.0\n"),
+            ("target/app-1.2.0.jar", "jar"),
+            ("pom.xml", "<project/>"),
+        ]);
+        let dir = dir.path().display();
+        let cargo = artifacts(&format!("cargo:{}", dir));
+        assert_eq!(cargo[0].uri, "https://static.crates.io/crates/my-crate/my-crate-1.2.0-beta.1.crate");
+        let npm = artifacts(&format!("npm:{}", dir));
+        assert_eq!(npm[0].uri, "https://registry.npmjs.org/@example/app/-/app-1.2.0.tgz");
+        let maven: Vec<String> = artifacts(&format!("maven:{}", dir)).into_iter().map(|a| a.uri).collect();
+        assert_eq!(maven, [
+            "https://repo1.maven.org/maven2/com/example/app/1.2.0/app-1.2.0.jar",
+            "https://repo1.maven.org/maven2/com/example/app/1.2.0/app-1.2.0.pom",
+        ]);
+    }
+
+    #[test]
+    fn reads_images_from_buildx_metadata() {
+        let digest = "a".repeat(64);
+        let metadata = serde_json::json!({
+            "containerimage.digest": format!("sha256:{}", digest),
+            "containerimage.descriptor": { "mediaType": "application/vnd.oci.image.index.v1+json" },
+            "image.name": "ghcr.io/example/app:1.2.0,registry.example.com:5000/app",
+        })
+        .to_string();
+        let dir = build_dir(&[("metadata.json", &metadata)]);
+        let images = artifacts(&format!("image-metadata:{}", dir.path().join("metadata.json").display()));
+        assert_eq!(images[0].uri, format!("oci://ghcr.io/example/app@sha256:{}", digest));
+        assert_eq!(images[1].uri, format!("oci://registry.example.com:5000/app@sha256:{}", digest));
+        assert_eq!(images[0].digest.as_ref().unwrap()["sha256"], digest);
+        assert_eq!(images[0].media_type.as_deref(), Some("application/vnd.oci.image.index.v1+json"));
+        assert!(BuildSource::parse("image:ghcr.io/example/app:1.2.0").is_err());
+    }
+}
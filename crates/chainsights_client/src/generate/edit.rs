@@ -0,0 +1,352 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Editing draft catalog and component statements, so they can be maintained without
+//! hand-editing JSON.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use chainsights_core::statement::InTotoStatement;
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::generate::write_statement;
+use crate::models::chainsights::{
+    ArtifactLink, AttestationLink, CATALOG_V2, COMPONENT_V2, CatalogComponentEntry, ChainsightsCatalogPredicate,
+    ChainsightsComponentPredicate, ChainsightsPredicate, SubCatalogLink, parse_predicate,
+};
+
+/// An unsigned statement being edited: its subject and predicate.
+pub(crate) struct Draft<T> {
+    pub(crate) subject_uri: String,
+    pub(crate) predicate: T,
+}
+
+impl Draft<ChainsightsCatalogPredicate> {
+    pub(crate) fn read_catalog(path: &Path, subject: Option<&str>) -> Result<Self> {
+        match read_draft(path, subject, CATALOG_V2)? {
+            (subject_uri, ChainsightsPredicate::Catalog(predicate)) => Ok(Self { subject_uri, predicate }),
+            (_, other) => bail!("'{}' is a {} statement, not a catalog", path.display(), other.kind()),
+        }
+    }
+
+    /// Writes the catalog as a draft statement ready for signing, stamped with the current time
+    /// and the next serial number.
+    pub(crate) fn write(&mut self, path: &Path) -> Result<()> {
+        self.predicate.timestamp = now();
+        self.predicate.serial = Some(self.predicate.serial.map_or(1, |serial| serial + 1));
+        write_statement(path, &self.subject_uri, CATALOG_V2, &self.predicate)
+    }
+}
+
+impl Draft<ChainsightsComponentPredicate> {
+    pub(crate) fn read_component(path: &Path, subject: Option<&str>) -> Result<Self> {
+        match read_draft(path, subject, COMPONENT_V2)? {
+            (subject_uri, ChainsightsPredicate::Component(predicate)) => Ok(Self { subject_uri, predicate }),
+            (_, other) => bail!("'{}' is a {} statement, not a component", path.display(), other.kind()),
+        }
+    }
+
+    /// Writes the component as a draft statement ready for signing, stamped with the current time.
+    pub(crate) fn write(&mut self, path: &Path) -> Result<()> {
+        self.predicate.timestamp = now();
+        write_statement(path, &self.subject_uri, COMPONENT_V2, &self.predicate)
+    }
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Reads an unsigned statement, or a bare predicate of `bare_predicate_type` whose subject is
+/// given by `subject`. Older predicate versions are upgraded, and written back as the current one.
+fn read_draft(path: &Path, subject: Option<&str>, bare_predicate_type: &str) -> Result<(String, ChainsightsPredicate)> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read '{}'", path.display()))?;
+    let json: Value = serde_json::from_str(&text).with_context(|| format!("'{}' is not a JSON statement", path.display()))?;
+    if json.get("dsseEnvelope").is_some() || json.get("payload").is_some() {
+        bail!("'{}' is signed; edit the unsigned statement it was signed from", path.display());
+    }
+    let (subject_uri, statement) = match json.get("predicateType") {
+        Some(_) => {
+            let subject_uri = match subject {
+                Some(subject) => subject.to_string(),
+                None => json["subject"][0]["uri"]
+                    .as_str()
+                    .or_else(|| json["subject"][0]["name"].as_str())
+                    .with_context(|| format!("The statement in '{}' has no subject", path.display()))?
+                    .to_string(),
+            };
+            let statement: InTotoStatement = serde_json::from_value(json)
+                .with_context(|| format!("'{}' is not an in-toto statement", path.display()))?;
+            (subject_uri, statement)
+        }
+        None => {
+            let subject_uri = subject
+                .with_context(|| format!("'{}' is a bare predicate; give its statement's subject with --subject", path.display()))?
+                .to_string();
+            (subject_uri.clone(), InTotoStatement::new(&subject_uri, HashMap::new(), bare_predicate_type, json))
+        }
+    };
+    let predicate = parse_predicate(&statement).with_context(|| format!("Failed to parse the predicate in '{}'", path.display()))?;
+    Ok((subject_uri, predicate))
+}
+
+/// Adds a component to a catalog. Its PURL must not already be listed.
+pub(crate) fn add_component(catalog: &mut ChainsightsCatalogPredicate, entry: CatalogComponentEntry) -> Result<()> {
+    if catalog.components.iter().any(|existing| existing.component_purl == entry.component_purl) {
+        bail!("The catalog already lists {}; remove it first to replace it", entry.component_purl);
+    }
+    catalog.components.push(entry);
+    Ok(())
+}
+
+/// Removes the component with PURL or name `component` from a catalog, returning it.
+pub(crate) fn remove_component(catalog: &mut ChainsightsCatalogPredicate, component: &str) -> Result<CatalogComponentEntry> {
+    let matches: Vec<usize> = (0..catalog.components.len())
+        .filter(|&i| catalog.components[i].component_purl == component || catalog.components[i].name == component)
+        .collect();
+    match matches[..] {
+        [index] => Ok(catalog.components.remove(index)),
+        [] => bail!("The catalog lists no component '{}'", component),
+        _ => bail!("Several components are named '{}'; give its PURL instead", component),
+    }
+}
+
+/// Adds a release link to a component, most recent first. Its URI must not already be linked.
+pub(crate) fn add_release(component: &mut ChainsightsComponentPredicate, link: AttestationLink) -> Result<()> {
+    if component.release_attestations.iter().any(|existing| existing.uri == link.uri) {
+        bail!("The component already links {}", link.uri);
+    }
+    component.release_attestations.insert(0, link);
+    Ok(())
+}
+
+/// Removes the release link with URI `uri` from a component.
+pub(crate) fn remove_release(component: &mut ChainsightsComponentPredicate, uri: &str) -> Result<AttestationLink> {
+    let index = component
+        .release_attestations
+        .iter()
+        .position(|link| link.uri == uri)
+        .with_context(|| format!("The component links no release at {}", uri))?;
+    Ok(component.release_attestations.remove(index))
+}
+
+/// Links a metadata artifact from a component, replacing any link to the same URI.
+pub(crate) fn link_metadata(component: &mut ChainsightsComponentPredicate, link: ArtifactLink) {
+    let links = component.metadata_links.get_or_insert_with(Vec::new);
+    links.retain(|existing| existing.uri != link.uri);
+    links.push(link);
+}
+
+/// How `merge_catalogs` combines team catalogs.
+pub(crate) enum MergeMode {
+    /// List every team's components in the merged catalog.
+    Components,
+    /// Link each team's catalog as a sub-catalog, published as `<base_uri>/<name>.jsonl` and
+    /// signed by `identity`, or by the identity every one of its component links expects.
+    SubCatalogs { base_uri: String, identity: Option<String> },
+}
+
+/// Composes an organization's root catalog from named team catalogs. A component PURL or
+/// sub-catalog name listed by two teams with different links is a collision, and all collisions
+/// are reported together; identical entries are listed once. Catalog-level links the teams set
+/// must agree, and paged catalogs must be merged page by page.
+pub(crate) fn merge_catalogs(catalogs: Vec<(String, ChainsightsCatalogPredicate)>, mode: MergeMode) -> Result<ChainsightsCatalogPredicate> {
+    let mut collisions = Vec::new();
+    let mut owners: HashMap<String, (&str, &CatalogComponentEntry)> = HashMap::new();
+    let mut sub_owners: HashMap<String, &str> = HashMap::new();
+    for (name, catalog) in &catalogs {
+        if catalog.next_page.is_some() || catalog.component_shards.is_some() {
+            bail!("The '{}' catalog is paged or sharded; merge its pages into one catalog first", name);
+        }
+        for entry in &catalog.components {
+            match owners.get(&entry.component_purl) {
+                Some((_, existing)) if existing.component_attestation_link.uri == entry.component_attestation_link.uri => {}
+                Some((owner, _)) => collisions.push(format!("{} is listed by both '{}' and '{}'", entry.component_purl, owner, name)),
+                None => {
+                    owners.insert(entry.component_purl.clone(), (name, entry));
+                }
+            }
+        }
+        for sub_catalog in catalog.sub_catalogs.iter().flatten() {
+            if let Some(owner) = sub_owners.insert(sub_catalog.name.to_ascii_lowercase(), name) {
+                collisions.push(format!("Sub-catalog '{}' is linked by both '{}' and '{}'", sub_catalog.name, owner, name));
+            }
+        }
+    }
+    if !collisions.is_empty() {
+        bail!("The catalogs collide:\n  {}", collisions.join("\n  "));
+    }
+
+    let mut merged = ChainsightsCatalogPredicate {
+        generator: None,
+        timestamp: now(),
+        expires: None,
+        serial: catalogs.iter().filter_map(|(_, catalog)| catalog.serial).max(),
+        components: Vec::new(),
+        sub_catalogs: None,
+        metadata_links: None,
+        revocation_link: agreed(&catalogs, "revocation link", |catalog| catalog.revocation_link.as_ref())?,
+        freshness_link: agreed(&catalogs, "freshness link", |catalog| catalog.freshness_link.as_ref())?,
+        security_contact: agreed(&catalogs, "security contact", |catalog| catalog.security_contact.as_ref())?,
+        next_page: None,
+        component_shards: None,
+    };
+    let mut sub_catalogs = Vec::new();
+    for (name, catalog) in catalogs {
+        for link in catalog.metadata_links.into_iter().flatten() {
+            let links = merged.metadata_links.get_or_insert_with(Vec::new);
+            if !links.iter().any(|existing| existing.uri == link.uri) {
+                links.push(link);
+            }
+        }
+        match &mode {
+            MergeMode::Components => {
+                for entry in catalog.components {
+                    if !merged.components.iter().any(|existing| existing.component_purl == entry.component_purl) {
+                        merged.components.push(entry);
+                    }
+                }
+                sub_catalogs.extend(catalog.sub_catalogs.into_iter().flatten());
+            }
+            MergeMode::SubCatalogs { base_uri, identity } => {
+                let identity = match identity {
+                    Some(identity) => identity.clone(),
+                    None => common_identity(catalog.components.iter().map(|entry| &entry.component_attestation_link))
+                        .with_context(|| format!("Cannot tell who signs the '{}' catalog; give its identity with --identity", name))?,
+                };
+                let uri = format!("{}/{}.jsonl", base_uri.trim_end_matches('/'), name);
+                sub_catalogs.push(SubCatalogLink { name, catalog_attestation_link: attestation_link(&uri, identity, None)? });
+            }
+        }
+    }
+    merged.sub_catalogs = (!sub_catalogs.is_empty()).then_some(sub_catalogs);
+    Ok(merged)
+}
+
+/// The value every catalog that sets a field agrees on.
+fn agreed<'a, T: Serialize + Clone + 'a>(
+    catalogs: &'a [(String, ChainsightsCatalogPredicate)],
+    field: &str,
+    get: impl Fn(&'a ChainsightsCatalogPredicate) -> Option<&'a T>,
+) -> Result<Option<T>> {
+    let mut agreed: Option<(&str, &T)> = None;
+    for (name, catalog) in catalogs {
+        let Some(value) = get(catalog) else {
+            continue;
+        };
+        match agreed {
+            Some((first, existing)) if serde_json::to_value(existing)? != serde_json::to_value(value)? => {
+                bail!("The '{}' and '{}' catalogs set different {}s; set it on one of them only", first, name, field)
+            }
+            Some(_) => {}
+            None => agreed = Some((name, value)),
+        }
+    }
+    Ok(agreed.map(|(_, value)| value.clone()))
+}
+
+/// The identity every one of `links` expects, which new links default to.
+pub(crate) fn common_identity<'a>(links: impl IntoIterator<Item = &'a AttestationLink>) -> Option<String> {
+    let mut identities = links.into_iter().map(|link| link.expected_signer_identity.as_str());
+    let first = identities.next()?;
+    identities.all(|identity| identity == first).then(|| first.to_string())
+}
+
+/// A link to a signed statement, pinned by `digest` (`sha256:<hex>`) if given.
+pub(crate) fn attestation_link(uri: &str, identity: String, digest: Option<&str>) -> Result<AttestationLink> {
+    let digest = match digest {
+        Some(digest) => {
+            let (algorithm, value) = digest.split_once(':').with_context(|| format!("Digest '{}' is not ALGORITHM:VALUE", digest))?;
+            Some(HashMap::from([(algorithm.to_ascii_lowercase(), value.to_ascii_lowercase())]))
+        }
+        None => None,
+    };
+    Ok(AttestationLink {
+        uri: uri.to_string(),
+        digest,
+        media_type: None,
+        expected_signer_identity: identity,
+        accepted_signer_identities: None,
+        expected_pgp_fingerprints: None,
+        expected_public_keys: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> ChainsightsCatalogPredicate {
+        serde_json::from_value(serde_json::json!({
+            "timestamp": "2025-01-01T00:00:00Z",
+            "components": [{
+                "name": "App",
+                "purl": "pkg:generic/example.com/app",
+                "componentAttestationLink": {
+                    "uri": "https://example.com/components/app.jsonl",
+                    "expectedSignerIdentity": "security@example.com"
+                }
+            }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn adds_and_removes_catalog_components() {
+        let mut catalog = catalog();
+        let identity = common_identity(catalog.components.iter().map(|entry| &entry.component_attestation_link));
+        assert_eq!(identity.as_deref(), Some("security@example.com"));
+        let entry = CatalogComponentEntry {
+            name: "Lib".to_string(),
+            description: None,
+            component_purl: "pkg:generic/example.com/lib".to_string(),
+            component_attestation_link: attestation_link("https://example.com/components/lib.jsonl", identity.unwrap(), Some("SHA256:AB")).unwrap(),
+            labels: None,
+        };
+        add_component(&mut catalog, entry.clone()).unwrap();
+        assert!(add_component(&mut catalog, entry).is_err());
+        assert_eq!(catalog.components[1].component_attestation_link.digest.as_ref().unwrap()["sha256"], "ab");
+
+        assert_eq!(remove_component(&mut catalog, "App").unwrap().component_purl, "pkg:generic/example.com/app");
+        assert!(remove_component(&mut catalog, "App").is_err());
+        remove_component(&mut catalog, "pkg:generic/example.com/lib").unwrap();
+        assert!(catalog.components.is_empty());
+        assert_eq!(common_identity(&[]), None);
+    }
+
+    #[test]
+    fn merges_team_catalogs_and_reports_collisions() {
+        let team = |name: &str, purl: &str, uri: &str| {
+            let mut catalog = catalog();
+            catalog.components[0].name = name.to_string();
+            catalog.components[0].component_purl = purl.to_string();
+            catalog.components[0].component_attestation_link.uri = uri.to_string();
+            catalog.serial = Some(3);
+            catalog
+        };
+        let a = team("App", "pkg:generic/example.com/app", "https://example.com/a/app.jsonl");
+        let b = team("Lib", "pkg:generic/example.com/lib", "https://example.com/b/lib.jsonl");
+
+        let merged = merge_catalogs(vec![("team-a".into(), a.clone()), ("team-b".into(), b.clone()), ("team-a2".into(), a.clone())], MergeMode::Components).unwrap();
+        assert_eq!(merged.components.len(), 2);
+        assert_eq!(merged.serial, Some(3));
+
+        let linked = merge_catalogs(
+            vec![("team-a".into(), a.clone()), ("team-b".into(), b)],
+            MergeMode::SubCatalogs { base_uri: "https://example.com/catalogs/".into(), identity: None },
+        )
+        .unwrap();
+        assert!(linked.components.is_empty());
+        let sub_catalogs = linked.sub_catalogs.unwrap();
+        assert_eq!(sub_catalogs[1].name, "team-b");
+        assert_eq!(sub_catalogs[1].catalog_attestation_link.uri, "https://example.com/catalogs/team-b.jsonl");
+        assert_eq!(sub_catalogs[1].catalog_attestation_link.expected_signer_identity, "security@example.com");
+
+        let clash = team("App", "pkg:generic/example.com/app", "https://example.com/c/app.jsonl");
+        let error = merge_catalogs(vec![("team-a".into(), a), ("team-c".into(), clash)], MergeMode::Components).unwrap_err();
+        assert!(error.to_string().contains("pkg:generic/example.com/app is listed by both 'team-a' and 'team-c'"));
+    }
+}
@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail};
+use futures::future::BoxFuture;
+use serde::Deserialize;
+
+use crate::generate::{
+    ComponentDraft, Forge, ReleaseDraft, download_digests, metadata_media_type,
+    tag_to_version,
+};
+use crate::models::chainsights::{ArtifactLink, RepositoryInfo};
+use crate::purl::slugify;
+
+/// The public GitHub REST API.
+pub(crate) const DEFAULT_GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Repository metadata as returned by `GET /repos/{owner}/{repo}`.
+#[derive(Deserialize, Debug)]
+struct GithubRepository {
+    name: String,
+    description: Option<String>,
+    html_url: String,
+    #[serde(default)]
+    topics: Vec<String>,
+}
+
+/// A release as returned by `GET /repos/{owner}/{repo}/releases`.
+#[derive(Deserialize, Debug)]
+struct GithubRelease {
+    tag_name: String,
+    name: Option<String>,
+    html_url: String,
+    published_at: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    assets: Vec<GithubAsset>,
+}
+
+/// A release asset. Newer API versions include the asset's digest (e.g., "sha256:...").
+#[derive(Deserialize, Debug)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+    content_type: Option<String>,
+    digest: Option<String>,
+}
+
+/// Reads repository metadata, releases, and release assets from the GitHub API.
+pub(crate) struct GithubForge {
+    client: reqwest::Client,
+    api_url: String,
+    token: Option<String>,
+}
+
+impl GithubForge {
+    pub(crate) fn new(client: reqwest::Client, api_url: &str, token: Option<String>) -> Self {
+        Self {
+            client,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            token,
+        }
+    }
+
+    /// Builds a component draft for `owner/name` including up to `max_releases` releases.
+    async fn repository_draft(&self, repo: &str, max_releases: usize) -> Result<ComponentDraft> {
+        if repo.split('/').count() != 2 || repo.split('/').any(str::is_empty) {
+            bail!("Repository must be of the form <owner>/<name>, found '{}'", repo);
+        }
+
+        println!("Reading repository metadata for {}", repo);
+        let repository: GithubRepository = self
+            .get_json(&format!("{}/repos/{}", self.api_url, repo))
+            .await?;
+        println!("Reading releases for {}", repo);
+        let releases: Vec<GithubRelease> = self
+            .get_json(&format!(
+                "{}/repos/{}/releases?per_page={}",
+                self.api_url,
+                repo,
+                max_releases.clamp(1, 100)
+            ))
+            .await?;
+
+        let mut release_drafts = Vec::new();
+        for release in releases.into_iter().filter(|r| !r.draft).take(max_releases) {
+            release_drafts.push(self.release_draft(release).await?);
+        }
+
+        let labels = (!repository.topics.is_empty()).then(|| {
+            repository
+                .topics
+                .iter()
+                .map(|topic| (format!("topic/{}", topic), "true".to_string()))
+                .collect::<HashMap<_, _>>()
+        });
+
+        Ok(ComponentDraft {
+            slug: slugify(&repository.name),
+            name: repository.name,
+            description: repository.description,
+            alias_purls: None,
+            repositories: vec![RepositoryInfo {
+                repo_type: "git".to_string(),
+                uri: repository.html_url,
+                paths: None,
+                primary_path: None,
+            }],
+            labels,
+            releases: release_drafts,
+        })
+    }
+
+    async fn release_draft(&self, release: GithubRelease) -> Result<ReleaseDraft> {
+        println!("  Processing release {}", release.tag_name);
+        let mut metadata_links = Vec::new();
+        let mut artifacts = Vec::new();
+        for asset in release.assets {
+            // GitHub's recorded sha256 saves a download, at the cost of the gitoid.
+            let digest = match asset.digest.as_deref().and_then(|d| d.strip_prefix("sha256:")) {
+                Some(sha256) => HashMap::from([("sha256".to_string(), sha256.to_string())]),
+                None => {
+                    download_digests(self.request(&asset.browser_download_url), &asset.browser_download_url)
+                        .await?
+                }
+            };
+            let metadata_type = metadata_media_type(&asset.name);
+            let link = ArtifactLink {
+                uri: asset.browser_download_url,
+                digest: Some(digest),
+                media_type: metadata_type.map(str::to_string).or(asset.content_type),
+                expected_signer_identity: None,
+                access: None,
+                request_url: None,
+            };
+            if metadata_type.is_some() {
+                metadata_links.push(link);
+            } else {
+                artifacts.push(link);
+            }
+        }
+
+        Ok(ReleaseDraft {
+            version: tag_to_version(&release.tag_name),
+            name: release.name.filter(|n| !n.is_empty()).unwrap_or(release.tag_name),
+            cpe: None,
+            release_date: release.published_at,
+            release_notes_uri: Some(release.html_url),
+            lifecycle_phase: Some(if release.prerelease { "beta" } else { "stable" }.to_string()),
+            metadata_links,
+            artifacts,
+        })
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        self.request(url)
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await
+            .with_context(|| format!("Failed to send request to '{}'", url))?
+            .error_for_status()
+            .with_context(|| format!("GitHub API request to '{}' failed", url))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse GitHub API response from '{}'", url))
+    }
+
+    fn request(&self, url: &str) -> reqwest::RequestBuilder {
+        // GitHub rejects requests without a User-Agent.
+        let request = self
+            .client
+            .get(url)
+            .header("User-Agent", concat!("chainsights/", env!("CARGO_PKG_VERSION")));
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+impl Forge for GithubForge {
+    fn component_draft<'a>(
+        &'a self,
+        repo: &'a str,
+        max_releases: usize,
+    ) -> BoxFuture<'a, Result<ComponentDraft>> {
+        Box::pin(self.repository_draft(repo, max_releases))
+    }
+}
@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail};
+use futures::future::BoxFuture;
+use serde::Deserialize;
+
+use crate::generate::{
+    ComponentDraft, Forge, ReleaseDraft, download_digests, metadata_media_type,
+    tag_to_version,
+};
+use crate::models::chainsights::{ArtifactLink, RepositoryInfo};
+use crate::purl::slugify;
+
+/// The gitlab.com REST API. Self-hosted instances use `https://<host>/api/v4`.
+pub(crate) const DEFAULT_GITLAB_API_URL: &str = "https://gitlab.com/api/v4";
+
+/// Project metadata as returned by `GET /projects/:id`.
+#[derive(Deserialize, Debug)]
+struct GitlabProject {
+    name: String,
+    path: String,
+    description: Option<String>,
+    web_url: String,
+    #[serde(default)]
+    topics: Vec<String>,
+}
+
+/// A release as returned by `GET /projects/:id/releases`.
+#[derive(Deserialize, Debug)]
+struct GitlabRelease {
+    tag_name: String,
+    name: Option<String>,
+    released_at: Option<String>,
+    #[serde(default)]
+    upcoming_release: bool,
+    #[serde(rename = "_links")]
+    links: Option<GitlabReleaseLinks>,
+    assets: Option<GitlabAssets>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitlabReleaseLinks {
+    #[serde(rename = "self")]
+    self_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitlabAssets {
+    #[serde(default)]
+    links: Vec<GitlabAssetLink>,
+}
+
+/// A release asset link. GitLab does not record digests, so assets are always downloaded.
+#[derive(Deserialize, Debug)]
+struct GitlabAssetLink {
+    name: String,
+    url: String,
+    direct_asset_url: Option<String>,
+}
+
+/// Reads project metadata, releases, and release asset links from the GitLab API.
+pub(crate) struct GitlabForge {
+    client: reqwest::Client,
+    api_url: String,
+    token: Option<String>,
+}
+
+impl GitlabForge {
+    pub(crate) fn new(client: reqwest::Client, api_url: &str, token: Option<String>) -> Self {
+        Self {
+            client,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            token,
+        }
+    }
+
+    /// Builds a component draft for a project path (e.g., `group/subgroup/name`).
+    async fn project_draft(&self, project: &str, max_releases: usize) -> Result<ComponentDraft> {
+        if project.split('/').count() < 2 || project.split('/').any(str::is_empty) {
+            bail!("Project must be of the form <namespace>/<name>, found '{}'", project);
+        }
+        let project_id = project.replace('/', "%2F");
+
+        println!("Reading project metadata for {}", project);
+        let gitlab_project: GitlabProject = self
+            .get_json(&format!("{}/projects/{}", self.api_url, project_id))
+            .await?;
+        println!("Reading releases for {}", project);
+        let releases: Vec<GitlabRelease> = self
+            .get_json(&format!(
+                "{}/projects/{}/releases?per_page={}",
+                self.api_url,
+                project_id,
+                max_releases.clamp(1, 100)
+            ))
+            .await?;
+
+        let mut release_drafts = Vec::new();
+        for release in releases
+            .into_iter()
+            .filter(|r| !r.upcoming_release)
+            .take(max_releases)
+        {
+            release_drafts.push(self.release_draft(release).await?);
+        }
+
+        let labels = (!gitlab_project.topics.is_empty()).then(|| {
+            gitlab_project
+                .topics
+                .iter()
+                .map(|topic| (format!("topic/{}", topic), "true".to_string()))
+                .collect::<HashMap<_, _>>()
+        });
+
+        Ok(ComponentDraft {
+            slug: slugify(&gitlab_project.path),
+            name: gitlab_project.name,
+            description: gitlab_project.description.filter(|d| !d.is_empty()),
+            alias_purls: None,
+            repositories: vec![RepositoryInfo {
+                repo_type: "git".to_string(),
+                uri: gitlab_project.web_url,
+                paths: None,
+                primary_path: None,
+            }],
+            labels,
+            releases: release_drafts,
+        })
+    }
+
+    async fn release_draft(&self, release: GitlabRelease) -> Result<ReleaseDraft> {
+        println!("  Processing release {}", release.tag_name);
+        let mut metadata_links = Vec::new();
+        let mut artifacts = Vec::new();
+        for asset in release.assets.map(|a| a.links).unwrap_or_default() {
+            let url = asset.direct_asset_url.unwrap_or(asset.url);
+            // Asset links can point anywhere; only send the token back to the GitLab instance.
+            let request = if url.starts_with(&self.instance_origin()) {
+                self.request(&url)
+            } else {
+                self.client.get(&url)
+            };
+            let digest = download_digests(request, &url).await?;
+            let metadata_type = metadata_media_type(&asset.name);
+            let link = ArtifactLink {
+                uri: url,
+                digest: Some(digest),
+                media_type: metadata_type.map(str::to_string),
+                expected_signer_identity: None,
+                access: None,
+                request_url: None,
+            };
+            if metadata_type.is_some() {
+                metadata_links.push(link);
+            } else {
+                artifacts.push(link);
+            }
+        }
+
+        Ok(ReleaseDraft {
+            version: tag_to_version(&release.tag_name),
+            name: release.name.filter(|n| !n.is_empty()).unwrap_or(release.tag_name),
+            cpe: None,
+            release_date: release.released_at,
+            release_notes_uri: release.links.and_then(|l| l.self_url),
+            lifecycle_phase: Some("stable".to_string()),
+            metadata_links,
+            artifacts,
+        })
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        self.request(url)
+            .send()
+            .await
+            .with_context(|| format!("Failed to send request to '{}'", url))?
+            .error_for_status()
+            .with_context(|| format!("GitLab API request to '{}' failed", url))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse GitLab API response from '{}'", url))
+    }
+
+    /// The scheme and host of the GitLab instance (e.g., `https://gitlab.com/`).
+    fn instance_origin(&self) -> String {
+        let scheme_end = self.api_url.find("://").map_or(0, |i| i + 3);
+        let host_end = self.api_url[scheme_end..]
+            .find('/')
+            .map_or(self.api_url.len(), |i| scheme_end + i);
+        format!("{}/", &self.api_url[..host_end])
+    }
+
+    fn request(&self, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.get(url);
+        match &self.token {
+            Some(token) => request.header("PRIVATE-TOKEN", token),
+            None => request,
+        }
+    }
+}
+
+impl Forge for GitlabForge {
+    fn component_draft<'a>(
+        &'a self,
+        repo: &'a str,
+        max_releases: usize,
+    ) -> BoxFuture<'a, Result<ComponentDraft>> {
+        Box::pin(self.project_draft(repo, max_releases))
+    }
+}
@@ -0,0 +1,321 @@
+// SPDX-License-Identifier: Apache-2.0
+
+pub(crate) mod baseline;
+pub(crate) mod build;
+pub(crate) mod edit;
+pub(crate) mod github;
+pub(crate) mod gitlab;
+pub(crate) mod sbom;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chrono::{SecondsFormat, Utc};
+use futures::future::BoxFuture;
+use sha2::{Digest, Sha256};
+
+use crate::models::chainsights::{
+    ArtifactLink, AttestationLink, CATALOG_V2, COMPONENT_V2, CatalogComponentEntry,
+    ChainsightsCatalogPredicate, ChainsightsComponentPredicate, ChainsightsFreshnessPredicate,
+    ChainsightsPredicate, ChainsightsReleasePredicate, EndorsedStatement, FRESHNESS_V1, Generator,
+    RELEASE_V1, RepositoryInfo, parse_predicate,
+};
+use crate::gitoid::{GITOID_DIGEST_KEY, GitoidAlgorithm, gitoid_uri};
+use chainsights_core::statement::InTotoStatement;
+
+/// A git forge that can describe a repository and its releases.
+pub(crate) trait Forge {
+    /// Builds a component draft for the repository, including up to `max_releases` of its
+    /// most recent releases with digests for every release asset.
+    fn component_draft<'a>(
+        &'a self,
+        repo: &'a str,
+        max_releases: usize,
+    ) -> BoxFuture<'a, Result<ComponentDraft>>;
+}
+
+/// Where generated drafts are published and who signs them.
+pub(crate) struct DraftOptions {
+    /// Domain the component is published under (e.g., example.com).
+    pub domain: String,
+    /// Identity expected to sign the generated statements.
+    pub identity: String,
+    /// Base URI the signed `.jsonl` files will be hosted at.
+    pub base_uri: String,
+    /// Directory the draft statements are written to.
+    pub output_dir: PathBuf,
+}
+
+/// Component information gathered by a generator.
+pub(crate) struct ComponentDraft {
+    /// URL-safe component identifier used in PURLs and file names (e.g., my-component).
+    pub slug: String,
+    /// Human-readable component name.
+    pub name: String,
+    pub description: Option<String>,
+    /// Versionless PURLs the component is distributed as in other ecosystems.
+    pub alias_purls: Option<Vec<String>>,
+    pub repositories: Vec<RepositoryInfo>,
+    pub labels: Option<HashMap<String, String>>,
+    pub releases: Vec<ReleaseDraft>,
+}
+
+/// Release information gathered by a generator.
+pub(crate) struct ReleaseDraft {
+    pub version: String,
+    pub name: String,
+    /// CPE 2.3 name of the release, when the source records one.
+    pub cpe: Option<String>,
+    pub release_date: Option<String>,
+    pub release_notes_uri: Option<String>,
+    pub lifecycle_phase: Option<String>,
+    /// Metadata artifacts such as SBOMs and provenance.
+    pub metadata_links: Vec<ArtifactLink>,
+    /// Released artifacts with their digests.
+    pub artifacts: Vec<ArtifactLink>,
+}
+
+/// Writes the draft component statement and one draft release statement per release,
+/// laid out as `components/<slug>.json` and `components/<slug>/<version>.json`.
+/// Returns the paths that were written.
+pub(crate) fn write_component_drafts(
+    draft: &ComponentDraft,
+    options: &DraftOptions,
+) -> Result<Vec<PathBuf>> {
+    let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let base_uri = options.base_uri.trim_end_matches('/');
+    let component_purl = format!("pkg:generic/{}/{}", options.domain, draft.slug);
+    let component_dir = options.output_dir.join("components").join(&draft.slug);
+    std::fs::create_dir_all(&component_dir)
+        .with_context(|| format!("Failed to create directory '{}'", component_dir.display()))?;
+
+    let mut written = Vec::new();
+    let mut release_attestations = Vec::new();
+    for release in &draft.releases {
+        let path = component_dir.join(format!("{}.json", release.version));
+        write_release_draft(release, &options.domain, &draft.slug, &timestamp, &path)?;
+        written.push(path);
+
+        release_attestations.push(AttestationLink {
+            uri: format!("{}/components/{}/{}.jsonl", base_uri, draft.slug, release.version),
+            digest: None,
+            media_type: None,
+            expected_signer_identity: options.identity.clone(),
+            accepted_signer_identities: None,
+            expected_pgp_fingerprints: None,
+            expected_public_keys: None,
+        });
+    }
+
+    let predicate = ChainsightsComponentPredicate {
+        generator: Some(generator()),
+        timestamp,
+        expires: None,
+        purl: component_purl,
+        name: draft.name.clone(),
+        description: draft.description.clone(),
+        aliases: None,
+        alias_purls: draft.alias_purls.clone(),
+        cpe: None,
+        labels: draft.labels.clone(),
+        security_contact: None,
+        license: None,
+        supplier: None,
+        repositories: draft.repositories.clone(),
+        sub_components: None,
+        relationships: None,
+        release_attestations,
+        metadata_links: None,
+        support_streams: None,
+        service: None,
+    };
+    let subject = format!("pkg:chainsights/{}/{}", options.domain, draft.slug);
+    let path = options
+        .output_dir
+        .join("components")
+        .join(format!("{}.json", draft.slug));
+    write_statement(&path, &subject, COMPONENT_V2, &predicate)?;
+    written.push(path);
+
+    Ok(written)
+}
+
+/// Writes the draft release statement of version `release` of component `slug`, with subject
+/// `pkg:chainsights/<domain>/<slug>@<version>`.
+pub(crate) fn write_release_draft(release: &ReleaseDraft, domain: &str, slug: &str, timestamp: &str, path: &Path) -> Result<()> {
+    let predicate = ChainsightsReleasePredicate {
+        generator: Some(generator()),
+        timestamp: timestamp.to_string(),
+        expires: None,
+        purl: format!("pkg:generic/{}/{}@{}", domain, slug, release.version),
+        name: release.name.clone(),
+        cpe: release.cpe.clone(),
+        release_date: release.release_date.clone(),
+        release_notes_uri: release.release_notes_uri.clone(),
+        lifecycle_phase: release.lifecycle_phase.clone(),
+        status: None,
+        license: None,
+        supplier: None,
+        metadata_links: non_empty(release.metadata_links.clone()),
+        artifacts: non_empty(release.artifacts.clone()),
+        artifact_groups: None,
+    };
+    let subject = format!("pkg:chainsights/{}/{}@{}", domain, slug, release.version);
+    write_statement(path, &subject, RELEASE_V1, &predicate)
+}
+
+/// Writes a draft root catalog statement listing `components`, with subject
+/// `pkg:chainsights/<domain>`.
+pub(crate) fn write_catalog_draft(domain: &str, components: Vec<CatalogComponentEntry>, path: &Path) -> Result<()> {
+    let predicate = ChainsightsCatalogPredicate {
+        generator: Some(generator()),
+        timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        expires: None,
+        serial: Some(1),
+        components,
+        sub_catalogs: None,
+        metadata_links: None,
+        revocation_link: None,
+        freshness_link: None,
+        security_contact: None,
+        next_page: None,
+        component_shards: None,
+    };
+    write_statement(path, &format!("pkg:chainsights/{}", domain), CATALOG_V2, &predicate)
+}
+
+/// Writes a draft freshness statement confirming that the signed root catalog in `catalog` (a
+/// Sigstore bundle, DSSE envelope, or statement with a detached signature) is current, valid for
+/// `valid_for`. The publisher or a witness signs and publishes a new one before it expires.
+pub(crate) fn write_freshness_draft(catalog: &Path, valid_for: chrono::Duration, path: &Path) -> Result<()> {
+    let document = std::fs::read(catalog).with_context(|| format!("Failed to read '{}'", catalog.display()))?;
+    let json: serde_json::Value =
+        serde_json::from_slice(&document).with_context(|| format!("'{}' is not a signed statement", catalog.display()))?;
+    let envelope = json.get("dsseEnvelope").unwrap_or(&json);
+    let payload = match envelope.get("payload").and_then(serde_json::Value::as_str) {
+        Some(payload) => STANDARD.decode(payload).context("The envelope's payload is not base64")?,
+        None => document,
+    };
+    let statement: serde_json::Value =
+        serde_json::from_slice(&payload).with_context(|| format!("'{}' does not hold a JSON statement", catalog.display()))?;
+    let subject = statement["subject"][0]["uri"]
+        .as_str()
+        .or_else(|| statement["subject"][0]["name"].as_str())
+        .with_context(|| format!("The statement in '{}' has no subject", catalog.display()))?
+        .to_string();
+    let statement: InTotoStatement =
+        serde_json::from_value(statement).with_context(|| format!("'{}' does not hold an in-toto statement", catalog.display()))?;
+    let ChainsightsPredicate::Catalog(predicate) = parse_predicate(&statement)? else {
+        bail!("'{}' is not a catalog statement", catalog.display());
+    };
+    let now = Utc::now();
+    let freshness = ChainsightsFreshnessPredicate {
+        generator: Some(generator()),
+        timestamp: now.to_rfc3339_opts(SecondsFormat::Secs, true),
+        expires: Some((now + valid_for).to_rfc3339_opts(SecondsFormat::Secs, true)),
+        catalog: EndorsedStatement {
+            uri: None,
+            digest: HashMap::from([("sha256".to_string(), hex::encode(Sha256::digest(&payload)))]),
+        },
+        serial: predicate.serial,
+    };
+    write_statement(path, &subject, FRESHNESS_V1, &freshness)
+}
+
+/// Identifies this tool as the generator of a predicate.
+pub(crate) fn generator() -> Generator {
+    Generator {
+        purl: format!("pkg:github/kusari-oss/chainsights@{}", env!("CARGO_PKG_VERSION")),
+    }
+}
+
+fn non_empty<T>(items: Vec<T>) -> Option<Vec<T>> {
+    (!items.is_empty()).then_some(items)
+}
+
+/// Serializes a predicate into a statement whose subject digests are the sha256 and OmniBOR
+/// gitoid of the predicate, and writes it as pretty JSON ready for signing.
+fn write_statement<T: serde::Serialize>(
+    path: &Path,
+    subject_uri: &str,
+    predicate_type: &str,
+    predicate: &T,
+) -> Result<()> {
+    let mut predicate = serde_json::to_value(predicate).context("Failed to serialize predicate")?;
+    strip_nulls(&mut predicate);
+    let predicate_bytes = serde_json::to_vec(&predicate).context("Failed to serialize predicate")?;
+    let digest = HashMap::from([
+        ("sha256".to_string(), hex::encode(Sha256::digest(&predicate_bytes))),
+        (GITOID_DIGEST_KEY.to_string(), gitoid_uri(&predicate_bytes, GitoidAlgorithm::Sha256)),
+    ]);
+    let statement = InTotoStatement::new(subject_uri, digest, predicate_type, predicate);
+    let json = serde_json::to_string_pretty(&statement).context("Failed to serialize statement")?;
+    std::fs::write(path, format!("{}\n", json))
+        .with_context(|| format!("Failed to write '{}'", path.display()))
+}
+
+/// Downloads a release asset and returns its sha256 and OmniBOR gitoid digests.
+pub(crate) async fn download_digests(
+    request: reqwest::RequestBuilder,
+    url: &str,
+) -> Result<HashMap<String, String>> {
+    println!("    Computing digests of {}", url);
+    let bytes = request
+        .send()
+        .await
+        .with_context(|| format!("Failed to download asset '{}'", url))?
+        .error_for_status()?
+        .bytes()
+        .await
+        .with_context(|| format!("Failed to read asset '{}'", url))?;
+    Ok(artifact_digests(&bytes))
+}
+
+/// The sha256 and OmniBOR gitoid digests of an artifact.
+pub(crate) fn artifact_digests(bytes: &[u8]) -> HashMap<String, String> {
+    HashMap::from([
+        ("sha256".to_string(), hex::encode(Sha256::digest(bytes))),
+        (GITOID_DIGEST_KEY.to_string(), gitoid_uri(bytes, GitoidAlgorithm::Sha256)),
+    ])
+}
+
+/// Strips a leading "v" from tags like "v1.2.0" so versions match PURL conventions.
+pub(crate) fn tag_to_version(tag: &str) -> String {
+    match tag.strip_prefix('v') {
+        Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => rest.to_string(),
+        _ => tag.to_string(),
+    }
+}
+
+/// Removes null-valued fields so drafts only show what was actually filled in.
+fn strip_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+/// Guesses the media type of a metadata artifact from its file name, returning None for
+/// files that are not recognized as supply chain metadata.
+pub(crate) fn metadata_media_type(file_name: &str) -> Option<&'static str> {
+    let lower = file_name.to_ascii_lowercase();
+    if lower.ends_with(".spdx.json") {
+        Some("application/spdx+json")
+    } else if lower.ends_with(".cdx.json") || lower.ends_with(".bom.json") {
+        Some("application/vnd.cyclonedx+json")
+    } else if lower.ends_with(".intoto.jsonl") || lower.ends_with(".sigstore.json") {
+        Some("application/vnd.in-toto+json")
+    } else if lower.ends_with(".swidtag") {
+        Some("application/swid+xml")
+    } else if lower.ends_with(".coswid") || lower.ends_with(".coswid.cbor") {
+        Some("application/swid+cbor")
+    } else {
+        None
+    }
+}
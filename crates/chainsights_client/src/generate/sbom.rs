@@ -0,0 +1,334 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::generate::{ComponentDraft, DraftOptions, ReleaseDraft};
+use crate::purl::{slugify, strip_purl_version};
+use crate::models::chainsights::{ArtifactLink, RepositoryInfo};
+
+/// An SPDX 2.x JSON document.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SpdxDocument {
+    #[serde(default)]
+    document_describes: Vec<String>,
+    #[serde(default)]
+    packages: Vec<SpdxPackage>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SpdxPackage {
+    #[serde(rename = "SPDXID")]
+    spdx_id: String,
+    name: String,
+    version_info: Option<String>,
+    description: Option<String>,
+    summary: Option<String>,
+    download_location: Option<String>,
+    #[serde(default)]
+    external_refs: Vec<SpdxExternalRef>,
+    #[serde(default)]
+    checksums: Vec<SpdxChecksum>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SpdxExternalRef {
+    reference_type: String,
+    reference_locator: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SpdxChecksum {
+    algorithm: String,
+    checksum_value: String,
+}
+
+/// A CycloneDX JSON document.
+#[derive(Deserialize, Debug)]
+struct CycloneDxDocument {
+    metadata: Option<CycloneDxMetadata>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CycloneDxMetadata {
+    component: Option<CycloneDxComponent>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDxComponent {
+    name: String,
+    version: Option<String>,
+    description: Option<String>,
+    purl: Option<String>,
+    cpe: Option<String>,
+    #[serde(default)]
+    hashes: Vec<CycloneDxHash>,
+    #[serde(default)]
+    external_references: Vec<CycloneDxExternalReference>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CycloneDxHash {
+    alg: String,
+    content: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDxExternalReference {
+    #[serde(rename = "type")]
+    ref_type: String,
+    url: String,
+    #[serde(default)]
+    hashes: Vec<CycloneDxHash>,
+}
+
+/// The subject of an SBOM, independent of its format.
+struct SbomSubject {
+    name: String,
+    version: Option<String>,
+    description: Option<String>,
+    purl: Option<String>,
+    cpe: Option<String>,
+    repositories: Vec<RepositoryInfo>,
+    artifacts: Vec<ArtifactLink>,
+    media_type: &'static str,
+}
+
+/// Derives a component draft with a single release from the primary package of an SPDX or
+/// CycloneDX JSON document. The SBOM itself is linked from the release as metadata, hosted at
+/// `<base-uri>/components/<slug>/<version>/<file name>`.
+pub(crate) fn component_draft_from_sbom(
+    path: &Path,
+    options: &DraftOptions,
+    version_override: Option<String>,
+) -> Result<ComponentDraft> {
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to read SBOM '{}'", path.display()))?;
+    let subject = parse_subject(&bytes)?;
+
+    let version = version_override
+        .or(subject.version)
+        .ok_or_else(|| anyhow!("SBOM does not record a version for '{}'; pass --version", subject.name))?;
+    let slug = slugify(&subject.name);
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .context("SBOM path has no file name")?;
+    let sbom_link = ArtifactLink {
+        uri: format!(
+            "{}/components/{}/{}/{}",
+            options.base_uri.trim_end_matches('/'),
+            slug,
+            version,
+            file_name
+        ),
+        digest: Some(sha256_digest(hex::encode(Sha256::digest(&bytes)))),
+        media_type: Some(subject.media_type.to_string()),
+        expected_signer_identity: None,
+        access: None,
+        request_url: None,
+    };
+    println!("Link to the SBOM (host it here): {}", sbom_link.uri);
+
+    Ok(ComponentDraft {
+        slug,
+        name: subject.name.clone(),
+        description: subject.description,
+        alias_purls: subject.purl.map(|purl| vec![strip_purl_version(&purl)]),
+        repositories: subject.repositories,
+        labels: None,
+        releases: vec![ReleaseDraft {
+            name: format!("{} {}", subject.name, version),
+            version,
+            cpe: subject.cpe,
+            release_date: None,
+            release_notes_uri: None,
+            lifecycle_phase: None,
+            metadata_links: vec![sbom_link],
+            artifacts: subject.artifacts,
+        }],
+    })
+}
+
+/// Returns the PURL of an SBOM's primary package, if it records one.
+pub(crate) fn sbom_purl(bytes: &[u8]) -> Result<Option<String>> {
+    Ok(parse_subject(bytes)?.purl)
+}
+
+fn parse_subject(bytes: &[u8]) -> Result<SbomSubject> {
+    let document: serde_json::Value =
+        serde_json::from_slice(bytes).context("SBOM is not valid JSON")?;
+
+    if document.get("spdxVersion").is_some() {
+        spdx_subject(serde_json::from_value(document).context("Failed to parse SPDX document")?)
+    } else if document.get("bomFormat").and_then(|f| f.as_str()) == Some("CycloneDX") {
+        cyclonedx_subject(
+            serde_json::from_value(document).context("Failed to parse CycloneDX document")?,
+        )
+    } else {
+        bail!("Unrecognized SBOM format; expected SPDX 2.x JSON or CycloneDX JSON");
+    }
+}
+
+fn spdx_subject(document: SpdxDocument) -> Result<SbomSubject> {
+    let package = document
+        .packages
+        .iter()
+        .find(|p| document.document_describes.contains(&p.spdx_id))
+        .or_else(|| document.packages.first())
+        .context("SPDX document contains no packages")?;
+
+    let purl = package
+        .external_refs
+        .iter()
+        .find(|r| r.reference_type == "purl")
+        .map(|r| r.reference_locator.clone());
+    let cpe = package
+        .external_refs
+        .iter()
+        .find(|r| r.reference_type == "cpe23Type")
+        .or_else(|| package.external_refs.iter().find(|r| r.reference_type == "cpe22Type"))
+        .map(|r| r.reference_locator.clone());
+    let repositories = package
+        .download_location
+        .as_deref()
+        .and_then(vcs_repository)
+        .into_iter()
+        .chain(
+            package
+                .external_refs
+                .iter()
+                .filter(|r| r.reference_type == "vcs")
+                .filter_map(|r| vcs_repository(&r.reference_locator)),
+        )
+        .collect();
+
+    // The package checksum describes the downloadable artifact, when there is one.
+    let sha256 = package
+        .checksums
+        .iter()
+        .find(|c| c.algorithm.eq_ignore_ascii_case("SHA256"))
+        .map(|c| c.checksum_value.to_lowercase());
+    let download = package
+        .download_location
+        .as_deref()
+        .filter(|l| l.starts_with("https://") || l.starts_with("http://"));
+    let artifacts = match (sha256, download.map(str::to_string).or(purl.clone())) {
+        (Some(sha256), Some(uri)) => vec![ArtifactLink {
+            uri,
+            digest: Some(sha256_digest(sha256)),
+            media_type: None,
+            expected_signer_identity: None,
+            access: None,
+            request_url: None,
+        }],
+        _ => Vec::new(),
+    };
+
+    Ok(SbomSubject {
+        name: package.name.clone(),
+        version: package.version_info.clone(),
+        description: package.description.clone().or(package.summary.clone()),
+        purl,
+        cpe,
+        repositories,
+        artifacts,
+        media_type: "application/spdx+json",
+    })
+}
+
+fn cyclonedx_subject(document: CycloneDxDocument) -> Result<SbomSubject> {
+    let component = document
+        .metadata
+        .and_then(|m| m.component)
+        .context("CycloneDX document has no metadata.component")?;
+
+    let repositories = component
+        .external_references
+        .iter()
+        .filter(|r| r.ref_type == "vcs")
+        .filter_map(|r| vcs_repository(&r.url))
+        .collect();
+
+    let mut artifacts: Vec<ArtifactLink> = component
+        .external_references
+        .iter()
+        .filter(|r| r.ref_type == "distribution")
+        .filter_map(|r| {
+            cyclonedx_sha256(&r.hashes).map(|sha256| ArtifactLink {
+                uri: r.url.clone(),
+                digest: Some(sha256_digest(sha256)),
+                media_type: None,
+                expected_signer_identity: None,
+                access: None,
+                request_url: None,
+            })
+        })
+        .collect();
+    if artifacts.is_empty()
+        && let (Some(sha256), Some(purl)) = (cyclonedx_sha256(&component.hashes), &component.purl)
+    {
+        artifacts.push(ArtifactLink {
+            uri: purl.clone(),
+            digest: Some(sha256_digest(sha256)),
+            media_type: None,
+            expected_signer_identity: None,
+            access: None,
+            request_url: None,
+        });
+    }
+
+    Ok(SbomSubject {
+        name: component.name,
+        version: component.version,
+        description: component.description,
+        purl: component.purl,
+        cpe: component.cpe,
+        repositories,
+        artifacts,
+        media_type: "application/vnd.cyclonedx+json",
+    })
+}
+
+fn cyclonedx_sha256(hashes: &[CycloneDxHash]) -> Option<String> {
+    hashes
+        .iter()
+        .find(|h| h.alg == "SHA-256")
+        .map(|h| h.content.to_lowercase())
+}
+
+fn sha256_digest(sha256: String) -> HashMap<String, String> {
+    HashMap::from([("sha256".to_string(), sha256)])
+}
+
+/// Interprets a download location or VCS reference (e.g., `git+https://host/repo.git@v1`) as
+/// a git repository, ignoring SPDX's NONE/NOASSERTION placeholders.
+fn vcs_repository(location: &str) -> Option<RepositoryInfo> {
+    let uri = location.strip_prefix("git+").unwrap_or(location);
+    let is_git = location.starts_with("git+") || uri.ends_with(".git") || uri.contains("github.com/");
+    if !is_git || !(uri.starts_with("https://") || uri.starts_with("ssh://") || uri.starts_with("git://")) {
+        return None;
+    }
+    // Drop any "@ref" or "#path" suffix after the repository path (the host may contain "user@").
+    let host_start = uri.find("://").map_or(0, |i| i + 3);
+    let path_start = uri[host_start..].find('/').map_or(uri.len(), |i| host_start + i);
+    let end = uri[path_start..]
+        .find(['@', '#'])
+        .map_or(uri.len(), |i| path_start + i);
+    Some(RepositoryInfo {
+        repo_type: "git".to_string(),
+        uri: uri[..end].to_string(),
+        paths: None,
+        primary_path: None,
+    })
+}
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Result, anyhow};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+/// Digest map key for a full OmniBOR identifier (e.g., "gitoid:blob:sha256:<hex>").
+pub(crate) const GITOID_DIGEST_KEY: &str = "gitoid";
+/// in-toto digest map key for a git blob hash, which is the SHA-1 gitoid of the content.
+pub(crate) const GIT_BLOB_DIGEST_KEY: &str = "gitBlob";
+
+/// Hash algorithms gitoids are defined for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GitoidAlgorithm {
+    Sha1,
+    Sha256,
+}
+
+impl GitoidAlgorithm {
+    fn name(self) -> &'static str {
+        match self {
+            GitoidAlgorithm::Sha1 => "sha1",
+            GitoidAlgorithm::Sha256 => "sha256",
+        }
+    }
+}
+
+/// Computes the gitoid of `bytes` as a blob: the hash of `blob <length>\0<bytes>`, in lowercase
+/// hex. This is the identifier OmniBOR uses for artifacts, and for SHA-1 it equals the git blob id.
+pub(crate) fn gitoid_blob_hex(bytes: &[u8], algorithm: GitoidAlgorithm) -> String {
+    let header = format!("blob {}\0", bytes.len());
+    match algorithm {
+        GitoidAlgorithm::Sha1 => {
+            let mut hasher = Sha1::new();
+            hasher.update(header.as_bytes());
+            hasher.update(bytes);
+            hex::encode(hasher.finalize())
+        }
+        GitoidAlgorithm::Sha256 => {
+            let mut hasher = Sha256::new();
+            hasher.update(header.as_bytes());
+            hasher.update(bytes);
+            hex::encode(hasher.finalize())
+        }
+    }
+}
+
+/// Computes the OmniBOR identifier of `bytes` (e.g., "gitoid:blob:sha256:<hex>").
+pub(crate) fn gitoid_uri(bytes: &[u8], algorithm: GitoidAlgorithm) -> String {
+    format!("gitoid:blob:{}:{}", algorithm.name(), gitoid_blob_hex(bytes, algorithm))
+}
+
+/// Parses an OmniBOR blob identifier into its algorithm and hex hash.
+pub(crate) fn parse_gitoid_uri(uri: &str) -> Result<(GitoidAlgorithm, &str)> {
+    let rest = uri
+        .strip_prefix("gitoid:blob:")
+        .ok_or_else(|| anyhow!("'{}' is not a gitoid blob identifier (gitoid:blob:<algorithm>:<hex>)", uri))?;
+    let (algorithm, hash) = rest
+        .split_once(':')
+        .ok_or_else(|| anyhow!("gitoid '{}' has no hash", uri))?;
+    let algorithm = match algorithm {
+        "sha1" => GitoidAlgorithm::Sha1,
+        "sha256" => GitoidAlgorithm::Sha256,
+        other => return Err(anyhow!("Unsupported gitoid hash algorithm '{}'", other)),
+    };
+    Ok((algorithm, hash))
+}
@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::Serialize;
+
+use crate::models::aggregation::AggregatedCatalogData;
+
+/// A node in the exported graph: the catalog, a component, or a release.
+#[derive(Serialize, Debug)]
+pub(crate) struct GraphNode {
+    /// PURL or URI identifying the node
+    pub id: String,
+    /// "catalog", "component", or "release"
+    pub kind: &'static str,
+    /// Human-readable label
+    pub label: String,
+}
+
+/// A directed edge in the exported graph.
+#[derive(Serialize, Debug)]
+pub(crate) struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    /// "lists", "releases", or a component relationship type such as "depends-on"
+    pub relation: String,
+}
+
+/// The catalog, components, releases, and component relationships of an aggregate.
+#[derive(Serialize, Debug, Default)]
+pub(crate) struct Graph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl Graph {
+    /// Builds the graph of an aggregate rooted at `root_id` (e.g., the domain). Relationship
+    /// targets that were not aggregated are added as component nodes so every edge has both ends.
+    pub(crate) fn from_aggregate(root_id: &str, data: &AggregatedCatalogData) -> Self {
+        let mut graph = Graph::default();
+        graph.nodes.push(GraphNode {
+            id: root_id.to_string(),
+            kind: "catalog",
+            label: root_id.to_string(),
+        });
+
+        for component in &data.components {
+            let Some(predicate) = &component.component_predicate else {
+                continue;
+            };
+            graph.nodes.push(GraphNode {
+                id: predicate.purl.clone(),
+                kind: "component",
+                label: predicate.name.clone(),
+            });
+            graph.edges.push(GraphEdge {
+                source: root_id.to_string(),
+                target: predicate.purl.clone(),
+                relation: "lists".to_string(),
+            });
+            for release in component.releases.iter().filter_map(|r| r.release_predicate.as_ref()) {
+                graph.nodes.push(GraphNode {
+                    id: release.purl.clone(),
+                    kind: "release",
+                    label: release.name.clone(),
+                });
+                graph.edges.push(GraphEdge {
+                    source: predicate.purl.clone(),
+                    target: release.purl.clone(),
+                    relation: "releases".to_string(),
+                });
+            }
+        }
+
+        for edge in &data.relationships {
+            if !edge.resolved && !graph.nodes.iter().any(|n| n.id == edge.target) {
+                graph.nodes.push(GraphNode {
+                    id: edge.target.clone(),
+                    kind: "component",
+                    label: edge.target.clone(),
+                });
+            }
+            graph.edges.push(GraphEdge {
+                source: edge.source.clone(),
+                target: edge.target.clone(),
+                relation: edge.relationship_type.to_string(),
+            });
+        }
+        graph
+    }
+
+    /// Renders the graph in Graphviz DOT format.
+    pub(crate) fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph chainsights {\n  rankdir=LR;\n");
+        for node in &self.nodes {
+            let shape = match node.kind {
+                "catalog" => "folder",
+                "release" => "note",
+                _ => "box",
+            };
+            dot.push_str(&format!(
+                "  \"{}\" [label=\"{}\", shape={}];\n",
+                escape(&node.id),
+                escape(&node.label),
+                shape
+            ));
+        }
+        for edge in &self.edges {
+            let style = match edge.relation.as_str() {
+                "lists" | "releases" => "solid",
+                _ => "dashed",
+            };
+            dot.push_str(&format!(
+                "  \"{}\" -> \"{}\" [label=\"{}\", style={}];\n",
+                escape(&edge.source),
+                escape(&edge.target),
+                escape(&edge.relation),
+                style
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,301 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! The query API as a gRPC service (`chainsights.v1.ChainsightsQuery`), so that platforms can
+//! consume results through typed clients generated from `proto/chainsights/v1/query.proto`, and
+//! follow long traversals as a stream of events.
+
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use futures::Stream;
+use futures::future::BoxFuture;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tonic::{Request, Response};
+
+use crate::models::aggregation::{AggregatedCatalogData, NodeVerification};
+use crate::output::{self, Status};
+use crate::traversal::{SilentHooks, TraversalHooks};
+
+#[allow(clippy::all, clippy::pedantic)]
+pub(crate) mod proto {
+    tonic::include_proto!("chainsights.v1");
+}
+
+use proto::chainsights_query_server::{ChainsightsQuery, ChainsightsQueryServer};
+use proto::traverse_event::Event;
+
+/// Runs traversals for the service, with the options it was started with.
+pub(crate) trait Resolver: Send + Sync + 'static {
+    /// Traverses `domain`, reporting its progress to `hooks`.
+    fn resolve_domain<'a>(&'a self, domain: &'a str, hooks: Arc<dyn TraversalHooks>) -> BoxFuture<'a, Result<AggregatedCatalogData>>;
+
+    /// Traverses the domain `purl` belongs to, or `domain` for ecosystem PURLs, descending into
+    /// the sub-catalogs the PURL names.
+    fn resolve_purl<'a>(
+        &'a self,
+        purl: &'a str,
+        domain: Option<String>,
+        hooks: Arc<dyn TraversalHooks>,
+    ) -> BoxFuture<'a, Result<AggregatedCatalogData>>;
+}
+
+struct QueryService {
+    resolver: Arc<dyn Resolver>,
+}
+
+/// Traversal errors are the caller's to fix (an unknown domain, a malformed PURL), or the
+/// publisher's; neither is the service failing.
+fn status(e: anyhow::Error) -> tonic::Status {
+    tonic::Status::failed_precondition(format!("{:#}", e))
+}
+
+#[tonic::async_trait]
+impl ChainsightsQuery for QueryService {
+    async fn resolve_domain(&self, request: Request<proto::ResolveDomainRequest>) -> Result<Response<proto::QueryResult>, tonic::Status> {
+        let domain = request.into_inner().domain;
+        output::print(0, Status::Info, &format!("ResolveDomain {}", domain));
+        let data = self.resolver.resolve_domain(&domain, Arc::new(SilentHooks)).await.map_err(status)?;
+        Ok(Response::new(query_result(&data).map_err(status)?))
+    }
+
+    async fn resolve_purl(&self, request: Request<proto::ResolvePurlRequest>) -> Result<Response<proto::QueryResult>, tonic::Status> {
+        let proto::ResolvePurlRequest { purl, domain } = request.into_inner();
+        output::print(0, Status::Info, &format!("ResolvePurl {}", purl));
+        let data = self.resolver.resolve_purl(&purl, domain, Arc::new(SilentHooks)).await.map_err(status)?;
+        Ok(Response::new(query_result(&data).map_err(status)?))
+    }
+
+    type StreamTraverseStream = Pin<Box<dyn Stream<Item = Result<proto::TraverseEvent, tonic::Status>> + Send>>;
+
+    async fn stream_traverse(
+        &self,
+        request: Request<proto::ResolveDomainRequest>,
+    ) -> Result<Response<Self::StreamTraverseStream>, tonic::Status> {
+        let domain = request.into_inner().domain;
+        output::print(0, Status::Info, &format!("StreamTraverse {}", domain));
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let resolver = self.resolver.clone();
+        tokio::spawn(async move {
+            let hooks = Arc::new(StreamHooks(sender.clone()));
+            let result = resolver.resolve_domain(&domain, hooks).await.and_then(|data| query_result(&data));
+            let _ = sender.send(result.map(|result| event(Event::Result(result))).map_err(status));
+        });
+        let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|event| (event, receiver))
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// Forwards traversal events to a `StreamTraverse` response.
+struct StreamHooks(UnboundedSender<Result<proto::TraverseEvent, tonic::Status>>);
+
+impl TraversalHooks for StreamHooks {
+    fn node_discovered(&self, uri: &str) {
+        let _ = self.0.send(Ok(event(Event::Discovered(uri.to_string()))));
+    }
+
+    fn node_processed(&self, uri: &str, verified: bool) {
+        let _ = self.0.send(Ok(event(Event::Processed(proto::NodeProcessed { uri: uri.to_string(), verified }))));
+    }
+
+    fn message(&self, depth: usize, status: Status, text: &str) {
+        let status = match status {
+            Status::Info => "info",
+            Status::Verified => "verified",
+            Status::Warning => "warning",
+            Status::Failed => "failed",
+        };
+        let message = proto::Message { depth: depth as u32, status: status.to_string(), text: text.to_string() };
+        let _ = self.0.send(Ok(event(Event::Message(message))));
+    }
+}
+
+fn event(event: Event) -> proto::TraverseEvent {
+    proto::TraverseEvent { event: Some(event) }
+}
+
+fn verification(verification: &Option<NodeVerification>) -> Option<proto::Verification> {
+    let verification = verification.as_ref()?;
+    Some(proto::Verification {
+        method: serde_json::to_value(verification.method).ok()?.as_str()?.to_string(),
+        identity: verification.identity.clone(),
+        rekor_entry: verification.rekor_entry,
+        payload_sha256: verification.payload_sha256.clone(),
+        signed_at: verification.signed_at.clone(),
+        skipped_checks: verification
+            .skipped_checks
+            .iter()
+            .filter_map(|check| serde_json::to_value(check).ok()?.as_str().map(str::to_string))
+            .collect(),
+    })
+}
+
+/// The typed result of a traversal, with the complete aggregate as JSON.
+fn query_result(data: &AggregatedCatalogData) -> Result<proto::QueryResult> {
+    let failure = |kind: proto::FailureKind, (uri, message): &(String, String)| proto::Failure {
+        uri: uri.clone(),
+        kind: kind.into(),
+        message: message.clone(),
+    };
+    let integrity = |error: &crate::models::aggregation::IntegrityError| proto::Failure {
+        uri: error.uri.clone(),
+        kind: proto::FailureKind::Integrity.into(),
+        message: error.to_string(),
+    };
+    let mut failures: Vec<proto::Failure> = data.component_errors.iter().map(|e| failure(proto::FailureKind::Component, e)).collect();
+    failures.extend(data.expired_components.iter().map(|e| failure(proto::FailureKind::Expired, e)));
+    failures.extend(data.integrity_errors.iter().map(integrity));
+    let mut components = Vec::new();
+    for component in &data.components {
+        failures.extend(component.release_errors.iter().map(|e| failure(proto::FailureKind::Release, e)));
+        failures.extend(component.expired_releases.iter().map(|e| failure(proto::FailureKind::Expired, e)));
+        failures.extend(component.integrity_errors.iter().map(integrity));
+        let Some(predicate) = &component.component_predicate else {
+            continue;
+        };
+        let releases = component
+            .releases
+            .iter()
+            .filter_map(|release| {
+                let predicate = release.release_predicate.as_ref()?;
+                Some(proto::Release {
+                    name: predicate.name.clone(),
+                    purl: predicate.purl.clone(),
+                    link_uri: release.release_link_uri.clone(),
+                    release_date: predicate.release_date.clone(),
+                    license: predicate.license.clone(),
+                    verification: verification(&release.verification),
+                })
+            })
+            .collect();
+        components.push(proto::Component {
+            name: predicate.name.clone(),
+            purl: predicate.purl.clone(),
+            link_uri: component.component_link_uri.clone(),
+            license: predicate.license.clone(),
+            verification: verification(&component.verification),
+            releases,
+        });
+    }
+    Ok(proto::QueryResult {
+        root_error: data.root_error.clone(),
+        catalog: data.catalog_predicate.as_ref().map(|catalog| proto::Catalog {
+            timestamp: catalog.timestamp.clone(),
+            expires: catalog.expires.clone(),
+            serial: catalog.serial,
+            verification: verification(&data.verification),
+        }),
+        components,
+        failures,
+        aggregate_json: serde_json::to_string(data).context("Failed to serialize the aggregate")?,
+    })
+}
+
+/// Serves the query API over gRPC at `addr` until the process exits.
+pub(crate) async fn serve(addr: SocketAddr, resolver: Arc<dyn Resolver>) -> Result<()> {
+    output::print(0, Status::Info, &format!("Serving chainsights.v1.ChainsightsQuery over gRPC at {}", addr));
+    tonic::transport::Server::builder()
+        .add_service(ChainsightsQueryServer::new(QueryService { resolver }))
+        .serve(addr)
+        .await
+        .with_context(|| format!("The gRPC server at {} failed", addr))
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use serde_json::json;
+
+    use super::proto::chainsights_query_client::ChainsightsQueryClient;
+    use super::*;
+
+    /// Resolves every domain to the same aggregate, reporting one attestation to the hooks.
+    struct FixedResolver(AggregatedCatalogData);
+
+    impl Resolver for FixedResolver {
+        fn resolve_domain<'a>(&'a self, domain: &'a str, hooks: Arc<dyn TraversalHooks>) -> BoxFuture<'a, Result<AggregatedCatalogData>> {
+            Box::pin(async move {
+                anyhow::ensure!(domain == "example.com", "No _chainsights record for '{}'", domain);
+                hooks.node_discovered("https://example.com/catalog.jsonl");
+                hooks.node_processed("https://example.com/catalog.jsonl", true);
+                Ok(self.0.clone())
+            })
+        }
+
+        fn resolve_purl<'a>(
+            &'a self,
+            _purl: &'a str,
+            domain: Option<String>,
+            hooks: Arc<dyn TraversalHooks>,
+        ) -> BoxFuture<'a, Result<AggregatedCatalogData>> {
+            Box::pin(async move { self.resolve_domain(&domain.unwrap_or_default(), hooks).await })
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_typed_results_and_streams_traversals() {
+        let data: AggregatedCatalogData = serde_json::from_value(json!({
+            "catalog_predicate": { "timestamp": "2025-06-01T00:00:00Z", "serial": 7, "components": [] },
+            "verification": { "method": "sigstore-keyless", "identity": "security@example.com" },
+            "components": [{
+                "component_predicate": {
+                    "timestamp": "2025-06-01T00:00:00Z",
+                    "name": "app",
+                    "purl": "pkg:chainsights/example.com/app",
+                    "repositories": [],
+                    "subComponents": null,
+                    "releaseAttestations": [],
+                },
+                "releases": [],
+                "component_link_uri": "https://example.com/app.jsonl",
+                "release_errors": [["https://example.com/app/1.0.jsonl", "bad signature"]],
+            }],
+            "root_error": null,
+            "component_errors": [],
+        }))
+        .unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        tokio::spawn(serve(addr, Arc::new(FixedResolver(data))));
+        let mut client = loop {
+            match ChainsightsQueryClient::connect(format!("http://{}", addr)).await {
+                Ok(client) => break client,
+                Err(_) => tokio::time::sleep(std::time::Duration::from_millis(10)).await,
+            }
+        };
+
+        let result = client
+            .resolve_domain(proto::ResolveDomainRequest { domain: "example.com".to_string() })
+            .await
+            .unwrap()
+            .into_inner();
+        let catalog = result.catalog.as_ref().unwrap();
+        assert_eq!(catalog.serial, Some(7));
+        assert_eq!(catalog.verification.as_ref().unwrap().method, "sigstore-keyless");
+        assert_eq!(result.components[0].purl, "pkg:chainsights/example.com/app");
+        assert_eq!(result.failures[0].kind(), proto::FailureKind::Release);
+        assert!(result.aggregate_json.contains("\"component_link_uri\":\"https://example.com/app.jsonl\""));
+
+        let error = client
+            .resolve_purl(proto::ResolvePurlRequest { purl: "pkg:npm/app".to_string(), domain: Some("unknown.example".to_string()) })
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), tonic::Code::FailedPrecondition);
+
+        let events: Vec<_> = client
+            .stream_traverse(proto::ResolveDomainRequest { domain: "example.com".to_string() })
+            .await
+            .unwrap()
+            .into_inner()
+            .map(|event| event.unwrap().event.unwrap())
+            .collect()
+            .await;
+        assert!(matches!(&events[0], Event::Discovered(uri) if uri == "https://example.com/catalog.jsonl"));
+        assert!(matches!(&events[1], Event::Processed(processed) if processed.verified));
+        assert!(matches!(&events[2], Event::Result(result) if result.components.len() == 1));
+    }
+}
@@ -0,0 +1,245 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! A local history of each supplier's completeness scores, release cadence and verification
+//! failures, so that changes over time can be reported.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chainsights_core::identity::normalize_domain;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::aggregation::AggregatedCatalogData;
+use crate::score::{ReleaseCriteria, SupplierScore};
+use crate::state::{JsonStateFile, state_file};
+
+/// Releases published this long before a snapshot count towards its release cadence.
+const CADENCE_WINDOW_DAYS: i64 = 90;
+
+/// Snapshots recorded this long before a domain's latest are dropped from its history.
+const RETENTION_DAYS: i64 = 730;
+
+/// What was observed of a supplier at one time. Coverages are percentages of its releases.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct Snapshot {
+    pub recorded_at: String,
+    pub score: f64,
+    pub sbom_coverage: f64,
+    pub provenance_coverage: f64,
+    pub vex_coverage: f64,
+    pub signed_artifact_coverage: f64,
+    pub fresh_coverage: f64,
+    /// Releases dated within the 90 days before the snapshot
+    pub recent_releases: usize,
+    /// Statements that failed to verify or had expired
+    pub verification_failures: usize,
+}
+
+impl Snapshot {
+    pub(crate) fn new(score: &SupplierScore, data: &AggregatedCatalogData, now: DateTime<Utc>) -> Self {
+        let releases: Vec<&ReleaseCriteria> = score.components.iter().flat_map(|c| &c.releases).map(|r| &r.criteria).collect();
+        let coverage = |met: fn(&ReleaseCriteria) -> bool| match releases.len() {
+            0 => 0.0,
+            total => releases.iter().filter(|criteria| met(criteria)).count() as f64 * 100.0 / total as f64,
+        };
+        let recent_releases = data
+            .components
+            .iter()
+            .flat_map(|component| &component.releases)
+            .filter_map(|release| release.release_predicate.as_deref())
+            .filter_map(|predicate| predicate.release_date.as_deref().or(Some(&predicate.timestamp)).and_then(parse_date))
+            .filter(|date| *date <= now && now - *date <= Duration::days(CADENCE_WINDOW_DAYS))
+            .count();
+        Self {
+            recorded_at: now.to_rfc3339_opts(SecondsFormat::Secs, true),
+            score: score.score,
+            sbom_coverage: coverage(|c| c.sbom),
+            provenance_coverage: coverage(|c| c.provenance),
+            vex_coverage: coverage(|c| c.vex),
+            signed_artifact_coverage: coverage(|c| c.signed_artifacts),
+            fresh_coverage: coverage(|c| c.fresh),
+            recent_releases,
+            verification_failures: data.outcome_counts().1,
+        }
+    }
+
+    fn metrics(&self) -> [(&'static str, f64, bool); 8] {
+        [
+            ("Completeness score", self.score, false),
+            ("SBOM coverage", self.sbom_coverage, true),
+            ("Provenance coverage", self.provenance_coverage, true),
+            ("VEX coverage", self.vex_coverage, true),
+            ("Signed artifact coverage", self.signed_artifact_coverage, true),
+            ("Fresh attestation coverage", self.fresh_coverage, true),
+            ("Releases in the last 90 days", self.recent_releases as f64, false),
+            ("Verification failures", self.verification_failures as f64, false),
+        ]
+    }
+}
+
+/// Release dates may be full RFC 3339 times or plain dates.
+fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+    date.parse::<DateTime<Utc>>()
+        .ok()
+        .or_else(|| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok().map(|day| day.and_time(chrono::NaiveTime::MIN).and_utc()))
+}
+
+/// A metric that changed between two snapshots.
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct TrendDelta {
+    pub metric: &'static str,
+    pub from: f64,
+    pub to: f64,
+    /// e.g. "VEX coverage dropped from 90% to 40% since 2025-07-01"
+    pub summary: String,
+}
+
+/// The changes in a supplier's metrics from a baseline snapshot to the latest.
+#[derive(Serialize, Debug)]
+pub(crate) struct Trend {
+    pub domain: String,
+    pub baseline: Snapshot,
+    pub current: Snapshot,
+    pub deltas: Vec<TrendDelta>,
+}
+
+impl Trend {
+    pub(crate) fn new(domain: &str, baseline: Snapshot, current: Snapshot) -> Self {
+        let since = baseline.recorded_at.get(..10).unwrap_or(&baseline.recorded_at).to_string();
+        let deltas = baseline
+            .metrics()
+            .into_iter()
+            .zip(current.metrics())
+            .filter(|((_, from, _), (_, to, _))| (from - to).abs() >= 0.05)
+            .map(|((metric, from, percentage), (_, to, _))| {
+                let format = |value: f64| match percentage {
+                    true => format!("{:.0}%", value),
+                    false if value.fract() == 0.0 => format!("{}", value),
+                    false => format!("{:.1}", value),
+                };
+                let direction = if to > from { "rose" } else { "dropped" };
+                TrendDelta {
+                    metric,
+                    from,
+                    to,
+                    summary: format!("{} {} from {} to {} since {}", metric, direction, format(from), format(to), since),
+                }
+            })
+            .collect();
+        Self { domain: domain.to_string(), baseline, current, deltas }
+    }
+}
+
+/// A JSON file of the snapshots recorded for each domain, oldest first.
+pub(crate) struct HistoryStore {
+    file: JsonStateFile,
+}
+
+impl HistoryStore {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self { file: JsonStateFile::new(path, "history") }
+    }
+
+    /// `$XDG_STATE_HOME/chainsights/history.json`, or `~/.local/state/chainsights/history.json`.
+    pub(crate) fn default_path() -> Result<PathBuf> {
+        state_file("history.json", "--history")
+    }
+
+    fn load(&self) -> Result<BTreeMap<String, Vec<Snapshot>>> {
+        self.file.load()
+    }
+
+    /// Appends a snapshot of `domain`. Only the last snapshot of each day is kept, and none from
+    /// more than two years before it, so the history stays small however often it is recorded.
+    pub(crate) fn record(&self, domain: &str, snapshot: Snapshot) -> Result<()> {
+        let domain = normalize_domain(domain)?;
+        let mut history = self.load()?;
+        let snapshots = history.entry(domain).or_default();
+        let recorded_at = parse_date(&snapshot.recorded_at);
+        let day = |snapshot: &Snapshot| snapshot.recorded_at.get(..10).map(str::to_string);
+        let today = day(&snapshot);
+        snapshots.retain(|kept| {
+            let expired = recorded_at.zip(parse_date(&kept.recorded_at)).is_some_and(|(now, at)| now - at > Duration::days(RETENTION_DAYS));
+            !expired && (today.is_none() || day(kept) != today)
+        });
+        snapshots.push(snapshot);
+        self.file.save(&history)
+    }
+
+    /// The snapshot to compare `domain` against: the latest recorded at or before `since`, or the
+    /// oldest if all are newer. None if nothing was recorded before `before`.
+    pub(crate) fn baseline(&self, domain: &str, since: DateTime<Utc>, before: DateTime<Utc>) -> Result<Option<Snapshot>> {
+        let domain = normalize_domain(domain)?;
+        let snapshots: Vec<Snapshot> = self
+            .load()?
+            .remove(&domain)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|snapshot| parse_date(&snapshot.recorded_at).is_some_and(|at| at < before))
+            .collect();
+        let at_or_before = snapshots.iter().rev().find(|snapshot| parse_date(&snapshot.recorded_at).is_some_and(|at| at <= since));
+        Ok(at_or_before.or(snapshots.first()).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn snapshot(recorded_at: &str, vex_coverage: f64) -> Snapshot {
+        Snapshot {
+            recorded_at: recorded_at.to_string(),
+            score: 60.0,
+            sbom_coverage: 100.0,
+            provenance_coverage: 50.0,
+            vex_coverage,
+            signed_artifact_coverage: 100.0,
+            fresh_coverage: 50.0,
+            recent_releases: 3,
+            verification_failures: 0,
+        }
+    }
+
+    #[test]
+    fn has_no_baseline_before_the_first_snapshot() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::new(dir.path().join("history.json"));
+        let now: DateTime<Utc> = "2025-10-01T00:00:00Z".parse().unwrap();
+        assert_eq!(store.baseline("example.com", now - Duration::days(90), now).unwrap(), None);
+    }
+
+    #[test]
+    fn baseline_is_the_snapshot_from_the_start_of_the_period() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::new(dir.path().join("history.json"));
+        let now: DateTime<Utc> = "2025-10-01T00:00:00Z".parse().unwrap();
+        store.record("example.com", snapshot("2025-06-01T00:00:00Z", 100.0)).unwrap();
+        store.record("example.com", snapshot("2025-07-01T00:00:00Z", 90.0)).unwrap();
+        store.record("Example.com.", snapshot("2025-09-01T00:00:00Z", 60.0)).unwrap();
+        let baseline = store.baseline("example.com", now - Duration::days(90), now).unwrap().unwrap();
+        assert_eq!(baseline.recorded_at, "2025-07-01T00:00:00Z");
+    }
+
+    #[test]
+    fn keeps_the_last_snapshot_of_each_day_for_two_years() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::new(dir.path().join("history.json"));
+        store.record("example.com", snapshot("2023-06-01T00:00:00Z", 100.0)).unwrap();
+        store.record("example.com", snapshot("2025-07-01T08:00:00Z", 90.0)).unwrap();
+        store.record("example.com", snapshot("2025-07-01T20:00:00Z", 80.0)).unwrap();
+        store.record("example.com", snapshot("2025-10-01T00:00:00Z", 60.0)).unwrap();
+        let recorded: Vec<String> = store.load().unwrap().remove("example.com").unwrap().into_iter().map(|s| s.recorded_at).collect();
+        assert_eq!(recorded, ["2025-07-01T20:00:00Z", "2025-10-01T00:00:00Z"]);
+    }
+
+    #[test]
+    fn reports_changes_against_the_baseline() {
+        let trend = Trend::new("example.com", snapshot("2025-07-01T00:00:00Z", 90.0), snapshot("2025-10-01T00:00:00Z", 40.0));
+        assert_eq!(trend.deltas.len(), 1);
+        assert_eq!(trend.deltas[0].summary, "VEX coverage dropped from 90% to 40% since 2025-07-01");
+    }
+}
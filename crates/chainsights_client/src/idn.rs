@@ -0,0 +1,8 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use chainsights_core::identity::normalize_domain;
+
+/// Clap value parser for domain arguments.
+pub(crate) fn parse_domain_arg(domain: &str) -> std::result::Result<String, String> {
+    normalize_domain(domain).map_err(|e| e.to_string())
+}
@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tracing a container image back to the release statements that attest to it, by matching its
+//! manifest digest against the digests of the artifacts suppliers publish.
+
+use serde::Serialize;
+
+use crate::models::aggregation::{AggregatedCatalogData, AggregatedReleaseData};
+use crate::models::chainsights::ArtifactLink;
+
+/// Media types of image manifests and indexes, OCI and Docker.
+const IMAGE_MEDIA_TYPES: &[&str] = &[
+    "application/vnd.oci.image.manifest.v1+json",
+    "application/vnd.oci.image.index.v1+json",
+    "application/vnd.docker.distribution.manifest.v2+json",
+    "application/vnd.docker.distribution.manifest.list.v2+json",
+];
+
+/// A release whose statement lists an image among its artifacts.
+#[derive(Serialize, Debug, Clone)]
+pub struct ImageRelease {
+    /// The domain publishing the release
+    pub domain: String,
+    /// The release PURL
+    pub release_purl: String,
+    /// URI of the release statement
+    pub release_uri: String,
+    /// The artifact link carrying the image's digest, which may name another registry or
+    /// repository if the image was mirrored
+    pub artifact_uri: String,
+    /// The platform of the artifact group the link is in, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    /// Identity or key that signed the release statement
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    /// Why the publisher revoked the release statement, if it did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revoked: Option<String>,
+    /// The release's status if the publisher pulled it (e.g., yanked)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+/// The verified releases of `data` with an artifact whose sha256 digest is the image manifest
+/// digest `digest` (`sha256:<hex>`). Releases accepted by an insecure mode are left out.
+#[cfg(feature = "cli")]
+pub(crate) fn find_image_releases(domain: &str, data: &AggregatedCatalogData, digest: &str) -> Vec<ImageRelease> {
+    let Some(hex) = digest.strip_prefix("sha256:").map(str::to_ascii_lowercase) else {
+        return Vec::new();
+    };
+    attested_images(domain, data)
+        .into_iter()
+        .filter(|(digest, _)| *digest == hex)
+        .map(|(_, release)| release)
+        .collect()
+}
+
+/// Every image the verified releases of `data` list, as its lowercase hex sha256 digest and the
+/// release. Releases accepted by an insecure mode are left out.
+pub(crate) fn attested_images(domain: &str, data: &AggregatedCatalogData) -> Vec<(String, ImageRelease)> {
+    let verified = |release: &&AggregatedReleaseData| release.verification.as_ref().is_some_and(|v| v.is_verified());
+    let mut found = Vec::new();
+    for release in data.components.iter().flat_map(|c| &c.releases).filter(verified) {
+        let Some(predicate) = release.release_predicate.as_deref() else {
+            continue;
+        };
+        let links = predicate
+            .artifacts
+            .iter()
+            .flatten()
+            .map(|link| (link, None))
+            .chain(predicate.artifact_groups.iter().flatten().flat_map(|group| group.artifacts.iter().map(move |link| (link, Some(&group.platform)))));
+        for (link, platform) in links.filter(|(link, _)| is_image(link)) {
+            let image_release = ImageRelease {
+                domain: domain.to_string(),
+                release_purl: predicate.purl.clone(),
+                release_uri: release.release_link_uri.clone(),
+                artifact_uri: link.uri.clone(),
+                platform: platform.map(ToString::to_string),
+                signer: release.verification.as_ref().and_then(|v| v.identity.clone()),
+                revoked: release.revocation.as_ref().map(|r| r.reason.clone().unwrap_or_else(|| "no reason given".to_string())),
+                status: predicate.status.as_ref().filter(|s| s.is_pulled()).map(ToString::to_string),
+            };
+            found.extend(pinned_digests(link).into_iter().map(|digest| (digest, image_release.clone())));
+        }
+    }
+    found
+}
+
+/// Whether a link is a container image: an `oci://` URI or an image manifest or index media type.
+fn is_image(link: &ArtifactLink) -> bool {
+    link.uri.starts_with("oci://") || link.media_type.as_deref().is_some_and(|media_type| IMAGE_MEDIA_TYPES.contains(&media_type))
+}
+
+/// The sha256 digests a link names, lowercase: its `sha256` digest and that of its `@sha256:` URI.
+fn pinned_digests(link: &ArtifactLink) -> Vec<String> {
+    let by_digest = link.digest.as_ref().and_then(|d| d.get("sha256")).map(String::as_str);
+    let by_uri = link.uri.rsplit_once("@sha256:").map(|(_, d)| d);
+    let mut digests: Vec<String> = by_digest.into_iter().chain(by_uri).map(|d| d.to_ascii_lowercase()).collect();
+    digests.dedup();
+    digests
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use crate::models::aggregation::AggregatedComponentData;
+
+    use super::*;
+
+    fn release(purl: &str, artifacts: serde_json::Value, verification: &str) -> AggregatedReleaseData {
+        AggregatedReleaseData {
+            release_predicate: Some(std::sync::Arc::new(serde_json::from_value(serde_json::json!({
+                "timestamp": "2025-01-01T00:00:00Z",
+                "purl": purl,
+                "name": purl,
+                "metadataLinks": [],
+                "artifacts": artifacts,
+            })).unwrap())),
+            release_link_uri: format!("https://example.com/{}.json", purl),
+            verification: Some(serde_json::from_str(verification).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn finds_the_verified_releases_listing_an_image_digest() {
+        let digest = "a".repeat(64);
+        let signed = r#"{"method": "sigstore-keyless", "identity": "release@example.com"}"#;
+        let data = AggregatedCatalogData {
+            components: vec![AggregatedComponentData {
+                releases: vec![
+                    release("app@1.0.0", serde_json::json!([{ "uri": format!("oci://ghcr.io/example/app@sha256:{}", digest) }]), signed),
+                    release("app@1.1.0", serde_json::json!([{ "uri": "oci://mirror.example.net/app:1.1.0", "digest": { "sha256": digest.to_uppercase() } }]), signed),
+                    release("app@2.0.0", serde_json::json!([
+                        { "uri": format!("oci://ghcr.io/example/app@sha256:{}", "b".repeat(64)) },
+                        { "uri": "https://example.com/app.tar.gz", "digest": { "sha256": digest } },
+                    ]), signed),
+                    release("app@0.9.0", serde_json::json!([{ "uri": format!("oci://ghcr.io/example/app@sha256:{}", digest) }]),
+                        r#"{"method": "sigstore-keyless", "skipped_checks": ["identity"]}"#),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let found = find_image_releases("example.com", &data, &format!("sha256:{}", digest));
+        let purls: Vec<_> = found.iter().map(|r| r.release_purl.as_str()).collect();
+        assert_eq!(purls, ["app@1.0.0", "app@1.1.0"]);
+        assert_eq!(found[0].signer.as_deref(), Some("release@example.com"));
+        assert!(find_image_releases("example.com", &data, &format!("sha256:{}", "c".repeat(64))).is_empty());
+    }
+}
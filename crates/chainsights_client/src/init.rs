@@ -0,0 +1,328 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! The `init` wizard: gathers a publisher's domain, components and releases, then generates,
+//! signs and lays out the whole attestation tree for hosting.
+
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use chrono::{SecondsFormat, Utc};
+
+use crate::generate::edit::{Draft, attestation_link};
+use crate::generate::{
+    ComponentDraft, DraftOptions, Forge, ReleaseDraft, artifact_digests, metadata_media_type, write_catalog_draft,
+    write_component_drafts,
+};
+use crate::models::chainsights::{ArtifactLink, CatalogComponentEntry, RepositoryInfo};
+use crate::purl::slugify;
+use crate::sign::{StatementSigner, sign_draft};
+
+/// Most recent releases imported from a forge.
+const MAX_IMPORTED_RELEASES: usize = 10;
+
+/// Asks questions on a terminal (or any reader and writer, in tests).
+pub(crate) struct Prompter<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    pub(crate) fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    /// Asks until answered, or returns `default` for an empty answer.
+    pub(crate) fn ask(&mut self, question: &str, default: Option<&str>) -> Result<String> {
+        loop {
+            match (self.read_answer(question, default)?, default) {
+                (Some(answer), _) => return Ok(answer),
+                (None, Some(default)) => return Ok(default.to_string()),
+                (None, None) => self.say("  An answer is required.")?,
+            }
+        }
+    }
+
+    /// Asks a question that may be left unanswered.
+    pub(crate) fn ask_optional(&mut self, question: &str) -> Result<Option<String>> {
+        self.read_answer(question, None)
+    }
+
+    pub(crate) fn confirm(&mut self, question: &str, default: bool) -> Result<bool> {
+        loop {
+            let answer = self.read_answer(question, Some(if default { "Y/n" } else { "y/N" }))?;
+            match answer.as_deref().map(str::to_ascii_lowercase).as_deref() {
+                None => return Ok(default),
+                Some("y" | "yes") => return Ok(true),
+                Some("n" | "no") => return Ok(false),
+                Some(_) => self.say("  Answer yes or no.")?,
+            }
+        }
+    }
+
+    pub(crate) fn say(&mut self, message: &str) -> Result<()> {
+        writeln!(self.output, "{}", message)?;
+        Ok(())
+    }
+
+    fn read_answer(&mut self, question: &str, hint: Option<&str>) -> Result<Option<String>> {
+        match hint {
+            Some(hint) => write!(self.output, "{} [{}]: ", question, hint)?,
+            None => write!(self.output, "{}: ", question)?,
+        }
+        self.output.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            bail!("Input ended before '{}' was answered", question);
+        }
+        let answer = line.trim();
+        Ok((!answer.is_empty()).then(|| answer.to_string()))
+    }
+}
+
+/// What the wizard gathered.
+pub(crate) struct InitAnswers {
+    pub(crate) domain: String,
+    /// Base URI the signed files will be hosted at.
+    pub(crate) base_uri: String,
+    pub(crate) components: Vec<ComponentDraft>,
+}
+
+/// Asks for the publisher's domain, hosting location and components. Components can be
+/// imported from `forge` (releases and asset digests included) or entered by hand.
+pub(crate) async fn gather<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    domain: Option<String>,
+    forge: &dyn Forge,
+) -> Result<InitAnswers> {
+    let domain = match domain {
+        Some(domain) => domain,
+        None => prompter.ask("Domain you publish under (e.g., example.com)", None)?,
+    };
+    let default_base = format!("https://{}/.well-known/chainsights", domain);
+    let base_uri = prompter
+        .ask("Base URI the signed files will be hosted at", Some(&default_base))?
+        .trim_end_matches('/')
+        .to_string();
+
+    let mut components = Vec::new();
+    loop {
+        let question = match components.is_empty() {
+            true => "Add a component",
+            false => "Add another component",
+        };
+        if !prompter.confirm(question, components.is_empty())? {
+            break;
+        }
+        let component = loop {
+            match prompter.ask_optional("GitHub repository to import it from (owner/name, blank to enter it by hand)")? {
+                Some(repo) => match forge.component_draft(&repo, MAX_IMPORTED_RELEASES).await {
+                    Ok(component) => break component,
+                    Err(e) => prompter.say(&format!("  Failed to import '{}': {:#}", repo, e))?,
+                },
+                None => break ask_component(prompter)?,
+            }
+        };
+        if components.iter().any(|existing: &ComponentDraft| existing.slug == component.slug) {
+            prompter.say(&format!("  A component named '{}' was already added; skipping it", component.slug))?;
+            continue;
+        }
+        components.push(component);
+    }
+    if components.is_empty() {
+        bail!("A catalog needs at least one component");
+    }
+    Ok(InitAnswers { domain, base_uri, components })
+}
+
+fn ask_component<R: BufRead, W: Write>(prompter: &mut Prompter<R, W>) -> Result<ComponentDraft> {
+    let name = prompter.ask("Component name", None)?;
+    let slug = slugify(&prompter.ask("Identifier used in its PURL and file names", Some(&slugify(&name)))?);
+    let description = prompter.ask_optional("Description")?;
+    let repositories = prompter
+        .ask_optional("Source repository URL")?
+        .map(|uri| RepositoryInfo { repo_type: "git".to_string(), uri, paths: None, primary_path: None })
+        .into_iter()
+        .collect();
+
+    let mut releases = Vec::new();
+    while let Some(version) = prompter.ask_optional("Release version (blank when done)")? {
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        let release_date = prompter.ask("Release date", Some(&now))?;
+        let (mut metadata_links, mut artifacts) = (Vec::new(), Vec::new());
+        while let Some(path) = prompter.ask_optional("Local copy of a released file to hash (blank when done)")? {
+            let bytes = match std::fs::read(&path) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    prompter.say(&format!("  Failed to read '{}': {}", path, e))?;
+                    continue;
+                }
+            };
+            let file_name = Path::new(&path).file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or(path);
+            let uri = prompter.ask(&format!("URL {} is downloaded from", file_name), None)?;
+            let media_type = metadata_media_type(&file_name);
+            let link = ArtifactLink {
+                uri,
+                digest: Some(artifact_digests(&bytes)),
+                media_type: media_type.map(str::to_string),
+                expected_signer_identity: None,
+                access: None,
+                request_url: None,
+            };
+            match media_type {
+                Some(_) => metadata_links.push(link),
+                None => artifacts.push(link),
+            }
+        }
+        releases.push(ReleaseDraft {
+            name: format!("{} {}", name, version),
+            version,
+            cpe: None,
+            release_date: Some(release_date),
+            release_notes_uri: None,
+            lifecycle_phase: Some("stable".to_string()),
+            metadata_links,
+            artifacts,
+        });
+    }
+    // Listed most recent first, as forges list them.
+    releases.reverse();
+
+    Ok(ComponentDraft {
+        slug,
+        name,
+        description,
+        alias_purls: None,
+        repositories,
+        labels: None,
+        releases,
+    })
+}
+
+/// The files `init` wrote.
+pub(crate) struct InitTree {
+    /// URI the root catalog will be served at.
+    pub(crate) root_uri: String,
+    /// Draft statements, kept for later edits and re-signing.
+    pub(crate) drafts: Vec<PathBuf>,
+    /// Signed statements, laid out as they are hosted under the base URI.
+    pub(crate) signed: Vec<PathBuf>,
+}
+
+/// Writes drafts of every statement to `<output_dir>/drafts` and signs them into
+/// `<output_dir>/public`. The catalog pins each component by digest.
+pub(crate) async fn write_init_tree(answers: &InitAnswers, signer: &dyn StatementSigner, output_dir: &Path) -> Result<InitTree> {
+    let drafts_dir = output_dir.join("drafts");
+    let public_dir = output_dir.join("public");
+    let options = DraftOptions {
+        domain: answers.domain.clone(),
+        identity: signer.identity(),
+        base_uri: answers.base_uri.clone(),
+        output_dir: drafts_dir.clone(),
+    };
+    let sign = async |draft: &Path| -> Result<(PathBuf, String)> {
+        let relative = draft.strip_prefix(&drafts_dir).context("Draft was written outside the drafts directory")?;
+        let signed = public_dir.join(relative).with_extension("jsonl");
+        let digest = sign_draft(signer, draft, &signed).await?;
+        Ok((signed, digest))
+    };
+
+    let mut tree = InitTree { root_uri: format!("{}/catalog.jsonl", answers.base_uri), drafts: Vec::new(), signed: Vec::new() };
+    let mut entries = Vec::new();
+    for component in &answers.components {
+        let written = write_component_drafts(component, &options)?;
+        let mut component_digest = String::new();
+        for draft in &written {
+            let (signed, digest) = sign(draft).await?;
+            tree.signed.push(signed);
+            component_digest = digest;
+        }
+        // The component statement is written last.
+        let draft = Draft::read_component(written.last().context("No component statement was written")?, None)?;
+        entries.push(CatalogComponentEntry {
+            name: draft.predicate.name,
+            description: draft.predicate.description,
+            component_purl: draft.predicate.purl,
+            component_attestation_link: attestation_link(
+                &format!("{}/components/{}.jsonl", answers.base_uri, component.slug),
+                signer.identity(),
+                Some(&format!("sha256:{}", component_digest)),
+            )?,
+            labels: draft.predicate.labels,
+        });
+        tree.drafts.extend(written);
+    }
+
+    let catalog = drafts_dir.join("catalog.json");
+    write_catalog_draft(&answers.domain, entries, &catalog)?;
+    tree.signed.push(sign(&catalog).await?.0);
+    tree.drafts.push(catalog);
+    Ok(tree)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::BoxFuture;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::sign::{DidKeySigner, Ed25519Key};
+
+    struct NoForge;
+
+    impl Forge for NoForge {
+        fn component_draft<'a>(&'a self, repo: &'a str, _: usize) -> BoxFuture<'a, Result<ComponentDraft>> {
+            Box::pin(async move { bail!("no forge for {}", repo) })
+        }
+    }
+
+    /// Answers the prompts for one component, "My App", with one release of an artifact in `dir`.
+    async fn gather_one_release(dir: &Path) -> InitAnswers {
+        let artifact = dir.join("app.tar.gz");
+        std::fs::write(&artifact, b"app").unwrap();
+        let input = [
+            "",
+            "",
+            "",
+            "My App",
+            "",
+            "",
+            "",
+            "1.0.0",
+            "2025-01-01T00:00:00Z",
+            artifact.to_str().unwrap(),
+            "https://downloads.example.com/app-1.0.0.tar.gz",
+            "",
+            "",
+            "n",
+        ]
+        .join("\n");
+        let mut prompter = Prompter::new(input.as_bytes(), Vec::new());
+        gather(&mut prompter, Some("example.com".to_string()), &NoForge).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn gathers_components_from_prompts() {
+        let dir = TempDir::new().unwrap();
+        let answers = gather_one_release(dir.path()).await;
+        assert_eq!(answers.base_uri, "https://example.com/.well-known/chainsights");
+        assert_eq!(answers.components[0].slug, "my-app");
+        let release = &answers.components[0].releases[0];
+        assert_eq!(release.artifacts[0].digest.as_ref().unwrap()["sha256"].len(), 64);
+    }
+
+    #[tokio::test]
+    async fn writes_a_signed_tree() {
+        let dir = TempDir::new().unwrap();
+        let answers = gather_one_release(dir.path()).await;
+        let out = dir.path().join("out");
+        let tree = write_init_tree(&answers, &DidKeySigner::new(Box::new(Ed25519Key::generate())).await.unwrap(), &out).await.unwrap();
+        assert_eq!(tree.root_uri, "https://example.com/.well-known/chainsights/catalog.jsonl");
+        let relative: Vec<_> = tree.signed.iter().map(|path| path.strip_prefix(out.join("public")).unwrap()).collect();
+        assert_eq!(relative, ["components/my-app/1.0.0.jsonl", "components/my-app.jsonl", "catalog.jsonl"].map(Path::new));
+        let catalog = Draft::read_catalog(&out.join("drafts/catalog.json"), None).unwrap();
+        let link = &catalog.predicate.components[0].component_attestation_link;
+        assert!(link.expected_signer_identity.starts_with("did:key:z6Mk"));
+        assert!(link.digest.is_some());
+    }
+}
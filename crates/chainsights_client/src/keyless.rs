@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Keyless signing through Sigstore: an ephemeral key is certified by Fulcio for the OIDC
+//! identity of the signer, such as a CI workflow, and each signature is logged in Rekor.
+
+use anyhow::{Context, Result, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD, engine::general_purpose::URL_SAFE_NO_PAD};
+use chainsights_core::bundle::{CertificateData, DsseEnvelope, SignatureData, SigstoreBundleData, VerificationMaterial, construct_pae};
+use futures::future::BoxFuture;
+use p256::ecdsa::signature::Signer as _;
+use p256::ecdsa::{Signature, SigningKey};
+use p256::pkcs8::{EncodePublicKey, LineEnding};
+use serde_json::{Value, json};
+
+use crate::rekor::RekorClient;
+use crate::sign::StatementSigner;
+
+/// The public good Fulcio instance.
+pub(crate) const DEFAULT_FULCIO_URL: &str = "https://fulcio.sigstore.dev";
+
+const IN_TOTO_PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+const BUNDLE_MEDIA_TYPE: &str = "application/vnd.dev.sigstore.bundle.v0.3+json";
+
+/// The OIDC token of the CI job: `SIGSTORE_ID_TOKEN` if set (as GitLab CI `id_tokens` are
+/// conventionally named), otherwise one requested from GitHub Actions for the `sigstore`
+/// audience, which needs the workflow's `id-token: write` permission.
+pub(crate) async fn ambient_identity_token(client: &reqwest::Client) -> Result<String> {
+    let variable = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+    if let Some(token) = variable("SIGSTORE_ID_TOKEN") {
+        return Ok(token);
+    }
+    let (Some(url), Some(request_token)) = (variable("ACTIONS_ID_TOKEN_REQUEST_URL"), variable("ACTIONS_ID_TOKEN_REQUEST_TOKEN")) else {
+        bail!(
+            "No ambient OIDC token: set SIGSTORE_ID_TOKEN, or run in GitHub Actions with `permissions: id-token: write`"
+        );
+    };
+    let response = client
+        .get(&url)
+        .query(&[("audience", "sigstore")])
+        .bearer_auth(request_token)
+        .send()
+        .await
+        .context("Failed to request an OIDC token from GitHub Actions")?
+        .error_for_status()
+        .context("GitHub Actions refused to issue an OIDC token")?;
+    let body: Value = response.json().await.context("Failed to parse the GitHub Actions OIDC token response")?;
+    Ok(body["value"].as_str().context("GitHub Actions returned no OIDC token")?.to_string())
+}
+
+/// The subject Fulcio certifies for a token, which the proof of possession signs: its verified
+/// `email` claim if it has one, otherwise its `sub` claim.
+fn token_subject(token: &str) -> Result<String> {
+    let claims = token.split('.').nth(1).context("The OIDC token is not a JWT")?;
+    let claims: Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(claims.trim_end_matches('=')).context("The OIDC token is not a JWT")?)
+        .context("The OIDC token's claims are not JSON")?;
+    if let Some(email) = claims["email"].as_str()
+        && claims["email_verified"].as_bool() != Some(false)
+    {
+        return Ok(email.to_string());
+    }
+    Ok(claims["sub"].as_str().context("The OIDC token has no subject")?.to_string())
+}
+
+/// Minimal client for the Fulcio certificate authority's v2 API.
+pub(crate) struct FulcioClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl FulcioClient {
+    pub(crate) fn new(client: reqwest::Client, base_url: &str) -> Self {
+        Self { client, base_url: base_url.trim_end_matches('/').to_string() }
+    }
+
+    /// Requests a short-lived certificate for `key` naming the identity `token` was issued to,
+    /// returning the leaf certificate in DER.
+    pub(crate) async fn signing_certificate(&self, token: &str, key: &SigningKey) -> Result<Vec<u8>> {
+        let public_key = key
+            .verifying_key()
+            .to_public_key_pem(LineEnding::LF)
+            .map_err(|e| anyhow::anyhow!("{}", e))
+            .context("Failed to encode the ephemeral public key")?;
+        let proof: Signature = key.sign(token_subject(token)?.as_bytes());
+        let request = json!({
+            "credentials": { "oidcIdentityToken": token },
+            "publicKeyRequest": {
+                "publicKey": { "algorithm": "ECDSA", "content": public_key },
+                "proofOfPossession": STANDARD.encode(proof.to_der()),
+            },
+        });
+        let response = self
+            .client
+            .post(format!("{}/api/v2/signingCert", self.base_url))
+            .json(&request)
+            .send()
+            .await
+            .with_context(|| format!("Failed to request a certificate from Fulcio at '{}'", self.base_url))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            bail!("Fulcio refused to issue a certificate: HTTP status {}: {}", status, body.trim());
+        }
+        let body: Value = response.json().await.context("Failed to parse the Fulcio response")?;
+        let chain = body
+            .get("signedCertificateEmbeddedSct")
+            .or_else(|| body.get("signedCertificateDetachedSct"))
+            .and_then(|certificate| certificate["chain"]["certificates"].as_array())
+            .context("Fulcio returned no certificate chain")?;
+        let leaf = chain.first().and_then(Value::as_str).context("Fulcio returned an empty certificate chain")?;
+        Ok(pem::parse(leaf).context("Fulcio returned an invalid certificate")?.into_contents())
+    }
+}
+
+/// Signs statements as Sigstore bundles with an ephemeral key certified by Fulcio, logging each
+/// signature in Rekor unless no log is given. The signer identity is the one in the certificate.
+pub(crate) struct KeylessSigner {
+    key: SigningKey,
+    certificate_der: Vec<u8>,
+    identity: String,
+    rekor: Option<RekorClient>,
+}
+
+impl KeylessSigner {
+    pub(crate) async fn new(fulcio: &FulcioClient, token: &str, rekor: Option<RekorClient>) -> Result<Self> {
+        let key = SigningKey::random(&mut rand::rngs::OsRng);
+        let certificate_der = fulcio.signing_certificate(token, &key).await?;
+        let identity = chainsights_core::verify::certificate_identities(&certificate_der)?
+            .into_iter()
+            .next()
+            .context("The Fulcio certificate names no identity")?;
+        Ok(Self { key, certificate_der, identity, rekor })
+    }
+}
+
+impl StatementSigner for KeylessSigner {
+    fn identity(&self) -> String {
+        self.identity.clone()
+    }
+
+    fn sign<'a>(&'a self, statement: &'a [u8]) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            let signature: Signature = self.key.sign(&construct_pae(IN_TOTO_PAYLOAD_TYPE, statement));
+            let mut bundle = SigstoreBundleData {
+                verification_material: VerificationMaterial {
+                    certificate: CertificateData { raw_bytes: STANDARD.encode(&self.certificate_der) },
+                    tlog_entries: Vec::new(),
+                    timestamp_verification_data: None,
+                },
+                dsse_envelope: DsseEnvelope {
+                    payload: STANDARD.encode(statement),
+                    payload_type: IN_TOTO_PAYLOAD_TYPE.to_string(),
+                    signatures: vec![SignatureData { sig: STANDARD.encode(signature.to_der()) }],
+                },
+            };
+            if let Some(rekor) = &self.rekor {
+                let entry = rekor.upload_bundle(&bundle).await?;
+                bundle.verification_material.tlog_entries.push(entry);
+            }
+            let mut bundle = serde_json::to_value(&bundle).context("Failed to serialize bundle")?;
+            bundle["mediaType"] = json!(BUNDLE_MEDIA_TYPE);
+            serde_json::to_string(&bundle).context("Failed to serialize bundle")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chainsights_core::identity::SignerIdentity;
+    use chainsights_core::timestamp::TimeSource;
+    use chainsights_core::verify::verify_signature_with_trusted_root;
+    use chainsights_testing::PrivateSigstore;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn signs_with_a_fulcio_certificate_for_the_token_identity() {
+        let workflow = "https://github.com/example/app/.github/workflows/release.yml@refs/tags/v1.0.0";
+        let claims = json!({ "sub": "repo:example/app:ref:refs/tags/v1.0.0", "iss": "https://token.actions.githubusercontent.com" });
+        let token = format!("e30.{}.c2ln", URL_SAFE_NO_PAD.encode(claims.to_string()));
+        assert_eq!(token_subject(&token).unwrap(), "repo:example/app:ref:refs/tags/v1.0.0");
+
+        // The mock Fulcio certifies whatever key it is sent for the workflow identity.
+        let sigstore = Arc::new(PrivateSigstore::new().unwrap());
+        let server = MockServer::start().await;
+        let fulcio = sigstore.clone();
+        Mock::given(method("POST"))
+            .and(path("/api/v2/signingCert"))
+            .respond_with(move |request: &Request| {
+                let body: Value = request.body_json().unwrap();
+                let public_key = body["publicKeyRequest"]["publicKey"]["content"].as_str().unwrap();
+                let certificate = fulcio.certify(workflow, public_key).unwrap();
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "signedCertificateEmbeddedSct": { "chain": { "certificates": [certificate] } } }))
+            })
+            .mount(&server)
+            .await;
+
+        let signer = KeylessSigner::new(&FulcioClient::new(reqwest::Client::new(), &server.uri()), &token, None).await.unwrap();
+        assert_eq!(signer.identity(), workflow);
+        let statement = br#"{"_type":"https://in-toto.io/Statement/v1"}"#;
+        let bundle = signer.sign(statement).await.unwrap();
+        let identities = [SignerIdentity::new(workflow)];
+        let verified =
+            verify_signature_with_trusted_root(&bundle, &identities, TimeSource::Local, chrono::Duration::seconds(300), &sigstore.trusted_root())
+                .unwrap();
+        assert_eq!(verified.payload, statement);
+    }
+}
@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use futures::future::BoxFuture;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+use crate::aws::AwsCredentials;
+use crate::kms::send;
+use crate::sign::KeySigner;
+
+/// The region of a key given by ARN (`arn:aws:kms:us-east-1:111122223333:key/...`).
+pub(crate) fn key_region(key_id: &str) -> Option<String> {
+    key_id.strip_prefix("arn:")?.split(':').nth(2).filter(|region| !region.is_empty()).map(str::to_string)
+}
+
+/// Signs with an AWS KMS `ECC_NIST_P256` key, given by key ID, alias (`alias/release`) or ARN,
+/// using the AWS credentials in the environment.
+pub(crate) struct AwsKmsSigner {
+    client: reqwest::Client,
+    endpoint: String,
+    credentials: AwsCredentials,
+    region: String,
+    key_id: String,
+}
+
+impl AwsKmsSigner {
+    pub(crate) fn new(client: reqwest::Client, endpoint: &str, credentials: AwsCredentials, region: &str, key_id: &str) -> Self {
+        Self {
+            client,
+            endpoint: format!("{}/", endpoint.trim_end_matches('/')),
+            credentials,
+            region: region.to_string(),
+            key_id: key_id.to_string(),
+        }
+    }
+
+    async fn call(&self, action: &str, body: Value) -> Result<Value> {
+        let url = reqwest::Url::parse(&self.endpoint).with_context(|| format!("Invalid AWS KMS endpoint '{}'", self.endpoint))?;
+        let body = serde_json::to_vec(&body)?;
+        let headers = self.credentials.signed_headers(
+            "POST",
+            &url,
+            vec![
+                ("content-type", "application/x-amz-json-1.1".to_string()),
+                ("x-amz-target", format!("TrentService.{}", action)),
+            ],
+            &body,
+            &self.region,
+            "kms",
+        );
+        let mut request = self.client.post(url).body(body);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        send(request, &format!("AWS KMS {}", action)).await
+    }
+}
+
+impl KeySigner for AwsKmsSigner {
+    fn did_key(&self) -> BoxFuture<'_, Result<String>> {
+        Box::pin(async move {
+            let response = self.call("GetPublicKey", json!({ "KeyId": self.key_id })).await?;
+            if let Some(spec) = response["KeySpec"].as_str().filter(|spec| *spec != "ECC_NIST_P256") {
+                bail!("AWS KMS key {} is {}; only ECC_NIST_P256 keys can sign statements", self.key_id, spec);
+            }
+            let spki = STANDARD
+                .decode(response["PublicKey"].as_str().context("GetPublicKey returned no public key")?)
+                .context("GetPublicKey returned an invalid public key")?;
+            chainsights_core::did::spki_did_key(&spki)
+        })
+    }
+
+    fn sign<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Vec<u8>>> {
+        Box::pin(async move {
+            let request = json!({
+                "KeyId": self.key_id,
+                "Message": STANDARD.encode(Sha256::digest(message)),
+                "MessageType": "DIGEST",
+                "SigningAlgorithm": "ECDSA_SHA_256",
+            });
+            let response = self.call("Sign", request).await?;
+            STANDARD
+                .decode(response["Signature"].as_str().context("Sign returned no signature")?)
+                .context("Sign returned an invalid signature")
+        })
+    }
+}
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, bail};
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use futures::future::BoxFuture;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use tokio::sync::OnceCell;
+
+use crate::kms::send;
+use crate::sign::KeySigner;
+
+const API_VERSION: &str = "7.4";
+
+/// Signs with an Azure Key Vault `P-256` key with ES256, with an access token for
+/// `https://vault.azure.net` such as `az account get-access-token` prints. A key given without
+/// a version signs with its current version.
+pub(crate) struct KeyVaultSigner {
+    client: reqwest::Client,
+    key_url: String,
+    access_token: String,
+    /// The key version's ID (`https://<vault>/keys/<name>/<version>`), once looked up.
+    kid: OnceCell<String>,
+}
+
+impl KeyVaultSigner {
+    /// `key` is `<name>` or `<name>/<version>`.
+    pub(crate) fn new(client: reqwest::Client, vault_url: &str, access_token: String, key: &str) -> Result<Self> {
+        let key = key.trim_matches('/');
+        if key.is_empty() || key.split('/').count() > 2 {
+            bail!("'{}' is not a Key Vault key (NAME or NAME/VERSION)", key);
+        }
+        Ok(Self {
+            client,
+            key_url: format!("{}/keys/{}", vault_url.trim_end_matches('/'), key),
+            access_token,
+            kid: OnceCell::new(),
+        })
+    }
+
+    async fn key(&self) -> Result<Value> {
+        let request = self.client.get(&self.key_url).query(&[("api-version", API_VERSION)]).bearer_auth(&self.access_token);
+        let response = send(request, "Key Vault get key").await?;
+        Ok(response["key"].clone())
+    }
+
+    async fn kid(&self) -> Result<&String> {
+        self.kid
+            .get_or_try_init(|| async {
+                let key = self.key().await?;
+                Ok(key["kid"].as_str().context("Key Vault returned a key without an ID")?.to_string())
+            })
+            .await
+    }
+}
+
+impl KeySigner for KeyVaultSigner {
+    fn did_key(&self) -> BoxFuture<'_, Result<String>> {
+        Box::pin(async move {
+            let key = self.key().await?;
+            if !matches!(key["kty"].as_str(), Some("EC" | "EC-HSM")) || key["crv"] != "P-256" {
+                bail!("Key Vault key {} is not a P-256 key; only P-256 keys can sign statements", self.key_url);
+            }
+            let coordinate = |name: &str| -> Result<Vec<u8>> {
+                URL_SAFE_NO_PAD
+                    .decode(key[name].as_str().with_context(|| format!("Key Vault key has no '{}' coordinate", name))?)
+                    .with_context(|| format!("Key Vault key has an invalid '{}' coordinate", name))
+            };
+            if let Some(kid) = key["kid"].as_str() {
+                let _ = self.kid.set(kid.to_string());
+            }
+            chainsights_core::did::p256_did_key(&[vec![0x04], coordinate("x")?, coordinate("y")?].concat())
+        })
+    }
+
+    fn sign<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Vec<u8>>> {
+        Box::pin(async move {
+            let request = self
+                .client
+                .post(format!("{}/sign", self.kid().await?))
+                .query(&[("api-version", API_VERSION)])
+                .bearer_auth(&self.access_token)
+                .json(&json!({ "alg": "ES256", "value": URL_SAFE_NO_PAD.encode(Sha256::digest(message)) }));
+            let response = send(request, "Key Vault sign").await?;
+            URL_SAFE_NO_PAD
+                .decode(response["value"].as_str().context("Key Vault returned no signature")?)
+                .context("Key Vault returned an invalid signature")
+        })
+    }
+}
@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use futures::future::BoxFuture;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::kms::send;
+use crate::sign::KeySigner;
+
+/// The Google Cloud KMS API.
+pub(crate) const DEFAULT_CLOUD_KMS_API_URL: &str = "https://cloudkms.googleapis.com/v1";
+
+/// Signs with a Cloud KMS `EC_SIGN_P256_SHA256` key version, with an OAuth access token such as
+/// the one `gcloud auth print-access-token` prints.
+pub(crate) struct CloudKmsSigner {
+    client: reqwest::Client,
+    version_url: String,
+    access_token: String,
+}
+
+impl CloudKmsSigner {
+    /// `name` is the key version's resource name:
+    /// `projects/P/locations/L/keyRings/R/cryptoKeys/K/cryptoKeyVersions/V`.
+    pub(crate) fn new(client: reqwest::Client, api_url: &str, access_token: String, name: &str) -> Result<Self> {
+        let name = name.trim_matches('/');
+        if !name.starts_with("projects/") || !name.contains("/cryptoKeyVersions/") {
+            bail!("'{}' is not a key version (projects/P/locations/L/keyRings/R/cryptoKeys/K/cryptoKeyVersions/V)", name);
+        }
+        Ok(Self { client, version_url: format!("{}/{}", api_url.trim_end_matches('/'), name), access_token })
+    }
+}
+
+impl KeySigner for CloudKmsSigner {
+    fn did_key(&self) -> BoxFuture<'_, Result<String>> {
+        Box::pin(async move {
+            let request = self.client.get(format!("{}/publicKey", self.version_url)).bearer_auth(&self.access_token);
+            let response = send(request, "Cloud KMS getPublicKey").await?;
+            if let Some(algorithm) = response["algorithm"].as_str().filter(|algorithm| *algorithm != "EC_SIGN_P256_SHA256") {
+                bail!("Cloud KMS key is {}; only EC_SIGN_P256_SHA256 keys can sign statements", algorithm);
+            }
+            let pem = pem::parse(response["pem"].as_str().context("getPublicKey returned no public key")?)
+                .context("getPublicKey returned an invalid public key")?;
+            chainsights_core::did::spki_did_key(pem.contents())
+        })
+    }
+
+    fn sign<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Vec<u8>>> {
+        Box::pin(async move {
+            let request = self
+                .client
+                .post(format!("{}:asymmetricSign", self.version_url))
+                .bearer_auth(&self.access_token)
+                .json(&json!({ "digest": { "sha256": STANDARD.encode(Sha256::digest(message)) } }));
+            let response = send(request, "Cloud KMS asymmetricSign").await?;
+            STANDARD
+                .decode(response["signature"].as_str().context("asymmetricSign returned no signature")?)
+                .context("asymmetricSign returned an invalid signature")
+        })
+    }
+}
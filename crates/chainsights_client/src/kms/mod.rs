@@ -0,0 +1,157 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Signing with keys held by a cloud KMS or a hardware token, so they never leave it. Keys are
+//! named by URI, as cosign names them: `awskms://`, `gcpkms://`, `azurekms://` and `pkcs11:`.
+//! The cloud backends sign with ECDSA P-256 keys, sending only the SHA-256 digest of the message.
+
+pub(crate) mod aws;
+pub(crate) mod azure;
+pub(crate) mod gcp;
+pub(crate) mod pkcs11;
+
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+
+use crate::aws::AwsCredentials;
+use crate::sign::KeySigner;
+
+/// True if `spec` names a key by a KMS or PKCS#11 URI.
+pub(crate) fn is_key_uri(spec: &str) -> bool {
+    ["awskms://", "gcpkms://", "azurekms://", "pkcs11:"].iter().any(|scheme| spec.starts_with(scheme))
+}
+
+/// The signer for a key URI. No request is made until the key is used.
+pub(crate) fn open(uri: &str) -> Result<Box<dyn KeySigner>> {
+    let client = reqwest::Client::new();
+    let variable = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+    if let Some(key) = uri.strip_prefix("awskms://") {
+        let (endpoint, key_id) = key.split_once('/').with_context(|| format!("'{}' is not awskms://[ENDPOINT]/KEY", uri))?;
+        let region = aws::key_region(key_id)
+            .or_else(|| variable("AWS_REGION"))
+            .or_else(|| variable("AWS_DEFAULT_REGION"))
+            .context("AWS KMS needs a region: give the key's ARN, or set AWS_REGION")?;
+        let endpoint = match endpoint {
+            "" => format!("https://kms.{}.amazonaws.com", region),
+            host => format!("https://{}", host),
+        };
+        return Ok(Box::new(aws::AwsKmsSigner::new(client, &endpoint, AwsCredentials::from_env()?, &region, key_id)));
+    }
+    if let Some(name) = uri.strip_prefix("gcpkms://") {
+        let access_token = variable("GOOGLE_OAUTH_ACCESS_TOKEN")
+            .context("Cloud KMS needs an access token in GOOGLE_OAUTH_ACCESS_TOKEN (gcloud auth print-access-token)")?;
+        return Ok(Box::new(gcp::CloudKmsSigner::new(client, gcp::DEFAULT_CLOUD_KMS_API_URL, access_token, name)?));
+    }
+    if let Some(key) = uri.strip_prefix("azurekms://") {
+        let access_token = variable("AZURE_ACCESS_TOKEN").context(
+            "Azure Key Vault needs an access token in AZURE_ACCESS_TOKEN \
+             (az account get-access-token --resource https://vault.azure.net)",
+        )?;
+        let (vault, key) = key.split_once('/').with_context(|| format!("'{}' is not azurekms://VAULT/KEY[/VERSION]", uri))?;
+        return Ok(Box::new(azure::KeyVaultSigner::new(client, &format!("https://{}", vault), access_token, key)?));
+    }
+    if uri.starts_with("pkcs11:") {
+        return Ok(Box::new(pkcs11::Pkcs11Signer::open(&pkcs11::Pkcs11Uri::parse(uri)?)?));
+    }
+    bail!("'{}' is not a KMS or PKCS#11 key URI", uri)
+}
+
+/// Sends a KMS request, returning the JSON response.
+async fn send(request: reqwest::RequestBuilder, action: &str) -> Result<Value> {
+    let response = request.send().await.with_context(|| format!("Failed to send {} request", action))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        bail!("{} failed with HTTP status {}: {}", action, status, body.trim());
+    }
+    response.json().await.with_context(|| format!("Failed to parse {} response", action))
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+    use base64::Engine as _;
+    use chainsights_core::did::{DidVerifier, did_key};
+    use chainsights_core::verifier::Verifier;
+    use p256::ecdsa::signature::hazmat::PrehashSigner;
+    use p256::ecdsa::{Signature, SigningKey};
+    use p256::pkcs8::{EncodePublicKey, LineEnding};
+    use serde_json::json;
+    use wiremock::matchers::{bearer_token, method, path, query_param};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    use super::*;
+    use crate::sign::{DidKeySigner, StatementSigner};
+
+    async fn assert_signs_verifiably(key: Box<dyn KeySigner>) {
+        let signer = DidKeySigner::new(key).await.unwrap();
+        assert!(signer.identity().starts_with("did:key:zDn"));
+        let statement = br#"{"_type":"https://in-toto.io/Statement/v1"}"#;
+        let envelope = signer.sign(statement).await.unwrap();
+        let verified = DidVerifier::new(vec![did_key(&signer.identity()).unwrap()]).verify(&envelope, None).unwrap();
+        assert_eq!(verified.payload, statement);
+    }
+
+    #[tokio::test]
+    async fn cloud_kms_signatures_verify_against_the_key_did() {
+        let server = MockServer::start().await;
+        let key = SigningKey::random(&mut rand::rngs::OsRng);
+
+        // Cloud KMS signs the SHA-256 digest it is sent, returning a DER signature.
+        let version = "projects/p/locations/global/keyRings/release/cryptoKeys/catalog/cryptoKeyVersions/1";
+        let pem = key.verifying_key().to_public_key_pem(LineEnding::LF).unwrap();
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/{}/publicKey", version)))
+            .and(bearer_token("gcp-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "pem": pem, "algorithm": "EC_SIGN_P256_SHA256" })))
+            .mount(&server)
+            .await;
+        let signing = key.clone();
+        Mock::given(method("POST"))
+            .and(path(format!("/v1/{}:asymmetricSign", version)))
+            .respond_with(move |request: &Request| {
+                let body: Value = request.body_json().unwrap();
+                let digest = STANDARD.decode(body["digest"]["sha256"].as_str().unwrap()).unwrap();
+                let signature: Signature = signing.sign_prehash(&digest).unwrap();
+                ResponseTemplate::new(200).set_body_json(json!({ "signature": STANDARD.encode(signature.to_der()) }))
+            })
+            .mount(&server)
+            .await;
+        let api_url = format!("{}/v1", server.uri());
+        let gcp = gcp::CloudKmsSigner::new(reqwest::Client::new(), &api_url, "gcp-token".to_string(), version).unwrap();
+        assert_signs_verifiably(Box::new(gcp)).await;
+
+        // Key Vault signs with the current key version, returning a fixed-size signature.
+        let point = key.verifying_key().to_encoded_point(false);
+        let kid = format!("{}/keys/release/0123abcd", server.uri());
+        Mock::given(method("GET"))
+            .and(path("/keys/release"))
+            .and(query_param("api-version", "7.4"))
+            .and(bearer_token("azure-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "key": {
+                "kid": kid,
+                "kty": "EC-HSM",
+                "crv": "P-256",
+                "x": URL_SAFE_NO_PAD.encode(point.x().unwrap()),
+                "y": URL_SAFE_NO_PAD.encode(point.y().unwrap()),
+            }})))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/keys/release/0123abcd/sign"))
+            .respond_with(move |request: &Request| {
+                let body: Value = request.body_json().unwrap();
+                assert_eq!(body["alg"], "ES256");
+                let digest = URL_SAFE_NO_PAD.decode(body["value"].as_str().unwrap()).unwrap();
+                let signature: Signature = key.sign_prehash(&digest).unwrap();
+                ResponseTemplate::new(200).set_body_json(json!({ "value": URL_SAFE_NO_PAD.encode(signature.to_bytes()) }))
+            })
+            .mount(&server)
+            .await;
+        let azure = azure::KeyVaultSigner::new(reqwest::Client::new(), &server.uri(), "azure-token".to_string(), "release").unwrap();
+        assert_signs_verifiably(Box::new(azure)).await;
+
+        assert_eq!(aws::key_region("arn:aws:kms:eu-west-1:111122223333:key/1234").as_deref(), Some("eu-west-1"));
+        assert_eq!(aws::key_region("alias/release"), None);
+        assert!(is_key_uri("pkcs11:object=release") && !is_key_uri("store:release"));
+    }
+}
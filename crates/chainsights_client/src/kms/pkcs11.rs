@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use cryptoki::context::{CInitializeArgs, CInitializeFlags, Pkcs11};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::slot::Slot;
+use cryptoki::types::AuthPin;
+use futures::future::BoxFuture;
+use sha2::{Digest, Sha256};
+
+use crate::sign::KeySigner;
+
+/// Environment variable naming the PKCS#11 module, when the URI gives no `module-path`.
+const MODULE_ENV: &str = "CHAINSIGHTS_PKCS11_MODULE";
+/// Environment variable holding the token's user PIN, when the URI gives no `pin-value`.
+const PIN_ENV: &str = "CHAINSIGHTS_PKCS11_PIN";
+
+/// A key on a PKCS#11 token, as an RFC 7512 URI names it, e.g.
+/// `pkcs11:token=YubiKey%20PIV;object=Private%20key%20for%20Digital%20Signature?module-path=/usr/lib/libykcs11.so`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Pkcs11Uri {
+    pub(crate) module_path: Option<PathBuf>,
+    pub(crate) token: Option<String>,
+    pub(crate) slot_id: Option<u64>,
+    pub(crate) object: Option<String>,
+    pub(crate) id: Option<Vec<u8>>,
+    pub(crate) pin: Option<String>,
+}
+
+impl Pkcs11Uri {
+    pub(crate) fn parse(uri: &str) -> Result<Self> {
+        let rest = uri.strip_prefix("pkcs11:").with_context(|| format!("'{}' is not a pkcs11: URI", uri))?;
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let mut parsed = Pkcs11Uri::default();
+        let attributes = path.split(';').chain(query.split('&')).filter(|attribute| !attribute.is_empty());
+        for attribute in attributes {
+            let (name, value) = attribute.split_once('=').with_context(|| format!("'{}' in '{}' is not NAME=VALUE", attribute, uri))?;
+            let value = percent_decode(value).with_context(|| format!("Invalid percent-encoding in '{}'", attribute))?;
+            let text = || String::from_utf8(value.clone()).with_context(|| format!("'{}' is not UTF-8", attribute));
+            match name {
+                "module-path" => parsed.module_path = Some(PathBuf::from(text()?)),
+                "token" => parsed.token = Some(text()?),
+                "slot-id" => parsed.slot_id = Some(text()?.parse().with_context(|| format!("Invalid slot-id in '{}'", uri))?),
+                "object" => parsed.object = Some(text()?),
+                "id" => parsed.id = Some(value),
+                "pin-value" => parsed.pin = Some(text()?),
+                // Other attributes (manufacturer, model, type, ...) narrow the match no further here.
+                _ => {}
+            }
+        }
+        if parsed.object.is_none() && parsed.id.is_none() {
+            bail!("'{}' names no key; give its object= label or id=", uri);
+        }
+        Ok(parsed)
+    }
+}
+
+fn percent_decode(value: &str) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail.get(..2).context("Truncated percent-encoding")?;
+            bytes.push(u8::from_str_radix(std::str::from_utf8(hex)?, 16)?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Signs with an ECDSA P-256 key on a PKCS#11 token, such as a YubiKey's PIV applet through
+/// `libykcs11` or an HSM. The token's public key object gives the key's `did:key`.
+pub(crate) struct Pkcs11Signer {
+    token: Arc<Token>,
+}
+
+struct Token {
+    context: Pkcs11,
+    uri: Pkcs11Uri,
+    pin: AuthPin,
+}
+
+impl Pkcs11Signer {
+    /// Loads the PKCS#11 module, and takes the PIN from the URI, `CHAINSIGHTS_PKCS11_PIN`, or the
+    /// terminal.
+    pub(crate) fn open(uri: &Pkcs11Uri) -> Result<Self> {
+        let module = match &uri.module_path {
+            Some(path) => path.clone(),
+            None => PathBuf::from(std::env::var(MODULE_ENV).with_context(|| {
+                format!("Give the PKCS#11 module as the URI's module-path, or set {}", MODULE_ENV)
+            })?),
+        };
+        let context = Pkcs11::new(&module).with_context(|| format!("Failed to load PKCS#11 module '{}'", module.display()))?;
+        context
+            .initialize(CInitializeArgs::new(CInitializeFlags::OS_LOCKING_OK))
+            .context("Failed to initialize the PKCS#11 module")?;
+        let pin = match uri.pin.clone().or_else(|| std::env::var(PIN_ENV).ok()) {
+            Some(pin) => pin,
+            None => rpassword::prompt_password(format!("PIN for {}: ", uri.token.as_deref().unwrap_or("the token")))
+                .context("Failed to read the PIN")?,
+        };
+        let uri = Pkcs11Uri { pin: None, ..uri.clone() };
+        Ok(Self { token: Arc::new(Token { context, uri, pin: AuthPin::from(pin) }) })
+    }
+}
+
+impl Token {
+    fn slot(&self) -> Result<Slot> {
+        let mut matching = Vec::new();
+        for slot in self.context.get_slots_with_token().context("Failed to list PKCS#11 slots")? {
+            let label = self.context.get_token_info(slot).context("Failed to read PKCS#11 token info")?.label().trim().to_string();
+            if self.uri.slot_id.is_none_or(|id| id == slot.id()) && self.uri.token.as_ref().is_none_or(|token| *token == label) {
+                matching.push(slot);
+            }
+        }
+        match matching[..] {
+            [slot] => Ok(slot),
+            [] => bail!("No PKCS#11 token matches; is it plugged in?"),
+            _ => bail!("Several PKCS#11 tokens match; give the token= label or slot-id="),
+        }
+    }
+
+    fn find(&self, session: &Session, class: ObjectClass) -> Result<ObjectHandle> {
+        let mut template = vec![Attribute::Class(class)];
+        if let Some(object) = &self.uri.object {
+            template.push(Attribute::Label(object.as_bytes().to_vec()));
+        }
+        if let Some(id) = &self.uri.id {
+            template.push(Attribute::Id(id.clone()));
+        }
+        let objects = session.find_objects(&template).context("Failed to search the PKCS#11 token")?;
+        match objects[..] {
+            [object] => Ok(object),
+            [] => bail!("The PKCS#11 token has no matching {} object", class),
+            _ => bail!("Several {} objects on the PKCS#11 token match; give its id=", class),
+        }
+    }
+
+    fn did_key(&self) -> Result<String> {
+        let session = self.context.open_ro_session(self.slot()?).context("Failed to open a PKCS#11 session")?;
+        let key = self.find(&session, ObjectClass::PUBLIC_KEY)?;
+        let point = match session.get_attributes(key, &[AttributeType::EcPoint]).context("Failed to read the public key")?.pop() {
+            Some(Attribute::EcPoint(point)) => point,
+            _ => bail!("The PKCS#11 public key is not an EC key"),
+        };
+        // CKA_EC_POINT is a DER OCTET STRING holding the SEC1 point, though some tokens omit it.
+        let sec1 = match point[..] {
+            [0x04, length, ref rest @ ..] if length as usize == rest.len() => rest,
+            _ => &point[..],
+        };
+        chainsights_core::did::p256_did_key(sec1).context("The PKCS#11 key is not a P-256 key")
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        let session = self.context.open_ro_session(self.slot()?).context("Failed to open a PKCS#11 session")?;
+        session.login(UserType::User, Some(&self.pin)).context("Failed to log in to the PKCS#11 token; is the PIN right?")?;
+        let key = self.find(&session, ObjectClass::PRIVATE_KEY)?;
+        session.sign(&Mechanism::Ecdsa, key, &Sha256::digest(message)).context("The PKCS#11 token failed to sign")
+    }
+}
+
+impl KeySigner for Pkcs11Signer {
+    fn did_key(&self) -> BoxFuture<'_, Result<String>> {
+        let token = self.token.clone();
+        Box::pin(async move { tokio::task::spawn_blocking(move || token.did_key()).await? })
+    }
+
+    fn sign<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Vec<u8>>> {
+        let token = self.token.clone();
+        let message = message.to_vec();
+        Box::pin(async move { tokio::task::spawn_blocking(move || token.sign(&message)).await? })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pkcs11_uris() {
+        let uri = Pkcs11Uri::parse(
+            "pkcs11:token=YubiKey%20PIV%20%2312345;id=%02;type=private?module-path=/usr/lib/libykcs11.so&pin-value=123456",
+        )
+        .unwrap();
+        assert_eq!(uri, Pkcs11Uri {
+            module_path: Some(PathBuf::from("/usr/lib/libykcs11.so")),
+            token: Some("YubiKey PIV #12345".to_string()),
+            slot_id: None,
+            object: None,
+            id: Some(vec![0x02]),
+            pin: Some("123456".to_string()),
+        });
+        assert_eq!(Pkcs11Uri::parse("pkcs11:slot-id=1;object=release").unwrap().object.as_deref(), Some("release"));
+        assert!(Pkcs11Uri::parse("pkcs11:token=YubiKey").is_err());
+        assert!(Pkcs11Uri::parse("pkcs11:object=%2").is_err());
+    }
+}
@@ -0,0 +1,265 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checking the build attestations a release links against an in-toto layout: the steps its supply
+//! chain must go through, who may carry out each one, and how many of them must attest to it.
+//!
+//! Layouts are the JSON documents in-toto defines (optionally inside their `signed` envelope),
+//! except that functionaries are named by the signer identities used everywhere else here rather
+//! than by key ID, in a `functionaries` list on each step. The layout's own signatures are not
+//! checked, so it should come from a trusted place. Steps are attested by in-toto link statements
+//! naming the step, or by any statement of the step's `expected_predicate_type` (such as SLSA
+//! provenance for a build step), linked from the release as `application/vnd.in-toto+json`.
+
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use chainsights_core::identity::{SignerIdentity, same_identity};
+use chainsights_core::verify::{verify_signature_with_pae, verify_signature_with_trusted_root};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::fetch::{Fetchers, fetch_and_verify_artifact};
+use crate::models::aggregation::{AggregatedCatalogData, AggregatedComponentData, LayoutCompliance, StepCompliance};
+use crate::models::chainsights::ArtifactLink;
+use crate::output::{self, Status};
+use crate::traversal::TraversalOptions;
+
+const IN_TOTO_MEDIA_TYPE: &str = "application/vnd.in-toto+json";
+const LINK_PREDICATE_PREFIX: &str = "https://in-toto.io/attestation/link/";
+
+/// An in-toto layout.
+#[derive(Deserialize, Debug)]
+pub(crate) struct Layout {
+    #[serde(rename = "_type")]
+    kind: String,
+    expires: Option<DateTime<Utc>>,
+    steps: Vec<Step>,
+}
+
+/// A step of a layout.
+#[derive(Deserialize, Debug)]
+struct Step {
+    name: String,
+    /// Signer identities allowed to carry out the step.
+    #[serde(default)]
+    functionaries: Vec<String>,
+    /// The in-toto way of naming functionaries, by key ID, which cannot be matched to signer identities.
+    #[serde(default)]
+    pubkeys: Vec<String>,
+    #[serde(default = "one")]
+    threshold: usize,
+    /// A predicate type whose statements attest to the step, besides link statements naming it.
+    expected_predicate_type: Option<String>,
+}
+
+fn one() -> usize {
+    1
+}
+
+impl Layout {
+    pub(crate) fn read(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read layout '{}'", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Invalid in-toto layout '{}'", path.display()))
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        let mut document: Value = serde_json::from_str(text).context("The layout is not JSON")?;
+        if let Some(signed) = document.get_mut("signed") {
+            document = signed.take();
+        }
+        let layout: Layout = serde_json::from_value(document)?;
+        if layout.kind != "layout" {
+            bail!("The document is an in-toto '{}', not a layout", layout.kind);
+        }
+        if layout.steps.is_empty() {
+            bail!("The layout has no steps");
+        }
+        for step in &layout.steps {
+            if step.functionaries.is_empty() && !step.pubkeys.is_empty() {
+                bail!("Step '{}' names its functionaries by key ID; list their signer identities as functionaries", step.name);
+            }
+            if step.threshold == 0 || step.threshold > step.functionaries.len() {
+                bail!("Step '{}' has a threshold of {} but {} functionaries", step.name, step.threshold, step.functionaries.len());
+            }
+        }
+        Ok(layout)
+    }
+
+    /// Verifies the in-toto attestations among `links` and checks them against the layout's steps.
+    /// Attestations of none of its steps are ignored.
+    pub(crate) async fn check(&self, links: &[ArtifactLink], fetchers: &Fetchers, options: &TraversalOptions) -> LayoutCompliance {
+        let mut steps: Vec<StepCompliance> = self
+            .steps
+            .iter()
+            .map(|step| StepCompliance { name: step.name.clone(), threshold: step.threshold, ..Default::default() })
+            .collect();
+        let mut errors = Vec::new();
+        if let Some(expires) = self.expires.filter(|expires| *expires < Utc::now()) {
+            errors.push(("layout".to_string(), format!("The layout expired at {}", expires.to_rfc3339())));
+        }
+
+        let identities: Vec<SignerIdentity> =
+            self.steps.iter().flat_map(|step| &step.functionaries).map(|identity| SignerIdentity::new(identity)).collect();
+        for link in links.iter().filter(|link| link.media_type.as_deref() == Some(IN_TOTO_MEDIA_TYPE) && !link.is_gated()) {
+            let (signer, statement) = match verify_attestation(link, &identities, fetchers, options).await {
+                Ok(verified) => verified,
+                Err(e) => {
+                    errors.push((link.uri.clone(), format!("{:#}", e)));
+                    continue;
+                }
+            };
+            let Some(index) = self.steps.iter().position(|step| step.is_attested_by(&statement)) else {
+                continue;
+            };
+            let (step, compliance) = (&self.steps[index], &mut steps[index]);
+            if !step.functionaries.iter().any(|functionary| same_identity(functionary, &signer)) {
+                errors.push((link.uri.clone(), format!("Signed by {}, who is not a functionary of step '{}'", signer, step.name)));
+                continue;
+            }
+            compliance.attestations.push(link.uri.clone());
+            if !compliance.functionaries.iter().any(|functionary| same_identity(functionary, &signer)) {
+                compliance.functionaries.push(signer);
+            }
+        }
+
+        for step in &mut steps {
+            step.satisfied = step.functionaries.len() >= step.threshold;
+        }
+        LayoutCompliance {
+            compliant: self.expires.is_none_or(|expires| expires >= Utc::now()) && steps.iter().all(|step| step.satisfied),
+            steps,
+            errors,
+        }
+    }
+}
+
+impl Step {
+    fn is_attested_by(&self, statement: &Value) -> bool {
+        let Some(predicate_type) = statement["predicateType"].as_str() else {
+            return false;
+        };
+        (predicate_type.starts_with(LINK_PREDICATE_PREFIX) && statement["predicate"]["name"] == self.name.as_str())
+            || self.expected_predicate_type.as_deref() == Some(predicate_type)
+    }
+}
+
+/// Fetches a linked attestation and verifies it was signed by one of `identities`, returning the
+/// signer and the statement.
+async fn verify_attestation(
+    link: &ArtifactLink,
+    identities: &[SignerIdentity],
+    fetchers: &Fetchers,
+    options: &TraversalOptions,
+) -> Result<(String, Value)> {
+    let bytes = fetch_and_verify_artifact(link, fetchers).await?;
+    let text = String::from_utf8(bytes).context("The attestation is not UTF-8")?;
+    let bundle = text.lines().find(|line| !line.trim().is_empty()).context("The attestation is empty")?;
+    let verified = match &options.trusted_root {
+        Some(root) => verify_signature_with_trusted_root(bundle, identities, options.time_source, options.clock_skew, root),
+        None => verify_signature_with_pae(bundle, identities, options.time_source, options.clock_skew),
+    }
+    .context("The attestation was not signed by a functionary of the layout")?;
+    let statement = serde_json::from_slice(&verified.payload).context("The attestation's payload is not a JSON statement")?;
+    Ok((verified.signer_identity, statement))
+}
+
+/// Checks the releases of every component of a catalog against `layout`.
+pub(crate) async fn check_layout(
+    aggregated_data: &mut AggregatedCatalogData,
+    layout: &Layout,
+    fetchers: &Fetchers,
+    options: &TraversalOptions,
+) {
+    for component in &mut aggregated_data.components {
+        check_component_layout(component, layout, fetchers, options).await;
+    }
+}
+
+/// Checks each release of a component against `layout`, recording its compliance in the release.
+pub(crate) async fn check_component_layout(
+    component: &mut AggregatedComponentData,
+    layout: &Layout,
+    fetchers: &Fetchers,
+    options: &TraversalOptions,
+) {
+    for release in &mut component.releases {
+        let compliance = layout.check(&release.metadata_artifacts, fetchers, options).await;
+        let purl = release.release_predicate.as_ref().map_or(release.release_link_uri.as_str(), |r| r.purl.as_str());
+        match compliance.compliant {
+            true => output::print(1, Status::Verified, &format!("{} complies with the in-toto layout", purl)),
+            false => output::print(1, Status::Failed, &format!("{} does not comply with the in-toto layout", purl)),
+        }
+        release.layout_compliance = Some(compliance);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chainsights_core::statement::InTotoStatement;
+    use chainsights_testing::PrivateSigstore;
+    use serde_json::json;
+    use sha2::{Digest, Sha256};
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn checks_linked_attestations_against_the_layout() {
+        let builder = "https://github.com/example/app/.github/workflows/release.yml@refs/tags/v1.0.0";
+        let reviewer = "reviewer@example.com";
+        let layout = Layout::parse(
+            &json!({ "signed": {
+                "_type": "layout",
+                "expires": "2999-01-01T00:00:00Z",
+                "steps": [
+                    { "_type": "step", "name": "build", "functionaries": [builder], "expected_predicate_type": "https://slsa.dev/provenance/v1" },
+                    { "_type": "step", "name": "review", "functionaries": [reviewer, "second@example.com"], "threshold": 2 },
+                ],
+            }, "signatures": [] })
+            .to_string(),
+        )
+        .unwrap();
+
+        let sigstore = PrivateSigstore::new().unwrap();
+        let server = MockServer::start().await;
+        let mut links = Vec::new();
+        let statements = [
+            ("provenance", builder, "https://slsa.dev/provenance/v1", json!({})),
+            ("review", reviewer, "https://in-toto.io/attestation/link/v0.3", json!({ "name": "review" })),
+            ("forged", "mallory@example.com", "https://slsa.dev/provenance/v1", json!({})),
+        ];
+        for (name, identity, predicate_type, predicate) in statements {
+            let statement = InTotoStatement::new("pkg:generic/app@1.0.0", HashMap::new(), predicate_type, predicate);
+            let (bundle, _) = sigstore.signer(identity).unwrap().sign(&statement).unwrap();
+            Mock::given(path(format!("/{}.intoto.jsonl", name)))
+                .respond_with(ResponseTemplate::new(200).set_body_string(bundle.clone()))
+                .mount(&server)
+                .await;
+            links.push(ArtifactLink {
+                uri: format!("{}/{}.intoto.jsonl", server.uri(), name),
+                digest: Some(HashMap::from([("sha256".to_string(), hex::encode(Sha256::digest(&bundle)))])),
+                media_type: Some(IN_TOTO_MEDIA_TYPE.to_string()),
+                expected_signer_identity: None,
+                access: None,
+                request_url: None,
+            });
+        }
+
+        let options = TraversalOptions { trusted_root: Some(std::sync::Arc::new(sigstore.trusted_root())), ..Default::default() };
+        let compliance = layout.check(&links, &Fetchers::new(reqwest::Client::new()), &options).await;
+        assert!(!compliance.compliant);
+        assert!(compliance.steps[0].satisfied);
+        assert_eq!(compliance.steps[0].functionaries, [builder]);
+        assert!(!compliance.steps[1].satisfied, "one reviewer of two");
+        assert_eq!(compliance.steps[1].attestations, [links[1].uri.clone()]);
+        assert_eq!(compliance.errors.len(), 1);
+        assert_eq!(compliance.errors[0].0, links[2].uri);
+
+        let keyed = json!({ "_type": "layout", "steps": [{ "name": "build", "pubkeys": ["abc123"] }] });
+        assert!(Layout::parse(&keyed.to_string()).is_err());
+    }
+}
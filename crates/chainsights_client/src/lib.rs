@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Discovers, verifies and aggregates the supply chain attestations a domain publishes.
+//!
+//! [`client::Client`] is the async entry point. [`blocking::Client`] offers the same operations
+//! for code that does not run an async runtime.
+//!
+//! Heavyweight subsystems are behind Cargo features, all on by default through `cli`:
+//!
+//! - `client`: the clients, fetching and traversal (reqwest, tokio); [`dane`] checks the TLS
+//!   certificates of attestation hosts against their TLSA records
+//! - `sigstore-verify`: bundle signature verification, implied by `client`
+//! - `dns-discovery`: finding a domain's root catalog through DNS (hickory-resolver); [`dns`]
+//!   lets tests answer the lookups without network access, and [`admission`] decides whether
+//!   container images are attested to by supplier releases
+//! - `oci`: fetching from and publishing to OCI registries (oci-client)
+//! - `cli`: the command-line interface (clap)
+//!
+//! `cli` does not enable the following:
+//!
+//! - `bench`: the criterion benchmarks
+//! - `grpc` and `admission`: the `serve-grpc` and `serve-admission` commands, both enabled by
+//!   `server`
+//! - `tui`: the interactive terminal browser
+//! - `plugins`: WebAssembly predicate parsers and policy checks
+//!
+//! With `default-features = false` only [`models`] is available, for consumers that just parse
+//! Chainsights predicates.
+
+#[cfg(feature = "dns-discovery")]
+pub mod admission;
+#[cfg(feature = "client")]
+pub mod blocking;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "client")]
+pub mod dane;
+#[cfg(feature = "dns-discovery")]
+pub mod dns;
+pub mod models;
+
+#[cfg(feature = "client")]
+mod archivista;
+#[cfg(feature = "cli")]
+mod aws;
+#[cfg(feature = "client")]
+mod cache;
+#[cfg(feature = "cli")]
+mod ci;
+#[cfg(feature = "cli")]
+mod clearlydefined;
+#[cfg(feature = "client")]
+mod consistency;
+#[cfg(feature = "cli")]
+mod csaf;
+#[cfg(feature = "cli")]
+mod depsdev;
+#[cfg(feature = "dns-discovery")]
+mod discovery;
+#[cfg(feature = "cli")]
+mod exec_hooks;
+#[cfg(feature = "client")]
+mod fetch;
+#[cfg(feature = "cli")]
+mod generate;
+#[cfg(feature = "cli")]
+mod gitoid;
+#[cfg(feature = "cli")]
+mod graph;
+#[cfg(feature = "grpc")]
+mod grpc;
+#[cfg(feature = "cli")]
+mod history;
+#[cfg(feature = "cli")]
+mod idn;
+#[cfg(feature = "dns-discovery")]
+mod image;
+#[cfg(feature = "cli")]
+mod init;
+#[cfg(feature = "cli")]
+mod keyless;
+#[cfg(feature = "cli")]
+mod keys;
+#[cfg(feature = "cli")]
+mod kms;
+#[cfg(feature = "cli")]
+mod layout;
+#[cfg(feature = "cli")]
+mod lifecycle;
+#[cfg(feature = "cli")]
+mod local_artifact;
+#[cfg(feature = "cli")]
+mod lockfile;
+#[cfg(feature = "cli")]
+mod metrics;
+#[cfg(feature = "cli")]
+mod meta;
+#[cfg(feature = "cli")]
+mod monitor;
+#[cfg(feature = "oci")]
+mod oci;
+#[cfg(feature = "client")]
+mod output;
+#[cfg(feature = "cli")]
+mod pins;
+#[cfg(feature = "plugins")]
+mod plugins;
+#[cfg(feature = "cli")]
+mod policy;
+#[cfg(feature = "cli")]
+mod progress;
+#[cfg(feature = "cli")]
+mod publish;
+#[cfg(feature = "client")]
+mod purl;
+#[cfg(feature = "client")]
+mod rekor;
+#[cfg(feature = "cli")]
+mod relay;
+#[cfg(feature = "cli")]
+mod relink;
+#[cfg(feature = "cli")]
+mod resolve;
+#[cfg(feature = "client")]
+mod scope;
+#[cfg(feature = "cli")]
+mod scan;
+#[cfg(feature = "cli")]
+mod score;
+#[cfg(feature = "cli")]
+mod scorecard;
+#[cfg(feature = "cli")]
+mod selftest;
+#[cfg(feature = "cli")]
+mod serials;
+#[cfg(feature = "cli")]
+mod setup;
+#[cfg(feature = "cli")]
+mod sign;
+#[cfg(feature = "cli")]
+mod state;
+#[cfg(feature = "client")]
+mod stats;
+#[cfg(feature = "cli")]
+mod swid;
+#[cfg(feature = "cli")]
+mod tea;
+#[cfg(feature = "cli")]
+mod telemetry;
+#[cfg(feature = "cli")]
+mod threshold;
+#[cfg(feature = "client")]
+mod traversal;
+#[cfg(feature = "cli")]
+mod trust;
+#[cfg(feature = "tui")]
+mod tui;
+#[cfg(feature = "admission")]
+mod webhook;
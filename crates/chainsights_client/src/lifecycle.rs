@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::models::chainsights::SupportStream;
+
+/// Where a version stands in its stream's support lifecycle.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum SupportStatus {
+    /// Bug and security fixes are still made.
+    Supported,
+    /// Active support has ended; only security fixes are made.
+    SecurityOnly,
+    /// The stream no longer receives any fixes.
+    EndOfLife,
+    /// The publisher has not declared a support window covering the version.
+    Unknown,
+}
+
+/// The answer to "is this pinned version still supported?".
+#[derive(Serialize, Debug)]
+pub(crate) struct SupportAnswer {
+    /// The queried PURL
+    pub purl: String,
+    /// The queried version
+    pub version: String,
+    /// The release stream covering the version, if the publisher declared one
+    pub stream: Option<String>,
+    /// Whether the stream is a long-term support stream
+    pub lts: bool,
+    /// The version's support status at `evaluated_at`
+    pub status: SupportStatus,
+    /// When active support ends or ended
+    pub support_end: Option<String>,
+    /// When the stream reaches or reached end of life
+    pub eol: Option<String>,
+    /// The time the status was evaluated for
+    pub evaluated_at: String,
+}
+
+/// Finds the most specific stream covering `version` and evaluates its support status at `at`.
+pub(crate) fn support_answer(
+    purl: &str,
+    version: &str,
+    streams: &[SupportStream],
+    at: DateTime<Utc>,
+) -> Result<SupportAnswer> {
+    let stream = streams
+        .iter()
+        .filter(|s| s.contains(version))
+        .max_by_key(|s| s.stream.len());
+
+    let status = match stream {
+        None => SupportStatus::Unknown,
+        Some(stream) => {
+            if stream.eol.as_deref().map(parse_lifecycle_date).transpose()?.is_some_and(|eol| at >= eol) {
+                SupportStatus::EndOfLife
+            } else if stream
+                .support_end
+                .as_deref()
+                .map(parse_lifecycle_date)
+                .transpose()?
+                .is_some_and(|end| at >= end)
+            {
+                SupportStatus::SecurityOnly
+            } else {
+                SupportStatus::Supported
+            }
+        }
+    };
+
+    Ok(SupportAnswer {
+        purl: purl.to_string(),
+        version: version.to_string(),
+        stream: stream.map(|s| s.stream.clone()),
+        lts: stream.and_then(|s| s.lts).unwrap_or(false),
+        status,
+        support_end: stream.and_then(|s| s.support_end.clone()),
+        eol: stream.and_then(|s| s.eol.clone()),
+        evaluated_at: at.to_rfc3339(),
+    })
+}
+
+/// Parses an RFC 3339 time or a YYYY-MM-DD date, which is taken as the start of that day in UTC.
+pub(crate) fn parse_lifecycle_date(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .with_context(|| format!("Invalid date '{}': expected RFC 3339 or YYYY-MM-DD", value))?;
+    Ok(date.and_hms_opt(0, 0, 0).context("Invalid date")?.and_utc())
+}
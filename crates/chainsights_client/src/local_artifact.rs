@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Verification of files obtained out-of-band, e.g. from a mirror or a vendor portal, against the
+//! digests a supplier's signed release statement publishes for its artifacts.
+
+use anyhow::{Context, Result, bail};
+
+use crate::fetch::calculate_digest;
+use crate::models::aggregation::AggregatedReleaseData;
+use crate::models::chainsights::{ArtifactLink, ChainsightsReleasePredicate, Platform};
+
+/// A link of a release statement whose digests a local file matches.
+pub(crate) struct LocalArtifactMatch<'a> {
+    pub link: &'a ArtifactLink,
+    /// The platform of the artifact group the link is in, if any
+    pub platform: Option<&'a Platform>,
+    /// The digest algorithms checked, all of which matched
+    pub algorithms: Vec<&'a str>,
+}
+
+/// Every link of `release` (artifacts, platform artifacts and metadata links) whose supported
+/// digests all match `bytes`. Links without a supported digest never match.
+pub(crate) fn match_release_artifacts<'a>(bytes: &[u8], release: &'a ChainsightsReleasePredicate) -> Result<Vec<LocalArtifactMatch<'a>>> {
+    let links = release
+        .artifacts
+        .iter()
+        .flatten()
+        .map(|link| (link, None))
+        .chain(release.artifact_groups.iter().flatten().flat_map(|group| group.artifacts.iter().map(move |link| (link, Some(&group.platform)))))
+        .chain(release.metadata_links.iter().flatten().map(|link| (link, None)));
+    let mut matches = Vec::new();
+    for (link, platform) in links {
+        let mut algorithms = Vec::new();
+        let mut mismatched = false;
+        for (algorithm, expected) in link.digest.iter().flatten() {
+            let Some(calculated) = calculate_digest(&link.uri, algorithm, expected, bytes)? else {
+                continue;
+            };
+            match !expected.is_empty() && calculated.eq_ignore_ascii_case(expected) {
+                true => algorithms.push(algorithm.as_str()),
+                false => mismatched = true,
+            }
+        }
+        if !mismatched && !algorithms.is_empty() {
+            algorithms.sort_unstable();
+            matches.push(LocalArtifactMatch { link, platform, algorithms });
+        }
+    }
+    Ok(matches)
+}
+
+/// Checks that a release can vouch for a local file: its statement must have been verified
+/// without an insecure mode and must not be revoked.
+pub(crate) fn check_release_trusted(release: &AggregatedReleaseData) -> Result<&ChainsightsReleasePredicate> {
+    let predicate = release
+        .release_predicate
+        .as_deref()
+        .with_context(|| format!("The release statement at '{}' was not loaded", release.release_link_uri))?;
+    if let Some(revocation) = &release.revocation {
+        bail!("Release {} has been revoked by its publisher: {}", predicate.purl, revocation.reason.as_deref().unwrap_or("no reason given"));
+    }
+    if !release.verification.as_ref().is_some_and(|v| v.is_verified()) {
+        bail!("Release {} was accepted UNVERIFIED by an insecure mode, so it cannot vouch for a file", predicate.purl);
+    }
+    Ok(predicate)
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+
+    fn link(uri: &str, digest: &[(&str, &str)]) -> ArtifactLink {
+        serde_json::from_value(serde_json::json!({
+            "uri": uri,
+            "digest": digest.iter().map(|(a, d)| (a.to_string(), d.to_string())).collect::<std::collections::HashMap<_, _>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn matches_links_whose_supported_digests_all_agree() {
+        let bytes = b"release binary";
+        let sha256 = hex::encode(Sha256::digest(bytes));
+        let release: ChainsightsReleasePredicate = serde_json::from_value(serde_json::json!({
+            "timestamp": "2025-01-01T00:00:00Z",
+            "purl": "pkg:chainsights/example.com/app@1.0.0",
+            "name": "1.0.0",
+            "metadataLinks": [link("https://example.com/sbom.json", &[("sha256", &"0".repeat(64))])],
+            "artifacts": [
+                link("https://example.com/app.tar.gz", &[("sha256", &sha256), ("sha512", "unsupported")]),
+                link("https://example.com/other.tar.gz", &[("sha256", &sha256), ("gitBlob", &"0".repeat(40))]),
+                link("https://example.com/undigested.tar.gz", &[]),
+            ],
+            "artifactGroups": [{
+                "platform": { "os": "linux", "architecture": "arm64" },
+                "artifacts": [link("https://example.com/app-arm64", &[("sha256", &sha256.to_uppercase())])],
+            }],
+        }))
+        .unwrap();
+
+        let matches = match_release_artifacts(bytes, &release).unwrap();
+        let uris: Vec<_> = matches.iter().map(|m| m.link.uri.as_str()).collect();
+        assert_eq!(uris, ["https://example.com/app.tar.gz", "https://example.com/app-arm64"]);
+        assert_eq!(matches[0].algorithms, ["sha256"]);
+        assert_eq!(matches[1].platform.map(|p| p.architecture.as_str()), Some("arm64"));
+        assert!(match_release_artifacts(b"tampered", &release).unwrap().is_empty());
+    }
+}
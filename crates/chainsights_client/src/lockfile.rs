@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reading the dependencies pinned by an application's lockfile as ecosystem PURLs.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use packageurl::PackageUrl;
+use serde_json::Value;
+
+/// The dependencies pinned by a `Cargo.lock`, `package-lock.json` (or `npm-shrinkwrap.json`) or
+/// `go.sum`, chosen by file name, as sorted and deduplicated PURLs.
+pub(crate) fn lockfile_purls(path: &Path) -> Result<Vec<String>> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read lockfile '{}'", path.display()))?;
+    let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    let purls = match file_name {
+        "Cargo.lock" => cargo_purls(&text)?,
+        "package-lock.json" | "npm-shrinkwrap.json" => {
+            let lockfile: Value = serde_json::from_str(&text).with_context(|| format!("Failed to parse '{}' as JSON", path.display()))?;
+            npm_purls(&lockfile)?
+        }
+        "go.sum" => go_purls(&text)?,
+        _ => bail!("Unsupported lockfile '{}', expected Cargo.lock, package-lock.json or go.sum", path.display()),
+    };
+    Ok(purls.into_iter().collect())
+}
+
+/// Packages from a registry; workspace members and path or git dependencies are not published,
+/// so they are skipped.
+fn cargo_purls(text: &str) -> Result<BTreeSet<String>> {
+    let mut purls = BTreeSet::new();
+    for block in text.split("[[package]]").skip(1) {
+        let field = |key: &str| {
+            block.lines().find_map(|line| {
+                let (name, value) = line.split_once('=')?;
+                (name.trim() == key).then(|| value.trim().trim_matches('"').to_string())
+            })
+        };
+        let (Some(name), Some(version), Some(source)) = (field("name"), field("version"), field("source")) else {
+            continue;
+        };
+        if source.starts_with("registry+") || source.starts_with("sparse+") {
+            purls.insert(purl("cargo", None, &name, &version)?);
+        }
+    }
+    Ok(purls)
+}
+
+/// Packages installed from the registry, from the `packages` map of lockfile versions 2 and 3 or
+/// the nested `dependencies` of version 1. Linked workspace packages and dependencies pinned to a
+/// git URL or tarball are skipped.
+fn npm_purls(lockfile: &Value) -> Result<BTreeSet<String>> {
+    let mut packages = Vec::new();
+    if let Some(entries) = lockfile.get("packages").and_then(Value::as_object) {
+        for (path, entry) in entries {
+            let Some((_, installed_name)) = path.rsplit_once("node_modules/") else {
+                continue;
+            };
+            if entry.get("link").and_then(Value::as_bool) == Some(true) {
+                continue;
+            }
+            let name = entry.get("name").and_then(Value::as_str).unwrap_or(installed_name);
+            if let Some(version) = entry.get("version").and_then(Value::as_str) {
+                packages.push((name.to_string(), version.to_string()));
+            }
+        }
+    } else if let Some(dependencies) = lockfile.get("dependencies") {
+        collect_npm_v1(dependencies, &mut packages);
+    }
+
+    let mut purls = BTreeSet::new();
+    for (name, version) in packages {
+        if version.contains(':') || version.contains('/') {
+            continue;
+        }
+        let (namespace, name) = match name.split_once('/') {
+            Some((scope, name)) if scope.starts_with('@') => (Some(scope), name),
+            _ => (None, name.as_str()),
+        };
+        purls.insert(purl("npm", namespace, name, &version)?);
+    }
+    Ok(purls)
+}
+
+fn collect_npm_v1(dependencies: &Value, packages: &mut Vec<(String, String)>) {
+    for (name, entry) in dependencies.as_object().into_iter().flatten() {
+        if let Some(version) = entry.get("version").and_then(Value::as_str) {
+            packages.push((name.clone(), version.to_string()));
+        }
+        if let Some(nested) = entry.get("dependencies") {
+            collect_npm_v1(nested, packages);
+        }
+    }
+}
+
+/// Each module version, whether go.sum lists the module's content or only its `go.mod`.
+fn go_purls(text: &str) -> Result<BTreeSet<String>> {
+    let mut purls = BTreeSet::new();
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(module), Some(version)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let version = version.trim_end_matches("/go.mod");
+        let (namespace, name) = match module.rsplit_once('/') {
+            Some((namespace, name)) => (Some(namespace), name),
+            None => (None, module),
+        };
+        purls.insert(purl("golang", namespace, name, version)?);
+    }
+    Ok(purls)
+}
+
+fn purl(ty: &str, namespace: Option<&str>, name: &str, version: &str) -> Result<String> {
+    let mut purl = PackageUrl::new(ty, name).with_context(|| format!("Invalid {} package name '{}'", ty, name))?;
+    if let Some(namespace) = namespace {
+        purl.with_namespace(namespace);
+    }
+    purl.with_version(version);
+    Ok(purl.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_registry_packages_from_each_lockfile_format() {
+        let cargo = r#"
+version = 4
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["serde"]
+
+[[package]]
+name = "serde"
+version = "1.0.219"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "5f0e2c6ed6606019b4e29e69dbaba95b11854410e5347d525002456dbbb786b6"
+"#;
+        assert_eq!(cargo_purls(cargo).unwrap().into_iter().collect::<Vec<_>>(), ["pkg:cargo/serde@1.0.219"]);
+
+        let npm = serde_json::json!({
+            "lockfileVersion": 3,
+            "packages": {
+                "": { "name": "app", "version": "1.0.0" },
+                "node_modules/left-pad": { "version": "1.3.0" },
+                "node_modules/@acme/widgets": { "version": "2.0.0" },
+                "node_modules/@acme/widgets/node_modules/left-pad": { "version": "1.1.0" },
+                "node_modules/local": { "resolved": "packages/local", "link": true },
+                "node_modules/forked": { "version": "git+ssh://git@github.com/acme/forked.git#abc" }
+            }
+        });
+        let npm = npm_purls(&npm).unwrap();
+        assert_eq!(npm.len(), 3);
+        assert!(npm.contains("pkg:npm/left-pad@1.1.0"));
+        assert!(npm.iter().any(|purl| purl.contains("acme") && purl.ends_with("/widgets@2.0.0")));
+
+        let go = "github.com/google/uuid v1.6.0 h1:NIvaJDMOsjHA8n1jAhLSgzrAzy1Hgr+hNrb57e+94F0=\n\
+                  github.com/google/uuid v1.6.0/go.mod h1:TIyPZe4MgqvfeYDBFedMoGGpEw/LqOeaOT+nhxU+yHo=\n";
+        assert_eq!(go_purls(go).unwrap().into_iter().collect::<Vec<_>>(), ["pkg:golang/github.com/google/uuid@v1.6.0"]);
+    }
+}
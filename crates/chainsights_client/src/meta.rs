@@ -0,0 +1,241 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, bail};
+use chainsights_core::identity::{SignerIdentity, normalize_domain};
+use chainsights_core::txt::ChainsightsRecord;
+use futures::future::BoxFuture;
+
+use crate::discovery::Discovery;
+use crate::fetch::{Fetchers, fetch_document_text};
+use crate::models::aggregation::{AggregatedCatalogData, RootDiscovery};
+use crate::output::Status;
+use crate::pins::RootPins;
+use crate::traversal::{TraversalOptions, traverse_and_aggregate};
+
+/// The `name` of the meta tag that points at a domain's root catalog.
+const META_NAME: &str = "chainsights";
+
+/// Finds the root catalog through a `<meta name="chainsights" content="uri=...;identity=...">`
+/// tag on the domain's homepage, for publishers who cannot add DNS records.
+///
+/// The catalog is verified as usual, but against the identity the page names, so anyone who
+/// can change the page can change who the catalog must be signed by. Aggregates are labeled
+/// with the `web-content` trust level, and this is only tried after DNS.
+pub(crate) struct MetaTagDiscovery {
+    options: TraversalOptions,
+    root_pins: Option<RootPins>,
+}
+
+impl MetaTagDiscovery {
+    pub(crate) fn new(options: TraversalOptions) -> Self {
+        Self { options, root_pins: None }
+    }
+
+    /// Checks the root the meta tag names against `root_pins` before traversing it, as DNS
+    /// discovery does, and pins it once its root catalog verified.
+    pub(crate) fn pinning_roots(self, root_pins: Option<RootPins>) -> Self {
+        Self { root_pins, ..self }
+    }
+}
+
+impl Discovery for MetaTagDiscovery {
+    fn discover<'a>(
+        &'a self,
+        domain: &'a str,
+        fetchers: &'a Fetchers,
+    ) -> BoxFuture<'a, Result<AggregatedCatalogData>> {
+        Box::pin(async move {
+            let page_url = format!("https://{}/", normalize_domain(domain)?);
+            self.options.hooks().message(1, Status::Info, &format!("Looking for a chainsights meta tag on: {}", page_url));
+            let html = fetch_document_text(fetchers, &page_url)
+                .await
+                .with_context(|| format!("Failed to fetch the homepage of '{}'", domain))?;
+            let content = find_meta_content(&html, META_NAME)
+                .with_context(|| format!("No <meta name=\"{}\"> tag on {}", META_NAME, page_url))?;
+            let root = parse_meta_content(&content)
+                .with_context(|| format!("Malformed chainsights meta tag on {}: '{}'", page_url, content))?;
+            let change = match &self.root_pins {
+                Some(pins) => pins.check(domain, &root)?,
+                None => None,
+            };
+            if let Some(change) = &change {
+                self.options.hooks().message(0, Status::Failed, &change.to_string());
+            }
+            let ChainsightsRecord { uri: root_uri, identity: root_identity, key } = &root;
+
+            self.options.hooks().message(0, Status::Info, &format!(
+                "Traversing from root URI: {} with expected identity: {}",
+                root_uri, root_identity
+            ));
+            self.options.hooks().message(1, Status::Warning, &format!(
+                "Found through an HTML meta tag: trust is limited to whoever can change {}",
+                page_url
+            ));
+            let root_identities = [SignerIdentity::new(root_identity)];
+            let options = self.options.scoped_to(domain).pinned_to(key.clone());
+            let mut aggregate = traverse_and_aggregate(root_uri, &root_identities, fetchers, &options)
+                .await
+                .with_context(|| format!("Traversal failed starting from {}", root_uri))?;
+            if let Some(pins) = &self.root_pins
+                && aggregate.verification.as_ref().is_some_and(|v| v.is_verified())
+                && pins.record(domain, &root)?
+            {
+                let pinned = match change.is_some() {
+                    true => "Accepted the changed root",
+                    false => "Pinned the root",
+                };
+                self.options.hooks().message(0, Status::Info, &format!("{} of '{}': {} at {}", pinned, domain, root_identity, root_uri));
+            }
+            aggregate.root_changes.extend(change);
+            aggregate.discovery = Some(RootDiscovery::html_meta(&page_url));
+            Ok(aggregate)
+        })
+    }
+}
+
+/// Parses a meta tag's `uri=...;identity=...` content. `key=` pins the root catalog's signing key
+/// as in TXT records; other attributes are ignored.
+fn parse_meta_content(content: &str) -> Result<ChainsightsRecord> {
+    let (mut uri, mut identity, mut key) = (None, None, None);
+    for pair in content.split(';').map(str::trim).filter(|pair| !pair.is_empty()) {
+        let (attribute, value) = pair
+            .split_once('=')
+            .with_context(|| format!("'{}' is not an attribute=value pair", pair))?;
+        let slot = match attribute.trim().to_ascii_lowercase().as_str() {
+            "uri" => &mut uri,
+            "identity" => &mut identity,
+            "key" => &mut key,
+            _ => continue,
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            bail!("Attribute '{}' has an empty value", attribute.trim());
+        }
+        if slot.replace(value.to_string()).is_some() {
+            bail!("Attribute '{}' appears more than once", attribute.trim());
+        }
+    }
+    Ok(ChainsightsRecord {
+        uri: uri.context("Meta tag has no 'uri' attribute")?,
+        identity: identity.context("Meta tag has no 'identity' attribute")?,
+        key,
+    })
+}
+
+/// Returns the decoded `content` of the first `<meta>` tag whose `name` is `name`, compared
+/// case-insensitively. Only tags are scanned, so this does not need a full HTML parser.
+fn find_meta_content(html: &str, name: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let mut rest = 0;
+    while let Some(start) = lower[rest..].find("<meta").map(|i| rest + i) {
+        let tag_start = start + "<meta".len();
+        let end = lower[tag_start..].find('>').map_or(html.len(), |i| tag_start + i);
+        rest = end;
+        let attributes = tag_attributes(&html[tag_start..end]);
+        let attribute = |wanted: &str| attributes.iter().find(|(name, _)| name == wanted).map(|(_, value)| value);
+        if attribute("name").is_some_and(|value| value.eq_ignore_ascii_case(name))
+            && let Some(content) = attribute("content")
+        {
+            return Some(decode_entities(content));
+        }
+    }
+    None
+}
+
+/// Splits the inside of a tag into lowercased attribute names and their raw values, which may be
+/// double-quoted, single-quoted or unquoted.
+fn tag_attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut chars = tag.trim_end_matches('/').chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == '/').is_some() {}
+        let name: String = std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace() && *c != '=')).collect();
+        if name.is_empty() {
+            return attributes;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut value = String::new();
+        if chars.next_if_eq(&'=').is_some() {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            match chars.next_if(|c| *c == '"' || *c == '\'') {
+                Some(quote) => value.extend(std::iter::from_fn(|| chars.next().filter(|c| *c != quote))),
+                None => value.extend(std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace()))),
+            }
+        }
+        attributes.push((name.to_ascii_lowercase(), value));
+    }
+}
+
+/// Decodes the character references likely in a URI or identity: the named ones for markup
+/// characters and numeric ones. Others are left as written.
+fn decode_entities(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let reference = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        let character = reference.and_then(|reference| match reference {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => {
+                let number = reference.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (character, reference) {
+            (Some(character), Some(reference)) => {
+                decoded.push(character);
+                rest = &rest[reference.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_and_parses_the_chainsights_meta_tag() {
+        let html = r#"<!DOCTYPE html>
+            <html><head>
+              <META charset=utf-8>
+              <meta name="description" content="uri=https://evil.example/x.jsonl;identity=x">
+              <meta content='uri=https://example.com/chainsights.jsonl?a=1&amp;b=2; identity = security@example.com'
+                    name="Chainsights" />
+            </head><body><meta name="chainsights" content="uri=https://late.example/;identity=y"></body></html>"#;
+        let content = find_meta_content(html, META_NAME).unwrap();
+        let record = parse_meta_content(&content).unwrap();
+        assert_eq!(record.uri, "https://example.com/chainsights.jsonl?a=1&b=2");
+        assert_eq!(record.identity, "security@example.com");
+        assert_eq!(record.key, None);
+
+        assert_eq!(find_meta_content("<meta name=chainsights content=uri=a;identity=b>", META_NAME).unwrap(), "uri=a;identity=b");
+        assert_eq!(find_meta_content("<p>no tags</p><meta name=\"chainsights\"", META_NAME), None);
+        assert_eq!(decode_entities("a&#64;b&#x2E;c &unknown; &"), "a@b.c &unknown; &");
+    }
+
+    #[test]
+    fn malformed_content_is_rejected() {
+        assert!(parse_meta_content("uri=https://example.com/c.jsonl").is_err());
+        assert!(parse_meta_content("uri=a;identity=b;uri=c").is_err());
+        assert!(parse_meta_content("uri=a;identity=").is_err());
+        assert!(parse_meta_content("uri=a;identity").is_err());
+        let record = parse_meta_content("uri=a;identity=b;key=SHA256:abc;future=1").unwrap();
+        assert_eq!(record.key.as_deref(), Some("SHA256:abc"));
+    }
+}
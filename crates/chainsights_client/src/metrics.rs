@@ -0,0 +1,222 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Prometheus metrics for long-running commands, served in the text exposition format at
+//! `/metrics` so that operations teams can alert when monitoring itself stops working.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use crate::models::aggregation::AggregatedCatalogData;
+use crate::output::{self, Status};
+
+/// Upper bounds, in seconds, of the traversal duration histogram's buckets.
+const DURATION_BUCKETS: [f64; 10] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0];
+
+/// What a failed verification failed on, as the `kind` label of `chainsights_verification_failures_total`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub(crate) enum FailureKind {
+    /// The root catalog did not verify.
+    Root,
+    /// A linked statement failed to fetch or verify, had expired or contradicted its link.
+    Statement,
+    /// A Rekor entry signed by the publisher is not published in its catalog.
+    UnpublishedRekorEntry,
+}
+
+impl FailureKind {
+    fn label(self) -> &'static str {
+        match self {
+            FailureKind::Root => "root",
+            FailureKind::Statement => "statement",
+            FailureKind::UnpublishedRekorEntry => "unpublished-rekor-entry",
+        }
+    }
+}
+
+#[derive(Default)]
+struct DomainMetrics {
+    /// Cumulative counts per bucket of `DURATION_BUCKETS`.
+    duration_buckets: [u64; DURATION_BUCKETS.len()],
+    duration_sum: f64,
+    duration_count: u64,
+    fetch_errors: u64,
+    verification_failures: BTreeMap<FailureKind, u64>,
+    last_success: Option<i64>,
+}
+
+/// Metrics of the domains a command monitors, shared between the command and the endpoint.
+#[derive(Default)]
+pub(crate) struct Metrics {
+    domains: Mutex<BTreeMap<String, DomainMetrics>>,
+}
+
+impl Metrics {
+    fn update(&self, domain: &str, update: impl FnOnce(&mut DomainMetrics)) {
+        let mut domains = self.domains.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        update(domains.entry(domain.to_string()).or_default());
+    }
+
+    /// Counts a domain as monitored before anything has been recorded for it.
+    pub(crate) fn monitor(&self, domain: &str) {
+        self.update(domain, |_| {});
+    }
+
+    /// Records a completed traversal of `domain` and the failures in its aggregate.
+    pub(crate) fn record_traversal(&self, domain: &str, duration: Duration, data: &AggregatedCatalogData) {
+        let seconds = duration.as_secs_f64();
+        let (_, failed) = data.outcome_counts();
+        self.update(domain, |metrics| {
+            for (count, bound) in metrics.duration_buckets.iter_mut().zip(DURATION_BUCKETS) {
+                *count += u64::from(seconds <= bound);
+            }
+            metrics.duration_sum += seconds;
+            metrics.duration_count += 1;
+            let (kind, failed) = match data.root_error {
+                Some(_) => (FailureKind::Root, 1),
+                None => (FailureKind::Statement, failed as u64),
+            };
+            *metrics.verification_failures.entry(kind).or_default() += failed;
+        });
+    }
+
+    /// Records verification failures of `domain` found outside the traversal.
+    pub(crate) fn record_failures(&self, domain: &str, kind: FailureKind, count: usize) {
+        self.update(domain, |metrics| *metrics.verification_failures.entry(kind).or_default() += count as u64);
+    }
+
+    /// Records a check of `domain` that could not complete, e.g. because a server was unreachable.
+    pub(crate) fn record_fetch_error(&self, domain: &str) {
+        self.update(domain, |metrics| metrics.fetch_errors += 1);
+    }
+
+    /// Records that a check of `domain` completed and found nothing wrong.
+    pub(crate) fn record_success(&self, domain: &str) {
+        self.update(domain, |metrics| metrics.last_success = Some(Utc::now().timestamp()));
+    }
+
+    /// The metrics in the Prometheus text exposition format.
+    pub(crate) fn render(&self) -> String {
+        let domains = self.domains.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut text = String::new();
+        family(&mut text, "chainsights_domains_monitored", "gauge", "Domains being monitored.");
+        let _ = writeln!(text, "chainsights_domains_monitored {}", domains.len());
+
+        family(&mut text, "chainsights_traversal_duration_seconds", "histogram", "Time taken to traverse a domain's attestations.");
+        for (domain, metrics) in domains.iter() {
+            for (count, bound) in metrics.duration_buckets.iter().zip(DURATION_BUCKETS) {
+                let _ = writeln!(text, "chainsights_traversal_duration_seconds_bucket{{domain=\"{}\",le=\"{}\"}} {}", escape(domain), bound, count);
+            }
+            let _ = writeln!(text, "chainsights_traversal_duration_seconds_bucket{{domain=\"{}\",le=\"+Inf\"}} {}", escape(domain), metrics.duration_count);
+            let _ = writeln!(text, "chainsights_traversal_duration_seconds_sum{{domain=\"{}\"}} {}", escape(domain), metrics.duration_sum);
+            let _ = writeln!(text, "chainsights_traversal_duration_seconds_count{{domain=\"{}\"}} {}", escape(domain), metrics.duration_count);
+        }
+
+        family(&mut text, "chainsights_fetch_errors_total", "counter", "Checks of a domain that could not complete because fetching failed.");
+        for (domain, metrics) in domains.iter() {
+            let _ = writeln!(text, "chainsights_fetch_errors_total{{domain=\"{}\"}} {}", escape(domain), metrics.fetch_errors);
+        }
+
+        family(&mut text, "chainsights_verification_failures_total", "counter", "Statements or log entries of a domain that failed verification.");
+        for (domain, metrics) in domains.iter() {
+            for (kind, count) in &metrics.verification_failures {
+                let _ = writeln!(text, "chainsights_verification_failures_total{{domain=\"{}\",kind=\"{}\"}} {}", escape(domain), kind.label(), count);
+            }
+        }
+
+        family(&mut text, "chainsights_last_success_timestamp_seconds", "gauge", "Unix time of the last check of a domain that found nothing wrong.");
+        for (domain, metrics) in domains.iter() {
+            if let Some(last_success) = metrics.last_success {
+                let _ = writeln!(text, "chainsights_last_success_timestamp_seconds{{domain=\"{}\"}} {}", escape(domain), last_success);
+            }
+        }
+        text
+    }
+}
+
+/// Writes the `HELP` and `TYPE` lines that introduce a metric family.
+fn family(text: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(text, "# HELP {} {}\n# TYPE {} {}", name, help, name, kind);
+}
+
+/// Escapes a label value for the text exposition format.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Serves `metrics` at `http://<addr>/metrics` until the process exits, and returns the address
+/// it listens on, which has the port the system chose when `addr`'s port is 0.
+pub(crate) async fn serve(addr: SocketAddr, metrics: Arc<Metrics>) -> Result<SocketAddr> {
+    let listener = TcpListener::bind(addr).await.with_context(|| format!("Failed to listen on {}", addr))?;
+    let addr = listener.local_addr()?;
+    output::print(0, Status::Info, &format!("Serving Prometheus metrics at http://{}/metrics", addr));
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                continue;
+            };
+            let metrics = metrics.clone();
+            tokio::spawn(async move {
+                let mut request = [0u8; 1024];
+                let Ok(read) = stream.read(&mut request).await else {
+                    return;
+                };
+                let request = String::from_utf8_lossy(&request[..read]);
+                let response = match request.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+                    ["GET", "/metrics"] => {
+                        let body = metrics.render();
+                        format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            body.len(),
+                            body
+                        )
+                    }
+                    _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    Ok(addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn serves_recorded_metrics() {
+        let metrics = Arc::new(Metrics::default());
+        let data: AggregatedCatalogData = serde_json::from_value(serde_json::json!({
+            "catalog_predicate": null,
+            "components": [],
+            "root_error": null,
+            "component_errors": [["https://example.com/a.jsonl", "404"], ["https://example.com/b.jsonl", "bad signature"]],
+        }))
+        .unwrap();
+        metrics.monitor("other.example");
+        metrics.record_traversal("example.com", Duration::from_millis(700), &data);
+        metrics.record_failures("example.com", FailureKind::UnpublishedRekorEntry, 1);
+        metrics.record_fetch_error("example.com");
+        metrics.record_success("example.com");
+
+        let addr = serve("127.0.0.1:0".parse().unwrap(), metrics).await.unwrap();
+        let text = reqwest::get(format!("http://{}/metrics", addr)).await.unwrap().text().await.unwrap();
+        assert!(text.contains("chainsights_domains_monitored 2\n"));
+        assert!(text.contains("chainsights_traversal_duration_seconds_bucket{domain=\"example.com\",le=\"0.5\"} 0\n"));
+        assert!(text.contains("chainsights_traversal_duration_seconds_bucket{domain=\"example.com\",le=\"1\"} 1\n"));
+        assert!(text.contains("chainsights_fetch_errors_total{domain=\"example.com\"} 1\n"));
+        assert!(text.contains("chainsights_verification_failures_total{domain=\"example.com\",kind=\"statement\"} 2\n"));
+        assert!(text.contains("chainsights_verification_failures_total{domain=\"example.com\",kind=\"unpublished-rekor-entry\"} 1\n"));
+        assert!(text.contains("chainsights_last_success_timestamp_seconds{domain=\"example.com\"}"));
+        assert!(!text.contains("chainsights_last_success_timestamp_seconds{domain=\"other.example\"}"));
+        assert_eq!(reqwest::get(format!("http://{}/", addr)).await.unwrap().status(), 404);
+    }
+}
@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
-pub(crate) mod aggregation;
-pub(crate) mod chainsights;
-pub(crate) mod statement;
-pub(crate) mod dsse;
-pub (crate) mod baseline;
\ No newline at end of file
+//! The Chainsights predicate models, re-exported from the `chainsights_predicates` crate.
+
+pub use chainsights_predicates::aggregation;
+pub use chainsights_predicates::baseline;
+pub use chainsights_predicates as chainsights;
@@ -0,0 +1,185 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Watching the Rekor transparency log for entries signed by a publisher's identities that the
+//! publisher's catalog does not account for: statements or artifacts signed with the publisher's
+//! identity but never published, as a compromised signing identity or shadow releases would leave.
+
+use std::collections::{BTreeSet, HashSet};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use chainsights_core::identity::same_identity;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::models::aggregation::AggregatedCatalogData;
+use crate::models::chainsights::FRESHNESS_V1;
+use crate::output::{self, Status};
+use crate::rekor::{LogEntrySummary, RekorClient};
+
+/// Every sha256 digest the aggregate accounts for: the payloads of the statements it verified,
+/// and the digests any of them pin, of linked statements and artifacts alike.
+pub(crate) fn published_digests(data: &AggregatedCatalogData) -> Result<HashSet<String>> {
+    let mut digests = HashSet::new();
+    collect_digests(&serde_json::to_value(data).context("Failed to serialize the aggregate")?, &mut digests);
+    Ok(digests)
+}
+
+fn collect_digests(value: &Value, digests: &mut HashSet<String>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match (key.as_str(), value) {
+                    ("digest", Value::Object(digest)) => {
+                        digests.extend(digest.get("sha256").and_then(Value::as_str).map(str::to_ascii_lowercase));
+                    }
+                    ("payload_sha256", Value::String(sha256)) => {
+                        digests.insert(sha256.to_ascii_lowercase());
+                    }
+                    _ => collect_digests(value, digests),
+                }
+            }
+        }
+        Value::Array(values) => values.iter().for_each(|value| collect_digests(value, digests)),
+        _ => {}
+    }
+}
+
+/// Checks the log entries signed by a set of identities against the catalog, remembering which
+/// entries it has already checked.
+pub(crate) struct RekorMonitor {
+    rekor: RekorClient,
+    identities: Vec<String>,
+    /// Entries integrated before this time are not checked.
+    since: Option<DateTime<Utc>>,
+    /// UUIDs of the entries already checked.
+    seen: BTreeSet<String>,
+    /// Where `seen` is kept between runs.
+    state: Option<PathBuf>,
+}
+
+impl RekorMonitor {
+    /// A monitor of the entries signed by `identities`, which must be email addresses since
+    /// those are what Rekor indexes signers by.
+    pub(crate) fn new(rekor: RekorClient, identities: Vec<String>, since: Option<DateTime<Utc>>, state: Option<PathBuf>) -> Result<Self> {
+        if let Some(identity) = identities.iter().find(|identity| !identity.contains('@') || identity.contains("://")) {
+            bail!("Rekor indexes signers by email address only, so '{}' cannot be monitored", identity);
+        }
+        let seen = match &state {
+            Some(path) if path.exists() => {
+                let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read '{}'", path.display()))?;
+                serde_json::from_str(&text).with_context(|| format!("Invalid monitor state file '{}'", path.display()))?
+            }
+            _ => BTreeSet::new(),
+        };
+        Ok(Self { rekor, identities, since, seen, state })
+    }
+
+    /// Returns the entries, not checked before, that are signed by a monitored identity but whose
+    /// digest `published` does not contain.
+    pub(crate) async fn unpublished_entries(&mut self, published: &HashSet<String>) -> Result<Vec<LogEntrySummary>> {
+        let mut unpublished = Vec::new();
+        for identity in &self.identities {
+            let mut uuids = self.rekor.search_by_email(identity).await?;
+            uuids.retain(|uuid| !self.seen.contains(uuid));
+            for uuid in uuids {
+                let entry = match self.rekor.entry_summary(&uuid).await {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        // Left unseen, so it is tried again on the next check.
+                        output::print(1, Status::Warning, &format!("Skipping Rekor entry {}: {:#}", uuid, e));
+                        continue;
+                    }
+                };
+                self.seen.insert(uuid);
+                if self.since.is_some_and(|since| entry.integrated_time.is_some_and(|time| time < since))
+                    || !entry.signer_identities.iter().any(|signer| same_identity(signer, identity))
+                {
+                    continue;
+                }
+                // Freshness statements are re-signed as time passes, so catalogs never pin them.
+                let published = entry.sha256.as_ref().is_some_and(|sha256| published.contains(sha256))
+                    || entry.predicate_type.as_deref() == Some(FRESHNESS_V1);
+                if !published {
+                    unpublished.push(entry);
+                }
+            }
+        }
+        self.save()?;
+        Ok(unpublished)
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.state else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create '{}'", dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(&self.seen).context("Failed to serialize monitor state")?;
+        std::fs::write(path, format!("{}\n", json)).with_context(|| format!("Failed to write '{}'", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{Engine as _, engine::general_purpose::STANDARD};
+    use chainsights_testing::EphemeralSigner;
+    use serde_json::json;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    /// A Rekor `intoto` entry for a statement signed by `identity` with payload digest `sha256`.
+    fn entry(uuid: &str, identity: &str, sha256: &str) -> Value {
+        let certificate = EphemeralSigner::new(identity).unwrap().certificate_pem();
+        let body = json!({
+            "apiVersion": "0.0.2",
+            "kind": "intoto",
+            "spec": { "content": {
+                "envelope": { "payloadType": "application/vnd.in-toto+json", "signatures": [{ "sig": "", "publicKey": STANDARD.encode(certificate) }] },
+                "payloadHash": { "algorithm": "sha256", "value": sha256 },
+            } },
+        });
+        json!({ uuid: { "body": STANDARD.encode(body.to_string()), "integratedTime": 1_750_000_000, "logIndex": 1 } })
+    }
+
+    #[tokio::test]
+    async fn reports_entries_the_catalog_does_not_publish() {
+        let identity = "releases@example.com";
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/index/retrieve"))
+            .and(body_json(json!({ "email": identity })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!(["published", "shadow"])))
+            .mount(&server)
+            .await;
+        for (uuid, sha256) in [("published", "aa11"), ("shadow", "bb22")] {
+            Mock::given(path(format!("/api/v1/log/entries/{}", uuid)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(entry(uuid, identity, sha256)))
+                .mount(&server)
+                .await;
+        }
+        let published = published_digests(&serde_json::from_value(json!({
+            "catalog_predicate": null,
+            "components": [],
+            "root_error": null,
+            "component_errors": [],
+            "verification": { "method": "sigstore-keyless", "payload_sha256": "AA11" },
+        })).unwrap())
+        .unwrap();
+
+        let rekor = RekorClient::new(reqwest::Client::new(), &server.uri());
+        let mut monitor = RekorMonitor::new(rekor.clone(), vec![identity.to_string()], None, None).unwrap();
+        let unpublished = monitor.unpublished_entries(&published).await.unwrap();
+        assert_eq!(unpublished.iter().map(|entry| entry.uuid.as_str()).collect::<Vec<_>>(), ["shadow"]);
+        assert_eq!(unpublished[0].signer_identities, [identity]);
+        assert!(monitor.unpublished_entries(&published).await.unwrap().is_empty(), "entries are reported once");
+
+        let since = DateTime::from_timestamp(1_800_000_000, 0);
+        let mut monitor = RekorMonitor::new(rekor.clone(), vec![identity.to_string()], since, None).unwrap();
+        assert!(monitor.unpublished_entries(&published).await.unwrap().is_empty());
+        assert!(RekorMonitor::new(rekor, vec!["https://github.com/example/app/.github/workflows/release.yml@refs/heads/main".to_string()], None, None).is_err());
+    }
+}
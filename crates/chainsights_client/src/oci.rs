@@ -0,0 +1,343 @@
+// SPDX-License-Identifier: Apache-2.0
+
+#[cfg(feature = "cli")]
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result, anyhow, bail};
+#[cfg(feature = "cli")]
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use futures::future::BoxFuture;
+use oci_client::client::ClientConfig;
+#[cfg(feature = "cli")]
+use oci_client::client::ImageLayer;
+use oci_client::manifest::{
+    IMAGE_MANIFEST_LIST_MEDIA_TYPE, IMAGE_MANIFEST_MEDIA_TYPE, OCI_IMAGE_INDEX_MEDIA_TYPE,
+    OCI_IMAGE_MEDIA_TYPE,
+};
+use oci_client::secrets::RegistryAuth;
+use oci_client::{Client, Reference};
+#[cfg(feature = "cli")]
+use oci_client::RegistryOperation;
+#[cfg(feature = "cli")]
+use sha2::{Digest, Sha256};
+#[cfg(feature = "cli")]
+use chainsights_core::bundle::SigstoreBundleData;
+#[cfg(feature = "cli")]
+use chainsights_core::statement::InTotoStatement;
+
+use crate::fetch::Fetcher;
+use crate::output::{self, Status};
+
+const OCI_SCHEME: &str = "oci://";
+
+/// Artifact type cosign uses for Sigstore bundles attached via the OCI 1.1 referrers API.
+pub(crate) const SIGSTORE_BUNDLE_ARTIFACT_TYPE: &str = "application/vnd.dev.sigstore.bundle.v0.3+json";
+/// Annotation cosign sets on bundle referrers to record the in-toto predicate type.
+const PREDICATE_TYPE_ANNOTATION: &str = "dev.sigstore.bundle.predicateType";
+/// Annotation cosign sets on bundle referrers to record the bundle content type.
+#[cfg(feature = "cli")]
+const CONTENT_ANNOTATION: &str = "dev.sigstore.bundle.content";
+/// The OCI empty descriptor used as config for artifact manifests.
+#[cfg(feature = "cli")]
+const EMPTY_CONFIG_MEDIA_TYPE: &str = "application/vnd.oci.empty.v1+json";
+#[cfg(feature = "cli")]
+const EMPTY_CONFIG: &[u8] = b"{}";
+/// Artifact type of documents pushed by `publish` as artifacts of their own, rather than as
+/// referrers of an image.
+pub(crate) const DOCUMENT_ARTIFACT_TYPE: &str = "application/vnd.chainsights.document.v1";
+/// Annotation recording a pushed document's path within its attestation tree.
+#[cfg(feature = "cli")]
+const TITLE_ANNOTATION: &str = "org.opencontainers.image.title";
+/// Predicate types that identify Chainsights attestations among an image's referrers.
+const CHAINSIGHTS_PREDICATE_PREFIX: &str = "https://chainsights.rest/";
+
+const MANIFEST_MEDIA_TYPES: &[&str] = &[
+    OCI_IMAGE_MEDIA_TYPE,
+    OCI_IMAGE_INDEX_MEDIA_TYPE,
+    IMAGE_MANIFEST_MEDIA_TYPE,
+    IMAGE_MANIFEST_LIST_MEDIA_TYPE,
+];
+
+/// Fetches Chainsights attestations attached to container images via the OCI referrers API.
+///
+/// URIs have the form `oci://<registry>/<repository>[:tag|@digest]`. The first referrer that is
+/// a Sigstore bundle with a Chainsights predicate type is returned, unless the reference is a
+/// document pushed by `publish`, which is returned itself.
+pub(crate) struct OciFetcher {
+    client: Client,
+    auth: RegistryAuth,
+}
+
+impl OciFetcher {
+    pub(crate) fn new(auth: RegistryAuth) -> Self {
+        Self {
+            client: Client::new(ClientConfig::default()),
+            auth,
+        }
+    }
+
+    /// Resolves an image reference to a reference pinned to its manifest digest.
+    async fn resolve_digest(&self, image: &Reference) -> Result<Reference> {
+        if image.digest().is_some() {
+            return Ok(image.clone());
+        }
+        let digest = self
+            .client
+            .fetch_manifest_digest(image, &self.auth)
+            .await
+            .with_context(|| format!("Failed to resolve digest of image '{}'", image))?;
+        Ok(image.clone_with_digest(digest))
+    }
+}
+
+impl OciFetcher {
+    /// Pulls the document `image` holds if it is a document artifact pushed by `publish`.
+    async fn pull_document(&self, image: &Reference) -> Result<Option<Vec<u8>>> {
+        // Images are not documents; leave any errors pulling them to the referrers lookup.
+        let Ok((manifest_bytes, _)) = self.client.pull_manifest_raw(image, &self.auth, MANIFEST_MEDIA_TYPES).await else {
+            return Ok(None);
+        };
+        let Ok(manifest) = serde_json::from_slice::<oci_client::manifest::OciImageManifest>(&manifest_bytes) else {
+            return Ok(None);
+        };
+        if manifest.artifact_type.as_deref() != Some(DOCUMENT_ARTIFACT_TYPE) {
+            return Ok(None);
+        }
+        let layer = manifest.layers.first().ok_or_else(|| anyhow!("Document artifact '{}' has no layers", image))?;
+        let mut document = Vec::new();
+        self.client
+            .pull_blob(image, layer, &mut document)
+            .await
+            .with_context(|| format!("Failed to pull document blob '{}'", layer.digest))?;
+        Ok(Some(document))
+    }
+}
+
+impl Fetcher for OciFetcher {
+    fn supports(&self, uri: &str) -> bool {
+        uri.starts_with(OCI_SCHEME)
+    }
+
+    fn fetch<'a>(&'a self, uri: &'a str) -> BoxFuture<'a, Result<Vec<u8>>> {
+        Box::pin(async move {
+            let image: Reference = uri
+                .strip_prefix(OCI_SCHEME)
+                .unwrap_or(uri)
+                .parse()
+                .with_context(|| format!("Invalid OCI image reference '{}'", uri))?;
+            let image = self.resolve_digest(&image).await?;
+            if let Some(document) = self.pull_document(&image).await? {
+                return Ok(document);
+            }
+
+            let referrers = self
+                .client
+                .pull_referrers(&image, Some(SIGSTORE_BUNDLE_ARTIFACT_TYPE))
+                .await
+                .with_context(|| format!("Failed to list referrers of image '{}'", image))?;
+            output::print(
+                2,
+                Status::Info,
+                &format!("Found {} bundle referrers attached to {}", referrers.manifests.len(), image),
+            );
+
+            for entry in &referrers.manifests {
+                let predicate_type = entry
+                    .annotations
+                    .as_ref()
+                    .and_then(|a| a.get(PREDICATE_TYPE_ANNOTATION));
+                if !predicate_type.is_some_and(|t| t.starts_with(CHAINSIGHTS_PREDICATE_PREFIX)) {
+                    continue;
+                }
+
+                let referrer = image.clone_with_digest(entry.digest.clone());
+                let (manifest_bytes, _) = self
+                    .client
+                    .pull_manifest_raw(&referrer, &self.auth, &[OCI_IMAGE_MEDIA_TYPE])
+                    .await
+                    .with_context(|| format!("Failed to pull referrer manifest '{}'", referrer))?;
+                let manifest: oci_client::manifest::OciImageManifest =
+                    serde_json::from_slice(&manifest_bytes)
+                        .context("Failed to parse referrer manifest")?;
+                let layer = manifest
+                    .layers
+                    .first()
+                    .ok_or_else(|| anyhow!("Referrer manifest '{}' has no layers", referrer))?;
+
+                let mut bundle = Vec::new();
+                self.client
+                    .pull_blob(&referrer, layer, &mut bundle)
+                    .await
+                    .with_context(|| format!("Failed to pull bundle blob '{}'", layer.digest))?;
+                return Ok(bundle);
+            }
+
+            bail!("No Chainsights attestation is attached to image '{}'", image)
+        })
+    }
+}
+
+/// Resolves an image reference (e.g., `ghcr.io/example/app:1.0.0`, optionally with `oci://`) to
+/// its manifest digest (`sha256:<hex>`). Only references not pinned by digest are looked up.
+#[cfg(feature = "cli")]
+pub(crate) async fn resolve_image_digest(image: &str, auth: RegistryAuth) -> Result<String> {
+    let reference: Reference = image
+        .strip_prefix(OCI_SCHEME)
+        .unwrap_or(image)
+        .parse()
+        .with_context(|| format!("Invalid OCI image reference '{}'", image))?;
+    let pinned = OciFetcher::new(auth).resolve_digest(&reference).await?;
+    pinned
+        .digest()
+        .map(str::to_string)
+        .with_context(|| format!("The registry returned no digest for image '{}'", image))
+}
+
+/// Attaches a signed bundle to an image as an OCI 1.1 referrer, using the same layout as
+/// `cosign attest --new-bundle-format`. Returns the digest of the referrer manifest.
+#[cfg(feature = "cli")]
+pub(crate) async fn attach_bundle(
+    image: &str,
+    bundle_json: &str,
+    auth: &RegistryAuth,
+) -> Result<String> {
+    let bundle: SigstoreBundleData =
+        serde_json::from_str(bundle_json).context("Failed to parse bundle JSON")?;
+    let payload = STANDARD
+        .decode(&bundle.dsse_envelope.payload)
+        .context("Failed to decode dsseEnvelope.payload")?;
+    let statement: InTotoStatement =
+        serde_json::from_slice(&payload).context("Failed to parse InTotoStatement JSON")?;
+
+    let client = Client::new(ClientConfig::default());
+    let image: Reference = image
+        .parse()
+        .with_context(|| format!("Invalid OCI image reference '{}'", image))?;
+    let (subject_bytes, subject_digest) = client
+        .pull_manifest_raw(&image, auth, MANIFEST_MEDIA_TYPES)
+        .await
+        .with_context(|| format!("Failed to pull manifest of image '{}'", image))?;
+    let subject_media_type = serde_json::from_slice::<serde_json::Value>(&subject_bytes)
+        .ok()
+        .and_then(|m| m.get("mediaType").and_then(|t| t.as_str()).map(str::to_string))
+        .unwrap_or_else(|| OCI_IMAGE_MEDIA_TYPE.to_string());
+    let subject = image.clone_with_digest(subject_digest.clone());
+    client
+        .auth(&subject, auth, RegistryOperation::Push)
+        .await
+        .with_context(|| format!("Failed to authenticate for push to '{}'", image))?;
+
+    let config = ImageLayer::new(EMPTY_CONFIG.to_vec(), EMPTY_CONFIG_MEDIA_TYPE.to_string(), None);
+    let layer = ImageLayer::new(
+        bundle_json.as_bytes().to_vec(),
+        SIGSTORE_BUNDLE_ARTIFACT_TYPE.to_string(),
+        None,
+    );
+    client
+        .push_blob(&subject, &config.data, &config.sha256_digest())
+        .await
+        .context("Failed to push empty config blob")?;
+    client
+        .push_blob(&subject, &layer.data, &layer.sha256_digest())
+        .await
+        .context("Failed to push bundle blob")?;
+
+    let annotations = BTreeMap::from([
+        (CONTENT_ANNOTATION.to_string(), "dsse-envelope".to_string()),
+        (PREDICATE_TYPE_ANNOTATION.to_string(), statement.predicate_type.clone()),
+    ]);
+    let manifest = serde_json::json!({
+        "schemaVersion": 2,
+        "mediaType": OCI_IMAGE_MEDIA_TYPE,
+        "artifactType": SIGSTORE_BUNDLE_ARTIFACT_TYPE,
+        "config": {
+            "mediaType": EMPTY_CONFIG_MEDIA_TYPE,
+            "digest": config.sha256_digest(),
+            "size": config.data.len(),
+        },
+        "layers": [{
+            "mediaType": SIGSTORE_BUNDLE_ARTIFACT_TYPE,
+            "digest": layer.sha256_digest(),
+            "size": layer.data.len(),
+        }],
+        "subject": {
+            "mediaType": subject_media_type,
+            "digest": subject_digest,
+            "size": subject_bytes.len(),
+        },
+        "annotations": annotations,
+    });
+    let manifest_bytes = serde_json::to_vec(&manifest).context("Failed to serialize manifest")?;
+    let manifest_digest = format!("sha256:{}", hex::encode(Sha256::digest(&manifest_bytes)));
+    let referrer = image.clone_with_digest(manifest_digest.clone());
+    client
+        .push_manifest_raw(
+            &referrer,
+            manifest_bytes,
+            OCI_IMAGE_MEDIA_TYPE
+                .parse()
+                .context("Invalid manifest media type")?,
+        )
+        .await
+        .context("Failed to push referrer manifest")?;
+
+    Ok(manifest_digest)
+}
+
+/// Pushes a document of an attestation tree to `reference` (`<registry>/<repository>:<tag>`) as
+/// an artifact of its own, so that `oci://<reference>` fetches it. Returns the manifest digest.
+#[cfg(feature = "cli")]
+pub(crate) async fn push_document(reference: &str, path: &str, document: &[u8], auth: &RegistryAuth) -> Result<String> {
+    let client = Client::new(ClientConfig::default());
+    let reference: Reference = reference
+        .parse()
+        .with_context(|| format!("Invalid OCI reference '{}'", reference))?;
+    client
+        .auth(&reference, auth, RegistryOperation::Push)
+        .await
+        .with_context(|| format!("Failed to authenticate for push to '{}'", reference))?;
+
+    let media_type = match serde_json::from_slice::<SigstoreBundleData>(document) {
+        Ok(_) => SIGSTORE_BUNDLE_ARTIFACT_TYPE,
+        Err(_) => "application/octet-stream",
+    };
+    let config = ImageLayer::new(EMPTY_CONFIG.to_vec(), EMPTY_CONFIG_MEDIA_TYPE.to_string(), None);
+    let layer = ImageLayer::new(document.to_vec(), media_type.to_string(), None);
+    client
+        .push_blob(&reference, &config.data, &config.sha256_digest())
+        .await
+        .context("Failed to push empty config blob")?;
+    client
+        .push_blob(&reference, &layer.data, &layer.sha256_digest())
+        .await
+        .context("Failed to push document blob")?;
+
+    let manifest = serde_json::json!({
+        "schemaVersion": 2,
+        "mediaType": OCI_IMAGE_MEDIA_TYPE,
+        "artifactType": DOCUMENT_ARTIFACT_TYPE,
+        "config": {
+            "mediaType": EMPTY_CONFIG_MEDIA_TYPE,
+            "digest": config.sha256_digest(),
+            "size": config.data.len(),
+        },
+        "layers": [{
+            "mediaType": media_type,
+            "digest": layer.sha256_digest(),
+            "size": layer.data.len(),
+            "annotations": { TITLE_ANNOTATION: path },
+        }],
+    });
+    let manifest_bytes = serde_json::to_vec(&manifest).context("Failed to serialize manifest")?;
+    client
+        .push_manifest_raw(
+            &reference,
+            manifest_bytes.clone(),
+            OCI_IMAGE_MEDIA_TYPE
+                .parse()
+                .context("Invalid manifest media type")?,
+        )
+        .await
+        .context("Failed to push document manifest")?;
+    Ok(format!("sha256:{}", hex::encode(Sha256::digest(&manifest_bytes))))
+}
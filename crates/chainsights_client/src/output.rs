@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Human-readable progress and result lines: a status symbol, indentation that follows the
+//! attestation tree, and ANSI colors when they are enabled.
+
+#[cfg(feature = "cli")]
+use std::io::{BufWriter, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Colors are off unless the CLI turns them on, so library output stays plain.
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/// What a line of human output reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Status {
+    /// Progress or context; headings at depth 0 are shown in bold.
+    Info,
+    /// A signature, digest or check that passed.
+    Verified,
+    /// A signature, digest or check that failed.
+    Failed,
+    /// Something skipped or degraded that the user should know about.
+    Warning,
+}
+
+impl Status {
+    fn symbol(self) -> &'static str {
+        match self {
+            Status::Info => "",
+            Status::Verified => "✅ ",
+            Status::Failed => "❌ ",
+            Status::Warning => "⚠️  ",
+        }
+    }
+
+    fn ansi_code(self) -> &'static str {
+        match self {
+            Status::Info => "1",
+            Status::Verified => "32",
+            Status::Failed => "31",
+            Status::Warning => "33",
+        }
+    }
+}
+
+/// Enables colors unless `no_color` is set, `NO_COLOR` is set to a non-empty value, or standard
+/// output is not a terminal.
+#[cfg(feature = "cli")]
+pub(crate) fn init_color(no_color: bool) {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    COLOR.store(!no_color && !no_color_env && std::io::stdout().is_terminal(), Ordering::Relaxed);
+}
+
+/// Formats a line at `depth` in the attestation tree: 0 for headings, 1 for the root catalog and
+/// what happens around it, 2 for components, 3 for releases.
+pub(crate) fn line(depth: usize, status: Status, text: &str) -> String {
+    let indent = "  ".repeat(depth);
+    let colored = COLOR.load(Ordering::Relaxed) && (status != Status::Info || depth == 0);
+    if colored {
+        format!("{}{}\x1b[{}m{}\x1b[0m", indent, status.symbol(), status.ansi_code(), text)
+    } else {
+        format!("{}{}{}", indent, status.symbol(), text)
+    }
+}
+
+/// Prints a line to standard output.
+pub(crate) fn print(depth: usize, status: Status, text: &str) {
+    println!("{}", line(depth, status, text));
+}
+
+#[cfg(feature = "dns-discovery")]
+/// Prints a line to standard error.
+pub(crate) fn eprint(depth: usize, status: Status, text: &str) {
+    eprintln!("{}", line(depth, status, text));
+}
+
+/// Writes `value` to standard output as pretty-printed JSON, streaming it through a buffer instead
+/// of rendering the whole document into a string first.
+#[cfg(feature = "cli")]
+pub(crate) fn json<T: serde::Serialize + ?Sized>(value: &T) -> anyhow::Result<()> {
+    let mut stdout = BufWriter::new(std::io::stdout().lock());
+    serde_json::to_writer_pretty(&mut stdout, value)?;
+    writeln!(stdout)?;
+    stdout.flush()?;
+    Ok(())
+}
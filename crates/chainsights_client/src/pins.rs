@@ -0,0 +1,210 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Root identity pinning: the identity and root catalog URI a domain's `_chainsights` record
+//! advertises are pinned on first use, or given explicitly, and a record advertising others is
+//! reported, since whoever holds a compromised key or hijacked zone can otherwise change both
+//! unnoticed.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chainsights_core::identity::{normalize_domain, same_identity};
+use chainsights_core::txt::ChainsightsRecord;
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::discovery::RefusedRoot;
+use crate::models::aggregation::RootChange;
+use crate::state::{JsonStateFile, state_file};
+
+/// The root a domain's record advertised when it was pinned.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct PinnedRoot {
+    pub identity: String,
+    pub uri: String,
+    pub pinned_at: String,
+}
+
+/// What to do when a domain's record no longer matches the root pinned on first use.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum OnRootChange {
+    /// Report the change and traverse the advertised root, keeping the old pin.
+    #[default]
+    Warn,
+    /// Fail discovery of the domain.
+    Fail,
+    /// Pin the advertised root instead, e.g. after a rotation announced out of band.
+    Accept,
+}
+
+/// A JSON file of the root pinned for each domain on first use, with explicit pins that take
+/// precedence over it.
+#[derive(Clone, Debug)]
+pub(crate) struct RootPins {
+    /// Where roots are pinned on first use; None to only check the explicit pins
+    file: Option<JsonStateFile>,
+    /// Identities given explicitly for a domain; a record naming any other is refused
+    explicit: BTreeMap<String, Vec<String>>,
+    on_change: OnRootChange,
+}
+
+impl RootPins {
+    pub(crate) fn new(path: Option<PathBuf>, on_change: OnRootChange) -> Self {
+        Self { file: path.map(|path| JsonStateFile::new(path, "root pin")), explicit: BTreeMap::new(), on_change }
+    }
+
+    /// These pins, with `identity` allowed for `domain`'s root whatever was recorded for it.
+    /// Pinning a domain more than once allows any of the identities. The domain must be given in
+    /// its normalized form.
+    pub(crate) fn pinning(mut self, domain: &str, identity: &str) -> Self {
+        self.explicit.entry(domain.to_string()).or_default().push(identity.to_string());
+        self
+    }
+
+    /// `$XDG_STATE_HOME/chainsights/root-pins.json`, or `~/.local/state/chainsights/root-pins.json`.
+    pub(crate) fn default_path() -> Result<PathBuf> {
+        state_file("root-pins.json", "--root-pin-state")
+    }
+
+    fn load(&self) -> Result<BTreeMap<String, PinnedRoot>> {
+        match &self.file {
+            Some(file) => file.load(),
+            None => Ok(BTreeMap::new()),
+        }
+    }
+
+    /// Compares the root `record` advertises for `domain` with its pins, before the root is
+    /// traversed. Fails if an explicit pin names another identity, or if the root changed since it
+    /// was pinned on first use and changes fail. Returns the change otherwise.
+    pub(crate) fn check(&self, domain: &str, record: &ChainsightsRecord) -> Result<Option<RootChange>> {
+        let domain = normalize_domain(domain)?;
+        if let Some(pinned) = self.explicit.get(&domain) {
+            if !pinned.iter().any(|identity| same_identity(identity, &record.identity)) {
+                return Err(RefusedRoot(format!(
+                    "The _chainsights record of '{}' advertises identity '{}', but the domain is pinned to '{}'; the domain's key or DNS zone may be compromised",
+                    domain,
+                    record.identity,
+                    pinned.join("' or '")
+                ))
+                .into());
+            }
+            return Ok(None);
+        }
+        let Some(pinned) = self.load()?.remove(&domain) else {
+            return Ok(None);
+        };
+        if same_identity(&pinned.identity, &record.identity) && pinned.uri == record.uri {
+            return Ok(None);
+        }
+        let change = RootChange {
+            domain,
+            pinned_identity: pinned.identity,
+            pinned_uri: pinned.uri,
+            pinned_at: pinned.pinned_at,
+            identity: record.identity.clone(),
+            uri: record.uri.clone(),
+        };
+        match self.on_change {
+            OnRootChange::Fail => Err(RefusedRoot(change.to_string()).into()),
+            OnRootChange::Warn | OnRootChange::Accept => Ok(Some(change)),
+        }
+    }
+
+    /// Pins the root `record` advertises for `domain` once its root catalog verified, unless the
+    /// domain is pinned already and changes are not accepted. Returns true if it was pinned.
+    pub(crate) fn record(&self, domain: &str, record: &ChainsightsRecord) -> Result<bool> {
+        let domain = normalize_domain(domain)?;
+        let Some(file) = self.file.as_ref().filter(|_| !self.explicit.contains_key(&domain)) else {
+            return Ok(false);
+        };
+        let mut pins: BTreeMap<String, PinnedRoot> = file.load()?;
+        let unchanged = pins.get(&domain).is_some_and(|pinned| same_identity(&pinned.identity, &record.identity) && pinned.uri == record.uri);
+        if unchanged || (pins.contains_key(&domain) && self.on_change != OnRootChange::Accept) {
+            return Ok(false);
+        }
+        pins.insert(domain, PinnedRoot {
+            identity: record.identity.clone(),
+            uri: record.uri.clone(),
+            pinned_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        });
+        file.save(&pins)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn record(identity: &str, uri: &str) -> ChainsightsRecord {
+        ChainsightsRecord { uri: uri.to_string(), identity: identity.to_string(), key: None }
+    }
+
+    fn original() -> ChainsightsRecord {
+        record("release@example.com", "https://example.com/catalog.json")
+    }
+
+    fn hijacked() -> ChainsightsRecord {
+        record("attacker@evil.test", "https://evil.test/catalog.json")
+    }
+
+    /// A state file in which example.com is pinned to its original root.
+    fn pinned_state() -> (TempDir, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("root-pins.json");
+        assert!(RootPins::new(Some(path.clone()), OnRootChange::Warn).record("example.com", &original()).unwrap());
+        (dir, path)
+    }
+
+    #[test]
+    fn pins_the_first_root_seen() {
+        let dir = TempDir::new().unwrap();
+        let pins = RootPins::new(Some(dir.path().join("root-pins.json")), OnRootChange::Warn);
+        assert_eq!(pins.check("example.com", &original()).unwrap(), None);
+        assert!(pins.record("example.com", &original()).unwrap());
+        assert!(!pins.record("Example.com.", &original()).unwrap());
+        assert_eq!(pins.check("example.com", &record("Release@example.com", "https://example.com/catalog.json")).unwrap(), None);
+    }
+
+    #[test]
+    fn reports_records_advertising_another_root_than_the_pinned_one() {
+        let (_dir, path) = pinned_state();
+        let pins = RootPins::new(Some(path.clone()), OnRootChange::Warn);
+        let change = pins.check("example.com", &hijacked()).unwrap().unwrap();
+        assert_eq!((change.pinned_identity.as_str(), change.identity.as_str()), ("release@example.com", "attacker@evil.test"));
+        assert!(change.to_string().contains("identity 'attacker@evil.test' instead of 'release@example.com' and root"));
+        // Changes are not pinned unless accepted, so the original root stays in force.
+        assert!(!pins.record("example.com", &hijacked()).unwrap());
+        let error = RootPins::new(Some(path), OnRootChange::Fail).check("example.com", &hijacked()).unwrap_err();
+        assert!(error.downcast_ref::<RefusedRoot>().is_some());
+    }
+
+    #[test]
+    fn explicit_pins_reject_other_identities() {
+        let (_dir, path) = pinned_state();
+        let explicit = RootPins::new(Some(path), OnRootChange::Warn).pinning("other.example", "release@other.example");
+        assert!(explicit.check("other.example", &hijacked()).unwrap_err().to_string().contains("pinned to 'release@other.example'"));
+        assert!(!explicit.record("other.example", &record("release@other.example", "https://other.example/catalog.json")).unwrap());
+        let rotating = explicit.pinning("other.example", "release-2026@other.example");
+        assert!(rotating.check("other.example", &record("release-2026@other.example", "https://other.example/catalog.json")).unwrap().is_none());
+        assert!(rotating.check("other.example", &hijacked()).unwrap_err().to_string().contains("'release@other.example' or 'release-2026@other.example'"));
+
+        // Workflow refs are case-sensitive, as in signature verification.
+        let workflow = "https://github.com/example/app/.github/workflows/release.yml@refs/heads/main";
+        let workflow_pin = RootPins::new(None, OnRootChange::Warn).pinning("app.example", workflow);
+        assert!(workflow_pin.check("app.example", &record(&workflow.replace("github.com", "GitHub.com"), "https://app.example/catalog.json")).unwrap().is_none());
+        assert!(workflow_pin.check("app.example", &record(&workflow.replace("main", "MAIN"), "https://app.example/catalog.json")).is_err());
+    }
+
+    #[test]
+    fn accepted_changes_are_pinned() {
+        let (_dir, path) = pinned_state();
+        let accepting = RootPins::new(Some(path), OnRootChange::Accept);
+        assert!(accepting.check("example.com", &hijacked()).unwrap().is_some());
+        assert!(accepting.record("example.com", &hijacked()).unwrap());
+        assert_eq!(accepting.check("example.com", &hijacked()).unwrap(), None);
+    }
+}
@@ -0,0 +1,293 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! WebAssembly plugins that parse proprietary predicate types and check bespoke policies, so
+//! that organizations can extend the client without recompiling it.
+//!
+//! A plugin is a core WebAssembly module (binary or text format) that imports nothing and
+//! exports:
+//!
+//! - `memory`
+//! - `chainsights_abi_version() -> i32`, returning [`ABI_VERSION`]
+//! - `chainsights_alloc(len: i32) -> i32`, returning a buffer of `len` bytes for the host to write
+//!   its input to
+//!
+//! and any of:
+//!
+//! - `chainsights_predicate_types() -> i64`: a JSON array of the predicate type URLs the plugin
+//!   parses
+//! - `chainsights_parse_predicate(ptr: i32, len: i32) -> i64`: given a verified in-toto statement
+//!   as JSON, returns `{"kind": "...", "details": ["..."]}`
+//! - `chainsights_check_policy(ptr: i32, len: i32) -> i64`: given a traversal's aggregate as JSON,
+//!   as printed by `domain`, returns `{"violations": ["..."]}`
+//!
+//! Results are UTF-8 JSON in the plugin's memory, returned as the pointer in the upper and the
+//! length in the lower 32 bits. Any result may instead be `{"error": "..."}`. Each call runs in a
+//! fresh instance with bounded memory and fuel, so a plugin cannot keep state between calls or
+//! run forever.
+
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail};
+use chainsights_core::statement::InTotoStatement;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::models::aggregation::AggregatedCatalogData;
+use crate::models::chainsights::CustomPredicate;
+use crate::traversal::PredicateParser;
+
+/// Version of the plugin ABI described above.
+pub(crate) const ABI_VERSION: i32 = 1;
+
+/// Largest linear memory a plugin instance may grow to.
+const MAX_MEMORY: usize = 64 * 1024 * 1024;
+
+/// Fuel each call may consume, roughly one unit per WebAssembly instruction.
+const FUEL: u64 = 2_000_000_000;
+
+/// What a plugin reports of a statement it parsed.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct PluginPredicate {
+    /// Short name of the predicate, like "release"
+    pub kind: String,
+    /// Lines describing the predicate's contents
+    #[serde(default)]
+    pub details: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct PolicyResult {
+    #[serde(default)]
+    violations: Vec<String>,
+}
+
+/// A compiled plugin.
+pub(crate) struct Plugin {
+    pub name: String,
+    module: Module,
+    /// Predicate types the plugin parses
+    pub predicate_types: Vec<String>,
+    /// Whether the plugin checks policies
+    pub checks_policy: bool,
+}
+
+impl Plugin {
+    /// Compiles the plugin at `path` and checks that it implements the ABI.
+    pub(crate) fn load(engine: &Engine, path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path).with_context(|| format!("Failed to read plugin '{}'", path.display()))?;
+        let name = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        Self::new(engine, &name, &bytes).with_context(|| format!("Failed to load plugin '{}'", path.display()))
+    }
+
+    fn new(engine: &Engine, name: &str, bytes: &[u8]) -> Result<Self> {
+        let module = Module::new(engine, bytes).map_err(|e| anyhow!("Invalid WebAssembly module: {:#}", e))?;
+        if let Some(import) = module.imports().next() {
+            bail!("Plugins may not import anything, but this one imports '{}::{}'", import.module(), import.name());
+        }
+        let mut plugin = Self { name: name.to_string(), module, predicate_types: Vec::new(), checks_policy: false };
+        let (mut store, instance) = plugin.instantiate()?;
+        let version = instance
+            .get_typed_func::<(), i32>(&mut store, "chainsights_abi_version")
+            .and_then(|version| version.call(&mut store, ()))
+            .map_err(|e| anyhow!("Failed to call chainsights_abi_version: {:#}", e))?;
+        if version != ABI_VERSION {
+            bail!("Plugin implements ABI version {}, but this client supports version {}", version, ABI_VERSION);
+        }
+        if instance.get_func(&mut store, "chainsights_predicate_types").is_some() {
+            let types = instance
+                .get_typed_func::<(), i64>(&mut store, "chainsights_predicate_types")
+                .and_then(|types| types.call(&mut store, ()))
+                .map_err(|e| anyhow!("Failed to call chainsights_predicate_types: {:#}", e))?;
+            plugin.predicate_types = read_result(&instance, &mut store, "chainsights_predicate_types", types)?;
+            if instance.get_func(&mut store, "chainsights_parse_predicate").is_none() {
+                bail!("Plugin names predicate types but does not export chainsights_parse_predicate");
+            }
+        }
+        plugin.checks_policy = instance.get_func(&mut store, "chainsights_check_policy").is_some();
+        Ok(plugin)
+    }
+
+    /// A fresh instance of the plugin, limited in memory and fuel.
+    fn instantiate(&self) -> Result<(Store<StoreLimits>, Instance)> {
+        let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY).instances(1).build();
+        let mut store = Store::new(self.module.engine(), limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(FUEL).map_err(|e| anyhow!("{:#}", e))?;
+        let instance = Instance::new(&mut store, &self.module, &[]).map_err(|e| anyhow!("Failed to instantiate: {:#}", e))?;
+        Ok((store, instance))
+    }
+
+    /// Calls `export` with `input` in a fresh instance and parses its JSON result.
+    fn call<T: DeserializeOwned>(&self, export: &str, input: &[u8]) -> Result<T> {
+        let (mut store, instance) = self.instantiate()?;
+        let len = i32::try_from(input.len()).context("Plugin input is too large")?;
+        let result = (|| {
+            let alloc = instance.get_typed_func::<i32, i32>(&mut store, "chainsights_alloc")?;
+            let function = instance.get_typed_func::<(i32, i32), i64>(&mut store, export)?;
+            let ptr = alloc.call(&mut store, len)?;
+            let memory = instance.get_memory(&mut store, "memory").ok_or_else(|| wasmtime::Error::msg("no exported memory"))?;
+            memory.write(&mut store, ptr as u32 as usize, input)?;
+            function.call(&mut store, (ptr, len))
+        })()
+        .map_err(|e| anyhow!("Plugin '{}' failed in {}: {:#}", self.name, export, e))?;
+        read_result(&instance, &mut store, export, result).with_context(|| format!("Plugin '{}' failed in {}", self.name, export))
+    }
+
+    /// Parses a verified statement of one of the plugin's predicate types.
+    pub(crate) fn parse_predicate(&self, statement: &InTotoStatement) -> Result<PluginPredicate> {
+        self.call("chainsights_parse_predicate", &serde_json::to_vec(statement)?)
+    }
+
+    /// The policy violations the plugin finds in a traversal.
+    pub(crate) fn check_policy(&self, data: &AggregatedCatalogData) -> Result<Vec<String>> {
+        let result: PolicyResult = self.call("chainsights_check_policy", &serde_json::to_vec(data)?)?;
+        Ok(result.violations)
+    }
+}
+
+/// Reads the JSON result a call returned as a packed pointer and length, failing if it reports
+/// an error.
+fn read_result<T: DeserializeOwned>(instance: &Instance, store: &mut Store<StoreLimits>, export: &str, packed: i64) -> Result<T> {
+    let (ptr, len) = ((packed as u64 >> 32) as usize, (packed as u64 & 0xffff_ffff) as usize);
+    let memory = instance.get_memory(&mut *store, "memory").context("Plugin does not export its memory")?;
+    let bytes = memory
+        .data(&*store)
+        .get(ptr..ptr.saturating_add(len))
+        .with_context(|| format!("{} returned {} bytes at {}, outside the plugin's memory", export, len, ptr))?;
+    let value: serde_json::Value = serde_json::from_slice(bytes).with_context(|| format!("{} did not return JSON", export))?;
+    if let Some(error) = value.get("error").and_then(|error| error.as_str()) {
+        bail!("{}", error);
+    }
+    serde_json::from_value(value).with_context(|| format!("{} returned an unexpected result", export))
+}
+
+/// The plugins given on the command line.
+#[derive(Default)]
+pub(crate) struct Plugins {
+    plugins: Vec<Plugin>,
+}
+
+impl Plugins {
+    /// Compiles every plugin in `paths`.
+    pub(crate) fn load(paths: &[impl AsRef<Path>]) -> Result<Self> {
+        if paths.is_empty() {
+            return Ok(Self::default());
+        }
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| anyhow!("Failed to start the plugin engine: {:#}", e))?;
+        let plugins = paths.iter().map(|path| Plugin::load(&engine, path.as_ref())).collect::<Result<_>>()?;
+        Ok(Self { plugins })
+    }
+
+    /// The plugin that parses `predicate_type`, if any.
+    pub(crate) fn parser_for(&self, predicate_type: &str) -> Option<&Plugin> {
+        self.plugins.iter().find(|plugin| plugin.predicate_types.iter().any(|t| t == predicate_type))
+    }
+
+    /// Whether any plugin parses predicates.
+    pub(crate) fn parses_predicates(&self) -> bool {
+        self.plugins.iter().any(|plugin| !plugin.predicate_types.is_empty())
+    }
+
+    /// Runs every policy-checking plugin against a traversal, failing with their violations.
+    pub(crate) fn check_policies(&self, data: &AggregatedCatalogData) -> Result<()> {
+        let mut violations = Vec::new();
+        for plugin in self.plugins.iter().filter(|plugin| plugin.checks_policy) {
+            violations.extend(plugin.check_policy(data)?.into_iter().map(|violation| format!("{}: {}", plugin.name, violation)));
+        }
+        match violations.is_empty() {
+            true => Ok(()),
+            false => bail!("{} plugin policy violation(s): {}", violations.len(), violations.join("; ")),
+        }
+    }
+}
+
+impl PredicateParser for Plugins {
+    fn parse(&self, statement: &InTotoStatement) -> Option<Result<CustomPredicate>> {
+        let plugin = self.parser_for(&statement.predicate_type)?;
+        Some(plugin.parse_predicate(statement).map(|parsed| CustomPredicate {
+            predicate_type: statement.predicate_type.clone(),
+            plugin: plugin.name.clone(),
+            kind: parsed.kind,
+            details: parsed.details,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROPRIETARY_V1: &str = "https://example.com/proprietary/v1";
+
+    /// A text-format plugin exporting `exports`, with a bump allocator.
+    fn plugin(exports: &str) -> Result<Plugin> {
+        let wat = format!(
+            r#"(module
+                (memory (export "memory") 1)
+                (global $next (mut i32) (i32.const 4096))
+                (func (export "chainsights_abi_version") (result i32) (i32.const 1))
+                (func (export "chainsights_alloc") (param $len i32) (result i32)
+                    (global.get $next)
+                    (global.set $next (i32.add (global.get $next) (local.get $len))))
+                {}
+            )"#,
+            exports
+        );
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        Plugin::new(&Engine::new(&config).unwrap(), "test", wat.as_bytes())
+    }
+
+    /// A function named `name` returning `json`, stored at `offset`.
+    fn returning(name: &str, params: &str, offset: u64, json: &str) -> String {
+        format!(
+            r#"(data (i32.const {offset}) "{}") (func (export "{name}") {params} (result i64) (i64.const {}))"#,
+            json.replace('"', "\\\""),
+            (offset << 32) | json.len() as u64
+        )
+    }
+
+    #[test]
+    fn plugins_parse_their_predicate_types_and_report_policy_violations() {
+        let parser = plugin(&format!(
+            "{} {}",
+            returning("chainsights_predicate_types", "", 0, &format!("[\"{}\"]", PROPRIETARY_V1)),
+            returning("chainsights_parse_predicate", "(param i32 i32)", 512, r#"{"kind": "proprietary", "details": ["build farm: eu-1"]}"#),
+        ))
+        .unwrap();
+        let statement = InTotoStatement::new("app", Default::default(), PROPRIETARY_V1, serde_json::json!({}));
+        assert_eq!(parser.predicate_types, [PROPRIETARY_V1]);
+        assert!(!parser.checks_policy);
+        assert_eq!(parser.parse_predicate(&statement).unwrap(), PluginPredicate {
+            kind: "proprietary".to_string(),
+            details: vec!["build farm: eu-1".to_string()],
+        });
+
+        let policy = plugin(&returning("chainsights_check_policy", "(param i32 i32)", 0, r#"{"violations": ["no SBOM for app"]}"#)).unwrap();
+        let plugins = Plugins { plugins: vec![parser, policy] };
+        assert_eq!(plugins.parser_for(PROPRIETARY_V1).map(|plugin| plugin.predicate_types.len()), Some(1));
+        assert!(plugins.parser_for("https://example.com/other/v1").is_none());
+        let custom = plugins.parse(&statement).unwrap().unwrap();
+        assert_eq!((custom.plugin.as_str(), custom.kind.as_str()), ("test", "proprietary"));
+        assert!(plugins.parses_predicates());
+        let error = plugins.check_policies(&AggregatedCatalogData::default()).unwrap_err();
+        assert_eq!(error.to_string(), "1 plugin policy violation(s): test: no SBOM for app");
+
+        let failing = plugin(&returning("chainsights_check_policy", "(param i32 i32)", 0, r#"{"error": "unsupported catalog"}"#)).unwrap();
+        assert!(format!("{:#}", failing.check_policy(&AggregatedCatalogData::default()).unwrap_err()).contains("unsupported catalog"));
+    }
+
+    #[test]
+    fn plugins_are_sandboxed() {
+        let spinning = plugin(r#"(func (export "chainsights_check_policy") (param i32 i32) (result i64) (loop $spin (br $spin)) (i64.const 0))"#).unwrap();
+        assert!(spinning.check_policy(&AggregatedCatalogData::default()).is_err());
+
+        let importing = r#"(module (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32))))"#;
+        let engine = Engine::new(Config::new().consume_fuel(true)).unwrap();
+        let error = Plugin::new(&engine, "test", importing.as_bytes()).err().unwrap();
+        assert!(error.to_string().contains("may not import"), "{}", error);
+    }
+}
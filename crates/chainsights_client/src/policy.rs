@@ -1,55 +1,223 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::models::{chainsights::ChainsightsCatalogPredicate, statement::InTotoStatement};
-use anyhow::Result;
-
-// TODO: Implement actual policy checking
-fn _check_policy(statement: &InTotoStatement) -> Result<Option<ChainsightsCatalogPredicate>> {
-    println!("Checking policy...");
-    const CHAINSIGHTS_PREDICATE_TYPE: &str = "https://chainsights.rest/catalog/v1";
-    if statement.predicate_type == "text/json" {
-        println!("  Outer type text/json, checking inner");
-        match serde_json::from_value::<ChainsightsCatalogPredicate>(statement.predicate.clone()) {
-            Ok(inner_predicate) => {
-                let inner_type = statement
-                    .predicate
-                    .get("predicateType")
-                    .and_then(|v| v.as_str());
-                if inner_type == Some(CHAINSIGHTS_PREDICATE_TYPE) {
-                    println!("  Inner type matches: {}", CHAINSIGHTS_PREDICATE_TYPE);
-                    println!("  ✅ Policy checks passed (Placeholder).");
-                    return Ok(Some(inner_predicate));
-                } else {
-                    println!(
-                        "  WARN: Inner type mismatch: expected '{}', found '{}'",
-                        CHAINSIGHTS_PREDICATE_TYPE,
-                        inner_type.unwrap_or("N/A")
-                    );
-                    return Ok(None);
-                }
-            }
-            Err(e) => {
-                println!("  WARN: Cannot parse inner predicate: {}", e);
-                return Ok(None);
-            }
+//! Signed client policies: the identity allowlists, required artifacts and trust roots an
+//! organization requires of every traversal, distributed as a signed bundle so that machines can
+//! fetch policy updates instead of having their configuration redeployed.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use chainsights_core::identity::normalize_domain;
+use chainsights_core::statement::InTotoStatement;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::generate::generator;
+use crate::models::chainsights::{ChainsightsPolicyPredicate, ChainsightsPredicate, POLICY_V1};
+use crate::state::{JsonStateFile, state_file};
+use crate::trust::{ExpectedSigner, SignedConfiguration, open_signed_statement};
+
+/// Subject name of policy statements.
+const POLICY_SUBJECT: &str = "policy.json";
+
+/// Builds the unsigned statement of a policy from its JSON document, which holds the predicate's
+/// fields other than its generator, timestamp and expiry. Its subject is the sha256 of the
+/// predicate as serialized in the statement.
+pub(crate) fn policy_statement(document: &str, expires: Option<DateTime<Utc>>) -> Result<InTotoStatement> {
+    let mut fields: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(document).context("Policy document is not a JSON object")?;
+    let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    fields.insert("timestamp".to_string(), timestamp.into());
+    let mut predicate: ChainsightsPolicyPredicate =
+        serde_json::from_value(fields.into()).context("Invalid policy document")?;
+    predicate.generator = Some(generator());
+    predicate.expires = expires.map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true));
+    predicate.root_identities = predicate
+        .root_identities
+        .into_iter()
+        .map(|(domain, identities)| Ok((normalize_domain(&domain)?, identities)))
+        .collect::<Result<_>>()?;
+    if let Some(rate) = predicate.max_error_rate
+        && !(0.0..=1.0).contains(&rate)
+    {
+        bail!("The policy's maxErrorRate {} must be between 0 and 1", rate);
+    }
+
+    let predicate = serde_json::to_value(predicate)?;
+    let digest = hex::encode(Sha256::digest(serde_json::to_vec(&predicate)?));
+    Ok(InTotoStatement::new(
+        POLICY_SUBJECT,
+        HashMap::from([("sha256".to_string(), digest)]),
+        POLICY_V1,
+        predicate,
+    ))
+}
+
+impl SignedConfiguration for ChainsightsPolicyPredicate {
+    const KIND: &'static str = "policy";
+
+    fn from_predicate(predicate: ChainsightsPredicate) -> Option<Self> {
+        match predicate {
+            ChainsightsPredicate::Policy(policy) => Some(*policy),
+            _ => None,
         }
-    } else if statement.predicate_type == CHAINSIGHTS_PREDICATE_TYPE {
-        println!("  Predicate type matches: {}", CHAINSIGHTS_PREDICATE_TYPE);
-        match serde_json::from_value::<ChainsightsCatalogPredicate>(statement.predicate.clone()) {
-            Ok(predicate) => {
-                println!("  ✅ Policy checks passed (Placeholder).");
-                Ok(Some(predicate))
+    }
+
+    fn expires(&self) -> Option<&str> {
+        self.expires.as_deref()
+    }
+}
+
+/// Verifies a signed policy against `signer` and returns its predicate. Fails for other
+/// predicates and for expired policies. A policy that sets a trusted root decides which
+/// certificates are trusted, so like a trust snapshot it must be signed by a pinned key or chain to
+/// a root that is already trusted.
+pub(crate) fn open_policy(name: &str, document: &str, signer: &ExpectedSigner) -> Result<ChainsightsPolicyPredicate> {
+    let policy: ChainsightsPolicyPredicate = open_signed_statement(name, document, signer)?;
+    if policy.trusted_root.is_some() && !signer.is_anchored() {
+        bail!(
+            "Policy '{}' from '{}' sets a trusted root, so it must be verified with a pinned key or against an already trusted root, not only its signer's identity",
+            policy.name,
+            name
+        );
+    }
+    Ok(policy)
+}
+
+/// The policy last applied from a source.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct AppliedPolicy {
+    pub(crate) name: String,
+    /// The `timestamp` the policy was issued at.
+    pub(crate) timestamp: String,
+}
+
+/// A JSON file of the policy last applied from each source, so that a validly signed but older
+/// policy, as an attacker replaying one to loosen the rules would serve, is refused.
+pub(crate) struct PolicyStore {
+    file: JsonStateFile,
+}
+
+impl PolicyStore {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self { file: JsonStateFile::new(path, "policy state") }
+    }
+
+    /// `$XDG_STATE_HOME/chainsights/policies.json`, or `~/.local/state/chainsights/policies.json`.
+    pub(crate) fn default_path() -> Result<PathBuf> {
+        state_file("policies.json", "--policy-state")
+    }
+
+    /// Records `policy` as applied from `source`. Fails if it was issued before the policy applied
+    /// from `source` last.
+    pub(crate) fn observe(&self, source: &str, policy: &ChainsightsPolicyPredicate) -> Result<()> {
+        let issued = |timestamp: &str| {
+            DateTime::parse_from_rfc3339(timestamp).with_context(|| format!("Invalid policy timestamp '{}'", timestamp))
+        };
+        let mut applied: BTreeMap<String, AppliedPolicy> = self.file.load()?;
+        let timestamp = issued(&policy.timestamp)?;
+        if let Some(last) = applied.get(source) {
+            let last_timestamp = issued(&last.timestamp)?;
+            if timestamp < last_timestamp {
+                bail!(
+                    "Policy '{}' from '{}' was issued at {}, but policy '{}' issued at {} was already applied from it; an older policy may be being replayed",
+                    policy.name,
+                    source,
+                    policy.timestamp,
+                    last.name,
+                    last.timestamp
+                );
             }
-            Err(e) => {
-                println!("  WARN: Cannot parse predicate: {}", e);
-                Ok(None)
+            if timestamp == last_timestamp {
+                return Ok(());
             }
         }
-    } else {
-        println!(
-            "  WARN: Unexpected predicate type: {}",
-            statement.predicate_type
-        );
-        Ok(None)
+        applied.insert(source.to_string(), AppliedPolicy { name: policy.name.clone(), timestamp: policy.timestamp.clone() });
+        self.file.save(&applied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chainsights_core::identity::SignerIdentity;
+    use chainsights_core::verifier::key_verifier;
+    use chainsights_testing::{EphemeralSigner, PrivateSigstore, SshSigner};
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::models::chainsights::RequiredArtifact;
+
+    fn sigstore<'a>(identities: &'a [SignerIdentity]) -> ExpectedSigner<'a> {
+        ExpectedSigner::Sigstore { identities, trusted_root: None }
+    }
+
+    #[test]
+    fn signed_policies_open_for_their_signer_until_they_expire() {
+        let signer = EphemeralSigner::new("security@example.test").unwrap();
+        let identities = [SignerIdentity::new("security@example.test")];
+        let document = r#"{
+            "name": "acme-production",
+            "rootIdentities": { "Example.com.": ["release@example.com"] },
+            "requiredArtifacts": ["sbom", "signed-artifacts"],
+            "requireAllReleases": true
+        }"#;
+        let sign = |expires| signer.sign(&policy_statement(document, expires).unwrap()).unwrap().0;
+
+        let bundle = sign(Some(Utc::now() + chrono::Duration::days(30)));
+        let policy = open_policy("policy.jsonl", &bundle, &sigstore(&identities)).unwrap();
+        assert_eq!(policy.name, "acme-production");
+        assert_eq!(policy.root_identities["example.com"], ["release@example.com"]);
+        assert_eq!(policy.required_artifacts, [RequiredArtifact::Sbom, RequiredArtifact::SignedArtifacts]);
+        assert!(policy.require_all_releases);
+        assert!(open_policy("policy.jsonl", &bundle, &sigstore(&[SignerIdentity::new("other@example.test")])).is_err());
+
+        let expired = sign(Some(Utc::now() - chrono::Duration::days(1)));
+        let error = open_policy("policy.jsonl", &expired, &sigstore(&identities)).unwrap_err();
+        assert!(error.to_string().contains("expired"), "{}", error);
+
+        assert!(policy_statement(r#"{ "name": "loose", "maxErrorRate": 10 }"#, None).is_err());
+        assert!(policy_statement(r#"{ "name": "typo", "requiredArtifacts": ["sboms"] }"#, None).is_err());
+    }
+
+    #[test]
+    fn policies_setting_a_trusted_root_need_a_pinned_key_or_a_trusted_root() {
+        let sigstore = PrivateSigstore::new().unwrap();
+        let trusted_root = sigstore.trusted_root();
+        let document = serde_json::json!({ "name": "acme-production", "trustedRoot": trusted_root }).to_string();
+        let statement = policy_statement(&document, None).unwrap();
+        let identities = [SignerIdentity::new("security@example.test")];
+
+        let self_signed = EphemeralSigner::new("security@example.test").unwrap().sign(&statement).unwrap().0;
+        let error = open_policy("policy.jsonl", &self_signed, &ExpectedSigner::Sigstore { identities: &identities, trusted_root: None }).unwrap_err();
+        assert!(error.to_string().contains("pinned key"), "{}", error);
+
+        let bundle = sigstore.signer("security@example.test").unwrap().sign(&statement).unwrap().0;
+        let trusted = ExpectedSigner::Sigstore { identities: &identities, trusted_root: Some(&trusted_root) };
+        assert!(open_policy("policy.jsonl", &bundle, &trusted).unwrap().trusted_root.is_some());
+
+        let key = SshSigner::new();
+        let document = serde_json::to_string_pretty(&statement).unwrap();
+        let signature = key.sign(document.as_bytes());
+        let verifier = key_verifier(&[key.public_key()]).unwrap();
+        let pinned = ExpectedSigner::Key { verifier: &*verifier, signature: Some(&signature) };
+        assert_eq!(open_policy("policy.json", &document, &pinned).unwrap().name, "acme-production");
+    }
+
+    #[test]
+    fn policies_older_than_the_last_applied_are_refused() {
+        let dir = TempDir::new().unwrap();
+        let store = PolicyStore::new(dir.path().join("policies.json"));
+        let policy = |timestamp: &str| -> ChainsightsPolicyPredicate {
+            serde_json::from_value(serde_json::json!({ "name": "acme-production", "timestamp": timestamp })).unwrap()
+        };
+        let source = "https://security.acme.example/policy.jsonl";
+        store.observe(source, &policy("2025-07-01T00:00:00Z")).unwrap();
+        store.observe(source, &policy("2025-07-01T00:00:00Z")).unwrap();
+        store.observe(source, &policy("2025-08-01T00:00:00Z")).unwrap();
+        let error = store.observe(source, &policy("2025-07-01T00:00:00Z")).unwrap_err();
+        assert!(error.to_string().contains("older policy may be being replayed"), "{}", error);
+        store.observe("policy.jsonl", &policy("2025-07-01T00:00:00Z")).unwrap();
     }
 }
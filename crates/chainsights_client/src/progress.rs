@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Progress bars for interactive traversals.
+
+use std::collections::HashSet;
+use std::io::IsTerminal;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use indicatif::{HumanBytes, ProgressBar, ProgressDrawTarget, ProgressFinish, ProgressStyle};
+
+use crate::output::{self, Status};
+use crate::traversal::TraversalHooks;
+
+/// Shows attestations processed out of those discovered so far, and the bytes downloaded, on
+/// standard error. Progress lines are printed above the bar instead of through it.
+pub(crate) struct TraversalProgress {
+    bar: ProgressBar,
+    discovered: Mutex<HashSet<String>>,
+    processed: Mutex<HashSet<String>>,
+    bytes: AtomicU64,
+}
+
+impl TraversalProgress {
+    /// Starts a progress bar if standard output is an interactive terminal. Returns None when
+    /// output is redirected, so piped or captured output stays free of progress updates.
+    pub(crate) fn start() -> Option<Self> {
+        if !std::io::stdout().is_terminal() {
+            return None;
+        }
+        let style = ProgressStyle::with_template("{spinner} [{elapsed}] {bar:30} {pos}/{len} attestations verified, {msg}")
+            .expect("progress template is valid")
+            .progress_chars("=> ");
+        let bar = ProgressBar::with_draw_target(Some(0), ProgressDrawTarget::stderr())
+            .with_style(style)
+            .with_finish(ProgressFinish::AndClear);
+        bar.set_message(format!("{} downloaded", HumanBytes(0)));
+        Some(Self {
+            bar,
+            discovered: Mutex::new(HashSet::new()),
+            processed: Mutex::new(HashSet::new()),
+            bytes: AtomicU64::new(0),
+        })
+    }
+
+    /// Removes the bar so that final output is printed on a clean terminal. Dropping the progress
+    /// display, e.g. on an error, removes it as well.
+    pub(crate) fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+impl TraversalHooks for TraversalProgress {
+    fn node_discovered(&self, uri: &str) {
+        let mut discovered = self.discovered.lock().unwrap();
+        if discovered.insert(uri.to_string()) {
+            self.bar.set_length(discovered.len() as u64);
+        }
+    }
+
+    fn node_processed(&self, uri: &str, _verified: bool) {
+        let mut processed = self.processed.lock().unwrap();
+        if processed.insert(uri.to_string()) {
+            self.bar.set_position(processed.len() as u64);
+        }
+    }
+
+    fn bytes_downloaded(&self, bytes: u64) {
+        let total = self.bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.bar.set_message(format!("{} downloaded", HumanBytes(total)));
+    }
+
+    fn message(&self, depth: usize, status: Status, text: &str) {
+        self.bar.suspend(|| output::print(depth, status, text));
+    }
+}
@@ -0,0 +1,587 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chainsights_core::statement::InTotoStatement;
+use chainsights_core::verify::decode_bundle_unverified;
+use futures::future::BoxFuture;
+use oci_client::secrets::RegistryAuth;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::aws::AwsCredentials;
+use crate::models::chainsights::{CATALOG_V1, CATALOG_V2};
+use crate::oci::push_document;
+use crate::output::{self, Status};
+
+const PATH_PLACEHOLDER: &str = "{path}";
+const TAG_PLACEHOLDER: &str = "{tag}";
+/// OCI tags are at most 128 characters.
+const MAX_TAG_LENGTH: usize = 128;
+const DEFAULT_S3_REGION: &str = "us-east-1";
+
+/// A directory of signed attestations and the documents they link to, such as SBOMs and detached
+/// signatures, by their path within it (`components/app.jsonl`).
+pub(crate) struct AttestationTree {
+    pub(crate) documents: BTreeMap<String, Vec<u8>>,
+}
+
+impl AttestationTree {
+    /// Reads every file under `dir`. Hidden files and directories, such as `.git`, are skipped.
+    pub(crate) fn read(dir: &Path) -> Result<Self> {
+        let mut documents = BTreeMap::new();
+        read_dir_into(dir, "", &mut documents)?;
+        if documents.is_empty() {
+            bail!("'{}' contains no files to publish", dir.display());
+        }
+        Ok(Self { documents })
+    }
+}
+
+fn read_dir_into(dir: &Path, prefix: &str, documents: &mut BTreeMap<String, Vec<u8>>) -> Result<()> {
+    let entries = std::fs::read_dir(dir).with_context(|| format!("Failed to read directory '{}'", dir.display()))?;
+    for entry in entries {
+        let entry = entry.with_context(|| format!("Failed to read directory '{}'", dir.display()))?;
+        let name = entry
+            .file_name()
+            .into_string()
+            .map_err(|name| anyhow!("File name {:?} in '{}' is not valid UTF-8", name, dir.display()))?;
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = format!("{}{}", prefix, name);
+        if entry.path().is_dir() {
+            read_dir_into(&entry.path(), &format!("{}/", path), documents)?;
+        } else {
+            let document = std::fs::read(entry.path())
+                .with_context(|| format!("Failed to read '{}'", entry.path().display()))?;
+            documents.insert(path, document);
+        }
+    }
+    Ok(())
+}
+
+/// The URI each file of a tree is served at once published: a URI with a `{path}` placeholder
+/// for the file's path within the tree, or `{tag}` for its OCI tag, e.g.
+/// `https://example.com/chainsights/{path}`.
+#[derive(Clone)]
+pub(crate) struct UriTemplate {
+    prefix: String,
+    suffix: String,
+    placeholder: &'static str,
+}
+
+impl UriTemplate {
+    pub(crate) fn parse(template: &str) -> Result<Self> {
+        let placeholders = [PATH_PLACEHOLDER, TAG_PLACEHOLDER];
+        let found: Vec<&'static str> =
+            placeholders.into_iter().filter(|placeholder| template.contains(placeholder)).collect();
+        let [placeholder] = found[..] else {
+            bail!("URI template '{}' must contain exactly one of {{path}} or {{tag}}", template);
+        };
+        let (prefix, suffix) = template.split_once(placeholder).unwrap_or_default();
+        if suffix.contains(placeholder) {
+            bail!("URI template '{}' must contain {} only once", template, placeholder);
+        }
+        Ok(Self { prefix: prefix.to_string(), suffix: suffix.to_string(), placeholder })
+    }
+
+    /// True if the template names files by OCI tag rather than by path.
+    pub(crate) fn is_tag(&self) -> bool {
+        self.placeholder == TAG_PLACEHOLDER
+    }
+
+    /// The URI the file at `path` is served at.
+    pub(crate) fn expand(&self, path: &str) -> String {
+        format!("{}{}{}", self.prefix, self.key(path), self.suffix)
+    }
+
+    /// What the placeholder stands for in the URI of the file at `path`.
+    pub(crate) fn key(&self, path: &str) -> String {
+        match self.placeholder {
+            TAG_PLACEHOLDER => oci_tag(path),
+            _ => path.to_string(),
+        }
+    }
+
+    /// The placeholder's value in `uri`, or None if `uri` is not served under the template.
+    pub(crate) fn key_of<'a>(&self, uri: &'a str) -> Option<&'a str> {
+        uri.strip_prefix(&self.prefix)?.strip_suffix(&self.suffix)
+    }
+}
+
+/// The OCI tag a file is pushed under: its path with the characters tags can't contain replaced by
+/// `-` (`components/app.jsonl` as `components-app.jsonl`). Paths too long for a tag are cut short
+/// and suffixed with a hash of the full path.
+pub(crate) fn oci_tag(path: &str) -> String {
+    let mut tag: String = path
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+            true => c,
+            false => '-',
+        })
+        .collect();
+    if !tag.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_') {
+        tag.insert(0, '_');
+    }
+    if tag.len() > MAX_TAG_LENGTH {
+        let hash = hex::encode(&Sha256::digest(path.as_bytes())[..8]);
+        tag.truncate(MAX_TAG_LENGTH - hash.len() - 1);
+        tag = format!("{}-{}", tag, hash);
+    }
+    tag
+}
+
+/// What publishing a tree does: the URI each file will be served at, and the root catalog's,
+/// which goes in the domain's `_chainsights` TXT record.
+#[derive(Debug)]
+pub(crate) struct PublishPlan {
+    /// Each file's URI, by path
+    pub(crate) uris: BTreeMap<String, String>,
+    pub(crate) root_uri: String,
+    /// The identity the root catalog's certificate names, if it is a Sigstore bundle
+    pub(crate) root_identity: Option<String>,
+}
+
+/// Works out where each file of `tree` will be served and checks that the links between its
+/// statements will resolve: every link under `template` must name a file in the tree. Links are
+/// signed, so they can't be rewritten here; `template` has to match the URIs the statements were
+/// generated with. The root catalog is `root` if given, or else the one catalog no other statement
+/// in the tree links to.
+pub(crate) fn plan_publish(tree: &AttestationTree, template: &UriTemplate, root: Option<&str>) -> Result<PublishPlan> {
+    let mut by_key: HashMap<String, &str> = HashMap::new();
+    for path in tree.documents.keys() {
+        if let Some(other) = by_key.insert(template.key(path), path) {
+            bail!("'{}' and '{}' would both be published at '{}'", other, path, template.expand(path));
+        }
+    }
+
+    let mut linked = HashSet::new();
+    let mut broken = Vec::new();
+    let mut catalogs = Vec::new();
+    let mut identities = HashMap::new();
+    for (path, document) in &tree.documents {
+        let Some((statement, identity)) = statement_of(document) else {
+            continue;
+        };
+        identities.insert(path.as_str(), identity);
+        if matches!(statement.predicate_type.as_str(), CATALOG_V1 | CATALOG_V2) {
+            catalogs.push(path.as_str());
+        }
+        let mut uris = Vec::new();
+        link_uris(&statement.predicate, &mut uris);
+        for uri in uris {
+            match template.key_of(&uri).map(|key| by_key.get(key)) {
+                Some(Some(target)) => {
+                    linked.insert(*target);
+                }
+                Some(None) => broken.push(format!("{} links to {}", path, uri)),
+                // Served from somewhere else, e.g. an artifact on a release page.
+                None => {}
+            }
+        }
+    }
+    if linked.is_empty() && identities.len() > 1 {
+        bail!("No links between the statements point under the URI template; it must match the URIs they were generated with");
+    }
+    if !broken.is_empty() {
+        bail!(
+            "Links point under the URI template at files that are not in the tree:\n  {}",
+            broken.join("\n  ")
+        );
+    }
+
+    let root_path = match root {
+        Some(root) => {
+            let root = root.trim_start_matches('/');
+            if !tree.documents.contains_key(root) {
+                bail!("The root catalog '{}' is not in the tree", root);
+            }
+            root
+        }
+        None => {
+            let roots: Vec<&str> = catalogs.into_iter().filter(|path| !linked.contains(path)).collect();
+            match roots[..] {
+                [root] => root,
+                [] => bail!("No catalog in the tree is left unlinked by the others; name the root with --root"),
+                _ => bail!("Several catalogs could be the root ({}); name it with --root", roots.join(", ")),
+            }
+        }
+    };
+    Ok(PublishPlan {
+        uris: tree.documents.keys().map(|path| (path.clone(), template.expand(path))).collect(),
+        root_uri: template.expand(root_path),
+        root_identity: identities.remove(root_path).flatten(),
+    })
+}
+
+/// The in-toto statement a document carries, and the identity its certificate names if it is a
+/// Sigstore bundle. Bundles, bare DSSE envelopes and plain statements (for detached signatures)
+/// are read; other files, such as artifacts and signatures, have none.
+fn statement_of(document: &[u8]) -> Option<(InTotoStatement, Option<String>)> {
+    let text = std::str::from_utf8(document).ok()?;
+    let first_line = text.lines().find(|line| !line.trim().is_empty())?;
+    for candidate in [first_line, text] {
+        let Ok(json) = serde_json::from_str::<Value>(candidate) else {
+            continue;
+        };
+        let envelope = json.get("dsseEnvelope").unwrap_or(&json);
+        if let Some(payload) = envelope.get("payload").and_then(Value::as_str) {
+            let statement = serde_json::from_slice(&STANDARD.decode(payload).ok()?).ok()?;
+            let identity = decode_bundle_unverified(candidate)
+                .ok()
+                .and_then(|bundle| bundle.identities.into_iter().next());
+            return Some((statement, identity));
+        }
+        return serde_json::from_value(json).ok().map(|statement| (statement, None));
+    }
+    None
+}
+
+/// Collects the `uri` of every link in a predicate.
+fn link_uris(value: &Value, uris: &mut Vec<String>) {
+    match value {
+        Value::Object(fields) => {
+            for (name, value) in fields {
+                match (name.as_str(), value) {
+                    ("uri", Value::String(uri)) => uris.push(uri.clone()),
+                    _ => link_uris(value, uris),
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| link_uris(item, uris)),
+        _ => {}
+    }
+}
+
+/// Hosting for published attestation trees.
+pub(crate) trait Uploader: Send + Sync {
+    /// Uploads the file at `path` within the tree.
+    fn upload<'a>(&'a self, path: &'a str, document: &'a [u8]) -> BoxFuture<'a, Result<()>>;
+}
+
+/// Options of the upload targets that need them.
+#[derive(Default)]
+pub(crate) struct TargetOptions {
+    /// An S3-compatible endpoint (e.g. MinIO or R2) to use instead of AWS, with path-style URLs
+    pub(crate) s3_endpoint: Option<String>,
+    pub(crate) s3_region: Option<String>,
+    /// WebDAV or registry credentials
+    pub(crate) username: Option<String>,
+    pub(crate) password: Option<String>,
+}
+
+/// Where a tree is uploaded: `s3://bucket[/prefix]`, `webdav://host/path` (`webdav+http://` for
+/// plain HTTP) or `oci://registry/repository`.
+pub(crate) enum UploadTarget {
+    S3 { bucket: String, prefix: String, base_url: String, region: String },
+    WebDav { base_url: String },
+    Oci { repository: String },
+}
+
+impl UploadTarget {
+    pub(crate) fn parse(target: &str, options: &TargetOptions) -> Result<Self> {
+        let (scheme, location) = target
+            .split_once("://")
+            .with_context(|| format!("Target '{}' has no scheme (s3://, webdav:// or oci://)", target))?;
+        let location = location.trim_end_matches('/');
+        if location.is_empty() {
+            bail!("Target '{}' names no location", target);
+        }
+        match scheme {
+            "s3" => {
+                let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+                let region = options.s3_region.clone().unwrap_or_else(|| DEFAULT_S3_REGION.to_string());
+                let base_url = match &options.s3_endpoint {
+                    Some(endpoint) => format!("{}/{}", endpoint.trim_end_matches('/'), bucket),
+                    None => format!("https://{}.s3.{}.amazonaws.com", bucket, region),
+                };
+                Ok(Self::S3 { bucket: bucket.to_string(), prefix: prefix.to_string(), base_url, region })
+            }
+            "webdav" | "webdavs" => Ok(Self::WebDav { base_url: format!("https://{}", location) }),
+            "webdav+http" => Ok(Self::WebDav { base_url: format!("http://{}", location) }),
+            "oci" => Ok(Self::Oci { repository: location.to_string() }),
+            other => bail!("Unsupported target scheme '{}'; use s3://, webdav:// or oci://", other),
+        }
+    }
+
+    /// The URI template of files served straight from the target.
+    pub(crate) fn default_template(&self) -> String {
+        match self {
+            Self::S3 { prefix, base_url, .. } if prefix.is_empty() => format!("{}/{}", base_url, PATH_PLACEHOLDER),
+            Self::S3 { prefix, base_url, .. } => format!("{}/{}/{}", base_url, prefix, PATH_PLACEHOLDER),
+            Self::WebDav { base_url } => format!("{}/{}", base_url, PATH_PLACEHOLDER),
+            Self::Oci { repository } => format!("oci://{}:{}", repository, TAG_PLACEHOLDER),
+        }
+    }
+
+    pub(crate) fn uploader(&self, client: reqwest::Client, options: &TargetOptions) -> Result<Box<dyn Uploader>> {
+        Ok(match self {
+            Self::S3 { bucket, prefix, base_url, region } => Box::new(S3Uploader {
+                client,
+                base_url: base_url.clone(),
+                prefix: prefix.clone(),
+                region: region.clone(),
+                credentials: AwsCredentials::from_env()
+                    .with_context(|| format!("No credentials to upload to bucket '{}'", bucket))?,
+            }),
+            Self::WebDav { base_url } => Box::new(WebDavUploader {
+                client,
+                base_url: base_url.clone(),
+                credentials: options.username.clone().map(|username| (username, options.password.clone())),
+                collections: Mutex::new(HashSet::new()),
+            }),
+            Self::Oci { repository } => Box::new(OciUploader {
+                repository: repository.clone(),
+                auth: match (&options.username, &options.password) {
+                    (Some(username), Some(password)) => RegistryAuth::Basic(username.clone(), password.clone()),
+                    (None, None) => RegistryAuth::Anonymous,
+                    _ => bail!("Both --username and --password are required for registry authentication"),
+                },
+            }),
+        })
+    }
+}
+
+/// Uploads every file of `tree` where `plan` says it will be served.
+pub(crate) async fn publish_tree(tree: &AttestationTree, plan: &PublishPlan, uploader: &dyn Uploader) -> Result<()> {
+    for (path, document) in &tree.documents {
+        output::print(1, Status::Info, &format!("Uploading {} to {}", path, plan.uris[path]));
+        uploader
+            .upload(path, document)
+            .await
+            .with_context(|| format!("Failed to upload '{}'", path))?;
+    }
+    Ok(())
+}
+
+fn content_type(path: &str) -> &'static str {
+    match path.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("json" | "jsonl") => "application/json",
+        Some("sig" | "minisig" | "asc" | "pem" | "txt") => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Percent-encodes a path for a URL, leaving `/` and the characters RFC 3986 leaves unreserved.
+fn encode_path(path: &str) -> String {
+    path.bytes()
+        .map(|byte| match byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~' | b'/') {
+            true => (byte as char).to_string(),
+            false => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Uploads to an S3 bucket, or an S3-compatible store, with `PutObject` requests signed with
+/// AWS Signature Version 4.
+struct S3Uploader {
+    client: reqwest::Client,
+    base_url: String,
+    prefix: String,
+    region: String,
+    credentials: AwsCredentials,
+}
+
+impl Uploader for S3Uploader {
+    fn upload<'a>(&'a self, path: &'a str, document: &'a [u8]) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let key = match self.prefix.is_empty() {
+                true => path.to_string(),
+                false => format!("{}/{}", self.prefix, path),
+            };
+            let url = reqwest::Url::parse(&format!("{}/{}", self.base_url, encode_path(&key)))
+                .with_context(|| format!("Invalid S3 URL for key '{}'", key))?;
+            let headers = self.credentials.signed_headers(
+                "PUT",
+                &url,
+                vec![("content-type", content_type(path).to_string())],
+                document,
+                &self.region,
+                "s3",
+            );
+            let mut request = self.client.put(url).body(document.to_vec());
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+            let response = request.send().await.context("Failed to send PutObject request")?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                bail!("PutObject failed with HTTP status {}: {}", status, body.trim());
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Uploads to a WebDAV collection with `PUT`, creating the collections on the way with `MKCOL`.
+struct WebDavUploader {
+    client: reqwest::Client,
+    base_url: String,
+    credentials: Option<(String, Option<String>)>,
+    /// Collections already created or found to exist
+    collections: Mutex<HashSet<String>>,
+}
+
+impl WebDavUploader {
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, format!("{}/{}", self.base_url, encode_path(path)));
+        match &self.credentials {
+            Some((username, password)) => request.basic_auth(username, password.as_ref()),
+            None => request,
+        }
+    }
+}
+
+impl Uploader for WebDavUploader {
+    fn upload<'a>(&'a self, path: &'a str, document: &'a [u8]) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mkcol = reqwest::Method::from_bytes(b"MKCOL").expect("MKCOL is a valid method");
+            let parents: Vec<&str> = path.match_indices('/').map(|(end, _)| &path[..end]).collect();
+            for collection in parents {
+                if self.collections.lock().unwrap_or_else(|e| e.into_inner()).contains(collection) {
+                    continue;
+                }
+                let response = self
+                    .request(mkcol.clone(), &format!("{}/", collection))
+                    .send()
+                    .await
+                    .with_context(|| format!("Failed to create collection '{}'", collection))?;
+                // 405 Method Not Allowed: the collection already exists.
+                if !response.status().is_success() && response.status() != reqwest::StatusCode::METHOD_NOT_ALLOWED {
+                    bail!("Creating collection '{}' failed with HTTP status {}", collection, response.status());
+                }
+                self.collections.lock().unwrap_or_else(|e| e.into_inner()).insert(collection.to_string());
+            }
+
+            let response = self
+                .request(reqwest::Method::PUT, path)
+                .header("content-type", content_type(path))
+                .body(document.to_vec())
+                .send()
+                .await
+                .context("Failed to send PUT request")?;
+            if !response.status().is_success() {
+                bail!("PUT failed with HTTP status {}", response.status());
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Pushes each file as an OCI artifact tagged with [`oci_tag`] of its path.
+struct OciUploader {
+    repository: String,
+    auth: RegistryAuth,
+}
+
+impl Uploader for OciUploader {
+    fn upload<'a>(&'a self, path: &'a str, document: &'a [u8]) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let reference = format!("{}:{}", self.repository, oci_tag(path));
+            push_document(&reference, path, document, &self.auth).await.map(|_| ())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chainsights_testing::{FixtureSpec, FixtureTree};
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    const BASE_URL: &str = "https://attestations.example.test/chainsights";
+
+    /// A tree of the fixture's documents, by path without the leading `/`.
+    fn tree_of(documents: &BTreeMap<String, Vec<u8>>) -> AttestationTree {
+        let documents = documents
+            .iter()
+            .map(|(path, document)| (path.trim_start_matches('/').to_string(), document.clone()))
+            .collect();
+        AttestationTree { documents }
+    }
+
+    fn fixture_tree() -> (FixtureTree, AttestationTree) {
+        let fixture = FixtureTree::generate(&FixtureSpec::default(), BASE_URL).unwrap();
+        let tree = tree_of(&fixture.documents);
+        (fixture, tree)
+    }
+
+    #[test]
+    fn plans_uris_and_finds_the_root_catalog() {
+        let (fixture, tree) = fixture_tree();
+        let template = UriTemplate::parse(&format!("{}/{{path}}", BASE_URL)).unwrap();
+        let plan = plan_publish(&tree, &template, None).unwrap();
+        assert_eq!(plan.root_uri, fixture.root_uri);
+        assert_eq!(plan.root_identity.as_deref(), Some(FixtureSpec::default().identity.as_str()));
+        assert_eq!(plan.uris.len(), fixture.documents.len());
+
+        // Links that would not resolve once published are refused.
+        let mut documents = fixture.documents.clone();
+        documents.retain(|path, _| !path.starts_with("/components/"));
+        let error = plan_publish(&tree_of(&documents), &template, None).unwrap_err();
+        assert!(error.to_string().contains("catalog.json links to"), "{}", error);
+        // So is a template the links were not generated with.
+        let elsewhere = UriTemplate::parse("https://cdn.example.test/{path}").unwrap();
+        let error = plan_publish(&tree, &elsewhere, Some("catalog.json")).unwrap_err();
+        assert!(error.to_string().contains("No links"), "{}", error);
+        assert!(plan_publish(&tree, &template, Some("missing.json")).is_err());
+    }
+
+    #[test]
+    fn parses_targets_and_templates() {
+        let options = TargetOptions::default();
+        let template = |target: &str| UploadTarget::parse(target, &options).unwrap().default_template();
+        assert_eq!(template("s3://bucket/attestations/"), "https://bucket.s3.us-east-1.amazonaws.com/attestations/{path}");
+        assert_eq!(template("webdav://dav.example.test/files"), "https://dav.example.test/files/{path}");
+        assert_eq!(template("oci://ghcr.io/example/attestations"), "oci://ghcr.io/example/attestations:{tag}");
+        let minio = TargetOptions { s3_endpoint: Some("http://localhost:9000/".to_string()), ..Default::default() };
+        assert_eq!(UploadTarget::parse("s3://bucket", &minio).unwrap().default_template(), "http://localhost:9000/bucket/{path}");
+        assert!(UploadTarget::parse("ftp://example.test/", &options).is_err());
+        assert!(UriTemplate::parse("https://example.test/").is_err());
+        assert!(UriTemplate::parse("oci://r/x:{tag}/{path}").is_err());
+
+        assert_eq!(oci_tag("components/app/1.0.0.jsonl"), "components-app-1.0.0.jsonl");
+        assert_eq!(oci_tag(".well-known/x"), "_.well-known-x");
+        let long = oci_tag(&"a/".repeat(100));
+        assert_eq!(long.len(), MAX_TAG_LENGTH);
+        assert_ne!(long, oci_tag(&format!("{}b", "a/".repeat(100))));
+    }
+
+    #[tokio::test]
+    async fn uploads_trees_over_webdav() {
+        let server = MockServer::start().await;
+        Mock::given(method("MKCOL")).respond_with(ResponseTemplate::new(201)).mount(&server).await;
+        Mock::given(method("PUT")).respond_with(ResponseTemplate::new(201)).mount(&server).await;
+        let (_, tree) = fixture_tree();
+        let target = format!("webdav+http://{}/dav", server.address());
+        let target = UploadTarget::parse(&target, &TargetOptions::default()).unwrap();
+        let template = UriTemplate::parse(&format!("{}/{{path}}", BASE_URL)).unwrap();
+        let plan = plan_publish(&tree, &template, None).unwrap();
+        let uploader = target.uploader(reqwest::Client::new(), &TargetOptions::default()).unwrap();
+        publish_tree(&tree, &plan, uploader.as_ref()).await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let puts: Vec<_> = requests.iter().filter(|request| request.method.as_str() == "PUT").collect();
+        assert_eq!(puts.len(), tree.documents.len());
+        assert!(puts.iter().any(|request| request.url.path() == "/dav/catalog.json"));
+        // Each collection is created once, before anything is put in it.
+        let mkcols: Vec<&str> = requests
+            .iter()
+            .filter(|request| request.method.as_str() == "MKCOL")
+            .map(|request| request.url.path())
+            .collect();
+        assert_eq!(mkcols.iter().filter(|path| **path == "/dav/components/").count(), 1);
+
+        server.reset().await;
+        Mock::given(method("PUT")).respond_with(ResponseTemplate::new(403)).mount(&server).await;
+        assert!(publish_tree(&tree, &plan, uploader.as_ref()).await.is_err());
+    }
+}
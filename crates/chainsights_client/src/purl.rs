@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: Apache-2.0
+
+#[cfg(feature = "cli")]
+use std::str::FromStr;
+
+#[cfg(feature = "cli")]
+use anyhow::{Context, Result, anyhow};
+#[cfg(feature = "cli")]
+use packageurl::PackageUrl;
+
+#[cfg(feature = "cli")]
+use chainsights_core::identity::normalize_domain;
+
+pub(crate) use chainsights_predicates::strip_purl_version;
+
+/// The parts of a `pkg:chainsights` PURL.
+///
+/// The first namespace segment is the publishing domain. Any further segments name the chain of
+/// sub-catalogs leading from the domain's root catalog to the catalog listing the component, so
+/// `pkg:chainsights/example.com/team-a/my-component@1.0` is `my-component` in the `team-a`
+/// sub-catalog of `example.com`.
+#[cfg(feature = "cli")]
+#[derive(Debug, PartialEq)]
+pub(crate) struct ChainsightsPurl {
+    pub domain: String,
+    /// Sub-catalog names below the root catalog, outermost first. Empty for root components.
+    pub catalog_path: Vec<String>,
+    pub component: String,
+    pub version: Option<String>,
+}
+
+/// Parses a PURL string with the custom "chainsights" type.
+#[cfg(feature = "cli")]
+pub(crate) fn parse_chainsights_purl(purl_str: &str) -> Result<ChainsightsPurl> {
+    let purl = PackageUrl::from_str(purl_str)
+        .with_context(|| format!("Invalid PURL syntax: '{}'", purl_str))?;
+
+    if purl.ty() != "chainsights" {
+        return Err(anyhow!(
+            "PURL type must be 'chainsights', found '{}'",
+            purl.ty()
+        ));
+    }
+
+    // For pkg:chainsights/example.com/team-a/component@version the namespace is "example.com/team-a".
+    let namespace = purl.namespace()
+       .ok_or_else(|| anyhow!("PURL for 'chainsights' type must contain a domain in the namespace (e.g., pkg:chainsights/example.com/...)"))?;
+    let mut segments = namespace.split('/');
+    let domain = segments.next().unwrap_or_default();
+    let catalog_path: Vec<String> = segments.map(str::to_string).collect();
+    if domain.is_empty() || catalog_path.iter().any(String::is_empty) {
+        return Err(anyhow!(
+            "PURL namespace '{}' must be a domain optionally followed by sub-catalog names",
+            namespace
+        ));
+    }
+
+    let domain = normalize_domain(domain)?;
+
+    let component = purl.name().to_string();
+    if component.is_empty() {
+        return Err(anyhow!("PURL must contain a component name"));
+    }
+
+    Ok(ChainsightsPurl {
+        domain,
+        catalog_path,
+        component,
+        version: purl.version().map(str::to_string),
+    })
+}
+
+/// True if a PURL namespace segment selects the sub-catalog with the given name. Segments match
+/// the name exactly, ignoring case, or its slug (so "Team A" is selected by `team-a`).
+#[cfg(feature = "cli")]
+pub(crate) fn sub_catalog_matches(name: &str, segment: &str) -> bool {
+    name.eq_ignore_ascii_case(segment) || slugify(name) == segment.to_ascii_lowercase()
+}
+
+/// Turns a display name into a lowercase, dash-separated identifier.
+#[cfg(feature = "cli")]
+pub(crate) fn slugify(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_root_component() {
+        let purl = parse_chainsights_purl("pkg:chainsights/example.com/my-component@1.0").unwrap();
+        assert_eq!(
+            purl,
+            ChainsightsPurl {
+                domain: "example.com".to_string(),
+                catalog_path: Vec::new(),
+                component: "my-component".to_string(),
+                version: Some("1.0".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn parses_sub_catalog_path() {
+        let purl =
+            parse_chainsights_purl("pkg:chainsights/example.com/team-a/my-component@1.0").unwrap();
+        assert_eq!(purl.domain, "example.com");
+        assert_eq!(purl.catalog_path, vec!["team-a"]);
+        assert_eq!(purl.component, "my-component");
+        assert_eq!(purl.version.as_deref(), Some("1.0"));
+    }
+
+    #[test]
+    fn parses_nested_sub_catalogs_without_version() {
+        let purl =
+            parse_chainsights_purl("pkg:chainsights/example.com/platform/team-a/my-component")
+                .unwrap();
+        assert_eq!(purl.catalog_path, vec!["platform", "team-a"]);
+        assert_eq!(purl.component, "my-component");
+        assert_eq!(purl.version, None);
+    }
+
+    #[test]
+    fn normalizes_internationalized_domains() {
+        let unicode = parse_chainsights_purl("pkg:chainsights/B%C3%BCcher.example/app").unwrap();
+        let punycode = parse_chainsights_purl("pkg:chainsights/xn--bcher-kva.example/app").unwrap();
+        assert_eq!(unicode.domain, "xn--bcher-kva.example");
+        assert_eq!(unicode, punycode);
+    }
+
+    #[test]
+    fn rejects_missing_domain() {
+        assert!(parse_chainsights_purl("pkg:chainsights/my-component@1.0").is_err());
+    }
+
+    #[test]
+    fn rejects_other_types() {
+        assert!(parse_chainsights_purl("pkg:npm/example.com/my-component@1.0").is_err());
+    }
+
+    #[test]
+    fn matches_sub_catalogs_by_name_or_slug() {
+        assert!(sub_catalog_matches("team-a", "team-a"));
+        assert!(sub_catalog_matches("Team A", "team-a"));
+        assert!(sub_catalog_matches("Platform", "platform"));
+        assert!(!sub_catalog_matches("Team B", "team-a"));
+    }
+
+    #[test]
+    fn strips_versions_and_qualifiers() {
+        assert_eq!(strip_purl_version("pkg:npm/left-pad@1.3.0"), "pkg:npm/left-pad");
+        assert_eq!(
+            strip_purl_version("pkg:npm/%40acme/app@2.0?arch=x64"),
+            "pkg:npm/%40acme/app"
+        );
+        assert_eq!(strip_purl_version("pkg:pypi/requests"), "pkg:pypi/requests");
+    }
+}
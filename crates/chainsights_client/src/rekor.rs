@@ -0,0 +1,445 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chainsights_core::bundle::{CertificateData, DsseEnvelope, SignatureData, SigstoreBundleData, VerificationMaterial};
+#[cfg(feature = "cli")]
+use chainsights_core::bundle::{InclusionPromise, KindVersion, LogId, TlogEntry};
+#[cfg(feature = "cli")]
+use chainsights_core::verify::certificate_identities;
+#[cfg(feature = "cli")]
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::output::{self, Status};
+
+/// The public good Rekor instance.
+#[cfg(feature = "cli")]
+pub(crate) const DEFAULT_REKOR_URL: &str = "https://rekor.sigstore.dev";
+
+/// Minimal client for the Rekor transparency log API.
+#[derive(Clone)]
+pub(crate) struct RekorClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+/// A single Rekor log entry as returned by `/api/v1/log/entries/{uuid}`.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct LogEntry {
+    /// Base64 encoded canonicalized entry body.
+    body: String,
+    /// Unix time at which the entry was added to the log.
+    #[cfg(feature = "cli")]
+    integrated_time: Option<i64>,
+    /// Hex encoded ID of the log that holds the entry.
+    #[cfg(feature = "cli")]
+    #[serde(rename = "logID")]
+    log_id: Option<String>,
+    /// Index of the entry in the log.
+    #[cfg(feature = "cli")]
+    log_index: Option<i64>,
+    /// Inclusion promise and proof for the entry.
+    #[cfg(feature = "cli")]
+    verification: Option<LogEntryVerification>,
+    /// Attestation storage for in-toto entries. Holds the base64 encoded DSSE payload.
+    attestation: Option<LogEntryAttestation>,
+}
+
+#[cfg(feature = "cli")]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct LogEntryVerification {
+    signed_entry_timestamp: Option<String>,
+    inclusion_proof: Option<RekorInclusionProof>,
+}
+
+/// Inclusion proof as returned by the Rekor API (hashes are hex encoded).
+#[cfg(feature = "cli")]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RekorInclusionProof {
+    log_index: i64,
+    root_hash: String,
+    tree_size: i64,
+    hashes: Vec<String>,
+    checkpoint: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LogEntryAttestation {
+    data: Option<String>,
+}
+
+/// What a log entry records: the digest of what was signed, and the identities the signing
+/// certificate names.
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone)]
+pub(crate) struct LogEntrySummary {
+    pub(crate) uuid: String,
+    pub(crate) log_index: Option<i64>,
+    pub(crate) integrated_time: Option<DateTime<Utc>>,
+    /// The entry kind, e.g. `intoto` or `hashedrekord`.
+    pub(crate) kind: String,
+    /// Hex sha256 of the DSSE payload of `intoto` and `dsse` entries, or of the signed artifact of
+    /// `hashedrekord` entries.
+    pub(crate) sha256: Option<String>,
+    /// The statement's predicate type, when the log stores the payload.
+    pub(crate) predicate_type: Option<String>,
+    pub(crate) signer_identities: Vec<String>,
+}
+
+/// The subset of an `intoto` v0.0.2 entry body needed to rebuild a bundle.
+#[derive(Deserialize, Debug)]
+struct IntotoEntryBody {
+    kind: String,
+    spec: IntotoEntrySpec,
+}
+
+#[derive(Deserialize, Debug)]
+struct IntotoEntrySpec {
+    content: IntotoEntryContent,
+}
+
+#[derive(Deserialize, Debug)]
+struct IntotoEntryContent {
+    envelope: IntotoEntryEnvelope,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct IntotoEntryEnvelope {
+    payload_type: String,
+    signatures: Vec<IntotoEntrySignature>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct IntotoEntrySignature {
+    /// Base64 encoded signature (Rekor encodes the envelope's base64 signature a second time).
+    sig: String,
+    /// Base64 encoded PEM certificate of the signer.
+    public_key: String,
+}
+
+impl RekorClient {
+    pub(crate) fn new(client: reqwest::Client, base_url: &str) -> Self {
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Searches the log for an in-toto entry whose DSSE payload has the given sha256 digest
+    /// and returns it as Sigstore bundle JSON, ready for the regular verification path.
+    pub(crate) async fn find_bundle_by_sha256(&self, sha256_hex: &str) -> Result<String> {
+        let uuids: Vec<String> = self
+            .client
+            .post(format!("{}/api/v1/index/retrieve", self.base_url))
+            .json(&serde_json::json!({ "hash": format!("sha256:{}", sha256_hex.to_lowercase()) }))
+            .send()
+            .await
+            .with_context(|| format!("Failed to search Rekor at '{}'", self.base_url))?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse Rekor search response")?;
+        output::print(2, Status::Info, &format!("Rekor returned {} candidate entries for sha256:{}", uuids.len(), sha256_hex));
+
+        for uuid in &uuids {
+            match self.entry_to_bundle(uuid, sha256_hex).await {
+                Ok(bundle) => return Ok(bundle),
+                Err(e) => output::print(2, Status::Warning, &format!("Skipping Rekor entry {}: {}", uuid, e)),
+            }
+        }
+        bail!("No usable Rekor entry found for sha256:{}", sha256_hex)
+    }
+
+    /// Returns the UUIDs of the entries whose certificates name `email`. Rekor only indexes
+    /// signers by email address, not by other identities such as CI workflow URIs.
+    #[cfg(feature = "cli")]
+    pub(crate) async fn search_by_email(&self, email: &str) -> Result<Vec<String>> {
+        self.client
+            .post(format!("{}/api/v1/index/retrieve", self.base_url))
+            .json(&serde_json::json!({ "email": email }))
+            .send()
+            .await
+            .with_context(|| format!("Failed to search Rekor at '{}'", self.base_url))?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse Rekor search response")
+    }
+
+    async fn fetch_entry(&self, uuid: &str) -> Result<LogEntry> {
+        let entries: HashMap<String, LogEntry> = self
+            .client
+            .get(format!("{}/api/v1/log/entries/{}", self.base_url, uuid))
+            .send()
+            .await
+            .with_context(|| format!("Failed to fetch Rekor entry '{}'", uuid))?
+            .error_for_status()?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse Rekor entry '{}'", uuid))?;
+        entries
+            .into_values()
+            .next()
+            .ok_or_else(|| anyhow!("Rekor returned no entry for '{}'", uuid))
+    }
+
+    /// Fetches a log entry and summarizes what was signed and by whom. `intoto`, `dsse` and
+    /// `hashedrekord` entries are understood.
+    #[cfg(feature = "cli")]
+    pub(crate) async fn entry_summary(&self, uuid: &str) -> Result<LogEntrySummary> {
+        let entry = self.fetch_entry(uuid).await?;
+        let body: serde_json::Value =
+            serde_json::from_slice(&STANDARD.decode(&entry.body).context("Failed to decode Rekor entry body")?)
+                .context("Failed to parse Rekor entry body")?;
+        let kind = body["kind"].as_str().unwrap_or_default().to_string();
+        let spec = &body["spec"];
+        let (digest, public_keys): (&serde_json::Value, Vec<&serde_json::Value>) = match kind.as_str() {
+            "intoto" => (
+                &spec["content"]["payloadHash"]["value"],
+                spec["content"]["envelope"]["signatures"].as_array().into_iter().flatten().map(|s| &s["publicKey"]).collect(),
+            ),
+            "dsse" => (
+                &spec["payloadHash"]["value"],
+                spec["signatures"].as_array().into_iter().flatten().map(|s| &s["verifier"]).collect(),
+            ),
+            "hashedrekord" => (&spec["data"]["hash"]["value"], vec![&spec["signature"]["publicKey"]["content"]]),
+            other => bail!("Unsupported entry kind '{}'", other),
+        };
+        // The stored attestation, when there is one, is the payload itself.
+        let payload = entry.attestation.and_then(|a| a.data).and_then(|data| STANDARD.decode(data).ok());
+        let signer_identities = public_keys
+            .into_iter()
+            .filter_map(|key| STANDARD.decode(key.as_str()?).ok())
+            .filter_map(|pem| pem::parse(pem).ok())
+            .filter(|pem| pem.tag() == "CERTIFICATE")
+            .filter_map(|cert| certificate_identities(cert.contents()).ok())
+            .flatten()
+            .collect();
+        Ok(LogEntrySummary {
+            uuid: uuid.to_string(),
+            log_index: entry.log_index,
+            integrated_time: entry.integrated_time.and_then(|t| DateTime::from_timestamp(t, 0)),
+            sha256: payload
+                .as_ref()
+                .map(|payload| hex::encode(Sha256::digest(payload)))
+                .or_else(|| digest.as_str().map(str::to_ascii_lowercase)),
+            predicate_type: payload
+                .and_then(|payload| serde_json::from_slice::<serde_json::Value>(&payload).ok())
+                .and_then(|statement| statement["predicateType"].as_str().map(str::to_string)),
+            signer_identities,
+            kind,
+        })
+    }
+
+    /// Fetches a log entry and rebuilds a bundle from it, checking the payload digest.
+    async fn entry_to_bundle(&self, uuid: &str, sha256_hex: &str) -> Result<String> {
+        let entry = self.fetch_entry(uuid).await?;
+
+        let body_bytes = STANDARD
+            .decode(&entry.body)
+            .context("Failed to decode Rekor entry body")?;
+        let body: IntotoEntryBody =
+            serde_json::from_slice(&body_bytes).context("Entry is not an intoto entry")?;
+        if body.kind != "intoto" {
+            bail!("Unsupported entry kind '{}'", body.kind);
+        }
+
+        let payload = entry
+            .attestation
+            .and_then(|a| a.data)
+            .context("Entry does not include the attestation payload")?;
+        let payload_bytes = STANDARD
+            .decode(&payload)
+            .context("Failed to decode attestation payload")?;
+        let calculated = hex::encode(Sha256::digest(&payload_bytes));
+        if !calculated.eq_ignore_ascii_case(sha256_hex) {
+            bail!(
+                "Payload digest mismatch. Expected sha256: {}, Calculated: {}",
+                sha256_hex,
+                calculated
+            );
+        }
+
+        let signature = body
+            .spec
+            .content
+            .envelope
+            .signatures
+            .into_iter()
+            .next()
+            .context("Entry envelope contains no signatures")?;
+        let cert_pem = STANDARD
+            .decode(&signature.public_key)
+            .context("Failed to decode entry public key")?;
+        let cert = pem::parse(&cert_pem).context("Entry public key is not a PEM certificate")?;
+
+        let bundle = SigstoreBundleData {
+            verification_material: VerificationMaterial {
+                certificate: CertificateData {
+                    raw_bytes: STANDARD.encode(cert.contents()),
+                },
+                tlog_entries: Vec::new(),
+                timestamp_verification_data: None,
+            },
+            dsse_envelope: DsseEnvelope {
+                payload,
+                payload_type: body.spec.content.envelope.payload_type,
+                signatures: vec![SignatureData {
+                    sig: unwrap_double_encoded_sig(&signature.sig),
+                }],
+            },
+        };
+        serde_json::to_string(&bundle).context("Failed to serialize bundle")
+    }
+
+    /// Uploads the DSSE envelope of a bundle to the log as an `intoto` v0.0.2 entry and returns
+    /// the resulting log entry in the layout used by bundle `tlogEntries`.
+    #[cfg(feature = "cli")]
+    pub(crate) async fn upload_bundle(&self, bundle: &SigstoreBundleData) -> Result<TlogEntry> {
+        let envelope = &bundle.dsse_envelope;
+        let cert_der = STANDARD
+            .decode(&bundle.verification_material.certificate.raw_bytes)
+            .context("Failed to decode certificate rawBytes")?;
+        let cert_pem = pem::encode(&pem::Pem::new("CERTIFICATE", cert_der));
+
+        // intoto v0.0.2 expects the envelope's base64 payload and signatures encoded a second time.
+        let signatures = envelope
+            .signatures
+            .iter()
+            .map(|s| {
+                serde_json::json!({
+                    "sig": STANDARD.encode(&s.sig),
+                    "publicKey": STANDARD.encode(&cert_pem),
+                })
+            })
+            .collect::<Vec<_>>();
+        let proposed_entry = serde_json::json!({
+            "apiVersion": "0.0.2",
+            "kind": "intoto",
+            "spec": {
+                "content": {
+                    "envelope": {
+                        "payload": STANDARD.encode(&envelope.payload),
+                        "payloadType": envelope.payload_type,
+                        "signatures": signatures,
+                    }
+                }
+            }
+        });
+
+        let response = self
+            .client
+            .post(format!("{}/api/v1/log/entries", self.base_url))
+            .json(&proposed_entry)
+            .send()
+            .await
+            .with_context(|| format!("Failed to upload entry to Rekor at '{}'", self.base_url))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            bail!("Rekor rejected the entry: HTTP Status {}: {}", status, text);
+        }
+        let entries: HashMap<String, LogEntry> = response
+            .json()
+            .await
+            .context("Failed to parse Rekor upload response")?;
+        let (uuid, entry) = entries
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Rekor returned no entry for the upload"))?;
+        output::eprint(1, Status::Verified, &format!("Uploaded to Rekor as entry {}", uuid));
+
+        log_entry_to_tlog_entry(entry)
+    }
+}
+
+/// Appends a transparency log entry to `verificationMaterial.tlogEntries` of a bundle,
+/// preserving any fields of the bundle that are not otherwise modeled.
+#[cfg(feature = "cli")]
+pub(crate) fn embed_tlog_entry(bundle_json: &mut serde_json::Value, entry: &TlogEntry) -> Result<()> {
+    let material = bundle_json
+        .get_mut("verificationMaterial")
+        .and_then(|m| m.as_object_mut())
+        .context("Bundle has no verificationMaterial object")?;
+    let entries = material
+        .entry("tlogEntries")
+        .or_insert_with(|| serde_json::Value::Array(Vec::new()))
+        .as_array_mut()
+        .context("Bundle tlogEntries is not an array")?;
+    entries.push(serde_json::to_value(entry).context("Failed to serialize tlog entry")?);
+    Ok(())
+}
+
+/// Converts a Rekor API log entry into the bundle `tlogEntries` layout.
+#[cfg(feature = "cli")]
+fn log_entry_to_tlog_entry(entry: LogEntry) -> Result<TlogEntry> {
+    let log_id = entry.log_id.context("Rekor entry is missing its log ID")?;
+    let verification = entry.verification;
+    let inclusion_promise = verification
+        .as_ref()
+        .and_then(|v| v.signed_entry_timestamp.clone())
+        .map(|set| InclusionPromise {
+            signed_entry_timestamp: set,
+        });
+    let inclusion_proof = match verification.and_then(|v| v.inclusion_proof) {
+        Some(proof) => Some(serde_json::json!({
+            "logIndex": proof.log_index.to_string(),
+            "rootHash": hex_to_base64(&proof.root_hash)?,
+            "treeSize": proof.tree_size.to_string(),
+            "hashes": proof
+                .hashes
+                .iter()
+                .map(|h| hex_to_base64(h))
+                .collect::<Result<Vec<_>>>()?,
+            "checkpoint": { "envelope": proof.checkpoint.unwrap_or_default() },
+        })),
+        None => None,
+    };
+
+    Ok(TlogEntry {
+        log_index: entry
+            .log_index
+            .context("Rekor entry is missing its log index")?
+            .to_string(),
+        log_id: LogId {
+            key_id: hex_to_base64(&log_id)?,
+        },
+        kind_version: KindVersion {
+            kind: "intoto".to_string(),
+            version: "0.0.2".to_string(),
+        },
+        integrated_time: entry
+            .integrated_time
+            .context("Rekor entry is missing its integrated time")?
+            .to_string(),
+        inclusion_promise,
+        inclusion_proof,
+        canonicalized_body: entry.body,
+    })
+}
+
+/// Re-encodes a hex string (as used by the Rekor API) as base64 (as used by bundles).
+#[cfg(feature = "cli")]
+fn hex_to_base64(value: &str) -> Result<String> {
+    let bytes = hex::decode(value).with_context(|| format!("Invalid hex value '{}'", value))?;
+    Ok(STANDARD.encode(bytes))
+}
+
+/// Rekor stores intoto signatures base64 encoded a second time. Undo that when present.
+fn unwrap_double_encoded_sig(sig: &str) -> String {
+    STANDARD
+        .decode(sig)
+        .ok()
+        .and_then(|inner| String::from_utf8(inner).ok())
+        .filter(|inner| STANDARD.decode(inner).is_ok())
+        .unwrap_or_else(|| sig.to_string())
+}
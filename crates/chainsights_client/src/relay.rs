@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use chainsights_core::identity::SignerIdentity;
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+
+use crate::discovery::Discovery;
+use crate::fetch::Fetchers;
+use chainsights_core::identity::normalize_domain;
+use crate::models::aggregation::{AggregatedCatalogData, AggregatedComponentData, AggregatedReleaseData, RelayedVerification};
+use crate::models::chainsights::{ChainsightsPredicate, VerificationResult, VerifiedDomain};
+use crate::output::Status;
+use crate::traversal::{TraversalOptions, fetch_verified_statement};
+
+/// Accepts a trusted verifier's published results for a domain instead of traversing it. The
+/// verification statement must be signed by the verifier's identity, name the queried domain,
+/// have been verified within the maximum age, if one is given, and, when a policy is required,
+/// have been evaluated under that policy.
+pub(crate) struct RelayDiscovery {
+    uri: String,
+    identity: String,
+    policy_uri: Option<String>,
+    max_age: Option<chrono::Duration>,
+    options: TraversalOptions,
+}
+
+impl RelayDiscovery {
+    pub(crate) fn new(
+        uri: &str,
+        identity: &str,
+        policy_uri: Option<String>,
+        max_age: Option<chrono::Duration>,
+        options: TraversalOptions,
+    ) -> Self {
+        Self {
+            uri: uri.to_string(),
+            identity: identity.to_string(),
+            policy_uri,
+            max_age,
+            options,
+        }
+    }
+}
+
+impl Discovery for RelayDiscovery {
+    fn discover<'a>(
+        &'a self,
+        domain: &'a str,
+        fetchers: &'a Fetchers,
+    ) -> BoxFuture<'a, Result<AggregatedCatalogData>> {
+        Box::pin(async move {
+            self.options.hooks().message(0, Status::Info, &format!(
+                "Fetching verification results from: {} with expected identity: {}",
+                self.uri, self.identity
+            ));
+            let predicate =
+                fetch_verified_statement(&self.uri, &[SignerIdentity::new(&self.identity)], fetchers, &self.options)
+                    .await
+                    .with_context(|| format!("Failed to verify verification statement '{}'", self.uri))?;
+            let verification = match predicate {
+                ChainsightsPredicate::Verification(verification) => verification,
+                other => bail!("Expected Verification predicate at '{}', found {:?}", self.uri, other),
+            };
+
+            if normalize_domain(&verification.domain)? != normalize_domain(domain)? {
+                bail!(
+                    "Verification statement '{}' covers domain '{}', not '{}'",
+                    self.uri,
+                    verification.domain,
+                    domain
+                );
+            }
+            if let Some(policy_uri) = &self.policy_uri
+                && &verification.policy.uri != policy_uri
+            {
+                bail!(
+                    "Verification statement '{}' was evaluated under policy '{}', not '{}'",
+                    self.uri,
+                    verification.policy.uri,
+                    policy_uri
+                );
+            }
+            if let Some(max_age) = self.max_age {
+                let time_verified = DateTime::parse_from_rfc3339(&verification.time_verified).with_context(|| {
+                    format!("Invalid verification time '{}' in '{}'", verification.time_verified, self.uri)
+                })?;
+                if Utc::now() - time_verified.with_timezone(&Utc) > max_age {
+                    bail!(
+                        "Verification statement '{}' was verified at {}, more than {} hours ago",
+                        self.uri,
+                        verification.time_verified,
+                        max_age.num_hours()
+                    );
+                }
+            }
+            if verification.verification_result != VerificationResult::Passed {
+                bail!(
+                    "Verifier '{}' reported that '{}' failed policy '{}'",
+                    verification.verifier.id,
+                    domain,
+                    verification.policy.uri
+                );
+            }
+
+            self.options.hooks().message(1, Status::Verified, &format!(
+                "Accepting results verified by {} at {} under policy {}",
+                verification.verifier.id, verification.time_verified, verification.policy.uri
+            ));
+            let mut aggregate = aggregate(verification.results);
+            aggregate.relayed_from = Some(RelayedVerification {
+                uri: self.uri.clone(),
+                signer_identity: self.identity.clone(),
+                verifier_id: verification.verifier.id,
+                time_verified: verification.time_verified,
+                policy_uri: verification.policy.uri,
+            });
+            Ok(aggregate)
+        })
+    }
+}
+
+/// Builds the aggregated output for the statements a verifier accepted. Its errors are listed as
+/// component errors, since the statement does not say which level they were found at.
+fn aggregate(results: VerifiedDomain) -> AggregatedCatalogData {
+    AggregatedCatalogData {
+        catalog_predicate: results.catalog.map(Arc::new),
+        components: results
+            .components
+            .into_iter()
+            .map(|component| AggregatedComponentData {
+                releases: component
+                    .releases
+                    .into_iter()
+                    .map(|release| AggregatedReleaseData {
+                        metadata_artifacts: release.predicate.metadata_links.clone().unwrap_or_default(),
+                        release_predicate: Some(Arc::new(release.predicate)),
+                        release_link_uri: release.uri,
+                        ..Default::default()
+                    })
+                    .collect(),
+                component_predicate: Some(Arc::new(component.predicate)),
+                component_link_uri: component.uri,
+                ..Default::default()
+            })
+            .collect(),
+        component_errors: results.errors.into_iter().map(|error| (error.uri, error.error)).collect(),
+        ..Default::default()
+    }
+}
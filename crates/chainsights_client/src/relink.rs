@@ -0,0 +1,388 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde_json::Value;
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::gitoid::{GITOID_DIGEST_KEY, gitoid_uri, parse_gitoid_uri};
+use crate::publish::{AttestationTree, UriTemplate};
+
+/// Extensions of the detached signatures a statement may have next to it.
+const DETACHED_SIGNATURE_EXTENSIONS: [&str; 3] = ["sig", "minisig", "asc"];
+const PGP_CLEARTEXT_BEGIN: &str = "-----BEGIN PGP SIGNED MESSAGE-----";
+const PGP_SIGNATURE_BEGIN: &str = "-----BEGIN PGP SIGNATURE-----";
+
+/// A `--map` rule: links under `from` are moved under `to`. Each side is either a URI template
+/// with `{path}` or `{tag}` or, for a plain move, a URI prefix
+/// (`https://example.github.io/attestations/=https://cdn.example.com/`).
+#[derive(Clone)]
+pub(crate) struct UriMapping {
+    from: UriTemplate,
+    to: UriTemplate,
+}
+
+impl UriMapping {
+    pub(crate) fn parse(rule: &str) -> Result<Self> {
+        let (from, to) = rule.split_once('=').with_context(|| format!("Mapping '{}' is not FROM=TO", rule))?;
+        let templated = |side: &str| side.contains("{path}") || side.contains("{tag}");
+        let (from, to) = match (templated(from), templated(to)) {
+            (true, true) => (UriTemplate::parse(from)?, UriTemplate::parse(to)?),
+            (false, false) => (UriTemplate::parse(&format!("{}{{path}}", from))?, UriTemplate::parse(&format!("{}{{path}}", to))?),
+            _ => bail!("Mapping '{}' must use a placeholder on both sides or on neither", rule),
+        };
+        if from.is_tag() && !to.is_tag() {
+            bail!("Mapping '{}' can't recover paths from OCI tags; map {{tag}} to {{tag}}", rule);
+        }
+        Ok(Self { from, to })
+    }
+
+    fn rewrite(&self, uri: &str) -> Option<String> {
+        self.from.key_of(uri).map(|key| self.to.expand(key))
+    }
+}
+
+/// How a statement in the tree is signed, which decides how it is written back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Signing {
+    /// A Sigstore bundle or DSSE envelope
+    Envelope,
+    /// A PGP cleartext-signed message
+    PgpCleartext,
+    /// A plain statement with a detached signature next to it (`.sig`, `.minisig` or `.asc`)
+    Detached,
+    /// An unsigned statement, such as a draft from `generate`
+    Unsigned,
+}
+
+impl fmt::Display for Signing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Signing::Envelope => "was a Sigstore bundle or DSSE envelope",
+            Signing::PgpCleartext => "was a PGP cleartext-signed message",
+            Signing::Detached => "had a detached signature",
+            Signing::Unsigned => "was unsigned",
+        })
+    }
+}
+
+struct Statement {
+    original: Value,
+    current: Value,
+    signing: Signing,
+    /// Whether plain statements were written pretty-printed, and with a trailing newline
+    pretty: bool,
+    trailing_newline: bool,
+    /// Whether the subject digests are those of the predicate, as `generate` writes them
+    subject_digests_predicate: bool,
+}
+
+impl Statement {
+    /// The statement as it is written back. For statements taken out of an envelope or PGP
+    /// message this is their compact JSON without a newline: the exact payload to sign, which
+    /// links to them are pinned to.
+    fn bytes(&self) -> Vec<u8> {
+        match (self.signing, self.pretty) {
+            (Signing::Envelope | Signing::PgpCleartext, _) => serde_json::to_vec(&self.current),
+            (_, true) => serde_json::to_vec_pretty(&self.current),
+            (_, false) => serde_json::to_vec(&self.current),
+        }
+        .map(|mut bytes| {
+            if self.trailing_newline && matches!(self.signing, Signing::Detached | Signing::Unsigned) {
+                bytes.push(b'\n');
+            }
+            bytes
+        })
+        .unwrap_or_default()
+    }
+
+    fn changed(&self) -> bool {
+        self.current != self.original
+    }
+}
+
+/// What relinking did to one file of the tree.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    /// Rewritten; the statement was signed and must be signed again
+    Resign(Signing),
+    /// Rewritten; the statement was not signed
+    Rewritten,
+    /// A detached signature of a rewritten statement, left out of the new tree
+    StaleSignature,
+}
+
+/// The relocated tree, and what happened to each file that changed.
+pub(crate) struct Relinked {
+    pub(crate) documents: BTreeMap<String, Vec<u8>>,
+    pub(crate) outcomes: BTreeMap<String, Outcome>,
+    /// Digest algorithms of links that could not be recomputed and were dropped, by path
+    pub(crate) dropped_digests: Vec<(String, String)>,
+}
+
+/// Rewrites the `uri` of every link in the tree's statements by `mappings` (the first that
+/// matches applies), then recomputes the digests that pin rewritten statements: those of links
+/// to them from elsewhere in the tree, and subject digests over the predicate. Statements that
+/// were signed come out as plain statements to sign again; their detached signatures are left out.
+pub(crate) fn relink_tree(tree: &AttestationTree, mappings: &[UriMapping]) -> Result<Relinked> {
+    let mut statements: BTreeMap<String, Statement> = BTreeMap::new();
+    for (path, document) in &tree.documents {
+        let signed_detached = DETACHED_SIGNATURE_EXTENSIONS
+            .iter()
+            .any(|extension| tree.documents.contains_key(&format!("{}.{}", path, extension)));
+        if let Some(statement) = parse_statement(document, signed_detached) {
+            statements.insert(path.clone(), statement);
+        }
+    }
+
+    for statement in statements.values_mut() {
+        if let Some(predicate) = statement.current.get_mut("predicate") {
+            rewrite_uris(predicate, mappings);
+        }
+    }
+
+    // Resolves link URIs, old or new, to the files of the tree they point at.
+    let mut by_key: HashMap<(usize, bool, String), &str> = HashMap::new();
+    for path in tree.documents.keys() {
+        for (index, mapping) in mappings.iter().enumerate() {
+            by_key.insert((index, false, mapping.from.key(path)), path);
+            by_key.insert((index, true, mapping.to.key(path)), path);
+        }
+    }
+    let resolve = |uri: &str| -> Option<String> {
+        mappings.iter().enumerate().find_map(|(index, mapping)| {
+            [(false, &mapping.from), (true, &mapping.to)].into_iter().find_map(|(to, template)| {
+                let key = template.key_of(uri)?;
+                by_key.get(&(index, to, key.to_string())).map(|path| path.to_string())
+            })
+        })
+    };
+
+    // Rewriting a statement changes the digests pinning it, which changes the statements linking
+    // to it in turn, so digests are updated until nothing changes.
+    let mut dropped_digests = Vec::new();
+    for _ in 0..=statements.len() {
+        let pinned: HashMap<String, Vec<u8>> = statements
+            .iter_mut()
+            .filter_map(|(path, statement)| {
+                update_subject_digests(statement);
+                statement.changed().then(|| (path.clone(), statement.bytes()))
+            })
+            .collect();
+        let mut updated = false;
+        for (path, statement) in statements.iter_mut() {
+            let Some(predicate) = statement.current.get_mut("predicate") else { continue };
+            let mut links = Vec::new();
+            collect_links(predicate, &mut links);
+            for link in links {
+                let Some(target) = link.get("uri").and_then(Value::as_str).and_then(&resolve) else { continue };
+                let (Some(bytes), Some(Value::Object(digest))) = (pinned.get(&target), link.get_mut("digest")) else {
+                    continue;
+                };
+                for (algorithm, value) in digest.iter_mut() {
+                    let Value::String(old) = value else { continue };
+                    match recompute_digest(algorithm, old, bytes) {
+                        Some(new) if new != *old => {
+                            *old = new;
+                            updated = true;
+                        }
+                        Some(_) => {}
+                        None => dropped_digests.push((path.clone(), algorithm.clone())),
+                    }
+                }
+                let before = digest.len();
+                digest.retain(|algorithm, value| {
+                    value.as_str().is_some_and(|old| recompute_digest(algorithm, old, bytes).is_some())
+                });
+                updated |= digest.len() != before;
+            }
+        }
+        if !updated {
+            return Ok(relinked(tree, statements, dropped_digests));
+        }
+    }
+    bail!("Statements pin each other's digests in a cycle, so the digests can't be recomputed")
+}
+
+fn relinked(tree: &AttestationTree, statements: BTreeMap<String, Statement>, mut dropped_digests: Vec<(String, String)>) -> Relinked {
+    let mut documents = tree.documents.clone();
+    let mut outcomes = BTreeMap::new();
+    for (path, statement) in statements.iter().filter(|(_, statement)| statement.changed()) {
+        documents.insert(path.clone(), statement.bytes());
+        let outcome = match statement.signing {
+            Signing::Unsigned => Outcome::Rewritten,
+            signing => Outcome::Resign(signing),
+        };
+        outcomes.insert(path.clone(), outcome);
+        for extension in DETACHED_SIGNATURE_EXTENSIONS {
+            let signature = format!("{}.{}", path, extension);
+            if documents.remove(&signature).is_some() {
+                outcomes.insert(signature, Outcome::StaleSignature);
+            }
+        }
+    }
+    dropped_digests.sort();
+    dropped_digests.dedup();
+    Relinked { documents, outcomes, dropped_digests }
+}
+
+/// Reads the statement of a document: the payload of a bundle or DSSE envelope, the text of a
+/// PGP cleartext message, or a plain statement. Other files, such as artifacts, have none.
+fn parse_statement(document: &[u8], signed_detached: bool) -> Option<Statement> {
+    let text = std::str::from_utf8(document).ok()?;
+    let (json, signing) = if text.trim_start().starts_with(PGP_CLEARTEXT_BEGIN) {
+        (serde_json::from_str::<Value>(&pgp_cleartext(text)?).ok()?, Signing::PgpCleartext)
+    } else {
+        let first_line = text.lines().find(|line| !line.trim().is_empty())?;
+        let json: Value = serde_json::from_str(first_line).or_else(|_| serde_json::from_str(text)).ok()?;
+        let envelope = json.get("dsseEnvelope").unwrap_or(&json);
+        match envelope.get("payload").and_then(Value::as_str) {
+            Some(payload) => (serde_json::from_slice(&STANDARD.decode(payload).ok()?).ok()?, Signing::Envelope),
+            None if signed_detached => (json, Signing::Detached),
+            None => (json, Signing::Unsigned),
+        }
+    };
+    if json.get("predicateType").and_then(Value::as_str).is_none() || json.get("predicate").is_none() {
+        return None;
+    }
+    let predicate_bytes = serde_json::to_vec(&json["predicate"]).ok()?;
+    let subject_digests_predicate = json["subject"].as_array().is_some_and(|subjects| {
+        subjects.iter().any(|subject| {
+            subject["digest"]["sha256"].as_str() == Some(hex::encode(Sha256::digest(&predicate_bytes)).as_str())
+        })
+    });
+    Some(Statement {
+        original: json.clone(),
+        current: json,
+        signing,
+        pretty: text.trim().contains('\n'),
+        trailing_newline: text.ends_with('\n'),
+        subject_digests_predicate,
+    })
+}
+
+/// The signed text of a PGP cleartext message, with dash-escaping undone.
+fn pgp_cleartext(text: &str) -> Option<String> {
+    let mut lines = text.trim_start().lines().skip(1).skip_while(|line| !line.trim().is_empty()).skip(1);
+    let signed: Vec<&str> = lines
+        .by_ref()
+        .take_while(|line| !line.starts_with(PGP_SIGNATURE_BEGIN))
+        .map(|line| line.strip_prefix("- ").unwrap_or(line))
+        .collect();
+    Some(signed.join("\n"))
+}
+
+fn rewrite_uris(value: &mut Value, mappings: &[UriMapping]) {
+    match value {
+        Value::Object(fields) => {
+            for (name, value) in fields.iter_mut() {
+                match (name.as_str(), value) {
+                    ("uri", Value::String(uri)) => {
+                        if let Some(rewritten) = mappings.iter().find_map(|mapping| mapping.rewrite(uri)) {
+                            *uri = rewritten;
+                        }
+                    }
+                    (_, value) => rewrite_uris(value, mappings),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| rewrite_uris(item, mappings)),
+        _ => {}
+    }
+}
+
+/// Collects every link in a predicate: the objects with a `uri`.
+fn collect_links<'a>(value: &'a mut Value, links: &mut Vec<&'a mut Value>) {
+    match value {
+        Value::Object(fields) if fields.get("uri").is_some_and(Value::is_string) => links.push(value),
+        Value::Object(fields) => fields.values_mut().for_each(|value| collect_links(value, links)),
+        Value::Array(items) => items.iter_mut().for_each(|item| collect_links(item, links)),
+        _ => {}
+    }
+}
+
+/// Recomputes subject digests that were taken over the predicate, now that it has changed.
+fn update_subject_digests(statement: &mut Statement) {
+    if !statement.subject_digests_predicate {
+        return;
+    }
+    let Ok(predicate_bytes) = serde_json::to_vec(&statement.current["predicate"]) else { return };
+    let Some(subjects) = statement.current.get_mut("subject").and_then(Value::as_array_mut) else { return };
+    for digest in subjects.iter_mut().filter_map(|subject| subject.get_mut("digest")).filter_map(Value::as_object_mut) {
+        for (algorithm, value) in digest.iter_mut() {
+            if let Some(new) = value.as_str().and_then(|old| recompute_digest(algorithm, old, &predicate_bytes)) {
+                *value = Value::String(new);
+            }
+        }
+    }
+}
+
+/// The digest `algorithm` of `bytes`, in the form of `old`. None for algorithms that can't be
+/// computed here.
+fn recompute_digest(algorithm: &str, old: &str, bytes: &[u8]) -> Option<String> {
+    match algorithm {
+        "sha256" => Some(hex::encode(Sha256::digest(bytes))),
+        "sha512" => Some(hex::encode(Sha512::digest(bytes))),
+        GITOID_DIGEST_KEY => parse_gitoid_uri(old).ok().map(|(algorithm, _)| gitoid_uri(bytes, algorithm)),
+        _ => None,
+    }
+}
+
+/// Writes a relinked tree to `dir`, which must not be the tree it was read from.
+pub(crate) fn write_tree(documents: &BTreeMap<String, Vec<u8>>, dir: &Path) -> Result<()> {
+    for (path, document) in documents {
+        let file = dir.join(path);
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("Failed to create directory '{}'", parent.display()))?;
+        }
+        std::fs::write(&file, document).with_context(|| format!("Failed to write '{}'", file.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chainsights_testing::{FixtureSpec, FixtureTree};
+
+    use super::*;
+
+    #[test]
+    fn relinks_signed_trees_and_repins_digests() {
+        let fixture = FixtureTree::generate(&FixtureSpec::default(), "https://example.github.io/attestations").unwrap();
+        let mut documents: BTreeMap<String, Vec<u8>> = fixture
+            .documents
+            .iter()
+            .map(|(path, document)| (path.trim_start_matches('/').to_string(), document.clone()))
+            .collect();
+        documents.insert("catalog.json.sig".to_string(), b"stale".to_vec());
+        let tree = AttestationTree { documents };
+        let mappings = [UriMapping::parse("https://example.github.io/attestations/=https://cdn.example.com/").unwrap()];
+        let relinked = relink_tree(&tree, &mappings).unwrap();
+
+        // Every statement links to something that moved, so each must be signed again; artifacts
+        // are unchanged.
+        assert_eq!(relinked.outcomes["catalog.json"], Outcome::Resign(Signing::Envelope));
+        assert_eq!(relinked.outcomes["catalog.json.sig"], Outcome::StaleSignature);
+        assert!(!relinked.documents.contains_key("catalog.json.sig"));
+        assert!(relinked.outcomes.keys().all(|path| !path.starts_with("artifacts/")));
+        assert!(relinked.dropped_digests.is_empty());
+
+        let catalog: Value = serde_json::from_slice(&relinked.documents["catalog.json"]).unwrap();
+        let link = &catalog["predicate"]["components"][0]["componentAttestationLink"];
+        assert_eq!(link["uri"], "https://cdn.example.com/components/app.json");
+        let component = &relinked.documents["components/app.json"];
+        assert_eq!(link["digest"]["sha256"], hex::encode(Sha256::digest(component)));
+        let predicate = serde_json::to_vec(&catalog["predicate"]).unwrap();
+        assert_eq!(catalog["subject"][0]["digest"]["sha256"], hex::encode(Sha256::digest(&predicate)));
+
+        // Links elsewhere are left alone, so relinking again changes nothing.
+        let tree = AttestationTree { documents: relinked.documents };
+        assert!(relink_tree(&tree, &mappings).unwrap().outcomes.is_empty());
+        assert!(UriMapping::parse("oci://r/x:{tag}=https://example.com/{path}").is_err());
+        assert!(UriMapping::parse("https://a/{path}=https://b/").is_err());
+    }
+}
@@ -0,0 +1,222 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, bail};
+use packageurl::PackageUrl;
+use serde::Serialize;
+
+use crate::depsdev::DepsDevClient;
+use crate::discovery::Discovery;
+use crate::fetch::Fetchers;
+use crate::models::aggregation::{AggregatedCatalogData, AggregatedComponentData};
+use crate::output::{self, Status};
+use chainsights_core::identity::normalize_domain;
+use crate::purl::strip_purl_version;
+
+/// A Chainsights component that an ecosystem package resolved to.
+#[derive(Serialize, Debug)]
+pub(crate) struct ResolvedComponent {
+    /// The component as a Chainsights PURL, carrying the ecosystem PURL's version if it had one
+    pub chainsights_purl: String,
+    /// The domain publishing the component
+    pub domain: String,
+    /// Human-readable component name
+    pub component: String,
+    /// The component PURL, alias, or repository URI that matched the ecosystem package
+    pub matched_by: String,
+}
+
+/// Where to look for the component publishing an ecosystem package.
+pub(crate) struct ResolveOptions<'a> {
+    /// Domains to search, in order.
+    pub domains: Vec<String>,
+    /// JSON file mapping versionless ecosystem PURLs (or PURL prefixes such as `pkg:npm/@acme`)
+    /// to the domain that publishes them.
+    pub mapping: Option<&'a Path>,
+    /// When set, deps.dev is asked for the package's source repository.
+    pub deps_dev: Option<DepsDevClient>,
+}
+
+/// An ecosystem package, with the domains to search for the component publishing it and the
+/// repositories it may be matched by.
+pub(crate) struct PackageQuery {
+    pub package: PackageUrl<'static>,
+    /// Candidate domains, most specific mapping first
+    pub domains: Vec<String>,
+    repositories: Vec<String>,
+}
+
+impl PackageQuery {
+    /// Works out where to look for an ecosystem PURL such as `pkg:npm/left-pad@1.3.0`.
+    pub(crate) async fn new(purl: &str, options: &ResolveOptions<'_>) -> Result<Self> {
+        let package =
+            PackageUrl::from_str(purl).with_context(|| format!("Invalid PURL syntax: '{}'", purl))?;
+        let versionless = strip_purl_version(purl);
+
+        let mut domains = match options.mapping {
+            Some(path) => mapped_domains(path, &versionless)?,
+            None => Vec::new(),
+        };
+        for domain in &options.domains {
+            if !domains.contains(domain) {
+                domains.push(domain.clone());
+            }
+        }
+        if domains.is_empty() {
+            bail!("No domains to search for '{}'; pass --domain or a --mapping file", purl);
+        }
+
+        let mut repositories = package_repositories(&package);
+        if let Some(deps_dev) = &options.deps_dev
+            && package.version().is_some()
+        {
+            match deps_dev.source_repository(purl).await {
+                Ok(Some(repository)) => repositories.push(normalize_repository(&repository)),
+                Ok(None) => {}
+                Err(e) => output::print(1, Status::Warning, &format!("deps.dev lookup failed for {}: {:#}", purl, e)),
+            }
+        }
+        Ok(Self { package, domains, repositories })
+    }
+
+    /// The components of `domain`'s aggregate whose PURL, alias PURLs, or repository URIs match
+    /// the package.
+    pub(crate) fn matching_components<'a>(
+        &self,
+        domain: &str,
+        data: &'a AggregatedCatalogData,
+    ) -> Result<Vec<(ResolvedComponent, &'a AggregatedComponentData)>> {
+        let mut resolved = Vec::new();
+        for component_data in &data.components {
+            let Some(component) = &component_data.component_predicate else {
+                continue;
+            };
+            let alias_match = component.matching_purl(&self.package);
+            let repository_match = component
+                .repositories
+                .iter()
+                .find(|r| self.repositories.contains(&normalize_repository(&r.uri)));
+            let matched_by = match (alias_match, repository_match) {
+                (Some(purl), _) if purl == component.purl => format!("purl {}", purl),
+                (Some(alias), _) => format!("alias {}", alias),
+                (None, Some(repository)) => format!("repository {}", repository.uri),
+                (None, None) => continue,
+            };
+
+            let mut chainsights_purl = PackageUrl::new("chainsights", component.name.clone())
+                .context("Failed to build Chainsights PURL")?;
+            chainsights_purl.with_namespace(domain.to_string());
+            if let Some(version) = self.package.version() {
+                chainsights_purl.with_version(version.to_string());
+            }
+            let resolved_component = ResolvedComponent {
+                chainsights_purl: chainsights_purl.to_string(),
+                domain: domain.to_string(),
+                component: component.name.clone(),
+                matched_by,
+            };
+            resolved.push((resolved_component, component_data));
+        }
+        Ok(resolved)
+    }
+}
+
+/// Searches the candidate domains for components whose PURL, alias PURLs, or repository URIs match an
+/// ecosystem PURL such as `pkg:npm/left-pad@1.3.0`.
+pub(crate) async fn resolve_ecosystem_purl(
+    purl: &str,
+    options: &ResolveOptions<'_>,
+    discovery: &dyn Discovery,
+    fetchers: &Fetchers,
+) -> Result<Vec<ResolvedComponent>> {
+    let query = PackageQuery::new(purl, options).await?;
+    output::print(1, Status::Info, &format!("Matching aliases against {}", strip_purl_version(purl)));
+    for repository in &query.repositories {
+        output::print(1, Status::Info, &format!("Matching repositories against {}", repository));
+    }
+
+    let mut resolved = Vec::new();
+    for domain in &query.domains {
+        output::print(0, Status::Info, &format!("Searching domain: {}", domain));
+        let aggregated_data = match discovery.discover(domain, fetchers).await {
+            Ok(data) => data,
+            Err(e) => {
+                output::print(1, Status::Warning, &format!("Skipping domain '{}': {:#}", domain, e));
+                continue;
+            }
+        };
+        resolved.extend(query.matching_components(domain, &aggregated_data)?.into_iter().map(|(component, _)| component));
+    }
+
+    Ok(resolved)
+}
+
+/// Returns the domains a mapping file assigns to a versionless PURL. Keys match exactly or as a
+/// prefix ending at a path separator, so `pkg:npm/@acme` covers every package in the scope.
+fn mapped_domains(path: &Path, versionless: &str) -> Result<Vec<String>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read mapping file '{}'", path.display()))?;
+    let mapping: HashMap<String, String> = serde_json::from_str(&text)
+        .with_context(|| format!("Mapping file '{}' must be a JSON object of PURL to domain", path.display()))?;
+    // npm scopes may be written either encoded or not.
+    let decoded = versionless.replace("%40", "@");
+
+    let mut matches: Vec<(&String, &String)> = mapping
+        .iter()
+        .filter(|(key, _)| {
+            let key = key.trim_end_matches('/').replace("%40", "@");
+            decoded == key || decoded.starts_with(&format!("{}/", key))
+        })
+        .collect();
+    // Most specific mapping first.
+    matches.sort_by_key(|(key, _)| std::cmp::Reverse(key.len()));
+    matches
+        .into_iter()
+        .map(|(_, domain)| normalize_domain(domain))
+        .collect()
+}
+
+/// Repository locations implied by the PURL itself: a `vcs_url` qualifier, or the package
+/// coordinates for forge types (pkg:github, pkg:gitlab, pkg:bitbucket) and Go modules.
+fn package_repositories(package: &PackageUrl) -> Vec<String> {
+    let mut repositories = Vec::new();
+    if let Some(vcs_url) = package.qualifiers().get("vcs_url") {
+        repositories.push(normalize_repository(vcs_url));
+    }
+    let host = match package.ty() {
+        "github" => Some("github.com"),
+        "gitlab" => Some("gitlab.com"),
+        "bitbucket" => Some("bitbucket.org"),
+        _ => None,
+    };
+    match (host, package.namespace()) {
+        (Some(host), Some(namespace)) => {
+            repositories.push(normalize_repository(&format!("{}/{}/{}", host, namespace, package.name())))
+        }
+        (None, Some(namespace)) if package.ty() == "golang" => {
+            repositories.push(normalize_repository(&format!("{}/{}", namespace, package.name())))
+        }
+        _ => {}
+    }
+    repositories
+}
+
+/// Reduces a repository URI to a lowercase `host/path` so clone URLs, web URLs, and deps.dev
+/// project keys compare equal.
+fn normalize_repository(uri: &str) -> String {
+    let uri = uri.strip_prefix("git+").unwrap_or(uri);
+    let without_scheme = uri.split_once("://").map_or(uri, |(_, rest)| rest);
+    let without_user = without_scheme
+        .split_once('@')
+        .filter(|(user, _)| !user.contains('/'))
+        .map_or(without_scheme, |(_, rest)| rest);
+    let without_ref = without_user.split(['#', '?', '@']).next().unwrap_or(without_user);
+    without_ref
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .replacen(':', "/", 1)
+        .to_ascii_lowercase()
+}
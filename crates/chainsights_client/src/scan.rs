@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Supply-chain coverage of an application's dependencies: which of them resolve to a
+//! Chainsights component, and which to a verified release of the pinned version.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::discovery::Discovery;
+use crate::fetch::Fetchers;
+use crate::models::aggregation::{AggregatedCatalogData, AggregatedComponentData};
+use crate::output::{self, Status};
+use crate::resolve::{PackageQuery, ResolveOptions, ResolvedComponent};
+
+/// How much transparency data was discovered for a dependency.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Coverage {
+    /// A verified, unrevoked release of the pinned version
+    Release,
+    /// The publishing component, but no verified release of the pinned version
+    Component,
+    /// Nothing
+    None,
+}
+
+/// A dependency from the lockfile and what was found for it.
+#[derive(Serialize, Debug)]
+pub(crate) struct ScannedDependency {
+    pub purl: String,
+    pub coverage: Coverage,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<ResolvedComponent>,
+    /// URI of the release statement for the pinned version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_uri: Option<String>,
+    /// Why the dependency could not be looked up, e.g. no domain is mapped to it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The dependencies of a lockfile with their coverage.
+#[derive(Serialize, Debug)]
+pub(crate) struct CoverageReport {
+    pub total: usize,
+    /// Dependencies resolving to at least a component
+    pub covered: usize,
+    /// Dependencies resolving to a verified release of the pinned version
+    pub covered_releases: usize,
+    pub dependencies: Vec<ScannedDependency>,
+}
+
+impl CoverageReport {
+    /// Share of dependencies with discoverable transparency data, between 0 and 1.
+    pub(crate) fn coverage(&self) -> f64 {
+        match self.total {
+            0 => 1.0,
+            total => self.covered as f64 / total as f64,
+        }
+    }
+}
+
+/// Resolves every dependency against its candidate domains, traversing each domain once.
+pub(crate) async fn scan_dependencies(
+    purls: &[String],
+    options: &ResolveOptions<'_>,
+    discovery: &dyn Discovery,
+    fetchers: &Fetchers,
+) -> Result<CoverageReport> {
+    let mut aggregates: HashMap<String, Option<AggregatedCatalogData>> = HashMap::new();
+    let mut dependencies = Vec::new();
+    for purl in purls {
+        let query = match PackageQuery::new(purl, options).await {
+            Ok(query) => query,
+            Err(e) => {
+                dependencies.push(ScannedDependency {
+                    purl: purl.clone(),
+                    coverage: Coverage::None,
+                    components: Vec::new(),
+                    release_uri: None,
+                    error: Some(format!("{:#}", e)),
+                });
+                continue;
+            }
+        };
+
+        let mut components = Vec::new();
+        let mut release_uri = None;
+        for domain in &query.domains {
+            if !aggregates.contains_key(domain) {
+                output::print(0, Status::Info, &format!("Searching domain: {}", domain));
+                let aggregated_data = match discovery.discover(domain, fetchers).await {
+                    Ok(data) => Some(data),
+                    Err(e) => {
+                        output::print(1, Status::Warning, &format!("Skipping domain '{}': {:#}", domain, e));
+                        None
+                    }
+                };
+                aggregates.insert(domain.clone(), aggregated_data);
+            }
+            let Some(aggregated_data) = &aggregates[domain] else {
+                continue;
+            };
+            for (component, component_data) in query.matching_components(domain, aggregated_data)? {
+                if release_uri.is_none() {
+                    release_uri = query.package.version().and_then(|version| verified_release(component_data, version));
+                }
+                components.push(component);
+            }
+        }
+
+        let coverage = match (&release_uri, components.is_empty()) {
+            (Some(_), _) => Coverage::Release,
+            (None, false) => Coverage::Component,
+            (None, true) => Coverage::None,
+        };
+        dependencies.push(ScannedDependency {
+            purl: purl.clone(),
+            coverage,
+            components,
+            release_uri,
+            error: None,
+        });
+    }
+
+    Ok(CoverageReport {
+        total: dependencies.len(),
+        covered: dependencies.iter().filter(|d| d.coverage != Coverage::None).count(),
+        covered_releases: dependencies.iter().filter(|d| d.coverage == Coverage::Release).count(),
+        dependencies,
+    })
+}
+
+/// The URI of a verified, unrevoked release of `component_data` whose version is `version`,
+/// ignoring a leading `v` on either side.
+fn verified_release(component_data: &AggregatedComponentData, version: &str) -> Option<String> {
+    let version = version.trim_start_matches('v');
+    component_data
+        .releases
+        .iter()
+        .filter(|release| release.revocation.is_none() && release.verification.as_ref().is_some_and(|v| v.is_verified()))
+        .find(|release| {
+            release
+                .release_predicate
+                .as_ref()
+                .and_then(|predicate| predicate.purl.rsplit_once('@').map(|(_, v)| v.trim_start_matches('v') == version))
+                .unwrap_or(false)
+        })
+        .map(|release| release.release_link_uri.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::aggregation::AggregatedReleaseData;
+
+    use super::*;
+
+    #[test]
+    fn only_verified_releases_of_the_pinned_version_count() {
+        let release = |version: &str, verified: bool| AggregatedReleaseData {
+            release_predicate: Some(std::sync::Arc::new(
+                serde_json::from_value(serde_json::json!({
+                    "purl": format!("pkg:chainsights/example.com/app@{}", version),
+                    "name": version,
+                    "timestamp": "2025-01-01T00:00:00Z",
+                }))
+                .unwrap(),
+            )),
+            release_link_uri: format!("https://example.com/releases/app/{}.json", version),
+            verification: Some(
+                serde_json::from_value(match verified {
+                    true => serde_json::json!({ "method": "sigstore-keyless" }),
+                    false => serde_json::json!({ "method": "sigstore-keyless", "skipped_checks": ["identity"] }),
+                })
+                .unwrap(),
+            ),
+            ..Default::default()
+        };
+        let component = AggregatedComponentData {
+            releases: vec![release("1.0.0", true), release("1.1.0", false)],
+            ..Default::default()
+        };
+        assert_eq!(verified_release(&component, "v1.0.0").as_deref(), Some("https://example.com/releases/app/1.0.0.json"));
+        assert_eq!(verified_release(&component, "1.1.0"), None);
+        assert_eq!(verified_release(&component, "2.0.0"), None);
+    }
+}
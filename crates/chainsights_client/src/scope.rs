@@ -0,0 +1,191 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Result, anyhow, bail};
+use chainsights_core::identity::same_identity;
+use chainsights_core::txt::ChainsightsRecord;
+use futures::future::BoxFuture;
+
+use crate::models::aggregation::CrossDomainLink;
+use crate::models::chainsights::AttestationLink;
+
+/// The domain-scoping policy: attestation links must point at hosts under the publisher's domain
+/// or an allowed host, so that a catalog cannot splice in statements another party validly signed.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct LinkScope {
+    /// The publisher's domain. Unset until a traversal starts, which uses the queried domain or,
+    /// without one, the root catalog's host.
+    pub domain: Option<String>,
+    /// Further hosts links may point at (e.g., a CDN), each including its subdomains
+    pub allowed_hosts: Vec<String>,
+    /// Other organizations' domains that component and release links may point at once the
+    /// domain's own record corroborates the link
+    pub cross_domain: Option<CrossDomainTrust>,
+}
+
+impl LinkScope {
+    pub(crate) fn new(allowed_hosts: Vec<String>) -> Self {
+        Self { domain: None, allowed_hosts, cross_domain: None }
+    }
+
+    /// This scope, also admitting component and release links into the domains `trust` names.
+    #[cfg(feature = "dns-discovery")]
+    pub(crate) fn trusting(self, trust: CrossDomainTrust) -> Self {
+        Self { cross_domain: Some(trust), ..self }
+    }
+
+    /// This scope, limited to `domain`.
+    pub(crate) fn for_domain(&self, domain: &str) -> Self {
+        Self {
+            domain: Some(domain.to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// Fails if `uri` points outside the domain and the allowed hosts. Always passes while the
+    /// domain is unset.
+    pub(crate) fn check(&self, uri: &str) -> Result<()> {
+        let Some(domain) = &self.domain else {
+            return Ok(());
+        };
+        let Some(host) = link_host(uri) else {
+            bail!("Link '{}' has no host to check against the scope of {}", uri, domain);
+        };
+        if within(&host, domain) || self.allowed_hosts.iter().any(|allowed| within(&host, allowed)) {
+            return Ok(());
+        }
+        bail!(
+            "Link '{}' points at '{}', outside {} and the allowed hosts",
+            uri,
+            host,
+            domain
+        )
+    }
+
+    /// Like [`check`](Self::check), for a component or release link: a link outside the scope that
+    /// points into a trusted foreign domain passes if the domain's record corroborates it, and
+    /// is labeled with the domain.
+    pub(crate) async fn check_link(&self, link: &AttestationLink) -> Result<Option<CrossDomainLink>> {
+        let outside = match self.check(&link.uri) {
+            Ok(()) => return Ok(None),
+            Err(e) => e,
+        };
+        let (Some(trust), Some(host)) = (&self.cross_domain, link_host(&link.uri)) else {
+            return Err(outside);
+        };
+        match trust.domains.iter().find(|domain| within(&host, domain)) {
+            Some(domain) => trust.corroborate(domain, link).await.map(Some),
+            None => Err(outside),
+        }
+    }
+}
+
+/// Looks up the `_chainsights` record a domain publishes.
+pub(crate) trait RecordLookup: Send + Sync {
+    fn chainsights_record<'a>(&'a self, domain: &'a str) -> BoxFuture<'a, Result<ChainsightsRecord>>;
+}
+
+/// Cross-domain trust rules: the foreign domains (e.g., a vendor's, for a reseller's catalog)
+/// whose statements a catalog may link to, each vouching for links into it with its own
+/// `_chainsights` record. A link is only followed if every identity it accepts is the one that
+/// record names, so a catalog cannot attribute statements to an identity the domain never claimed.
+/// Links pinning keys instead of identities are refused.
+#[derive(Clone)]
+pub(crate) struct CrossDomainTrust {
+    domains: Vec<String>,
+    records: Arc<dyn RecordLookup>,
+    /// Each domain's identity, or why it has none, looked up once per traversal
+    identities: Arc<Mutex<HashMap<String, Result<String, String>>>>,
+}
+
+impl fmt::Debug for CrossDomainTrust {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CrossDomainTrust").field("domains", &self.domains).finish_non_exhaustive()
+    }
+}
+
+impl CrossDomainTrust {
+    /// Trusts links into `domains`, including their subdomains, as corroborated by the records
+    /// `records` looks up.
+    #[cfg(feature = "dns-discovery")]
+    pub(crate) fn new(domains: Vec<String>, records: Arc<dyn RecordLookup>) -> Self {
+        Self { domains, records, identities: Arc::default() }
+    }
+
+    async fn corroborate(&self, domain: &str, link: &AttestationLink) -> Result<CrossDomainLink> {
+        let cached = self.identities.lock().unwrap().get(domain).cloned();
+        let identity = match cached {
+            Some(identity) => identity,
+            None => {
+                let identity = match self.records.chainsights_record(domain).await {
+                    Ok(record) => Ok(record.identity),
+                    Err(e) => Err(format!("{:#}", e)),
+                };
+                self.identities.lock().unwrap().insert(domain.to_string(), identity.clone());
+                identity
+            }
+        };
+        let identity = identity.map_err(|e| anyhow!("Cross-domain link '{}' into {} is not corroborated: {}", link.uri, domain, e))?;
+        // Key pins replace the identity check, so the record could not vouch for the signer.
+        if !link.pgp_fingerprints().is_empty() || !link.public_keys().is_empty() {
+            bail!("Cross-domain link '{}' into {} pins signing keys, which its _chainsights record cannot corroborate", link.uri, domain);
+        }
+        if let Some(claimed) = link
+            .accepted_identities()
+            .into_iter()
+            .find(|accepted| !same_identity(&accepted.identity, &identity))
+        {
+            bail!(
+                "Cross-domain link '{}' expects '{}', but the _chainsights record of {} names '{}'",
+                link.uri,
+                claimed.identity,
+                domain,
+                identity
+            );
+        }
+        Ok(CrossDomainLink {
+            domain: domain.to_string(),
+            identity,
+            corroborated_by: format!("_chainsights.{}", domain),
+        })
+    }
+}
+
+/// The lowercase host of a URI of any scheme with an authority (https, oci, archivista).
+pub(crate) fn link_host(uri: &str) -> Option<String> {
+    let url = reqwest::Url::parse(uri).ok()?;
+    url.host_str().map(|host| host.trim_end_matches('.').to_ascii_lowercase())
+}
+
+/// True if `host` is `domain` or one of its subdomains.
+fn within(host: &str, domain: &str) -> bool {
+    let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+    host == domain || host.strip_suffix(&domain).is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_must_stay_within_the_domain_or_allowed_hosts() {
+        let scope = LinkScope::new(vec!["cdn.example.net".to_string()]).for_domain("example.com");
+        assert!(scope.check("https://example.com/catalog.json").is_ok());
+        assert!(scope.check("https://static.EXAMPLE.com/app.json").is_ok());
+        assert!(scope.check("https://assets.cdn.example.net/app.json").is_ok());
+        assert!(scope.check("oci://registry.example.com/app@sha256:abc").is_ok());
+        assert!(scope.check("https://example.org/app.json").is_err());
+        assert!(scope.check("https://notexample.com/app.json").is_err());
+        assert!(scope.check("https://example.com.attacker.test/app.json").is_err());
+        assert!(scope.check("archivista://archivista.testifysec.io/abc").is_err());
+        assert!(scope.check("not a uri").is_err());
+    }
+
+    #[test]
+    fn unset_domain_allows_everything() {
+        assert!(LinkScope::new(Vec::new()).check("https://example.org/app.json").is_ok());
+    }
+}
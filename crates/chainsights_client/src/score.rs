@@ -0,0 +1,210 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Completeness scores for the attestations a supplier publishes, so that suppliers can be
+//! compared on how much of their supply chain they make verifiable.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::depsdev::SBOM_MEDIA_TYPES;
+use crate::models::aggregation::{AggregatedCatalogData, AggregatedComponentData, AggregatedReleaseData};
+use crate::models::chainsights::{ArtifactLink, CSAF_MEDIA_TYPE};
+
+const IN_TOTO_MEDIA_TYPE: &str = "application/vnd.in-toto+json";
+const OPENVEX_MEDIA_TYPE: &str = "application/openvex+json";
+
+/// The completeness criteria a release is graded on, each worth the same share of its score.
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct ReleaseCriteria {
+    /// Links an SPDX or CycloneDX SBOM
+    pub sbom: bool,
+    /// Links an in-toto build attestation
+    pub provenance: bool,
+    /// Links an OpenVEX or CSAF document, or is named by a CSAF advisory
+    pub vex: bool,
+    /// Verified, unrevoked, and pins a digest for every artifact it links
+    pub signed_artifacts: bool,
+    /// Signed within the freshness window
+    pub fresh: bool,
+}
+
+impl ReleaseCriteria {
+    const COUNT: usize = 5;
+
+    fn met(&self) -> usize {
+        [self.sbom, self.provenance, self.vex, self.signed_artifacts, self.fresh].into_iter().filter(|met| *met).count()
+    }
+
+    /// Percentage of the criteria met.
+    fn score(&self) -> f64 {
+        self.met() as f64 * 100.0 / Self::COUNT as f64
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct ReleaseScore {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purl: Option<String>,
+    pub score: f64,
+    #[serde(flatten)]
+    pub criteria: ReleaseCriteria,
+    /// Why the release statement failed to verify, in which case it meets no criteria
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct ComponentScore {
+    pub component: String,
+    pub uri: String,
+    /// Average score of the component's releases; 0 for a component without releases
+    pub score: f64,
+    pub releases: Vec<ReleaseScore>,
+}
+
+/// The score of one supplier domain: the average score of its components.
+#[derive(Serialize, Debug)]
+pub(crate) struct SupplierScore {
+    pub domain: String,
+    pub score: f64,
+    pub components: Vec<ComponentScore>,
+    /// Why the domain could not be traversed, in which case it scores 0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SupplierScore {
+    /// A supplier whose catalog could not be traversed.
+    pub(crate) fn failed(domain: &str, error: String) -> Self {
+        Self { domain: domain.to_string(), score: 0.0, components: Vec::new(), error: Some(error) }
+    }
+
+    pub(crate) fn release_count(&self) -> usize {
+        self.components.iter().map(|component| component.releases.len()).sum()
+    }
+}
+
+/// Grades every component and release of a traversed domain. Releases that failed to verify
+/// count as meeting no criteria; components that failed to verify are left out.
+pub(crate) fn score_supplier(domain: &str, data: &AggregatedCatalogData, fresh_within: Duration, now: DateTime<Utc>) -> SupplierScore {
+    let components: Vec<ComponentScore> = data.components.iter().map(|component| score_component(component, fresh_within, now)).collect();
+    SupplierScore {
+        domain: domain.to_string(),
+        score: average(components.iter().map(|component| component.score)),
+        components,
+        error: data.root_error.clone(),
+    }
+}
+
+fn score_component(component: &AggregatedComponentData, fresh_within: Duration, now: DateTime<Utc>) -> ComponentScore {
+    let mut releases: Vec<ReleaseScore> = component
+        .releases
+        .iter()
+        .map(|release| {
+            let criteria = release_criteria(release, fresh_within, now);
+            ReleaseScore {
+                uri: release.release_link_uri.clone(),
+                purl: release.release_predicate.as_ref().map(|predicate| predicate.purl.clone()),
+                score: criteria.score(),
+                criteria,
+                error: None,
+            }
+        })
+        .collect();
+    releases.extend(component.release_errors.iter().chain(&component.expired_releases).map(|(uri, error)| ReleaseScore {
+        uri: uri.clone(),
+        purl: None,
+        score: 0.0,
+        criteria: ReleaseCriteria::default(),
+        error: Some(error.clone()),
+    }));
+    ComponentScore {
+        component: component.component_predicate.as_ref().map(|predicate| predicate.name.clone()).unwrap_or_default(),
+        uri: component.component_link_uri.clone(),
+        score: average(releases.iter().map(|release| release.score)),
+        releases,
+    }
+}
+
+pub(crate) fn release_criteria(release: &AggregatedReleaseData, fresh_within: Duration, now: DateTime<Utc>) -> ReleaseCriteria {
+    let has_media_type = |media_types: &[&str]| {
+        release.metadata_artifacts.iter().any(|link| link.media_type.as_deref().is_some_and(|media_type| media_types.contains(&media_type)))
+    };
+    let verified = release.revocation.is_none() && release.verification.as_ref().is_some_and(|v| v.is_verified());
+    let artifacts: Vec<&ArtifactLink> = release
+        .release_predicate
+        .iter()
+        .flat_map(|predicate| {
+            predicate.artifacts.iter().flatten().chain(predicate.artifact_groups.iter().flatten().flat_map(|group| &group.artifacts))
+        })
+        .collect();
+    let signed_at = release
+        .verification
+        .as_ref()
+        .and_then(|v| v.signed_at.as_deref())
+        .or(release.release_predicate.as_ref().map(|predicate| predicate.timestamp.as_str()))
+        .and_then(|time| time.parse::<DateTime<Utc>>().ok());
+
+    ReleaseCriteria {
+        sbom: has_media_type(SBOM_MEDIA_TYPES),
+        provenance: has_media_type(&[IN_TOTO_MEDIA_TYPE]),
+        vex: has_media_type(&[OPENVEX_MEDIA_TYPE, CSAF_MEDIA_TYPE]) || !release.csaf_advisories.is_empty(),
+        signed_artifacts: verified
+            && !artifacts.is_empty()
+            && artifacts.iter().all(|link| link.digest.as_ref().is_some_and(|digest| digest.values().any(|d| !d.is_empty()))),
+        fresh: signed_at.is_some_and(|signed_at| now - signed_at <= fresh_within),
+    }
+}
+
+fn average(scores: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = scores.fold((0.0, 0), |(sum, count), score| (sum + score, count + 1));
+    match count {
+        0 => 0.0,
+        count => sum / count as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn releases_are_graded_on_each_criterion() {
+        let now: DateTime<Utc> = "2025-06-01T00:00:00Z".parse().unwrap();
+        let release = |metadata_links: serde_json::Value, digest: bool, timestamp: &str| AggregatedReleaseData {
+            release_predicate: Some(std::sync::Arc::new(
+                serde_json::from_value(serde_json::json!({
+                    "purl": "pkg:chainsights/example.com/app@1.0.0",
+                    "name": "1.0.0",
+                    "timestamp": timestamp,
+                    "artifacts": [{ "uri": "https://example.com/app.tar.gz", "digest": if digest { serde_json::json!({ "sha256": "ab" }) } else { serde_json::Value::Null } }],
+                }))
+                .unwrap(),
+            )),
+            metadata_artifacts: serde_json::from_value(metadata_links).unwrap(),
+            verification: Some(serde_json::from_value(serde_json::json!({ "method": "sigstore-keyless" })).unwrap()),
+            ..Default::default()
+        };
+        let complete = release(
+            serde_json::json!([
+                { "uri": "https://example.com/app.spdx.json", "mediaType": "application/spdx+json" },
+                { "uri": "https://example.com/app.intoto.json", "mediaType": "application/vnd.in-toto+json" },
+                { "uri": "https://example.com/app.vex.json", "mediaType": "application/openvex+json" },
+            ]),
+            true,
+            "2025-05-01T00:00:00Z",
+        );
+        let bare = release(serde_json::json!([]), false, "2023-01-01T00:00:00Z");
+        let component = AggregatedComponentData {
+            releases: vec![complete, bare],
+            release_errors: vec![("https://example.com/releases/app/2.0.0.json".to_string(), "bad signature".to_string())],
+            ..Default::default()
+        };
+
+        let scored = score_component(&component, Duration::days(365), now);
+        assert_eq!(scored.releases.iter().map(|release| release.score).collect::<Vec<_>>(), [100.0, 0.0, 0.0]);
+        assert!((scored.score - 100.0 / 3.0).abs() < 1e-9);
+        assert_eq!(average(std::iter::empty()), 0.0);
+    }
+}
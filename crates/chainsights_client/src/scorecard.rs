@@ -0,0 +1,276 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! OpenSSF Scorecard results linked from components as signed in-toto attestations
+//! (`application/vnd.in-toto+json`, predicate type `https://scorecard.dev/result/v0.1`), and
+//! requirements on their check scores such as `pinned-dependencies>=8`. Only links that name the
+//! identity expected to sign them are considered, since Scorecard results are usually signed by
+//! the workflow that ran Scorecard rather than the component's publisher.
+
+use std::fmt;
+
+use anyhow::{Context, Result, bail};
+use chainsights_core::identity::SignerIdentity;
+use chainsights_core::verify::{verify_signature_with_pae, verify_signature_with_trusted_root};
+use serde::Deserialize;
+
+use crate::fetch::{Fetchers, fetch_and_verify_artifact};
+use crate::models::aggregation::{AggregatedCatalogData, AggregatedComponentData, ScorecardCheck, ScorecardResult};
+use crate::models::chainsights::ArtifactLink;
+use crate::output::{self, Status};
+use crate::traversal::TraversalOptions;
+
+const IN_TOTO_MEDIA_TYPE: &str = "application/vnd.in-toto+json";
+const SCORECARD_PREDICATE_PREFIX: &str = "https://scorecard.dev/result/";
+
+/// A Scorecard result, as `scorecard --format intoto` writes it.
+#[derive(Deserialize, Debug)]
+struct ScorecardPredicate {
+    date: Option<String>,
+    repo: Option<Repo>,
+    score: Option<f64>,
+    #[serde(default)]
+    checks: Vec<Check>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Repo {
+    name: Option<String>,
+    commit: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Check {
+    name: String,
+    score: i32,
+    reason: Option<String>,
+}
+
+/// Fetches a linked attestation and verifies it was signed by the identity its link expects,
+/// returning its Scorecard result, or None if it is some other attestation.
+async fn read_scorecard(
+    link: &ArtifactLink,
+    identity: &str,
+    fetchers: &Fetchers,
+    options: &TraversalOptions,
+) -> Result<Option<ScorecardResult>> {
+    let bytes = fetch_and_verify_artifact(link, fetchers).await?;
+    let text = String::from_utf8(bytes).context("The attestation is not UTF-8")?;
+    let bundle = text.lines().find(|line| !line.trim().is_empty()).context("The attestation is empty")?;
+    let identities = [SignerIdentity::new(identity)];
+    let verified = match &options.trusted_root {
+        Some(root) => verify_signature_with_trusted_root(bundle, &identities, options.time_source, options.clock_skew, root),
+        None => verify_signature_with_pae(bundle, &identities, options.time_source, options.clock_skew),
+    }
+    .with_context(|| format!("The attestation was not signed by {}", identity))?;
+    let mut statement: serde_json::Value =
+        serde_json::from_slice(&verified.payload).context("The attestation's payload is not a JSON statement")?;
+    if !statement["predicateType"].as_str().is_some_and(|t| t.starts_with(SCORECARD_PREDICATE_PREFIX)) {
+        return Ok(None);
+    }
+    let predicate: ScorecardPredicate =
+        serde_json::from_value(statement["predicate"].take()).context("Invalid Scorecard result")?;
+    let (repository, commit) = predicate.repo.map_or((None, None), |repo| (repo.name, repo.commit));
+    Ok(Some(ScorecardResult {
+        uri: link.uri.clone(),
+        signer_identity: verified.signer_identity,
+        repository,
+        commit,
+        date: predicate.date,
+        score: predicate.score,
+        checks: predicate
+            .checks
+            .into_iter()
+            .map(|check| ScorecardCheck { name: check.name, score: check.score, reason: check.reason })
+            .collect(),
+    }))
+}
+
+/// Reads the Scorecard results linked from every component of a catalog.
+pub(crate) async fn collect_scorecards(aggregated_data: &mut AggregatedCatalogData, fetchers: &Fetchers, options: &TraversalOptions) {
+    for component in &mut aggregated_data.components {
+        collect_component_scorecard(component, fetchers, options).await;
+    }
+}
+
+/// Verifies the in-toto attestations a component links with an expected signer identity and
+/// keeps the most recent Scorecard result among them.
+pub(crate) async fn collect_component_scorecard(component: &mut AggregatedComponentData, fetchers: &Fetchers, options: &TraversalOptions) {
+    let links: Vec<ArtifactLink> = component
+        .component_predicate
+        .as_ref()
+        .and_then(|c| c.metadata_links.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|link| link.media_type.as_deref() == Some(IN_TOTO_MEDIA_TYPE) && !link.is_gated())
+        .collect();
+    for link in links {
+        let Some(identity) = &link.expected_signer_identity else {
+            continue;
+        };
+        match read_scorecard(&link, identity, fetchers, options).await {
+            Ok(Some(result)) => {
+                output::print(1, Status::Verified, &format!("Scorecard result from {} (signed by {})", link.uri, result.signer_identity));
+                // RFC 3339 dates compare in time order as strings.
+                if component.scorecard.as_ref().is_none_or(|current| current.date < result.date) {
+                    component.scorecard = Some(result);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => component.scorecard_errors.push((link.uri.clone(), format!("{:#}", e))),
+        }
+    }
+}
+
+/// A minimum score for a Scorecard check, or for the aggregate score as `score`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ScorecardRequirement {
+    check: String,
+    minimum: f64,
+}
+
+impl fmt::Display for ScorecardRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}>={}", self.check, self.minimum)
+    }
+}
+
+impl ScorecardRequirement {
+    /// The score the result gives to the required check, if any; inconclusive checks count as absent.
+    fn score(&self, result: &ScorecardResult) -> Option<f64> {
+        match self.check.eq_ignore_ascii_case("score") {
+            true => result.score,
+            false => result.check_score(&self.check).filter(|score| *score >= 0).map(f64::from),
+        }
+    }
+}
+
+/// Clap value parser for Scorecard requirements, given as `CHECK>=SCORE` (e.g. `pinned-dependencies>=8`).
+pub(crate) fn parse_scorecard_requirement(requirement: &str) -> std::result::Result<ScorecardRequirement, String> {
+    let (check, minimum) = requirement
+        .split_once(">=")
+        .ok_or_else(|| format!("Invalid Scorecard requirement '{}', expected CHECK>=SCORE such as pinned-dependencies>=8", requirement))?;
+    let minimum: f64 = minimum
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid minimum score '{}' in '{}'", minimum, requirement))?;
+    if !(0.0..=10.0).contains(&minimum) {
+        return Err(format!("The minimum score in '{}' must be between 0 and 10", requirement));
+    }
+    Ok(ScorecardRequirement { check: check.trim().to_string(), minimum })
+}
+
+/// Returns an error listing the components whose Scorecard results fall short of `requirements`,
+/// including components with no verified result at all.
+pub(crate) fn check_scorecard_requirements<'a>(
+    components: impl IntoIterator<Item = &'a AggregatedComponentData>,
+    requirements: &[ScorecardRequirement],
+) -> Result<()> {
+    let mut violations = 0;
+    for component in components {
+        let Some(name) = component.component_predicate.as_ref().map(|c| c.name.as_str()) else {
+            continue;
+        };
+        let Some(result) = &component.scorecard else {
+            output::print(0, Status::Failed, &format!("'{}' has no verified Scorecard result", name));
+            violations += 1;
+            continue;
+        };
+        for requirement in requirements {
+            match requirement.score(result) {
+                Some(score) if score >= requirement.minimum => {}
+                Some(score) => {
+                    output::print(0, Status::Failed, &format!("'{}' scores {} but {} is required", name, score, requirement));
+                    violations += 1;
+                }
+                None => {
+                    output::print(0, Status::Failed, &format!("'{}' has no conclusive score for {}", name, requirement.check));
+                    violations += 1;
+                }
+            }
+        }
+    }
+    if violations > 0 {
+        bail!("{} Scorecard requirement(s) not met", violations);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use chainsights_core::statement::InTotoStatement;
+    use chainsights_testing::PrivateSigstore;
+    use serde_json::json;
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::models::chainsights::ChainsightsComponentPredicate;
+
+    #[tokio::test]
+    async fn verifies_linked_results_and_checks_requirements() {
+        let scorecard_identity = "https://github.com/example/app/.github/workflows/scorecard.yml@refs/heads/main";
+        let sigstore = PrivateSigstore::new().unwrap();
+        let server = MockServer::start().await;
+        let results = [
+            ("old", "2025-01-01T00:00:00Z", 4),
+            ("new", "2025-06-01T00:00:00Z", 9),
+        ];
+        let mut links = Vec::new();
+        for (name, date, pinned) in results {
+            let predicate = json!({
+                "date": date,
+                "repo": { "name": "github.com/example/app", "commit": "abc123" },
+                "scorecard": { "version": "v5.0.0" },
+                "score": 7.5,
+                "checks": [
+                    { "name": "Pinned-Dependencies", "score": pinned, "reason": "dependencies are pinned" },
+                    { "name": "Fuzzing", "score": -1, "reason": "internal error" },
+                ],
+            });
+            let statement = InTotoStatement::new("git+https://github.com/example/app", HashMap::new(), "https://scorecard.dev/result/v0.1", predicate);
+            let (bundle, _) = sigstore.signer(scorecard_identity).unwrap().sign(&statement).unwrap();
+            Mock::given(path(format!("/{}.intoto.jsonl", name)))
+                .respond_with(ResponseTemplate::new(200).set_body_string(bundle))
+                .mount(&server)
+                .await;
+            links.push(ArtifactLink {
+                uri: format!("{}/{}.intoto.jsonl", server.uri(), name),
+                digest: None,
+                media_type: Some(IN_TOTO_MEDIA_TYPE.to_string()),
+                expected_signer_identity: Some(scorecard_identity.to_string()),
+                access: None,
+                request_url: None,
+            });
+        }
+        links[0].expected_signer_identity = Some("mallory@example.com".to_string());
+        links.push(ArtifactLink { uri: format!("{}/new.intoto.jsonl", server.uri()), ..links[1].clone() });
+
+        let predicate: ChainsightsComponentPredicate = serde_json::from_value(json!({
+            "timestamp": "2025-06-01T00:00:00Z",
+            "name": "app",
+            "purl": "pkg:chainsights/example.com/app",
+            "repositories": [],
+            "subComponents": null,
+            "releaseAttestations": [],
+            "metadataLinks": links,
+        }))
+        .unwrap();
+        let mut component = AggregatedComponentData { component_predicate: Some(Arc::new(predicate)), ..Default::default() };
+        let options = TraversalOptions { trusted_root: Some(Arc::new(sigstore.trusted_root())), ..Default::default() };
+        collect_component_scorecard(&mut component, &Fetchers::new(reqwest::Client::new()), &options).await;
+
+        let result = component.scorecard.as_ref().unwrap();
+        assert_eq!(result.date.as_deref(), Some("2025-06-01T00:00:00Z"));
+        assert_eq!(result.check_score("pinned-dependencies"), Some(9));
+        assert_eq!(component.scorecard_errors.len(), 1, "the result signed by the wrong identity");
+
+        let requirement = |text: &str| parse_scorecard_requirement(text).unwrap();
+        assert!(check_scorecard_requirements([&component], &[requirement("pinned-dependencies>=8"), requirement("score>=7")]).is_ok());
+        assert!(check_scorecard_requirements([&component], &[requirement("Pinned-Dependencies >= 10")]).is_err());
+        assert!(check_scorecard_requirements([&component], &[requirement("fuzzing>=0")]).is_err());
+        assert!(parse_scorecard_requirement("pinned-dependencies>=11").is_err());
+    }
+}
@@ -0,0 +1,219 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! An offline end-to-end run against a generated fixture tree, for validating installs.
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use chainsights_core::identity::SignerIdentity;
+use chainsights_testing::{FixtureSpec, MockEnvironment};
+use chrono::{DateTime, Utc};
+
+use crate::discovery::{ChainsightsDiscovery, Discovery};
+use crate::dns::{StaticResolver, SystemResolver};
+use crate::fetch::{Fetchers, fetch_and_verify_artifact};
+use crate::output::{self, Status};
+use crate::traversal::{TraversalOptions, traverse_and_aggregate};
+
+/// A local clock before this is almost certainly unset (e.g., a board without a real-time clock).
+const EARLIEST_PLAUSIBLE_TIME: &str = "2025-01-01T00:00:00Z";
+
+/// Release that the tampering check corrupts in the default fixture.
+const TAMPERED_RELEASE_PATH: &str = "/releases/app/1.1.0.json";
+
+/// The outcome of one self-test check.
+#[derive(Debug)]
+pub(crate) struct SelftestCheck {
+    /// What was checked
+    pub name: &'static str,
+    pub passed: bool,
+    /// What was observed, or why the check failed
+    pub detail: String,
+}
+
+/// The outcome of a whole self-test run.
+#[derive(Debug, Default)]
+pub(crate) struct SelftestReport {
+    pub checks: Vec<SelftestCheck>,
+}
+
+impl SelftestReport {
+    /// The number of checks that failed.
+    pub(crate) fn failures(&self) -> usize {
+        self.checks.iter().filter(|check| !check.passed).count()
+    }
+
+    fn record(&mut self, name: &'static str, result: Result<String>) -> bool {
+        let check = match result {
+            Ok(detail) => SelftestCheck { name, passed: true, detail },
+            Err(e) => SelftestCheck {
+                name,
+                passed: false,
+                detail: format!("{:#}", e),
+            },
+        };
+        let status = if check.passed { Status::Verified } else { Status::Failed };
+        output::print(1, status, &format!("{}: {}", check.name, check.detail));
+        let passed = check.passed;
+        self.checks.push(check);
+        passed
+    }
+}
+
+/// Checks the local environment, then discovers, traverses and verifies a signed fixture tree
+/// served from an in-process HTTP server with DNS answered locally, and finally checks that
+/// tampered and wrongly signed statements are rejected. Nothing leaves the machine.
+pub(crate) async fn run_selftest(options: TraversalOptions) -> SelftestReport {
+    let mut report = SelftestReport::default();
+
+    report.record("Local clock", check_clock(Utc::now()));
+    report.record(
+        "DNS configuration",
+        SystemResolver.check_configuration().map(|()| "system resolver configuration loaded".to_string()),
+    );
+    let client = match reqwest::Client::builder().build() {
+        Ok(client) => {
+            report.record("TLS backend", Ok("HTTP client with TLS support initialized".to_string()));
+            client
+        }
+        Err(e) => {
+            report.record("TLS backend", Err(e.into()));
+            return report;
+        }
+    };
+
+    let spec = FixtureSpec::default();
+    let env = match MockEnvironment::start(spec.clone()).await {
+        Ok(env) => {
+            report.record(
+                "Fixture generation",
+                Ok(format!("signed tree for {} served at {}", env.domain(), env.base_url())),
+            );
+            env
+        }
+        Err(e) => {
+            report.record("Fixture generation", Err(e));
+            return report;
+        }
+    };
+
+    let fetchers = Fetchers::new(client);
+    let discovery = ChainsightsDiscovery::new(options.clone(), Arc::new(StaticResolver::new(env.txt_records())));
+    let aggregate = match discovery.discover(env.domain(), &fetchers).await {
+        Ok(aggregate) => aggregate,
+        Err(e) => {
+            report.record("Discovery and traversal", Err(e));
+            return report;
+        }
+    };
+    let traversal = (|| {
+        if let Some(error) = &aggregate.root_error {
+            bail!("{}", error);
+        }
+        if let Some((uri, error)) = aggregate.component_errors.first() {
+            bail!("component '{}' failed: {}", uri, error);
+        }
+        if let Some((uri, error)) = aggregate.components.iter().flat_map(|c| &c.release_errors).next() {
+            bail!("release '{}' failed: {}", uri, error);
+        }
+        let expected_releases: usize = spec.components.iter().map(|c| c.versions.len()).sum();
+        let releases: usize = aggregate.components.iter().map(|c| c.releases.len()).sum();
+        if aggregate.components.len() != spec.components.len() || releases != expected_releases {
+            bail!(
+                "expected {} components and {} releases, verified {} and {}",
+                spec.components.len(),
+                expected_releases,
+                aggregate.components.len(),
+                releases
+            );
+        }
+        Ok(format!("verified {} components and {} releases", aggregate.components.len(), releases))
+    })();
+    if !report.record("Discovery and traversal", traversal) {
+        return report;
+    }
+
+    let mut artifacts = 0;
+    let mut artifact_result = Ok(());
+    for link in aggregate.components.iter().flat_map(|c| &c.releases).flat_map(|r| &r.metadata_artifacts) {
+        if let Err(e) = fetch_and_verify_artifact(link, &fetchers).await {
+            artifact_result = Err(e);
+            break;
+        }
+        artifacts += 1;
+    }
+    report.record(
+        "Artifact digests",
+        artifact_result.map(|()| format!("{} SBOMs matched their pinned digests", artifacts)),
+    );
+
+    let identities = [SignerIdentity::new(env.identity())];
+    report.record("Policy: tampered release rejected", check_tampering(&env, &identities, &fetchers, &options).await);
+    report.record("Policy: untrusted signer rejected", check_untrusted_signer(&env, &fetchers, &options).await);
+
+    report
+}
+
+fn check_clock(now: DateTime<Utc>) -> Result<String> {
+    let earliest = DateTime::parse_from_rfc3339(EARLIEST_PLAUSIBLE_TIME)?;
+    if now < earliest {
+        bail!("local time {} is before {}; set the system clock", now.to_rfc3339(), EARLIEST_PLAUSIBLE_TIME);
+    }
+    Ok(format!("local time {}", now.to_rfc3339()))
+}
+
+async fn check_tampering(
+    env: &MockEnvironment,
+    identities: &[SignerIdentity],
+    fetchers: &Fetchers,
+    options: &TraversalOptions,
+) -> Result<String> {
+    let bundle = env
+        .document(TAMPERED_RELEASE_PATH)
+        .context("Fixture has no release to tamper with")?;
+    let tampered = String::from_utf8_lossy(bundle).replacen("\"sig\":\"", "\"sig\":\"AAAA", 1);
+    env.replace(TAMPERED_RELEASE_PATH, tampered).await?;
+
+    let aggregate = traverse_and_aggregate(env.root_uri(), identities, fetchers, options).await?;
+    let uri = env.uri(TAMPERED_RELEASE_PATH);
+    let rejected = aggregate
+        .components
+        .iter()
+        .flat_map(|c| &c.release_errors)
+        .any(|(error_uri, _)| *error_uri == uri);
+    if !rejected {
+        bail!("the corrupted signature on '{}' was accepted", uri);
+    }
+    Ok(format!("corrupted signature on '{}' reported as a release error", uri))
+}
+
+async fn check_untrusted_signer(env: &MockEnvironment, fetchers: &Fetchers, options: &TraversalOptions) -> Result<String> {
+    let identities = [SignerIdentity::new("untrusted@selftest.invalid")];
+    let aggregate = traverse_and_aggregate(env.root_uri(), &identities, fetchers, options).await?;
+    if aggregate.root_error.is_none() {
+        bail!("the catalog was accepted for an identity that did not sign it");
+    }
+    Ok("catalog rejected for an identity that did not sign it".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn selftest_passes() {
+        let options = TraversalOptions {
+            clock_skew: chrono::Duration::seconds(300),
+            ..TraversalOptions::default()
+        };
+        let report = run_selftest(options).await;
+        assert_eq!(report.failures(), 0, "{:?}", report.checks);
+        assert_eq!(report.checks.len(), 8);
+    }
+
+    #[test]
+    fn unset_clock_fails() {
+        assert!(check_clock(DateTime::UNIX_EPOCH).is_err());
+        assert!(check_clock(Utc::now()).is_ok());
+    }
+}
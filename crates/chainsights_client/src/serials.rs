@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rollback protection for root catalogs, as TUF gives its snapshots: the last catalog serial
+//! seen from each domain is recorded, and a validly signed catalog with a lower serial, as an
+//! attacker replaying an older catalog would serve, is reported as a rollback.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chainsights_core::identity::normalize_domain;
+use serde::{Deserialize, Serialize};
+
+use crate::models::chainsights::ChainsightsCatalogPredicate;
+use crate::state::{JsonStateFile, state_file};
+
+/// The last catalog seen from a domain.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct SeenSerial {
+    pub(crate) serial: u64,
+    /// The `timestamp` of the catalog that carried the serial.
+    pub(crate) timestamp: String,
+}
+
+/// A catalog older than one already seen from its domain.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Rollback {
+    pub(crate) domain: String,
+    /// The catalog's serial; None if it has none.
+    pub(crate) serial: Option<u64>,
+    pub(crate) last_seen: SeenSerial,
+}
+
+impl fmt::Display for Rollback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let SeenSerial { serial: last, timestamp } = &self.last_seen;
+        match self.serial {
+            Some(serial) if serial == *last => write!(
+                f,
+                "The root catalog of '{}' reuses serial {}, already seen on a catalog from {}",
+                self.domain, serial, timestamp
+            ),
+            Some(serial) => write!(
+                f,
+                "The root catalog of '{}' has serial {}, but serial {} (from {}) was already seen; an older catalog may be being replayed",
+                self.domain, serial, last, timestamp
+            ),
+            None => write!(
+                f,
+                "The root catalog of '{}' has no serial, but serial {} (from {}) was already seen; an older catalog may be being replayed",
+                self.domain, last, timestamp
+            ),
+        }
+    }
+}
+
+/// A JSON file of the last catalog serial seen from each domain.
+pub(crate) struct SerialStore {
+    file: JsonStateFile,
+}
+
+impl SerialStore {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self { file: JsonStateFile::new(path, "serial state") }
+    }
+
+    /// `$XDG_STATE_HOME/chainsights/serials.json`, or `~/.local/state/chainsights/serials.json`.
+    pub(crate) fn default_path() -> Result<PathBuf> {
+        state_file("serials.json", "--serial-state")
+    }
+
+    /// Compares the serial of a verified root catalog from `domain` with the last one seen,
+    /// recording it if it is newer. Returns the rollback if it is older, or reuses the last serial
+    /// on a different catalog.
+    pub(crate) fn observe(&self, domain: &str, catalog: &ChainsightsCatalogPredicate) -> Result<Option<Rollback>> {
+        let domain = normalize_domain(domain)?;
+        let mut serials: BTreeMap<String, SeenSerial> = self.file.load()?;
+        let rolled_back = match (catalog.serial, serials.get(&domain)) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(serial), Some(seen)) => serial < seen.serial || (serial == seen.serial && catalog.timestamp != seen.timestamp),
+        };
+        if rolled_back {
+            let last_seen = serials[&domain].clone();
+            return Ok(Some(Rollback { domain, serial: catalog.serial, last_seen }));
+        }
+        if let Some(serial) = catalog.serial
+            && serials.get(&domain).is_none_or(|seen| seen.serial < serial)
+        {
+            serials.insert(domain, SeenSerial { serial, timestamp: catalog.timestamp.clone() });
+            self.file.save(&serials)?;
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn catalog(serial: Option<u64>, timestamp: &str) -> ChainsightsCatalogPredicate {
+        serde_json::from_value(serde_json::json!({ "timestamp": timestamp, "serial": serial, "components": [] })).unwrap()
+    }
+
+    /// A store that has seen serial 6 of example.com.
+    fn store_at_serial_6() -> (TempDir, SerialStore) {
+        let dir = TempDir::new().unwrap();
+        let store = SerialStore::new(dir.path().join("serials.json"));
+        assert_eq!(store.observe("example.com", &catalog(Some(6), "2025-06-01T00:00:00Z")).unwrap(), None);
+        (dir, store)
+    }
+
+    #[test]
+    fn accepts_catalogs_at_or_past_the_last_seen_serial() {
+        let dir = TempDir::new().unwrap();
+        let store = SerialStore::new(dir.path().join("serials.json"));
+        assert_eq!(store.observe("example.com", &catalog(Some(5), "2025-05-01T00:00:00Z")).unwrap(), None);
+        assert_eq!(store.observe("Example.com.", &catalog(Some(5), "2025-05-01T00:00:00Z")).unwrap(), None);
+        assert_eq!(store.observe("example.com", &catalog(Some(6), "2025-06-01T00:00:00Z")).unwrap(), None);
+        assert_eq!(store.observe("other.example", &catalog(None, "2025-01-01T00:00:00Z")).unwrap(), None);
+    }
+
+    #[test]
+    fn reports_catalogs_older_than_the_last_seen() {
+        let (_dir, store) = store_at_serial_6();
+        let rollback = store.observe("example.com", &catalog(Some(5), "2025-05-01T00:00:00Z")).unwrap().unwrap();
+        assert_eq!(rollback.last_seen, SeenSerial { serial: 6, timestamp: "2025-06-01T00:00:00Z".to_string() });
+        // Rollbacks are not recorded, so the newest serial stays in force.
+        assert!(store.observe("example.com", &catalog(Some(5), "2025-05-01T00:00:00Z")).unwrap().is_some());
+    }
+
+    #[test]
+    fn reports_catalogs_dropping_or_reusing_the_last_seen_serial() {
+        let (_dir, store) = store_at_serial_6();
+        assert!(store.observe("example.com", &catalog(None, "2025-07-01T00:00:00Z")).unwrap().is_some());
+        assert!(store.observe("example.com", &catalog(Some(6), "2025-07-01T00:00:00Z")).unwrap().is_some(), "a different catalog reusing serial 6");
+    }
+}
@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, bail};
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use crate::setup::DnsProvider;
+
+/// The Cloudflare API.
+pub(crate) const DEFAULT_CLOUDFLARE_API_URL: &str = "https://api.cloudflare.com/client/v4";
+
+/// The envelope every Cloudflare API response is wrapped in.
+#[derive(Deserialize)]
+struct CloudflareResponse<T> {
+    success: bool,
+    #[serde(default)]
+    errors: Vec<CloudflareError>,
+    result: Option<T>,
+}
+
+#[derive(Deserialize)]
+struct CloudflareError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct Zone {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct DnsRecord {
+    id: String,
+    content: String,
+}
+
+/// Manages records through the Cloudflare API with an API token that can edit the zone's DNS.
+pub(crate) struct CloudflareProvider {
+    client: reqwest::Client,
+    api_url: String,
+    token: String,
+    /// The zone's ID, looked up by name when not given
+    zone_id: Option<String>,
+}
+
+impl CloudflareProvider {
+    pub(crate) fn new(client: reqwest::Client, api_url: &str, token: String, zone_id: Option<String>) -> Self {
+        Self {
+            client,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            token,
+            zone_id,
+        }
+    }
+
+    /// The ID of the zone `name` is in: the closest enclosing zone in the account.
+    async fn zone_id(&self, name: &str) -> Result<String> {
+        if let Some(zone_id) = &self.zone_id {
+            return Ok(zone_id.clone());
+        }
+        let mut candidate = Some(name);
+        while let Some(zone) = candidate {
+            let url = format!("{}/zones?name={}", self.api_url, zone);
+            let zones: Vec<Zone> = self.call(self.client.get(&url), "list zones").await?;
+            if let Some(zone) = zones.into_iter().next() {
+                return Ok(zone.id);
+            }
+            candidate = zone.split_once('.').map(|(_, parent)| parent).filter(|parent| parent.contains('.'));
+        }
+        bail!("No Cloudflare zone the token can access contains '{}'", name)
+    }
+
+    async fn call<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder, action: &str) -> Result<T> {
+        let response = request
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .with_context(|| format!("Failed to send Cloudflare request to {}", action))?;
+        let status = response.status();
+        let body: CloudflareResponse<T> = response
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse Cloudflare response to {} (HTTP status {})", action, status))?;
+        if !body.success || !status.is_success() {
+            let errors: Vec<String> =
+                body.errors.iter().map(|error| format!("{} (code {})", error.message, error.code)).collect();
+            bail!("Cloudflare request to {} failed with HTTP status {}: {}", action, status, errors.join("; "));
+        }
+        body.result.with_context(|| format!("Cloudflare response to {} has no result", action))
+    }
+}
+
+impl DnsProvider for CloudflareProvider {
+    fn replace_txt<'a>(&'a self, name: &'a str, values: &'a [String], ttl: u32) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let zone_id = self.zone_id(name).await?;
+            let records_url = format!("{}/zones/{}/dns_records", self.api_url, zone_id);
+            let existing: Vec<DnsRecord> = self
+                .call(self.client.get(format!("{}?type=TXT&name={}", records_url, name)), "list records")
+                .await?;
+
+            // Cloudflare may return TXT content quoted; values are compared without the quotes.
+            let unquoted = |content: &str| content.strip_prefix('"').and_then(|c| c.strip_suffix('"')).unwrap_or(content).to_string();
+            let existing_values: Vec<String> = existing.iter().map(|record| unquoted(&record.content)).collect();
+            for value in values.iter().filter(|value| !existing_values.contains(value)) {
+                let record = json!({ "type": "TXT", "name": name, "content": value, "ttl": ttl });
+                let _: Value = self.call(self.client.post(&records_url).json(&record), "create a record").await?;
+            }
+            for record in existing.iter().filter(|record| !values.contains(&unquoted(&record.content))) {
+                let url = format!("{}/{}", records_url, record.id);
+                let _: Value = self.call(self.client.delete(&url), "delete a record").await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::{bearer_token, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    fn success(result: Value) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({ "success": true, "errors": [], "result": result }))
+    }
+
+    #[tokio::test]
+    async fn replaces_txt_records_in_the_enclosing_zone() {
+        let server = MockServer::start().await;
+        Mock::given(path("/zones"))
+            .and(query_param("name", "_chainsights.example.com"))
+            .respond_with(success(json!([])))
+            .mount(&server)
+            .await;
+        Mock::given(path("/zones"))
+            .and(query_param("name", "example.com"))
+            .and(bearer_token("token"))
+            .respond_with(success(json!([{ "id": "zone1" }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/zones/zone1/dns_records"))
+            .respond_with(success(json!([
+                { "id": "old", "content": "\"v=chainsights1 uri=https://old.example/\"" },
+                { "id": "kept", "content": "v=chainsights1 uri=https://new.example/" },
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST")).respond_with(success(json!({ "id": "created" }))).mount(&server).await;
+        Mock::given(method("DELETE")).and(path("/zones/zone1/dns_records/old")).respond_with(success(json!({}))).mount(&server).await;
+
+        let provider = CloudflareProvider::new(reqwest::Client::new(), &server.uri(), "token".to_string(), None);
+        let values = ["v=chainsights1 uri=https://new.example/".to_string(), "other".to_string()];
+        provider.replace_txt("_chainsights.example.com", &values, 300).await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let created: Vec<Value> = requests
+            .iter()
+            .filter(|request| request.method.as_str() == "POST")
+            .map(|request| serde_json::from_slice(&request.body).unwrap())
+            .collect();
+        assert_eq!(created, [json!({ "type": "TXT", "name": "_chainsights.example.com", "content": "other", "ttl": 300 })]);
+        assert_eq!(requests.iter().filter(|request| request.method.as_str() == "DELETE").count(), 1);
+
+        server.reset().await;
+        Mock::given(path("/zones"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(json!({
+                "success": false, "errors": [{ "code": 9109, "message": "Invalid access token" }], "result": null
+            })))
+            .mount(&server)
+            .await;
+        let error = provider.replace_txt("_chainsights.example.com", &values, 300).await.unwrap_err();
+        assert!(error.to_string().contains("Invalid access token"), "{}", error);
+    }
+}
@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, bail};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::setup::{DnsProvider, presentation};
+
+/// The Google Cloud DNS API.
+pub(crate) const DEFAULT_CLOUD_DNS_API_URL: &str = "https://dns.googleapis.com/dns/v1";
+
+/// A resource record set as the Cloud DNS API represents it.
+#[derive(Serialize, Deserialize)]
+struct ResourceRecordSet {
+    name: String,
+    #[serde(rename = "type")]
+    record_type: String,
+    ttl: u32,
+    rrdatas: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ResourceRecordSets {
+    #[serde(default)]
+    rrsets: Vec<ResourceRecordSet>,
+}
+
+/// Manages records in a Cloud DNS managed zone with an OAuth access token, such as the one
+/// `gcloud auth print-access-token` prints.
+pub(crate) struct CloudDnsProvider {
+    client: reqwest::Client,
+    zone_url: String,
+    access_token: String,
+}
+
+impl CloudDnsProvider {
+    pub(crate) fn new(client: reqwest::Client, api_url: &str, access_token: String, project: &str, managed_zone: &str) -> Self {
+        Self {
+            client,
+            zone_url: format!("{}/projects/{}/managedZones/{}", api_url.trim_end_matches('/'), project, managed_zone),
+            access_token,
+        }
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder, action: &str) -> Result<reqwest::Response> {
+        let response = request
+            .bearer_auth(&self.access_token)
+            .send()
+            .await
+            .with_context(|| format!("Failed to send Cloud DNS request to {}", action))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            bail!("Cloud DNS request to {} failed with HTTP status {}: {}", action, status, body.trim());
+        }
+        Ok(response)
+    }
+}
+
+impl DnsProvider for CloudDnsProvider {
+    fn replace_txt<'a>(&'a self, name: &'a str, values: &'a [String], ttl: u32) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let fqdn = format!("{}.", name);
+            let list_url = format!("{}/rrsets", self.zone_url);
+            let existing: ResourceRecordSets = self
+                .send(self.client.get(&list_url).query(&[("name", fqdn.as_str()), ("type", "TXT")]), "list records")
+                .await?
+                .json()
+                .await
+                .context("Failed to parse Cloud DNS record sets")?;
+            let addition = ResourceRecordSet {
+                name: fqdn,
+                record_type: "TXT".to_string(),
+                ttl,
+                rrdatas: values.iter().map(|value| presentation(value)).collect(),
+            };
+            // A change is applied atomically, so the old set is replaced without a gap.
+            let change = json!({ "additions": [addition], "deletions": existing.rrsets });
+            self.send(self.client.post(format!("{}/changes", self.zone_url)).json(&change), "change records")
+                .await?;
+            Ok(())
+        })
+    }
+}
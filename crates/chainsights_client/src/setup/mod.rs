@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Managing a publisher's DNS records through their DNS provider's API.
+
+pub(crate) mod cloudflare;
+pub(crate) mod gcloud;
+pub(crate) mod rfc2136;
+pub(crate) mod route53;
+
+use anyhow::{Result, bail};
+use chainsights_core::txt::ChainsightsRecord;
+use futures::future::BoxFuture;
+
+use crate::output::{self, Status};
+
+/// TXT character strings are at most 255 bytes; longer values are split across several.
+const MAX_CHARACTER_STRING: usize = 255;
+
+/// A DNS provider whose records can be changed through its API.
+pub(crate) trait DnsProvider: Send + Sync {
+    /// Replaces the TXT records of `name` (e.g. `_chainsights.example.com`) with `values`,
+    /// creating the record set if it does not exist.
+    fn replace_txt<'a>(&'a self, name: &'a str, values: &'a [String], ttl: u32) -> BoxFuture<'a, Result<()>>;
+}
+
+/// A TXT record to set, by its fully qualified name.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct TxtRecord {
+    pub(crate) name: String,
+    pub(crate) value: String,
+}
+
+/// The TXT records to set for `domain`: its `_chainsights` record, if given, and challenge
+/// records such as domain verification tokens, named relative to the domain
+/// (`_chainsights-challenge` for `_chainsights-challenge.example.com`) unless already under it.
+pub(crate) fn planned_records(
+    domain: &str,
+    record: Option<&ChainsightsRecord>,
+    challenges: &[(String, String)],
+) -> Result<Vec<TxtRecord>> {
+    let mut records: Vec<TxtRecord> = record
+        .map(|record| TxtRecord { name: format!("_chainsights.{}", domain), value: record.to_string() })
+        .into_iter()
+        .collect();
+    for (name, value) in challenges {
+        let name = name.trim_end_matches('.').to_ascii_lowercase();
+        let name = match name == domain || name.ends_with(&format!(".{}", domain)) {
+            true => name,
+            false => format!("{}.{}", name, domain),
+        };
+        if records.iter().any(|record| record.name == name) {
+            bail!("More than one record would be set at '{}'", name);
+        }
+        records.push(TxtRecord { name, value: value.clone() });
+    }
+    if records.is_empty() {
+        bail!("Nothing to set: give the root catalog's --uri and --identity, or a --challenge record");
+    }
+    Ok(records)
+}
+
+/// Sets each record through `provider`, replacing whatever TXT records its name had.
+pub(crate) async fn apply_records(provider: &dyn DnsProvider, records: &[TxtRecord], ttl: u32) -> Result<()> {
+    for record in records {
+        output::print(0, Status::Info, &format!("Setting TXT record {}", record.name));
+        provider.replace_txt(&record.name, std::slice::from_ref(&record.value), ttl).await?;
+        output::print(1, Status::Verified, &record.value);
+    }
+    Ok(())
+}
+
+/// Splits a TXT value into character strings of at most 255 bytes, without splitting characters.
+pub(crate) fn character_strings(value: &str) -> Vec<&str> {
+    let mut strings = Vec::new();
+    let mut rest = value;
+    while rest.len() > MAX_CHARACTER_STRING {
+        let end = (0..=MAX_CHARACTER_STRING).rev().find(|&end| rest.is_char_boundary(end)).unwrap_or_default();
+        strings.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    strings.push(rest);
+    strings
+}
+
+/// A TXT value in zone file presentation format, as Route53 and Cloud DNS take it: each
+/// character string quoted, with `"` and `\` escaped.
+pub(crate) fn presentation(value: &str) -> String {
+    character_strings(value)
+        .iter()
+        .map(|string| format!("\"{}\"", string.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plans_records_and_formats_values() {
+        let record = ChainsightsRecord {
+            uri: "https://example.com/chainsights.jsonl".to_string(),
+            identity: "security@example.com".to_string(),
+            key: None,
+        };
+        let challenges = [
+            ("_chainsights-challenge".to_string(), "token".to_string()),
+            ("_verify.example.com.".to_string(), "other".to_string()),
+        ];
+        let records = planned_records("example.com", Some(&record), &challenges).unwrap();
+        let names: Vec<&str> = records.iter().map(|record| record.name.as_str()).collect();
+        assert_eq!(names, ["_chainsights.example.com", "_chainsights-challenge.example.com", "_verify.example.com"]);
+        assert!(planned_records("example.com", None, &[]).is_err());
+        assert!(planned_records("example.com", Some(&record), &[("_chainsights".to_string(), "x".to_string())]).is_err());
+
+        assert_eq!(presentation(r#"a "quoted" \ value"#), r#""a \"quoted\" \\ value""#);
+        let long = format!("{}é{}", "a".repeat(254), "b".repeat(300));
+        let strings = character_strings(&long);
+        assert_eq!(strings.iter().map(|string| string.len()).collect::<Vec<_>>(), [254, 255, 47]);
+        assert_eq!(strings.concat(), long);
+    }
+}
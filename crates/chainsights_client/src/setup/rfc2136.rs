@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail};
+use futures::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::setup::{DnsProvider, character_strings};
+
+const OPCODE_UPDATE: u16 = 5;
+const TYPE_SOA: u16 = 6;
+const TYPE_TXT: u16 = 16;
+const TYPE_TSIG: u16 = 250;
+const CLASS_IN: u16 = 1;
+const CLASS_ANY: u16 = 255;
+const TSIG_ALGORITHM: &str = "hmac-sha256";
+/// How far the server's clock may be from ours for a TSIG signature to be accepted.
+const TSIG_FUDGE: u16 = 300;
+const UPDATE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A TSIG key the server accepts updates signed with (HMAC-SHA256).
+pub(crate) struct TsigKey {
+    pub(crate) name: String,
+    pub(crate) secret: Vec<u8>,
+}
+
+/// Manages records with RFC 2136 dynamic updates sent over TCP to the zone's primary server,
+/// signed with a TSIG key (RFC 8945) unless the server authorizes updates by address.
+pub(crate) struct Rfc2136Provider {
+    /// The server's `host:port`
+    server: String,
+    zone: String,
+    key: Option<TsigKey>,
+}
+
+impl Rfc2136Provider {
+    pub(crate) fn new(server: &str, zone: &str, key: Option<TsigKey>) -> Self {
+        let server = match server.rsplit_once(':') {
+            Some((_, port)) if port.parse::<u16>().is_ok() && !server.ends_with(']') => server.to_string(),
+            _ => format!("{}:53", server),
+        };
+        Self { server, zone: zone.trim_end_matches('.').to_ascii_lowercase(), key }
+    }
+
+    async fn exchange(&self, message: &[u8]) -> Result<Vec<u8>> {
+        let mut stream = TcpStream::connect(&self.server)
+            .await
+            .with_context(|| format!("Failed to connect to {}", self.server))?;
+        let length = u16::try_from(message.len()).context("Update message is too long")?;
+        stream.write_all(&[&length.to_be_bytes()[..], message].concat()).await?;
+        let mut length = [0; 2];
+        stream.read_exact(&mut length).await?;
+        let mut response = vec![0; u16::from_be_bytes(length) as usize];
+        stream.read_exact(&mut response).await?;
+        Ok(response)
+    }
+}
+
+impl DnsProvider for Rfc2136Provider {
+    fn replace_txt<'a>(&'a self, name: &'a str, values: &'a [String], ttl: u32) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            if name != self.zone && !name.ends_with(&format!(".{}", self.zone)) {
+                bail!("'{}' is not in zone '{}'", name, self.zone);
+            }
+            let id = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos() as u16;
+            let time_signed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            let message = update_message(id, &self.zone, name, values, ttl)?;
+            let message = match &self.key {
+                Some(key) => sign_message(message, key, time_signed)?,
+                None => message,
+            };
+            let response = tokio::time::timeout(UPDATE_TIMEOUT, self.exchange(&message))
+                .await
+                .with_context(|| format!("Timed out waiting for {}", self.server))?
+                .with_context(|| format!("Dynamic update to {} failed", self.server))?;
+            if response.len() < 12 || response[..2] != id.to_be_bytes() {
+                bail!("{} sent a malformed response", self.server);
+            }
+            match response[3] & 0x0f {
+                0 => Ok(()),
+                rcode => bail!("{} refused the update: {}", self.server, rcode_name(rcode)),
+            }
+        })
+    }
+}
+
+/// An UPDATE message that deletes the TXT record set of `name` and adds one record per value.
+fn update_message(id: u16, zone: &str, name: &str, values: &[String], ttl: u32) -> Result<Vec<u8>> {
+    let mut message = Vec::new();
+    message.extend(id.to_be_bytes());
+    message.extend((OPCODE_UPDATE << 11).to_be_bytes());
+    let updates = u16::try_from(values.len() + 1).context("Too many values")?;
+    // Zone, prerequisite, update and additional counts.
+    for count in [1, 0, updates, 0] {
+        message.extend(u16::to_be_bytes(count));
+    }
+    message.extend(encode_name(zone)?);
+    message.extend(TYPE_SOA.to_be_bytes());
+    message.extend(CLASS_IN.to_be_bytes());
+
+    // Class ANY with no data deletes the whole record set.
+    message.extend(encode_name(name)?);
+    message.extend(TYPE_TXT.to_be_bytes());
+    message.extend(CLASS_ANY.to_be_bytes());
+    message.extend(0u32.to_be_bytes());
+    message.extend(0u16.to_be_bytes());
+    for value in values {
+        let data: Vec<u8> = character_strings(value)
+            .into_iter()
+            .flat_map(|string| std::iter::once(string.len() as u8).chain(string.bytes()))
+            .collect();
+        message.extend(encode_name(name)?);
+        message.extend(TYPE_TXT.to_be_bytes());
+        message.extend(CLASS_IN.to_be_bytes());
+        message.extend(ttl.to_be_bytes());
+        message.extend(u16::try_from(data.len()).context("TXT value is too long")?.to_be_bytes());
+        message.extend(data);
+    }
+    Ok(message)
+}
+
+/// Appends a TSIG record signing `message`, as RFC 8945 describes for requests.
+fn sign_message(mut message: Vec<u8>, key: &TsigKey, time_signed: u64) -> Result<Vec<u8>> {
+    let key_name = encode_name(&key.name.to_ascii_lowercase())?;
+    let algorithm = encode_name(TSIG_ALGORITHM)?;
+    let time = &time_signed.to_be_bytes()[2..];
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(&key.secret).context("Invalid TSIG secret")?;
+    mac.update(&message);
+    mac.update(&key_name);
+    mac.update(&CLASS_ANY.to_be_bytes());
+    mac.update(&0u32.to_be_bytes());
+    mac.update(&algorithm);
+    mac.update(time);
+    mac.update(&TSIG_FUDGE.to_be_bytes());
+    // Error and other data length.
+    mac.update(&[0; 4]);
+    let mac = mac.finalize().into_bytes();
+
+    let mut data = algorithm;
+    data.extend(time);
+    data.extend(TSIG_FUDGE.to_be_bytes());
+    data.extend((mac.len() as u16).to_be_bytes());
+    data.extend(mac);
+    data.extend(&message[..2]);
+    data.extend([0; 4]);
+
+    message.extend(key_name);
+    message.extend(TYPE_TSIG.to_be_bytes());
+    message.extend(CLASS_ANY.to_be_bytes());
+    message.extend(0u32.to_be_bytes());
+    message.extend((data.len() as u16).to_be_bytes());
+    message.extend(data);
+    message[11] = 1;
+    Ok(message)
+}
+
+/// A domain name in uncompressed wire format.
+fn encode_name(name: &str) -> Result<Vec<u8>> {
+    let mut encoded = Vec::new();
+    for label in name.trim_end_matches('.').split('.').filter(|label| !label.is_empty()) {
+        if label.len() > 63 {
+            bail!("Label '{}' of '{}' is longer than 63 bytes", label, name);
+        }
+        encoded.push(label.len() as u8);
+        encoded.extend(label.bytes());
+    }
+    encoded.push(0);
+    Ok(encoded)
+}
+
+fn rcode_name(rcode: u8) -> String {
+    match rcode {
+        1 => "FORMERR".to_string(),
+        2 => "SERVFAIL".to_string(),
+        3 => "NXDOMAIN".to_string(),
+        4 => "NOTIMP".to_string(),
+        5 => "REFUSED".to_string(),
+        8 => "NXRRSET".to_string(),
+        9 => "NOTAUTH (is the TSIG key right?)".to_string(),
+        10 => "NOTZONE".to_string(),
+        rcode => format!("RCODE {}", rcode),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn sends_signed_updates_and_reports_refusals() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server = listener.local_addr().unwrap().to_string();
+        let received = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for rcode in [0, 5] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut length = [0; 2];
+                stream.read_exact(&mut length).await.unwrap();
+                let mut request = vec![0; u16::from_be_bytes(length) as usize];
+                stream.read_exact(&mut request).await.unwrap();
+                let mut response = request[..12].to_vec();
+                response[2] |= 0x80;
+                response[3] = rcode;
+                stream.write_all(&[&12u16.to_be_bytes()[..], &response].concat()).await.unwrap();
+                requests.push(request);
+            }
+            requests
+        });
+
+        let key = TsigKey { name: "update-key".to_string(), secret: b"secret".to_vec() };
+        let provider = Rfc2136Provider::new(&server, "Example.com.", Some(key));
+        let values = ["v=chainsights1 uri=https://example.com/c.jsonl".to_string()];
+        provider.replace_txt("_chainsights.example.com", &values, 300).await.unwrap();
+        let error = provider.replace_txt("_chainsights.example.com", &values, 300).await.unwrap_err();
+        assert!(error.to_string().contains("REFUSED"), "{}", error);
+        assert!(provider.replace_txt("_chainsights.example.org", &values, 300).await.is_err());
+
+        let request = &received.await.unwrap()[0];
+        // An UPDATE with one zone, two updates and the TSIG record.
+        assert_eq!(request[2] >> 3, OPCODE_UPDATE as u8);
+        assert_eq!(&request[4..12], &[0, 1, 0, 0, 0, 2, 0, 1]);
+        assert_eq!(&request[12..25], b"\x07example\x03com\x00");
+        let key_name = encode_name("update-key").unwrap();
+        assert!(request.windows(key_name.len() + 2).any(|window| window == [&key_name[..], &[0, 250]].concat()));
+    }
+}
@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, bail};
+use futures::future::BoxFuture;
+
+use crate::aws::AwsCredentials;
+use crate::setup::{DnsProvider, presentation};
+
+/// The Route53 API, which is global and signed for `us-east-1`.
+pub(crate) const DEFAULT_ROUTE53_API_URL: &str = "https://route53.amazonaws.com/2013-04-01";
+const ROUTE53_REGION: &str = "us-east-1";
+
+/// Manages records in a Route53 hosted zone with `ChangeResourceRecordSets`, using the AWS
+/// credentials in the environment.
+pub(crate) struct Route53Provider {
+    client: reqwest::Client,
+    api_url: String,
+    credentials: AwsCredentials,
+    hosted_zone_id: String,
+}
+
+impl Route53Provider {
+    /// `hosted_zone_id` may be given bare (`Z0123456789ABC`) or as `/hostedzone/Z0123456789ABC`.
+    pub(crate) fn new(client: reqwest::Client, api_url: &str, credentials: AwsCredentials, hosted_zone_id: &str) -> Self {
+        Self {
+            client,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            credentials,
+            hosted_zone_id: hosted_zone_id.trim_start_matches("/hostedzone/").to_string(),
+        }
+    }
+}
+
+impl DnsProvider for Route53Provider {
+    fn replace_txt<'a>(&'a self, name: &'a str, values: &'a [String], ttl: u32) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let url = format!("{}/hostedzone/{}/rrset/", self.api_url, self.hosted_zone_id);
+            let url = reqwest::Url::parse(&url).with_context(|| format!("Invalid Route53 URL '{}'", url))?;
+            let body = change_batch(name, values, ttl).into_bytes();
+            let headers = self.credentials.signed_headers(
+                "POST",
+                &url,
+                vec![("content-type", "text/xml".to_string())],
+                &body,
+                ROUTE53_REGION,
+                "route53",
+            );
+            let mut request = self.client.post(url).body(body);
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+            let response = request.send().await.context("Failed to send ChangeResourceRecordSets request")?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                bail!("ChangeResourceRecordSets failed with HTTP status {}: {}", status, body.trim());
+            }
+            Ok(())
+        })
+    }
+}
+
+/// A `ChangeResourceRecordSetsRequest` that upserts the TXT record set of `name`.
+fn change_batch(name: &str, values: &[String], ttl: u32) -> String {
+    let records: String = values
+        .iter()
+        .map(|value| format!("<ResourceRecord><Value>{}</Value></ResourceRecord>", escape_xml(&presentation(value))))
+        .collect();
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            r#"<ChangeResourceRecordSetsRequest xmlns="https://route53.amazonaws.com/doc/2013-04-01/">"#,
+            "<ChangeBatch><Changes><Change><Action>UPSERT</Action><ResourceRecordSet>",
+            "<Name>{}.</Name><Type>TXT</Type><TTL>{}</TTL><ResourceRecords>{}</ResourceRecords>",
+            "</ResourceRecordSet></Change></Changes></ChangeBatch></ChangeResourceRecordSetsRequest>"
+        ),
+        escape_xml(name),
+        ttl,
+        records
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Signing draft statements, for publishers who sign with a key of their own rather than
+//! through Sigstore.
+
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chainsights_core::bundle::{DsseEnvelope, SignatureData, construct_pae};
+use chainsights_core::did::ed25519_did_key;
+use ed25519_dalek::pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey};
+use ed25519_dalek::{Signer as _, SigningKey};
+use futures::future::BoxFuture;
+use pkcs8::LineEnding;
+use sha2::{Digest, Sha256};
+
+const IN_TOTO_PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+/// Signs in-toto statements, producing the document that is published for them.
+pub(crate) trait StatementSigner: Send + Sync {
+    /// The identity attestation links name as the expected signer.
+    fn identity(&self) -> String;
+
+    /// Signs the statement bytes, returning the signed document on a single line.
+    fn sign<'a>(&'a self, statement: &'a [u8]) -> BoxFuture<'a, Result<String>>;
+}
+
+/// A private key that signs messages, held in memory, by an external signer, or by a KMS or
+/// hardware token.
+pub(crate) trait KeySigner: Send + Sync {
+    /// The `did:key` DID of the public key.
+    fn did_key(&self) -> BoxFuture<'_, Result<String>>;
+
+    /// Signs `message`, returning the raw signature (fixed-size or DER for ECDSA keys).
+    fn sign<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Vec<u8>>>;
+}
+
+/// An Ed25519 private key held in memory.
+pub(crate) struct Ed25519Key {
+    key: SigningKey,
+}
+
+impl Ed25519Key {
+    pub(crate) fn generate() -> Self {
+        Self { key: SigningKey::generate(&mut rand::rngs::OsRng) }
+    }
+
+    /// Parses a PKCS#8 PEM private key, as `openssl genpkey -algorithm ed25519` writes it,
+    /// decrypting it with `passphrase` if it is encrypted.
+    pub(crate) fn from_pem(pem: &str, passphrase: Option<&str>) -> Result<Self> {
+        let key = match passphrase {
+            Some(passphrase) => SigningKey::from_pkcs8_encrypted_pem(pem, passphrase)
+                .map_err(|e| anyhow!("{}", e))
+                .context("Failed to decrypt the key; is the passphrase right?")?,
+            None => SigningKey::from_pkcs8_pem(pem)
+                .map_err(|e| anyhow!("{}", e))
+                .context("Not a PKCS#8 Ed25519 private key")?,
+        };
+        Ok(Self { key })
+    }
+
+    /// The private key as PKCS#8 PEM, encrypted with `passphrase` (scrypt and AES-256-CBC) if given.
+    pub(crate) fn to_pem(&self, passphrase: Option<&str>) -> Result<String> {
+        let pem = match passphrase {
+            Some(passphrase) => self.key.to_pkcs8_encrypted_pem(rand::rngs::OsRng, passphrase, LineEnding::LF),
+            None => self.key.to_pkcs8_pem(LineEnding::LF),
+        };
+        Ok(pem.map_err(|e| anyhow!("{}", e)).context("Failed to encode the private key")?.to_string())
+    }
+
+    /// The public key as SubjectPublicKeyInfo PEM.
+    pub(crate) fn public_key_pem(&self) -> Result<String> {
+        self.key
+            .verifying_key()
+            .to_public_key_pem(LineEnding::LF)
+            .map_err(|e| anyhow!("{}", e))
+            .context("Failed to encode the public key")
+    }
+
+    pub(crate) fn did(&self) -> String {
+        ed25519_did_key(self.key.verifying_key().as_bytes())
+    }
+}
+
+impl KeySigner for Ed25519Key {
+    fn did_key(&self) -> BoxFuture<'_, Result<String>> {
+        Box::pin(async move { Ok(self.did()) })
+    }
+
+    fn sign<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Vec<u8>>> {
+        Box::pin(async move { Ok(self.key.sign(message).to_bytes().to_vec()) })
+    }
+}
+
+/// Signs statements as DSSE envelopes with a key whose identity is its `did:key` DID.
+pub(crate) struct DidKeySigner {
+    key: Box<dyn KeySigner>,
+    identity: String,
+}
+
+impl DidKeySigner {
+    pub(crate) async fn new(key: Box<dyn KeySigner>) -> Result<Self> {
+        let identity = key.did_key().await?;
+        Ok(Self { key, identity })
+    }
+}
+
+impl StatementSigner for DidKeySigner {
+    fn identity(&self) -> String {
+        self.identity.clone()
+    }
+
+    fn sign<'a>(&'a self, statement: &'a [u8]) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            let signature = self.key.sign(&construct_pae(IN_TOTO_PAYLOAD_TYPE, statement)).await?;
+            serde_json::to_string(&DsseEnvelope {
+                payload: STANDARD.encode(statement),
+                payload_type: IN_TOTO_PAYLOAD_TYPE.to_string(),
+                signatures: vec![SignatureData { sig: STANDARD.encode(signature) }],
+            })
+            .context("Failed to serialize DSSE envelope")
+        })
+    }
+}
+
+/// Writes a file readable only by its owner where the platform allows, refusing to overwrite one.
+pub(crate) fn write_private(path: &Path, contents: &str) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path).with_context(|| format!("Failed to create '{}'", path.display()))?;
+    std::io::Write::write_all(&mut file, contents.as_bytes()).with_context(|| format!("Failed to write '{}'", path.display()))
+}
+
+/// Signs the draft statement at `draft` and writes the signed document to `signed`. Returns the
+/// sha256 of the signed statement, which links to it are pinned with.
+pub(crate) async fn sign_draft(signer: &dyn StatementSigner, draft: &Path, signed: &Path) -> Result<String> {
+    let text = std::fs::read_to_string(draft).with_context(|| format!("Failed to read '{}'", draft.display()))?;
+    let statement: serde_json::Value =
+        serde_json::from_str(&text).with_context(|| format!("'{}' is not a JSON statement", draft.display()))?;
+    let payload = serde_json::to_vec(&statement).context("Failed to serialize statement")?;
+    let document = signer.sign(&payload).await.with_context(|| format!("Failed to sign '{}'", draft.display()))?;
+    if let Some(parent) = signed.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("Failed to create directory '{}'", parent.display()))?;
+    }
+    std::fs::write(signed, format!("{}\n", document)).with_context(|| format!("Failed to write '{}'", signed.display()))?;
+    Ok(hex::encode(Sha256::digest(&payload)))
+}
+
+#[cfg(test)]
+mod tests {
+    use chainsights_core::did::{DidVerifier, did_key};
+    use chainsights_core::verifier::Verifier;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn private_keys_are_encrypted_with_their_passphrase() {
+        let encrypted = Ed25519Key::generate().to_pem(Some("correct horse")).unwrap();
+        assert!(encrypted.contains("ENCRYPTED PRIVATE KEY"));
+        assert!(Ed25519Key::from_pem(&encrypted, Some("wrong")).is_err());
+        assert!(Ed25519Key::from_pem(&encrypted, Some("correct horse")).is_ok());
+    }
+
+    #[tokio::test]
+    async fn signed_drafts_verify_against_the_key_did() {
+        let dir = TempDir::new().unwrap();
+        let signer = DidKeySigner::new(Box::new(Ed25519Key::generate())).await.unwrap();
+        let draft = dir.path().join("catalog.json");
+        let bundle = dir.path().join("site/catalog.jsonl");
+        std::fs::write(&draft, "{\n  \"_type\": \"https://in-toto.io/Statement/v1\"\n}\n").unwrap();
+        let digest = sign_draft(&signer, &draft, &bundle).await.unwrap();
+        let signed = std::fs::read_to_string(&bundle).unwrap();
+        let verified = DidVerifier::new(vec![did_key(&signer.identity()).unwrap()]).verify(signed.trim(), None).unwrap();
+        assert_eq!(verified.payload, br#"{"_type":"https://in-toto.io/Statement/v1"}"#);
+        assert_eq!(digest, hex::encode(Sha256::digest(&verified.payload)));
+    }
+}
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! JSON files in which the CLI keeps what it has seen across runs, such as root pins, catalog
+//! serials and supplier history.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// `name` in `$XDG_STATE_HOME/chainsights`, or `~/.local/state/chainsights`. `flag` is the option
+/// that gives the file explicitly, for the error when HOME is not set.
+pub(crate) fn state_file(name: &str, flag: &str) -> Result<PathBuf> {
+    let state = match std::env::var_os("XDG_STATE_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME").with_context(|| format!("HOME is not set; give the state file with {}", flag))?)
+            .join(".local")
+            .join("state"),
+    };
+    Ok(state.join("chainsights").join(name))
+}
+
+/// A state file holding one JSON value. A missing file reads as the value's default, and writes
+/// replace the file atomically, so that a crash or a concurrent reader never sees it half written.
+#[derive(Clone, Debug)]
+pub(crate) struct JsonStateFile {
+    path: PathBuf,
+    /// What the file holds, for errors, e.g. "root pin"
+    what: &'static str,
+}
+
+impl JsonStateFile {
+    pub(crate) fn new(path: PathBuf, what: &'static str) -> Self {
+        Self { path, what }
+    }
+
+    pub(crate) fn load<T: DeserializeOwned + Default>(&self) -> Result<T> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(text) => serde_json::from_str(&text).with_context(|| format!("Invalid {} file '{}'", self.what, self.path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read '{}'", self.path.display())),
+        }
+    }
+
+    /// Writes `value` to a temporary file next to the state file, then renames it over the state
+    /// file.
+    pub(crate) fn save<T: Serialize>(&self, value: &T) -> Result<()> {
+        let dir = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create '{}'", dir.display()))?;
+        let json = serde_json::to_string_pretty(value).with_context(|| format!("Failed to serialize {} state", self.what))?;
+        let name = self.path.file_name().with_context(|| format!("'{}' is not a file", self.path.display()))?;
+        let temporary = dir.join(format!(".{}.{}.tmp", name.to_string_lossy(), std::process::id()));
+        let written = std::fs::File::create(&temporary)
+            .and_then(|mut file| {
+                file.write_all(format!("{}\n", json).as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|()| std::fs::rename(&temporary, &self.path));
+        if written.is_err() {
+            let _ = std::fs::remove_file(&temporary);
+        }
+        written.with_context(|| format!("Failed to write '{}'", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn missing_files_read_as_empty() {
+        let dir = TempDir::new().unwrap();
+        let file = JsonStateFile::new(dir.path().join("state.json"), "test");
+        assert_eq!(file.load::<BTreeMap<String, u64>>().unwrap(), BTreeMap::new());
+    }
+
+    #[test]
+    fn saves_replace_the_file_without_leaving_temporary_files() {
+        let dir = TempDir::new().unwrap();
+        let file = JsonStateFile::new(dir.path().join("nested/state.json"), "test");
+        file.save(&BTreeMap::from([("example.com", 1)])).unwrap();
+        file.save(&BTreeMap::from([("example.com", 2)])).unwrap();
+        assert_eq!(file.load::<BTreeMap<String, u64>>().unwrap(), BTreeMap::from([("example.com".to_string(), 2)]));
+        let entries: Vec<_> = std::fs::read_dir(dir.path().join("nested")).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        assert_eq!(entries, ["state.json"]);
+    }
+
+    #[test]
+    fn invalid_files_are_errors() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("state.json"), "{").unwrap();
+        let file = JsonStateFile::new(dir.path().join("state.json"), "test");
+        let error = file.load::<BTreeMap<String, u64>>().unwrap_err();
+        assert!(error.to_string().contains("Invalid test file"), "{}", error);
+    }
+}
@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Crawl statistics: requests, bytes, cache hits and latency per host, and verification timing,
+//! so that operators can see what a traversal costs and publishers how heavy their catalog is.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::models::aggregation::{CrawlStats, HostStats, VerificationStats};
+
+#[derive(Default)]
+struct HostCounters {
+    requests: u64,
+    failed_requests: u64,
+    bytes: u64,
+    cache_hits: u64,
+    latency: Duration,
+}
+
+#[derive(Default)]
+struct Counters {
+    hosts: HashMap<String, HostCounters>,
+    verifications: u64,
+    verification_time: Duration,
+    slowest_verification: Duration,
+}
+
+/// Collects the statistics of a run's traversals; shared by its fetchers and traversal options.
+pub(crate) struct StatsRecorder {
+    started: Instant,
+    counters: Mutex<Counters>,
+}
+
+impl StatsRecorder {
+    #[cfg(any(feature = "cli", test))]
+    pub(crate) fn new() -> Self {
+        Self { started: Instant::now(), counters: Mutex::new(Counters::default()) }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Counters> {
+        self.counters.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Records a request for `uri` that took `latency` and received `bytes`, or failed.
+    pub(crate) fn record_request(&self, uri: &str, latency: Duration, bytes: Option<usize>) {
+        let mut counters = self.lock();
+        let host = counters.hosts.entry(host_of(uri)).or_default();
+        host.requests += 1;
+        host.latency += latency;
+        match bytes {
+            Some(bytes) => host.bytes += bytes as u64,
+            None => host.failed_requests += 1,
+        }
+    }
+
+    /// Records that `uri` was answered from a cache.
+    pub(crate) fn record_cache_hit(&self, uri: &str) {
+        self.lock().hosts.entry(host_of(uri)).or_default().cache_hits += 1;
+    }
+
+    /// Records the verification of one statement, successful or not.
+    pub(crate) fn record_verification(&self, elapsed: Duration) {
+        let mut counters = self.lock();
+        counters.verifications += 1;
+        counters.verification_time += elapsed;
+        counters.slowest_verification = counters.slowest_verification.max(elapsed);
+    }
+
+    /// The statistics so far.
+    pub(crate) fn snapshot(&self) -> CrawlStats {
+        let counters = self.lock();
+        let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        let mut hosts: Vec<HostStats> = counters
+            .hosts
+            .iter()
+            .map(|(host, c)| HostStats {
+                host: host.clone(),
+                requests: c.requests,
+                failed_requests: c.failed_requests,
+                bytes: c.bytes,
+                cache_hits: c.cache_hits,
+                average_latency_ms: match c.requests {
+                    0 => 0.0,
+                    requests => millis(c.latency) / requests as f64,
+                },
+            })
+            .collect();
+        hosts.sort_by(|a, b| (b.requests + b.cache_hits).cmp(&(a.requests + a.cache_hits)).then_with(|| a.host.cmp(&b.host)));
+        CrawlStats {
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            hosts,
+            verification: VerificationStats {
+                statements: counters.verifications,
+                total_ms: millis(counters.verification_time),
+                average_ms: match counters.verifications {
+                    0 => 0.0,
+                    statements => millis(counters.verification_time) / statements as f64,
+                },
+                max_ms: millis(counters.slowest_verification),
+            },
+        }
+    }
+}
+
+/// The host of `uri`, or its scheme when it has none (e.g. `did:key` or a bare path).
+fn host_of(uri: &str) -> String {
+    match reqwest::Url::parse(uri) {
+        Ok(url) => url.host_str().map_or_else(|| url.scheme().to_string(), str::to_string),
+        Err(_) => "unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregates_requests_per_host() {
+        let stats = StatsRecorder::new();
+        stats.record_request("https://example.com/catalog.json", Duration::from_millis(30), Some(100));
+        stats.record_request("https://example.com/app.json", Duration::from_millis(10), Some(50));
+        stats.record_request("https://example.com/missing.json", Duration::from_millis(20), None);
+        stats.record_cache_hit("https://example.com/catalog.json");
+        stats.record_request("https://cdn.example.net/sbom.json", Duration::from_millis(5), Some(1000));
+        stats.record_verification(Duration::from_millis(4));
+        stats.record_verification(Duration::from_millis(2));
+
+        let snapshot = stats.snapshot();
+        let [example, cdn] = &snapshot.hosts[..] else {
+            panic!("expected two hosts: {:?}", snapshot.hosts);
+        };
+        assert_eq!((example.host.as_str(), example.requests, example.failed_requests), ("example.com", 3, 1));
+        assert_eq!((example.bytes, example.cache_hits), (150, 1));
+        assert!((example.average_latency_ms - 20.0).abs() < 0.01);
+        assert_eq!((cdn.host.as_str(), cdn.bytes), ("cdn.example.net", 1000));
+        assert_eq!(snapshot.verification.statements, 2);
+        assert!((snapshot.verification.average_ms - 3.0).abs() < 0.01);
+        assert!((snapshot.verification.max_ms - 4.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,284 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Software identification tags linked from releases: SWID tags (ISO/IEC 19770-2, XML) as
+//! `application/swid+xml` and CoSWID tags (RFC 9393, CBOR) as `application/swid+cbor`, for asset
+//! inventories that identify software by tag ID. A CoSWID tag may be wrapped in a COSE_Sign1
+//! envelope, whose signature is not checked; the tag is trusted through the digest of its link.
+
+use anyhow::{Context, Result, anyhow, bail};
+use ciborium::Value as Cbor;
+use quick_xml::XmlVersion;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+
+use crate::fetch::{Fetchers, verify_digests};
+use crate::models::aggregation::{AggregatedCatalogData, AggregatedComponentData, SwidTag};
+use crate::models::chainsights::ArtifactLink;
+use crate::output::{self, Status};
+
+pub(crate) const SWID_MEDIA_TYPE: &str = "application/swid+xml";
+pub(crate) const COSWID_MEDIA_TYPE: &str = "application/swid+cbor";
+
+/// The CBOR tag a CoSWID tag may be wrapped in (RFC 9393, section 8).
+const COSWID_CBOR_TAG: u64 = 1398229316;
+/// The CBOR tag of a COSE_Sign1 envelope.
+const COSE_SIGN1_CBOR_TAG: u64 = 18;
+
+// CoSWID map keys (RFC 9393, section 6.1).
+const TAG_ID: i128 = 0;
+const SOFTWARE_NAME: i128 = 1;
+const ENTITY: i128 = 2;
+const CORPUS: i128 = 8;
+const PATCH: i128 = 9;
+const SUPPLEMENTAL: i128 = 11;
+const SOFTWARE_VERSION: i128 = 13;
+const REG_ID: i128 = 32;
+const ROLE: i128 = 33;
+/// The `tag-creator` entity role.
+const TAG_CREATOR_ROLE: i128 = 1;
+
+/// Parses a SWID tag's `SoftwareIdentity` element.
+fn parse_swid(xml: &str) -> Result<SwidTag> {
+    let mut reader = Reader::from_str(xml);
+    let mut tag: Option<SwidTag> = None;
+    loop {
+        match reader.read_event().context("The SWID tag is not valid XML")? {
+            Event::Start(element) | Event::Empty(element) => match element.local_name().as_ref() {
+                "SoftwareIdentity" if tag.is_none() => {
+                    let tag_type = ["corpus", "patch", "supplemental"]
+                        .into_iter()
+                        .find(|kind| attribute(&element, kind).is_ok_and(|value| value.as_deref() == Some("true")))
+                        .unwrap_or("primary");
+                    tag = Some(SwidTag {
+                        format: "swid".to_string(),
+                        tag_id: attribute(&element, "tagId")?.context("The SWID tag has no tagId")?,
+                        name: attribute(&element, "name")?.context("The SWID tag has no name")?,
+                        version: attribute(&element, "version")?,
+                        tag_type: tag_type.to_string(),
+                        ..Default::default()
+                    });
+                }
+                "Entity" => {
+                    if let Some(tag) = tag.as_mut()
+                        && tag.tag_creator.is_none()
+                        && attribute(&element, "role")?.is_some_and(|roles| roles.split_whitespace().any(|role| role == "tagCreator"))
+                    {
+                        tag.tag_creator = attribute(&element, "regid")?;
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    tag.context("The document has no SoftwareIdentity element")
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>> {
+    let Some(attribute) = element.try_get_attribute(name).context("Invalid XML attribute")? else {
+        return Ok(None);
+    };
+    Ok(Some(attribute.normalized_value(XmlVersion::Implicit1_0).context("Invalid XML attribute")?.into_owned()))
+}
+
+/// Parses a CoSWID tag, unwrapping it from its CBOR tag and any COSE_Sign1 envelope.
+fn parse_coswid(bytes: &[u8]) -> Result<SwidTag> {
+    let mut value: Cbor = ciborium::from_reader(bytes).map_err(|e| anyhow!("The CoSWID tag is not valid CBOR: {}", e))?;
+    loop {
+        value = match value {
+            Cbor::Tag(COSWID_CBOR_TAG, inner) => *inner,
+            Cbor::Tag(COSE_SIGN1_CBOR_TAG, inner) => match *inner {
+                Cbor::Array(mut parts) if parts.len() == 4 => match parts.swap_remove(2) {
+                    Cbor::Bytes(payload) => ciborium::from_reader(payload.as_slice())
+                        .map_err(|e| anyhow!("The signed CoSWID payload is not valid CBOR: {}", e))?,
+                    _ => bail!("The COSE_Sign1 envelope has no embedded payload"),
+                },
+                _ => bail!("Invalid COSE_Sign1 envelope"),
+            },
+            Cbor::Map(entries) => return coswid_tag(&entries),
+            _ => bail!("The CoSWID tag is not a CBOR map"),
+        };
+    }
+}
+
+fn coswid_tag(entries: &[(Cbor, Cbor)]) -> Result<SwidTag> {
+    let get = |key: i128| map_get(entries, key);
+    let tag_id = match get(TAG_ID) {
+        Some(Cbor::Text(id)) => id.clone(),
+        Some(Cbor::Bytes(uuid)) if uuid.len() == 16 => {
+            let hex = hex::encode(uuid);
+            format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+        }
+        _ => bail!("The CoSWID tag has no tag-id"),
+    };
+    let Some(Cbor::Text(name)) = get(SOFTWARE_NAME) else {
+        bail!("The CoSWID tag has no software-name");
+    };
+    let tag_type = [(CORPUS, "corpus"), (PATCH, "patch"), (SUPPLEMENTAL, "supplemental")]
+        .into_iter()
+        .find(|(key, _)| matches!(get(*key), Some(Cbor::Bool(true))))
+        .map_or("primary", |(_, kind)| kind);
+    let entities = match get(ENTITY) {
+        Some(Cbor::Array(entities)) => entities.iter().collect(),
+        Some(entity) => vec![entity],
+        None => Vec::new(),
+    };
+    let tag_creator = entities.into_iter().find_map(|entity| {
+        let Cbor::Map(entity) = entity else { return None };
+        let is_creator = match map_get(entity, ROLE)? {
+            Cbor::Array(roles) => roles.iter().any(is_tag_creator_role),
+            role => is_tag_creator_role(role),
+        };
+        match map_get(entity, REG_ID) {
+            Some(Cbor::Text(reg_id)) if is_creator => Some(reg_id.clone()),
+            _ => None,
+        }
+    });
+    Ok(SwidTag {
+        format: "coswid".to_string(),
+        tag_id,
+        name: name.clone(),
+        version: match get(SOFTWARE_VERSION) {
+            Some(Cbor::Text(version)) => Some(version.clone()),
+            _ => None,
+        },
+        tag_type: tag_type.to_string(),
+        tag_creator,
+        ..Default::default()
+    })
+}
+
+fn map_get(entries: &[(Cbor, Cbor)], key: i128) -> Option<&Cbor> {
+    entries
+        .iter()
+        .find(|(k, _)| matches!(k, Cbor::Integer(k) if i128::from(*k) == key))
+        .map(|(_, value)| value)
+}
+
+fn is_tag_creator_role(role: &Cbor) -> bool {
+    match role {
+        Cbor::Integer(role) => i128::from(*role) == TAG_CREATOR_ROLE,
+        Cbor::Text(role) => role == "tag-creator",
+        _ => false,
+    }
+}
+
+/// Fetches a linked tag, checks it against the link's digests, and parses it as the format its
+/// media type names.
+async fn read_tag(link: &ArtifactLink, fetchers: &Fetchers) -> Result<SwidTag> {
+    let bytes = fetchers.fetch(&link.uri).await.with_context(|| format!("Failed to fetch SWID tag '{}'", link.uri))?;
+    let digest_verified = match &link.digest {
+        Some(digest) => verify_digests(&link.uri, &bytes, digest)?,
+        None => false,
+    };
+    let mut tag = match link.media_type.as_deref() {
+        Some(SWID_MEDIA_TYPE) => parse_swid(std::str::from_utf8(&bytes).context("The SWID tag is not UTF-8")?)?,
+        _ => parse_coswid(&bytes)?,
+    };
+    tag.uri = link.uri.clone();
+    tag.digest_verified = digest_verified;
+    Ok(tag)
+}
+
+/// Reads the SWID and CoSWID tags linked from every release of a catalog.
+pub(crate) async fn collect_swid_tags(aggregated_data: &mut AggregatedCatalogData, fetchers: &Fetchers) {
+    for component in &mut aggregated_data.components {
+        collect_component_swid_tags(component, fetchers).await;
+    }
+}
+
+/// Reads the SWID and CoSWID tags linked from each release of a component into the release.
+/// Tags that cannot be fetched, fail their digest check, or cannot be parsed are recorded as
+/// enrichment errors.
+pub(crate) async fn collect_component_swid_tags(component: &mut AggregatedComponentData, fetchers: &Fetchers) {
+    for release in &mut component.releases {
+        let links: Vec<ArtifactLink> = release
+            .metadata_artifacts
+            .iter()
+            .filter(|link| matches!(link.media_type.as_deref(), Some(SWID_MEDIA_TYPE | COSWID_MEDIA_TYPE)) && !link.is_gated())
+            .cloned()
+            .collect();
+        for link in links {
+            match read_tag(&link, fetchers).await {
+                Ok(tag) => {
+                    if !tag.digest_verified {
+                        output::eprint(1, Status::Warning, &format!("No digest to check SWID tag '{}' against", link.uri));
+                    }
+                    output::print(1, Status::Info, &format!("SWID tag {} ({}) from {}", tag.tag_id, tag.name, link.uri));
+                    release.swid_tags.push(tag);
+                }
+                Err(e) => release.enrichment_errors.push((link.uri, format!("{:#}", e))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use sha2::{Digest, Sha256};
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn reads_swid_and_signed_coswid_tags() {
+        let swid = r#"<?xml version="1.0" encoding="utf-8"?>
+<SoftwareIdentity xmlns="http://standards.iso.org/iso/19770/-2/2015/schema.xsd"
+    name="Example App" tagId="example.com-app-1.2.0" version="1.2.0" patch="false">
+  <Entity name="Example" regid="example.com" role="tagCreator softwareCreator"/>
+</SoftwareIdentity>"#;
+        let coswid = Cbor::Map(vec![
+            (Cbor::from(TAG_ID as i64), Cbor::Bytes((0u8..16).collect())),
+            (Cbor::from(SOFTWARE_NAME as i64), Cbor::from("Example App")),
+            (Cbor::from(SOFTWARE_VERSION as i64), Cbor::from("1.2.0")),
+            (Cbor::from(SUPPLEMENTAL as i64), Cbor::Bool(true)),
+            (
+                Cbor::from(ENTITY as i64),
+                Cbor::Map(vec![
+                    (Cbor::from(REG_ID as i64), Cbor::from("example.com")),
+                    (Cbor::from(ROLE as i64), Cbor::Array(vec![Cbor::from(1), Cbor::from(2)])),
+                ]),
+            ),
+        ]);
+        let mut payload = Vec::new();
+        ciborium::into_writer(&Cbor::Tag(COSWID_CBOR_TAG, Box::new(coswid)), &mut payload).unwrap();
+        let envelope = Cbor::Tag(
+            COSE_SIGN1_CBOR_TAG,
+            Box::new(Cbor::Array(vec![Cbor::Bytes(Vec::new()), Cbor::Map(Vec::new()), Cbor::Bytes(payload), Cbor::Bytes(vec![0; 64])])),
+        );
+        let mut signed = Vec::new();
+        ciborium::into_writer(&envelope, &mut signed).unwrap();
+
+        let server = MockServer::start().await;
+        Mock::given(path("/app.swidtag")).respond_with(ResponseTemplate::new(200).set_body_string(swid)).mount(&server).await;
+        Mock::given(path("/app.coswid")).respond_with(ResponseTemplate::new(200).set_body_bytes(signed.clone())).mount(&server).await;
+        let link = |name: &str, media_type: &str, digest: &[u8]| ArtifactLink {
+            uri: format!("{}/{}", server.uri(), name),
+            digest: Some(HashMap::from([("sha256".to_string(), hex::encode(Sha256::digest(digest)))])),
+            media_type: Some(media_type.to_string()),
+            expected_signer_identity: None,
+            access: None,
+            request_url: None,
+        };
+        let fetchers = Fetchers::new(reqwest::Client::new());
+
+        let tag = read_tag(&link("app.swidtag", SWID_MEDIA_TYPE, swid.as_bytes()), &fetchers).await.unwrap();
+        assert_eq!(tag.tag_id, "example.com-app-1.2.0");
+        assert_eq!(tag.version.as_deref(), Some("1.2.0"));
+        assert_eq!(tag.tag_type, "primary");
+        assert_eq!(tag.tag_creator.as_deref(), Some("example.com"));
+        assert!(tag.digest_verified);
+
+        let tag = read_tag(&link("app.coswid", COSWID_MEDIA_TYPE, &signed), &fetchers).await.unwrap();
+        assert_eq!(tag.format, "coswid");
+        assert_eq!(tag.tag_id, "00010203-0405-0607-0809-0a0b0c0d0e0f");
+        assert_eq!(tag.tag_type, "supplemental");
+        assert_eq!(tag.tag_creator.as_deref(), Some("example.com"));
+
+        assert!(read_tag(&link("app.coswid", COSWID_MEDIA_TYPE, b"other"), &fetchers).await.is_err());
+    }
+}
@@ -0,0 +1,406 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow};
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+
+use crate::discovery::Discovery;
+use crate::purl::strip_purl_version;
+use crate::fetch::Fetchers;
+use crate::output::{self, Status};
+use crate::models::aggregation::{
+    AggregatedCatalogData, AggregatedComponentData, AggregatedReleaseData,
+};
+use crate::models::chainsights::{
+    ArtifactLink, AttestationLink, CatalogComponentEntry, ChainsightsCatalogPredicate,
+    ChainsightsComponentPredicate, ChainsightsReleasePredicate,
+};
+
+/// Major.minor of the Transparency Exchange API versions this client understands.
+const SUPPORTED_TEA_VERSION_PREFIX: &str = "0.1";
+/// Page size requested from paginated TEA endpoints.
+const PAGE_SIZE: usize = 100;
+
+/// The `.well-known/tea` discovery document.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TeaWellKnown {
+    endpoints: Vec<TeaEndpoint>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TeaEndpoint {
+    url: String,
+    versions: Vec<String>,
+    /// Preference between endpoints, from 0 to 1. Higher is preferred.
+    priority: Option<f64>,
+}
+
+/// One page of a paginated TEA response.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TeaPage<T> {
+    timestamp: Option<String>,
+    total_results: usize,
+    results: Vec<T>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct TeaIdentifier {
+    id_type: String,
+    id_value: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TeaProduct {
+    name: String,
+    #[serde(default)]
+    components: Vec<TeaComponentRef>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TeaComponentRef {
+    uuid: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TeaComponent {
+    name: String,
+    #[serde(default)]
+    identifiers: Vec<TeaIdentifier>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TeaRelease {
+    uuid: String,
+    version: String,
+    release_date: Option<String>,
+    #[serde(default)]
+    pre_release: bool,
+    #[serde(default)]
+    identifiers: Vec<TeaIdentifier>,
+}
+
+/// The set of artifacts published for a release at a point in time.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TeaCollection {
+    date: Option<String>,
+    #[serde(default)]
+    artifacts: Vec<TeaArtifact>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TeaArtifact {
+    #[serde(default)]
+    formats: Vec<TeaArtifactFormat>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TeaArtifactFormat {
+    media_type: Option<String>,
+    url: String,
+    #[serde(default)]
+    checksums: Vec<TeaChecksum>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TeaChecksum {
+    alg_type: String,
+    alg_value: String,
+}
+
+/// Discovers products published through the CycloneDX Transparency Exchange API (TEA).
+///
+/// The API is located through `https://<domain>/.well-known/tea`. Every product's components,
+/// their releases, and each release's latest artifact collection are mapped onto the same
+/// catalog/component/release shape produced by Chainsights traversal. TEA documents are not
+/// signed statements, so they are only as trustworthy as the TLS connection they came over.
+pub(crate) struct TeaDiscovery;
+
+impl Discovery for TeaDiscovery {
+    fn discover<'a>(
+        &'a self,
+        domain: &'a str,
+        fetchers: &'a Fetchers,
+    ) -> BoxFuture<'a, Result<AggregatedCatalogData>> {
+        Box::pin(async move {
+            let api_url = discover_api_url(domain, fetchers).await?;
+            output::print(0, Status::Info, &format!("Traversing TEA API: {}", api_url));
+            output::print(1, Status::Warning, "TEA responses are not signed; trust is limited to TLS");
+            let client = TeaClient { api_url, fetchers };
+            client.aggregate(domain).await
+        })
+    }
+}
+
+/// Reads the `.well-known/tea` document and returns the versioned base URL of the preferred endpoint.
+async fn discover_api_url(domain: &str, fetchers: &Fetchers) -> Result<String> {
+    let well_known_url = format!("https://{}/.well-known/tea", domain);
+    output::print(1, Status::Info, &format!("Fetching TEA discovery document: {}", well_known_url));
+    let mut well_known: TeaWellKnown = fetch_json(fetchers, &well_known_url)
+        .await
+        .with_context(|| format!("Failed to discover a TEA endpoint for domain '{}'", domain))?;
+
+    well_known
+        .endpoints
+        .sort_by(|a, b| b.priority.unwrap_or(1.0).total_cmp(&a.priority.unwrap_or(1.0)));
+    well_known
+        .endpoints
+        .iter()
+        .find_map(|endpoint| {
+            endpoint
+                .versions
+                .iter()
+                .find(|v| v.starts_with(SUPPORTED_TEA_VERSION_PREFIX))
+                .map(|version| format!("{}/v{}", endpoint.url.trim_end_matches('/'), version))
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "No TEA endpoint for domain '{}' supports API version {}.x",
+                domain,
+                SUPPORTED_TEA_VERSION_PREFIX
+            )
+        })
+}
+
+struct TeaClient<'a> {
+    api_url: String,
+    fetchers: &'a Fetchers,
+}
+
+impl TeaClient<'_> {
+    async fn aggregate(&self, domain: &str) -> Result<AggregatedCatalogData> {
+        let mut aggregated_data = AggregatedCatalogData::default();
+        let (timestamp, products) = match self.products().await {
+            Ok(products) => products,
+            Err(e) => {
+                aggregated_data.root_error =
+                    Some(format!("Failed to list products from '{}': {:#}", self.api_url, e));
+                return Ok(aggregated_data);
+            }
+        };
+
+        let mut entries = Vec::new();
+        for product in &products {
+            for component_ref in &product.components {
+                let component_uri = format!("{}/component/{}", self.api_url, component_ref.uuid);
+                let component: TeaComponent = match fetch_json(self.fetchers, &component_uri).await {
+                    Ok(component) => component,
+                    Err(e) => {
+                        aggregated_data
+                            .component_errors
+                            .push((component_uri, format!("{:#}", e)));
+                        continue;
+                    }
+                };
+                let component_purl = identifier(&component.identifiers, "PURL")
+                    .map(|purl| strip_purl_version(&purl))
+                    .unwrap_or_else(|| format!("pkg:generic/{}/{}", domain, component_ref.uuid));
+                let labels = HashMap::from([("tea/product".to_string(), product.name.clone())]);
+
+                entries.push(CatalogComponentEntry {
+                    name: component.name.clone(),
+                    description: None,
+                    component_purl: component_purl.clone(),
+                    component_attestation_link: AttestationLink {
+                        uri: component_uri.clone(),
+                        digest: None,
+                        media_type: Some("application/json".to_string()),
+                        expected_signer_identity: String::new(),
+                        accepted_signer_identities: None,
+                        expected_pgp_fingerprints: None,
+                        expected_public_keys: None,
+                    },
+                    labels: Some(labels.clone()),
+                });
+                aggregated_data.components.push(
+                    self.component_data(&component_ref.uuid, component, component_purl, labels, &timestamp)
+                        .await,
+                );
+            }
+        }
+
+        aggregated_data.catalog_predicate = Some(Arc::new(ChainsightsCatalogPredicate {
+            generator: None,
+            timestamp,
+            expires: None,
+            serial: None,
+            components: entries,
+            sub_catalogs: None,
+            metadata_links: None,
+            revocation_link: None,
+            freshness_link: None,
+            security_contact: None,
+            next_page: None,
+            component_shards: None,
+        }));
+        Ok(aggregated_data)
+    }
+
+    /// Lists every product, following pagination. Returns the server timestamp of the first page.
+    async fn products(&self) -> Result<(String, Vec<TeaProduct>)> {
+        let mut products = Vec::new();
+        let mut timestamp = None;
+        loop {
+            let url = format!(
+                "{}/products?pageOffset={}&pageSize={}",
+                self.api_url,
+                products.len(),
+                PAGE_SIZE
+            );
+            let page: TeaPage<TeaProduct> = fetch_json(self.fetchers, &url).await?;
+            timestamp = timestamp.or(page.timestamp);
+            let received = page.results.len();
+            products.extend(page.results);
+            if received == 0 || products.len() >= page.total_results {
+                break;
+            }
+        }
+        Ok((timestamp.unwrap_or_default(), products))
+    }
+
+    async fn component_data(
+        &self,
+        uuid: &str,
+        component: TeaComponent,
+        component_purl: String,
+        labels: HashMap<String, String>,
+        timestamp: &str,
+    ) -> AggregatedComponentData {
+        let component_uri = format!("{}/component/{}", self.api_url, uuid);
+        let releases_uri = format!("{}/releases", component_uri);
+        let mut agg_comp_data = AggregatedComponentData {
+            component_link_uri: component_uri,
+            ..Default::default()
+        };
+
+        let releases: Vec<TeaRelease> = match fetch_json(self.fetchers, &releases_uri).await {
+            Ok(releases) => releases,
+            Err(e) => {
+                agg_comp_data.release_errors.push((releases_uri, format!("{:#}", e)));
+                Vec::new()
+            }
+        };
+
+        let mut release_attestations = Vec::new();
+        for release in releases {
+            let collection_uri =
+                format!("{}/componentRelease/{}/collection/latest", self.api_url, release.uuid);
+            release_attestations.push(AttestationLink {
+                uri: collection_uri.clone(),
+                digest: None,
+                media_type: Some("application/json".to_string()),
+                expected_signer_identity: String::new(),
+                accepted_signer_identities: None,
+                expected_pgp_fingerprints: None,
+                expected_public_keys: None,
+            });
+
+            let collection: TeaCollection = match fetch_json(self.fetchers, &collection_uri).await {
+                Ok(collection) => collection,
+                Err(e) => {
+                    agg_comp_data.release_errors.push((collection_uri, format!("{:#}", e)));
+                    continue;
+                }
+            };
+            let metadata_links: Vec<ArtifactLink> = collection
+                .artifacts
+                .iter()
+                .flat_map(|artifact| &artifact.formats)
+                .map(format_link)
+                .collect();
+            let release_predicate = ChainsightsReleasePredicate {
+                generator: None,
+                timestamp: collection.date.unwrap_or_else(|| timestamp.to_string()),
+                expires: None,
+                purl: identifier(&release.identifiers, "PURL")
+                    .unwrap_or_else(|| format!("{}@{}", component_purl, release.version)),
+                name: release.version.clone(),
+                cpe: identifier(&release.identifiers, "CPE"),
+                release_date: release.release_date,
+                release_notes_uri: None,
+                lifecycle_phase: release.pre_release.then(|| "beta".to_string()),
+                status: None,
+                license: None,
+                supplier: None,
+                metadata_links: Some(metadata_links.clone()),
+                artifacts: None,
+                artifact_groups: None,
+            };
+            agg_comp_data.releases.push(AggregatedReleaseData {
+                release_predicate: Some(Arc::new(release_predicate)),
+                metadata_artifacts: metadata_links,
+                release_link_uri: collection_uri,
+                ..Default::default()
+            });
+        }
+
+        agg_comp_data.component_predicate = Some(Arc::new(ChainsightsComponentPredicate {
+            generator: None,
+            timestamp: timestamp.to_string(),
+            expires: None,
+            purl: component_purl,
+            name: component.name,
+            description: None,
+            aliases: None,
+            alias_purls: None,
+            cpe: identifier(&component.identifiers, "CPE"),
+            labels: Some(labels),
+            security_contact: None,
+            license: None,
+            supplier: None,
+            repositories: Vec::new(),
+            sub_components: None,
+            relationships: None,
+            release_attestations,
+            metadata_links: None,
+            support_streams: None,
+            service: None,
+        }));
+        agg_comp_data
+    }
+}
+
+/// Converts a TEA artifact format into a link, mapping TEA checksum names (e.g., "SHA-256")
+/// onto the lowercase digest names used in Chainsights links (e.g., "sha256").
+fn format_link(format: &TeaArtifactFormat) -> ArtifactLink {
+    let digest: HashMap<String, String> = format
+        .checksums
+        .iter()
+        .map(|c| (c.alg_type.to_ascii_lowercase().replace('-', ""), c.alg_value.to_ascii_lowercase()))
+        .collect();
+    ArtifactLink {
+        uri: format.url.clone(),
+        digest: (!digest.is_empty()).then_some(digest),
+        media_type: format.media_type.clone(),
+        expected_signer_identity: None,
+        access: None,
+        request_url: None,
+    }
+}
+
+fn identifier(identifiers: &[TeaIdentifier], id_type: &str) -> Option<String> {
+    identifiers
+        .iter()
+        .find(|i| i.id_type.eq_ignore_ascii_case(id_type))
+        .map(|i| i.id_value.clone())
+}
+
+async fn fetch_json<T: DeserializeOwned>(fetchers: &Fetchers, url: &str) -> Result<T> {
+    let bytes = fetchers.fetch(url).await?;
+    serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse TEA response from '{}'", url))
+}
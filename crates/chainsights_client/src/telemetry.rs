@@ -0,0 +1,307 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! OpenTelemetry traces of traversal runs, exported as OTLP over HTTP with JSON encoding so that
+//! long crawls can be analyzed for per-host latency in existing observability stacks.
+//!
+//! A [`Tracer`] records one trace at a time, rooted at a span covering the whole run. Spans
+//! started while another span's future is being polled become its children, so the fetches and
+//! verification of an attestation nest under it even when attestations are processed concurrently.
+
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+
+tokio::task_local! {
+    /// The span whose future is being polled.
+    static CURRENT_SPAN: SpanId;
+}
+
+type SpanId = [u8; 8];
+
+/// OTLP span kinds.
+const SPAN_KIND_INTERNAL: u8 = 1;
+const SPAN_KIND_CLIENT: u8 = 3;
+
+/// OTLP status codes.
+const STATUS_CODE_ERROR: u8 = 2;
+
+/// The value of a span attribute.
+#[derive(Clone, Debug)]
+pub(crate) enum AttributeValue {
+    String(String),
+    Int(i64),
+}
+
+impl From<&str> for AttributeValue {
+    fn from(value: &str) -> Self {
+        AttributeValue::String(value.to_string())
+    }
+}
+
+impl From<String> for AttributeValue {
+    fn from(value: String) -> Self {
+        AttributeValue::String(value)
+    }
+}
+
+impl From<usize> for AttributeValue {
+    fn from(value: usize) -> Self {
+        AttributeValue::Int(value as i64)
+    }
+}
+
+impl From<u32> for AttributeValue {
+    fn from(value: u32) -> Self {
+        AttributeValue::Int(i64::from(value))
+    }
+}
+
+pub(crate) type Attributes = Vec<(&'static str, AttributeValue)>;
+
+struct SpanData {
+    id: SpanId,
+    parent: SpanId,
+    name: &'static str,
+    kind: u8,
+    start: u64,
+    end: u64,
+    attributes: Attributes,
+    error: Option<String>,
+}
+
+struct Trace {
+    id: [u8; 16],
+    root: SpanId,
+    start: u64,
+    spans: Vec<SpanData>,
+}
+
+/// Records the spans of a run for export.
+pub(crate) struct Tracer {
+    root_name: &'static str,
+    ids: RandomState,
+    counter: AtomicU64,
+    trace: Mutex<Trace>,
+}
+
+impl Tracer {
+    /// Starts a trace whose root span, named `root_name`, lasts until the trace is exported.
+    pub(crate) fn new(root_name: &'static str) -> Self {
+        let tracer = Self {
+            root_name,
+            ids: RandomState::new(),
+            counter: AtomicU64::new(0),
+            trace: Mutex::new(Trace { id: [0; 16], root: [0; 8], start: 0, spans: Vec::new() }),
+        };
+        *tracer.lock() = tracer.new_trace();
+        tracer
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Trace> {
+        self.trace.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn random_u64(&self) -> u64 {
+        let mut hasher = self.ids.build_hasher();
+        hasher.write_u64(self.counter.fetch_add(1, Ordering::Relaxed));
+        hasher.write_u64(now());
+        hasher.finish()
+    }
+
+    fn new_trace(&self) -> Trace {
+        let mut id = [0; 16];
+        id[..8].copy_from_slice(&self.random_u64().to_be_bytes());
+        id[8..].copy_from_slice(&self.random_u64().to_be_bytes());
+        Trace { id, root: self.random_u64().to_be_bytes(), start: now(), spans: Vec::new() }
+    }
+
+    /// Runs `future` in a span named `name`, a child of the current span or of the root span.
+    /// `describe` adds attributes from a successful result; a failure sets the span's status.
+    pub(crate) async fn in_span<T, F>(
+        &self,
+        name: &'static str,
+        mut attributes: Attributes,
+        future: F,
+        describe: impl FnOnce(&T) -> Attributes,
+    ) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        let parent = CURRENT_SPAN.try_with(|id| *id).unwrap_or_else(|_| self.lock().root);
+        let id = self.random_u64().to_be_bytes();
+        let start = now();
+        let result = CURRENT_SPAN.scope(id, future).await;
+        let error = match &result {
+            Ok(value) => {
+                attributes.extend(describe(value));
+                None
+            }
+            Err(e) => Some(format!("{:#}", e)),
+        };
+        // Spans that name the server they talk to are requests to it; the rest happen in the client.
+        let kind = match attributes.iter().any(|(key, _)| *key == "server.address") {
+            true => SPAN_KIND_CLIENT,
+            false => SPAN_KIND_INTERNAL,
+        };
+        self.lock().spans.push(SpanData { id, parent, name, kind, start, end: now(), attributes, error });
+        result
+    }
+
+    /// Ends the current trace and returns it as an OTLP `ExportTraceServiceRequest` in JSON,
+    /// starting a new trace for the spans that follow.
+    pub(crate) fn finish(&self, root_attributes: Attributes) -> Value {
+        let trace = std::mem::replace(&mut *self.lock(), self.new_trace());
+        let root = SpanData {
+            id: trace.root,
+            parent: [0; 8],
+            name: self.root_name,
+            kind: SPAN_KIND_INTERNAL,
+            start: trace.start,
+            end: now(),
+            attributes: root_attributes,
+            error: None,
+        };
+        let spans: Vec<Value> = std::iter::once(root)
+            .chain(trace.spans)
+            .map(|span| {
+                let mut json = json!({
+                    "traceId": hex::encode(trace.id),
+                    "spanId": hex::encode(span.id),
+                    "name": span.name,
+                    "kind": span.kind,
+                    "startTimeUnixNano": span.start.to_string(),
+                    "endTimeUnixNano": span.end.to_string(),
+                    "attributes": attributes_json(&span.attributes),
+                });
+                if span.parent != [0; 8] {
+                    json["parentSpanId"] = json!(hex::encode(span.parent));
+                }
+                if let Some(error) = span.error {
+                    json["status"] = json!({ "code": STATUS_CODE_ERROR, "message": error });
+                }
+                json
+            })
+            .collect();
+        json!({
+            "resourceSpans": [{
+                "resource": { "attributes": attributes_json(&[("service.name", "chainsights_client".into())]) },
+                "scopeSpans": [{
+                    "scope": { "name": "chainsights_client", "version": env!("CARGO_PKG_VERSION") },
+                    "spans": spans,
+                }],
+            }],
+        })
+    }
+
+    /// Ends the current trace and sends it to the OTLP/HTTP collector at `endpoint`, the base URL
+    /// to which `/v1/traces` is appended as with `OTEL_EXPORTER_OTLP_ENDPOINT`.
+    pub(crate) async fn export(&self, client: &reqwest::Client, endpoint: &str, root_attributes: Attributes) -> Result<()> {
+        let url = format!("{}/v1/traces", endpoint.trim_end_matches('/'));
+        client
+            .post(&url)
+            .json(&self.finish(root_attributes))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Failed to export the trace to {}", url))?;
+        Ok(())
+    }
+}
+
+/// Runs `future` in a span of `tracer` if there is one; see [`Tracer::in_span`].
+pub(crate) async fn traced<T, F>(
+    tracer: Option<&Tracer>,
+    name: &'static str,
+    attributes: impl FnOnce() -> Attributes,
+    future: F,
+    describe: impl FnOnce(&T) -> Attributes,
+) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    match tracer {
+        Some(tracer) => tracer.in_span(name, attributes(), future, describe).await,
+        None => future.await,
+    }
+}
+
+fn attributes_json(attributes: &[(&'static str, AttributeValue)]) -> Value {
+    attributes
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                AttributeValue::String(value) => json!({ "stringValue": value }),
+                // OTLP/JSON encodes 64-bit integers as strings.
+                AttributeValue::Int(value) => json!({ "intValue": value.to_string() }),
+            };
+            json!({ "key": key, "value": value })
+        })
+        .collect()
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn nests_concurrent_spans_and_exports_them() {
+        let tracer = Tracer::new("traversal");
+        let attestation = |uri: &'static str| {
+            tracer.in_span(
+                "attestation",
+                vec![("chainsights.uri", uri.into())],
+                async {
+                    tokio::task::yield_now().await;
+                    let fetched = tracer.in_span("fetch", vec![("url.full", uri.into()), ("server.address", "example.com".into())], async { Ok(vec![0u8; 3]) }, |bytes| {
+                        vec![("http.response.body.size", bytes.len().into())]
+                    });
+                    fetched.await?;
+                    anyhow::ensure!(!uri.ends_with("bad"), "bad signature");
+                    Ok(())
+                },
+                |_| Vec::new(),
+            )
+        };
+        let (good, bad) = futures::join!(attestation("https://example.com/good"), attestation("https://example.com/bad"));
+        assert!(good.is_ok() && bad.is_err());
+        assert!(traced(None, "fetch", Vec::new, async { Ok(()) }, |_| Vec::new()).await.is_ok());
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST")).and(path("/v1/traces")).respond_with(ResponseTemplate::new(200)).expect(1).mount(&server).await;
+        tracer.export(&reqwest::Client::new(), &format!("{}/", server.uri()), vec![("chainsights.domain", "example.com".into())]).await.unwrap();
+        let request: Value = server.received_requests().await.unwrap()[0].body_json().unwrap();
+
+        let spans = request["resourceSpans"][0]["scopeSpans"][0]["spans"].as_array().unwrap();
+        assert_eq!(spans.len(), 5);
+        let root = &spans[0];
+        assert_eq!(root["name"], "traversal");
+        assert!(root.get("parentSpanId").is_none());
+        assert!(spans.iter().all(|span| span["traceId"] == root["traceId"]));
+        for fetch in spans.iter().filter(|span| span["name"] == "fetch") {
+            let parent = spans.iter().find(|span| span["spanId"] == fetch["parentSpanId"]).unwrap();
+            assert_eq!(parent["name"], "attestation");
+            assert_eq!(parent["attributes"][0]["value"]["stringValue"], fetch["attributes"][0]["value"]["stringValue"]);
+            assert_eq!(fetch["kind"], SPAN_KIND_CLIENT);
+            assert_eq!(fetch["attributes"][2]["value"]["intValue"], "3");
+        }
+        let failed = spans.iter().find(|span| span.get("status").is_some()).unwrap();
+        assert_eq!(failed["parentSpanId"], root["spanId"]);
+        assert_eq!(failed["status"]["message"], "bad signature");
+
+        // The next trace starts afresh.
+        assert_eq!(tracer.finish(Vec::new())["resourceSpans"][0]["scopeSpans"][0]["spans"].as_array().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,295 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+
+use crate::models::aggregation::AggregatedCatalogData;
+use crate::models::chainsights::RequiredArtifact;
+use crate::score::{ReleaseCriteria, release_criteria};
+
+/// How much of a traversal may fail before a command reports failure. By default partial
+/// failures are only reported in the output.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct FailThreshold {
+    /// Largest tolerated share of failed statements, between 0 and 1
+    pub max_error_rate: Option<f64>,
+    /// Fail if any statement failed, so that every component and release must verify
+    pub require_all: bool,
+    /// Fail unless a freshness statement confirms the root catalog was current within this long
+    pub max_catalog_age: Option<chrono::Duration>,
+    /// What every verified, unrevoked release must link or satisfy
+    pub required_artifacts: Vec<RequiredArtifact>,
+}
+
+impl FailThreshold {
+    /// Returns an error describing the failures if the traversal falls short of the threshold.
+    pub(crate) fn check(&self, data: &AggregatedCatalogData) -> Result<()> {
+        if let Some(root_error) = &data.root_error
+            && (self.require_all || self.max_error_rate.is_some())
+        {
+            bail!("Root catalog failed to verify: {}", root_error);
+        }
+        let (verified, failed) = data.outcome_counts();
+        if self.require_all && failed > 0 {
+            bail!("{} of {} statements failed to verify or had expired", failed, verified + failed);
+        }
+        let unverified = data.unverified_count();
+        if self.require_all && unverified > 0 {
+            bail!("{} statement(s) were accepted UNVERIFIED by an insecure mode", unverified);
+        }
+        // A relayed result vouches for the whole traversal in the verifier's one signed statement.
+        let unsigned = data.unsigned_count();
+        if self.require_all && data.relayed_from.is_none() && unsigned > 0 {
+            bail!("{} statement(s) were accepted without any signature verification", unsigned);
+        }
+        if let Some(max_error_rate) = self.max_error_rate {
+            let error_rate = failed as f64 / (verified + failed) as f64;
+            if error_rate > max_error_rate {
+                bail!(
+                    "{} of {} statements failed to verify or had expired ({:.1}%, more than the tolerated {:.1}%)",
+                    failed,
+                    verified + failed,
+                    error_rate * 100.0,
+                    max_error_rate * 100.0
+                );
+            }
+        }
+        if let Some(max_age) = self.max_catalog_age
+            && data.catalog_predicate.is_some()
+        {
+            let Some(freshness) = &data.freshness else {
+                bail!(
+                    "The root catalog's freshness is not confirmed: {}",
+                    data.freshness_error.as_deref().unwrap_or("it links no freshness statement")
+                );
+            };
+            let confirmed_at: DateTime<Utc> = freshness
+                .confirmed_at
+                .parse()
+                .with_context(|| format!("Invalid freshness time '{}'", freshness.confirmed_at))?;
+            if Utc::now() - confirmed_at > max_age {
+                bail!(
+                    "The root catalog was last confirmed current at {}, more than {} hour(s) ago",
+                    freshness.confirmed_at,
+                    max_age.num_hours()
+                );
+            }
+        }
+        self.check_required_artifacts(data)
+    }
+
+    fn check_required_artifacts(&self, data: &AggregatedCatalogData) -> Result<()> {
+        if self.required_artifacts.is_empty() {
+            return Ok(());
+        }
+        let now = Utc::now();
+        let releases = data
+            .components
+            .iter()
+            .flat_map(|component| &component.releases)
+            .filter(|release| release.revocation.is_none() && release.verification.as_ref().is_some_and(|v| v.is_verified()));
+        let mut checked = 0;
+        let mut lacking = Vec::new();
+        for release in releases {
+            checked += 1;
+            let criteria = release_criteria(release, chrono::Duration::zero(), now);
+            let missing: Vec<String> =
+                self.required_artifacts.iter().filter(|artifact| !meets(&criteria, **artifact)).map(ToString::to_string).collect();
+            if !missing.is_empty() {
+                lacking.push(format!("{} ({})", release.release_link_uri, missing.join(", ")));
+            }
+        }
+        if !lacking.is_empty() {
+            let shown = lacking.len().min(5);
+            let more = match lacking.len() - shown {
+                0 => String::new(),
+                more => format!(" and {} more", more),
+            };
+            bail!(
+                "{} of {} verified releases lack required artifacts: {}{}",
+                lacking.len(),
+                checked,
+                lacking[..shown].join("; "),
+                more
+            );
+        }
+        Ok(())
+    }
+}
+
+fn meets(criteria: &ReleaseCriteria, artifact: RequiredArtifact) -> bool {
+    match artifact {
+        RequiredArtifact::Sbom => criteria.sbom,
+        RequiredArtifact::Provenance => criteria.provenance,
+        RequiredArtifact::Vex => criteria.vex,
+        RequiredArtifact::SignedArtifacts => criteria.signed_artifacts,
+        // Requirements this client does not know cannot be met.
+        _ => false,
+    }
+}
+
+/// Clap value parser for error rates, given as a percentage (`10%` or `10`).
+pub(crate) fn parse_error_rate(rate: &str) -> std::result::Result<f64, String> {
+    let percent: f64 = rate
+        .trim()
+        .trim_end_matches('%')
+        .parse()
+        .map_err(|_| format!("Invalid error rate '{}', expected a percentage such as 10%", rate))?;
+    if !(0.0..=100.0).contains(&percent) {
+        return Err(format!("Error rate '{}' must be between 0% and 100%", rate));
+    }
+    Ok(percent / 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::aggregation::{
+        AggregatedComponentData, AggregatedReleaseData, CatalogFreshness, IntegrityError, IntegrityErrorKind, NodeVerification,
+    };
+    use crate::models::chainsights::ChainsightsCatalogPredicate;
+
+    use super::*;
+
+    fn verified() -> Option<NodeVerification> {
+        Some(serde_json::from_value(serde_json::json!({ "method": "sigstore-keyless" })).unwrap())
+    }
+
+    /// A catalog with one component that has `releases` verified and `failed` failed releases.
+    fn aggregate(releases: usize, failed: usize) -> AggregatedCatalogData {
+        let release = AggregatedReleaseData {
+            verification: verified(),
+            ..Default::default()
+        };
+        AggregatedCatalogData {
+            verification: verified(),
+            components: vec![AggregatedComponentData {
+                verification: verified(),
+                releases: vec![release; releases],
+                release_errors: (0..failed).map(|i| (format!("https://example.com/releases/{}.json", i), "error".to_string())).collect(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn default_tolerates_partial_failures() {
+        assert!(FailThreshold::default().check(&aggregate(1, 5)).is_ok());
+    }
+
+    #[test]
+    fn error_rate_is_judged_over_all_statements() {
+        let threshold = FailThreshold {
+            max_error_rate: Some(0.1),
+            ..Default::default()
+        };
+        // Root and component plus 7 releases verified, 1 failed: 10%.
+        assert!(threshold.check(&aggregate(7, 1)).is_ok());
+        assert!(threshold.check(&aggregate(6, 1)).is_err());
+    }
+
+    #[test]
+    fn require_all_rejects_any_failure() {
+        let threshold = FailThreshold {
+            require_all: true,
+            ..Default::default()
+        };
+        assert!(threshold.check(&aggregate(3, 0)).is_ok());
+        assert!(threshold.check(&aggregate(3, 1)).is_err());
+        let root_failed = AggregatedCatalogData {
+            root_error: Some("bad signature".to_string()),
+            ..Default::default()
+        };
+        assert!(threshold.check(&root_failed).is_err());
+    }
+
+    #[test]
+    fn require_all_rejects_unsigned_statements() {
+        let threshold = FailThreshold {
+            require_all: true,
+            ..Default::default()
+        };
+        let mut data = aggregate(2, 0);
+        data.components[0].releases[1].verification = None;
+        assert_eq!(data.outcome_counts(), (3, 0));
+        assert!(FailThreshold::default().check(&data).is_ok());
+        assert!(threshold.check(&data).unwrap_err().to_string().contains("without any signature verification"));
+    }
+
+    #[test]
+    fn statements_that_failed_in_several_ways_are_counted_once() {
+        let mut data = aggregate(1, 1);
+        let uri = data.components[0].release_errors[0].0.clone();
+        data.components[0].expired_releases.push((uri.clone(), "expired".to_string()));
+        data.components[0].integrity_errors.push(IntegrityError {
+            uri,
+            kind: IntegrityErrorKind::ReleasePurl,
+            expected_purl: "pkg:generic/example.com/app".to_string(),
+            found_purl: "pkg:generic/example.com/lib@1.0.0".to_string(),
+        });
+        assert_eq!(data.outcome_counts(), (3, 1));
+    }
+
+    #[test]
+    fn require_all_rejects_statements_accepted_by_an_insecure_mode() {
+        let threshold = FailThreshold {
+            require_all: true,
+            ..Default::default()
+        };
+        let mut data = aggregate(1, 0);
+        data.components[0].releases[0].verification =
+            Some(serde_json::from_str(r#"{"method": "sigstore-keyless", "skipped_checks": ["identity"]}"#).unwrap());
+        assert!(FailThreshold::default().check(&data).is_ok());
+        assert!(threshold.check(&data).unwrap_err().to_string().contains("UNVERIFIED"));
+    }
+
+    #[test]
+    fn max_catalog_age_requires_a_recent_freshness_statement() {
+        let threshold = FailThreshold {
+            max_catalog_age: Some(chrono::Duration::hours(24)),
+            ..Default::default()
+        };
+        let catalog: ChainsightsCatalogPredicate = serde_json::from_value(serde_json::json!({ "timestamp": "2025-01-01T00:00:00Z", "components": [] })).unwrap();
+        let confirmed = |hours_ago| AggregatedCatalogData {
+            catalog_predicate: Some(std::sync::Arc::new(catalog.clone())),
+            freshness: Some(CatalogFreshness {
+                uri: "https://example.com/freshness.json".to_string(),
+                signer_identity: Some("witness@example.org".to_string()),
+                confirmed_at: (Utc::now() - chrono::Duration::hours(hours_ago)).to_rfc3339(),
+                expires: None,
+            }),
+            ..Default::default()
+        };
+        assert!(threshold.check(&confirmed(2)).is_ok());
+        assert!(threshold.check(&confirmed(30)).is_err());
+        let unconfirmed = AggregatedCatalogData { freshness: None, ..confirmed(2) };
+        assert!(threshold.check(&unconfirmed).is_err());
+    }
+
+    #[test]
+    fn required_artifacts_must_be_linked_by_every_verified_release() {
+        let threshold = FailThreshold {
+            required_artifacts: vec![RequiredArtifact::Sbom],
+            ..Default::default()
+        };
+        let mut data = aggregate(2, 1);
+        for (release, name) in data.components[0].releases.iter_mut().zip(["1.0.0", "2.0.0"]) {
+            release.release_link_uri = format!("https://example.com/releases/app/{}.json", name);
+        }
+        data.components[0].releases[0].metadata_artifacts =
+            serde_json::from_value(serde_json::json!([{ "uri": "https://example.com/app.spdx.json", "mediaType": "application/spdx+json" }])).unwrap();
+        let error = threshold.check(&data).unwrap_err().to_string();
+        assert_eq!(error, "1 of 2 verified releases lack required artifacts: https://example.com/releases/app/2.0.0.json (sbom)");
+
+        data.components[0].releases.pop();
+        assert!(threshold.check(&data).is_ok());
+    }
+
+    #[test]
+    fn parses_percentages() {
+        assert_eq!(parse_error_rate("10%"), Ok(0.1));
+        assert_eq!(parse_error_rate("25"), Ok(0.25));
+        assert!(parse_error_rate("150%").is_err());
+        assert!(parse_error_rate("ten").is_err());
+    }
+}
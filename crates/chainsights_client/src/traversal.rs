@@ -1,34 +1,429 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+use std::sync::Arc;
 
-use crate::{attestation::verify_signature_with_pae, fetch::fetch_manifest_text, models::{self, statement::InTotoStatement}, AggregatedCatalogData, AggregatedComponentData, AggregatedReleaseData, ChainsightsPredicate};
+use crate::consistency::{is_version_of, same_package};
+use crate::output::{self, Status};
+use crate::scope::{LinkScope, link_host};
+use crate::stats::StatsRecorder;
+#[cfg(feature = "cli")]
+use crate::purl::sub_catalog_matches;
+#[cfg(feature = "cli")]
+use crate::telemetry::{Tracer, traced};
+use crate::{purl::strip_purl_version, fetch::{fetch_catalog_text, fetch_did_document, fetch_document_text, fetch_manifest_text, Fetchers}, rekor::RekorClient, models::{self, aggregation::{AggregatedCatalogData, AggregatedComponentData, CatalogFreshness, AggregatedReleaseData, ClaimSource, CrossDomainLink, CustomStatement, Provenance, Endorsement, IntegrityError, IntegrityErrorKind, NodeVerification, SkippedCheck, VerificationMethod}, chainsights::{ChainsightsPredicate, ArtifactLink, AttestationLink, ChainsightsCatalogPredicate, ChainsightsComponentPredicate, CustomPredicate, ENDORSEMENT_MEDIA_TYPE, ChainsightsRevocationPredicate, RevokedAttestation}}};
 use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use chainsights_core::did::{DidKey, DidVerifier, did_document_keys, did_key, did_web_document_url, is_did};
+use chainsights_core::identity::{BuildPolicy, SignerIdentity, same_identity};
+use chainsights_core::pgp::{PgpKeyring, PgpVerifier};
+use chainsights_core::verifier::{Verifier, is_public_key_identity, key_verifier, matches_key_pin, published_key_fingerprints};
+use chainsights_core::statement::InTotoStatement;
+use chainsights_core::timestamp::TimeSource;
+use chainsights_core::trust::TrustedRoot;
+use chainsights_core::verify::{VerifiedBundle, decode_bundle_unverified, verify_signature_with_pae, verify_signature_with_trusted_root};
+use sha2::{Digest, Sha256};
 
 // TODO: This should be configurable
 const MAX_DEPTH: u32 = 10;
+/// Upper bound on the pages and shards followed for a single catalog.
+const MAX_CATALOG_PAGES: usize = 1000;
+/// How many of a component's releases are fetched and verified at once.
+const MAX_CONCURRENT_RELEASES: usize = 8;
+/// Media type of metadata links to in-toto statements.
+const IN_TOTO_MEDIA_TYPE: &str = "application/vnd.in-toto+json";
+
+/// Options controlling how the attestation graph is traversed.
+#[derive(Default, Clone)]
+pub(crate) struct TraversalOptions {
+    /// When set, attestations that fail to fetch are looked up in Rekor by their link digest.
+    pub rekor_fallback: Option<RekorClient>,
+    /// When set, revoked components and releases are left out of the aggregate instead of being marked.
+    pub exclude_revoked: bool,
+    /// How far past its `expires` time a statement is still accepted, to allow for clock differences.
+    pub clock_skew: chrono::Duration,
+    /// Where the signing time used for certificate validity, freshness and expiry comes from.
+    pub time_source: TimeSource,
+    /// Receives traversal events, e.g. to drive a progress display. Progress lines are printed to
+    /// standard output when unset.
+    pub hooks: Option<Arc<dyn TraversalHooks>>,
+    /// When set, attestation links pointing outside the publisher's domain and the allowed hosts
+    /// are reported as errors instead of followed.
+    pub link_scope: Option<LinkScope>,
+    /// When set, release statements are only accepted from certificates whose CI build context
+    /// (repository, ref, trigger) satisfies this policy.
+    pub release_build_policy: Option<BuildPolicy>,
+    /// When set, bundles are verified against this Sigstore instance's trust material, e.g. a
+    /// private instance's Fulcio roots and Rekor and CT log keys.
+    pub trusted_root: Option<Arc<TrustedRoot>>,
+    /// Public keys of PGP-signed attestations, which links pin by fingerprint.
+    pub pgp_keyring: Option<Arc<PgpKeyring>>,
+    /// The `key=` pin of the domain's `_chainsights` TXT record: a fingerprint of the key or
+    /// certificate the root catalog must be signed with, or the URI of a published public key.
+    pub root_key_pin: Option<String>,
+    /// When set, every attestation processed and its verification are recorded as spans.
+    #[cfg(feature = "cli")]
+    pub tracer: Option<Arc<Tracer>>,
+    /// Checks to skip for local development against unsigned or self-signed fixtures.
+    pub insecure: InsecureMode,
+    /// When set, verification times are recorded, and the aggregate carries the statistics so far.
+    pub stats: Option<Arc<StatsRecorder>>,
+    /// Endorsers the consumer trusts, as (name, identity) pairs. Endorsements are attributed to
+    /// these names only; the endorser named in a statement is the publisher's claim.
+    pub trusted_endorsers: Vec<(String, String)>,
+    /// When set, statements of predicate types Chainsights does not define are parsed with it, and
+    /// the in-toto statements components and releases link are read for those it parses.
+    pub predicate_parser: Option<Arc<dyn PredicateParser>>,
+}
+
+/// Verification checks skipped for local development. Every node accepted only because a check
+/// was skipped records it in [`NodeVerification::skipped_checks`] and is reported UNVERIFIED.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct InsecureMode {
+    /// Accept statements without checking their signatures, including unsigned statements.
+    pub skip_signature: bool,
+    /// Accept correctly signed statements whatever identity signed them.
+    pub skip_identity: bool,
+}
+
+impl TraversalOptions {
+    pub(crate) fn hooks(&self) -> &dyn TraversalHooks {
+        self.hooks.as_deref().unwrap_or(&PrintHooks)
+    }
+
+    /// These options with the domain-scoping policy, if there is one, limited to `domain`.
+    pub(crate) fn scoped_to(&self, domain: &str) -> Self {
+        Self {
+            link_scope: self.link_scope.as_ref().map(|scope| scope.for_domain(domain)),
+            ..self.clone()
+        }
+    }
+
+    /// These options with the root catalog pinned to `key`, from a `_chainsights` TXT record.
+    #[cfg(feature = "dns-discovery")]
+    pub(crate) fn pinned_to(self, key: Option<String>) -> Self {
+        Self { root_key_pin: key, ..self }
+    }
+
+    /// Checks a link against the domain-scoping policy, if there is one.
+    fn check_link_scope(&self, uri: &str) -> Result<()> {
+        self.link_scope.as_ref().map_or(Ok(()), |scope| scope.check(uri))
+    }
+
+    /// Checks a component or release link against the domain-scoping policy and its cross-domain
+    /// trust rules, if there is one. Returns the foreign domain that corroborated the link, if any.
+    async fn check_node_link(&self, link: &AttestationLink, depth: usize) -> Result<Option<CrossDomainLink>> {
+        let Some(scope) = &self.link_scope else {
+            return Ok(None);
+        };
+        let cross_domain = scope.check_link(link).await?;
+        if let Some(cross_domain) = &cross_domain {
+            self.hooks().message(depth, Status::Info, &format!(
+                "CROSS-DOMAIN: {} on {}, corroborated by {} ({})",
+                link.uri, cross_domain.domain, cross_domain.corroborated_by, cross_domain.identity
+            ));
+        }
+        Ok(cross_domain)
+    }
+}
+
+/// Events raised while traversing. Attestations are identified by URI and may be reported as
+/// discovered more than once; implementations deduplicate if they need to.
+pub(crate) trait TraversalHooks: Send + Sync {
+    /// A link to an attestation was found and will be followed.
+    fn node_discovered(&self, _uri: &str) {}
+
+    /// An attestation was fetched and processed, successfully or not.
+    fn node_processed(&self, _uri: &str, _verified: bool) {}
+
+    /// A statement or artifact of this many bytes was downloaded.
+    fn bytes_downloaded(&self, _bytes: u64) {}
+
+    /// A line describing what the traversal is doing, at `depth` in the attestation tree (see
+    /// [`output::line`]).
+    fn message(&self, depth: usize, status: Status, text: &str) {
+        output::print(depth, status, text);
+    }
+}
+
+/// Parses statements of predicate types Chainsights does not define, such as the proprietary
+/// ones the `--plugin` plugins name.
+pub(crate) trait PredicateParser: Send + Sync {
+    /// Parses `statement`, or returns None if its predicate type is not one this parses.
+    fn parse(&self, statement: &InTotoStatement) -> Option<Result<CustomPredicate>>;
+}
+
+/// The hooks used when none are set: progress lines go to standard output.
+struct PrintHooks;
+
+impl TraversalHooks for PrintHooks {}
+
+/// Hooks that print nothing, for library callers that traverse quietly.
+pub(crate) struct SilentHooks;
+
+impl TraversalHooks for SilentHooks {
+    fn message(&self, _depth: usize, _status: Status, _text: &str) {}
+}
+
+/// The error returned for a correctly signed statement whose `expires` time has passed, so that
+/// expired statements can be reported separately from invalid ones.
+#[derive(Debug)]
+pub(crate) struct ExpiredAttestation {
+    pub uri: String,
+    pub expires: String,
+}
+
+impl std::fmt::Display for ExpiredAttestation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Attestation at '{}' expired at {}", self.uri, self.expires)
+    }
+}
+
+impl std::error::Error for ExpiredAttestation {}
+
+/// Returns the expiry time if `error` is an expired (but otherwise valid) attestation.
+fn expired_at(error: &anyhow::Error) -> Option<String> {
+    error.downcast_ref::<ExpiredAttestation>().map(|e| e.expires.clone())
+}
 
 pub(crate) async fn traverse_and_aggregate(
     root_uri: &str,
-    root_identity: &str,
+    root_identities: &[SignerIdentity],
+    fetchers: &Fetchers,
+    options: &TraversalOptions,
 ) -> Result<AggregatedCatalogData> {
     // 1. Initialization
-    let client = reqwest::Client::new(); // Create client once
     let mut visited_uris = HashSet::new();
+    // Publishers without Sigstore announce their root catalog's public key as its identity.
+    let root_keys: Vec<String> = root_identities
+        .iter()
+        .map(|identity| identity.identity.clone())
+        .filter(|identity| is_public_key_identity(identity))
+        .collect();
+    // Without a queried domain, links are scoped to the host the root catalog is served from.
+    let rooted;
+    let options = match (&options.link_scope, link_host(root_uri)) {
+        (Some(LinkScope { domain: None, .. }), Some(host)) => {
+            rooted = options.scoped_to(&host);
+            &rooted
+        }
+        _ => options,
+    };
 
     let mut aggregated_data = AggregatedCatalogData::default();
 
     // 2. Process Root URI
-    match process_attestation_uri(root_uri, root_identity, &mut visited_uris, 0, &client).await {
-        Ok(ChainsightsPredicate::Catalog(catalog)) => {
-            aggregated_data.catalog_predicate = Some(catalog.clone()); // Store the root predicate
+    let root_result = match resolve_key_pin(options.root_key_pin.as_deref(), fetchers).await {
+        Ok(key_pins) => {
+            let signers = ExpectedSigners::root(root_identities, &root_keys, &key_pins);
+            process_attestation_uri(root_uri, signers, None, &mut visited_uris, 0, fetchers, options).await
+        }
+        Err(e) => Err(e),
+    };
+    match root_result {
+        Ok((ChainsightsPredicate::Catalog(mut catalog), catalog_sha256, verification)) => {
+            let catalog_source = ClaimSource::new(root_uri, &verification);
+            aggregated_data.provenance =
+                Some(Provenance { statement: catalog_source.clone(), linked_from: None, fields: BTreeMap::new() });
+            aggregated_data.verification = Some(verification);
+            // The statement that linked each component, for its provenance.
+            let mut linked_from: HashMap<String, ClaimSource> = catalog
+                .components
+                .iter()
+                .map(|c| (c.component_attestation_link.uri.clone(), catalog_source.clone()))
+                .collect();
+            (aggregated_data.endorsements, aggregated_data.endorsement_errors) = collect_endorsements(
+                catalog.metadata_links.as_ref(),
+                &catalog_sha256,
+                &mut visited_uris,
+                1,
+                fetchers,
+                options,
+            )
+            .await;
+
+            if let Some(freshness_link) = &catalog.freshness_link {
+                match verify_freshness(freshness_link, &catalog, &catalog_sha256, &mut visited_uris, fetchers, options).await {
+                    Ok(freshness) => {
+                        options
+                            .hooks()
+                            .message(1, Status::Verified, &format!("Catalog confirmed current at {}", freshness.confirmed_at));
+                        aggregated_data.freshness = Some(freshness);
+                    }
+                    Err(e) => {
+                        options.hooks().message(1, Status::Failed, &format!("Catalog freshness not confirmed: {:#}", e));
+                        aggregated_data.freshness_error = Some(format!("{:#}", e));
+                    }
+                }
+            }
+
+            if let Some(revocation_link) = &catalog.revocation_link {
+                let result = match options.check_link_scope(&revocation_link.uri) {
+                    Ok(()) => {
+                        process_attestation_uri(
+                            &revocation_link.uri,
+                            ExpectedSigners::of(revocation_link, &revocation_link.accepted_identities()),
+                            revocation_link.digest.as_ref(),
+                            &mut visited_uris,
+                            1,
+                            fetchers,
+                            options,
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok((ChainsightsPredicate::Revocation(revocations), _, _)) => {
+                        options.hooks().message(1, Status::Info, &format!(
+                            "Loaded {} revocation(s) from {}",
+                            revocations.revocations.len(),
+                            revocation_link.uri
+                        ));
+                        aggregated_data.revocation_predicate = Some(Arc::new(revocations));
+                    }
+                    Ok((other_pred, _, _)) => {
+                        aggregated_data.revocation_error = Some(format!(
+                            "Expected Revocation predicate at '{}', found {:?}",
+                            revocation_link.uri, other_pred
+                        ));
+                    }
+                    Err(e) => {
+                        aggregated_data.revocation_error =
+                            Some(format!("Failed to process revocation list '{}': {}", revocation_link.uri, e));
+                    }
+                }
+                // Excluding revoked nodes is meaningless without the list, and an attacker who
+                // can block it must not be able to bring revoked statements back.
+                if options.exclude_revoked
+                    && let Some(error) = &aggregated_data.revocation_error
+                {
+                    aggregated_data.root_error =
+                        Some(format!("{}; revoked attestations cannot be excluded without it", error));
+                    aggregated_data.stats = options.stats.as_ref().map(|stats| stats.snapshot());
+                    return Ok(aggregated_data);
+                }
+            }
+            let revocations = aggregated_data.revocation_predicate.clone();
+
+            let mut pages: VecDeque<AttestationLink> = catalog
+                .component_shards
+                .iter()
+                .flatten()
+                .chain(&catalog.next_page)
+                .cloned()
+                .collect();
+            let mut page_count = 0;
+            while let Some(page_link) = pages.pop_front() {
+                let page_uri = page_link.uri.clone();
+                if revocations.as_ref().and_then(|r| r.find(&page_link)).is_some() {
+                    aggregated_data
+                        .component_errors
+                        .push((page_uri, "Catalog page has been revoked".to_string()));
+                    continue;
+                }
+                if let Err(e) = options.check_link_scope(&page_uri) {
+                    aggregated_data.component_errors.push((page_uri, e.to_string()));
+                    continue;
+                }
+                page_count += 1;
+                if page_count > MAX_CATALOG_PAGES {
+                    aggregated_data.component_errors.push((
+                        page_uri,
+                        format!("Catalog has more than {} pages; remaining pages skipped", MAX_CATALOG_PAGES),
+                    ));
+                    break;
+                }
+                match process_attestation_uri(
+                    &page_uri,
+                    ExpectedSigners::of(&page_link, &page_link.accepted_identities()),
+                    page_link.digest.as_ref(),
+                    &mut visited_uris,
+                    0,
+                    fetchers,
+                    options,
+                )
+                .await
+                {
+                    Ok((ChainsightsPredicate::Catalog(_), page_sha256, _))
+                        if revocations.as_ref().and_then(|r| r.find_sha256(&page_sha256)).is_some() =>
+                    {
+                        aggregated_data
+                            .component_errors
+                            .push((page_uri, "Catalog page has been revoked".to_string()));
+                    }
+                    Ok((ChainsightsPredicate::Catalog(page), _, page_verification)) => {
+                        options
+                            .hooks()
+                            .message(1, Status::Info, &format!("Loaded {} component(s) from catalog page {}", page.components.len(), page_uri));
+                        let page_source = ClaimSource::new(&page_uri, &page_verification);
+                        for component in &page.components {
+                            linked_from
+                                .entry(component.component_attestation_link.uri.clone())
+                                .or_insert_with(|| page_source.clone());
+                        }
+                        catalog.components.extend(page.components);
+                        pages.extend(page.component_shards.into_iter().flatten().chain(page.next_page));
+                    }
+                    Ok((other_pred, _, _)) => {
+                        aggregated_data.component_errors.push((
+                            page_uri,
+                            format!("Expected Catalog predicate for catalog page, found {:?}", other_pred),
+                        ));
+                    }
+                    Err(e) => {
+                        aggregated_data.component_errors.push((page_uri, e.to_string()));
+                    }
+                }
+            }
+            // Store the root predicate once every page has been merged into it; components and
+            // releases below are likewise shared with the aggregate rather than copied into it.
+            let catalog = Arc::new(catalog);
+            aggregated_data.catalog_predicate = Some(Arc::clone(&catalog));
+            let components = &catalog.components;
 
             // 3. Recursive Traversal
-            for component in &catalog.components {
-                let component_uri = &component.component_attestation_link.uri;
-                let component_identity = &component
-                    .component_attestation_link
-                    .expected_signer_identity;
+            // Components listed in the catalog come first. Components outside the catalog that
+            // they have relationships with are appended as they are found.
+            let listed_purls: HashSet<String> =
+                components.iter().map(|c| strip_purl_version(&c.component_purl)).collect();
+            let listed_uris: HashSet<&str> = components
+                .iter()
+                .map(|c| c.component_attestation_link.uri.as_str())
+                .collect();
+            let mut queue: VecDeque<(AttestationLink, bool)> = components
+                .iter()
+                .map(|c| (c.component_attestation_link.clone(), true))
+                .collect();
+            for (link, _) in &queue {
+                options.hooks().node_discovered(&link.uri);
+            }
+            while let Some((component_link, listed)) = queue.pop_front() {
+                let component_uri = &component_link.uri;
+                if !listed && visited_uris.contains(component_uri) {
+                    // Already aggregated through another relationship.
+                    continue;
+                }
+                let component_identities = component_link.accepted_identities();
+                let component_revocation = revocations
+                    .as_ref()
+                    .and_then(|r| r.find(&component_link))
+                    .cloned();
+                if component_revocation.is_some() && options.exclude_revoked {
+                    options
+                        .hooks()
+                        .message(2, Status::Warning, &format!("Skipping revoked component attestation: {}", component_uri));
+                    continue;
+                }
+
+                let cross_domain = match options.check_node_link(&component_link, 2).await {
+                    Ok(cross_domain) => cross_domain,
+                    Err(e) => {
+                        aggregated_data.component_errors.push((component_uri.clone(), e.to_string()));
+                        continue;
+                    }
+                };
 
                 // Check visited state *before* recursive call (important for sequential)
                 if visited_uris.contains(component_uri) {
@@ -38,7 +433,7 @@ pub(crate) async fn traverse_and_aggregate(
                     ));
                     continue; // Skip this component, proceed to the next
                 }
-                if 0 + 1 >= MAX_DEPTH {
+                if 1 >= MAX_DEPTH {
                     // Check depth before call
                     aggregated_data.component_errors.push((
                         component_uri.clone(),
@@ -52,96 +447,116 @@ pub(crate) async fn traverse_and_aggregate(
 
                 match process_attestation_uri(
                     component_uri,
-                    component_identity,
+                    ExpectedSigners::of(&component_link, &component_identities),
+                    component_link.digest.as_ref(),
                     &mut visited_uris,
                     1,
-                    &client,
+                    fetchers,
+                    options,
                 )
                 .await
                 {
-                    Ok(ChainsightsPredicate::Component(component_predicate)) => {
-                        let mut agg_comp_data = AggregatedComponentData {
-                            component_predicate: Some(component_predicate.clone()),
-                            component_link_uri: component_uri.clone(),
-                            ..Default::default()
-                        };
-
-                        // Recursively process releases for this component
-                        for release_link in &component_predicate.release_attestations {
-                            let release_uri = &release_link.uri;
-                            let release_identity = &release_link.expected_signer_identity;
-
-                            if visited_uris.contains(release_uri) {
-                                agg_comp_data.release_errors.push((
-                                    release_uri.clone(),
-                                    format!(
-                                        "Cycle detected: URI '{}' already visited",
-                                        release_uri
-                                    ),
-                                ));
-                                continue;
-                            }
-                            if 1 + 1 >= MAX_DEPTH {
-                                agg_comp_data.release_errors.push((
-                                    release_uri.clone(),
-                                    format!("Maximum traversal depth ({}) would be exceeded at URI '{}'", MAX_DEPTH, release_uri)
-                                ));
-                                continue;
-                            }
-
-                            match process_attestation_uri(
-                                release_uri,
-                                release_identity,
-                                &mut visited_uris,
-                                2,
-                                &client,
-                            )
-                            .await
+                    Ok((ChainsightsPredicate::Component(component_predicate), component_sha256, verification)) => {
+                        // A revoked statement may have been re-linked under a URI the list does not name.
+                        let component_revocation = component_revocation
+                            .or_else(|| revocations.as_ref().and_then(|r| r.find_sha256(&component_sha256)).cloned());
+                        if component_revocation.is_some() && options.exclude_revoked {
+                            options
+                                .hooks()
+                                .message(2, Status::Warning, &format!("Skipping revoked component attestation: {}", component_uri));
+                            continue;
+                        }
+                        let component_source = ClaimSource::new(component_uri, &verification);
+                        // Components found through relationships have no catalog entry to contradict.
+                        let entry = components.iter().find(|c| c.component_attestation_link.uri == *component_uri);
+                        if let Some(entry) = entry
+                            && !same_package(&entry.component_purl, &component_predicate.purl)
+                        {
+                            let error = IntegrityError {
+                                uri: component_uri.clone(),
+                                kind: IntegrityErrorKind::ComponentPurl,
+                                expected_purl: entry.component_purl.clone(),
+                                found_purl: component_predicate.purl.clone(),
+                            };
+                            options.hooks().message(2, Status::Failed, &error.to_string());
+                            aggregated_data.integrity_errors.push(error);
+                            continue;
+                        }
+                        let component_predicate = Arc::new(component_predicate);
+                        for relationship in component_predicate.relationships.iter().flatten() {
+                            if let Some(link) = &relationship.component_attestation_link
+                                && !listed_purls.contains(&strip_purl_version(&relationship.target_purl))
+                                && !listed_uris.contains(link.uri.as_str())
                             {
-                                Ok(ChainsightsPredicate::Release(release_predicate)) => {
-                                    agg_comp_data.releases.push(AggregatedReleaseData {
-                                        release_predicate: Some(release_predicate.clone()),
-                                        metadata_artifacts: release_predicate
-                                            .metadata_links
-                                            .unwrap_or(Vec::new())
-                                            .clone(), // Assuming artifacts are directly in predicate
-                                        release_link_uri: release_uri.clone(),
-                                        ..Default::default()
-                                    });
-                                }
-                                Ok(other_pred) => {
-                                    agg_comp_data.release_errors.push((
-                                        release_uri.clone(),
-                                        format!(
-                                            "Expected Release predicate, found {:?}",
-                                            other_pred
-                                        ),
-                                    ));
-                                }
-                                Err(e) => {
-                                    agg_comp_data
-                                        .release_errors
-                                        .push((release_uri.clone(), e.to_string()));
-                                }
+                                options.hooks().message(3, Status::Info, &format!(
+                                    "Following {} relationship to {}",
+                                    relationship.relationship_type, relationship.target_purl
+                                ));
+                                options.hooks().node_discovered(&link.uri);
+                                linked_from.entry(link.uri.clone()).or_insert_with(|| component_source.clone());
+                                queue.push_back((link.clone(), false));
                             }
                         }
+                        let (endorsements, endorsement_errors) = collect_endorsements(
+                            component_predicate.metadata_links.as_ref(),
+                            &component_sha256,
+                            &mut visited_uris,
+                            2,
+                            fetchers,
+                            options,
+                        )
+                        .await;
+                        let (custom_statements, custom_statement_errors) = collect_custom_statements(
+                            component_predicate.metadata_links.as_ref(),
+                            &mut visited_uris,
+                            2,
+                            fetchers,
+                            options,
+                        )
+                        .await;
+                        let mut agg_comp_data = aggregate_releases(
+                            component_predicate,
+                            &component_source,
+                            component_revocation,
+                            revocations.as_deref(),
+                            &mut visited_uris,
+                            fetchers,
+                            options,
+                        )
+                        .await;
+                        agg_comp_data.provenance = Some(Provenance {
+                            statement: component_source,
+                            linked_from: linked_from.get(component_uri).cloned(),
+                            fields: BTreeMap::new(),
+                        });
+                        agg_comp_data.verification = Some(verification);
+                        agg_comp_data.cross_domain = cross_domain;
+                        if !listed {
+                            agg_comp_data.via_relationship = linked_from.get(component_uri).map(|source| source.uri.clone());
+                        }
+                        agg_comp_data.endorsements = endorsements;
+                        agg_comp_data.endorsement_errors = endorsement_errors;
+                        agg_comp_data.custom_statements = custom_statements;
+                        agg_comp_data.custom_statement_errors = custom_statement_errors;
                         aggregated_data.components.push(agg_comp_data);
                     }
-                    Ok(other_pred) => {
+                    Ok((other_pred, _, _)) => {
                         aggregated_data.component_errors.push((
                             component_uri.clone(),
                             format!("Expected Component predicate, found {:?}", other_pred),
                         ));
                     }
-                    Err(e) => {
-                        aggregated_data
+                    Err(e) => match expired_at(&e) {
+                        Some(expires) => aggregated_data.expired_components.push((component_uri.clone(), expires)),
+                        None => aggregated_data
                             .component_errors
-                            .push((component_uri.clone(), e.to_string()));
-                    }
+                            .push((component_uri.clone(), e.to_string())),
+                    },
                 }
             }
+            aggregated_data.relationships = aggregated_data.relationship_edges();
         }
-        Ok(other_pred) => {
+        Ok((other_pred, _, _)) => {
             // Root URI did not yield a Catalog predicate
             aggregated_data.root_error = Some(format!(
                 "Expected Catalog predicate at root URI '{}', but found {:?}",
@@ -155,16 +570,476 @@ pub(crate) async fn traverse_and_aggregate(
         }
     }
 
+    aggregated_data.stats = options.stats.as_ref().map(|stats| stats.snapshot());
     Ok(aggregated_data)
 }
 
+/// A release field, falling back to its component's, recording in `fields` whether the release
+/// or component statement (`sources`, in that order) asserted it.
+fn derived_field<T>(
+    fields: &mut BTreeMap<String, ClaimSource>,
+    field: &str,
+    (release, component): (&ClaimSource, &ClaimSource),
+    own: Option<T>,
+    fallback: impl FnOnce() -> Option<T>,
+) -> Option<T> {
+    let (value, source) = match own {
+        Some(value) => (Some(value), release),
+        None => (fallback(), component),
+    };
+    if value.is_some() {
+        fields.insert(field.to_string(), source.clone());
+    }
+    value
+}
+
+/// Traverses a component's releases and aggregates them with the component.
+async fn aggregate_releases(
+    component_predicate: Arc<ChainsightsComponentPredicate>,
+    component_source: &ClaimSource,
+    revocation: Option<RevokedAttestation>,
+    revocations: Option<&ChainsightsRevocationPredicate>,
+    visited_uris: &mut HashSet<String>,
+    fetchers: &Fetchers,
+    options: &TraversalOptions,
+) -> AggregatedComponentData {
+    let mut agg_comp_data = AggregatedComponentData {
+        component_predicate: Some(Arc::clone(&component_predicate)),
+        component_link_uri: component_source.uri.clone(),
+        revocation,
+        ..Default::default()
+    };
+
+    for release_link in &component_predicate.release_attestations {
+        options.hooks().node_discovered(&release_link.uri);
+    }
+
+    // Claim every release first, then fetch and verify them concurrently. Results are aggregated
+    // in the order the component lists its releases.
+    let mut claimed = Vec::new();
+    for release_link in &component_predicate.release_attestations {
+        let release_uri = &release_link.uri;
+        let release_revocation =
+            revocations.and_then(|r| r.find(release_link)).cloned();
+        if release_revocation.is_some() && options.exclude_revoked {
+            options
+                .hooks()
+                .message(3, Status::Warning, &format!("Skipping revoked release attestation: {}", release_uri));
+            continue;
+        }
+
+        let cross_domain = match options.check_node_link(release_link, 3).await {
+            Ok(cross_domain) => cross_domain,
+            Err(e) => {
+                agg_comp_data.release_errors.push((release_uri.clone(), e.to_string()));
+                continue;
+            }
+        };
+
+        if visited_uris.contains(release_uri) {
+            agg_comp_data.release_errors.push((
+                release_uri.clone(),
+                format!(
+                    "Cycle detected: URI '{}' already visited",
+                    release_uri
+                ),
+            ));
+            continue;
+        }
+        if 1 + 1 >= MAX_DEPTH {
+            agg_comp_data.release_errors.push((
+                release_uri.clone(),
+                format!("Maximum traversal depth ({}) would be exceeded at URI '{}'", MAX_DEPTH, release_uri)
+            ));
+            continue;
+        }
+        visited_uris.insert(release_uri.clone());
+        claimed.push((release_link, release_revocation, cross_domain));
+    }
+
+    let loads: Vec<_> = claimed
+        .iter()
+        .map(|(release_link, _, _)| load_release(release_link, fetchers, options))
+        .collect();
+    let loaded: Vec<_> = stream::iter(loads).buffered(MAX_CONCURRENT_RELEASES).collect().await;
+
+    for ((release_link, release_revocation, cross_domain), result) in claimed.into_iter().zip(loaded) {
+        let release_uri = &release_link.uri;
+        match result {
+            Ok((ChainsightsPredicate::Release(release_predicate), _, _))
+                if !is_version_of(&release_predicate.purl, &component_predicate.purl) =>
+            {
+                let error = IntegrityError {
+                    uri: release_uri.clone(),
+                    kind: IntegrityErrorKind::ReleasePurl,
+                    expected_purl: component_predicate.purl.clone(),
+                    found_purl: release_predicate.purl.clone(),
+                };
+                options.hooks().message(3, Status::Failed, &error.to_string());
+                agg_comp_data.integrity_errors.push(error);
+            }
+            Ok((ChainsightsPredicate::Release(_), release_sha256, _))
+                if release_revocation.is_none()
+                    && options.exclude_revoked
+                    && revocations.and_then(|r| r.find_sha256(&release_sha256)).is_some() =>
+            {
+                options
+                    .hooks()
+                    .message(3, Status::Warning, &format!("Skipping revoked release attestation: {}", release_uri));
+            }
+            Ok((ChainsightsPredicate::Release(release_predicate), release_sha256, verification)) => {
+                let release_revocation =
+                    release_revocation.or_else(|| revocations.and_then(|r| r.find_sha256(&release_sha256)).cloned());
+                let (endorsements, endorsement_errors) = collect_endorsements(
+                    release_predicate.metadata_links.as_ref(),
+                    &release_sha256,
+                    visited_uris,
+                    3,
+                    fetchers,
+                    options,
+                )
+                .await;
+                let (custom_statements, custom_statement_errors) = collect_custom_statements(
+                    release_predicate.metadata_links.as_ref(),
+                    visited_uris,
+                    3,
+                    fetchers,
+                    options,
+                )
+                .await;
+                let release_predicate = Arc::new(release_predicate);
+                let release_source = ClaimSource::new(release_uri, &verification);
+                // Fields the release does not set itself fall back to the component's.
+                let mut fields = BTreeMap::new();
+                let sources = (&release_source, component_source);
+                let cpe = derived_field(&mut fields, "cpe", sources, release_predicate.cpe.clone(), || {
+                    let purl = packageurl::PackageUrl::from_str(&release_predicate.purl).ok()?;
+                    component_predicate.release_cpe(purl.version()?)
+                });
+                let license = derived_field(&mut fields, "license", sources, release_predicate.license.clone(), || {
+                    component_predicate.license.clone()
+                });
+                let supplier = derived_field(&mut fields, "supplier", sources, release_predicate.supplier.clone(), || {
+                    component_predicate.supplier.clone()
+                });
+                agg_comp_data.releases.push(AggregatedReleaseData {
+                    release_predicate: Some(Arc::clone(&release_predicate)),
+                    metadata_artifacts: release_predicate.metadata_links.clone().unwrap_or_default(),
+                    release_link_uri: release_uri.clone(),
+                    provenance: Some(Provenance {
+                        statement: release_source,
+                        linked_from: Some(component_source.clone()),
+                        fields,
+                    }),
+                    verification: Some(verification),
+                    revocation: release_revocation,
+                    cross_domain,
+                    cpe,
+                    license,
+                    supplier,
+                    endorsements,
+                    endorsement_errors,
+                    custom_statements,
+                    custom_statement_errors,
+                    ..Default::default()
+                });
+            }
+            Ok((other_pred, _, _)) => {
+                agg_comp_data.release_errors.push((
+                    release_uri.clone(),
+                    format!(
+                        "Expected Release predicate, found {:?}",
+                        other_pred
+                    ),
+                ));
+            }
+            Err(e) => match expired_at(&e) {
+                Some(expires) => agg_comp_data.expired_releases.push((release_uri.clone(), expires)),
+                None => agg_comp_data
+                    .release_errors
+                    .push((release_uri.clone(), e.to_string())),
+            },
+        }
+    }
+    agg_comp_data
+}
+
+/// Loads a release that [`aggregate_releases`] has claimed, reporting it to the traversal hooks.
+async fn load_release(
+    release_link: &AttestationLink,
+    fetchers: &Fetchers,
+    options: &TraversalOptions,
+) -> Result<(ChainsightsPredicate, String, NodeVerification)> {
+    let uri = &release_link.uri;
+    let identities = release_link.accepted_identities();
+    let result = load_attestation(uri, ExpectedSigners::of(release_link, &identities), release_link.digest.as_ref(), 2, fetchers, options).await;
+    let verified = result.as_ref().is_ok_and(|(_, _, verification)| verification.is_verified());
+    report_processed(uri, 2, &result, verified, options);
+    result
+}
+
+/// Fetches, verifies and parses a single statement outside of a catalog traversal, with the same
+/// signature, freshness and expiry checks.
+#[cfg(feature = "cli")]
+pub(crate) async fn fetch_verified_statement(
+    uri: &str,
+    expected_identities: &[SignerIdentity],
+    fetchers: &Fetchers,
+    options: &TraversalOptions,
+) -> Result<ChainsightsPredicate> {
+    let (predicate, _, _) =
+        process_attestation_uri(uri, ExpectedSigners::sigstore(expected_identities), None, &mut HashSet::new(), 0, fetchers, options).await?;
+    Ok(predicate)
+}
+
+/// Fetches the endorsement bundles among a statement's metadata links and keeps those that endorse
+/// the statement, identified by the SHA-256 digest of its DSSE payload. Each endorsement must be
+/// signed by the identity its link expects. The publisher chooses that identity, so endorsements
+/// are only attributed to the consumer's trusted endorsers.
+async fn collect_endorsements(
+    metadata_links: Option<&Vec<ArtifactLink>>,
+    statement_sha256: &str,
+    visited_uris: &mut HashSet<String>,
+    depth: u32,
+    fetchers: &Fetchers,
+    options: &TraversalOptions,
+) -> (Vec<Endorsement>, Vec<(String, String)>) {
+    let mut endorsements = Vec::new();
+    let mut errors = Vec::new();
+    for link in metadata_links
+        .into_iter()
+        .flatten()
+        .filter(|l| l.media_type.as_deref() == Some(ENDORSEMENT_MEDIA_TYPE))
+    {
+        let Some(identity) = &link.expected_signer_identity else {
+            errors.push((link.uri.clone(), "Endorsement link has no expected signer identity".to_string()));
+            continue;
+        };
+        match process_attestation_uri(
+            &link.uri,
+            ExpectedSigners::sigstore(&[SignerIdentity::new(identity)]),
+            link.digest.as_ref(),
+            visited_uris,
+            depth,
+            fetchers,
+            options,
+        )
+        .await
+        {
+            Ok((ChainsightsPredicate::Endorsement(predicate), _, _)) => {
+                if predicate.endorsed_statement.matches_sha256(statement_sha256) {
+                    let trusted_endorser = options
+                        .trusted_endorsers
+                        .iter()
+                        .find(|(_, trusted)| same_identity(trusted, identity))
+                        .map(|(name, _)| name.clone());
+                    match &trusted_endorser {
+                        Some(name) => options
+                            .hooks()
+                            .message(depth as usize + 2, Status::Verified, &format!("Endorsed by {} ({})", name, identity)),
+                        None => options.hooks().message(depth as usize + 2, Status::Info, &format!(
+                            "Endorsement signed by {}, which is not a trusted endorser",
+                            identity
+                        )),
+                    }
+                    endorsements.push(Endorsement {
+                        uri: link.uri.clone(),
+                        signer_identity: identity.clone(),
+                        trusted_endorser,
+                        predicate,
+                    });
+                } else {
+                    errors.push((
+                        link.uri.clone(),
+                        format!("Endorsement does not cover this statement (sha256:{})", statement_sha256),
+                    ));
+                }
+            }
+            Ok((other_pred, _, _)) => {
+                errors.push((link.uri.clone(), format!("Expected Endorsement predicate, found {:?}", other_pred)));
+            }
+            Err(e) => errors.push((link.uri.clone(), e.to_string())),
+        }
+    }
+    (endorsements, errors)
+}
+
+/// Fetches the in-toto statements a component or release links with an expected signer and keeps
+/// those of predicate types `options.predicate_parser` parses. Does nothing without a parser.
+async fn collect_custom_statements(
+    metadata_links: Option<&Vec<ArtifactLink>>,
+    visited_uris: &mut HashSet<String>,
+    depth: u32,
+    fetchers: &Fetchers,
+    options: &TraversalOptions,
+) -> (Vec<CustomStatement>, Vec<(String, String)>) {
+    let mut statements = Vec::new();
+    let mut errors = Vec::new();
+    if options.predicate_parser.is_none() {
+        return (statements, errors);
+    }
+    for link in metadata_links
+        .into_iter()
+        .flatten()
+        .filter(|l| l.media_type.as_deref() == Some(IN_TOTO_MEDIA_TYPE) && !l.is_gated())
+    {
+        let Some(identity) = &link.expected_signer_identity else {
+            continue;
+        };
+        match process_attestation_uri(
+            &link.uri,
+            ExpectedSigners::sigstore(&[SignerIdentity::new(identity)]),
+            link.digest.as_ref(),
+            visited_uris,
+            depth,
+            fetchers,
+            options,
+        )
+        .await
+        {
+            Ok((ChainsightsPredicate::Custom(predicate), _, _)) => {
+                options.hooks().message(depth as usize + 2, Status::Verified, &format!(
+                    "Parsed {} predicate with plugin '{}'",
+                    predicate.kind, predicate.plugin
+                ));
+                statements.push(CustomStatement { uri: link.uri.clone(), signer_identity: identity.clone(), predicate: *predicate });
+            }
+            Ok(_) => {}
+            Err(e) => errors.push((link.uri.clone(), e.to_string())),
+        }
+    }
+    (statements, errors)
+}
+
+/// Fetches and verifies the freshness statement a root catalog links, checking that it names the
+/// catalog by digest and, if both carry one, by serial.
+async fn verify_freshness(
+    link: &AttestationLink,
+    catalog: &ChainsightsCatalogPredicate,
+    catalog_sha256: &str,
+    visited_uris: &mut HashSet<String>,
+    fetchers: &Fetchers,
+    options: &TraversalOptions,
+) -> Result<CatalogFreshness> {
+    options.check_link_scope(&link.uri)?;
+    // The statement is re-signed as time passes, so the link pins no digest.
+    let (predicate, _, verification) =
+        process_attestation_uri(&link.uri, ExpectedSigners::of(link, &link.accepted_identities()), None, visited_uris, 1, fetchers, options)
+            .await?;
+    let ChainsightsPredicate::Freshness(freshness) = predicate else {
+        anyhow::bail!("Expected Freshness predicate at '{}', found {}", link.uri, predicate.kind());
+    };
+    if !freshness.catalog.matches_sha256(catalog_sha256) {
+        anyhow::bail!("The freshness statement names another catalog than the one served (sha256:{})", catalog_sha256);
+    }
+    if let (Some(confirmed), Some(served)) = (freshness.serial, catalog.serial)
+        && confirmed != served
+    {
+        anyhow::bail!("The freshness statement confirms serial {}, but the catalog served has serial {}", confirmed, served);
+    }
+    Ok(CatalogFreshness {
+        uri: link.uri.clone(),
+        signer_identity: verification.identity,
+        confirmed_at: verification.signed_at.unwrap_or(freshness.timestamp),
+        expires: freshness.expires,
+    })
+}
+
+/// Follows a chain of sub-catalog names down from an already traversed catalog, traversing each
+/// selected sub-catalog in turn. Returns the aggregate of the innermost sub-catalog.
+#[cfg(feature = "cli")]
+pub(crate) async fn traverse_catalog_path(
+    root: AggregatedCatalogData,
+    catalog_path: &[String],
+    fetchers: &Fetchers,
+    options: &TraversalOptions,
+) -> Result<AggregatedCatalogData> {
+    let mut current = root;
+    for segment in catalog_path {
+        let catalog = current.catalog_predicate.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Cannot select sub-catalog '{}': {}",
+                segment,
+                current.root_error.as_deref().unwrap_or("no catalog was loaded")
+            )
+        })?;
+        let link = catalog
+            .sub_catalogs
+            .iter()
+            .flatten()
+            .find(|s| sub_catalog_matches(&s.name, segment))
+            .ok_or_else(|| anyhow::anyhow!("Catalog has no sub-catalog named '{}'", segment))?;
+        if let Some(revocation) = current
+            .revocation_predicate
+            .as_ref()
+            .and_then(|r| r.find(&link.catalog_attestation_link))
+        {
+            anyhow::bail!(
+                "Sub-catalog '{}' has been revoked: {}",
+                link.name,
+                revocation.reason.as_deref().unwrap_or("no reason given")
+            );
+        }
+        let uri = link.catalog_attestation_link.uri.clone();
+        options
+            .check_link_scope(&uri)
+            .with_context(|| format!("Cannot select sub-catalog '{}'", link.name))?;
+        let identities = link.catalog_attestation_link.accepted_identities();
+
+        options.hooks().message(0, Status::Info, &format!(
+            "Traversing sub-catalog '{}': {} with expected identity: {}",
+            link.name, uri, link.catalog_attestation_link.expected_signer_identity
+        ));
+        current = traverse_and_aggregate(&uri, &identities, fetchers, options)
+            .await
+            .with_context(|| format!("Traversal failed starting from {}", uri))?;
+    }
+    Ok(current)
+}
+
+/// Fetches, verifies and parses the statement at `uri`, reporting it to the traversal hooks.
 async fn process_attestation_uri(
     uri: &str,
-    expected_identity: &str,
+    signers: ExpectedSigners<'_>,
+    digest: Option<&HashMap<String, String>>,
     visited_uris: &mut HashSet<String>,
     depth: u32,
-    _client: &reqwest::Client,
-) -> Result<ChainsightsPredicate> {
+    fetchers: &Fetchers,
+    options: &TraversalOptions,
+) -> Result<(ChainsightsPredicate, String, NodeVerification)> {
+    options.hooks().node_discovered(uri);
+    let result = match claim_uri(uri, visited_uris, depth) {
+        Ok(()) => {
+            let attestation = load_attestation(uri, signers, digest, depth, fetchers, options);
+            #[cfg(feature = "cli")]
+            let attestation = traced(
+                options.tracer.as_deref(),
+                "attestation",
+                || vec![("chainsights.uri", uri.into()), ("chainsights.depth", depth.into())],
+                attestation,
+                |(predicate, _, _)| vec![("chainsights.kind", predicate.kind().into())],
+            );
+            attestation.await
+        }
+        Err(e) => Err(e),
+    };
+    let verified = result.as_ref().is_ok_and(|(_, _, verification)| verification.is_verified());
+    report_processed(uri, depth, &result, verified, options);
+    result
+}
+
+/// Reports the outcome of processing an attestation to the traversal hooks.
+fn report_processed<T>(uri: &str, depth: u32, result: &Result<T>, verified: bool, options: &TraversalOptions) {
+    let hooks = options.hooks();
+    if let Err(e) = result {
+        hooks.message(depth as usize + 1, Status::Failed, &format!("{}: {}", uri, e));
+    }
+    hooks.node_processed(uri, verified);
+}
+
+/// Checks that `uri` has not been visited and is within the depth limit, and marks it visited
+/// before anything is fetched.
+fn claim_uri(uri: &str, visited_uris: &mut HashSet<String>, depth: u32) -> Result<()> {
     if visited_uris.contains(uri) {
         return Err(anyhow::anyhow!(
             "Cycle detected: URI '{}' already visited",
@@ -178,23 +1053,212 @@ async fn process_attestation_uri(
             uri
         ));
     }
-    // Mark current URI as visited *before* the network call
     visited_uris.insert(uri.to_string());
+    Ok(())
+}
 
-    let manifest_text = fetch_manifest_text(uri)
+/// Who an attestation must be signed by: one of the accepted certificate identities for a
+/// Sigstore bundle, one of the PGP, SSH or minisign keys its link pins, or a key of one of the
+/// DIDs it accepts for a DSSE envelope. A root catalog may
+/// also be pinned to a key by DNS, which applies whatever it is signed with.
+#[derive(Clone, Copy)]
+struct ExpectedSigners<'a> {
+    identities: &'a [SignerIdentity],
+    pgp_fingerprints: &'a [String],
+    public_keys: &'a [String],
+    key_pins: &'a [String],
+}
+
+impl<'a> ExpectedSigners<'a> {
+    /// Signers of a Sigstore bundle reached without an attestation link, e.g. a root catalog.
+    fn sigstore(identities: &'a [SignerIdentity]) -> Self {
+        Self { identities, pgp_fingerprints: &[], public_keys: &[], key_pins: &[] }
+    }
+
+    /// Signers of a root catalog, which is key-signed if its identities include public keys and
+    /// must be signed by one of `key_pins` if there are any.
+    fn root(identities: &'a [SignerIdentity], public_keys: &'a [String], key_pins: &'a [String]) -> Self {
+        Self { identities, pgp_fingerprints: &[], public_keys, key_pins }
+    }
+
+    /// The signers `link` expects, given its accepted identities.
+    fn of(link: &'a AttestationLink, identities: &'a [SignerIdentity]) -> Self {
+        Self { identities, pgp_fingerprints: link.pgp_fingerprints(), public_keys: link.public_keys(), key_pins: &[] }
+    }
+
+    /// Checks the verified signing key against the pinned keys, if there are any.
+    fn check_key_pins(&self, uri: &str, verified: &VerifiedBundle) -> Result<()> {
+        if self.key_pins.is_empty() || self.key_pins.iter().any(|pin| matches_key_pin(pin, &verified.key_fingerprints)) {
+            return Ok(());
+        }
+        anyhow::bail!(
+            "'{}' is signed by key {}, not the key pinned in DNS ({})",
+            uri,
+            verified.key_fingerprints.first().map_or("(unknown)", String::as_str),
+            self.key_pins.join(", ")
+        )
+    }
+
+    /// The verifier for the keys pinned for the attestation at `uri`, or for the keys of the DIDs
+    /// it must be signed by, or None for a Sigstore bundle.
+    async fn verifier(
+        &self,
+        uri: &str,
+        fetchers: &Fetchers,
+        options: &TraversalOptions,
+    ) -> Result<Option<Box<dyn Verifier>>> {
+        if !self.pgp_fingerprints.is_empty() {
+            let keyring = options
+                .pgp_keyring
+                .clone()
+                .with_context(|| format!("'{}' is PGP-signed but no PGP keyring is configured", uri))?;
+            return Ok(Some(Box::new(PgpVerifier::new(keyring, self.pgp_fingerprints.to_vec()))));
+        }
+        if !self.public_keys.is_empty() {
+            let verifier = key_verifier(self.public_keys)
+                .with_context(|| format!("Invalid public keys pinned for '{}'", uri))?;
+            return Ok(Some(verifier));
+        }
+        let dids: Vec<&str> = self.identities.iter().map(|id| id.identity.as_str()).filter(|id| is_did(id)).collect();
+        if !dids.is_empty() {
+            if dids.len() != self.identities.len() {
+                anyhow::bail!("'{}' accepts both DIDs and certificate identities, but may only accept one kind", uri);
+            }
+            let mut keys = Vec::new();
+            for did in dids {
+                keys.extend(resolve_did(did, fetchers).await.with_context(|| format!("Failed to resolve '{}'", did))?);
+            }
+            return Ok(Some(Box::new(DidVerifier::new(keys))));
+        }
+        Ok(None)
+    }
+}
+
+/// The verification keys of a DID: the key a `did:key` encodes, or the assertion keys of the DID
+/// document a `did:web` publishes.
+async fn resolve_did(did: &str, fetchers: &Fetchers) -> Result<Vec<DidKey>> {
+    if did.starts_with("did:key:") {
+        return Ok(vec![did_key(did)?]);
+    }
+    let url = did_web_document_url(did)?;
+    let document = fetch_did_document(fetchers, &url)
         .await
-        .with_context(|| format!("Failed to fetch manifest text from URI '{}'", uri))?;
+        .with_context(|| format!("Failed to fetch the DID document from '{}'", url))?;
+    did_document_keys(did, &document)
+}
+
+/// Resolves a DNS key pin to the fingerprints it allows: the pin itself, or those of the public
+/// keys published at it if it is a URI.
+async fn resolve_key_pin(pin: Option<&str>, fetchers: &Fetchers) -> Result<Vec<String>> {
+    match pin {
+        None => Ok(Vec::new()),
+        Some(uri) if uri.contains("://") => {
+            let document = fetch_document_text(fetchers, uri)
+                .await
+                .with_context(|| format!("Failed to fetch the public key pinned in DNS from '{}'", uri))?;
+            published_key_fingerprints(&document).with_context(|| format!("Invalid public key at '{}'", uri))
+        }
+        Some(fingerprint) => Ok(vec![fingerprint.to_string()]),
+    }
+}
 
+/// A bundle whose signature has been verified, with the predicate parsed from its statement.
+pub(crate) struct ParsedAttestation {
+    pub verified: VerifiedBundle,
+    pub predicate: ChainsightsPredicate,
+    pub payload_sha256: String,
+}
 
-    let statement_payload = verify_signature_with_pae(&manifest_text, expected_identity)
-        .with_context(|| {
-            format!(
-                "Signature/identity verification failed for URI '{}' with expected identity '{}'",
-                uri, expected_identity
-            )
-        })?;
+/// Verifies a bundle and parses its statement: the CPU-bound part of processing a node, from
+/// certificate parsing and PAE construction to JSON parsing.
+pub(crate) fn verify_and_parse(
+    uri: &str,
+    manifest_text: &str,
+    expected_identities: &[SignerIdentity],
+    time_source: TimeSource,
+    clock_skew: chrono::Duration,
+    trusted_root: Option<&TrustedRoot>,
+    parser: Option<&dyn PredicateParser>,
+) -> Result<ParsedAttestation> {
+    let verified = match trusted_root {
+        Some(root) => verify_signature_with_trusted_root(manifest_text, expected_identities, time_source, clock_skew, root),
+        None => verify_signature_with_pae(manifest_text, expected_identities, time_source, clock_skew),
+    }
+    .with_context(|| {
+        format!(
+            "Signature/identity verification failed for URI '{}' with expected identity '{}'",
+            uri,
+            expected_identities
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("', '")
+        )
+    })?;
+    parse_verified(uri, verified, parser)
+}
 
-    let statement: InTotoStatement = serde_json::from_slice(&statement_payload) // Using from_slice since we already have bytes
+/// Verifies a key-signed statement, with its detached signature if it has one, and parses it.
+pub(crate) fn verify_key_signed_and_parse(
+    uri: &str,
+    document: &str,
+    signature: Option<&str>,
+    verifier: &dyn Verifier,
+    parser: Option<&dyn PredicateParser>,
+) -> Result<ParsedAttestation> {
+    let verified = verifier
+        .verify(document, signature)
+        .with_context(|| format!("Signature verification failed for URI '{}'", uri))?;
+    parse_verified(uri, verified, parser)
+}
+
+/// Verifies a bundle like [`verify_and_parse`], but for `--insecure-skip-identity` accepts any
+/// signer named in its certificate when none of `expected_identities` signed it. Returns whether
+/// the identity check was skipped.
+fn verify_any_identity_and_parse(
+    uri: &str,
+    manifest_text: &str,
+    expected_identities: &[SignerIdentity],
+    time_source: TimeSource,
+    clock_skew: chrono::Duration,
+    trusted_root: Option<&TrustedRoot>,
+    parser: Option<&dyn PredicateParser>,
+) -> Result<(ParsedAttestation, bool)> {
+    let strict = verify_and_parse(uri, manifest_text, expected_identities, time_source, clock_skew, trusted_root, parser);
+    let Err(e) = strict else {
+        return strict.map(|parsed| (parsed, false));
+    };
+    let named: Vec<SignerIdentity> = decode_bundle_unverified(manifest_text)
+        .map(|bundle| bundle.identities.iter().map(|identity| SignerIdentity::new(identity)).collect())
+        .unwrap_or_default();
+    if named.is_empty() {
+        return Err(e);
+    }
+    verify_and_parse(uri, manifest_text, &named, time_source, clock_skew, trusted_root, parser).map(|parsed| (parsed, true))
+}
+
+/// Parses a statement without verifying it, for `--insecure-skip-signature`: the payload of a
+/// Sigstore bundle, or an unsigned in-toto statement.
+fn parse_unverified(uri: &str, manifest_text: &str, parser: Option<&dyn PredicateParser>) -> Result<ParsedAttestation> {
+    let payload = match decode_bundle_unverified(manifest_text) {
+        Ok(bundle) => bundle.payload,
+        Err(_) => manifest_text.trim().as_bytes().to_vec(),
+    };
+    let verified = VerifiedBundle {
+        payload,
+        trusted_time: None,
+        signer_identity: "UNVERIFIED".to_string(),
+        rekor_entry: false,
+        extensions: Default::default(),
+        key_fingerprints: Vec::new(),
+    };
+    parse_verified(uri, verified, parser)
+}
+
+/// Parses the statement of a verified bundle or key-signed document. Predicate types Chainsights
+/// does not define are parsed with `parser`, if it parses them.
+fn parse_verified(uri: &str, verified: VerifiedBundle, parser: Option<&dyn PredicateParser>) -> Result<ParsedAttestation> {
+    let statement: InTotoStatement = serde_json::from_slice(&verified.payload) // Using from_slice since we already have bytes
        .with_context(|| format!("Failed to parse InTotoStatement JSON from URI '{}'", uri))?;
 
     let predicate = models::chainsights::parse_predicate(&statement).with_context(|| {
@@ -203,6 +1267,304 @@ async fn process_attestation_uri(
             uri
         )
     })?;
+    let predicate = match (predicate, parser.and_then(|parser| parser.parse(&statement))) {
+        (ChainsightsPredicate::Unknown { .. }, Some(custom)) => ChainsightsPredicate::Custom(Box::new(
+            custom.with_context(|| format!("Failed to parse the {} statement at URI '{}'", statement.predicate_type, uri))?,
+        )),
+        (predicate, _) => predicate,
+    };
 
-    Ok(predicate)
-}
\ No newline at end of file
+    let payload_sha256 = hex::encode(Sha256::digest(&verified.payload));
+    Ok(ParsedAttestation { verified, predicate, payload_sha256 })
+}
+
+/// Fetches an attestation already claimed with [`claim_uri`], then verifies and parses it on the
+/// blocking thread pool so that other nodes keep downloading in the meantime.
+async fn load_attestation(
+    uri: &str,
+    signers: ExpectedSigners<'_>,
+    digest: Option<&HashMap<String, String>>,
+    depth: u32,
+    fetchers: &Fetchers,
+    options: &TraversalOptions,
+) -> Result<(ChainsightsPredicate, String, NodeVerification)> {
+    // Catalogs sit at depth 0: the root, its pages and standalone statements. They are what
+    // repeated queries of a domain fetch again, so they go through the catalog cache if enabled.
+    // Key signatures cover the whole document rather than the first line of a jsonl file.
+    let insecure = options.insecure;
+    let verifier = match insecure.skip_signature {
+        true => None,
+        false => signers.verifier(uri, fetchers, options).await?,
+    };
+    let fetched = match depth {
+        _ if verifier.is_some() => fetch_document_text(fetchers, uri).await,
+        0 => fetch_catalog_text(fetchers, uri).await,
+        _ => fetch_manifest_text(fetchers, uri).await,
+    };
+    let manifest_text = match fetched {
+        Ok(text) => text,
+        Err(e) => {
+            let sha256 = digest.and_then(|d| d.get("sha256"));
+            match (&options.rekor_fallback, sha256) {
+                (Some(rekor), Some(sha256)) => {
+                    options.hooks().message(depth as usize + 2, Status::Warning, &format!(
+                        "Fetch failed for '{}' ({}), searching Rekor for sha256:{}",
+                        uri, e, sha256
+                    ));
+                    rekor.find_bundle_by_sha256(sha256).await.with_context(|| {
+                        format!("Failed to fetch manifest text from URI '{}' ({}) and Rekor fallback failed", uri, e)
+                    })?
+                }
+                _ => {
+                    return Err(e)
+                        .with_context(|| format!("Failed to fetch manifest text from URI '{}'", uri));
+                }
+            }
+        }
+    };
+    let mut manifest_len = manifest_text.len() as u64;
+
+    let key_signed = verifier.is_some();
+    let mut skipped_checks = Vec::new();
+    let verification = async {
+        if insecure.skip_signature {
+            skipped_checks.push(SkippedCheck::Signature);
+            parse_unverified(uri, &manifest_text, options.predicate_parser.as_deref())
+        } else if let Some(verifier) = verifier {
+            let signature = match verifier.signature_uri(uri, &manifest_text) {
+                Some(signature_uri) => {
+                    let signature = fetch_document_text(fetchers, &signature_uri)
+                        .await
+                        .with_context(|| format!("Failed to fetch detached signature from URI '{}'", signature_uri))?;
+                    manifest_len += signature.len() as u64;
+                    Some(signature)
+                }
+                None => None,
+            };
+            let uri = uri.to_string();
+            let parser = options.predicate_parser.clone();
+            tokio::task::spawn_blocking(move || {
+                verify_key_signed_and_parse(&uri, &manifest_text, signature.as_deref(), verifier.as_ref(), parser.as_deref())
+            })
+            .await
+            .context("Verification task failed")?
+        } else {
+            let uri = uri.to_string();
+            let expected_identities = signers.identities.to_vec();
+            let (time_source, clock_skew) = (options.time_source, options.clock_skew);
+            let trusted_root = options.trusted_root.clone();
+            let skip_identity = insecure.skip_identity;
+            let parser = options.predicate_parser.clone();
+            let (parsed, identity_skipped) = tokio::task::spawn_blocking(move || match skip_identity {
+                true => verify_any_identity_and_parse(&uri, &manifest_text, &expected_identities, time_source, clock_skew, trusted_root.as_deref(), parser.as_deref()),
+                false => verify_and_parse(&uri, &manifest_text, &expected_identities, time_source, clock_skew, trusted_root.as_deref(), parser.as_deref())
+                    .map(|parsed| (parsed, false)),
+            })
+            .await
+            .context("Verification task failed")??;
+            if identity_skipped {
+                skipped_checks.push(SkippedCheck::Identity);
+            }
+            Ok(parsed)
+        }
+    };
+    let started = std::time::Instant::now();
+    #[cfg(feature = "cli")]
+    let verification = {
+        let signature = match (insecure.skip_signature, key_signed) {
+            (true, _) => "none",
+            (false, true) => "key",
+            (false, false) => "sigstore",
+        };
+        traced(
+            options.tracer.as_deref(),
+            "verify",
+            || vec![("chainsights.uri", uri.into()), ("chainsights.signature", signature.into())],
+            verification,
+            |parsed| vec![("chainsights.signer", parsed.verified.signer_identity.clone().into())],
+        )
+    };
+    let parsed = verification.await;
+    if let Some(stats) = options.stats.as_ref().filter(|_| !insecure.skip_signature) {
+        stats.record_verification(started.elapsed());
+    }
+    let ParsedAttestation { verified, predicate, payload_sha256 } = parsed?;
+    // Key pins and build policies are checked against the signature, so they are skipped with it.
+    if !insecure.skip_signature {
+        signers.check_key_pins(uri, &verified)?;
+    }
+    if let (ChainsightsPredicate::Release(_), Some(policy), false) = (&predicate, &options.release_build_policy, insecure.skip_signature) {
+        policy
+            .check(&verified.extensions)
+            .with_context(|| format!("Release statement at '{}' violates the build policy", uri))?;
+    }
+
+    options.hooks().bytes_downloaded(manifest_len);
+    match skipped_checks.as_slice() {
+        [] => options
+            .hooks()
+            .message(depth as usize + 1, Status::Verified, &format!("{} signed by {}", uri, verified.signer_identity)),
+        [SkippedCheck::Signature, ..] => options
+            .hooks()
+            .message(depth as usize + 1, Status::Warning, &format!("UNVERIFIED: {} (signature not checked)", uri)),
+        [SkippedCheck::Identity, ..] => options.hooks().message(depth as usize + 1, Status::Warning, &format!(
+            "UNVERIFIED: {} signed by {}, which is not an expected identity",
+            uri, verified.signer_identity
+        )),
+    }
+    if let Some(signed_at) = verified.trusted_time {
+        options.hooks().message(depth as usize + 2, Status::Info, &format!(
+            "Signing time from {:?} time source: {}",
+            options.time_source,
+            signed_at.to_rfc3339()
+        ));
+    }
+
+    // Never judge expiry by a local clock that is behind the trusted signing time.
+    let mut now = chrono::Utc::now();
+    if let Some(signed_at) = verified.trusted_time {
+        if signed_at > now + options.clock_skew {
+            options.hooks().message(depth as usize + 2, Status::Warning, &format!(
+                "Local clock is behind the trusted signing time {} of '{}', using the signing time",
+                signed_at.to_rfc3339(),
+                uri
+            ));
+            now = signed_at;
+        }
+        if let Some(timestamp) = predicate.timestamp() {
+            let generated_at = chrono::DateTime::parse_from_rfc3339(timestamp)
+                .with_context(|| format!("Invalid timestamp '{}' in statement at URI '{}'", timestamp, uri))?;
+            if generated_at > signed_at + options.clock_skew {
+                anyhow::bail!(
+                    "Statement at URI '{}' claims to be generated at {}, after it was signed at {}",
+                    uri,
+                    timestamp,
+                    signed_at.to_rfc3339()
+                );
+            }
+        }
+    }
+
+    if let Some(expires) = predicate.expires() {
+        let expires_at = chrono::DateTime::parse_from_rfc3339(expires)
+            .with_context(|| format!("Invalid expires time '{}' in statement at URI '{}'", expires, uri))?;
+        if now > expires_at + options.clock_skew {
+            return Err(ExpiredAttestation {
+                uri: uri.to_string(),
+                expires: expires.to_string(),
+            }
+            .into());
+        }
+    }
+
+    let verification = NodeVerification {
+        method: match (insecure.skip_signature, key_signed) {
+            (true, _) => VerificationMethod::Unverified,
+            (false, true) => VerificationMethod::Key,
+            (false, false) => VerificationMethod::SigstoreKeyless,
+        },
+        identity: (!insecure.skip_signature).then_some(verified.signer_identity),
+        rekor_entry: verified.rekor_entry,
+        payload_sha256: Some(payload_sha256.clone()),
+        signed_at: verified.trusted_time.map(|t| t.to_rfc3339()),
+        time_source: options.time_source,
+        skipped_checks,
+    };
+    Ok((predicate, payload_sha256, verification))
+}
+
+#[cfg(test)]
+mod tests {
+    use chainsights_testing::{FixtureSpec, MockEnvironment};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn insecure_modes_mark_the_nodes_they_let_through() {
+        let env = MockEnvironment::start(FixtureSpec::new("example.com", "release@example.com").component("app", &["1.0.0"]))
+            .await
+            .unwrap();
+        let fetchers = Fetchers::new(reqwest::Client::new());
+        let options = |insecure| TraversalOptions { hooks: Some(Arc::new(SilentHooks)), insecure, ..Default::default() };
+
+        let imposter = [SignerIdentity::new("mallory@example.com")];
+        let strict = traverse_and_aggregate(env.root_uri(), &imposter, &fetchers, &options(InsecureMode::default())).await.unwrap();
+        assert!(strict.root_error.is_some());
+        let skip_identity = options(InsecureMode { skip_identity: true, ..Default::default() });
+        let data = traverse_and_aggregate(env.root_uri(), &imposter, &fetchers, &skip_identity).await.unwrap();
+        let root = data.verification.as_ref().unwrap();
+        assert_eq!(root.skipped_checks, [SkippedCheck::Identity]);
+        assert_eq!(root.identity.as_deref(), Some("release@example.com"));
+        assert!(data.components[0].verification.as_ref().unwrap().is_verified(), "signed by the identity the catalog expects");
+        assert_eq!(data.unverified_count(), 1);
+
+        // An unsigned root catalog: the bare statement instead of its bundle.
+        let root_path = env.root_uri().strip_prefix(&env.base_url()).unwrap().to_string();
+        let bundle = std::str::from_utf8(env.document(&root_path).unwrap()).unwrap();
+        env.replace(&root_path, decode_bundle_unverified(bundle).unwrap().payload).await.unwrap();
+        let publisher = [SignerIdentity::new(env.identity())];
+        let strict = traverse_and_aggregate(env.root_uri(), &publisher, &fetchers, &options(InsecureMode::default())).await.unwrap();
+        assert!(strict.root_error.is_some());
+        let skip_signature = options(InsecureMode { skip_signature: true, ..Default::default() });
+        let data = traverse_and_aggregate(env.root_uri(), &publisher, &fetchers, &skip_signature).await.unwrap();
+        let root = data.verification.as_ref().unwrap();
+        assert_eq!(root.skipped_checks, [SkippedCheck::Signature]);
+        assert_eq!(root.method, VerificationMethod::Unverified);
+        assert_eq!(root.identity, None);
+        assert_eq!(data.components.len(), 1);
+        assert_eq!(data.unverified_count(), 3, "every statement is unverified without signature checks");
+        assert!(serde_json::to_string(&data).unwrap().contains(r#""skipped_checks":["signature"]"#));
+    }
+    #[tokio::test]
+    async fn stats_count_the_requests_and_verifications_of_a_run() {
+        let env = MockEnvironment::start(FixtureSpec::new("example.com", "release@example.com").component("app", &["1.0.0"]))
+            .await
+            .unwrap();
+        let stats = Arc::new(StatsRecorder::new());
+        let fetchers = Fetchers::new(reqwest::Client::new())
+            .with_catalog_cache(std::time::Duration::from_secs(60))
+            .with_stats(Some(stats.clone()));
+        let options = TraversalOptions { hooks: Some(Arc::new(SilentHooks)), stats: Some(stats), ..Default::default() };
+        let publisher = [SignerIdentity::new(env.identity())];
+
+        let first = traverse_and_aggregate(env.root_uri(), &publisher, &fetchers, &options).await.unwrap();
+        let second = traverse_and_aggregate(env.root_uri(), &publisher, &fetchers, &options).await.unwrap();
+        let [host] = &first.stats.as_ref().unwrap().hosts[..] else { panic!("expected one host") };
+        assert_eq!((host.requests, host.failed_requests, host.cache_hits), (3, 0, 0));
+        assert!(host.bytes > 0);
+        assert_eq!(first.stats.as_ref().unwrap().verification.statements, 3);
+        let stats = second.stats.unwrap();
+        assert_eq!((stats.hosts[0].requests, stats.hosts[0].cache_hits), (5, 1), "the root catalog came from the cache");
+        assert_eq!(stats.verification.statements, 6, "cached catalogs are verified again");
+    }
+
+    struct ProprietaryParser;
+
+    impl PredicateParser for ProprietaryParser {
+        fn parse(&self, statement: &InTotoStatement) -> Option<Result<CustomPredicate>> {
+            (statement.predicate_type == "https://example.com/proprietary/v1").then(|| {
+                Ok(CustomPredicate {
+                    predicate_type: statement.predicate_type.clone(),
+                    plugin: "proprietary".to_string(),
+                    kind: "build".to_string(),
+                    details: Vec::new(),
+                })
+            })
+        }
+    }
+
+    #[test]
+    fn predicate_parsers_parse_only_types_chainsights_does_not_define() {
+        let statement = |predicate_type: &str, predicate| {
+            serde_json::to_string(&InTotoStatement::new("app", Default::default(), predicate_type, predicate)).unwrap()
+        };
+        let proprietary = statement("https://example.com/proprietary/v1", serde_json::json!({}));
+        let parsed = parse_unverified("app.json", &proprietary, Some(&ProprietaryParser)).unwrap();
+        let ChainsightsPredicate::Custom(custom) = parsed.predicate else { panic!("expected a custom predicate") };
+        assert_eq!((custom.plugin.as_str(), custom.kind.as_str()), ("proprietary", "build"));
+        assert!(matches!(parse_unverified("app.json", &proprietary, None).unwrap().predicate, ChainsightsPredicate::Unknown { .. }));
+
+        let other = statement("https://example.com/other/v1", serde_json::json!({}));
+        assert!(matches!(parse_unverified("app.json", &other, Some(&ProprietaryParser)).unwrap().predicate, ChainsightsPredicate::Unknown { .. }));
+    }
+}
@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail};
+use chainsights_core::identity::SignerIdentity;
+use chainsights_core::statement::InTotoStatement;
+use chainsights_core::timestamp::TimeSource;
+use chainsights_core::trust::TrustedRoot;
+use chainsights_core::verifier::Verifier;
+use chrono::{DateTime, SecondsFormat, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::generate::generator;
+use crate::models::chainsights::{ChainsightsPredicate, ChainsightsTrustSnapshotPredicate, TRUST_SNAPSHOT_V1};
+use crate::traversal::{ParsedAttestation, verify_and_parse, verify_key_signed_and_parse};
+
+/// Subject name of trust snapshot statements.
+const SNAPSHOT_SUBJECT: &str = "trusted_root.json";
+
+/// Builds the unsigned statement of a trust snapshot. Its subject is the sha256 of the trusted
+/// root as serialized in the predicate.
+pub(crate) fn snapshot_statement(
+    trusted_root: TrustedRoot,
+    source: &str,
+    expires: Option<DateTime<Utc>>,
+) -> Result<InTotoStatement> {
+    let digest = hex::encode(Sha256::digest(serde_json::to_vec(&trusted_root)?));
+    let predicate = ChainsightsTrustSnapshotPredicate {
+        generator: Some(generator()),
+        timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        expires: expires.map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        source: source.to_string(),
+        trusted_root,
+    };
+    Ok(InTotoStatement::new(
+        SNAPSHOT_SUBJECT,
+        HashMap::from([("sha256".to_string(), digest)]),
+        TRUST_SNAPSHOT_V1,
+        serde_json::to_value(predicate)?,
+    ))
+}
+
+/// Who must have signed a statement that configures the client itself, such as a trust snapshot.
+pub(crate) enum ExpectedSigner<'a> {
+    /// A Sigstore bundle signed by one of `identities`, whose certificate chains to `trusted_root`
+    /// if there is one
+    Sigstore {
+        identities: &'a [SignerIdentity],
+        trusted_root: Option<&'a TrustedRoot>,
+    },
+    /// A statement signed by a pinned key, with its detached signature
+    Key { verifier: &'a dyn Verifier, signature: Option<&'a str> },
+}
+
+impl ExpectedSigner<'_> {
+    /// True if the signature is checked against trust the client already holds: a pinned key or a
+    /// trusted root, rather than only the identity a certificate claims.
+    pub(crate) fn is_anchored(&self) -> bool {
+        !matches!(self, ExpectedSigner::Sigstore { trusted_root: None, .. })
+    }
+}
+
+/// The predicate of a signed statement that configures the client, which stops applying once it
+/// expires.
+pub(crate) trait SignedConfiguration: Sized {
+    /// What the statement is called in errors, e.g. "trust snapshot"
+    const KIND: &'static str;
+
+    fn from_predicate(predicate: ChainsightsPredicate) -> Option<Self>;
+
+    fn expires(&self) -> Option<&str>;
+}
+
+impl SignedConfiguration for ChainsightsTrustSnapshotPredicate {
+    const KIND: &'static str = "trust snapshot";
+
+    fn from_predicate(predicate: ChainsightsPredicate) -> Option<Self> {
+        match predicate {
+            ChainsightsPredicate::TrustSnapshot(snapshot) => Some(*snapshot),
+            _ => None,
+        }
+    }
+
+    fn expires(&self) -> Option<&str> {
+        self.expires.as_deref()
+    }
+}
+
+/// Verifies a signed statement against `signer` and returns its predicate. Fails for predicates
+/// of another kind and for expired statements.
+pub(crate) fn open_signed_statement<T: SignedConfiguration>(name: &str, document: &str, signer: &ExpectedSigner) -> Result<T> {
+    let ParsedAttestation { predicate, .. } = match signer {
+        ExpectedSigner::Sigstore { identities, trusted_root } => {
+            verify_and_parse(name, document, identities, TimeSource::Local, chrono::Duration::zero(), *trusted_root, None)?
+        }
+        ExpectedSigner::Key { verifier, signature } => verify_key_signed_and_parse(name, document, *signature, *verifier, None)?,
+    };
+    let kind = predicate.kind().to_string();
+    let Some(statement) = T::from_predicate(predicate) else {
+        bail!("'{}' is a {} statement, not a {}", name, kind, T::KIND);
+    };
+    if let Some(expires) = statement.expires() {
+        let expires = DateTime::parse_from_rfc3339(expires).with_context(|| format!("Invalid {} expiry '{}'", T::KIND, expires))?;
+        if expires < Utc::now() {
+            bail!("The {} '{}' expired at {}; issue a fresh one", T::KIND, name, expires.to_rfc3339());
+        }
+    }
+    Ok(statement)
+}
+
+/// Verifies a signed trust snapshot and returns its predicate. The snapshot decides which
+/// certificates are trusted, so a certificate alone cannot vouch for it: it must be signed by a
+/// pinned key or chain to a root that is already trusted.
+pub(crate) fn open_trust_snapshot(
+    name: &str,
+    document: &str,
+    signer: &ExpectedSigner,
+) -> Result<ChainsightsTrustSnapshotPredicate> {
+    if !signer.is_anchored() {
+        bail!(
+            "Trust snapshot '{}' must be verified with a pinned key or against an already trusted root, not only its signer's identity",
+            name
+        );
+    }
+    open_signed_statement(name, document, signer)
+}
+
+#[cfg(test)]
+mod tests {
+    use chainsights_core::verifier::key_verifier;
+    use chainsights_testing::{EphemeralSigner, PrivateSigstore, SshSigner};
+
+    use super::*;
+
+    #[test]
+    fn snapshots_open_against_a_trusted_root_until_they_expire() {
+        let sigstore = PrivateSigstore::new().unwrap();
+        let trusted_root = sigstore.trusted_root();
+        let signer = sigstore.signer("security@example.test").unwrap();
+        let identities = [SignerIdentity::new("security@example.test")];
+        let trusted = ExpectedSigner::Sigstore { identities: &identities, trusted_root: Some(&trusted_root) };
+        let sign = |expires| {
+            let statement = snapshot_statement(trusted_root.clone(), "trusted_root.json", expires).unwrap();
+            signer.sign(&statement).unwrap().0
+        };
+
+        let bundle = sign(Some(Utc::now() + chrono::Duration::days(30)));
+        let snapshot = open_trust_snapshot("snapshot.jsonl", &bundle, &trusted).unwrap();
+        assert_eq!(snapshot.source, "trusted_root.json");
+        assert_eq!(snapshot.trusted_root.certificate_authorities.len(), 1);
+        let others = [SignerIdentity::new("other@example.test")];
+        let other = ExpectedSigner::Sigstore { identities: &others, trusted_root: Some(&trusted_root) };
+        assert!(open_trust_snapshot("snapshot.jsonl", &bundle, &other).is_err());
+
+        let expired = sign(Some(Utc::now() - chrono::Duration::days(1)));
+        let error = open_trust_snapshot("snapshot.jsonl", &expired, &trusted).unwrap_err();
+        assert!(error.to_string().contains("expired"), "{}", error);
+    }
+
+    #[test]
+    fn snapshots_need_a_pinned_key_or_a_trusted_root() {
+        let trusted_root = PrivateSigstore::new().unwrap().trusted_root();
+        let statement = snapshot_statement(trusted_root.clone(), "trusted_root.json", None).unwrap();
+        let identities = [SignerIdentity::new("security@example.test")];
+        let bundle = EphemeralSigner::new("security@example.test").unwrap().sign(&statement).unwrap().0;
+        let unanchored = ExpectedSigner::Sigstore { identities: &identities, trusted_root: None };
+        let error = open_trust_snapshot("snapshot.jsonl", &bundle, &unanchored).unwrap_err();
+        assert!(error.to_string().contains("pinned key"), "{}", error);
+        let self_signed = ExpectedSigner::Sigstore { identities: &identities, trusted_root: Some(&trusted_root) };
+        assert!(open_trust_snapshot("snapshot.jsonl", &bundle, &self_signed).is_err());
+
+        let key = SshSigner::new();
+        let document = serde_json::to_string_pretty(&statement).unwrap();
+        let signature = key.sign(document.as_bytes());
+        let verifier = key_verifier(&[key.public_key()]).unwrap();
+        let pinned = ExpectedSigner::Key { verifier: &*verifier, signature: Some(&signature) };
+        assert_eq!(open_trust_snapshot("snapshot.json", &document, &pinned).unwrap().source, "trusted_root.json");
+        let other = key_verifier(&[SshSigner::new().public_key()]).unwrap();
+        let unpinned = ExpectedSigner::Key { verifier: &*other, signature: Some(&signature) };
+        assert!(open_trust_snapshot("snapshot.json", &document, &unpinned).is_err());
+    }
+}
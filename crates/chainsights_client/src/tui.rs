@@ -0,0 +1,532 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! An interactive terminal browser of traversal results: the catalog, its components, their
+//! releases and the artifacts those link, as a tree with verification badges and a detail pane.
+//! Artifacts are only fetched when asked for.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use ratatui::Frame;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+
+use crate::fetch::{Fetchers, verify_digests};
+use crate::models::aggregation::{AggregatedCatalogData, AggregatedComponentData, AggregatedReleaseData, CrossDomainLink, NodeVerification, Provenance};
+use crate::models::chainsights::{ArtifactLink, RevokedAttestation};
+
+/// How many lines of a fetched text artifact are shown.
+const PREVIEW_LINES: usize = 40;
+
+/// The state of a node, shown as a badge before its label.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Badge {
+    Verified,
+    Failed,
+    Expired,
+    Revoked,
+    /// Not verified: an artifact not fetched yet, or a node from a source without signatures.
+    Unverified,
+    /// An access-controlled artifact, which is never fetched.
+    Gated,
+}
+
+impl Badge {
+    fn span(self) -> Span<'static> {
+        let (symbol, color) = match self {
+            Badge::Verified => ("✓", Color::Green),
+            Badge::Failed => ("✗", Color::Red),
+            Badge::Expired => ("⌛", Color::Yellow),
+            Badge::Revoked => ("⊘", Color::Yellow),
+            Badge::Unverified => ("·", Color::DarkGray),
+            Badge::Gated => ("🔒", Color::Cyan),
+        };
+        Span::styled(symbol, Style::default().fg(color).add_modifier(Modifier::BOLD))
+    }
+
+    fn of(verification: &Option<NodeVerification>, revocation: &Option<RevokedAttestation>) -> Self {
+        match (verification, revocation) {
+            (_, Some(_)) => Badge::Revoked,
+            (Some(verification), None) if verification.is_verified() => Badge::Verified,
+            (_, None) => Badge::Unverified,
+        }
+    }
+}
+
+struct Node {
+    label: String,
+    badge: Badge,
+    details: Vec<(String, String)>,
+    children: Vec<Node>,
+    /// The link of an artifact node, which can be fetched.
+    artifact: Option<ArtifactLink>,
+    expanded: bool,
+}
+
+impl Node {
+    fn new(label: impl Into<String>, badge: Badge) -> Self {
+        Self { label: label.into(), badge, details: Vec::new(), children: Vec::new(), artifact: None, expanded: false }
+    }
+
+    fn detail(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.details.push((key.to_string(), value.into()));
+        self
+    }
+
+    fn optional(self, key: &str, value: Option<impl Into<String>>) -> Self {
+        match value {
+            Some(value) => self.detail(key, value),
+            None => self,
+        }
+    }
+
+    fn verification(self, verification: &Option<NodeVerification>) -> Self {
+        let Some(verification) = verification else {
+            return self.detail("Verification", "none (not signed, or taken from an unsigned source)");
+        };
+        let method = serde_json::to_value(verification.method).ok().and_then(|m| m.as_str().map(str::to_string));
+        let skipped: Vec<String> = verification
+            .skipped_checks
+            .iter()
+            .filter_map(|check| serde_json::to_value(check).ok()?.as_str().map(str::to_string))
+            .collect();
+        self.optional("Verification", method)
+            .optional("UNVERIFIED, not checked", (!skipped.is_empty()).then(|| skipped.join(", ")))
+            .optional("Signer", verification.identity.clone())
+            .detail("Rekor entry", if verification.rekor_entry { "yes" } else { "no" })
+            .optional("Signed at", verification.signed_at.clone())
+            .optional("Payload sha256", verification.payload_sha256.clone())
+    }
+
+    fn cross_domain(self, cross_domain: &Option<CrossDomainLink>) -> Self {
+        let Some(cross_domain) = cross_domain else {
+            return self;
+        };
+        self.detail("CROSS-DOMAIN", cross_domain.domain.clone())
+            .detail("Corroborated by", format!("{} ({})", cross_domain.corroborated_by, cross_domain.identity))
+    }
+
+    fn revocation(self, revocation: &Option<RevokedAttestation>) -> Self {
+        let Some(revocation) = revocation else {
+            return self;
+        };
+        self.detail("Revoked", revocation.reason.clone().unwrap_or_else(|| "yes".to_string()))
+            .optional("Revoked at", revocation.revoked_at.clone())
+    }
+
+    /// A failed statement or artifact: its URI and the error.
+    fn failure(badge: Badge, (uri, error): &(String, String)) -> Self {
+        Node::new(uri.clone(), badge).detail("URI", uri.clone()).detail("Error", error.clone())
+    }
+
+    fn artifact(link: &ArtifactLink, platform: Option<String>) -> Self {
+        let name = link.uri.rsplit('/').find(|segment| !segment.is_empty()).unwrap_or(&link.uri).to_string();
+        let digest = link.digest.as_ref().map(|digest| {
+            let mut digests: Vec<String> = digest.iter().map(|(algorithm, value)| format!("{}:{}", algorithm, value)).collect();
+            digests.sort();
+            digests.join(", ")
+        });
+        let mut node = Node::new(name, if link.is_gated() { Badge::Gated } else { Badge::Unverified })
+            .detail("URI", link.uri.clone())
+            .optional("Platform", platform)
+            .optional("Media type", link.media_type.clone())
+            .optional("Digest", digest)
+            .optional("Expected signer", link.expected_signer_identity.clone());
+        if link.is_gated() {
+            node = node.detail("Access", link.access_instructions());
+        }
+        node.artifact = Some(link.clone());
+        node
+    }
+}
+
+fn catalog_node(domain: &str, data: &AggregatedCatalogData) -> Node {
+    let Some(catalog) = &data.catalog_predicate else {
+        let error = data.root_error.clone().unwrap_or_else(|| "No root catalog".to_string());
+        let mut node = Node::new(domain, Badge::Failed).detail("Error", error);
+        node.expanded = true;
+        return node;
+    };
+    let mut node = Node::new(domain, Badge::of(&data.verification, &None))
+        .detail("Catalog timestamp", catalog.timestamp.clone())
+        .optional("Expires", catalog.expires.clone())
+        .optional("Serial", catalog.serial.map(|serial| serial.to_string()))
+        .verification(&data.verification)
+        .optional("Discovered through", data.discovery.as_ref().map(|d| d.source.clone()))
+        .optional("Confirmed current at", data.freshness.as_ref().map(|f| f.confirmed_at.clone()))
+        .optional("Freshness error", data.freshness_error.clone())
+        .optional("Relayed from", data.relayed_from.as_ref().map(|r| r.verifier_id.clone()));
+    node.children = data.components.iter().map(component_node).collect();
+    node.children.extend(data.component_errors.iter().map(|e| Node::failure(Badge::Failed, e)));
+    node.children.extend(data.expired_components.iter().map(|e| Node::failure(Badge::Expired, e)));
+    node.children.extend(
+        data.integrity_errors
+            .iter()
+            .map(|error| Node::failure(Badge::Failed, &(error.uri.clone(), error.to_string()))),
+    );
+    node.expanded = true;
+    node
+}
+
+fn linked_from(provenance: &Option<Provenance>) -> Option<String> {
+    provenance.as_ref()?.linked_from.as_ref().map(|source| source.uri.clone())
+}
+
+fn component_node(component: &AggregatedComponentData) -> Node {
+    let predicate = component.component_predicate.as_deref();
+    let label = predicate.map_or(component.component_link_uri.clone(), |c| c.name.clone());
+    let mut node = Node::new(label, Badge::of(&component.verification, &component.revocation))
+        .optional("PURL", predicate.map(|c| c.purl.clone()))
+        .optional("Description", predicate.and_then(|c| c.description.clone()))
+        .optional("License", predicate.and_then(|c| c.license.clone()))
+        .detail("Statement", component.component_link_uri.clone())
+        .optional("Linked from", linked_from(&component.provenance))
+        .verification(&component.verification)
+        .cross_domain(&component.cross_domain)
+        .revocation(&component.revocation);
+    node.children = component.releases.iter().map(release_node).collect();
+    node.children.extend(component.release_errors.iter().map(|e| Node::failure(Badge::Failed, e)));
+    node.children.extend(component.expired_releases.iter().map(|e| Node::failure(Badge::Expired, e)));
+    node.children.extend(
+        component
+            .integrity_errors
+            .iter()
+            .map(|error| Node::failure(Badge::Failed, &(error.uri.clone(), error.to_string()))),
+    );
+    node
+}
+
+fn release_node(release: &AggregatedReleaseData) -> Node {
+    let predicate = release.release_predicate.as_deref();
+    let label = predicate.map_or(release.release_link_uri.clone(), |r| r.name.clone());
+    let mut node = Node::new(label, Badge::of(&release.verification, &release.revocation))
+        .optional("PURL", predicate.map(|r| r.purl.clone()))
+        .optional("Release date", predicate.and_then(|r| r.release_date.clone()))
+        .optional("Lifecycle phase", predicate.and_then(|r| r.lifecycle_phase.clone()))
+        .optional("License", release.license.clone())
+        .optional("CPE", release.cpe.clone())
+        .detail("Statement", release.release_link_uri.clone())
+        .optional("Linked from", linked_from(&release.provenance))
+        .verification(&release.verification)
+        .cross_domain(&release.cross_domain)
+        .revocation(&release.revocation);
+    node.children = release.metadata_artifacts.iter().map(|link| Node::artifact(link, None)).collect();
+    if let Some(predicate) = predicate {
+        node.children.extend(predicate.artifacts.iter().flatten().map(|link| Node::artifact(link, None)));
+        for group in predicate.artifact_groups.iter().flatten() {
+            node.children.extend(group.artifacts.iter().map(|link| Node::artifact(link, Some(group.platform.to_string()))));
+        }
+    }
+    node.children.extend(release.artifact_fetch_errors.iter().map(|e| Node::failure(Badge::Failed, e)));
+    node
+}
+
+/// What fetching an artifact on demand found.
+struct FetchedArtifact {
+    size: usize,
+    /// Whether the artifact matched a digest its link pins; false if the link pins none.
+    digest_verified: bool,
+    /// The first lines of a text artifact.
+    preview: Option<String>,
+}
+
+async fn fetch_artifact(link: &ArtifactLink, fetchers: &Fetchers) -> Result<FetchedArtifact> {
+    let bytes = fetchers.fetch(&link.uri).await.with_context(|| format!("Failed to fetch '{}'", link.uri))?;
+    let digest_verified = match &link.digest {
+        Some(digest) => verify_digests(&link.uri, &bytes, digest)?,
+        None => false,
+    };
+    let preview = std::str::from_utf8(&bytes)
+        .ok()
+        .map(|text| text.lines().take(PREVIEW_LINES).collect::<Vec<_>>().join("\n"));
+    Ok(FetchedArtifact { size: bytes.len(), digest_verified, preview })
+}
+
+/// What a key press asks of the event loop.
+#[derive(Debug)]
+enum Action {
+    None,
+    Quit,
+    Fetch(ArtifactLink),
+}
+
+/// The tree being browsed and the selection in it.
+struct Browser {
+    root: Node,
+    /// Index of the selected row among the visible rows.
+    selected: usize,
+    /// Results of the artifacts fetched so far, by URI.
+    fetched: HashMap<String, Result<FetchedArtifact, String>>,
+    status: String,
+}
+
+impl Browser {
+    fn new(domain: &str, data: &AggregatedCatalogData) -> Self {
+        Self { root: catalog_node(domain, data), selected: 0, fetched: HashMap::new(), status: String::new() }
+    }
+
+    /// The visible rows, in order: each node's depth and its path of child indices from the root.
+    fn rows(&self) -> Vec<(usize, Vec<usize>)> {
+        fn visit(node: &Node, path: Vec<usize>, rows: &mut Vec<(usize, Vec<usize>)>) {
+            rows.push((path.len(), path.clone()));
+            if node.expanded {
+                for (index, child) in node.children.iter().enumerate() {
+                    let mut child_path = path.clone();
+                    child_path.push(index);
+                    visit(child, child_path, rows);
+                }
+            }
+        }
+        let mut rows = Vec::new();
+        visit(&self.root, Vec::new(), &mut rows);
+        rows
+    }
+
+    fn node(&self, path: &[usize]) -> &Node {
+        path.iter().fold(&self.root, |node, &index| &node.children[index])
+    }
+
+    fn node_mut(&mut self, path: &[usize]) -> &mut Node {
+        path.iter().fold(&mut self.root, |node, &index| &mut node.children[index])
+    }
+
+    fn selected_path(&self) -> Vec<usize> {
+        self.rows().swap_remove(self.selected).1
+    }
+
+    /// The badge of a node, or of an artifact once it has been fetched.
+    fn badge(&self, node: &Node) -> Badge {
+        match node.artifact.as_ref().and_then(|link| self.fetched.get(&link.uri)) {
+            Some(Ok(fetched)) if fetched.digest_verified => Badge::Verified,
+            Some(Ok(_)) => Badge::Unverified,
+            Some(Err(_)) => Badge::Failed,
+            None => node.badge,
+        }
+    }
+
+    fn handle(&mut self, key: KeyCode) -> Action {
+        let rows = self.rows().len();
+        let path = self.selected_path();
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1).min(rows - 1),
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Home | KeyCode::Char('g') => self.selected = 0,
+            KeyCode::End | KeyCode::Char('G') => self.selected = rows - 1,
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => {
+                let node = self.node_mut(&path);
+                if !node.children.is_empty() {
+                    node.expanded = true;
+                } else if let Some(link) = node.artifact.clone() {
+                    return self.fetch(link);
+                }
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                let node = self.node_mut(&path);
+                if node.expanded && !node.children.is_empty() {
+                    node.expanded = false;
+                } else if let Some((_, parent)) = path.split_last() {
+                    let parent = parent.to_vec();
+                    self.node_mut(&parent).expanded = false;
+                    self.selected = self.rows().iter().position(|(_, row)| *row == parent).unwrap_or(0);
+                }
+            }
+            KeyCode::Char('f') => {
+                if let Some(link) = self.node(&path).artifact.clone() {
+                    return self.fetch(link);
+                }
+                self.status = "Only artifacts can be fetched".to_string();
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn fetch(&mut self, link: ArtifactLink) -> Action {
+        if link.is_gated() {
+            self.status = link.access_instructions();
+            return Action::None;
+        }
+        self.status = format!("Fetching {}", link.uri);
+        Action::Fetch(link)
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let [main, help] = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [tree, details] = Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]).areas(main);
+
+        let rows = self.rows();
+        let items: Vec<ListItem> = rows
+            .iter()
+            .map(|(depth, path)| {
+                let node = self.node(path);
+                let marker = match (node.children.is_empty(), node.expanded) {
+                    (true, _) => "  ",
+                    (false, true) => "▾ ",
+                    (false, false) => "▸ ",
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw("  ".repeat(*depth)),
+                    Span::raw(marker),
+                    self.badge(node).span(),
+                    Span::raw(" "),
+                    Span::raw(node.label.clone()),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(" Attestations "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, tree, &mut ListState::default().with_selected(Some(self.selected)));
+
+        let node = self.node(&rows[self.selected].1);
+        let mut lines: Vec<Line> = node
+            .details
+            .iter()
+            .map(|(key, value)| {
+                Line::from(vec![Span::styled(format!("{}: ", key), Style::default().add_modifier(Modifier::BOLD)), Span::raw(value.clone())])
+            })
+            .collect();
+        if let Some(link) = &node.artifact {
+            lines.push(Line::default());
+            match self.fetched.get(&link.uri) {
+                None if !link.is_gated() => lines.push(Line::from("Press f to fetch")),
+                None => {}
+                Some(Err(error)) => lines.push(Line::styled(error.clone(), Style::default().fg(Color::Red))),
+                Some(Ok(fetched)) => {
+                    let integrity = match (fetched.digest_verified, &link.digest) {
+                        (true, _) => "digest verified",
+                        (false, None) => "no digest to verify",
+                        (false, Some(_)) => "no supported digest to verify",
+                    };
+                    lines.push(Line::from(format!("Fetched {} bytes, {}", fetched.size, integrity)));
+                    if let Some(preview) = &fetched.preview {
+                        lines.push(Line::default());
+                        lines.extend(preview.lines().map(|line| Line::from(line.to_string())));
+                    }
+                }
+            }
+        }
+        let details_pane = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title(" Details "))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(details_pane, details);
+
+        let help_line = match self.status.is_empty() {
+            true => "↑↓ move  → expand  ← collapse  f fetch artifact  q quit".to_string(),
+            false => self.status.clone(),
+        };
+        frame.render_widget(Paragraph::new(help_line).style(Style::default().fg(Color::DarkGray)), help);
+    }
+}
+
+/// Browses the aggregate of `domain` until the user quits.
+pub(crate) async fn browse(domain: &str, data: &AggregatedCatalogData, fetchers: &Fetchers) -> Result<()> {
+    let mut browser = Browser::new(domain, data);
+    let mut terminal = ratatui::init();
+    let result = async {
+        loop {
+            terminal.draw(|frame| browser.render(frame))?;
+            if !event::poll(Duration::from_millis(250))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match browser.handle(key.code) {
+                Action::None => {}
+                Action::Quit => return Ok(()),
+                Action::Fetch(link) => {
+                    terminal.draw(|frame| browser.render(frame))?;
+                    let fetched = fetch_artifact(&link, fetchers).await.map_err(|e| format!("{:#}", e));
+                    browser.status = String::new();
+                    browser.fetched.insert(link.uri, fetched);
+                }
+            }
+        }
+    }
+    .await;
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use serde_json::json;
+
+    use super::*;
+
+    fn screen(browser: &Browser) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+        terminal.draw(|frame| browser.render(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer.content().chunks(buffer.area.width as usize).map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>() + "\n").collect()
+    }
+
+    #[test]
+    fn browses_the_tree_and_requests_artifacts() {
+        let data: AggregatedCatalogData = serde_json::from_value(json!({
+            "catalog_predicate": { "timestamp": "2025-06-01T00:00:00Z", "serial": 3, "components": [] },
+            "verification": { "method": "sigstore-keyless", "identity": "security@example.com" },
+            "components": [{
+                "component_predicate": {
+                    "timestamp": "2025-06-01T00:00:00Z",
+                    "name": "app",
+                    "purl": "pkg:chainsights/example.com/app",
+                    "repositories": [],
+                    "subComponents": null,
+                    "releaseAttestations": [],
+                },
+                "verification": { "method": "sigstore-keyless", "identity": "security@example.com" },
+                "releases": [{
+                    "release_predicate": { "timestamp": "2025-06-01T00:00:00Z", "purl": "pkg:chainsights/example.com/app@1.0.0", "name": "app 1.0.0" },
+                    "metadata_artifacts": [
+                        { "uri": "https://example.com/app/1.0.0/sbom.json", "digest": { "sha256": "ab" } },
+                        { "uri": "https://example.com/app/1.0.0/pentest.pdf", "access": "gated", "requestUrl": "https://example.com/request" },
+                    ],
+                    "release_link_uri": "https://example.com/app/1.0.0.jsonl",
+                    "artifact_fetch_errors": [],
+                }],
+                "component_link_uri": "https://example.com/app.jsonl",
+                "release_errors": [],
+            }],
+            "root_error": null,
+            "component_errors": [["https://example.com/lib.jsonl", "bad signature"]],
+        }))
+        .unwrap();
+        let mut browser = Browser::new("example.com", &data);
+        let text = screen(&browser);
+        assert!(text.contains("▾ ✓ example.com"), "{}", text);
+        assert!(text.contains("▸ ✓ app"));
+        assert!(text.contains("✗ https://example.com/lib.jsonl"));
+        assert!(text.contains("Serial: 3"));
+
+        for key in [KeyCode::Down, KeyCode::Right, KeyCode::Down, KeyCode::Right, KeyCode::Down] {
+            assert!(matches!(browser.handle(key), Action::None));
+        }
+        assert!(screen(&browser).contains("Press f to fetch"));
+        let Action::Fetch(link) = browser.handle(KeyCode::Char('f')) else {
+            panic!("expected a fetch of the SBOM");
+        };
+        assert_eq!(link.uri, "https://example.com/app/1.0.0/sbom.json");
+        browser.fetched.insert(link.uri, Ok(FetchedArtifact { size: 2, digest_verified: true, preview: Some("{}".to_string()) }));
+        assert!(screen(&browser).contains("Fetched 2 bytes, digest verified"));
+
+        browser.handle(KeyCode::Down);
+        assert!(matches!(browser.handle(KeyCode::Enter), Action::None), "gated artifacts are not fetched");
+        assert!(browser.status.contains("request access at https://example.com/request"));
+
+        browser.handle(KeyCode::Left);
+        assert!(screen(&browser).contains("▸ · app 1.0.0"));
+        assert!(matches!(browser.handle(KeyCode::Char('q')), Action::Quit));
+    }
+}
@@ -0,0 +1,262 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! A Kubernetes validating admission webhook that admits workloads only if every container image
+//! is pinned by digest and attested to by a supplier release, as decided by an
+//! [`AdmissionVerifier`].
+
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::{self, pki_types::pem::PemObject};
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+use crate::admission::{AdmissionVerifier, Verdict};
+use crate::output::{self, Status};
+
+/// Largest request accepted; the API server limits objects to about 3 MiB.
+const MAX_REQUEST_BYTES: usize = 4 * 1024 * 1024;
+
+/// Serves the webhook at `/validate`, and a readiness check at `/healthz`, until the process
+/// exits. Without a certificate and key it serves plain HTTP, for use behind a TLS proxy.
+pub(crate) async fn serve(addr: SocketAddr, verifier: Arc<AdmissionVerifier>, tls: Option<(&Path, &Path)>) -> Result<()> {
+    let acceptor = tls.map(|(cert, key)| tls_acceptor(cert, key)).transpose()?;
+    let listener = TcpListener::bind(addr).await.with_context(|| format!("Failed to listen on {}", addr))?;
+    let scheme = if acceptor.is_some() { "https" } else { "http" };
+    output::print(0, Status::Info, &format!("Serving admission reviews at {}://{}/validate", scheme, listener.local_addr()?));
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let (verifier, acceptor) = (verifier.clone(), acceptor.clone());
+        tokio::spawn(async move {
+            match acceptor {
+                Some(acceptor) => {
+                    if let Ok(stream) = acceptor.accept(stream).await {
+                        handle(stream, &verifier).await;
+                    }
+                }
+                None => handle(stream, &verifier).await,
+            }
+        });
+    }
+}
+
+fn tls_acceptor(cert: &Path, key: &Path) -> Result<TlsAcceptor> {
+    let certs = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("Failed to read certificates from '{}'", cert.display()))?;
+    let key = PrivateKeyDer::from_pem_file(key).with_context(|| format!("Failed to read private key from '{}'", key.display()))?;
+    let config = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .context("Failed to configure TLS")?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("The certificate does not match the private key")?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+async fn handle<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, verifier: &AdmissionVerifier) {
+    let response = match read_request(&mut stream).await {
+        Ok((method, path, body)) => match (method.as_str(), path.split('?').next().unwrap_or_default()) {
+            ("POST", "/validate") => match review(verifier, &body).await {
+                Ok(review) => http_response("200 OK", "application/json", &review.to_string()),
+                Err(e) => http_response("400 Bad Request", "text/plain", &format!("{:#}", e)),
+            },
+            ("GET", "/healthz") => http_response("200 OK", "text/plain", "ok"),
+            _ => http_response("404 Not Found", "text/plain", ""),
+        },
+        Err(e) => http_response("400 Bad Request", "text/plain", &format!("{:#}", e)),
+    };
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+fn http_response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+/// Reads an HTTP/1.1 request: its method, path and body.
+async fn read_request<S: AsyncRead + Unpin>(stream: &mut S) -> Result<(String, String, Vec<u8>)> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 8192];
+    let head_end = loop {
+        if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end;
+        }
+        if buffer.len() > MAX_REQUEST_BYTES {
+            bail!("Request headers are too large");
+        }
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            bail!("Connection closed before the request was complete");
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+    let head = String::from_utf8_lossy(&buffer[..head_end]).into_owned();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (method, path) = (request_line.next().unwrap_or_default().to_string(), request_line.next().unwrap_or_default().to_string());
+    let content_length: usize = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .map(|(_, value)| value.trim().parse())
+        .transpose()
+        .context("Invalid Content-Length")?
+        .unwrap_or(0);
+    if content_length > MAX_REQUEST_BYTES {
+        bail!("Request body is too large");
+    }
+    let mut body = buffer.split_off(head_end + 4);
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            bail!("Connection closed before the request body was complete");
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+    Ok((method, path, body))
+}
+
+/// Answers an `admission.k8s.io/v1` AdmissionReview. Objects without a pod spec, and deletions,
+/// are admitted; a pod spec is admitted only if every image is.
+pub(crate) async fn review(verifier: &AdmissionVerifier, body: &[u8]) -> Result<Value> {
+    let review: Value = serde_json::from_slice(body).context("Request is not an AdmissionReview")?;
+    let request = review.get("request").context("AdmissionReview has no request")?;
+    let uid = request.get("uid").and_then(Value::as_str).context("AdmissionReview request has no uid")?;
+    let images = request.get("object").map(pod_images).unwrap_or_default();
+
+    let mut denials = Vec::new();
+    let mut warnings = Vec::new();
+    for image in &images {
+        if !image.contains("@sha256:") {
+            denials.push(format!("{} is not pinned by digest", image));
+            continue;
+        }
+        match verifier.verify_image_digest(image).await {
+            Verdict::Allowed { releases, stale_since } => {
+                warnings.extend(
+                    releases
+                        .iter()
+                        .filter_map(|release| release.status.as_ref().map(|status| format!("{} comes from release {}, which is {}", image, release.release_purl, status))),
+                );
+                if let Some(since) = stale_since {
+                    warnings.push(format!("{} was last verified at {}; its supplier could not be traversed since", image, since));
+                }
+            }
+            Verdict::Denied { reason } => denials.push(format!("{}: {}", image, reason)),
+        }
+    }
+    let mut response = json!({ "uid": uid, "allowed": denials.is_empty() });
+    if !denials.is_empty() {
+        response["status"] = json!({ "code": 403, "message": denials.join("; ") });
+    }
+    if !warnings.is_empty() {
+        response["warnings"] = json!(warnings);
+    }
+    Ok(json!({
+        "apiVersion": review.get("apiVersion").and_then(Value::as_str).unwrap_or("admission.k8s.io/v1"),
+        "kind": "AdmissionReview",
+        "response": response,
+    }))
+}
+
+/// The container images of a Pod, of a workload's pod template (Deployment, StatefulSet, Job and
+/// the like), or of a CronJob's job template.
+fn pod_images(object: &Value) -> Vec<String> {
+    let spec = &object["spec"];
+    let pod_spec = [&spec["jobTemplate"]["spec"]["template"]["spec"], &spec["template"]["spec"], spec]
+        .into_iter()
+        .find(|candidate| candidate.get("containers").is_some());
+    let Some(pod_spec) = pod_spec else {
+        return Vec::new();
+    };
+    ["initContainers", "containers", "ephemeralContainers"]
+        .iter()
+        .filter_map(|field| pod_spec[*field].as_array())
+        .flatten()
+        .filter_map(|container| container["image"].as_str().map(str::to_string))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::BoxFuture;
+
+    use crate::discovery::Discovery;
+    use crate::fetch::Fetchers;
+    use crate::models::aggregation::AggregatedCatalogData;
+
+    use super::*;
+
+    /// Discovers the same aggregate for every domain.
+    struct StaticDiscovery(AggregatedCatalogData);
+
+    impl Discovery for StaticDiscovery {
+        fn discover<'a>(&'a self, _: &'a str, _: &'a Fetchers) -> BoxFuture<'a, Result<AggregatedCatalogData>> {
+            Box::pin(async move { Ok(self.0.clone()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn admits_pods_whose_images_are_all_attested() {
+        let attested = "a".repeat(64);
+        let data: AggregatedCatalogData = serde_json::from_value(json!({
+            "catalog_predicate": null,
+            "root_error": null,
+            "component_errors": [],
+            "components": [{
+                "component_predicate": null,
+                "component_link_uri": "https://example.com/app.json",
+                "release_errors": [],
+                "releases": [{
+                    "release_predicate": {
+                        "timestamp": "2025-01-01T00:00:00Z",
+                        "purl": "pkg:chainsights/example.com/app@1.0.0",
+                        "name": "1.0.0",
+                        "metadataLinks": [],
+                        "artifacts": [{ "uri": format!("oci://ghcr.io/example/app@sha256:{}", attested) }],
+                    },
+                    "metadata_artifacts": [],
+                    "release_link_uri": "https://example.com/app/1.0.0.json",
+                    "verification": { "method": "sigstore-keyless", "identity": "release@example.com" },
+                    "artifact_fetch_errors": [],
+                }],
+            }],
+        }))
+        .unwrap();
+        let verifier = AdmissionVerifier::with_discovery(Box::new(StaticDiscovery(data)), Fetchers::new(reqwest::Client::new()), vec!["example.com".to_string()]);
+        let deployment = |image: &str| {
+            json!({
+                "apiVersion": "admission.k8s.io/v1",
+                "kind": "AdmissionReview",
+                "request": {
+                    "uid": "705ab4f5-6393-11e8-b7cc-42010a800002",
+                    "object": { "kind": "Deployment", "spec": { "template": { "spec": { "containers": [{ "name": "app", "image": image }] } } } },
+                },
+            })
+            .to_string()
+        };
+
+        let admitted = review(&verifier, deployment(&format!("mirror.example.net/app@sha256:{}", attested)).as_bytes()).await.unwrap();
+        assert_eq!(admitted["response"]["uid"], "705ab4f5-6393-11e8-b7cc-42010a800002");
+        assert_eq!(admitted["response"]["allowed"], true);
+        let unattested = review(&verifier, deployment(&format!("ghcr.io/example/app@sha256:{}", "b".repeat(64))).as_bytes()).await.unwrap();
+        assert_eq!(unattested["response"]["allowed"], false);
+        assert_eq!(unattested["response"]["status"]["code"], 403);
+        let tagged = review(&verifier, deployment("ghcr.io/example/app:1.0.0").as_bytes()).await.unwrap();
+        assert!(tagged["response"]["status"]["message"].as_str().unwrap().contains("not pinned by digest"));
+    }
+}
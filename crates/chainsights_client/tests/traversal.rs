@@ -0,0 +1,653 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! End-to-end discovery, traversal and verification against a signed fixture tree served
+//! in-process, with DNS answered by a stub resolver.
+
+use std::time::Duration;
+
+use chainsights_client::client::Client;
+use chainsights_client::dns::StaticResolver;
+use chainsights_client::models::aggregation::{IntegrityErrorKind, NodeVerification, VerificationMethod};
+use chainsights_client::models::chainsights::{FRESHNESS_V1, REVOCATION_V1};
+use chainsights_core::identity::{BuildPolicy, FulcioExtensions};
+use chainsights_core::statement::InTotoStatement;
+use chainsights_core::verify::decode_bundle_unverified;
+use chainsights_testing::fixture::CATALOG_PATH;
+use chainsights_core::pgp::PgpKeyring;
+use sha2::{Digest, Sha256};
+use chainsights_testing::{
+    DidSigner, EphemeralSigner, FixtureSpec, MinisignSigner, MockEnvironment, PgpSigner, PrivateSigstore, SshSigner,
+};
+
+fn client_for(env: &MockEnvironment) -> Client {
+    Client::builder().txt_resolver(StaticResolver::new(env.txt_records())).build()
+}
+
+/// Replaces the statement at `path` with an edited copy, validly signed by the fixture identity.
+async fn resign(env: &MockEnvironment, path: &str, edit: impl FnOnce(&mut InTotoStatement)) {
+    resign_with(env, path, &EphemeralSigner::new(env.identity()).unwrap(), edit).await;
+}
+
+/// Replaces the statement at `path` with an edited copy signed by `signer`.
+async fn resign_with(
+    env: &MockEnvironment,
+    path: &str,
+    signer: &EphemeralSigner,
+    edit: impl FnOnce(&mut InTotoStatement),
+) {
+    let bundle = String::from_utf8(env.document(path).unwrap().to_vec()).unwrap();
+    let mut statement: InTotoStatement =
+        serde_json::from_slice(&decode_bundle_unverified(&bundle).unwrap().payload).unwrap();
+    edit(&mut statement);
+    let (bundle, _) = signer.sign(&statement).unwrap();
+    env.replace(path, bundle).await.unwrap();
+}
+
+#[tokio::test]
+async fn discovers_the_whole_tree() {
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let data = client_for(&env).discover(env.domain()).await.unwrap();
+
+    assert!(data.root_error.is_none(), "{:?}", data.root_error);
+    assert!(data.component_errors.is_empty(), "{:?}", data.component_errors);
+    assert_eq!(data.components.len(), 2);
+    let releases: usize = data.components.iter().map(|c| c.releases.len()).sum();
+    assert_eq!(releases, 3);
+    for component in &data.components {
+        assert!(component.release_errors.is_empty(), "{:?}", component.release_errors);
+        for release in &component.releases {
+            assert_eq!(release.metadata_artifacts.len(), 1);
+        }
+    }
+}
+
+#[tokio::test]
+async fn traces_every_node_to_the_statements_that_asserted_it() {
+    let env = MockEnvironment::start(FixtureSpec::default().pages(2)).await.unwrap();
+    resign(&env, "/releases/app/1.0.0.json", |s| s.predicate["license"] = serde_json::Value::Null).await;
+    let data = client_for(&env).discover(env.domain()).await.unwrap();
+
+    let catalog = &data.provenance.as_ref().unwrap().statement;
+    assert_eq!(catalog.uri, env.uri(CATALOG_PATH));
+    assert_eq!(catalog.signer.as_deref(), Some(env.identity()));
+    assert_eq!(catalog.payload_sha256, data.verification.as_ref().unwrap().payload_sha256);
+    let [app, lib] = &data.components[..] else {
+        panic!("expected two components");
+    };
+    let app_provenance = app.provenance.as_ref().unwrap();
+    assert_eq!(app_provenance.statement.uri, app.component_link_uri);
+    assert_eq!(app_provenance.linked_from.as_ref(), Some(catalog));
+    let page = lib.provenance.as_ref().unwrap().linked_from.as_ref().unwrap();
+    assert_eq!(page.uri, env.uri("/catalog/page-2.json"), "lib is listed on the second page");
+
+    let release = |version: &str| {
+        let release = app.releases.iter().find(|r| r.release_link_uri.ends_with(&format!("/{}.json", version))).unwrap();
+        release.provenance.as_ref().unwrap()
+    };
+    assert_eq!(release("1.1.0").linked_from.as_ref(), Some(&app_provenance.statement));
+    assert_eq!(release("1.1.0").fields["license"], release("1.1.0").statement);
+    assert_eq!(release("1.0.0").fields["license"], app_provenance.statement, "falls back to the component's license");
+    assert!(!release("1.0.0").fields.contains_key("cpe"));
+}
+
+#[tokio::test]
+async fn annotates_how_each_node_was_verified() {
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let data = client_for(&env).traverse(env.root_uri(), env.identity()).await.unwrap();
+
+    let assert_keyless = |verification: Option<&NodeVerification>| {
+        let verification = verification.expect("node has a verification annotation");
+        assert_eq!(verification.method, VerificationMethod::SigstoreKeyless);
+        assert_eq!(verification.identity.as_deref(), Some(env.identity()));
+        assert!(!verification.rekor_entry);
+        assert!(verification.signed_at.is_none());
+    };
+    assert_keyless(data.verification.as_ref());
+    for component in &data.components {
+        assert_keyless(component.verification.as_ref());
+        for release in &component.releases {
+            assert_keyless(release.verification.as_ref());
+        }
+    }
+
+    let json = serde_json::to_value(&data).unwrap();
+    assert_eq!(json["verification"]["method"], "sigstore-keyless");
+    assert_eq!(json["verification"]["time_source"], "local");
+}
+
+#[tokio::test]
+async fn marks_components_reached_only_through_relationships() {
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let mut lib = serde_json::Value::Null;
+    resign(&env, CATALOG_PATH, |s| lib = s.predicate["components"].as_array_mut().unwrap().remove(1)).await;
+    let relationship = serde_json::json!([{
+        "type": "depends-on",
+        "targetPurl": lib["purl"],
+        "componentAttestationLink": lib["componentAttestationLink"],
+    }]);
+    resign(&env, "/components/app.json", |s| s.predicate["relationships"] = relationship).await;
+
+    let data = client_for(&env).discover(env.domain()).await.unwrap();
+    let [app, lib] = &data.components[..] else {
+        panic!("expected the listed component and the one it depends on: {:?}", data.component_errors);
+    };
+    assert!(app.via_relationship.is_none());
+    assert_eq!(lib.via_relationship.as_ref(), Some(&app.component_link_uri));
+}
+
+#[tokio::test]
+async fn tampered_release_is_reported() {
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let path = "/releases/app/1.1.0.json";
+    let tampered = String::from_utf8(env.document(path).unwrap().to_vec())
+        .unwrap()
+        .replace("\"sig\":\"", "\"sig\":\"AAAA");
+    env.replace(path, tampered).await.unwrap();
+
+    let data = client_for(&env).traverse(env.root_uri(), env.identity()).await.unwrap();
+    let app = data
+        .components
+        .iter()
+        .find(|c| c.component_link_uri == env.uri("/components/app.json"))
+        .unwrap();
+    assert_eq!(app.releases.len(), 1);
+    assert_eq!(app.release_errors.len(), 1);
+    assert_eq!(app.release_errors[0].0, env.uri(path));
+}
+
+#[tokio::test]
+async fn mismatched_links_are_integrity_errors() {
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    resign(&env, "/components/lib.json", |s| s.predicate["purl"] = "pkg:generic/example.test/app".into()).await;
+    resign(&env, "/releases/app/1.1.0.json", |s| s.predicate["purl"] = "pkg:generic/example.test/lib@1.1.0".into()).await;
+
+    let data = client_for(&env).traverse(env.root_uri(), env.identity()).await.unwrap();
+    assert_eq!(data.components.len(), 1);
+    assert_eq!(data.integrity_errors.len(), 1);
+    assert_eq!(data.integrity_errors[0].kind, IntegrityErrorKind::ComponentPurl);
+    assert_eq!(data.integrity_errors[0].uri, env.uri("/components/lib.json"));
+
+    let app = &data.components[0];
+    assert_eq!(app.releases.len(), 1);
+    assert_eq!(app.integrity_errors.len(), 1);
+    assert_eq!(app.integrity_errors[0].kind, IntegrityErrorKind::ReleasePurl);
+    assert_eq!(app.integrity_errors[0].found_purl, "pkg:generic/example.test/lib@1.1.0");
+    assert_eq!(data.outcome_counts(), (3, 2));
+}
+
+#[tokio::test]
+async fn scoped_links_must_stay_within_the_domain() {
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let scoped = |allowed_hosts: &[&str]| {
+        Client::builder()
+            .txt_resolver(StaticResolver::new(env.txt_records()))
+            .scope_links(allowed_hosts)
+            .build()
+    };
+    // The fixture is served from 127.0.0.1 rather than its domain.
+    let data = scoped(&[]).discover(env.domain()).await.unwrap();
+    assert!(data.components.is_empty());
+    assert_eq!(data.component_errors.len(), 2);
+    let data = scoped(&["127.0.0.1"]).discover(env.domain()).await.unwrap();
+    assert_eq!(data.components.len(), 2);
+
+    let lib_uri = env.uri("/components/lib.json");
+    let spliced_uri = lib_uri.replace("127.0.0.1", "localhost");
+    resign(&env, CATALOG_PATH, |s| {
+        for entry in s.predicate["components"].as_array_mut().unwrap() {
+            if entry["componentAttestationLink"]["uri"] == lib_uri.as_str() {
+                entry["componentAttestationLink"]["uri"] = spliced_uri.clone().into();
+            }
+        }
+    })
+    .await;
+    let data = scoped(&[]).traverse(env.root_uri(), env.identity()).await.unwrap();
+    assert_eq!(data.components.len(), 1);
+    assert_eq!(data.component_errors.len(), 1);
+    assert_eq!(data.component_errors[0].0, spliced_uri);
+    let unscoped = client_for(&env).traverse(env.root_uri(), env.identity()).await.unwrap();
+    assert_eq!(unscoped.components.len(), 2);
+}
+
+#[tokio::test]
+async fn cross_domain_links_need_the_foreign_domain_to_corroborate_their_identity() {
+    let reseller = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let vendor = MockEnvironment::start(FixtureSpec::new("vendor.test", "release@vendor.test").component("widget", &["3.0.0"]))
+        .await
+        .unwrap();
+    // Both are served from 127.0.0.1, so the vendor's statements are linked through localhost.
+    let widget_uri = vendor.uri("/components/widget.json").replace("127.0.0.1", "localhost");
+    let link_to_widget = |identity: &str| {
+        let identity = identity.to_string();
+        let widget_uri = widget_uri.clone();
+        move |s: &mut InTotoStatement| {
+            s.predicate["components"].as_array_mut().unwrap().push(serde_json::json!({
+                "name": "widget",
+                "purl": "pkg:generic/vendor.test/widget",
+                "componentAttestationLink": { "uri": widget_uri, "expectedSignerIdentity": identity },
+            }));
+        }
+    };
+    let mut records = reseller.txt_records();
+    records.insert("_chainsights.localhost".to_string(), vendor.txt_records().into_values().next().unwrap());
+    let client = |trusted: &[&str]| {
+        Client::builder()
+            .txt_resolver(StaticResolver::new(records.clone()))
+            .scope_links(&["127.0.0.1"])
+            .trust_cross_domain(trusted)
+            .build()
+    };
+
+    resign(&reseller, CATALOG_PATH, link_to_widget("release@vendor.test")).await;
+    let data = client(&[]).discover(reseller.domain()).await.unwrap();
+    assert_eq!(data.components.len(), 2);
+    assert!(data.component_errors[0].1.contains("outside"), "{:?}", data.component_errors);
+
+    let data = client(&["localhost"]).discover(reseller.domain()).await.unwrap();
+    assert_eq!(data.components.len(), 3, "{:?}", data.component_errors);
+    let widget = data.components.iter().find(|c| c.component_link_uri == widget_uri).unwrap();
+    let label = widget.cross_domain.as_ref().unwrap();
+    assert_eq!((label.domain.as_str(), label.identity.as_str()), ("localhost", "release@vendor.test"));
+    assert_eq!(label.corroborated_by, "_chainsights.localhost");
+    assert!(data.components.iter().filter(|c| c.component_link_uri != widget_uri).all(|c| c.cross_domain.is_none()));
+
+    // The reseller cannot attribute the vendor's statements to an identity the vendor never named.
+    let reseller = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    resign(&reseller, CATALOG_PATH, link_to_widget("release@example.test")).await;
+    let mut records = reseller.txt_records();
+    records.insert("_chainsights.localhost".to_string(), vendor.txt_records().into_values().next().unwrap());
+    let data = Client::builder()
+        .txt_resolver(StaticResolver::new(records))
+        .scope_links(&["127.0.0.1"])
+        .trust_cross_domain(&["localhost"])
+        .build()
+        .discover(reseller.domain())
+        .await
+        .unwrap();
+    assert_eq!(data.components.len(), 2);
+    assert!(data.component_errors[0].1.contains("names 'release@vendor.test'"), "{:?}", data.component_errors);
+}
+
+#[tokio::test]
+async fn releases_must_satisfy_the_build_policy() {
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let tagged_build = FulcioExtensions {
+        source_repository_uri: Some("https://github.com/example/app".to_string()),
+        source_repository_ref: Some("refs/tags/v1.1.0".to_string()),
+        build_trigger: Some("push".to_string()),
+        ..Default::default()
+    };
+    let signer = EphemeralSigner::with_extensions(env.identity(), &tagged_build).unwrap();
+    resign_with(&env, "/releases/app/1.1.0.json", &signer, |_| {}).await;
+
+    let client = Client::builder()
+        .txt_resolver(StaticResolver::new(env.txt_records()))
+        .release_build_policy(BuildPolicy {
+            source_repository: Some("https://github.com/example/app".to_string()),
+            source_ref: Some("refs/tags/*".to_string()),
+            build_trigger: None,
+        })
+        .build();
+    let data = client.discover(env.domain()).await.unwrap();
+    let app = data.components.iter().find(|c| c.component_predicate.as_ref().unwrap().name == "app").unwrap();
+    assert_eq!(app.releases.len(), 1);
+    assert_eq!(app.releases[0].release_predicate.as_ref().unwrap().purl, "pkg:generic/example.test/app@1.1.0");
+    // Releases signed without a build context fail the policy; components and catalogs are unaffected.
+    assert_eq!(app.release_errors.len(), 1);
+    let lib = data.components.iter().find(|c| c.component_predicate.as_ref().unwrap().name == "lib").unwrap();
+    assert!(lib.releases.is_empty());
+    assert_eq!(lib.release_errors.len(), 1);
+}
+
+#[tokio::test]
+async fn verifies_against_a_private_trusted_root() {
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let instance = PrivateSigstore::new().unwrap();
+    let signer = instance.signer(env.identity()).unwrap();
+    resign_with(&env, CATALOG_PATH, &signer, |_| {}).await;
+
+    let client = Client::builder()
+        .txt_resolver(StaticResolver::new(env.txt_records()))
+        .trusted_root(instance.trusted_root())
+        .build();
+    let data = client.discover(env.domain()).await.unwrap();
+    assert!(data.root_error.is_none(), "{:?}", data.root_error);
+    // The rest of the fixture is signed with self-signed certificates the instance did not issue.
+    assert!(data.components.is_empty());
+    assert_eq!(data.component_errors.len(), 2);
+}
+
+#[tokio::test]
+async fn verifies_pgp_signed_releases_by_pinned_fingerprint() {
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let pgp = PgpSigner::new("Release Manager <release@example.test>").unwrap();
+    let statement = |path: &str| -> InTotoStatement {
+        let bundle = String::from_utf8(env.document(path).unwrap().to_vec()).unwrap();
+        serde_json::from_slice(&decode_bundle_unverified(&bundle).unwrap().payload).unwrap()
+    };
+    // 1.0.0 is cleartext-signed; 1.1.0 is plain JSON with a detached signature next to it.
+    let cleartext = pgp.sign_cleartext(&statement("/releases/app/1.0.0.json")).unwrap();
+    env.replace("/releases/app/1.0.0.json", cleartext).await.unwrap();
+    let document = serde_json::to_vec(&statement("/releases/app/1.1.0.json")).unwrap();
+    env.serve("/releases/app/1.1.0.json.asc", pgp.sign_detached(&document).unwrap()).await;
+    env.replace("/releases/app/1.1.0.json", document).await.unwrap();
+    let fingerprint = pgp.fingerprint();
+    resign(&env, "/components/app.json", |s| {
+        for link in s.predicate["releaseAttestations"].as_array_mut().unwrap() {
+            link["expectedPgpFingerprints"] = serde_json::json!([fingerprint]);
+        }
+    })
+    .await;
+
+    let keyring = PgpKeyring::from_armored(&pgp.armored_public_key().unwrap()).unwrap();
+    let client = Client::builder()
+        .txt_resolver(StaticResolver::new(env.txt_records()))
+        .pgp_keyring(keyring)
+        .build();
+    let data = client.discover(env.domain()).await.unwrap();
+    let app = data.components.iter().find(|c| c.component_predicate.as_ref().unwrap().name == "app").unwrap();
+    assert!(app.release_errors.is_empty(), "{:?}", app.release_errors);
+    assert_eq!(app.releases.len(), 2);
+    for release in &app.releases {
+        let verification = release.verification.as_ref().unwrap();
+        assert_eq!(verification.method, VerificationMethod::Key);
+        assert_eq!(verification.identity.as_deref(), Some(pgp.fingerprint().as_str()));
+    }
+
+    // Without the keys, the pinned releases can't be verified.
+    let data = client_for(&env).discover(env.domain()).await.unwrap();
+    let app = data.components.iter().find(|c| c.component_predicate.as_ref().unwrap().name == "app").unwrap();
+    assert!(app.releases.is_empty());
+    assert_eq!(app.release_errors.len(), 2);
+}
+
+#[tokio::test]
+async fn verifies_ssh_and_minisign_signatures_by_pinned_key() {
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let (ssh, minisign) = (SshSigner::new(), MinisignSigner::new());
+    let statement = |path: &str| -> InTotoStatement {
+        let bundle = String::from_utf8(env.document(path).unwrap().to_vec()).unwrap();
+        serde_json::from_slice(&decode_bundle_unverified(&bundle).unwrap().payload).unwrap()
+    };
+    // The app component is signed with an SSH key pinned in the catalog, and the catalog with the
+    // minisign key published in the TXT record.
+    let component = serde_json::to_vec(&statement("/components/app.json")).unwrap();
+    env.serve("/components/app.json.sig", ssh.sign(&component)).await;
+    env.replace("/components/app.json", component).await.unwrap();
+    let mut catalog = statement(CATALOG_PATH);
+    for entry in catalog.predicate["components"].as_array_mut().unwrap() {
+        if entry["componentAttestationLink"]["uri"].as_str().unwrap().ends_with("/components/app.json") {
+            entry["componentAttestationLink"]["expectedPublicKeys"] = serde_json::json!([ssh.public_key()]);
+        }
+    }
+    let catalog = serde_json::to_vec(&catalog).unwrap();
+    env.serve(&format!("{}.minisig", CATALOG_PATH), minisign.sign(&catalog)).await;
+    env.replace(CATALOG_PATH, catalog).await.unwrap();
+    let txt_records = |identity: &str| {
+        let mut records = env.txt_records();
+        for record in records.values_mut().flatten() {
+            *record = record.replace(&format!("identity={}", env.identity()), &format!("identity={}", identity));
+        }
+        records
+    };
+
+    let client = Client::builder()
+        .txt_resolver(StaticResolver::new(txt_records(&format!("minisign:{}", minisign.public_key()))))
+        .build();
+    let data = client.discover(env.domain()).await.unwrap();
+    assert!(data.root_error.is_none(), "{:?}", data.root_error);
+    assert_eq!(data.verification.as_ref().unwrap().method, VerificationMethod::Key);
+    assert!(data.component_errors.is_empty(), "{:?}", data.component_errors);
+    let app = data.components.iter().find(|c| c.component_predicate.as_ref().unwrap().name == "app").unwrap();
+    let verification = app.verification.as_ref().unwrap();
+    assert_eq!(verification.method, VerificationMethod::Key);
+    assert!(verification.identity.as_deref().unwrap().starts_with("SHA256:"));
+    assert_eq!(app.releases.len(), 2);
+
+    // A different key in the TXT record doesn't verify the catalog.
+    let client = Client::builder()
+        .txt_resolver(StaticResolver::new(txt_records(&format!("minisign:{}", MinisignSigner::new().public_key()))))
+        .build();
+    assert!(client.discover(env.domain()).await.unwrap().root_error.is_some());
+}
+
+#[tokio::test]
+async fn verifies_dsse_envelopes_signed_by_did_keys() {
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let (catalog_signer, app_signer) = (DidSigner::new(), DidSigner::new());
+    let statement = |path: &str| -> InTotoStatement {
+        let bundle = String::from_utf8(env.document(path).unwrap().to_vec()).unwrap();
+        serde_json::from_slice(&decode_bundle_unverified(&bundle).unwrap().payload).unwrap()
+    };
+    // The app component is signed by a did:key named in the catalog, and the catalog by the
+    // did:key named in the TXT record.
+    let component = serde_json::to_vec(&statement("/components/app.json")).unwrap();
+    env.replace("/components/app.json", app_signer.sign(&component)).await.unwrap();
+    let mut catalog = statement(CATALOG_PATH);
+    for entry in catalog.predicate["components"].as_array_mut().unwrap() {
+        if entry["componentAttestationLink"]["uri"].as_str().unwrap().ends_with("/components/app.json") {
+            entry["componentAttestationLink"]["expectedSignerIdentity"] = serde_json::json!(app_signer.did_key());
+        }
+    }
+    env.replace(CATALOG_PATH, catalog_signer.sign(&serde_json::to_vec(&catalog).unwrap())).await.unwrap();
+    let client_expecting = |identity: String| {
+        let mut records = env.txt_records();
+        for record in records.values_mut().flatten() {
+            *record = record.replace(&format!("identity={}", env.identity()), &format!("identity={}", identity));
+        }
+        Client::builder().txt_resolver(StaticResolver::new(records)).build()
+    };
+
+    let data = client_expecting(catalog_signer.did_key()).discover(env.domain()).await.unwrap();
+    assert!(data.root_error.is_none(), "{:?}", data.root_error);
+    assert_eq!(data.verification.as_ref().unwrap().method, VerificationMethod::Key);
+    assert!(data.component_errors.is_empty(), "{:?}", data.component_errors);
+    let app = data.components.iter().find(|c| c.component_predicate.as_ref().unwrap().name == "app").unwrap();
+    let signed_by = app.verification.as_ref().unwrap().identity.as_deref().unwrap();
+    assert!(signed_by.starts_with(&format!("{}#", app_signer.did_key())), "{}", signed_by);
+    assert_eq!(app.releases.len(), 2);
+
+    // Another DID's key doesn't verify the catalog.
+    let data = client_expecting(DidSigner::new().did_key()).discover(env.domain()).await.unwrap();
+    assert!(data.root_error.is_some());
+}
+
+#[tokio::test]
+async fn root_catalog_must_match_the_key_pinned_in_dns() {
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let signer = EphemeralSigner::new(env.identity()).unwrap();
+    env.serve("/chainsights.pem", signer.certificate_pem()).await;
+    let pinned_to = |key: String| {
+        let mut records = env.txt_records();
+        for record in records.values_mut().flatten() {
+            record.push_str(&format!(" key={}", key));
+        }
+        Client::builder().txt_resolver(StaticResolver::new(records)).build()
+    };
+
+    // The fixture's certificate names the same identity, but isn't the pinned one.
+    let data = pinned_to(env.uri("/chainsights.pem")).discover(env.domain()).await.unwrap();
+    assert!(data.root_error.as_deref().unwrap().contains("pinned in DNS"), "{:?}", data.root_error);
+    assert!(data.components.is_empty());
+
+    resign_with(&env, CATALOG_PATH, &signer, |_| {}).await;
+    let data = pinned_to(env.uri("/chainsights.pem")).discover(env.domain()).await.unwrap();
+    assert!(data.root_error.is_none(), "{:?}", data.root_error);
+    assert_eq!(data.components.len(), 2);
+    assert!(pinned_to(env.uri("/missing.pem")).discover(env.domain()).await.unwrap().root_error.is_some());
+}
+
+/// Re-signs the fixture's root catalog with serial 7 and a freshness link, and serves a freshness
+/// statement signed by `witness` for the catalog with payload digest `sha256` (the new catalog's if
+/// None) and `serial`.
+async fn serve_freshness(env: &MockEnvironment, witness: &EphemeralSigner, sha256: Option<&str>, serial: u64) {
+    let bundle = String::from_utf8(env.document(CATALOG_PATH).unwrap().to_vec()).unwrap();
+    let mut catalog: InTotoStatement = serde_json::from_slice(&decode_bundle_unverified(&bundle).unwrap().payload).unwrap();
+    catalog.predicate["serial"] = 7.into();
+    catalog.predicate["freshnessLink"] =
+        serde_json::json!({ "uri": env.uri("/freshness.json"), "expectedSignerIdentity": witness.identity() });
+    let (bundle, payload) = EphemeralSigner::new(env.identity()).unwrap().sign(&catalog).unwrap();
+    env.replace(CATALOG_PATH, bundle).await.unwrap();
+    let predicate = serde_json::json!({
+        "timestamp": "2025-06-01T00:00:00Z",
+        "catalog": { "digest": { "sha256": sha256.map_or_else(|| hex::encode(Sha256::digest(&payload)), str::to_string) } },
+        "serial": serial,
+    });
+    let statement = InTotoStatement::new(&format!("pkg:chainsights/{}", env.domain()), Default::default(), FRESHNESS_V1, predicate);
+    env.serve("/freshness.json", witness.sign(&statement).unwrap().0).await;
+}
+
+#[tokio::test]
+async fn verifies_the_witnessed_freshness_of_the_root_catalog() {
+    let witness = EphemeralSigner::new("witness@example.org").unwrap();
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    serve_freshness(&env, &witness, None, 7).await;
+    let data = client_for(&env).discover(env.domain()).await.unwrap();
+    let confirmed = data.freshness.as_ref().unwrap_or_else(|| panic!("{:?}", data.freshness_error));
+    assert_eq!(confirmed.signer_identity.as_deref(), Some("witness@example.org"));
+    assert_eq!(confirmed.confirmed_at, "2025-06-01T00:00:00Z");
+
+    // A freshness statement for an older catalog doesn't confirm this one.
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    serve_freshness(&env, &witness, Some(&hex::encode(Sha256::digest(b"older catalog"))), 6).await;
+    let data = client_for(&env).discover(env.domain()).await.unwrap();
+    assert!(data.freshness.is_none());
+    assert!(data.freshness_error.as_deref().unwrap().contains("another catalog"), "{:?}", data.freshness_error);
+    assert_eq!(data.components.len(), 2);
+
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    serve_freshness(&env, &witness, None, 6).await;
+    let data = client_for(&env).discover(env.domain()).await.unwrap();
+    assert!(data.freshness_error.as_deref().unwrap().contains("serial 6"), "{:?}", data.freshness_error);
+}
+
+#[tokio::test]
+async fn revokes_statements_by_payload_digest_and_needs_the_list_to_exclude_them() {
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let release = String::from_utf8(env.document("/releases/app/1.1.0.json").unwrap().to_vec()).unwrap();
+    let release_sha256 = hex::encode(Sha256::digest(decode_bundle_unverified(&release).unwrap().payload));
+    let revocations = serde_json::json!({
+        "timestamp": "2025-06-01T00:00:00Z",
+        "revocations": [{ "digest": { "sha256": release_sha256 }, "reason": "signed with a compromised key" }],
+    });
+    let statement = InTotoStatement::new(&format!("pkg:chainsights/{}", env.domain()), Default::default(), REVOCATION_V1, revocations);
+    env.serve("/revocations.json", EphemeralSigner::new(env.identity()).unwrap().sign(&statement).unwrap().0).await;
+    let revocation_link = serde_json::json!({ "uri": env.uri("/revocations.json"), "expectedSignerIdentity": env.identity() });
+    resign(&env, CATALOG_PATH, |s| s.predicate["revocationLink"] = revocation_link).await;
+
+    // The component links the release without a digest, so only its payload identifies it.
+    let data = client_for(&env).discover(env.domain()).await.unwrap();
+    let app = data.components.iter().find(|c| c.component_link_uri.ends_with("/app.json")).unwrap();
+    let revoked = app.releases.iter().find(|r| r.release_link_uri.ends_with("/1.1.0.json")).unwrap();
+    assert_eq!(revoked.revocation.as_ref().unwrap().reason.as_deref(), Some("signed with a compromised key"));
+    let excluding = || Client::builder().txt_resolver(StaticResolver::new(env.txt_records())).exclude_revoked(true).build();
+    let data = excluding().discover(env.domain()).await.unwrap();
+    let app = data.components.iter().find(|c| c.component_link_uri.ends_with("/app.json")).unwrap();
+    assert_eq!(app.releases.len(), 1);
+
+    // Blocking the list must not bring the release back.
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let revocation_link = serde_json::json!({ "uri": env.uri("/revocations.json"), "expectedSignerIdentity": env.identity() });
+    resign(&env, CATALOG_PATH, |s| s.predicate["revocationLink"] = revocation_link).await;
+    let data = client_for(&env).discover(env.domain()).await.unwrap();
+    assert!(data.revocation_error.is_some());
+    assert_eq!(data.components.len(), 2);
+    let data = Client::builder().txt_resolver(StaticResolver::new(env.txt_records())).exclude_revoked(true).build().discover(env.domain()).await.unwrap();
+    assert!(data.root_error.as_deref().unwrap().contains("revocation list"), "{:?}", data.root_error);
+    assert!(data.components.is_empty());
+}
+
+#[tokio::test]
+async fn wrong_root_identity_fails() {
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let data = client_for(&env).traverse(env.root_uri(), "attacker@example.test").await.unwrap();
+    assert!(data.root_error.is_some());
+    assert!(data.components.is_empty());
+}
+
+#[tokio::test]
+async fn follows_catalog_pages() {
+    let env = MockEnvironment::start(FixtureSpec::synthetic(5, 2).pages(3)).await.unwrap();
+    let data = client_for(&env).discover(env.domain()).await.unwrap();
+
+    assert!(data.component_errors.is_empty(), "{:?}", data.component_errors);
+    assert_eq!(data.catalog_predicate.as_ref().unwrap().components.len(), 5);
+    assert_eq!(data.components.len(), 5);
+    assert!(data.components.iter().all(|c| c.releases.len() == 2));
+}
+
+#[tokio::test]
+async fn cached_client_reuses_the_root_catalog() {
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let cached = Client::builder()
+        .txt_resolver(StaticResolver::new(env.txt_records()))
+        .cache_ttl(Duration::from_secs(60))
+        .build();
+    cached.discover(env.domain()).await.unwrap();
+
+    env.replace(CATALOG_PATH, "not a bundle\n").await.unwrap();
+    let data = cached.discover(env.domain()).await.unwrap();
+    assert!(data.root_error.is_none(), "{:?}", data.root_error);
+    assert_eq!(data.components.len(), 2);
+
+    let uncached = client_for(&env).discover(env.domain()).await.unwrap();
+    assert!(uncached.root_error.is_some());
+}
+
+#[tokio::test]
+async fn admits_images_attested_by_a_supplier_release() {
+    use chainsights_client::admission::{AdmissionVerifier, Verdict};
+
+    let env = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let digest = hex::encode(Sha256::digest(b"image manifest"));
+    resign(&env, "/releases/app/1.1.0.json", |s| {
+        s.predicate["artifacts"] = serde_json::json!([{ "uri": format!("oci://ghcr.io/example/app@sha256:{}", digest) }]);
+    })
+    .await;
+    let verifier = AdmissionVerifier::new(client_for(&env), &[env.domain()]).unwrap();
+    assert_eq!(verifier.warm().await.unwrap(), 1);
+
+    let Verdict::Allowed { releases, stale_since } = verifier.verify_image_digest(&format!("sha256:{}", digest)).await else {
+        panic!("the image is attested");
+    };
+    assert_eq!(stale_since, None);
+    assert_eq!(releases[0].release_uri, env.uri("/releases/app/1.1.0.json"));
+    assert_eq!(releases[0].signer.as_deref(), Some(env.identity()));
+    let unattested = verifier.verify_image_digest(&format!("ghcr.io/example/app@sha256:{}", "0".repeat(64))).await;
+    assert!(!unattested.is_allowed());
+}
+
+#[tokio::test]
+async fn follows_delegations_to_subdomain_roots_within_their_namespace() {
+    let parent = MockEnvironment::start(FixtureSpec::default()).await.unwrap();
+    let cloud = MockEnvironment::start(FixtureSpec::new("cloud.example.test", "release@cloud.example.test").component("svc", &["2.0.0"]))
+        .await
+        .unwrap();
+    let rogue = MockEnvironment::start(FixtureSpec::new("rogue.example.test", "release@elsewhere.test").component("tool", &["1.0.0"]))
+        .await
+        .unwrap();
+    let mut records = parent.txt_records();
+    records.get_mut("_chainsights.example.test").unwrap().extend([
+        "v=chainsights1 delegate=cloud.example.test".to_string(),
+        "v=chainsights1 delegate=rogue.example.test".to_string(),
+    ]);
+    records.extend(cloud.txt_records());
+    records.extend(rogue.txt_records());
+    let client = |follow| Client::builder().txt_resolver(StaticResolver::new(records.clone())).follow_delegations(follow).build();
+
+    let data = client(false).discover(parent.domain()).await.unwrap();
+    assert_eq!(data.components.len(), 2);
+    assert!(data.delegations.is_empty());
+
+    let data = client(true).discover(parent.domain()).await.unwrap();
+    let purls: Vec<&str> = data.components.iter().filter_map(|c| c.component_predicate.as_ref()).map(|c| c.purl.as_str()).collect();
+    assert_eq!(purls.len(), 3, "{:?}", purls);
+    assert!(purls.iter().any(|purl| purl.contains("cloud.example.test")));
+    let [delegated, refused] = &data.delegations[..] else {
+        panic!("expected two delegations, got {:?}", data.delegations);
+    };
+    assert_eq!(delegated.namespace, "cloud.example.test");
+    assert_eq!(delegated.identity.as_deref(), Some("release@cloud.example.test"));
+    assert!(delegated.error.is_none());
+    assert!(refused.error.as_deref().unwrap().contains("outside the delegated namespace"), "{:?}", refused.error);
+}
@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+
+/// Struct to hold the parsed Chainsights bundle data.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SigstoreBundleData {
+    pub verification_material: VerificationMaterial,
+    pub dsse_envelope: DsseEnvelope,
+    // mediaType is ignored here. Long term, we may want to verify the tlog entries and timestamps.
+}
+
+/// Struct to hold the verification material data.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMaterial {
+    pub certificate: CertificateData,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tlog_entries: Vec<TlogEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_verification_data: Option<TimestampVerificationData>,
+}
+
+/// Struct to hold the signed timestamps embedded in a bundle.
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TimestampVerificationData {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rfc3161_timestamps: Vec<Rfc3161Timestamp>,
+}
+
+/// Struct to hold one RFC 3161 timestamp.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Rfc3161Timestamp {
+    pub signed_timestamp: String, // Base64 encoded DER timestamp token
+}
+
+/// Struct to hold a transparency log entry embedded in a bundle.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TlogEntry {
+    pub log_index: String,
+    pub log_id: LogId,
+    pub kind_version: KindVersion,
+    pub integrated_time: String,
+    pub inclusion_promise: Option<InclusionPromise>,
+    pub inclusion_proof: Option<serde_json::Value>,
+    pub canonicalized_body: String, // Base64 encoded entry body
+}
+
+/// Struct to hold the identifier of a transparency log.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LogId {
+    pub key_id: String, // Base64 encoded log key ID
+}
+
+/// Struct to hold the kind and version of a transparency log entry.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct KindVersion {
+    pub kind: String,
+    pub version: String,
+}
+
+/// Struct to hold the signed entry timestamp promising inclusion in the log.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InclusionPromise {
+    pub signed_entry_timestamp: String, // Base64 encoded signature
+}
+
+/// Struct to hold the certificate data.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateData {
+    pub raw_bytes: String, // Base64 encoded DER certificate
+}
+
+/// Struct to hold the DSSE envelope data.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DsseEnvelope {
+    pub payload: String,      // Base64 encoded payload (in-toto statement)
+    pub payload_type: String, // e.g., application/vnd.in-toto+json
+    pub signatures: Vec<SignatureData>,
+}
+
+/// Struct to hold the signature data.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureData {
+    pub sig: String, // Base64 encoded signature
+}
+/// Constructs the DSSE v1 Pre-Authentication Encoding bytes that envelope signatures cover.
+pub fn construct_pae(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+    let header = format!(
+        "DSSEv1 {} {} {} ",
+        payload_type.len(),
+        payload_type,
+        payload.len()
+    );
+    let mut pae = Vec::with_capacity(header.len() + payload.len());
+    pae.extend_from_slice(header.as_bytes());
+    pae.extend_from_slice(payload);
+    pae
+}
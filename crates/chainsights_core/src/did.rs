@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD, engine::general_purpose::URL_SAFE_NO_PAD};
+use ed25519_dalek::Verifier as _;
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::pkcs8::EncodePublicKey;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::bundle::{DsseEnvelope, construct_pae};
+use crate::verifier::{Verifier, key_signed};
+use crate::verify::{VerifiedBundle, public_key_fingerprint};
+
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+/// Multicodec prefixes (as varints) of the key types `did:key` and `publicKeyMultibase` carry.
+const MULTICODEC_ED25519_PUB: &[u8] = &[0xed, 0x01];
+const MULTICODEC_P256_PUB: &[u8] = &[0x80, 0x24];
+
+/// True if a signer identity is a DID this crate can verify for: `did:web` or `did:key`.
+pub fn is_did(identity: &str) -> bool {
+    identity.starts_with("did:web:") || identity.starts_with("did:key:")
+}
+
+/// Where the DID document of a `did:web` DID is published: `did:web:example.com` at
+/// `https://example.com/.well-known/did.json`, and `did:web:example.com:user:alice` at
+/// `https://example.com/user/alice/did.json`. A port is written percent-encoded
+/// (`did:web:example.com%3A8443`).
+pub fn did_web_document_url(did: &str) -> Result<String> {
+    let id = did.strip_prefix("did:web:").with_context(|| format!("'{}' is not a did:web DID", did))?;
+    let mut segments = id.split(':');
+    let host = segments.next().unwrap_or_default().replace("%3A", ":").replace("%3a", ":");
+    if host.is_empty() || host.contains(['/', '#', '?']) {
+        bail!("'{}' does not name a valid host", did);
+    }
+    let path: Vec<&str> = segments.collect();
+    if path.iter().any(|segment| segment.is_empty() || segment.contains(['/', '#', '?'])) {
+        bail!("'{}' has an invalid path", did);
+    }
+    match path.is_empty() {
+        true => Ok(format!("https://{}/.well-known/did.json", host)),
+        false => Ok(format!("https://{}/{}/did.json", host, path.join("/"))),
+    }
+}
+
+/// A verification key of a DID: its verification method ID (`did:web:example.com#key-1`) and
+/// the Ed25519 or P-256 public key.
+#[derive(Debug, Clone)]
+pub struct DidKey {
+    pub id: String,
+    key: KeyMaterial,
+}
+
+#[derive(Debug, Clone)]
+enum KeyMaterial {
+    Ed25519(ed25519_dalek::VerifyingKey),
+    P256(p256::ecdsa::VerifyingKey),
+}
+
+impl DidKey {
+    /// The `SHA256:` fingerprint of the key's SubjectPublicKeyInfo, as DNS key pins name it.
+    pub fn fingerprint(&self) -> Result<String> {
+        let spki = match &self.key {
+            KeyMaterial::Ed25519(key) => key.to_public_key_der(),
+            KeyMaterial::P256(key) => key.to_public_key_der(),
+        }
+        .context("Failed to encode DID public key")?;
+        Ok(public_key_fingerprint(spki.as_bytes()))
+    }
+
+    /// Verifies a signature over `message`. P-256 signatures may be ASN.1 DER or fixed-size.
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<()> {
+        match &self.key {
+            KeyMaterial::Ed25519(key) => {
+                let signature = ed25519_dalek::Signature::from_slice(signature).context("Invalid Ed25519 signature")?;
+                key.verify(message, &signature).context("Ed25519 signature does not match")
+            }
+            KeyMaterial::P256(key) => {
+                use p256::ecdsa::signature::Verifier;
+                let signature = p256::ecdsa::Signature::from_der(signature)
+                    .or_else(|_| p256::ecdsa::Signature::from_slice(signature))
+                    .context("Invalid ECDSA signature")?;
+                key.verify(message, &signature).context("ECDSA P-256 signature does not match")
+            }
+        }
+    }
+}
+
+/// The key a `did:key` DID encodes, e.g. `did:key:z6Mk...` for an Ed25519 key.
+pub fn did_key(did: &str) -> Result<DidKey> {
+    let encoded = did.strip_prefix("did:key:").with_context(|| format!("'{}' is not a did:key DID", did))?;
+    let key = decode_multibase_key(encoded).with_context(|| format!("Invalid did:key '{}'", did))?;
+    Ok(DidKey { id: format!("{}#{}", did, encoded), key })
+}
+
+/// The `did:key` DID of an Ed25519 public key (`did:key:z6Mk...`).
+pub fn ed25519_did_key(public_key: &[u8; 32]) -> String {
+    format!("did:key:z{}", encode_base58(&[MULTICODEC_ED25519_PUB, public_key.as_slice()].concat()))
+}
+
+/// The `did:key` DID of a P-256 public key given as a SEC1 point, compressed or not
+/// (`did:key:zDn...`).
+pub fn p256_did_key(sec1: &[u8]) -> Result<String> {
+    let key = p256::PublicKey::from_sec1_bytes(sec1).context("Invalid P-256 public key")?;
+    let point = key.to_encoded_point(true);
+    Ok(format!("did:key:z{}", encode_base58(&[MULTICODEC_P256_PUB, point.as_bytes()].concat())))
+}
+
+/// The `did:key` DID of an Ed25519 or P-256 public key given as DER SubjectPublicKeyInfo, as
+/// KMS and hardware token APIs return it.
+pub fn spki_did_key(spki: &[u8]) -> Result<String> {
+    use p256::pkcs8::DecodePublicKey;
+    if let Ok(key) = ed25519_dalek::VerifyingKey::from_public_key_der(spki) {
+        return Ok(ed25519_did_key(key.as_bytes()));
+    }
+    let key = p256::PublicKey::from_public_key_der(spki).map_err(|_| anyhow::anyhow!("Not an Ed25519 or P-256 public key"))?;
+    p256_did_key(key.to_encoded_point(true).as_bytes())
+}
+
+/// A DID document, with just what is needed to find the keys statements can be signed with.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DidDocument {
+    id: String,
+    #[serde(default)]
+    verification_method: Vec<Value>,
+    #[serde(default)]
+    assertion_method: Vec<Value>,
+}
+
+/// The keys of a DID document that `did` may sign statements with: its `assertionMethod`
+/// verification methods, given inline or by reference to `verificationMethod`. Keys may be
+/// given as `publicKeyJwk` (Ed25519 or P-256), `publicKeyMultibase` or, for
+/// `Ed25519VerificationKey2018`, `publicKeyBase58`.
+pub fn did_document_keys(did: &str, document: &str) -> Result<Vec<DidKey>> {
+    let document: DidDocument = serde_json::from_str(document).context("Failed to parse DID document")?;
+    if document.id != did {
+        bail!("DID document is for '{}', not '{}'", document.id, did);
+    }
+    let absolute = |id: &str| match id.strip_prefix('#') {
+        Some(fragment) => format!("{}#{}", did, fragment),
+        None => id.to_string(),
+    };
+    let mut keys = Vec::new();
+    for method in &document.assertion_method {
+        let method = match method {
+            Value::String(reference) => {
+                let reference = absolute(reference);
+                document
+                    .verification_method
+                    .iter()
+                    .find(|method| method["id"].as_str().map(absolute).as_deref() == Some(reference.as_str()))
+                    .with_context(|| format!("assertionMethod '{}' is not in the DID document", reference))?
+            }
+            method => method,
+        };
+        let id = absolute(method["id"].as_str().context("Verification method has no id")?);
+        let key = verification_method_key(method).with_context(|| format!("Invalid verification method '{}'", id))?;
+        keys.push(DidKey { id, key });
+    }
+    if keys.is_empty() {
+        bail!("DID document for '{}' has no assertionMethod keys", did);
+    }
+    Ok(keys)
+}
+
+fn verification_method_key(method: &Value) -> Result<KeyMaterial> {
+    if let Some(jwk) = method.get("publicKeyJwk") {
+        let coordinate = |name: &str| -> Result<Vec<u8>> {
+            let value = jwk[name].as_str().with_context(|| format!("JWK has no '{}'", name))?;
+            URL_SAFE_NO_PAD.decode(value).with_context(|| format!("JWK '{}' is not base64url", name))
+        };
+        return match (jwk["kty"].as_str(), jwk["crv"].as_str()) {
+            (Some("OKP"), Some("Ed25519")) => ed25519_key(&coordinate("x")?),
+            (Some("EC"), Some("P-256")) => {
+                let point = [&[0x04][..], &coordinate("x")?, &coordinate("y")?].concat();
+                p256_key(&point)
+            }
+            (kty, crv) => bail!("Unsupported JWK key type {:?} with curve {:?}", kty, crv),
+        };
+    }
+    if let Some(multibase) = method["publicKeyMultibase"].as_str() {
+        return decode_multibase_key(multibase);
+    }
+    if let Some(base58) = method["publicKeyBase58"].as_str()
+        && method["type"] == "Ed25519VerificationKey2018"
+    {
+        return ed25519_key(&decode_base58(base58)?);
+    }
+    bail!("Verification method has no supported public key")
+}
+
+/// Decodes a base58btc multibase (`z...`) multicodec public key.
+fn decode_multibase_key(multibase: &str) -> Result<KeyMaterial> {
+    let encoded = multibase.strip_prefix('z').context("Only base58btc ('z') multibase keys are supported")?;
+    let bytes = decode_base58(encoded)?;
+    if let Some(key) = bytes.strip_prefix(MULTICODEC_ED25519_PUB) {
+        ed25519_key(key)
+    } else if let Some(key) = bytes.strip_prefix(MULTICODEC_P256_PUB) {
+        p256_key(key)
+    } else {
+        bail!("Only Ed25519 and P-256 multicodec keys are supported")
+    }
+}
+
+fn ed25519_key(bytes: &[u8]) -> Result<KeyMaterial> {
+    let bytes: &[u8; 32] = bytes.try_into().context("Ed25519 public keys are 32 bytes")?;
+    Ok(KeyMaterial::Ed25519(ed25519_dalek::VerifyingKey::from_bytes(bytes).context("Invalid Ed25519 public key")?))
+}
+
+fn p256_key(sec1: &[u8]) -> Result<KeyMaterial> {
+    Ok(KeyMaterial::P256(p256::ecdsa::VerifyingKey::from_sec1_bytes(sec1).context("Invalid P-256 public key")?))
+}
+
+fn decode_base58(text: &str) -> Result<Vec<u8>> {
+    // Little-endian digits of the number, in base 256.
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in text.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|&digit| digit == c).context("Invalid base58 character")?;
+        for byte in bytes.iter_mut() {
+            carry += *byte as usize * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    bytes.extend(std::iter::repeat_n(0, text.bytes().take_while(|&c| c == b'1').count()));
+    bytes.reverse();
+    Ok(bytes)
+}
+
+fn encode_base58(bytes: &[u8]) -> String {
+    // Little-endian digits of the number, in base 58.
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in bytes {
+        let mut carry = byte as usize;
+        for digit in digits.iter_mut() {
+            carry += *digit as usize * 256;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+    std::iter::repeat_n('1', zeros).chain(digits.iter().rev().map(|&digit| BASE58_ALPHABET[digit as usize] as char)).collect()
+}
+
+/// Verifies DSSE envelopes signed by one of a DID's verification keys. The attestation is the
+/// envelope itself (`payloadType`, `payload`, `signatures`), or a Sigstore bundle whose
+/// `dsseEnvelope` is signed this way; any certificate in the bundle is ignored.
+pub struct DidVerifier {
+    keys: Vec<DidKey>,
+}
+
+impl DidVerifier {
+    pub fn new(keys: Vec<DidKey>) -> Self {
+        Self { keys }
+    }
+}
+
+impl Verifier for DidVerifier {
+    fn signature_uri(&self, _uri: &str, _document: &str) -> Option<String> {
+        None
+    }
+
+    fn verify(&self, document: &str, _signature: Option<&str>) -> Result<VerifiedBundle> {
+        let mut json: Value = serde_json::from_str(document).context("Failed to parse DSSE envelope JSON")?;
+        if let Some(envelope) = json.get_mut("dsseEnvelope") {
+            json = envelope.take();
+        }
+        let envelope: DsseEnvelope = serde_json::from_value(json).context("Document is not a DSSE envelope")?;
+        let payload = STANDARD.decode(&envelope.payload).context("Failed to decode DSSE payload")?;
+        let pae = construct_pae(&envelope.payload_type, &payload);
+        for signature in &envelope.signatures {
+            let Ok(signature) = STANDARD.decode(&signature.sig) else { continue };
+            if let Some(key) = self.keys.iter().find(|key| key.verify(&pae, &signature).is_ok()) {
+                return Ok(key_signed(&payload, key.id.clone(), key.fingerprint()?));
+            }
+        }
+        bail!(
+            "No DSSE signature by a key of {}",
+            self.keys.iter().map(|key| key.id.as_str()).collect::<Vec<_>>().join(", ")
+        )
+    }
+}
@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Converts a domain to its canonical form: lowercase ASCII with Unicode labels in punycode
+/// (e.g., "Bücher.Example" becomes "xn--bcher-kva.example"). A trailing root dot is dropped and
+/// any port is kept.
+///
+/// DNS lookups, PURL namespaces, and certificate identities all compare domains in this form so
+/// that Unicode and punycode spellings of the same supplier domain are interchangeable.
+pub fn normalize_domain(domain: &str) -> Result<String> {
+    let (host, port) = match domain.rsplit_once(':') {
+        Some((host, port)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => {
+            (host, Some(port))
+        }
+        _ => (domain, None),
+    };
+    let host = host.strip_suffix('.').unwrap_or(host);
+    let ascii = idna::domain_to_ascii(host)
+        .map_err(|e| anyhow!("Invalid domain name '{}': {}", domain, e))?;
+    if ascii.is_empty() {
+        return Err(anyhow!("Invalid domain name '{}': empty", domain));
+    }
+    Ok(match port {
+        Some(port) => format!("{}:{}", ascii, port),
+        None => ascii,
+    })
+}
+
+/// Normalizes the domain part of an email identity (`user@domain`) or URI identity
+/// (`https://domain/...`) so identities compare equal regardless of how the domain is spelled.
+/// Identities that do not contain a recognizable domain are returned unchanged.
+pub fn normalize_identity(identity: &str) -> String {
+    if let Some((scheme, rest)) = identity.split_once("://") {
+        let host_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+        return match normalize_domain(&rest[..host_end]) {
+            Ok(host) => format!("{}://{}{}", scheme.to_ascii_lowercase(), host, &rest[host_end..]),
+            Err(_) => identity.to_string(),
+        };
+    }
+    if let Some((local, domain)) = identity.rsplit_once('@') {
+        return match normalize_domain(domain) {
+            Ok(domain) => format!("{}@{}", local, domain),
+            Err(_) => identity.to_string(),
+        };
+    }
+    identity.to_string()
+}
+
+/// Whether two identities name the same signer, after normalizing their domains. Email addresses
+/// compare case-insensitively, but URIs exactly: the paths and refs of workflow identities, such
+/// as `.../release.yml@refs/heads/main`, are case-sensitive.
+pub fn same_identity(a: &str, b: &str) -> bool {
+    let (a, b) = (normalize_identity(a), normalize_identity(b));
+    match a.contains("://") || b.contains("://") {
+        true => a == b,
+        false => a.eq_ignore_ascii_case(&b),
+    }
+}
+
+/// A signer identity, optionally accepted only for statements signed within a validity window.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignerIdentity {
+    /// Identity expected in the signing certificate (e.g., "release@example.com").
+    pub identity: String,
+    /// RFC 3339 time before which signatures by this identity are not accepted.
+    pub not_before: Option<String>,
+    /// RFC 3339 time after which signatures by this identity are not accepted.
+    pub not_after: Option<String>,
+}
+
+impl SignerIdentity {
+    /// An identity accepted regardless of signing time.
+    pub fn new(identity: &str) -> Self {
+        Self {
+            identity: identity.to_string(),
+            not_before: None,
+            not_after: None,
+        }
+    }
+
+    /// Returns true if a signature made at `signing_time` falls inside this identity's window.
+    pub fn accepts_signing_time(&self, signing_time: DateTime<Utc>) -> Result<bool> {
+        let parse = |time: &str| {
+            DateTime::parse_from_rfc3339(time)
+                .with_context(|| format!("Invalid validity time '{}' for identity '{}'", time, self.identity))
+        };
+        if let Some(not_before) = &self.not_before
+            && signing_time < parse(not_before)?
+        {
+            return Ok(false);
+        }
+        if let Some(not_after) = &self.not_after
+            && signing_time > parse(not_after)?
+        {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+impl std::fmt::Display for SignerIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.identity)?;
+        match (&self.not_before, &self.not_after) {
+            (None, None) => Ok(()),
+            (not_before, not_after) => write!(
+                f,
+                " (signed from {} until {})",
+                not_before.as_deref().unwrap_or("any time"),
+                not_after.as_deref().unwrap_or("any time")
+            ),
+        }
+    }
+}
+
+/// Build context recorded in the extensions of a Fulcio signing certificate issued to a CI
+/// workflow (see <https://github.com/sigstore/fulcio/blob/main/docs/oid-info.md>).
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct FulcioExtensions {
+    /// OIDC issuer that authenticated the signer (e.g., "https://token.actions.githubusercontent.com")
+    pub issuer: Option<String>,
+    /// Repository the build ran in (e.g., "https://github.com/example/app")
+    pub source_repository_uri: Option<String>,
+    /// Git ref the build ran on (e.g., "refs/tags/v1.2.0")
+    pub source_repository_ref: Option<String>,
+    /// Commit the build ran on
+    pub source_repository_digest: Option<String>,
+    /// Workflow that signed, with its ref (e.g., ".../.github/workflows/release.yml@refs/tags/v1.2.0")
+    pub build_signer_uri: Option<String>,
+    /// Event that triggered the build (e.g., "push")
+    pub build_trigger: Option<String>,
+    /// Whether the build ran on hosted or self-hosted runners
+    pub runner_environment: Option<String>,
+}
+
+/// Constraints on the CI build context a statement must have been signed in, checked against the
+/// signing certificate's Fulcio extensions. Unset fields are not checked.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildPolicy {
+    /// Repository the build must have run in (e.g., "https://github.com/example/app")
+    pub source_repository: Option<String>,
+    /// Pattern the build's git ref must match, where `*` matches any characters (e.g., "refs/tags/*")
+    pub source_ref: Option<String>,
+    /// Event that must have triggered the build (e.g., "push")
+    pub build_trigger: Option<String>,
+}
+
+impl BuildPolicy {
+    /// True if the policy constrains anything.
+    pub fn is_empty(&self) -> bool {
+        self.source_repository.is_none() && self.source_ref.is_none() && self.build_trigger.is_none()
+    }
+
+    /// Fails unless the certificate's build context satisfies every constraint.
+    pub fn check(&self, extensions: &FulcioExtensions) -> Result<()> {
+        let require = |name: &str, actual: &Option<String>, accepts: &dyn Fn(&str) -> bool, expected: &str| {
+            match actual {
+                Some(actual) if accepts(actual) => Ok(()),
+                Some(actual) => bail!("Certificate {} '{}' does not match the required '{}'", name, actual, expected),
+                None => bail!("Certificate records no {}, but '{}' is required", name, expected),
+            }
+        };
+        if let Some(repository) = &self.source_repository {
+            let expected = repository.trim_end_matches('/');
+            require(
+                "source repository",
+                &extensions.source_repository_uri,
+                &|actual| actual.trim_end_matches('/').eq_ignore_ascii_case(expected),
+                repository,
+            )?;
+        }
+        if let Some(pattern) = &self.source_ref {
+            require("source ref", &extensions.source_repository_ref, &|actual| wildcard_match(pattern, actual), pattern)?;
+        }
+        if let Some(trigger) = &self.build_trigger {
+            require("build trigger", &extensions.build_trigger, &|actual| actual == trigger, trigger)?;
+        }
+        Ok(())
+    }
+}
+
+/// Matches `text` against a pattern in which `*` stands for any run of characters.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
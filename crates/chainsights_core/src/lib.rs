@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parsing and signature verification of Chainsights bundles, without any I/O.
+//!
+//! Callers fetch bundles and resolve DNS records themselves and hand the bytes to this crate
+//! ([`txt`] parses the `_chainsights` TXT records they find), which keeps it buildable for
+//! WebAssembly targets such as browser extensions and serverless platforms.
+//!
+//! The `verify` feature (on by default) provides signature verification: of Sigstore bundles,
+//! of SSH and minisign signatures through [`verifier::Verifier`], and of DSSE envelopes signed by
+//! the keys of a `did:web` or `did:key` DID ([`did`]). Without it the crate
+//! only models and parses bundles and statements, and has no cryptography dependencies. The
+//! `pgp` feature adds verification of PGP-signed statements. [`tlsa`] matches TLS certificates
+//! against DANE TLSA records.
+
+pub mod bundle;
+#[cfg(feature = "verify")]
+pub mod did;
+pub mod identity;
+#[cfg(feature = "pgp")]
+pub mod pgp;
+pub mod statement;
+pub mod timestamp;
+#[cfg(feature = "verify")]
+pub mod tlsa;
+pub mod trust;
+pub mod txt;
+#[cfg(feature = "verify")]
+pub mod verifier;
+#[cfg(feature = "verify")]
+pub mod verify;
@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow, bail};
+use pgp::types::PublicKeyTrait;
+use pgp::cleartext::CleartextSignedMessage;
+use pgp::{Deserializable, SignedPublicKey, StandaloneSignature};
+
+use crate::verifier::{Verifier, key_signed};
+use crate::verify::VerifiedBundle;
+
+const PUBLIC_KEY_BEGIN: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----";
+const PUBLIC_KEY_END: &str = "-----END PGP PUBLIC KEY BLOCK-----";
+const CLEARTEXT_BEGIN: &str = "-----BEGIN PGP SIGNED MESSAGE-----";
+
+/// PGP public keys that statements may be signed with, e.g. from a distribution keyring or an
+/// Apache project's KEYS file. Which of them a statement must be signed with is pinned by
+/// fingerprint in the link to it.
+#[derive(Debug, Clone, Default)]
+pub struct PgpKeyring {
+    keys: Vec<SignedPublicKey>,
+}
+
+impl PgpKeyring {
+    /// Parses every armored public key block in `text`, skipping anything between them (such as
+    /// the `gpg --list-sigs` output in KEYS files). Keys whose self-signatures don't verify are
+    /// rejected.
+    pub fn from_armored(text: &str) -> Result<Self> {
+        let mut keys = Vec::new();
+        let mut rest = text;
+        while let Some(start) = rest.find(PUBLIC_KEY_BEGIN) {
+            let end = rest[start..]
+                .find(PUBLIC_KEY_END)
+                .map(|end| start + end + PUBLIC_KEY_END.len())
+                .context("Unterminated PGP public key block")?;
+            let (parsed, _) = SignedPublicKey::from_string_many(&rest[start..end])
+                .map_err(|e| anyhow!("Failed to parse PGP public key block: {}", e))?;
+            for key in parsed {
+                let key = key.map_err(|e| anyhow!("Failed to parse PGP public key: {}", e))?;
+                key.verify()
+                    .map_err(|e| anyhow!("Invalid self-signature on PGP key {}: {}", fingerprint_hex(&key), e))?;
+                keys.push(key);
+            }
+            rest = &rest[end..];
+        }
+        if keys.is_empty() {
+            bail!("No PGP public keys found");
+        }
+        Ok(Self { keys })
+    }
+
+    /// Uppercase hex fingerprints of the primary keys.
+    pub fn fingerprints(&self) -> Vec<String> {
+        self.keys.iter().map(fingerprint_hex).collect()
+    }
+
+    /// Finds a key among those pinned by `fingerprints` whose primary key or subkey made
+    /// `signature` over `data`, returning the primary key's fingerprint.
+    fn verify(&self, signature: &StandaloneSignature, data: &[u8], fingerprints: &[String]) -> Option<String> {
+        let pinned = |fingerprint: &str| fingerprints.iter().any(|pin| normalize_fingerprint(pin) == fingerprint);
+        self.keys.iter().find_map(|key| {
+            let primary = fingerprint_hex(key);
+            let primary_pinned = pinned(&primary);
+            let by_primary = primary_pinned && signature.verify(&key.primary_key, data).is_ok();
+            let by_subkey = key.public_subkeys.iter().any(|subkey| {
+                (primary_pinned || pinned(&hex_upper(subkey.key.fingerprint().as_bytes())))
+                    && signature.verify(&subkey.key, data).is_ok()
+            });
+            (by_primary || by_subkey).then_some(primary)
+        })
+    }
+}
+
+/// True if `text` is a cleartext-signed PGP message rather than a document with a detached
+/// signature.
+pub fn is_pgp_cleartext(text: &str) -> bool {
+    text.trim_start().starts_with(CLEARTEXT_BEGIN)
+}
+
+/// Verifies a cleartext-signed PGP message against the keys in `keyring` pinned by
+/// `fingerprints` and returns its signed text, with CRLF line endings, as the payload. The signer
+/// identity is the fingerprint of the signing key's primary key.
+pub fn verify_pgp_cleartext(text: &str, keyring: &PgpKeyring, fingerprints: &[String]) -> Result<VerifiedBundle> {
+    let (message, _) = CleartextSignedMessage::from_string(text.trim_start())
+        .map_err(|e| anyhow!("Failed to parse PGP cleartext message: {}", e))?;
+    let signed_text = message.signed_text();
+    let signer = message
+        .signatures()
+        .iter()
+        .find_map(|signature| keyring.verify(signature, signed_text.as_bytes(), fingerprints));
+    verified(signer, &signed_text, fingerprints)
+}
+
+/// Verifies an armored detached PGP signature over `payload` against the keys in `keyring`
+/// pinned by `fingerprints`. The signer identity is the fingerprint of the signing key's primary
+/// key.
+pub fn verify_pgp_detached(
+    payload: &str,
+    signature: &str,
+    keyring: &PgpKeyring,
+    fingerprints: &[String],
+) -> Result<VerifiedBundle> {
+    let (signatures, _) = StandaloneSignature::from_string_many(signature)
+        .map_err(|e| anyhow!("Failed to parse detached PGP signature: {}", e))?;
+    let mut signer = None;
+    for signature in signatures {
+        let signature = signature.map_err(|e| anyhow!("Failed to parse detached PGP signature: {}", e))?;
+        signer = keyring.verify(&signature, payload.as_bytes(), fingerprints);
+        if signer.is_some() {
+            break;
+        }
+    }
+    verified(signer, payload, fingerprints)
+}
+
+/// Verifies PGP-signed statements against the keys in a keyring pinned by fingerprint:
+/// cleartext-signed, or with an armored detached signature at `<uri>.asc`.
+pub struct PgpVerifier {
+    keyring: Arc<PgpKeyring>,
+    fingerprints: Vec<String>,
+}
+
+impl PgpVerifier {
+    pub fn new(keyring: Arc<PgpKeyring>, fingerprints: Vec<String>) -> Self {
+        Self { keyring, fingerprints }
+    }
+}
+
+impl Verifier for PgpVerifier {
+    fn signature_uri(&self, uri: &str, document: &str) -> Option<String> {
+        (!is_pgp_cleartext(document)).then(|| format!("{}.asc", uri))
+    }
+
+    fn verify(&self, document: &str, signature: Option<&str>) -> Result<VerifiedBundle> {
+        match signature {
+            Some(signature) => verify_pgp_detached(document, signature, &self.keyring, &self.fingerprints),
+            None => verify_pgp_cleartext(document, &self.keyring, &self.fingerprints),
+        }
+    }
+}
+
+fn verified(signer: Option<String>, payload: &str, fingerprints: &[String]) -> Result<VerifiedBundle> {
+    let signer_identity = signer.with_context(|| {
+        format!(
+            "No valid PGP signature by a key with fingerprint {}",
+            fingerprints.iter().map(|pin| normalize_fingerprint(pin)).collect::<Vec<_>>().join(", ")
+        )
+    })?;
+    Ok(key_signed(payload.as_bytes(), signer_identity.clone(), signer_identity))
+}
+
+/// Uppercase hex without spaces or a `0x` prefix, as fingerprints are compared.
+fn normalize_fingerprint(fingerprint: &str) -> String {
+    let fingerprint: String = fingerprint.chars().filter(|c| !c.is_whitespace()).collect();
+    let fingerprint = fingerprint.strip_prefix("0x").unwrap_or(&fingerprint);
+    fingerprint.to_ascii_uppercase()
+}
+
+fn fingerprint_hex(key: &SignedPublicKey) -> String {
+    hex_upper(key.primary_key.fingerprint().as_bytes())
+}
+
+fn hex_upper(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
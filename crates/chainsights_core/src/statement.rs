@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Represents the in-toto statement structure.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InTotoStatement {
+    #[serde(rename = "_type")]
+    _type: String,
+    subject: Vec<Subject>,
+    pub predicate_type: String,
+    pub predicate: serde_json::Value,
+}
+
+impl InTotoStatement {
+    /// Builds an in-toto v1 statement about a single subject.
+    pub fn new(
+        subject_uri: &str,
+        subject_digest: HashMap<String, String>,
+        predicate_type: &str,
+        predicate: serde_json::Value,
+    ) -> Self {
+        Self {
+            _type: "https://in-toto.io/Statement/v1".to_string(),
+            subject: vec![Subject {
+                name: None,
+                uri: Some(subject_uri.to_string()),
+                digest: subject_digest,
+            }],
+            predicate_type: predicate_type.to_string(),
+            predicate,
+        }
+    }
+}
+
+// TODO: Support the entire resource descriptor
+/// This is a simplified ResourceDescriptor for the subject of the in-toto statement.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Subject {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    uri: Option<String>,
+    // TODO: We currently ignore this, but we should verify it.
+    digest: HashMap<String, String>,
+}
@@ -0,0 +1,188 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+/// Where the time used to judge certificate validity, freshness and expiry comes from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum TimeSource {
+    /// The local system clock
+    #[default]
+    Local,
+    /// The integrated time of the bundle's Rekor transparency log entry
+    Rekor,
+    /// The RFC 3161 timestamp embedded in the bundle
+    Rfc3161,
+}
+
+/// DER encodings of the digest algorithm OIDs accepted in a timestamp's message imprint.
+const SHA256_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+const SHA384_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02];
+const SHA512_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03];
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_OID: u8 = 0x06;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_EXPLICIT_0: u8 = 0xa0;
+
+/// DER encoding of the CMS messageDigest signed attribute OID (1.2.840.113549.1.9.4).
+const MESSAGE_DIGEST_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x04];
+
+const TAG_EXPLICIT_1: u8 = 0xa1;
+
+/// An RFC 3161 timestamp token whose message imprint covers a bundle signature. The timestamp
+/// authority's signature over it is checked separately, against the authorities of a trusted root.
+pub struct Rfc3161Token<'a> {
+    /// When the timestamp authority saw the signature
+    pub gen_time: DateTime<Utc>,
+    /// The DER SET of signed attributes the authority signed, which binds the TSTInfo through
+    /// its messageDigest attribute
+    pub signed_attributes: Vec<u8>,
+    /// The authority's signature over `signed_attributes`
+    pub signature: &'a [u8],
+}
+
+/// Parses a DER-encoded RFC 3161 timestamp token, after checking that its message imprint is a
+/// digest of `signature` and that its signed attributes cover its TSTInfo.
+pub fn parse_rfc3161_token<'a>(token: &'a [u8], signature: &[u8]) -> Result<Rfc3161Token<'a>> {
+    // ContentInfo { contentType, [0] SignedData }
+    let (content_info, _) = expect(token, TAG_SEQUENCE)?;
+    let (_, rest) = expect(content_info, TAG_OID)?;
+    let (explicit, _) = expect(rest, TAG_EXPLICIT_0)?;
+    // SignedData { version, digestAlgorithms, encapContentInfo, [0] certificates, [1] crls, signerInfos }
+    let (signed_data, _) = expect(explicit, TAG_SEQUENCE)?;
+    let (_, rest) = expect(signed_data, TAG_INTEGER)?;
+    let (_, rest) = expect(rest, TAG_SET)?;
+    let (encap, mut rest) = expect(rest, TAG_SEQUENCE)?;
+    for optional in [TAG_EXPLICIT_0, TAG_EXPLICIT_1] {
+        if rest.first() == Some(&optional) {
+            rest = expect(rest, optional)?.1;
+        }
+    }
+    let (signer_infos, _) = expect(rest, TAG_SET)?;
+    // EncapsulatedContentInfo { eContentType, [0] OCTET STRING TSTInfo }
+    let (_, encap_rest) = expect(encap, TAG_OID)?;
+    let (explicit, _) = expect(encap_rest, TAG_EXPLICIT_0)?;
+    let (tst_info_der, _) = expect(explicit, TAG_OCTET_STRING)?;
+    // TSTInfo { version, policy, messageImprint, serialNumber, genTime, ... }
+    let (tst_info, _) = expect(tst_info_der, TAG_SEQUENCE)?;
+    let (_, rest) = expect(tst_info, TAG_INTEGER)?;
+    let (_, rest) = expect(rest, TAG_OID)?;
+    let (imprint, rest) = expect(rest, TAG_SEQUENCE)?;
+    let (_, rest) = expect(rest, TAG_INTEGER)?;
+    let (gen_time, _) = expect(rest, TAG_GENERALIZED_TIME)?;
+
+    let (algorithm, hashed_message) = expect(imprint, TAG_SEQUENCE)?;
+    let (algorithm_oid, _) = expect(algorithm, TAG_OID)?;
+    let (hashed_message, _) = expect(hashed_message, TAG_OCTET_STRING)?;
+    if hashed_message != digest(algorithm_oid, signature)?.as_slice() {
+        bail!("RFC 3161 timestamp does not cover the bundle signature");
+    }
+
+    // SignerInfo { version, sid, digestAlgorithm, [0] signedAttrs, signatureAlgorithm, signature }
+    let (signer_info, _) = expect(signer_infos, TAG_SEQUENCE)?;
+    let (_, rest) = expect(signer_info, TAG_INTEGER)?;
+    let (_, _, rest) = any(rest)?;
+    let (digest_algorithm, rest) = expect(rest, TAG_SEQUENCE)?;
+    let (digest_oid, _) = expect(digest_algorithm, TAG_OID)?;
+    let (attributes, rest) = expect(rest, TAG_EXPLICIT_0).context("RFC 3161 timestamp has no signed attributes")?;
+    let (_, rest) = expect(rest, TAG_SEQUENCE)?;
+    let (token_signature, _) = expect(rest, TAG_OCTET_STRING)?;
+
+    let mut message_digest = None;
+    let mut remaining = attributes;
+    while !remaining.is_empty() {
+        let (attribute, rest) = expect(remaining, TAG_SEQUENCE)?;
+        remaining = rest;
+        let (oid, values) = expect(attribute, TAG_OID)?;
+        if oid == MESSAGE_DIGEST_OID {
+            let (values, _) = expect(values, TAG_SET)?;
+            message_digest = Some(expect(values, TAG_OCTET_STRING)?.0);
+        }
+    }
+    let message_digest = message_digest.context("RFC 3161 timestamp has no messageDigest attribute")?;
+    if message_digest != digest(digest_oid, tst_info_der)?.as_slice() {
+        bail!("RFC 3161 timestamp's signed attributes do not cover its TSTInfo");
+    }
+
+    // The signature covers the attributes encoded as a SET rather than with their implicit tag.
+    let mut signed_attributes = der_header(TAG_SET, attributes.len());
+    signed_attributes.extend_from_slice(attributes);
+    Ok(Rfc3161Token { gen_time: parse_generalized_time(gen_time)?, signed_attributes, signature: token_signature })
+}
+
+/// Digests `data` with the algorithm a DER OID names.
+fn digest(algorithm_oid: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    Ok(match algorithm_oid {
+        SHA256_OID => Sha256::digest(data).to_vec(),
+        SHA384_OID => Sha384::digest(data).to_vec(),
+        SHA512_OID => Sha512::digest(data).to_vec(),
+        _ => bail!("Unsupported digest algorithm in RFC 3161 timestamp"),
+    })
+}
+
+/// Parses a DER GeneralizedTime (`YYYYMMDDHHMMSS[.fff]Z`), ignoring fractional seconds.
+fn parse_generalized_time(value: &[u8]) -> Result<DateTime<Utc>> {
+    let text = std::str::from_utf8(value).context("RFC 3161 genTime is not ASCII")?;
+    let seconds = text
+        .strip_suffix('Z')
+        .map(|t| t.split('.').next().unwrap_or(t))
+        .with_context(|| format!("RFC 3161 genTime '{}' is not in UTC", text))?;
+    let time = NaiveDateTime::parse_from_str(seconds, "%Y%m%d%H%M%S")
+        .with_context(|| format!("Invalid RFC 3161 genTime '{}'", text))?;
+    Ok(time.and_utc())
+}
+
+/// Splits the leading DER element with the given tag off `input`, returning its contents and the
+/// bytes that follow it.
+fn expect(input: &[u8], tag: u8) -> Result<(&[u8], &[u8])> {
+    let (actual, content, rest) = any(input)?;
+    if actual != tag {
+        bail!("Unexpected DER tag 0x{:02x}, expected 0x{:02x}", actual, tag);
+    }
+    Ok((content, rest))
+}
+
+/// The DER tag and length header of an element with `length` bytes of content.
+fn der_header(tag: u8, length: usize) -> Vec<u8> {
+    let mut header = vec![tag];
+    match length {
+        0..=0x7f => header.push(length as u8),
+        _ => {
+            let bytes: Vec<u8> = length.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+            header.push(0x80 | bytes.len() as u8);
+            header.extend(bytes);
+        }
+    }
+    header
+}
+
+/// Splits the leading DER element off `input`, returning its tag, its contents and the bytes that
+/// follow it.
+fn any(input: &[u8]) -> Result<(u8, &[u8], &[u8])> {
+    let (&actual, rest) = input.split_first().context("Truncated DER element")?;
+    let (&first, mut rest) = rest.split_first().context("Truncated DER length")?;
+    let length = if first < 0x80 {
+        first as usize
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > 4 || rest.len() < count {
+            bail!("Unsupported DER length encoding");
+        }
+        let length = rest[..count].iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        rest = &rest[count..];
+        length
+    };
+    if rest.len() < length {
+        bail!("Truncated DER element");
+    }
+    let (content, rest) = rest.split_at(length);
+    Ok((actual, content, rest))
+}
@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use anyhow::{Context, Result, bail};
+use sha2::{Digest, Sha256, Sha512};
+use x509_parser::parse_x509_certificate;
+
+/// A DANE TLSA record (RFC 6698): a certificate or public key that a TLS server on a given port
+/// and host must present, published at `_<port>._tcp.<host>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsaRecord {
+    /// 0 and 2 constrain the issuing CA or trust anchor; 1 and 3 the server's own certificate.
+    pub usage: u8,
+    /// 0 for the whole certificate, 1 for its SubjectPublicKeyInfo.
+    pub selector: u8,
+    /// 0 for the selected data itself, 1 for its SHA-256 and 2 for its SHA-512.
+    pub matching_type: u8,
+    pub data: Vec<u8>,
+}
+
+impl FromStr for TlsaRecord {
+    type Err = anyhow::Error;
+
+    /// Parses the presentation format, e.g. `3 1 1 0C72AC70...`.
+    fn from_str(text: &str) -> Result<Self> {
+        let mut fields = text.split_whitespace();
+        let mut number = |name: &str| -> Result<u8> {
+            let field = fields.next().with_context(|| format!("TLSA record has no {}", name))?;
+            field.parse().with_context(|| format!("Invalid TLSA {} '{}'", name, field))
+        };
+        let (usage, selector, matching_type) = (number("usage")?, number("selector")?, number("matching type")?);
+        let hex: String = fields.collect();
+        if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+            bail!("TLSA certificate data is not hex");
+        }
+        let data = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .context("TLSA certificate data is not hex")?;
+        Ok(Self { usage, selector, matching_type, data })
+    }
+}
+
+impl TlsaRecord {
+    /// True for records about the server's own certificate (PKIX-EE and DANE-EE), which can be
+    /// checked against the leaf certificate alone.
+    pub fn is_end_entity(&self) -> bool {
+        matches!(self.usage, 1 | 3)
+    }
+
+    /// Checks whether a DER-encoded certificate matches the record's selector and matching type.
+    /// The usage is not considered.
+    pub fn matches(&self, certificate_der: &[u8]) -> Result<bool> {
+        let selected = match self.selector {
+            0 => certificate_der.to_vec(),
+            1 => {
+                let (_, certificate) =
+                    parse_x509_certificate(certificate_der).context("Failed to parse TLS certificate")?;
+                certificate.public_key().raw.to_vec()
+            }
+            other => bail!("Unsupported TLSA selector {}", other),
+        };
+        let digest = match self.matching_type {
+            0 => selected,
+            1 => Sha256::digest(&selected).to_vec(),
+            2 => Sha512::digest(&selected).to_vec(),
+            other => bail!("Unsupported TLSA matching type {}", other),
+        };
+        Ok(digest == self.data)
+    }
+}
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::bundle::{CertificateData, LogId};
+
+/// Media type of the Sigstore trusted root format this struct models.
+pub const TRUSTED_ROOT_MEDIA_TYPE: &str = "application/vnd.dev.sigstore.trustedroot+json;version=0.1";
+
+/// The trust material of a Sigstore instance, in the `trusted_root.json` format that Sigstore
+/// distributes via TUF and that sigstore-scaffolding and `cosign trusted-root create` produce for
+/// private instances: Fulcio certificate chains, Rekor log keys and CT log keys.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedRoot {
+    #[serde(default)]
+    pub media_type: String,
+    /// Rekor transparency logs whose signed entry timestamps are trusted
+    #[serde(default)]
+    pub tlogs: Vec<TransparencyLogInstance>,
+    /// Fulcio instances whose certificates are trusted
+    #[serde(default)]
+    pub certificate_authorities: Vec<CertificateAuthority>,
+    /// Certificate transparency logs whose signed certificate timestamps are trusted
+    #[serde(default)]
+    pub ctlogs: Vec<TransparencyLogInstance>,
+    /// RFC 3161 timestamp authorities whose timestamps are trusted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timestamp_authorities: Vec<CertificateAuthority>,
+}
+
+/// A Rekor or CT log and the key it signs with.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TransparencyLogInstance {
+    #[serde(default)]
+    pub base_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash_algorithm: Option<String>,
+    pub public_key: TrustedPublicKey,
+    /// SHA-256 of the DER public key
+    pub log_id: LogId,
+}
+
+/// A public key and the period it was used to sign in.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedPublicKey {
+    pub raw_bytes: String, // Base64 encoded DER SubjectPublicKeyInfo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_details: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_for: Option<ValidityPeriod>,
+}
+
+/// A Fulcio instance and the certificate chain it issues from.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateAuthority {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    pub cert_chain: CertificateChain,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_for: Option<ValidityPeriod>,
+}
+
+/// Certificates from the issuing certificate up to the root.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CertificateChain {
+    pub certificates: Vec<CertificateData>,
+}
+
+/// When trust material was in use, as RFC 3339 times. An open end means it still is.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ValidityPeriod {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+}
+
+impl ValidityPeriod {
+    /// True if `time` falls within the period. Unparsable bounds contain nothing.
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        let parse = |bound: &str| DateTime::parse_from_rfc3339(bound).map(|bound| bound.with_timezone(&Utc));
+        self.start.as_deref().is_none_or(|start| parse(start).is_ok_and(|start| time >= start))
+            && self.end.as_deref().is_none_or(|end| parse(end).is_ok_and(|end| time <= end))
+    }
+}
+
+impl TrustedRoot {
+    /// Parses a `trusted_root.json` document.
+    pub fn from_json(text: &str) -> Result<Self> {
+        let root: Self = serde_json::from_str(text).context("Failed to parse trusted root JSON")?;
+        if root.certificate_authorities.is_empty() {
+            bail!("Trusted root names no certificate authorities");
+        }
+        Ok(root)
+    }
+}
@@ -0,0 +1,236 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt;
+
+use anyhow::{Context, Result, bail};
+
+use crate::identity::{normalize_identity, same_identity};
+
+/// The version tag of the current `_chainsights` TXT record format.
+pub const RECORD_VERSION: &str = "chainsights1";
+
+/// A domain's `_chainsights` TXT record: where its root catalog is published and who signs it.
+///
+/// Records are `attribute=value` pairs separated by whitespace, led by `v=chainsights1`:
+///
+/// ```text
+/// v=chainsights1 uri=https://example.com/chainsights.jsonl identity=security@example.com
+/// ```
+///
+/// As in RFC 1464, a backquote quotes the next character, so attributes and values can contain
+/// spaces, `=` and backquotes (`` identity=Release` Team ``). A record may be split across
+/// several character strings, which are concatenated first. Records without a version tag are
+/// read the same way, for publishers that predate it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainsightsRecord {
+    /// Where the root catalog is published.
+    pub uri: String,
+    /// The identity the root catalog must be signed by.
+    pub identity: String,
+    /// The `key=` pin, if any: a fingerprint of the key or certificate the root catalog must be
+    /// signed with, or the URI of a published public key.
+    pub key: Option<String>,
+}
+
+impl ChainsightsRecord {
+    /// Parses one TXT record, given as its character strings. Returns `Ok(None)` for records
+    /// that are not Chainsights records, such as SPF or site verification records, and an error
+    /// for Chainsights records that are malformed or of an unsupported version.
+    pub fn parse<S: AsRef<str>>(strings: &[S]) -> Result<Option<Self>> {
+        let text: String = strings.iter().map(AsRef::as_ref).collect();
+        let pairs = match split_pairs(&text) {
+            Ok(pairs) => pairs,
+            Err(e) if text.trim_start().starts_with("v=chainsights") || text.contains("uri=") => return Err(e),
+            Err(_) => return Ok(None),
+        };
+        match pairs.first() {
+            Some((attribute, version)) if attribute == "v" => {
+                if !version.starts_with("chainsights") {
+                    return Ok(None);
+                }
+                if version != RECORD_VERSION {
+                    bail!("Unsupported record version '{}', expected '{}'", version, RECORD_VERSION);
+                }
+            }
+            // Unversioned records are only taken for ours if they name a catalog.
+            _ if !pairs.iter().any(|(attribute, _)| attribute == "uri") => return Ok(None),
+            _ => {}
+        }
+        if pairs.iter().any(|(attribute, _)| attribute == "delegate") {
+            return Ok(None);
+        }
+
+        let (mut uri, mut identity, mut key) = (None, None, None);
+        for (attribute, value) in pairs.into_iter().skip_while(|(attribute, _)| attribute == "v") {
+            let slot = match attribute.as_str() {
+                "uri" => &mut uri,
+                "identity" => &mut identity,
+                "key" => &mut key,
+                "v" => bail!("The version tag must come first"),
+                // Attributes added by later minor revisions are ignored.
+                _ => continue,
+            };
+            if value.is_empty() {
+                bail!("Attribute '{}' has an empty value", attribute);
+            }
+            if slot.replace(value).is_some() {
+                bail!("Attribute '{}' appears more than once", attribute);
+            }
+        }
+        Ok(Some(Self {
+            uri: uri.context("Record has no 'uri' attribute")?,
+            identity: identity.context("Record has no 'identity' attribute")?,
+            key,
+        }))
+    }
+}
+
+impl fmt::Display for ChainsightsRecord {
+    /// Formats the record as it is published, with the version tag and escaped values.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v={} uri={} identity={}", RECORD_VERSION, escape(&self.uri), escape(&self.identity))?;
+        if let Some(key) = &self.key {
+            write!(f, " key={}", escape(key))?;
+        }
+        Ok(())
+    }
+}
+
+/// A `_chainsights` TXT record delegating a subdomain's namespace to the subdomain's own root.
+///
+/// A domain may publish any number of these beside its own root record, or instead of one:
+///
+/// ```text
+/// v=chainsights1 delegate=cloud.example.com
+/// v=chainsights1 delegate=store.example.com identity=https://github.com/acme/store/.github/workflows/release.yml@refs/heads/main
+/// ```
+///
+/// Consumers following the delegation look up `_chainsights.cloud.example.com`. The delegated
+/// root must be signed by an identity within the delegated namespace (an email address or URI at
+/// the subdomain or below it), or by the `identity=` the delegation pins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelegationRecord {
+    /// The delegated subdomain.
+    pub namespace: String,
+    /// The identity the delegated root must be signed by, if the delegating domain pins one.
+    pub identity: Option<String>,
+}
+
+impl DelegationRecord {
+    /// Parses one TXT record, given as its character strings. Returns `Ok(None)` for records
+    /// that are not versioned Chainsights delegation records.
+    pub fn parse<S: AsRef<str>>(strings: &[S]) -> Result<Option<Self>> {
+        let text: String = strings.iter().map(AsRef::as_ref).collect();
+        let Ok(pairs) = split_pairs(&text) else {
+            return Ok(None);
+        };
+        match pairs.first() {
+            Some((attribute, version)) if attribute == "v" && version == RECORD_VERSION => {}
+            _ => return Ok(None),
+        }
+        if !pairs.iter().any(|(attribute, _)| attribute == "delegate") {
+            return Ok(None);
+        }
+
+        let (mut namespace, mut identity) = (None, None);
+        for (attribute, value) in pairs.into_iter().skip(1) {
+            let slot = match attribute.as_str() {
+                "delegate" => &mut namespace,
+                "identity" => &mut identity,
+                "uri" | "key" => bail!("A delegation record cannot also name a catalog"),
+                "v" => bail!("The version tag must come first"),
+                _ => continue,
+            };
+            if value.is_empty() {
+                bail!("Attribute '{}' has an empty value", attribute);
+            }
+            if slot.replace(value).is_some() {
+                bail!("Attribute '{}' appears more than once", attribute);
+            }
+        }
+        Ok(Some(Self { namespace: namespace.context("Record has no 'delegate' attribute")?, identity }))
+    }
+
+    /// Whether a delegated root signed by `identity` stays within the delegation: it is the
+    /// pinned identity, or an email address or URI whose domain is the namespace or below it.
+    /// Domains must be given in their normalized form.
+    pub fn permits(&self, identity: &str) -> bool {
+        if let Some(pinned) = &self.identity {
+            return same_identity(pinned, identity);
+        }
+        let identity = normalize_identity(identity);
+        let domain = match identity.split_once("://") {
+            Some((_, rest)) => rest.split(['/', '?', '#']).next().map(|host| host.rsplit_once(':').map_or(host, |(host, _)| host)),
+            None => identity.rsplit_once('@').map(|(_, domain)| domain),
+        };
+        let namespace = self.namespace.to_ascii_lowercase();
+        domain.is_some_and(|domain| {
+            let domain = domain.to_ascii_lowercase();
+            domain == namespace || domain.ends_with(&format!(".{}", namespace))
+        })
+    }
+}
+
+impl fmt::Display for DelegationRecord {
+    /// Formats the record as it is published.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v={} delegate={}", RECORD_VERSION, escape(&self.namespace))?;
+        if let Some(identity) = &self.identity {
+            write!(f, " identity={}", escape(identity))?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits a record into `attribute=value` pairs, resolving backquote escapes. Attributes are
+/// lowercased; values are kept as written.
+fn split_pairs(text: &str) -> Result<Vec<(String, String)>> {
+    let mut pairs = Vec::new();
+    let mut chars = text.chars();
+    let mut current: Option<(String, Option<String>)> = None;
+    let finish = |current: Option<(String, Option<String>)>, pairs: &mut Vec<(String, String)>| -> Result<()> {
+        match current {
+            Some((attribute, Some(value))) => pairs.push((attribute.to_ascii_lowercase(), value)),
+            Some((attribute, None)) => bail!("'{}' is not an attribute=value pair", attribute),
+            None => {}
+        }
+        Ok(())
+    };
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            finish(current.take(), &mut pairs)?;
+            continue;
+        }
+        let (attribute, value) = current.get_or_insert_with(Default::default);
+        let literal = match c {
+            '`' => chars.next().context("Record ends with an unfinished backquote escape")?,
+            '=' if value.is_none() => {
+                if attribute.is_empty() {
+                    bail!("Record has a value without an attribute name");
+                }
+                *value = Some(String::new());
+                continue;
+            }
+            c => c,
+        };
+        match value {
+            Some(value) => value.push(literal),
+            None => attribute.push(literal),
+        }
+    }
+    finish(current, &mut pairs)?;
+    Ok(pairs)
+}
+
+/// Backquote-escapes whitespace and backquotes in a value. An `=` after the first needs no
+/// escape.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c.is_whitespace() || c == '`' {
+            escaped.push('`');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
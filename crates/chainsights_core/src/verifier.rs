@@ -0,0 +1,419 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD, engine::general_purpose::STANDARD_NO_PAD};
+use blake2::Blake2b512;
+use ed25519_dalek::Verifier as _;
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::identity::FulcioExtensions;
+use crate::verify::{VerifiedBundle, certificate_fingerprints, public_key_fingerprint};
+
+/// Namespace statements must be signed in with `ssh-keygen -Y sign -n`, so that signatures made
+/// for another purpose (e.g. git commits, which use "git") are not accepted.
+pub const SSH_SIGNATURE_NAMESPACE: &str = "chainsights";
+
+const SSH_SIGNATURE_BEGIN: &str = "-----BEGIN SSH SIGNATURE-----";
+const SSH_SIGNATURE_END: &str = "-----END SSH SIGNATURE-----";
+const SSH_ED25519: &str = "ssh-ed25519";
+const SSH_ECDSA_P256: &str = "ecdsa-sha2-nistp256";
+
+/// A signature scheme other than Sigstore bundles that statements can be signed with. Each
+/// verifier holds the keys a link pins; traversal picks one for a link by the keys it pins.
+pub trait Verifier: Send + Sync {
+    /// Where the detached signature of the document at `uri` is published, or None if
+    /// `document` carries its own signature.
+    fn signature_uri(&self, uri: &str, document: &str) -> Option<String>;
+
+    /// Verifies `document`, with its detached signature if it has one, and returns the signed
+    /// statement as the payload and the signing key as the signer identity.
+    fn verify(&self, document: &str, signature: Option<&str>) -> Result<VerifiedBundle>;
+}
+
+/// A public key pinned for key-signed statements: an OpenSSH public key line
+/// (`ssh-ed25519 AAAA... comment`) or a minisign public key (`RWQ...`, optionally with its
+/// `untrusted comment:` line). In DNS TXT records, where values can't contain spaces, keys are
+/// written `ssh-ed25519:AAAA...` and `minisign:RWQ...`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PublicKey {
+    Ssh(SshPublicKey),
+    Minisign(MinisignPublicKey),
+}
+
+impl FromStr for PublicKey {
+    type Err = anyhow::Error;
+
+    fn from_str(key: &str) -> Result<Self> {
+        let key = key.trim();
+        if let Some(key) = key.strip_prefix("minisign:") {
+            key.parse().map(Self::Minisign)
+        } else if key.starts_with("ssh-") || key.starts_with("ecdsa-") {
+            key.parse().map(Self::Ssh)
+        } else {
+            key.parse().map(Self::Minisign)
+        }
+    }
+}
+
+/// True if a root signer identity, e.g. from a `_chainsights` TXT record, is a public key in
+/// its compact form rather than a certificate identity.
+pub fn is_public_key_identity(identity: &str) -> bool {
+    ["ssh-", "ecdsa-sha2-", "minisign:"].iter().any(|prefix| identity.starts_with(prefix))
+}
+
+/// Builds the verifier for a link's pinned public keys, which must all be SSH keys or all
+/// minisign keys.
+pub fn key_verifier(keys: &[String]) -> Result<Box<dyn Verifier>> {
+    let keys = keys.iter().map(|key| key.parse()).collect::<Result<Vec<PublicKey>>>()?;
+    let ssh: Vec<SshPublicKey> = keys
+        .iter()
+        .filter_map(|key| match key {
+            PublicKey::Ssh(key) => Some(key.clone()),
+            PublicKey::Minisign(_) => None,
+        })
+        .collect();
+    let minisign: Vec<MinisignPublicKey> = keys
+        .iter()
+        .filter_map(|key| match key {
+            PublicKey::Minisign(key) => Some(key.clone()),
+            PublicKey::Ssh(_) => None,
+        })
+        .collect();
+    match (ssh.is_empty(), minisign.is_empty()) {
+        (false, true) => Ok(Box::new(SshVerifier { keys: ssh })),
+        (true, false) => Ok(Box::new(MinisignVerifier { keys: minisign })),
+        (true, true) => bail!("No public keys given"),
+        (false, false) => bail!("A link may pin SSH or minisign keys, not both"),
+    }
+}
+
+/// An `ssh-ed25519` or `ecdsa-sha2-nistp256` public key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshPublicKey {
+    blob: Vec<u8>,
+}
+
+impl FromStr for SshPublicKey {
+    type Err = anyhow::Error;
+
+    fn from_str(line: &str) -> Result<Self> {
+        let mut fields = line.split_whitespace();
+        let (algorithm, encoded) = match (fields.next(), fields.next()) {
+            (Some(algorithm), Some(encoded)) => (algorithm, encoded),
+            (Some(compact), None) => compact.split_once(':').with_context(|| format!("Invalid SSH public key '{}'", line))?,
+            _ => bail!("Invalid SSH public key '{}'", line),
+        };
+        let blob = STANDARD.decode(encoded).context("Failed to decode SSH public key")?;
+        let key = Self { blob };
+        if key.algorithm()? != algorithm {
+            bail!("SSH public key is not of type {}", algorithm);
+        }
+        key.load()?;
+        Ok(key)
+    }
+}
+
+impl SshPublicKey {
+    /// The fingerprint `ssh-keygen -l` shows, e.g. `SHA256:uNiVztksCsDhcc0u9e8BujQXVUpKZIDTMczCvj3tD2s`.
+    pub fn fingerprint(&self) -> String {
+        format!("SHA256:{}", STANDARD_NO_PAD.encode(Sha256::digest(&self.blob)))
+    }
+
+    fn algorithm(&self) -> Result<String> {
+        let mut reader = SshReader(&self.blob);
+        Ok(String::from_utf8_lossy(reader.string()?).into_owned())
+    }
+
+    /// Decodes the key, failing for unsupported types.
+    fn load(&self) -> Result<SshKey> {
+        let mut key = SshReader(&self.blob);
+        match key.string()? {
+            algorithm if algorithm == SSH_ED25519.as_bytes() => {
+                let key = ed25519_dalek::VerifyingKey::try_from(key.string()?).context("Invalid Ed25519 SSH key")?;
+                Ok(SshKey::Ed25519(key))
+            }
+            algorithm if algorithm == SSH_ECDSA_P256.as_bytes() => {
+                if key.string()? != b"nistp256" {
+                    bail!("Invalid ECDSA SSH key");
+                }
+                let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(key.string()?).context("Invalid ECDSA SSH key")?;
+                Ok(SshKey::P256(key))
+            }
+            other => bail!("SSH keys of type {} are not supported", String::from_utf8_lossy(other)),
+        }
+    }
+
+    /// Verifies an SSH signature blob (algorithm name and signature) over `message`.
+    fn verify(&self, signature: &[u8], message: &[u8]) -> Result<()> {
+        let mut signature = SshReader(signature);
+        if signature.string()? != self.algorithm()?.as_bytes() {
+            bail!("SSH signature algorithm does not match the key");
+        }
+        match self.load()? {
+            SshKey::Ed25519(key) => {
+                let signature = ed25519_dalek::Signature::from_slice(signature.string()?)
+                    .context("Invalid Ed25519 SSH signature")?;
+                key.verify(message, &signature).context("Ed25519 SSH signature does not match")
+            }
+            SshKey::P256(key) => {
+                use p256::ecdsa::signature::Verifier as _;
+
+                let mut integers = SshReader(signature.string()?);
+                let (r, s) = (integers.mpint()?, integers.mpint()?);
+                let signature = p256::ecdsa::Signature::from_scalars(r, s).context("Invalid ECDSA SSH signature")?;
+                key.verify(message, &signature).context("ECDSA SSH signature does not match")
+            }
+        }
+    }
+}
+
+enum SshKey {
+    Ed25519(ed25519_dalek::VerifyingKey),
+    P256(p256::ecdsa::VerifyingKey),
+}
+
+/// Verifies `ssh-keygen -Y sign` signatures, published next to the statement at `<uri>.sig`.
+pub struct SshVerifier {
+    keys: Vec<SshPublicKey>,
+}
+
+impl Verifier for SshVerifier {
+    fn signature_uri(&self, uri: &str, _document: &str) -> Option<String> {
+        Some(format!("{}.sig", uri))
+    }
+
+    fn verify(&self, document: &str, signature: Option<&str>) -> Result<VerifiedBundle> {
+        let signature = signature.context("SSH signatures are detached")?;
+        let armored = signature
+            .trim()
+            .strip_prefix(SSH_SIGNATURE_BEGIN)
+            .and_then(|rest| rest.strip_suffix(SSH_SIGNATURE_END))
+            .context("Not an armored SSH signature")?;
+        let blob = STANDARD
+            .decode(armored.split_whitespace().collect::<String>())
+            .context("Failed to decode SSH signature")?;
+
+        let mut reader = SshReader(&blob);
+        if reader.take(6)? != b"SSHSIG" || reader.u32()? != 1 {
+            bail!("Not an SSHSIG version 1 signature");
+        }
+        let public_key = reader.string()?;
+        let namespace = reader.string()?;
+        let reserved = reader.string()?;
+        let hash_algorithm = reader.string()?;
+        let signature = reader.string()?;
+        if namespace != SSH_SIGNATURE_NAMESPACE.as_bytes() {
+            bail!(
+                "SSH signature is in namespace '{}', not '{}'",
+                String::from_utf8_lossy(namespace),
+                SSH_SIGNATURE_NAMESPACE
+            );
+        }
+        let key = self
+            .keys
+            .iter()
+            .find(|key| key.blob == public_key)
+            .with_context(|| format!("SSH signature is not by a pinned key (signed by {})", SshPublicKey { blob: public_key.to_vec() }.fingerprint()))?;
+
+        let digest = match hash_algorithm {
+            b"sha512" => Sha512::digest(document.as_bytes()).to_vec(),
+            b"sha256" => Sha256::digest(document.as_bytes()).to_vec(),
+            other => bail!("SSH signature hash algorithm '{}' is not supported", String::from_utf8_lossy(other)),
+        };
+        let mut signed = b"SSHSIG".to_vec();
+        for field in [namespace, reserved, hash_algorithm, &digest] {
+            signed.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            signed.extend_from_slice(field);
+        }
+        key.verify(signature, &signed).context("SSH signature does not match the statement")?;
+        Ok(key_signed(document.as_bytes(), key.fingerprint(), key.fingerprint()))
+    }
+}
+
+/// A minisign Ed25519 public key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinisignPublicKey {
+    key_id: [u8; 8],
+    key: [u8; 32],
+}
+
+impl FromStr for MinisignPublicKey {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let encoded = text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+            .context("Empty minisign public key")?;
+        let bytes = STANDARD.decode(encoded).context("Failed to decode minisign public key")?;
+        if bytes.len() != 42 || &bytes[..2] != b"Ed" {
+            bail!("Not a minisign Ed25519 public key");
+        }
+        Ok(Self {
+            key_id: bytes[2..10].try_into()?,
+            key: bytes[10..].try_into()?,
+        })
+    }
+}
+
+impl MinisignPublicKey {
+    /// The key ID as `minisign` shows it, in uppercase hex.
+    pub fn key_id(&self) -> String {
+        self.key_id.iter().rev().map(|b| format!("{:02X}", b)).collect()
+    }
+}
+
+/// Verifies minisign signatures, published next to the statement at `<uri>.minisig`. Both
+/// prehashed (the default since minisign 0.10) and legacy signatures are accepted; the trusted
+/// comment's global signature must verify too.
+pub struct MinisignVerifier {
+    keys: Vec<MinisignPublicKey>,
+}
+
+impl Verifier for MinisignVerifier {
+    fn signature_uri(&self, uri: &str, _document: &str) -> Option<String> {
+        Some(format!("{}.minisig", uri))
+    }
+
+    fn verify(&self, document: &str, signature: Option<&str>) -> Result<VerifiedBundle> {
+        let signature = signature.context("minisign signatures are detached")?;
+        let mut lines = signature.lines().map(str::trim).filter(|line| !line.is_empty());
+        let mut next_line = |what: &str| lines.next().with_context(|| format!("minisign signature has no {}", what));
+        next_line("untrusted comment")?;
+        let signature = STANDARD.decode(next_line("signature")?).context("Failed to decode minisign signature")?;
+        let trusted_comment = next_line("trusted comment")?
+            .strip_prefix("trusted comment: ")
+            .context("minisign signature has no trusted comment")?;
+        let global_signature =
+            STANDARD.decode(next_line("global signature")?).context("Failed to decode minisign global signature")?;
+        if signature.len() != 74 {
+            bail!("Invalid minisign signature length");
+        }
+
+        let (algorithm, key_id, signature) = (&signature[..2], &signature[2..10], &signature[10..]);
+        let key = self
+            .keys
+            .iter()
+            .find(|key| key.key_id == key_id)
+            .context("minisign signature is not by a pinned key")?;
+        let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(&key.key).context("Invalid minisign public key")?;
+        let message = match algorithm {
+            b"ED" => Blake2b512::digest(document.as_bytes()).to_vec(),
+            b"Ed" => document.as_bytes().to_vec(),
+            _ => bail!("Unsupported minisign signature algorithm"),
+        };
+        let verify = |signature: &[u8], message: &[u8]| -> Result<()> {
+            let signature = ed25519_dalek::Signature::from_slice(signature).context("Invalid minisign signature")?;
+            verifying_key.verify(message, &signature).map_err(|_| anyhow!("minisign signature does not match"))
+        };
+        verify(signature, &message).context("minisign signature does not match the statement")?;
+        verify(&global_signature, &[signature, trusted_comment.as_bytes()].concat())
+            .context("minisign trusted comment signature does not match")?;
+        Ok(key_signed(document.as_bytes(), format!("minisign:{}", key.key_id()), key.key_id()))
+    }
+}
+
+/// The result of verifying a key-signed statement: no certificate, log entry or trusted time.
+pub(crate) fn key_signed(payload: &[u8], signer_identity: String, key_fingerprint: String) -> VerifiedBundle {
+    VerifiedBundle {
+        payload: payload.to_vec(),
+        trusted_time: None,
+        signer_identity,
+        rekor_entry: false,
+        extensions: FulcioExtensions::default(),
+        key_fingerprints: vec![key_fingerprint],
+    }
+}
+
+/// True if a key pin, e.g. the `key=` value of a `_chainsights` TXT record, names one of the
+/// fingerprints of a verified signing key. Pins are compared as `ssh-keygen -l` shows
+/// fingerprints (`SHA256:...`), or as hex ignoring case, spaces, colons and a `0x` or
+/// `minisign:` prefix.
+pub fn matches_key_pin(pin: &str, fingerprints: &[String]) -> bool {
+    let pin = normalize_key_pin(pin);
+    !pin.is_empty() && fingerprints.iter().any(|fingerprint| normalize_key_pin(fingerprint) == pin)
+}
+
+fn normalize_key_pin(pin: &str) -> String {
+    let pin = pin.trim();
+    if let Some(digest) = pin.strip_prefix("SHA256:") {
+        return format!("SHA256:{}", digest.trim_end_matches('='));
+    }
+    let pin = pin.strip_prefix("minisign:").or_else(|| pin.strip_prefix("0x")).unwrap_or(pin);
+    pin.chars().filter(|c| !c.is_whitespace() && *c != ':').collect::<String>().to_ascii_uppercase()
+}
+
+/// The fingerprints of the keys in a published public key document, such as the one a
+/// `_chainsights` TXT record's `key=` URI points to: PEM public keys or certificates, PGP public
+/// key blocks (with the `pgp` feature), OpenSSH public key lines or a minisign public key.
+pub fn published_key_fingerprints(document: &str) -> Result<Vec<String>> {
+    let mut fingerprints = Vec::new();
+    if document.contains("-----BEGIN PGP PUBLIC KEY BLOCK-----") {
+        #[cfg(feature = "pgp")]
+        fingerprints.extend(crate::pgp::PgpKeyring::from_armored(document)?.fingerprints());
+        #[cfg(not(feature = "pgp"))]
+        bail!("PGP public keys are not supported in this build");
+    } else if document.contains("-----BEGIN ") {
+        for pem in x509_parser::pem::Pem::iter_from_buffer(document.as_bytes()) {
+            let pem = pem.context("Failed to parse PEM public key")?;
+            match pem.label.as_str() {
+                "PUBLIC KEY" => fingerprints.push(public_key_fingerprint(&pem.contents)),
+                "CERTIFICATE" => fingerprints.extend(certificate_fingerprints(&pem.contents)),
+                other => bail!("Unsupported PEM block '{}' in public key document", other),
+            }
+        }
+    } else {
+        for line in document.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') || line.starts_with("untrusted comment:") {
+                continue;
+            }
+            fingerprints.push(match line.parse()? {
+                PublicKey::Ssh(key) => key.fingerprint(),
+                PublicKey::Minisign(key) => key.key_id(),
+            });
+        }
+    }
+    if fingerprints.is_empty() {
+        bail!("No public keys found");
+    }
+    Ok(fingerprints)
+}
+
+/// Reads the SSH wire format: big-endian u32s and length-prefixed strings.
+struct SshReader<'a>(&'a [u8]);
+
+impl<'a> SshReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.0.len() < len {
+            bail!("Truncated SSH data");
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn string(&mut self) -> Result<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    /// A non-negative mpint as a P-256 scalar.
+    fn mpint(&mut self) -> Result<p256::FieldBytes> {
+        let bytes = self.string()?;
+        let bytes = bytes.strip_prefix(&[0]).unwrap_or(bytes);
+        let mut padded = p256::FieldBytes::default();
+        if bytes.len() > padded.len() {
+            bail!("SSH integer too large");
+        }
+        let offset = padded.len() - bytes.len();
+        padded[offset..].copy_from_slice(bytes);
+        Ok(padded)
+    }
+}
@@ -0,0 +1,772 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD, engine::general_purpose::STANDARD_NO_PAD};
+use chrono::{DateTime, Duration, Utc};
+use p256::pkcs8::DecodePublicKey;
+use sha2::{Digest, Sha256};
+use x509_parser::oid_registry::{OID_KEY_TYPE_EC_PUBLIC_KEY, OID_PKCS1_RSAENCRYPTION, OID_SIG_ED25519};
+use x509_parser::certificate::X509Certificate;
+use x509_parser::x509::SubjectPublicKeyInfo;
+use x509_parser::{parse_x509_certificate, prelude::GeneralName};
+
+use crate::bundle::{SigstoreBundleData, TlogEntry, construct_pae};
+use crate::identity::{FulcioExtensions, SignerIdentity, same_identity};
+use crate::timestamp::{Rfc3161Token, TimeSource, parse_rfc3161_token};
+use crate::trust::{TransparencyLogInstance, TrustedRoot};
+
+/// A bundle whose signature and signer identity have been verified.
+pub struct VerifiedBundle {
+    /// The decoded DSSE payload
+    pub payload: Vec<u8>,
+    /// When the bundle was signed, according to the trusted time source (None for the local clock)
+    pub trusted_time: Option<DateTime<Utc>>,
+    /// The certificate identity that matched one of the accepted identities
+    pub signer_identity: String,
+    /// Whether the bundle carries a Rekor transparency log entry (its inclusion is not verified)
+    pub rekor_entry: bool,
+    /// The OIDC issuer and CI build context from the certificate's Fulcio extensions
+    pub extensions: FulcioExtensions,
+    /// Fingerprints of the signing key or certificate, which a publisher can pin in DNS (see
+    /// [`crate::verifier::matches_key_pin`])
+    pub key_fingerprints: Vec<String>,
+}
+
+// TODO: Don't bypass Rekor/Fulcio verification
+/// Verifies the signature from a Sigstore bundle JSON by manually constructing
+/// the PAE and checking it against the certificate's public key. Also checks identity.
+/// NOTE: This bypasses Rekor/Fulcio verification.
+///
+/// With a trusted time source other than the local clock, the signing time is taken from it and
+/// must fall within the certificate's validity, give or take `clock_skew`. Without a trusted root
+/// a bundle's timestamps cannot be verified, so other time sources fail here; use
+/// [`verify_signature_with_trusted_root`] for them.
+pub fn verify_signature_with_pae(
+    bundle_json_text: &str,
+    accepted_identities: &[SignerIdentity],
+    time_source: TimeSource,
+    clock_skew: Duration,
+) -> Result<VerifiedBundle> {
+    verify_bundle(bundle_json_text, accepted_identities, time_source, clock_skew, None)
+}
+
+/// Like [`verify_signature_with_pae`], additionally checking the bundle against the trust material
+/// of a Sigstore instance, public or private: the certificate must be issued by one of its
+/// certificate authorities and, if it names CT logs, carry a signed certificate timestamp from one
+/// of them, and every Rekor entry in the bundle must carry a signed entry timestamp from one of its
+/// logs and record this bundle's payload, signature and certificate. With the `rfc3161` time
+/// source, the bundle's timestamp must be signed by one of its timestamp authorities. Rekor
+/// inclusion proofs are not checked.
+pub fn verify_signature_with_trusted_root(
+    bundle_json_text: &str,
+    accepted_identities: &[SignerIdentity],
+    time_source: TimeSource,
+    clock_skew: Duration,
+    trusted_root: &TrustedRoot,
+) -> Result<VerifiedBundle> {
+    verify_bundle(bundle_json_text, accepted_identities, time_source, clock_skew, Some(trusted_root))
+}
+
+fn verify_bundle(
+    bundle_json_text: &str,
+    accepted_identities: &[SignerIdentity],
+    time_source: TimeSource,
+    clock_skew: Duration,
+    trusted_root: Option<&TrustedRoot>,
+) -> Result<VerifiedBundle> {
+    // 1. Parse the bundle JSON
+    let bundle: SigstoreBundleData =
+        serde_json::from_str(bundle_json_text).context("Failed to parse bundle JSON")?;
+
+    // 2. Extract necessary components
+    let cert_base64 = &bundle.verification_material.certificate.raw_bytes;
+    let payload_base64 = &bundle.dsse_envelope.payload;
+    let payload_type = &bundle.dsse_envelope.payload_type;
+    let sig_base64 = bundle
+        .dsse_envelope
+        .signatures
+        .first()
+        .map(|s| &s.sig)
+        .context("Bundle contains no signatures in dsseEnvelope")?;
+
+    // 3. Decode Payload
+    let payload_bytes = STANDARD
+        .decode(payload_base64)
+        .context("Failed to decode dsseEnvelope.payload")?;
+
+    // 4. Construct PAE (Pre-Authentication Encoding) data
+    // PAE(type, payload) = "DSSEv1" SP len(type) SP type SP len(payload) SP payload
+    let pae_data = construct_pae(payload_type, &payload_bytes);
+
+    // 5. Decode the certificate and signature
+    let cert_der_bytes = STANDARD
+        .decode(cert_base64)
+        .context("Failed to decode certificate rawBytes")?;
+    let sig_bytes = STANDARD
+        .decode(sig_base64)
+        .context("Failed to decode dsseEnvelope signature")?;
+
+    // 6. Verify the signature over the PAE data with the certificate's public key
+    verify_signature(&cert_der_bytes, &sig_bytes, &pae_data)
+        .context("Signature verification failed for PAE data")?;
+    if let Some(trusted_root) = trusted_root {
+        let signed = SignedContent { payload: &payload_bytes, pae: &pae_data, signature: &sig_bytes, cert_der_bytes: &cert_der_bytes };
+        verify_trust_material(&signed, &bundle.verification_material.tlog_entries, trusted_root)
+            .context("Verification against the trusted root failed")?;
+    }
+
+    // 7. Establish the signing time from the configured time source
+    let integrated_time = bundle
+        .verification_material
+        .tlog_entries
+        .first()
+        .map(|entry| parse_integrated_time(&entry.integrated_time))
+        .transpose()?;
+    // Only times whose signatures were checked against the trusted root can be trusted.
+    let trusted_time = match (time_source, trusted_root) {
+        (TimeSource::Local, _) => None,
+        (TimeSource::Rekor | TimeSource::Rfc3161, None) => {
+            bail!("The {:?} time source needs a trusted root to verify the bundle's timestamps against", time_source)
+        }
+        // The entry's body was checked to record this bundle along with its signed entry timestamp.
+        (TimeSource::Rekor, Some(_)) => {
+            Some(integrated_time.context("Bundle has no Rekor tlog entry to take the signing time from")?)
+        }
+        (TimeSource::Rfc3161, Some(trusted_root)) => {
+            let token = bundle
+                .verification_material
+                .timestamp_verification_data
+                .as_ref()
+                .and_then(|data| data.rfc3161_timestamps.first())
+                .context("Bundle has no RFC 3161 timestamp to take the signing time from")?;
+            let token_der = STANDARD
+                .decode(&token.signed_timestamp)
+                .context("Failed to decode RFC 3161 signedTimestamp")?;
+            let token = parse_rfc3161_token(&token_der, &sig_bytes).context("Invalid RFC 3161 timestamp")?;
+            verify_timestamp_authority(&token, trusted_root).context("Invalid RFC 3161 timestamp")?;
+            Some(token.gen_time)
+        }
+    };
+
+    // 8. Verify Identity (Certificate SAN Check) - Reuse function from previous step
+    // Pass the DER bytes directly to avoid re-decoding. Identity windows are judged by verified
+    // time only: the integrated time counts once its signed entry timestamp has been checked
+    // against the trusted root and its body against the bundle, and otherwise the certificate's
+    // notBefore stands in for it. A time standing in for notBefore must fall within the
+    // certificate's validity.
+    let verified_integrated_time = integrated_time.filter(|_| trusted_root.is_some());
+    let signing_time = trusted_time.or(verified_integrated_time);
+    let signer_identity = inspect_certificate_identity_from_der(
+        &cert_der_bytes,
+        accepted_identities,
+        signing_time,
+        signing_time.is_some().then_some(clock_skew),
+    )
+    .context("Certificate identity verification failed")?;
+
+    // 9. Return the original decoded payload bytes
+    Ok(VerifiedBundle {
+        payload: payload_bytes,
+        trusted_time,
+        signer_identity,
+        rekor_entry: integrated_time.is_some(),
+        extensions: parse_x509_certificate(&cert_der_bytes)
+            .map(|(_, cert)| certificate_extensions(&cert))
+            .unwrap_or_default(),
+        key_fingerprints: certificate_fingerprints(&cert_der_bytes),
+    })
+}
+
+/// A bundle decoded without checking its signature, certificate or identity. Nothing in it can be
+/// trusted; it exists to debug malformed bundles.
+pub struct UnverifiedBundle {
+    /// The DSSE payload type
+    pub payload_type: String,
+    /// The decoded DSSE payload
+    pub payload: Vec<u8>,
+    /// Number of signatures in the DSSE envelope
+    pub signatures: usize,
+    /// Email and URI identities named in the certificate's SAN
+    pub identities: Vec<String>,
+    /// The OIDC issuer and CI build context from the certificate's Fulcio extensions
+    pub extensions: FulcioExtensions,
+    /// The certificate's validity window
+    pub validity: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    /// The integrated time of the first Rekor tlog entry, if the bundle has one
+    pub integrated_time: Option<DateTime<Utc>>,
+    /// Why the certificate could not be decoded, if it could not
+    pub certificate_error: Option<String>,
+}
+
+/// Decodes a Sigstore bundle without verifying it. Only the bundle JSON and its payload must be
+/// well-formed; certificate problems are reported in [`UnverifiedBundle::certificate_error`].
+pub fn decode_bundle_unverified(bundle_json_text: &str) -> Result<UnverifiedBundle> {
+    let bundle: SigstoreBundleData =
+        serde_json::from_str(bundle_json_text).context("Failed to parse bundle JSON")?;
+    let payload = STANDARD
+        .decode(&bundle.dsse_envelope.payload)
+        .context("Failed to decode dsseEnvelope.payload")?;
+    let integrated_time = bundle
+        .verification_material
+        .tlog_entries
+        .first()
+        .map(|entry| parse_integrated_time(&entry.integrated_time))
+        .transpose()?;
+
+    let mut decoded = UnverifiedBundle {
+        payload_type: bundle.dsse_envelope.payload_type,
+        payload,
+        signatures: bundle.dsse_envelope.signatures.len(),
+        identities: Vec::new(),
+        extensions: FulcioExtensions::default(),
+        validity: None,
+        integrated_time,
+        certificate_error: None,
+    };
+    let cert_der_bytes = match STANDARD.decode(&bundle.verification_material.certificate.raw_bytes) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            decoded.certificate_error = Some(format!("Failed to decode certificate rawBytes: {}", e));
+            return Ok(decoded);
+        }
+    };
+    let cert = match parse_x509_certificate(&cert_der_bytes) {
+        Ok((_, cert)) => cert,
+        Err(e) => {
+            decoded.certificate_error = Some(format!("Failed to parse X.509 certificate from DER: {}", e));
+            return Ok(decoded);
+        }
+    };
+    decoded.identities = san_identities(&cert);
+    decoded.extensions = certificate_extensions(&cert);
+    decoded.validity = DateTime::from_timestamp(cert.validity().not_before.timestamp(), 0)
+        .zip(DateTime::from_timestamp(cert.validity().not_after.timestamp(), 0));
+    Ok(decoded)
+}
+
+/// The email and URI identities named in a DER certificate's SAN, such as the workflow identity
+/// Fulcio issues a CI job's certificate for.
+pub fn certificate_identities(cert_der_bytes: &[u8]) -> Result<Vec<String>> {
+    let (_, cert) = parse_x509_certificate(cert_der_bytes).map_err(|e| anyhow!("Failed to parse X.509 certificate from DER: {}", e))?;
+    Ok(san_identities(&cert))
+}
+
+fn san_identities(cert: &X509Certificate) -> Vec<String> {
+    let mut identities = Vec::new();
+    if let Ok(Some(san)) = cert.subject_alternative_name() {
+        for name in &san.value.general_names {
+            match name {
+                GeneralName::RFC822Name(identity) | GeneralName::URI(identity) => identities.push(identity.to_string()),
+                _ => {}
+            }
+        }
+    }
+    identities
+}
+
+/// Fulcio certificate extensions, holding DER-encoded UTF8Strings.
+const OID_FULCIO_ISSUER_V2: &str = "1.3.6.1.4.1.57264.1.8";
+const OID_FULCIO_BUILD_SIGNER_URI: &str = "1.3.6.1.4.1.57264.1.9";
+const OID_FULCIO_RUNNER_ENVIRONMENT: &str = "1.3.6.1.4.1.57264.1.11";
+const OID_FULCIO_SOURCE_REPOSITORY_URI: &str = "1.3.6.1.4.1.57264.1.12";
+const OID_FULCIO_SOURCE_REPOSITORY_DIGEST: &str = "1.3.6.1.4.1.57264.1.13";
+const OID_FULCIO_SOURCE_REPOSITORY_REF: &str = "1.3.6.1.4.1.57264.1.14";
+const OID_FULCIO_BUILD_TRIGGER: &str = "1.3.6.1.4.1.57264.1.20";
+/// Deprecated Fulcio extensions holding raw UTF-8 bytes, read when the current one is missing.
+const OID_FULCIO_ISSUER: &str = "1.3.6.1.4.1.57264.1.1";
+const OID_FULCIO_GITHUB_WORKFLOW_TRIGGER: &str = "1.3.6.1.4.1.57264.1.2";
+const OID_FULCIO_GITHUB_WORKFLOW_SHA: &str = "1.3.6.1.4.1.57264.1.3";
+const OID_FULCIO_GITHUB_WORKFLOW_REF: &str = "1.3.6.1.4.1.57264.1.6";
+
+/// Reads the OIDC issuer and CI build context from a certificate's Fulcio extensions.
+/// The fingerprints a certificate can be pinned by: its public key's, as `SHA256:` and base64
+/// like SSH key fingerprints, and the certificate's own SHA-256 in hex.
+pub(crate) fn certificate_fingerprints(cert_der_bytes: &[u8]) -> Vec<String> {
+    let mut fingerprints = Vec::new();
+    if let Ok((_, cert)) = parse_x509_certificate(cert_der_bytes) {
+        fingerprints.push(public_key_fingerprint(cert.public_key().raw));
+    }
+    fingerprints.push(Sha256::digest(cert_der_bytes).iter().map(|b| format!("{:02X}", b)).collect());
+    fingerprints
+}
+
+/// The `SHA256:` fingerprint of a DER-encoded SubjectPublicKeyInfo.
+pub(crate) fn public_key_fingerprint(spki_der: &[u8]) -> String {
+    format!("SHA256:{}", STANDARD_NO_PAD.encode(Sha256::digest(spki_der)))
+}
+
+fn certificate_extensions(cert: &X509Certificate<'_>) -> FulcioExtensions {
+    use x509_parser::der_parser::asn1_rs::FromDer;
+
+    let raw = |oid: &str| {
+        cert.extensions()
+            .iter()
+            .find(|e| e.oid.to_id_string() == oid)
+            .map(|e| e.value)
+    };
+    let utf8_string = |oid: &str| raw(oid).and_then(|value| <&str>::from_der(value).ok()).map(|(_, s)| s.to_string());
+    let legacy = |oid: &str| raw(oid).and_then(|value| String::from_utf8(value.to_vec()).ok());
+    FulcioExtensions {
+        issuer: utf8_string(OID_FULCIO_ISSUER_V2).or_else(|| legacy(OID_FULCIO_ISSUER)),
+        source_repository_uri: utf8_string(OID_FULCIO_SOURCE_REPOSITORY_URI),
+        source_repository_ref: utf8_string(OID_FULCIO_SOURCE_REPOSITORY_REF)
+            .or_else(|| legacy(OID_FULCIO_GITHUB_WORKFLOW_REF)),
+        source_repository_digest: utf8_string(OID_FULCIO_SOURCE_REPOSITORY_DIGEST)
+            .or_else(|| legacy(OID_FULCIO_GITHUB_WORKFLOW_SHA)),
+        build_signer_uri: utf8_string(OID_FULCIO_BUILD_SIGNER_URI),
+        build_trigger: utf8_string(OID_FULCIO_BUILD_TRIGGER)
+            .or_else(|| legacy(OID_FULCIO_GITHUB_WORKFLOW_TRIGGER)),
+        runner_environment: utf8_string(OID_FULCIO_RUNNER_ENVIRONMENT),
+    }
+}
+
+/// Verifies `signature` over `message` with the public key of a DER certificate.
+fn verify_signature(cert_der_bytes: &[u8], signature: &[u8], message: &[u8]) -> Result<()> {
+    let (_, cert) = parse_x509_certificate(cert_der_bytes)
+        .map_err(|e| anyhow!("Failed to parse X.509 certificate from DER: {}", e))?;
+    verify_with_public_key(cert.public_key(), signature, message)
+}
+
+/// Verifies `signature` over `message` with a public key. Supports the keys Sigstore issues
+/// certificates for and signs logs with: ECDSA P-256/SHA-256 and P-384/SHA-384 with ASN.1
+/// signatures, Ed25519, and RSA PKCS#1 v1.5 with SHA-256.
+fn verify_with_public_key(spki: &SubjectPublicKeyInfo<'_>, signature: &[u8], message: &[u8]) -> Result<()> {
+    use p256::ecdsa::signature::Verifier;
+
+    let spki_der = spki.raw;
+    let algorithm = &spki.algorithm.algorithm;
+    if *algorithm == OID_KEY_TYPE_EC_PUBLIC_KEY {
+        match spki.subject_public_key.data.len() {
+            65 => {
+                let key = p256::ecdsa::VerifyingKey::from_public_key_der(spki_der)
+                    .context("Invalid P-256 public key")?;
+                let signature = p256::ecdsa::Signature::from_der(signature).context("Invalid ECDSA signature")?;
+                key.verify(message, &signature).context("ECDSA P-256 signature does not match")
+            }
+            97 => {
+                let key = p384::ecdsa::VerifyingKey::from_public_key_der(spki_der)
+                    .context("Invalid P-384 public key")?;
+                let signature = p384::ecdsa::Signature::from_der(signature).context("Invalid ECDSA signature")?;
+                key.verify(message, &signature).context("ECDSA P-384 signature does not match")
+            }
+            length => bail!("EC public keys of {} bytes are not supported", length),
+        }
+    } else if *algorithm == OID_SIG_ED25519 {
+        let key = ed25519_dalek::VerifyingKey::from_public_key_der(spki_der).context("Invalid Ed25519 public key")?;
+        let signature = ed25519_dalek::Signature::from_slice(signature).context("Invalid Ed25519 signature")?;
+        key.verify(message, &signature).context("Ed25519 signature does not match")
+    } else if *algorithm == OID_PKCS1_RSAENCRYPTION {
+        let key = rsa::RsaPublicKey::from_public_key_der(spki_der).context("Invalid RSA public key")?;
+        let key = rsa::pkcs1v15::VerifyingKey::<Sha256>::new(key);
+        let signature = rsa::pkcs1v15::Signature::try_from(signature).context("Invalid RSA signature")?;
+        key.verify(message, &signature).context("RSA signature does not match")
+    } else {
+        bail!("Public keys with algorithm OID {} are not supported", algorithm)
+    }
+}
+
+/// Verifies `signature` over `message` with a base64 DER public key from a trusted root.
+fn verify_with_trusted_key(key: &str, signature: &[u8], message: &[u8]) -> Result<()> {
+    use x509_parser::prelude::FromDer;
+
+    let key_der = STANDARD.decode(key).context("Failed to decode trusted public key")?;
+    let (_, spki) = SubjectPublicKeyInfo::from_der(&key_der)
+        .map_err(|e| anyhow!("Failed to parse trusted public key: {}", e))?;
+    verify_with_public_key(&spki, signature, message)
+}
+
+/// What a bundle's signature covers and who made it, which its Rekor entries must record.
+struct SignedContent<'a> {
+    /// The decoded DSSE payload
+    payload: &'a [u8],
+    /// The PAE of the payload, which the signature is over
+    pae: &'a [u8],
+    signature: &'a [u8],
+    cert_der_bytes: &'a [u8],
+}
+
+/// Checks a leaf certificate and the bundle's Rekor entries against a trusted root.
+fn verify_trust_material(signed: &SignedContent<'_>, tlog_entries: &[TlogEntry], trusted_root: &TrustedRoot) -> Result<()> {
+    let cert_der_bytes = signed.cert_der_bytes;
+    let (_, cert) = parse_x509_certificate(cert_der_bytes)
+        .map_err(|e| anyhow!("Failed to parse X.509 certificate from DER: {}", e))?;
+    let issued_at = DateTime::from_timestamp(cert.validity().not_before.timestamp(), 0)
+        .context("Invalid certificate notBefore time")?;
+    let issuer_key = certificate_issuer_key(&cert, trusted_root, issued_at)?;
+    if !trusted_root.ctlogs.is_empty() {
+        verify_embedded_scts(&cert, &issuer_key, &trusted_root.ctlogs)?;
+    }
+    for entry in tlog_entries {
+        verify_inclusion_promise(entry, &trusted_root.tlogs)?;
+        verify_entry_body(entry, signed)?;
+    }
+    Ok(())
+}
+
+/// Checks that a Rekor entry records this bundle: the hash of what was signed, the signature, and
+/// the signing certificate or its public key. Without this, a genuine entry of any other bundle
+/// could lend the bundle its integrated time. `intoto`, `dsse` and `hashedrekord` entries are
+/// understood.
+fn verify_entry_body(entry: &TlogEntry, signed: &SignedContent<'_>) -> Result<()> {
+    let body = STANDARD
+        .decode(&entry.canonicalized_body)
+        .context("Failed to decode tlog canonicalizedBody")?;
+    let body: serde_json::Value = serde_json::from_slice(&body).context("Failed to parse tlog canonicalizedBody")?;
+    let spec = &body["spec"];
+    let signatures = |list: &serde_json::Value, signature: &str, key: &str| -> Vec<(String, String)> {
+        let field = |value: &serde_json::Value, name: &str| value[name].as_str().unwrap_or_default().to_string();
+        list.as_array().into_iter().flatten().map(|entry| (field(entry, signature), field(entry, key))).collect()
+    };
+    // DSSE entries hash the payload; hashedrekord entries hash the message signed, here the PAE.
+    let (hash, hashed, signatures) = match body["kind"].as_str().unwrap_or_default() {
+        "intoto" => (&spec["content"]["payloadHash"], signed.payload, signatures(&spec["content"]["envelope"]["signatures"], "sig", "publicKey")),
+        "dsse" => (&spec["payloadHash"], signed.payload, signatures(&spec["signatures"], "signature", "verifier")),
+        "hashedrekord" => (&spec["data"]["hash"], signed.pae, vec![(
+            spec["signature"]["content"].as_str().unwrap_or_default().to_string(),
+            spec["signature"]["publicKey"]["content"].as_str().unwrap_or_default().to_string(),
+        )]),
+        other => bail!("Rekor entry {} is of unsupported kind '{}'", entry.log_index, other),
+    };
+    let digest: String = Sha256::digest(hashed).iter().map(|b| format!("{:02x}", b)).collect();
+    if hash["algorithm"].as_str() != Some("sha256") || !hash["value"].as_str().is_some_and(|value| value.eq_ignore_ascii_case(&digest)) {
+        bail!("Rekor entry {} does not record this bundle's payload (sha256:{})", entry.log_index, digest);
+    }
+    let (_, cert) = parse_x509_certificate(signed.cert_der_bytes)
+        .map_err(|e| anyhow!("Failed to parse X.509 certificate from DER: {}", e))?;
+    let recorded = signatures.iter().any(|(signature, key)| {
+        entry_signature_matches(signature, signed.signature)
+            && match decode_entry_pem(key) {
+                Some((label, der)) if label == "CERTIFICATE" => der == signed.cert_der_bytes,
+                Some((label, der)) if label == "PUBLIC KEY" => der == cert.public_key().raw,
+                _ => false,
+            }
+    });
+    if !recorded {
+        bail!("Rekor entry {} does not record this bundle's signature and certificate", entry.log_index);
+    }
+    Ok(())
+}
+
+/// Whether a base64 signature from a Rekor entry is `signature`. `intoto` entries encode the
+/// envelope's base64 signature a second time.
+fn entry_signature_matches(recorded: &str, signature: &[u8]) -> bool {
+    let Ok(decoded) = STANDARD.decode(recorded) else {
+        return false;
+    };
+    decoded == signature
+        || std::str::from_utf8(&decoded).ok().and_then(|inner| STANDARD.decode(inner).ok()).is_some_and(|inner| inner == signature)
+}
+
+/// Decodes the base64 PEM a Rekor entry records a signer's certificate or public key as, returning
+/// its label and DER contents.
+fn decode_entry_pem(key: &str) -> Option<(String, Vec<u8>)> {
+    let pem = String::from_utf8(STANDARD.decode(key).ok()?).ok()?;
+    let (label, _) = pem.trim_start().strip_prefix("-----BEGIN ")?.split_once("-----")?;
+    let contents: String = pem.lines().map(str::trim).filter(|line| !line.starts_with("-----")).collect();
+    Some((label.to_string(), STANDARD.decode(contents).ok()?))
+}
+
+/// Returns the DER public key of the trusted certificate that issued `cert`, failing if no
+/// certificate authority in use at `issued_at` did.
+fn certificate_issuer_key(cert: &X509Certificate<'_>, trusted_root: &TrustedRoot, issued_at: DateTime<Utc>) -> Result<Vec<u8>> {
+    let authorities = trusted_root
+        .certificate_authorities
+        .iter()
+        .filter(|ca| ca.valid_for.as_ref().is_none_or(|period| period.contains(issued_at)));
+    for certificate in authorities.flat_map(|ca| &ca.cert_chain.certificates) {
+        let der = STANDARD
+            .decode(&certificate.raw_bytes)
+            .context("Failed to decode trusted root certificate")?;
+        let (_, issuer) = parse_x509_certificate(&der)
+            .map_err(|e| anyhow!("Failed to parse trusted root certificate: {}", e))?;
+        if issuer.subject().as_raw() == cert.issuer().as_raw()
+            && verify_with_public_key(issuer.public_key(), &cert.signature_value.data, cert.tbs_certificate.as_ref()).is_ok()
+        {
+            return Ok(issuer.public_key().raw.to_vec());
+        }
+    }
+    bail!("Certificate issued by '{}' does not chain to a certificate authority in the trusted root", cert.issuer())
+}
+
+/// Checks that an RFC 3161 timestamp is signed by a timestamp authority of the trusted root that
+/// was in use at its generation time.
+fn verify_timestamp_authority(token: &Rfc3161Token<'_>, trusted_root: &TrustedRoot) -> Result<()> {
+    let authorities = trusted_root
+        .timestamp_authorities
+        .iter()
+        .filter(|tsa| tsa.valid_for.as_ref().is_none_or(|period| period.contains(token.gen_time)));
+    for certificate in authorities.flat_map(|tsa| &tsa.cert_chain.certificates) {
+        let der = STANDARD
+            .decode(&certificate.raw_bytes)
+            .context("Failed to decode trusted timestamp authority certificate")?;
+        let (_, authority) = parse_x509_certificate(&der)
+            .map_err(|e| anyhow!("Failed to parse trusted timestamp authority certificate: {}", e))?;
+        if verify_with_public_key(authority.public_key(), token.signature, &token.signed_attributes).is_ok() {
+            return Ok(());
+        }
+    }
+    bail!("Timestamp is not signed by a timestamp authority in the trusted root")
+}
+
+/// Checks that a Rekor entry carries a signed entry timestamp from a trusted log.
+fn verify_inclusion_promise(entry: &TlogEntry, tlogs: &[TransparencyLogInstance]) -> Result<()> {
+    let promise = entry
+        .inclusion_promise
+        .as_ref()
+        .with_context(|| format!("Rekor entry {} has no signed entry timestamp", entry.log_index))?;
+    let log_id = STANDARD.decode(&entry.log_id.key_id).context("Failed to decode tlog logId")?;
+    let integrated_time = parse_integrated_time(&entry.integrated_time)?;
+    let log_index: u64 = entry
+        .log_index
+        .parse()
+        .with_context(|| format!("Invalid tlog logIndex '{}'", entry.log_index))?;
+    let log = trusted_log(tlogs, &log_id, integrated_time)
+        .with_context(|| format!("Rekor entry {} is from a log that is not in the trusted root", log_index))?;
+
+    // The signed entry timestamp covers the canonical JSON of these fields, keys sorted.
+    let signed = format!(
+        r#"{{"body":{},"integratedTime":{},"logID":"{}","logIndex":{}}}"#,
+        serde_json::to_string(&entry.canonicalized_body)?,
+        integrated_time.timestamp(),
+        log_id.iter().map(|b| format!("{:02x}", b)).collect::<String>(),
+        log_index
+    );
+    let signature = STANDARD
+        .decode(&promise.signed_entry_timestamp)
+        .context("Failed to decode signedEntryTimestamp")?;
+    verify_with_trusted_key(&log.public_key.raw_bytes, &signature, signed.as_bytes())
+        .with_context(|| format!("Invalid signed entry timestamp for Rekor entry {}", log_index))
+}
+
+/// The trusted log with this ID whose key was in use at `time`.
+fn trusted_log<'a>(
+    logs: &'a [TransparencyLogInstance],
+    log_id: &[u8],
+    time: DateTime<Utc>,
+) -> Option<&'a TransparencyLogInstance> {
+    logs.iter().find(|log| {
+        STANDARD.decode(&log.log_id.key_id).is_ok_and(|id| id == log_id)
+            && log.public_key.valid_for.as_ref().is_none_or(|period| period.contains(time))
+    })
+}
+
+/// Certificate extension holding the signed certificate timestamps of the CT logs that logged the
+/// certificate, as a TLS-encoded list inside an OCTET STRING.
+const OID_CT_SCT_LIST: &str = "1.3.6.1.4.1.11129.2.4.2";
+const OID_CT_SCT_LIST_DER: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0xd6, 0x79, 0x02, 0x04, 0x02];
+
+/// Checks that a certificate embeds at least one valid signed certificate timestamp (RFC 6962)
+/// from a trusted CT log.
+fn verify_embedded_scts(cert: &X509Certificate<'_>, issuer_key: &[u8], ctlogs: &[TransparencyLogInstance]) -> Result<()> {
+    let extension = cert
+        .extensions()
+        .iter()
+        .find(|e| e.oid.to_id_string() == OID_CT_SCT_LIST)
+        .context("Certificate has no embedded signed certificate timestamps")?;
+    let Some((0x04, mut list, _)) = der_element(extension.value) else {
+        bail!("Malformed signed certificate timestamp extension");
+    };
+    let mut scts = take_vector(&mut list, 2)?;
+    let precertificate = precertificate_tbs(cert.tbs_certificate.as_ref())?;
+    let issuer_key_hash = Sha256::digest(issuer_key);
+
+    let mut errors = Vec::new();
+    while !scts.is_empty() {
+        let mut sct = take_vector(&mut scts, 2)?;
+        let version = take(&mut sct, 1)?;
+        let log_id = take(&mut sct, 32)?;
+        let timestamp = take(&mut sct, 8)?;
+        let extensions = take_vector(&mut sct, 2)?;
+        let _algorithms = take(&mut sct, 2)?;
+        let signature = take_vector(&mut sct, 2)?;
+        if version != [0] {
+            errors.push(format!("unsupported SCT version {}", version[0]));
+            continue;
+        }
+        let millis = u64::from_be_bytes(timestamp.try_into()?);
+        let Some(logged_at) = DateTime::from_timestamp_millis(millis as i64) else {
+            errors.push(format!("invalid SCT timestamp {}", millis));
+            continue;
+        };
+        let Some(log) = trusted_log(ctlogs, log_id, logged_at) else {
+            errors.push(format!("SCT from untrusted log {}", STANDARD.encode(log_id)));
+            continue;
+        };
+
+        // RFC 6962 section 3.2: the digitally-signed struct of a precertificate entry
+        let mut signed = vec![0, 0];
+        signed.extend_from_slice(timestamp);
+        signed.extend_from_slice(&[0, 1]);
+        signed.extend_from_slice(&issuer_key_hash);
+        signed.extend_from_slice(&(precertificate.len() as u32).to_be_bytes()[1..]);
+        signed.extend_from_slice(&precertificate);
+        signed.extend_from_slice(&(extensions.len() as u16).to_be_bytes());
+        signed.extend_from_slice(extensions);
+        match verify_with_trusted_key(&log.public_key.raw_bytes, signature, &signed) {
+            Ok(()) => return Ok(()),
+            Err(e) => errors.push(format!("{:#}", e)),
+        }
+    }
+    bail!(
+        "Certificate has no valid signed certificate timestamp from a CT log in the trusted root ({})",
+        errors.join("; ")
+    )
+}
+
+/// The TBSCertificate a CT log signed for a certificate: the certificate's own, without the
+/// extension holding the SCTs.
+fn precertificate_tbs(tbs: &[u8]) -> Result<Vec<u8>> {
+    let Some((0x30, mut fields, _)) = der_element(tbs) else {
+        bail!("Malformed TBSCertificate");
+    };
+    let mut rebuilt = Vec::new();
+    while !fields.is_empty() {
+        let (tag, content, rest) = der_element(fields).context("Malformed TBSCertificate field")?;
+        let field = &fields[..fields.len() - rest.len()];
+        fields = rest;
+        // Extensions are the explicitly tagged [3] field
+        if tag != 0xa3 {
+            rebuilt.extend_from_slice(field);
+            continue;
+        }
+        let Some((0x30, mut extensions, _)) = der_element(content) else {
+            bail!("Malformed certificate extensions");
+        };
+        let mut kept = Vec::new();
+        while !extensions.is_empty() {
+            let (_, extension, rest) = der_element(extensions).context("Malformed certificate extension")?;
+            let is_sct_list = der_element(extension).is_some_and(|(tag, oid, _)| tag == 0x06 && oid == OID_CT_SCT_LIST_DER);
+            if !is_sct_list {
+                kept.extend_from_slice(&extensions[..extensions.len() - rest.len()]);
+            }
+            extensions = rest;
+        }
+        rebuilt.extend(der_encode(0xa3, &der_encode(0x30, &kept)));
+    }
+    Ok(der_encode(0x30, &rebuilt))
+}
+
+/// Splits the first DER element off `input` as its tag, its content and the bytes after it. Only
+/// single-byte tags are supported, which is all X.509 uses.
+fn der_element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = input.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (len, rest) = match first {
+        0..=0x7f => (first as usize, rest),
+        0x81..=0x84 => {
+            let count = (first & 0x7f) as usize;
+            let bytes = rest.get(..count)?;
+            (bytes.iter().fold(0, |len, &b| (len << 8) | b as usize), &rest[count..])
+        }
+        _ => return None,
+    };
+    let content = rest.get(..len)?;
+    Some((tag, content, &rest[len..]))
+}
+
+/// DER-encodes an element with a single-byte tag.
+fn der_encode(tag: u8, content: &[u8]) -> Vec<u8> {
+    let len = content.len();
+    let mut der = vec![tag];
+    match len {
+        0..=0x7f => der.push(len as u8),
+        0x80..=0xff => der.extend([0x81, len as u8]),
+        0x100..=0xffff => der.extend([0x82, (len >> 8) as u8, len as u8]),
+        _ => der.extend([0x83, (len >> 16) as u8, (len >> 8) as u8, len as u8]),
+    }
+    der.extend_from_slice(content);
+    der
+}
+
+/// Takes `len` bytes off the front of a TLS-encoded structure.
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if input.len() < len {
+        bail!("Truncated signed certificate timestamp");
+    }
+    let (head, rest) = input.split_at(len);
+    *input = rest;
+    Ok(head)
+}
+
+/// Takes a TLS variable-length vector with a `length_bytes`-byte length prefix.
+fn take_vector<'a>(input: &mut &'a [u8], length_bytes: usize) -> Result<&'a [u8]> {
+    let len = take(input, length_bytes)?.iter().fold(0, |len, &b| (len << 8) | b as usize);
+    take(input, len)
+}
+
+/// Parses a tlog entry's integrated time, given in seconds since the epoch.
+fn parse_integrated_time(time: &str) -> Result<DateTime<Utc>> {
+    time.parse::<i64>()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .with_context(|| format!("Invalid tlog integratedTime '{}'", time))
+}
+
+/// Helper function to inspect certificate identity directly from DER bytes.
+/// (Adapted from previous `inspect_certificate_identity` function)
+///
+/// Identities with a validity window are checked against the signing time: the trusted or Rekor
+/// integrated time when there is one, otherwise the start of the certificate's validity. When
+/// `validity_skew` is set, the signing time must also fall within the certificate's validity.
+fn inspect_certificate_identity_from_der(
+    cert_der_bytes: &[u8],
+    accepted_identities: &[SignerIdentity],
+    signing_time: Option<DateTime<Utc>>,
+    validity_skew: Option<Duration>,
+) -> Result<String> {
+    match parse_x509_certificate(cert_der_bytes) {
+        Ok((_, cert)) => {
+            let not_before = DateTime::from_timestamp(cert.validity().not_before.timestamp(), 0)
+                .context("Invalid certificate notBefore time")?;
+            let not_after = DateTime::from_timestamp(cert.validity().not_after.timestamp(), 0)
+                .context("Invalid certificate notAfter time")?;
+            let signing_time = signing_time.unwrap_or(not_before);
+            if let Some(skew) = validity_skew
+                && (signing_time < not_before - skew || signing_time > not_after + skew)
+            {
+                bail!(
+                    "Certificate valid from {} to {} was not valid at signing time {}",
+                    not_before.to_rfc3339(),
+                    not_after.to_rfc3339(),
+                    signing_time.to_rfc3339()
+                )
+            }
+
+            let mut outside_window = Vec::new();
+            // Certificates without a parsable SAN extension match no identity
+            if let Ok(Some(san)) = cert.subject_alternative_name() {
+                for name in &san.value.general_names {
+                    // Emails for people and service accounts, URIs for CI workflows
+                    let (GeneralName::RFC822Name(identity) | GeneralName::URI(identity)) = name else {
+                        continue;
+                    };
+                    for accepted in accepted_identities.iter().filter(|accepted| same_identity(identity, &accepted.identity)) {
+                        if accepted.accepts_signing_time(signing_time)? {
+                            return Ok(identity.to_string());
+                        }
+                        outside_window.push(accepted.to_string());
+                    }
+                }
+            }
+
+            if !outside_window.is_empty() {
+                bail!(
+                    "Certificate identity '{}' was not accepted for statements signed at {}",
+                    outside_window.join("', '"),
+                    signing_time.to_rfc3339()
+                )
+            }
+            bail!(
+                "Expected identity '{}' not found in certificate SAN",
+                accepted_identities
+                    .iter()
+                    .map(|accepted| accepted.identity.as_str())
+                    .collect::<Vec<_>>()
+                    .join("', '")
+            )
+        }
+        Err(e) => {
+            bail!("Failed to parse X.509 certificate from DER: {}", e)
+        }
+    }
+}
@@ -0,0 +1,1003 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::io::Write;
+use std::sync::Arc;
+
+use chainsights_core::timestamp::TimeSource;
+use serde::{Deserialize, Serialize};
+
+use crate::predicate::{
+    ArtifactLink, ChainsightsCatalogPredicate, ChainsightsComponentPredicate, ChainsightsEndorsementPredicate,
+    ChainsightsReleasePredicate, ChainsightsRevocationPredicate, CustomPredicate, Organization, RelationshipType, RevokedAttestation,
+    strip_purl_version,
+};
+
+/// AggregatedCatalogData is the top-level structure for the aggregated Chainsights data output.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct AggregatedCatalogData {
+    /// The root predicate parsed from the Chainsights catalog
+    pub catalog_predicate: Option<Arc<ChainsightsCatalogPredicate>>,
+    /// How the root catalog statement was verified; absent for nodes that were not verified (e.g. from TEA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<NodeVerification>,
+    /// The list of components aggregated from the catalog
+    pub components: Vec<AggregatedComponentData>,
+    /// Any errors encountered while processing the root URI
+    pub root_error: Option<String>,
+    /// Any errors encountered while processing component links
+    pub component_errors: Vec<(String, String)>,
+    /// Validly signed component statements that were rejected because they expired, with their expiry time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expired_components: Vec<(String, String)>,
+    /// Validly signed component statements that were rejected because they contradict their catalog entry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub integrity_errors: Vec<IntegrityError>,
+    /// The revocation list linked from the catalog, if it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_predicate: Option<Arc<ChainsightsRevocationPredicate>>,
+    /// Any error encountered while fetching the revocation list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_error: Option<String>,
+    /// Relationships declared between the aggregated components
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relationships: Vec<RelationshipEdge>,
+    /// The verified freshness statement of the root catalog, if it links one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freshness: Option<CatalogFreshness>,
+    /// Any error encountered while fetching or checking the freshness statement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freshness_error: Option<String>,
+    /// Verified third-party endorsements of the root catalog
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endorsements: Vec<Endorsement>,
+    /// Endorsement links that could not be verified, with the reason
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endorsement_errors: Vec<(String, String)>,
+    /// Set when this aggregate was taken from a trusted verifier instead of traversed locally
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relayed_from: Option<RelayedVerification>,
+    /// How the root catalog and its expected signer were located
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discovery: Option<RootDiscovery>,
+    /// The signed statement the root catalog was taken from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// What the traversal cost, when statistics were requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<CrawlStats>,
+    /// Subdomain namespaces the domain delegates to their own roots, when delegations were
+    /// followed. The components of each delegated root are included in `components`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub delegations: Vec<NamespaceDelegation>,
+    /// Domains whose `_chainsights` record advertises a different root identity or URI than the
+    /// one pinned for them, when root pinning is enabled
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub root_changes: Vec<RootChange>,
+}
+
+/// NamespaceDelegation records a subdomain whose namespace a domain delegates to the
+/// subdomain's own `_chainsights` record and root catalog.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct NamespaceDelegation {
+    /// The delegated subdomain
+    pub namespace: String,
+    /// The TXT record name the delegation was read from
+    pub source: String,
+    /// The delegated root catalog, when its record could be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root_uri: Option<String>,
+    /// The identity the delegated root is signed by, when its record could be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+    /// Why the delegated root was not traversed, e.g. its identity is outside the namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// CrossDomainLink labels a node linked from the publisher's catalog but published on another
+/// organization's domain (e.g., a reseller linking the vendor's statements), which was accepted
+/// because that domain's own `_chainsights` record names the identity the link expects.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct CrossDomainLink {
+    /// The foreign domain the node is published on
+    pub domain: String,
+    /// The identity the foreign domain's record names
+    pub identity: String,
+    /// The TXT record name the identity was corroborated by
+    pub corroborated_by: String,
+}
+
+/// RootChange records a domain whose `_chainsights` record no longer advertises the root identity
+/// or URI pinned for it on first use, which a compromised key or hijacked zone would cause as much
+/// as a legitimate rotation.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RootChange {
+    /// The domain whose record changed
+    pub domain: String,
+    /// The identity pinned for the domain
+    pub pinned_identity: String,
+    /// The root catalog URI pinned for the domain
+    pub pinned_uri: String,
+    /// When the pin was recorded
+    pub pinned_at: String,
+    /// The identity the record now advertises
+    pub identity: String,
+    /// The root catalog URI the record now advertises
+    pub uri: String,
+}
+
+impl fmt::Display for RootChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ROOT CHANGED: the _chainsights record of '{}' now advertises", self.domain)?;
+        if self.identity != self.pinned_identity {
+            write!(f, " identity '{}' instead of '{}'", self.identity, self.pinned_identity)?;
+        }
+        if self.identity != self.pinned_identity && self.uri != self.pinned_uri {
+            write!(f, " and")?;
+        }
+        if self.uri != self.pinned_uri {
+            write!(f, " root '{}' instead of '{}'", self.uri, self.pinned_uri)?;
+        }
+        write!(f, ", pinned since {}; the domain's key or DNS zone may be compromised", self.pinned_at)
+    }
+}
+
+/// CrawlStats summarizes the cost of a traversal: its requests to each host and the time spent
+/// verifying statements.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct CrawlStats {
+    /// Milliseconds from the start of the run to the end of the traversal
+    pub elapsed_ms: u64,
+    /// Requests per host, busiest first
+    pub hosts: Vec<HostStats>,
+    /// Time spent verifying statement signatures
+    pub verification: VerificationStats,
+}
+
+/// HostStats counts the requests made to one host.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct HostStats {
+    /// Host name, or the URI scheme for URIs without one
+    pub host: String,
+    /// Requests sent, including failed ones
+    pub requests: u64,
+    /// Requests that failed; failed requests are not retried
+    pub failed_requests: u64,
+    /// Bytes received
+    pub bytes: u64,
+    /// Documents answered from a cache instead of requested again
+    pub cache_hits: u64,
+    /// Mean time from sending a request to receiving the whole response
+    pub average_latency_ms: f64,
+}
+
+/// VerificationStats times the verification of statement signatures.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct VerificationStats {
+    /// Statements whose signatures were checked, successfully or not
+    pub statements: u64,
+    /// Total time spent verifying
+    pub total_ms: f64,
+    /// Mean time per statement
+    pub average_ms: f64,
+    /// Longest time for one statement
+    pub max_ms: f64,
+}
+
+/// RelayedVerification records whose verification results an aggregate was accepted from.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RelayedVerification {
+    /// The URI the verification statement was fetched from
+    pub uri: String,
+    /// The identity that signed the verification statement
+    pub signer_identity: String,
+    /// The verifier's ID
+    pub verifier_id: String,
+    /// When the verifier traversed the domain
+    pub time_verified: String,
+    /// URI of the policy the verifier applied
+    pub policy_uri: String,
+}
+
+/// RelationshipEdge is one relationship declared by a component.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RelationshipEdge {
+    /// Component PURL of the component declaring the relationship
+    pub source: String,
+    /// Component PURL of the related component
+    pub target: String,
+    /// The kind of relationship
+    pub relationship_type: RelationshipType,
+    /// Whether the related component was found and aggregated
+    pub resolved: bool,
+}
+
+/// IntegrityError is a verified statement that contradicts the statement linking to it, and was
+/// left out of the aggregate.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct IntegrityError {
+    /// URI of the contradicting statement
+    pub uri: String,
+    /// Which link is inconsistent
+    pub kind: IntegrityErrorKind,
+    /// The PURL the linking statement names
+    pub expected_purl: String,
+    /// The PURL the linked statement declares
+    pub found_purl: String,
+}
+
+/// IntegrityErrorKind names the cross-link an [`IntegrityError`] breaks.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum IntegrityErrorKind {
+    /// A catalog entry's `componentPurl` is not the `purl` of the component statement it links to
+    ComponentPurl,
+    /// A release statement's `purl` is not a version of its component's `purl`
+    ReleasePurl,
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            IntegrityErrorKind::ComponentPurl => write!(
+                f,
+                "Component statement at {} is for '{}', but its catalog entry names '{}'",
+                self.uri, self.found_purl, self.expected_purl
+            ),
+            IntegrityErrorKind::ReleasePurl => write!(
+                f,
+                "Release statement at {} is for '{}', which is not a version of its component '{}'",
+                self.uri, self.found_purl, self.expected_purl
+            ),
+        }
+    }
+}
+
+impl AggregatedCatalogData {
+    /// Collects the relationships declared by every aggregated component.
+    pub fn relationship_edges(&self) -> Vec<RelationshipEdge> {
+        let predicates: Vec<_> = self
+            .components
+            .iter()
+            .filter_map(|c| c.component_predicate.as_ref())
+            .collect();
+        predicates
+            .iter()
+            .flat_map(|component| {
+                component.relationships.iter().flatten().map(|relationship| {
+                    let target = strip_purl_version(&relationship.target_purl);
+                    RelationshipEdge {
+                        source: component.purl.clone(),
+                        target: relationship.target_purl.clone(),
+                        relationship_type: relationship.relationship_type,
+                        resolved: predicates.iter().any(|c| strip_purl_version(&c.purl) == target),
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Counts the root catalog, components and releases accepted with checks skipped by an insecure
+    /// development mode.
+    pub fn unverified_count(&self) -> usize {
+        let unverified = |verification: &Option<NodeVerification>| {
+            usize::from(verification.as_ref().is_some_and(|v| !v.is_verified()))
+        };
+        unverified(&self.verification)
+            + self
+                .components
+                .iter()
+                .map(|c| unverified(&c.verification) + c.releases.iter().map(|r| unverified(&r.verification)).sum::<usize>())
+                .sum::<usize>()
+    }
+
+    /// Counts the root catalog, components and releases that carry no signature verification at
+    /// all, such as those built from an unsigned discovery protocol's answers.
+    pub fn unsigned_count(&self) -> usize {
+        if self.root_error.is_some() {
+            return 0;
+        }
+        let unsigned = |verification: &Option<NodeVerification>| usize::from(verification.is_none());
+        unsigned(&self.verification)
+            + self
+                .components
+                .iter()
+                .map(|c| unsigned(&c.verification) + c.releases.iter().map(|r| unsigned(&r.verification)).sum::<usize>())
+                .sum::<usize>()
+    }
+
+    /// Counts the statements that were verified and those that failed, had expired or contradicted
+    /// their links: the root catalog, its revocation list, components and releases. Statements
+    /// accepted unsigned or by an insecure mode are neither. A statement that failed in more than
+    /// one way is counted once. Endorsements are not counted.
+    pub fn outcome_counts(&self) -> (usize, usize) {
+        if self.root_error.is_some() {
+            return (0, 1);
+        }
+        let verified = |verification: &Option<NodeVerification>| {
+            usize::from(verification.as_ref().is_some_and(NodeVerification::is_verified))
+        };
+        let mut verified_count = verified(&self.verification) + usize::from(self.revocation_predicate.is_some());
+        let mut failed: HashSet<&str> = self
+            .component_errors
+            .iter()
+            .chain(&self.expired_components)
+            .map(|(uri, _)| uri.as_str())
+            .chain(self.integrity_errors.iter().map(|e| e.uri.as_str()))
+            .collect();
+        for component in &self.components {
+            verified_count += verified(&component.verification)
+                + component.releases.iter().map(|r| verified(&r.verification)).sum::<usize>();
+            failed.extend(component.release_errors.iter().chain(&component.expired_releases).map(|(uri, _)| uri.as_str()));
+            failed.extend(component.integrity_errors.iter().map(|e| e.uri.as_str()));
+        }
+        (verified_count, failed.len() + usize::from(self.revocation_error.is_some()))
+    }
+
+    /// Keeps only releases whose license expression mentions one of `licenses` (SPDX identifiers,
+    /// compared case-insensitively), and the components that still have releases. Components
+    /// without any releases are kept when their own license matches.
+    pub fn retain_licenses(&mut self, licenses: &[String]) {
+        let mentions_any = |expression: Option<&str>| {
+            expression.is_some_and(|e| licenses.iter().any(|l| license_expression_mentions(e, l)))
+        };
+        self.components.retain_mut(|component| {
+            let component_license = component.component_predicate.as_ref().and_then(|c| c.license.as_deref());
+            if component.releases.is_empty() {
+                return mentions_any(component_license);
+            }
+            component.releases.retain(|r| mentions_any(r.license.as_deref()));
+            !component.releases.is_empty()
+        });
+    }
+
+    /// Writes the aggregate as newline-delimited JSON: a `"record": "catalog"` object holding
+    /// everything but the components, then one `"record": "component"` object per component. Each
+    /// line is written as soon as it is serialized, so no single string holds the whole aggregate.
+    pub fn write_ndjson<W: Write>(&self, mut writer: W) -> serde_json::Result<()> {
+        // Destructured without `..`, so that a field added to the aggregate cannot be left out of
+        // the header.
+        let AggregatedCatalogData {
+            catalog_predicate,
+            verification,
+            components,
+            root_error,
+            component_errors,
+            expired_components,
+            integrity_errors,
+            revocation_predicate,
+            revocation_error,
+            relationships,
+            freshness,
+            freshness_error,
+            endorsements,
+            endorsement_errors,
+            relayed_from,
+            discovery,
+            provenance,
+            stats,
+            delegations,
+            root_changes,
+        } = self;
+        let header = NdjsonRecord::Catalog(CatalogRecord {
+            catalog_predicate,
+            verification,
+            root_error,
+            component_errors,
+            expired_components,
+            integrity_errors,
+            revocation_predicate,
+            revocation_error,
+            relationships,
+            freshness,
+            freshness_error,
+            endorsements,
+            endorsement_errors,
+            relayed_from,
+            discovery,
+            provenance,
+            stats,
+            delegations,
+            root_changes,
+        });
+        let records = std::iter::once(header).chain(components.iter().map(NdjsonRecord::Component));
+        for record in records {
+            serde_json::to_writer(&mut writer, &record)?;
+            writer.write_all(b"\n").map_err(serde_json::Error::io)?;
+        }
+        writer.flush().map_err(serde_json::Error::io)
+    }
+}
+
+/// One line of [`AggregatedCatalogData::write_ndjson`] output.
+#[derive(Serialize)]
+#[serde(tag = "record", rename_all = "lowercase")]
+enum NdjsonRecord<'a> {
+    Catalog(CatalogRecord<'a>),
+    Component(&'a AggregatedComponentData),
+}
+
+/// The fields of [`AggregatedCatalogData`] other than `components`, serialized the same way.
+#[derive(Serialize)]
+struct CatalogRecord<'a> {
+    catalog_predicate: &'a Option<Arc<ChainsightsCatalogPredicate>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verification: &'a Option<NodeVerification>,
+    root_error: &'a Option<String>,
+    component_errors: &'a Vec<(String, String)>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    expired_components: &'a Vec<(String, String)>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    integrity_errors: &'a Vec<IntegrityError>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    revocation_predicate: &'a Option<Arc<ChainsightsRevocationPredicate>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    revocation_error: &'a Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    relationships: &'a Vec<RelationshipEdge>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    freshness: &'a Option<CatalogFreshness>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    freshness_error: &'a Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    endorsements: &'a Vec<Endorsement>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    endorsement_errors: &'a Vec<(String, String)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    relayed_from: &'a Option<RelayedVerification>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    discovery: &'a Option<RootDiscovery>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provenance: &'a Option<Provenance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: &'a Option<CrawlStats>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    delegations: &'a Vec<NamespaceDelegation>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    root_changes: &'a Vec<RootChange>,
+}
+
+/// Returns true if an SPDX license expression names `license`, ignoring operators and parentheses.
+fn license_expression_mentions(expression: &str, license: &str) -> bool {
+    expression
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .filter(|token| !matches!(*token, "" | "AND" | "OR" | "WITH"))
+        .any(|token| token.trim_end_matches('+').eq_ignore_ascii_case(license))
+}
+
+/// AggregatedComponentData contains the data for a single component, including its releases and any errors encountered.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct AggregatedComponentData {
+    /// The component predicate parsed from following the link in a Chainsights catalog
+    pub component_predicate: Option<Arc<ChainsightsComponentPredicate>>,
+    /// The list of releases aggregated from the component
+    pub releases: Vec<AggregatedReleaseData>,
+    /// The URI from which this component manifest was fetched
+    pub component_link_uri: String,
+    /// The signed statements the component was taken from; absent for nodes that were not verified (e.g. from TEA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// How the component statement was verified; absent for nodes that were not verified (e.g. from TEA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<NodeVerification>,
+    /// Any errors encountered while processing the release links
+    pub release_errors: Vec<(String, String)>,
+    /// Validly signed release statements that were rejected because they expired, with their expiry time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expired_releases: Vec<(String, String)>,
+    /// Validly signed release statements that were rejected because they contradict the component
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub integrity_errors: Vec<IntegrityError>,
+    /// Set when the publisher has revoked the component attestation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation: Option<RevokedAttestation>,
+    /// Verified third-party endorsements of the component statement
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endorsements: Vec<Endorsement>,
+    /// Endorsement links that could not be verified, with the reason
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endorsement_errors: Vec<(String, String)>,
+    /// Linked statements of predicate types a plugin parses
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_statements: Vec<CustomStatement>,
+    /// Linked in-toto statements that could not be verified or parsed while looking for them, with the reason
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_statement_errors: Vec<(String, String)>,
+    /// Set when the component is published on another organization's domain that corroborated
+    /// the link's identity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cross_domain: Option<CrossDomainLink>,
+    /// Set, to the URI of the component statement declaring the relationship, when the component
+    /// is not listed in the catalog and was only reached through another component's relationship
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub via_relationship: Option<String>,
+    /// The most recent verified OpenSSF Scorecard result linked from the component
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scorecard: Option<ScorecardResult>,
+    /// Linked attestations that could not be verified while looking for Scorecard results, with the reason
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scorecard_errors: Vec<(String, String)>,
+}
+
+/// AggregatedReleaseData contains the data for a single release, including its artifacts and any errors encountered.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct AggregatedReleaseData {
+    /// The release predicate parsed from following the link in the Chainsights component manifest
+    pub release_predicate: Option<Arc<ChainsightsReleasePredicate>>,
+    /// The list of metadata artifacts linked from the release predicate (e.g. SBOM, SLSA attestation)
+    pub metadata_artifacts: Vec<ArtifactLink>,
+    /// The URI from which this release manifest was fetched
+    pub release_link_uri: String,
+    /// The signed statements the release and its derived fields were taken from; absent for nodes that were not verified (e.g. from TEA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// How the release statement was verified; absent for nodes that were not verified (e.g. from TEA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<NodeVerification>,
+    /// Any errors encountered while processing the artifact links
+    pub artifact_fetch_errors: Vec<(String, String)>, // (URI, Error Message) for artifact fetching
+    /// Set when the publisher has revoked the release attestation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation: Option<RevokedAttestation>,
+    /// Set when the release is published on another organization's domain that corroborated the
+    /// link's identity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cross_domain: Option<CrossDomainLink>,
+    /// CPE name of the release, falling back to the component's CPE bound to the release version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpe: Option<String>,
+    /// SPDX license expression of the release, falling back to the component's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// Supplier of the release, falling back to the component's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supplier: Option<Organization>,
+    /// Ecosystem package data from deps.dev, when enrichment is enabled
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deps_dev: Vec<DepsDevEnrichment>,
+    /// Curated license data from ClearlyDefined, when enrichment is enabled
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clearly_defined: Vec<ClearlyDefinedEnrichment>,
+    /// Any errors encountered while enriching the release, keyed by the PURL or URI involved
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub enrichment_errors: Vec<(String, String)>,
+    /// Verified third-party endorsements of the release statement
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endorsements: Vec<Endorsement>,
+    /// Endorsement links that could not be verified, with the reason
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endorsement_errors: Vec<(String, String)>,
+    /// Linked statements of predicate types a plugin parses
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_statements: Vec<CustomStatement>,
+    /// Linked in-toto statements that could not be verified or parsed while looking for them, with the reason
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_statement_errors: Vec<(String, String)>,
+    /// Whether the release's linked build attestations satisfy the in-toto layout checked against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout_compliance: Option<LayoutCompliance>,
+    /// CSAF security advisories linked from the catalog, component or release that name the release
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub csaf_advisories: Vec<CsafAdvisory>,
+    /// SWID and CoSWID tags linked from the release
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub swid_tags: Vec<SwidTag>,
+}
+
+/// NodeVerification records how a node of the aggregate was verified, so consumers can apply
+/// their own trust thresholds.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct NodeVerification {
+    /// How the node's authenticity was established
+    pub method: VerificationMethod,
+    /// The signer identity that matched one of the accepted identities, for signed nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+    /// Whether the bundle carries a Rekor transparency log entry
+    #[serde(default)]
+    pub rekor_entry: bool,
+    /// Hex sha256 of the signed payload, by which links pin the statement and Rekor indexes it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_sha256: Option<String>,
+    /// When the statement was signed, according to `time_source`; absent for the local clock
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed_at: Option<String>,
+    /// Where signing times were taken from when checking validity, freshness and expiry
+    #[serde(default)]
+    pub time_source: TimeSource,
+    /// Checks skipped by an insecure development mode. A node with skipped checks is UNVERIFIED,
+    /// whatever its method says.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_checks: Vec<SkippedCheck>,
+}
+
+impl NodeVerification {
+    /// Whether every check was made, i.e. the node was not accepted by an insecure mode.
+    pub fn is_verified(&self) -> bool {
+        self.skipped_checks.is_empty()
+    }
+}
+
+/// ClaimSource identifies a signed statement that asserted part of the aggregate.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ClaimSource {
+    /// The URI the statement was fetched from
+    pub uri: String,
+    /// Hex sha256 of the statement's signed payload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_sha256: Option<String>,
+    /// The identity that signed the statement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+}
+
+impl ClaimSource {
+    /// The statement fetched from `uri` and verified as `verification` records.
+    pub fn new(uri: &str, verification: &NodeVerification) -> Self {
+        Self {
+            uri: uri.to_string(),
+            payload_sha256: verification.payload_sha256.clone(),
+            signer: verification.identity.clone(),
+        }
+    }
+}
+
+/// Provenance traces a node of the aggregate back to the signed statements that asserted it.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    /// The statement the node was parsed from
+    pub statement: ClaimSource,
+    /// The statement whose link was followed to it: the catalog or catalog page listing a
+    /// component, the component declaring a relationship to it, or the component listing a release
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linked_from: Option<ClaimSource>,
+    /// The statements that asserted the node's derived fields, by field name (e.g. a release's
+    /// `license`, which falls back to its component's)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, ClaimSource>,
+}
+
+/// SkippedCheck is a verification step that an insecure development mode left out.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkippedCheck {
+    /// The statement's signature was not checked, so anyone could have written it
+    Signature,
+    /// The signature was checked, but the signer is not one of the expected identities
+    Identity,
+}
+
+/// VerificationMethod is how a node's authenticity was established.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum VerificationMethod {
+    /// A Sigstore bundle signed with a short-lived certificate naming the signer identity
+    SigstoreKeyless,
+    /// A signature by a long-lived public key rather than a certificate identity
+    Key,
+    /// An unsigned document that matched a digest pinned by a signed statement
+    DigestOnly,
+    /// No signature was checked, because `--insecure-skip-signature` accepted the statement as is
+    Unverified,
+}
+
+/// RootDiscovery records where the root catalog's URI and expected signer identity came from.
+/// The catalog is verified against that identity, so whoever controls the source controls what
+/// the whole aggregate is verified against.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RootDiscovery {
+    /// How the root catalog was located
+    pub method: DiscoveryMethod,
+    /// The TXT record name or page URL the catalog URI and identity were read from
+    pub source: String,
+    /// Who can change what the catalog is verified against
+    pub trust_level: DiscoveryTrustLevel,
+}
+
+impl RootDiscovery {
+    /// Discovery through the domain's `_chainsights` DNS TXT record at `name`.
+    pub fn dns_txt(name: &str) -> Self {
+        Self { method: DiscoveryMethod::DnsTxt, source: name.to_string(), trust_level: DiscoveryTrustLevel::Dns }
+    }
+
+    /// Discovery through a `<meta name="chainsights">` tag on the page at `url`.
+    pub fn html_meta(url: &str) -> Self {
+        Self {
+            method: DiscoveryMethod::HtmlMeta,
+            source: url.to_string(),
+            trust_level: DiscoveryTrustLevel::WebContent,
+        }
+    }
+}
+
+/// DiscoveryMethod is how a domain's root catalog was located.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum DiscoveryMethod {
+    /// The domain's `_chainsights` DNS TXT record
+    DnsTxt,
+    /// A `<meta name="chainsights">` tag on the domain's homepage
+    HtmlMeta,
+}
+
+/// DiscoveryTrustLevel is who can change where a domain's root catalog is found and who must sign it.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum DiscoveryTrustLevel {
+    /// Only whoever controls the domain's DNS zone
+    Dns,
+    /// Anyone who can change the content the domain's web server returns, such as a CMS account or
+    /// a compromised host
+    WebContent,
+}
+
+/// Endorsement is a third-party endorsement whose signature and endorsed digest were verified.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Endorsement {
+    /// The URI the endorsement was fetched from
+    pub uri: String,
+    /// The identity that signed the endorsement
+    pub signer_identity: String,
+    /// The name the consumer trusts the signer identity as (e.g., from `--trust-endorser`). Unset
+    /// for other signers, whose endorser name in the predicate is only their own claim.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trusted_endorser: Option<String>,
+    /// The endorsement predicate
+    pub predicate: ChainsightsEndorsementPredicate,
+}
+
+/// CustomStatement is a verified statement of a proprietary predicate type linked from a node, as
+/// the plugin parsing its predicate type reported it.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CustomStatement {
+    /// The URI the statement was fetched from
+    pub uri: String,
+    /// The identity that signed the statement
+    pub signer_identity: String,
+    /// What the plugin reported of the statement
+    pub predicate: CustomPredicate,
+}
+
+/// CatalogFreshness records a verified statement that the root catalog was still current at a
+/// point in time.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct CatalogFreshness {
+    /// The URI the freshness statement was fetched from
+    pub uri: String,
+    /// The identity that signed the freshness statement, such as a witness
+    pub signer_identity: Option<String>,
+    /// When the catalog was confirmed current: the statement's signing time from the transparency
+    /// log or timestamp authority if one was used, or else its own timestamp
+    pub confirmed_at: String,
+    /// The statement's expiry time, if it declares one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+}
+
+/// LayoutCompliance records how a release's linked build attestations measure up to an in-toto
+/// layout: which functionaries attested to each of its steps.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct LayoutCompliance {
+    /// Whether the layout was in force and every step was attested by at least its threshold of functionaries
+    pub compliant: bool,
+    pub steps: Vec<StepCompliance>,
+    /// Linked attestations that could not be verified or were signed by the wrong functionary, and
+    /// problems with the layout itself, with the reason
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<(String, String)>,
+}
+
+/// StepCompliance records the verified attestations of one step of an in-toto layout.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct StepCompliance {
+    /// The step's name in the layout
+    pub name: String,
+    /// How many distinct functionaries must attest to the step
+    pub threshold: usize,
+    /// The functionaries whose attestations of the step were verified
+    pub functionaries: Vec<String>,
+    /// URIs of the verified attestations of the step
+    pub attestations: Vec<String>,
+    /// Whether enough functionaries attested to the step
+    pub satisfied: bool,
+}
+
+/// CsafAdvisory is a CSAF security advisory that names a release among its products.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct CsafAdvisory {
+    /// The advisory's tracking ID (e.g. "EXAMPLE-SA-2025-0001")
+    pub id: String,
+    pub title: String,
+    /// The URI the advisory was fetched from
+    pub uri: String,
+    /// The advisory's aggregate severity (e.g. "Important"), when it gives one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    /// The release's status in each vulnerability of the advisory that mentions it
+    pub vulnerabilities: Vec<CsafVulnerability>,
+}
+
+/// CsafVulnerability is a release's status in one vulnerability of a CSAF advisory.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct CsafVulnerability {
+    /// The CVE, or the advisory's own identifier for the vulnerability
+    pub id: String,
+    /// The CSAF product status the release is listed under (e.g. "known_affected", "fixed")
+    pub status: String,
+}
+
+impl CsafVulnerability {
+    /// Whether the status says the release is, or may be, affected.
+    pub fn is_affected(&self) -> bool {
+        matches!(
+            self.status.as_str(),
+            "known_affected" | "first_affected" | "last_affected" | "under_investigation"
+        )
+    }
+}
+
+/// SwidTag is a SWID (ISO/IEC 19770-2) or CoSWID (RFC 9393) software identification tag linked
+/// from a release.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct SwidTag {
+    /// The URI the tag was fetched from
+    pub uri: String,
+    /// "swid" for XML tags, "coswid" for CBOR ones
+    pub format: String,
+    /// The tag's globally unique ID, by which asset inventories refer to the software
+    pub tag_id: String,
+    /// The software name given by the tag
+    pub name: String,
+    /// The software version given by the tag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// "primary", "corpus", "patch", or "supplemental"
+    pub tag_type: String,
+    /// The registration ID (e.g. "example.com") of the entity that created the tag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag_creator: Option<String>,
+    /// Whether the tag matched a digest given by its link
+    pub digest_verified: bool,
+}
+
+/// ScorecardResult is a signed OpenSSF Scorecard assessment of a component's source repository.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq)]
+pub struct ScorecardResult {
+    /// The URI of the attestation
+    pub uri: String,
+    /// Who signed the attestation
+    pub signer_identity: String,
+    /// The repository assessed (e.g. "github.com/example/app")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// The commit of the repository assessed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// When the assessment was made
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// The aggregate score (0-10), when the result gives one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    pub checks: Vec<ScorecardCheck>,
+}
+
+impl ScorecardResult {
+    /// The score of a check, looked up by name regardless of case (e.g. "pinned-dependencies").
+    pub fn check_score(&self, name: &str) -> Option<i32> {
+        self.checks.iter().find(|check| check.name.eq_ignore_ascii_case(name)).map(|check| check.score)
+    }
+}
+
+/// ScorecardCheck is the outcome of one Scorecard check.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ScorecardCheck {
+    /// The check's name (e.g. "Pinned-Dependencies")
+    pub name: String,
+    /// The check's score from 0 to 10, or -1 when it was inconclusive
+    pub score: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// DepsDevEnrichment holds what deps.dev knows about one ecosystem package version of a release.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct DepsDevEnrichment {
+    /// The ecosystem PURL that was looked up (e.g. pkg:npm/left-pad@1.3.0)
+    pub purl: String,
+    /// SPDX license expressions reported for the version
+    pub licenses: Vec<String>,
+    /// Security advisory identifiers affecting the version (e.g. GHSA IDs)
+    pub advisories: Vec<String>,
+    /// Source repository of the package, as linked by deps.dev
+    pub source_repository: Option<String>,
+    /// OpenSSF Scorecard overall score of the source repository
+    pub scorecard_score: Option<f64>,
+    /// Date of the Scorecard run
+    pub scorecard_date: Option<String>,
+    /// Direct dependencies of the version as "name@version"
+    pub direct_dependencies: Vec<String>,
+    /// Number of transitive (indirect) dependencies
+    pub indirect_dependency_count: usize,
+}
+
+/// ClearlyDefinedEnrichment holds ClearlyDefined's curated license data for one ecosystem package
+/// version of a release.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct ClearlyDefinedEnrichment {
+    /// The ecosystem PURL that was looked up (e.g. pkg:npm/left-pad@1.3.0)
+    pub purl: String,
+    /// The ClearlyDefined coordinates of the package (e.g. npm/npmjs/-/left-pad/1.3.0)
+    pub coordinates: String,
+    /// The SPDX license expression the package declares, as curated
+    pub declared_license: Option<String>,
+    /// SPDX license expressions discovered in the package's files
+    pub discovered_licenses: Vec<String>,
+    /// ClearlyDefined's score (0-100) of how clearly the package's licensing is described
+    pub license_score: Option<u32>,
+    /// ClearlyDefined's overall score (0-100) of the package's definition
+    pub effective_score: Option<u32>,
+    /// Where the package's source is, as curated
+    pub source_location: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ndjson_lines_reassemble_into_the_aggregate() {
+        let aggregate = AggregatedCatalogData {
+            root_error: None,
+            component_errors: vec![("https://example.com/missing.json".to_string(), "404".to_string())],
+            freshness: Some(CatalogFreshness {
+                uri: "https://example.com/freshness.json".to_string(),
+                signer_identity: Some("witness@example.org".to_string()),
+                confirmed_at: "2025-06-01T00:00:00Z".to_string(),
+                expires: None,
+            }),
+            freshness_error: Some("stale".to_string()),
+            components: vec![
+                AggregatedComponentData {
+                    component_link_uri: "https://example.com/a.json".to_string(),
+                    releases: vec![AggregatedReleaseData {
+                        release_link_uri: "https://example.com/a/1.0.json".to_string(),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                },
+                AggregatedComponentData {
+                    component_link_uri: "https://example.com/b.json".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        aggregate.write_ndjson(&mut buffer).unwrap();
+
+        let mut lines = std::str::from_utf8(&buffer)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap());
+        let mut catalog = lines.next().unwrap();
+        assert_eq!(catalog["record"], "catalog");
+        let components: Vec<_> = lines
+            .map(|mut component| {
+                assert_eq!(component["record"], "component");
+                component.as_object_mut().unwrap().remove("record");
+                component
+            })
+            .collect();
+        let catalog = catalog.as_object_mut().unwrap();
+        catalog.remove("record");
+        catalog.insert("components".to_string(), components.into());
+
+        assert_eq!(serde_json::Value::Object(catalog.clone()), serde_json::to_value(&aggregate).unwrap());
+    }
+}
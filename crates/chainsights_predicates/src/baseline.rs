@@ -1,10 +1,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
-// TODO: These structs should probably be in a separate crate, as they're not specific to Chainsights.
-// NOTE: This isn't currently used, but we may want to use it in the future.
+// NOTE: This isn't currently used by traversal, but we may want to use it in the future.
 
 use serde::{Deserialize, Serialize};
 
+/// Predicate type of OpenSSF Baseline attestations recorded by a manual assessment.
+pub const BASELINE_MANUAL: &str = "https://baseline.openssf.org/attestation/manual";
+
 /// Represents the OpenSSF Baseline predicate, which includes information about the verification process.
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
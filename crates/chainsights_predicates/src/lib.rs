@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! The Chainsights predicate models: the catalog, component, release and other statements a
+//! domain publishes, the aggregated traversal output, and the OpenSSF Baseline predicate.
+//!
+//! This crate has no networking, async runtime or cryptography dependencies, so tools that only
+//! read or write Chainsights statements can depend on it alone. [`parse_predicate`] picks the
+//! model for an in-toto statement from its `predicateType`.
+//!
+//! # Schema versions
+//!
+//! Each predicate type URL carries its schema version, and each kind of predicate is versioned
+//! on its own:
+//!
+//! | Predicate | Current type URL | Also reads | Model |
+//! |-----------|------------------|------------|-------|
+//! | Catalog | [`CATALOG_V2`] | [`CATALOG_V1`] | [`ChainsightsCatalogPredicate`] |
+//! | Component | [`COMPONENT_V2`] | [`COMPONENT_V1`] | [`ChainsightsComponentPredicate`] |
+//! | Release | [`RELEASE_V1`] | | [`ChainsightsReleasePredicate`] |
+//! | Revocation | [`REVOCATION_V1`] | | [`ChainsightsRevocationPredicate`] |
+//! | Endorsement | [`ENDORSEMENT_V1`] | | [`ChainsightsEndorsementPredicate`] |
+//! | Verification | [`VERIFICATION_V3`] | [`VERIFICATION_V1`], [`VERIFICATION_V2`] | [`ChainsightsVerificationPredicate`] |
+//! | Trust snapshot | [`TRUST_SNAPSHOT_V1`] | | [`ChainsightsTrustSnapshotPredicate`] |
+//! | Policy | [`POLICY_V1`] | | [`ChainsightsPolicyPredicate`] |
+//! | Freshness | [`FRESHNESS_V1`] | | [`ChainsightsFreshnessPredicate`] |
+//!
+//! Within a version, fields are only ever added, and added fields are optional, so statements
+//! written against an older release of this crate keep parsing. Renaming, removing or changing
+//! the meaning of a field requires a new type URL, plus an upgrade step so [`parse_predicate`]
+//! (and [`upgrade_predicate`]) can turn documents of the old version into the current model.
+//! Publishers can move to a new version whenever they like. Enums are `#[non_exhaustive]` so new
+//! variants, such as a new relationship type, are not breaking changes.
+//!
+//! v2 renamed catalog entries' `componentPurl` and sub-components' `subComponentPurl` to `purl`,
+//! and repositories' `repoType` to `type`. Verification v2 embeds v2 catalogs and components.
+//! Verification v3 replaces v2's `aggregate`, the client's aggregated output, with `results`, which
+//! holds only the verified statements and the errors.
+
+pub mod aggregation;
+pub mod baseline;
+mod migrate;
+mod predicate;
+
+pub use migrate::upgrade_predicate;
+pub use predicate::*;
@@ -0,0 +1,242 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Upgrades predicates written against older schema versions to the current in-memory model.
+//!
+//! Each step rewrites a predicate of one schema version into the shape of the next. Steps are
+//! chained, so a new version only needs a step from the version before it to keep reading
+//! every older one.
+
+use serde_json::{Value, json};
+
+use crate::predicate::{CATALOG_V1, CATALOG_V2, COMPONENT_V1, COMPONENT_V2, VERIFICATION_V1, VERIFICATION_V2, VERIFICATION_V3};
+
+/// One upgrade step: the predicate type it reads, the type it produces, and the rewrite.
+struct Upgrade {
+    from: &'static str,
+    to: &'static str,
+    apply: fn(&mut Value),
+}
+
+const UPGRADES: &[Upgrade] = &[
+    Upgrade {
+        from: CATALOG_V1,
+        to: CATALOG_V2,
+        apply: catalog_v1_to_v2,
+    },
+    Upgrade {
+        from: COMPONENT_V1,
+        to: COMPONENT_V2,
+        apply: component_v1_to_v2,
+    },
+    Upgrade {
+        from: VERIFICATION_V1,
+        to: VERIFICATION_V2,
+        apply: verification_v1_to_v2,
+    },
+    Upgrade {
+        from: VERIFICATION_V2,
+        to: VERIFICATION_V3,
+        apply: verification_v2_to_v3,
+    },
+];
+
+/// Rewrites `predicate` in place from the schema of `predicate_type` to the current schema of
+/// the same kind, and returns the current predicate type. Current and unrecognized predicate
+/// types are returned unchanged.
+///
+/// Malformed documents are left as they are for deserialization to report.
+pub fn upgrade_predicate<'a>(predicate_type: &'a str, predicate: &mut Value) -> &'a str {
+    let mut current = predicate_type;
+    while let Some(upgrade) = UPGRADES.iter().find(|upgrade| upgrade.from == current) {
+        (upgrade.apply)(predicate);
+        current = upgrade.to;
+    }
+    current
+}
+
+/// Catalog v2 names component entries' PURL `purl`, like every other Chainsights predicate.
+fn catalog_v1_to_v2(catalog: &mut Value) {
+    for entry in array_entries(catalog, "components") {
+        rename_field(entry, "componentPurl", "purl");
+    }
+}
+
+/// Component v2 names a repository's kind `type`, like relationships, and a sub-component's PURL
+/// `purl`.
+fn component_v1_to_v2(component: &mut Value) {
+    for repository in array_entries(component, "repositories") {
+        rename_field(repository, "repoType", "type");
+    }
+    for sub_component in array_entries(component, "subComponents") {
+        rename_field(sub_component, "subComponentPurl", "purl");
+    }
+}
+
+/// Verification v2 embeds an aggregate of v2 catalog and component predicates.
+fn verification_v1_to_v2(verification: &mut Value) {
+    let Some(aggregate) = verification.get_mut("aggregate") else {
+        return;
+    };
+    if let Some(catalog) = aggregate.get_mut("catalog_predicate") {
+        catalog_v1_to_v2(catalog);
+    }
+    for component in array_entries(aggregate, "components") {
+        if let Some(predicate) = component.get_mut("component_predicate") {
+            component_v1_to_v2(predicate);
+        }
+    }
+}
+
+/// Verification v3 carries the verified statements under `results` instead of the client's
+/// aggregated output. Components and releases whose statements did not verify are left out, and
+/// the errors recorded against them are kept.
+fn verification_v2_to_v3(verification: &mut Value) {
+    let Some(mut aggregate) = verification.as_object_mut().and_then(|v| v.remove("aggregate")) else {
+        return;
+    };
+    let mut errors: Vec<Value> = Vec::new();
+    let mut push_errors = |value: &mut Value, key: &str| {
+        for entry in array_entries(value, key) {
+            if let Some([uri, error]) = entry.as_array().map(Vec::as_slice) {
+                errors.push(json!({"uri": uri, "error": error}));
+            }
+        }
+    };
+    push_errors(&mut aggregate, "component_errors");
+    let mut components = Vec::new();
+    for component in array_entries(&mut aggregate, "components") {
+        push_errors(component, "release_errors");
+        let Some(predicate) = component.get_mut("component_predicate").map(Value::take).filter(|p| !p.is_null()) else {
+            continue;
+        };
+        let mut releases = Vec::new();
+        for release in array_entries(component, "releases") {
+            if let Some(predicate) = release.get_mut("release_predicate").map(Value::take).filter(|p| !p.is_null()) {
+                releases.push(json!({"uri": release["release_link_uri"].take(), "predicate": predicate}));
+            }
+        }
+        components.push(json!({"uri": component["component_link_uri"].take(), "predicate": predicate, "releases": releases}));
+    }
+    verification["results"] = json!({
+        "catalog": aggregate.get_mut("catalog_predicate").map(Value::take).unwrap_or(Value::Null),
+        "components": components,
+        "errors": errors,
+    });
+}
+
+/// The entries of the array under `key`, or nothing when there isn't one.
+fn array_entries<'a>(value: &'a mut Value, key: &str) -> impl Iterator<Item = &'a mut Value> {
+    value
+        .get_mut(key)
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+}
+
+/// Moves the value of `from` to `to`, unless the object already has a `to` field.
+fn rename_field(object: &mut Value, from: &str, to: &str) {
+    if let Some(object) = object.as_object_mut()
+        && !object.contains_key(to)
+        && let Some(value) = object.remove(from)
+    {
+        object.insert(to.to_string(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn upgrades_component_v1() {
+        let mut component = json!({
+            "repositories": [{"repoType": "git", "uri": "https://github.com/example/app"}],
+            "subComponents": [{"name": "Auth", "subComponentPurl": "pkg:generic/example.com/auth"}]
+        });
+        assert_eq!(upgrade_predicate(COMPONENT_V1, &mut component), COMPONENT_V2);
+        assert_eq!(
+            component,
+            json!({
+                "repositories": [{"type": "git", "uri": "https://github.com/example/app"}],
+                "subComponents": [{"name": "Auth", "purl": "pkg:generic/example.com/auth"}]
+            })
+        );
+    }
+
+    #[test]
+    fn upgrades_verification_v2_aggregates_to_results() {
+        let mut verification = json!({
+            "verificationResult": "PASSED",
+            "aggregate": {
+                "catalog_predicate": {"components": []},
+                "components": [
+                    {
+                        "component_link_uri": "https://example.com/app.jsonl",
+                        "component_predicate": {"name": "app"},
+                        "releases": [
+                            {"release_link_uri": "https://example.com/app@1.0.0.jsonl", "release_predicate": {"version": "1.0.0"}},
+                            {"release_link_uri": "https://example.com/app@0.9.0.jsonl", "release_predicate": null}
+                        ],
+                        "release_errors": [["https://example.com/app@0.9.0.jsonl", "Signature mismatch"]]
+                    },
+                    {"component_link_uri": "https://example.com/lib.jsonl", "component_predicate": null}
+                ],
+                "component_errors": [["https://example.com/lib.jsonl", "HTTP Status 404"]]
+            }
+        });
+        assert_eq!(upgrade_predicate(VERIFICATION_V2, &mut verification), VERIFICATION_V3);
+        assert_eq!(
+            verification,
+            json!({
+                "verificationResult": "PASSED",
+                "results": {
+                    "catalog": {"components": []},
+                    "components": [{
+                        "uri": "https://example.com/app.jsonl",
+                        "predicate": {"name": "app"},
+                        "releases": [{"uri": "https://example.com/app@1.0.0.jsonl", "predicate": {"version": "1.0.0"}}]
+                    }],
+                    "errors": [
+                        {"uri": "https://example.com/lib.jsonl", "error": "HTTP Status 404"},
+                        {"uri": "https://example.com/app@0.9.0.jsonl", "error": "Signature mismatch"}
+                    ]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn upgrades_aggregates_embedded_in_verification_v1() {
+        let mut verification = json!({
+            "aggregate": {
+                "catalog_predicate": {"components": [{"componentPurl": "pkg:generic/example.com/app"}]},
+                "components": [
+                    {"component_predicate": {"repositories": [{"repoType": "git"}]}},
+                    {"component_predicate": null}
+                ]
+            }
+        });
+        assert_eq!(upgrade_predicate(VERIFICATION_V1, &mut verification), VERIFICATION_V3);
+        assert_eq!(verification["results"]["catalog"]["components"][0]["purl"], "pkg:generic/example.com/app");
+        assert_eq!(verification["results"]["components"][0]["predicate"]["repositories"][0]["type"], "git");
+    }
+
+    #[test]
+    fn leaves_current_and_unknown_predicates_alone() {
+        let original = json!({"components": [{"componentPurl": "pkg:generic/example.com/app"}]});
+        for predicate_type in [CATALOG_V2, "https://slsa.dev/provenance/v1"] {
+            let mut predicate = original.clone();
+            assert_eq!(upgrade_predicate(predicate_type, &mut predicate), predicate_type);
+            assert_eq!(predicate, original);
+        }
+    }
+
+    #[test]
+    fn keeps_fields_already_in_the_new_shape() {
+        let mut catalog = json!({"components": [{"purl": "pkg:generic/new", "componentPurl": "pkg:generic/old"}]});
+        upgrade_predicate(CATALOG_V1, &mut catalog);
+        assert_eq!(catalog["components"][0]["purl"], "pkg:generic/new");
+    }
+}
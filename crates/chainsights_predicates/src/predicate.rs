@@ -0,0 +1,1406 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use chainsights_core::identity::{BuildPolicy, SignerIdentity};
+use chainsights_core::statement::InTotoStatement;
+use chainsights_core::trust::TrustedRoot;
+use packageurl::PackageUrl;
+use serde::{Deserialize, Serialize};
+
+use crate::migrate::upgrade_predicate;
+
+/// Predicate type of root and sub-catalog manifests written against schema v1.
+pub const CATALOG_V1: &str = "https://chainsights.rest/catalog/v1";
+/// Predicate type of the root and sub-catalog manifests.
+pub const CATALOG_V2: &str = "https://chainsights.rest/catalog/v2";
+/// Predicate type of component manifests written against schema v1.
+pub const COMPONENT_V1: &str = "https://chainsights.rest/component/v1";
+/// Predicate type of component manifests.
+pub const COMPONENT_V2: &str = "https://chainsights.rest/component/v2";
+/// Predicate type of release manifests.
+pub const RELEASE_V1: &str = "https://chainsights.rest/release/v1";
+/// Predicate type for a domain's list of revoked attestations.
+pub const REVOCATION_V1: &str = "https://chainsights.rest/revocation/v1";
+/// Predicate type of third-party endorsements of another Chainsights statement.
+pub const ENDORSEMENT_V1: &str = "https://chainsights.rest/endorsement/v1";
+/// Predicate type of verification summaries written against schema v1, whose aggregate holds v1
+/// catalog and component predicates.
+pub const VERIFICATION_V1: &str = "https://chainsights.rest/verification/v1";
+/// Predicate type of verification summaries whose aggregate is the client's aggregated traversal
+/// output.
+pub const VERIFICATION_V2: &str = "https://chainsights.rest/verification/v2";
+/// Predicate type of a verifier's signed summary of its own traversal of a domain.
+pub const VERIFICATION_V3: &str = "https://chainsights.rest/verification/v3";
+/// Predicate type of a signed proof that a root catalog was still current at a point in time.
+pub const FRESHNESS_V1: &str = "https://chainsights.rest/freshness/v1";
+/// Predicate type of a signed snapshot of Sigstore trust material, for offline verification.
+pub const TRUST_SNAPSHOT_V1: &str = "https://chainsights.rest/trust-snapshot/v1";
+/// Predicate type of an organization's signed client policy.
+pub const POLICY_V1: &str = "https://chainsights.rest/policy/v1";
+/// Media type of metadata links that point at endorsement bundles.
+pub const ENDORSEMENT_MEDIA_TYPE: &str = "application/vnd.chainsights.endorsement+json";
+/// Media type of metadata links that point at CSAF security advisories or a CSAF provider's metadata.
+pub const CSAF_MEDIA_TYPE: &str = "application/csaf+json";
+
+/// Represents a link to an attestation, including its URI, digest, media type, and expected signer identity.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationLink {
+    /// URI of the attestation (e.g., "https://example.com/attestation.json").
+    pub uri: String,
+    /// Optional digest of the attestation (e.g., {"sha256": "..."}) This is useful for verifying unsigned artifacts.
+    /// For signed attestations the digest covers the DSSE payload and is used to locate the attestation in Rekor.
+    pub digest: Option<HashMap<String, String>>,
+    /// Optional media type of the attestation (e.g., "application/vnd.in-toto+json").
+    pub media_type: Option<String>,
+    /// Optional expected identity for the attestation. A `did:web` or `did:key` DID instead means
+    /// the attestation is a DSSE envelope signed by one of the DID's assertion keys.
+    pub expected_signer_identity: String,
+    /// Additional identities accepted for the attestation, such as a previous release manager or CI
+    /// identity, each optionally limited to statements signed within a time window.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accepted_signer_identities: Option<Vec<SignerIdentity>>,
+    /// Fingerprints of PGP keys the attestation is signed with. When present, the attestation is a
+    /// PGP cleartext-signed statement, or a statement with a detached signature at `<uri>.asc`,
+    /// rather than a Sigstore bundle, and the signer identities are not used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_pgp_fingerprints: Option<Vec<String>>,
+    /// SSH public keys (`ssh-ed25519 AAAA...`) or minisign public keys (`RWQ...`) the attestation
+    /// is signed with, for publishers with a plain keypair. When present, the attestation is a
+    /// statement with a detached signature at `<uri>.sig` (SSH) or `<uri>.minisig` (minisign),
+    /// and the signer identities are not used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_public_keys: Option<Vec<String>>,
+}
+
+impl AttestationLink {
+    /// All identities accepted for this link: the expected identity, valid at any time, followed
+    /// by any additional accepted identities.
+    pub fn accepted_identities(&self) -> Vec<SignerIdentity> {
+        std::iter::once(SignerIdentity::new(&self.expected_signer_identity))
+            .chain(self.accepted_signer_identities.iter().flatten().cloned())
+            .collect()
+    }
+
+    /// The PGP key fingerprints pinned for this link, empty for Sigstore-signed attestations.
+    pub fn pgp_fingerprints(&self) -> &[String] {
+        self.expected_pgp_fingerprints.as_deref().unwrap_or_default()
+    }
+
+    /// The SSH or minisign public keys pinned for this link, empty unless it is key-signed.
+    pub fn public_keys(&self) -> &[String] {
+        self.expected_public_keys.as_deref().unwrap_or_default()
+    }
+}
+
+/// Enum to hold the different parsed Chainsights predicate types.
+#[derive(Deserialize, Serialize, Debug)]
+#[non_exhaustive]
+pub enum ChainsightsPredicate {
+    Catalog(ChainsightsCatalogPredicate),
+    Component(ChainsightsComponentPredicate),
+    Release(ChainsightsReleasePredicate),
+    Revocation(ChainsightsRevocationPredicate),
+    Endorsement(ChainsightsEndorsementPredicate),
+    Verification(Box<ChainsightsVerificationPredicate>),
+    TrustSnapshot(Box<ChainsightsTrustSnapshotPredicate>),
+    Policy(Box<ChainsightsPolicyPredicate>),
+    Freshness(ChainsightsFreshnessPredicate),
+    /// A statement of a predicate type Chainsights does not define, parsed by a plugin
+    Custom(Box<CustomPredicate>),
+    //Baseline(BaselinePredicate), // Added Baseline predicate type
+    Unknown {
+        predicate_type: String,
+        predicate_value: serde_json::Value,
+    },
+}
+
+impl ChainsightsPredicate {
+    /// A short name for the kind of statement (e.g., "release"), or the predicate type of a
+    /// statement that is not a Chainsights predicate.
+    pub fn kind(&self) -> &str {
+        match self {
+            ChainsightsPredicate::Catalog(_) => "catalog",
+            ChainsightsPredicate::Component(_) => "component",
+            ChainsightsPredicate::Release(_) => "release",
+            ChainsightsPredicate::Revocation(_) => "revocation",
+            ChainsightsPredicate::Endorsement(_) => "endorsement",
+            ChainsightsPredicate::Verification(_) => "verification",
+            ChainsightsPredicate::TrustSnapshot(_) => "trust snapshot",
+            ChainsightsPredicate::Policy(_) => "policy",
+            ChainsightsPredicate::Freshness(_) => "freshness",
+            ChainsightsPredicate::Custom(p) => &p.kind,
+            ChainsightsPredicate::Unknown { predicate_type, .. } => predicate_type,
+        }
+    }
+
+    /// Returns the time the statement says it was generated at.
+    pub fn timestamp(&self) -> Option<&str> {
+        match self {
+            ChainsightsPredicate::Catalog(p) => Some(&p.timestamp),
+            ChainsightsPredicate::Component(p) => Some(&p.timestamp),
+            ChainsightsPredicate::Release(p) => Some(&p.timestamp),
+            ChainsightsPredicate::Revocation(p) => Some(&p.timestamp),
+            ChainsightsPredicate::Endorsement(p) => Some(&p.timestamp),
+            ChainsightsPredicate::Verification(p) => Some(&p.time_verified),
+            ChainsightsPredicate::TrustSnapshot(p) => Some(&p.timestamp),
+            ChainsightsPredicate::Policy(p) => Some(&p.timestamp),
+            ChainsightsPredicate::Freshness(p) => Some(&p.timestamp),
+            ChainsightsPredicate::Custom(_) | ChainsightsPredicate::Unknown { .. } => None,
+        }
+    }
+
+    /// Returns the statement's expiry time, if it declares one.
+    pub fn expires(&self) -> Option<&str> {
+        match self {
+            ChainsightsPredicate::Catalog(p) => p.expires.as_deref(),
+            ChainsightsPredicate::Component(p) => p.expires.as_deref(),
+            ChainsightsPredicate::Release(p) => p.expires.as_deref(),
+            ChainsightsPredicate::Revocation(p) => p.expires.as_deref(),
+            ChainsightsPredicate::Endorsement(p) => p.expires.as_deref(),
+            ChainsightsPredicate::Verification(p) => p.expires.as_deref(),
+            ChainsightsPredicate::TrustSnapshot(p) => p.expires.as_deref(),
+            ChainsightsPredicate::Policy(p) => p.expires.as_deref(),
+            ChainsightsPredicate::Freshness(p) => p.expires.as_deref(),
+            ChainsightsPredicate::Custom(_) | ChainsightsPredicate::Unknown { .. } => None,
+        }
+    }
+}
+
+/// Represents the Chainsights catalog predicate, which includes information about the catalog and its components.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainsightsCatalogPredicate {
+    pub generator: Option<Generator>,
+    /// The timestamp when this catalog was generated.
+    pub timestamp: String,
+    /// Optional RFC 3339 time after which this statement must no longer be trusted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    /// Optional snapshot serial number, increased with every catalog the domain publishes, so that
+    /// clients can tell an older catalog served in place of the current one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial: Option<u64>,
+    // TODO: Add sub_catalogs.
+    /// List of components included in this catalog.
+    pub components: Vec<CatalogComponentEntry>,
+    /// Optional sub-catalogs (e.g., "sub-catalog" for a specific domain, or ).
+    pub sub_catalogs: Option<Vec<SubCatalogLink>>,
+
+    /// Optional Metadata links for the catalog itself. For example a SOC2 report.
+    pub metadata_links: Option<Vec<ArtifactLink>>,
+
+    /// Optional link to the domain's signed revocation list.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_link: Option<AttestationLink>,
+
+    /// Optional link to a regularly re-signed freshness statement naming this catalog, which bounds
+    /// how long an outdated catalog can be served without detection. The link has no digest, since
+    /// the statement behind it changes, and may expect a witness rather than the publisher.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freshness_link: Option<AttestationLink>,
+
+    /// Optional default disclosure channel for everything in the catalog.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security_contact: Option<SecurityContact>,
+
+    /// Optional link to the next page of this catalog's components. Pages are catalog statements
+    /// whose components are appended to this one's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_page: Option<AttestationLink>,
+    /// Optional links to shards of this catalog's component list, fetched like pages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub component_shards: Option<Vec<AttestationLink>>,
+}
+
+/// An organization supplying software, as in the SPDX and CycloneDX supplier fields.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Organization {
+    /// Organization name (e.g., "Example Corp").
+    pub name: String,
+    /// Optional URL of the organization.
+    pub url: Option<String>,
+}
+
+/// Where and how to report vulnerabilities.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityContact {
+    /// Email address for vulnerability reports (e.g., "security@example.com").
+    pub email: Option<String>,
+    /// URI of the vulnerability disclosure policy (VDP).
+    pub policy_uri: Option<String>,
+    /// URI or fingerprint of the PGP key to encrypt reports with.
+    pub pgp_key: Option<String>,
+    /// URI of the publisher's security.txt (RFC 9116).
+    pub security_txt: Option<String>,
+}
+
+impl std::fmt::Display for SecurityContact {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = [
+            self.email.as_ref().map(|e| format!("email {}", e)),
+            self.policy_uri.as_ref().map(|u| format!("policy {}", u)),
+            self.pgp_key.as_ref().map(|k| format!("PGP key {}", k)),
+            self.security_txt.as_ref().map(|u| format!("security.txt {}", u)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if parts.is_empty() {
+            write!(f, "none listed")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+/// Represents a single component entry in the catalog.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogComponentEntry {
+    /// Human-readable name (e.g., "Awesome Web App", "Core Processing Library").
+    pub name: String,
+    /// Brief description of the component.
+    pub description: Option<String>,
+    /// Canonical PURL identifier for the component (typically versionless). REQUIRED.
+    #[serde(rename = "purl")]
+    pub component_purl: String,
+    /// Link to the ChainsightsComponentPredicate bundle for this component. REQUIRED.
+    pub component_attestation_link: AttestationLink,
+    /// Optional key-value labels for categorization/filtering.
+    pub labels: Option<HashMap<String, String>>,
+}
+
+/// Represents a link to a sub-catalog, including its name and attestation link.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubCatalogLink {
+    /// Human-readable name of the sub-catalog.
+    pub name: String,
+    /// Link to the ChainsightsCatalogPredicate bundle for this sub-catalog. REQUIRED.
+    pub catalog_attestation_link: AttestationLink,
+}
+
+/// Represents a Chainsights component predicate, which includes information about the component and its repositories.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainsightsComponentPredicate {
+    /// Optional generator information for the predicate.
+    pub generator: Option<Generator>,
+    /// Timestamp when this attestation was generated.
+    pub timestamp: String,
+    /// Optional RFC 3339 time after which this statement must no longer be trusted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    /// Canonical PURL matching the one in the Catalog. REQUIRED.
+    pub purl: String,
+    /// Human-readable name of the component (e.g., "Awesome Web App", "Core Processing Library"). REQUIRED.
+    pub name: String,
+    /// Optional human-readable description of the component.
+    pub description: Option<String>,
+    /// Other names or identifiers this component might be known by.
+    pub aliases: Option<Vec<String>>,
+    /// Versionless PURLs the component is distributed as in other ecosystems (e.g., pkg:npm/..., pkg:oci/...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias_purls: Option<Vec<String>>,
+    /// Optional CPE 2.3 name of the component, usually with a wildcard version
+    /// (e.g., "cpe:2.3:a:example:my-component:*:*:*:*:*:*:*:*").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpe: Option<String>,
+    /// Optional key-value labels for categorization.
+    pub labels: Option<HashMap<String, String>>,
+    /// Optional disclosure channel for this component, overriding the catalog's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security_contact: Option<SecurityContact>,
+    /// Optional SPDX license expression covering the component (e.g., "Apache-2.0 OR MIT").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// Optional organization that supplies the component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supplier: Option<Organization>,
+
+    // --- Repository & Source Information ---
+    /// List of repositories contributing code or artifacts to this component.
+    pub repositories: Vec<RepositoryInfo>,
+
+    // --- Hierarchy Links ---
+    /// Links to finer-grained sub-components, if applicable (e.g., microservices within a SaaS product).
+    pub sub_components: Option<Vec<SubComponentLink>>,
+    /// Optional relationships to other components, in this catalog or elsewhere.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relationships: Option<Vec<ComponentRelationship>>,
+    /// Links to ChainsightsReleasePredicate bundles for specific versions of this component.
+    /// Potentially ordered (e.g., most recent first), though order isn't guaranteed by the structure itself.
+    pub release_attestations: Vec<AttestationLink>,
+
+    /// Optional metadata links for the component itself (e.g., Baseline).
+    pub metadata_links: Option<Vec<ArtifactLink>>,
+
+    /// Optional support windows for the component's release streams.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub support_streams: Option<Vec<SupportStream>>,
+
+    /// Optional description of the hosted service, for components that are operated rather than
+    /// downloaded (SaaS).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service: Option<ServiceInfo>,
+}
+
+/// The support lifecycle of a release stream: every version starting with `stream` (e.g., "1.2"
+/// covers 1.2.0 and 1.2.7). Dates are RFC 3339 times or YYYY-MM-DD dates.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SupportStream {
+    /// Version prefix identifying the stream (e.g., "1", "1.2").
+    pub stream: String,
+    /// Optional long-term support marker.
+    pub lts: Option<bool>,
+    /// When active support (bug fixes) ends. After this only security fixes are made.
+    pub support_end: Option<String>,
+    /// When the stream reaches end of life and no longer receives any fixes.
+    pub eol: Option<String>,
+}
+
+/// Where and how a hosted service is operated.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceInfo {
+    /// The endpoints customers use (e.g., "https://api.example.com").
+    #[serde(default)]
+    pub endpoints: Vec<ServiceEndpoint>,
+    /// Regions the service runs in, as the provider names them (e.g., "eu-west-1", "us-central1").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regions: Option<Vec<String>>,
+    /// Jurisdictions customer data is stored in, as ISO 3166 country codes or region names (e.g., "DE", "EU").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_residency: Option<Vec<String>>,
+    /// Page reporting the service's availability and incidents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_page_uri: Option<String>,
+    /// Links to compliance reports and certifications covering the service (e.g., SOC 2, ISO/IEC 27001).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compliance_reports: Option<Vec<ArtifactLink>>,
+}
+
+/// An endpoint of a hosted service.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceEndpoint {
+    /// URI of the endpoint (e.g., "https://api.eu.example.com").
+    pub uri: String,
+    /// Optional description of what the endpoint serves (e.g., "REST API").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Regions served by the endpoint, when it is regional.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regions: Option<Vec<String>>,
+}
+
+impl SupportStream {
+    /// Returns true if `version` belongs to this stream.
+    pub fn contains(&self, version: &str) -> bool {
+        let version = version.strip_prefix('v').unwrap_or(version);
+        let stream = self.stream.strip_prefix('v').unwrap_or(&self.stream);
+        version == stream
+            || version
+                .strip_prefix(stream)
+                .is_some_and(|rest| rest.starts_with(['.', '-', '+']))
+    }
+}
+
+impl ChainsightsComponentPredicate {
+    /// Returns the component CPE bound to a release version, when the CPE is a CPE 2.3 formatted
+    /// string whose version is a wildcard. Other CPEs are returned unchanged.
+    pub fn release_cpe(&self, version: &str) -> Option<String> {
+        let cpe = self.cpe.as_ref()?;
+        let mut parts: Vec<&str> = cpe.split(':').collect();
+        if cpe.contains('\\') || parts.len() != 13 || parts[..2] != ["cpe", "2.3"] || !matches!(parts[5], "*" | "-") {
+            return Some(cpe.clone());
+        }
+        parts[5] = version;
+        Some(parts.join(":"))
+    }
+
+    /// Returns the component PURL or alias that names the same package as `package`, ignoring
+    /// version and qualifiers. Free-form `aliases` are considered when they are PURLs.
+    pub fn matching_purl(&self, package: &PackageUrl) -> Option<&str> {
+        std::iter::once(&self.purl)
+            .chain(self.alias_purls.iter().flatten())
+            .chain(self.aliases.iter().flatten())
+            .map(String::as_str)
+            .find(|candidate| {
+                PackageUrl::from_str(candidate).is_ok_and(|candidate| {
+                    candidate.ty().eq_ignore_ascii_case(package.ty())
+                        && candidate.namespace() == package.namespace()
+                        && candidate.name() == package.name()
+                })
+            })
+    }
+
+    /// Versionless ecosystem PURLs from `alias_purls` and any free-form aliases that are PURLs.
+    pub fn ecosystem_purls(&self) -> impl Iterator<Item = &String> {
+        self.alias_purls
+            .iter()
+            .flatten()
+            .chain(self.aliases.iter().flatten().filter(|a| a.starts_with("pkg:")))
+    }
+}
+
+/// Removes the version (and any qualifiers or subpath) from a PURL so it identifies the
+/// component rather than a single release.
+pub fn strip_purl_version(purl: &str) -> String {
+    let end = purl.find(['?', '#']).unwrap_or(purl.len());
+    let without_qualifiers = &purl[..end];
+    // The version separator is the last '@' after the type (namespaces may contain encoded '@').
+    match without_qualifiers.rfind('@') {
+        Some(at) if at > "pkg:".len() => without_qualifiers[..at].to_string(),
+        _ => without_qualifiers.to_string(),
+    }
+}
+
+/// Represents a repository contributing to the component, including its type, URI, and paths.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryInfo {
+    /// Type of repository (e.g., "git", "svn", "oci").
+    #[serde(rename = "type")]
+    pub repo_type: String,
+    /// The primary URI of the repository (e.g., git clone URL). REQUIRED.
+    pub uri: String,
+    /// Specific paths within the repository relevant to this component.
+    /// Used for monorepo support. E.g., ["/services/auth", "/libs/shared"]
+    pub paths: Option<Vec<String>>,
+    /// The primary or root path for the component within the repo, if applicable.
+    pub primary_path: Option<String>,
+}
+
+/// A relationship from a component to another component.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentRelationship {
+    /// How the component relates to the target.
+    #[serde(rename = "type")]
+    pub relationship_type: RelationshipType,
+    /// Component PURL of the related component.
+    pub target_purl: String,
+    /// Optional link to the related component's attestation, followed when the related component
+    /// is not listed in the catalog being traversed.
+    pub component_attestation_link: Option<AttestationLink>,
+}
+
+/// Kinds of relationship between components.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum RelationshipType {
+    /// The component needs the target to build or run.
+    DependsOn,
+    /// The component includes the target (e.g., a bundled library or an image layer).
+    Contains,
+    /// The component replaces the target.
+    Supersedes,
+}
+
+impl std::fmt::Display for RelationshipType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RelationshipType::DependsOn => "depends-on",
+            RelationshipType::Contains => "contains",
+            RelationshipType::Supersedes => "supersedes",
+        })
+    }
+}
+
+/// Represents a link to a sub-component, including its PURL and attestation link.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubComponentLink {
+    /// Human-readable name of the sub-component.
+    pub name: String,
+    /// PURL for the sub-component. REQUIRED.
+    #[serde(rename = "purl")]
+    pub sub_component_purl: String,
+    /// Link to the sub-component's own ChainsightsComponentPredicate bundle. REQUIRED.
+    pub component_attestation_link: AttestationLink,
+}
+
+/// A predicate for a specific release of a component.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainsightsReleasePredicate {
+    /// Optional generator information for the predicate.
+    pub generator: Option<Generator>,
+    /// Timestamp when this attestation was generated.
+    pub timestamp: String,
+    /// Optional RFC 3339 time after which this statement must no longer be trusted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+
+    // --- Release Identification & Metadata ---
+    /// PURL of the component. REQUIRED.
+    pub purl: String,
+    /// Human-readable name of the release (e.g., "v1.2.0", "2023-10-01").
+    pub name: String,
+    /// Optional CPE 2.3 name of the release (e.g., "cpe:2.3:a:example:my-component:1.2.0:*:*:*:*:*:*:*").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpe: Option<String>,
+    /// ISO 8601 date when this version was released.
+    pub release_date: Option<String>,
+    /// Optional link to human-readable release notes.
+    pub release_notes_uri: Option<String>,
+    /// Optional indicator of the release's maturity (e.g., "development", "beta", "stable", "deprecated").
+    pub lifecycle_phase: Option<String>,
+    /// Optional status set when the publisher pulls a release after publishing it. Absent means active.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<ReleaseStatus>,
+    /// Optional SPDX license expression for this release, when it differs from the component's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// Optional supplier of this release, when it differs from the component's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supplier: Option<Organization>,
+
+    // --- Linked Artifacts ---
+    /// Links to associated supply chain artifacts (SBOMs, SLSA, VEX, etc.). REQUIRED.
+    pub metadata_links: Option<Vec<ArtifactLink>>,
+
+    /// List of artifacts associated with this release.
+    pub artifacts: Option<Vec<ArtifactLink>>,
+
+    /// Optional platform-specific artifacts, one group per platform (e.g., the linux/arm64 binary
+    /// and its SBOM).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_groups: Option<Vec<ArtifactGroup>>,
+}
+
+/// Artifacts built for one platform.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactGroup {
+    /// The platform the artifacts run on.
+    pub platform: Platform,
+    /// The artifacts, with their digests and media types.
+    pub artifacts: Vec<ArtifactLink>,
+}
+
+/// A target platform, using the OCI image index terms (e.g., os "linux", architecture "arm64",
+/// variant "v8").
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Platform {
+    pub os: String,
+    pub architecture: String,
+    pub variant: Option<String>,
+}
+
+impl Platform {
+    /// Returns true if artifacts built for this platform suit `wanted`. Common architecture
+    /// aliases (x86_64/amd64, aarch64/arm64) are treated as equal, and a variant only has to
+    /// match when `wanted` names one.
+    pub fn matches(&self, wanted: &Platform) -> bool {
+        fn architecture(name: &str) -> String {
+            match name.to_ascii_lowercase().as_str() {
+                "x86_64" | "x86-64" => "amd64".to_string(),
+                "aarch64" => "arm64".to_string(),
+                other => other.to_string(),
+            }
+        }
+        self.os.eq_ignore_ascii_case(&wanted.os)
+            && architecture(&self.architecture) == architecture(&wanted.architecture)
+            && match &wanted.variant {
+                Some(variant) => self.variant.as_deref().is_some_and(|v| v.eq_ignore_ascii_case(variant)),
+                None => true,
+            }
+    }
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.os, self.architecture)?;
+        if let Some(variant) = &self.variant {
+            write!(f, "/{}", variant)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Platform {
+    type Err = anyhow::Error;
+
+    /// Parses "os/arch" or "os/arch/variant".
+    fn from_str(platform: &str) -> Result<Self> {
+        let mut parts = platform.split('/');
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(os), Some(architecture), variant, None) if !os.is_empty() && !architecture.is_empty() => Ok(Self {
+                os: os.to_string(),
+                architecture: architecture.to_string(),
+                variant: variant.filter(|v| !v.is_empty()).map(str::to_string),
+            }),
+            _ => Err(anyhow::anyhow!("Invalid platform '{}': expected os/arch[/variant]", platform)),
+        }
+    }
+}
+
+/// Whether a release is still offered by its publisher.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseStatus {
+    /// The release's state.
+    pub state: ReleaseState,
+    /// Human-readable reason for yanking or withdrawing the release.
+    pub reason: Option<String>,
+    /// Version that replaces this release (e.g., "1.0.1").
+    pub superseded_by: Option<String>,
+}
+
+impl ReleaseStatus {
+    /// Returns true if consumers should no longer use the release.
+    pub fn is_pulled(&self) -> bool {
+        !matches!(self.state, ReleaseState::Active)
+    }
+}
+
+impl std::fmt::Display for ReleaseStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = match self.state {
+            ReleaseState::Active => "active",
+            ReleaseState::Yanked => "yanked",
+            ReleaseState::Withdrawn => "withdrawn",
+        };
+        write!(f, "{}", state)?;
+        if let Some(reason) = &self.reason {
+            write!(f, ": {}", reason)?;
+        }
+        if let Some(superseded_by) = &self.superseded_by {
+            write!(f, " (superseded by {})", superseded_by)?;
+        }
+        Ok(())
+    }
+}
+
+/// Release states. Yanked releases stay available for existing users but should not be newly
+/// adopted; withdrawn releases should not be used at all.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum ReleaseState {
+    Active,
+    Yanked,
+    Withdrawn,
+}
+
+/// A publisher's list of previously published attestations that must no longer be trusted.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainsightsRevocationPredicate {
+    /// Optional generator information for the predicate.
+    pub generator: Option<Generator>,
+    /// Timestamp when this revocation list was generated.
+    pub timestamp: String,
+    /// Optional RFC 3339 time after which this statement must no longer be trusted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    /// The revoked attestations.
+    pub revocations: Vec<RevokedAttestation>,
+}
+
+impl ChainsightsRevocationPredicate {
+    /// Returns the revocation covering an attestation link, if any.
+    pub fn find(&self, link: &AttestationLink) -> Option<&RevokedAttestation> {
+        self.revocations.iter().find(|r| r.matches(link))
+    }
+
+    /// Returns the revocation covering a fetched statement, identified by the SHA-256 digest of
+    /// its DSSE payload, if any. This catches a revoked statement re-linked under another URI.
+    pub fn find_sha256(&self, sha256: &str) -> Option<&RevokedAttestation> {
+        self.revocations
+            .iter()
+            .find(|r| r.digest.as_ref().and_then(|d| d.get("sha256")).is_some_and(|d| d.eq_ignore_ascii_case(sha256)))
+    }
+}
+
+/// What a plugin reported of a statement whose predicate type it parses, such as a proprietary
+/// build attestation.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct CustomPredicate {
+    /// The statement's predicate type
+    pub predicate_type: String,
+    /// Name of the plugin that parsed the statement
+    pub plugin: String,
+    /// Short name of the predicate, as the plugin reports it (e.g., "build-farm-run")
+    pub kind: String,
+    /// Lines describing the predicate's contents
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+}
+
+/// A statement, re-signed at regular intervals by the publisher or a witness, that a root catalog
+/// was still the current one at `timestamp`. Its `expires` time, or the age a client accepts,
+/// bounds how stale a served catalog can be.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainsightsFreshnessPredicate {
+    /// Optional generator information for the predicate.
+    pub generator: Option<Generator>,
+    /// Timestamp when the catalog was last confirmed current.
+    pub timestamp: String,
+    /// Optional RFC 3339 time after which this statement must no longer be trusted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    /// The current root catalog statement, by the digest of its DSSE payload.
+    pub catalog: EndorsedStatement,
+    /// Optional serial number of the current root catalog.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial: Option<u64>,
+}
+
+/// A third party's countersignature of another Chainsights statement, such as an auditor or
+/// foundation vouching for a release. Endorsements are linked from the endorsed statement's
+/// metadata links with the endorsement media type and signed by the endorser.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainsightsEndorsementPredicate {
+    /// Optional generator information for the predicate.
+    pub generator: Option<Generator>,
+    /// Timestamp when this endorsement was made.
+    pub timestamp: String,
+    /// Optional RFC 3339 time after which this statement must no longer be trusted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    /// The endorsing organization.
+    pub endorser: Organization,
+    /// The statement being endorsed.
+    pub endorsed_statement: EndorsedStatement,
+    /// What the endorser vouches for (e.g., "audited", "reviewed", "certified").
+    pub assertion: String,
+    /// Optional human-readable remarks.
+    pub comment: Option<String>,
+    /// Optional link to supporting evidence, such as an audit report.
+    pub evidence_uri: Option<String>,
+}
+
+/// Identifies an endorsed statement by the digest of its DSSE payload.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EndorsedStatement {
+    /// Optional URI the statement was published at.
+    pub uri: Option<String>,
+    /// Digest of the statement's DSSE payload (e.g., {"sha256": "..."}). REQUIRED.
+    pub digest: HashMap<String, String>,
+}
+
+impl EndorsedStatement {
+    /// Returns true if this names the statement whose DSSE payload has the given SHA-256 digest.
+    pub fn matches_sha256(&self, sha256: &str) -> bool {
+        self.digest.get("sha256").is_some_and(|d| d.eq_ignore_ascii_case(sha256))
+    }
+}
+
+/// A verifier's signed summary of its traversal of a domain, modelled on the SLSA verification
+/// summary attestation (VSA). Consumers that trust the verifier can accept the statements it
+/// verified instead of traversing the domain themselves.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainsightsVerificationPredicate {
+    /// The organization or service that performed the verification.
+    pub verifier: Verifier,
+    /// RFC 3339 time the verification was performed.
+    pub time_verified: String,
+    /// Optional RFC 3339 time after which this statement must no longer be trusted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    /// The domain that was traversed (e.g., "example.com").
+    pub domain: String,
+    /// The policy the verifier applied.
+    pub policy: VerificationPolicy,
+    /// Whether the domain passed the policy.
+    pub verification_result: VerificationResult,
+    /// The statements the verifier accepted.
+    pub results: VerifiedDomain,
+}
+
+/// The statements a verifier accepted while traversing a domain, and those it could not verify.
+/// This is the signed form of a verifier's results, kept apart from the client's aggregated output
+/// so that the output can change without changing what verifiers sign.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiedDomain {
+    /// The domain's root catalog.
+    pub catalog: Option<ChainsightsCatalogPredicate>,
+    /// The verified components.
+    #[serde(default)]
+    pub components: Vec<VerifiedComponent>,
+    /// Statements that failed verification.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<VerificationError>,
+}
+
+/// A verified component statement and its verified releases.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiedComponent {
+    /// URI the component statement was fetched from.
+    pub uri: String,
+    /// The component predicate.
+    pub predicate: ChainsightsComponentPredicate,
+    /// The component's verified releases.
+    #[serde(default)]
+    pub releases: Vec<VerifiedRelease>,
+}
+
+/// A verified release statement.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiedRelease {
+    /// URI the release statement was fetched from.
+    pub uri: String,
+    /// The release predicate.
+    pub predicate: ChainsightsReleasePredicate,
+}
+
+/// A statement a verifier could not verify.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationError {
+    /// URI of the statement.
+    pub uri: String,
+    /// Why it failed.
+    pub error: String,
+}
+
+/// A snapshot of a Sigstore instance's trust material (Fulcio certificate chains, Rekor and CT
+/// log keys), signed so that it can be carried into an air-gapped environment and trusted there
+/// for verifying bundles without reaching the instance or its TUF repository.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainsightsTrustSnapshotPredicate {
+    pub generator: Option<Generator>,
+    /// RFC 3339 time the snapshot was taken.
+    pub timestamp: String,
+    /// Optional RFC 3339 time after which the snapshot must be refreshed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    /// Where the trust material was taken from (e.g., a file or URL).
+    pub source: String,
+    /// The trust material, in Sigstore's `trusted_root.json` format.
+    pub trusted_root: TrustedRoot,
+}
+
+/// An organization's client policy: the identities, artifacts and trust material its machines
+/// require, signed so that it can be distributed and updated without redeploying configuration
+/// to every machine.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainsightsPolicyPredicate {
+    pub generator: Option<Generator>,
+    /// RFC 3339 time the policy was issued.
+    pub timestamp: String,
+    /// Optional RFC 3339 time after which the policy must be reissued.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    /// Name of the policy (e.g., "acme-production").
+    pub name: String,
+    /// Identities each domain's root catalog may be signed by, keyed by domain. A `_chainsights`
+    /// record advertising any other identity fails discovery.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub root_identities: BTreeMap<String, Vec<String>>,
+    /// What every verified release must link or satisfy.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_artifacts: Vec<RequiredArtifact>,
+    /// Whether the root catalog and every component and release must verify.
+    #[serde(default)]
+    pub require_all_releases: bool,
+    /// Optional largest tolerated share of statements that failed to verify or had expired,
+    /// between 0 and 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_error_rate: Option<f64>,
+    /// Optional constraints on the CI build context of release signing certificates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_build_policy: Option<BuildPolicy>,
+    /// Optional Sigstore trust material to verify bundles against, in `trusted_root.json` format.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trusted_root: Option<TrustedRoot>,
+}
+
+/// Something a policy requires of every verified release.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum RequiredArtifact {
+    /// A linked SPDX or CycloneDX SBOM
+    Sbom,
+    /// A linked in-toto build attestation
+    Provenance,
+    /// A linked OpenVEX or CSAF document, or a CSAF advisory naming the release
+    Vex,
+    /// Artifacts that each pin a digest
+    SignedArtifacts,
+}
+
+impl FromStr for RequiredArtifact {
+    type Err = anyhow::Error;
+
+    fn from_str(artifact: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(artifact.to_string())).map_err(|_| {
+            anyhow::anyhow!("Unknown required artifact '{}': expected sbom, provenance, vex or signed-artifacts", artifact)
+        })
+    }
+}
+
+impl fmt::Display for RequiredArtifact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = serde_json::to_value(self).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+        f.write_str(&name)
+    }
+}
+
+/// Identifies a verifier, as in the SLSA VSA `verifier` field.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Verifier {
+    /// URI identifying the verifier (e.g., "https://verifier.example.org").
+    pub id: String,
+    /// Optional versions of the verifier's components (e.g., {"chainsights_client": "0.1.0"}).
+    pub version: Option<HashMap<String, String>>,
+}
+
+/// The policy a verifier evaluated.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationPolicy {
+    /// URI of the policy.
+    pub uri: String,
+    /// Optional digest of the policy document (e.g., {"sha256": "..."}).
+    pub digest: Option<HashMap<String, String>>,
+}
+
+/// Outcome of a verification.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[non_exhaustive]
+pub enum VerificationResult {
+    Passed,
+    Failed,
+}
+
+/// A revoked component, release, or catalog attestation, identified by URI and/or digest.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RevokedAttestation {
+    /// URI the attestation was published at.
+    pub uri: Option<String>,
+    /// Digest of the attestation (e.g., {"sha256": "..."}).
+    pub digest: Option<HashMap<String, String>>,
+    /// Human-readable reason for the revocation (e.g., "signed with a compromised key").
+    pub reason: Option<String>,
+    /// RFC 3339 time the attestation was revoked.
+    pub revoked_at: Option<String>,
+}
+
+impl RevokedAttestation {
+    /// A link is revoked when its URI matches, or when it pins a digest that matches for any
+    /// algorithm both sides list.
+    fn matches(&self, link: &AttestationLink) -> bool {
+        if self.uri.as_deref() == Some(link.uri.as_str()) {
+            return true;
+        }
+        match (&self.digest, &link.digest) {
+            (Some(revoked), Some(linked)) => revoked
+                .iter()
+                .any(|(algorithm, value)| linked.get(algorithm).is_some_and(|v| v.eq_ignore_ascii_case(value))),
+            _ => false,
+        }
+    }
+}
+
+/// Represents the generator of the predicate, typically a tool or service.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Generator {
+    pub purl: String,
+}
+
+// TODO: Split ArtifactLink into signed and unsigned versions.
+/// Represents a link to an artifact, including optional metadata for verification.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactLink {
+    /// URI of the artifact (e.g., "https://example.com/artifact.json").
+    pub uri: String,
+    /// Optional digest of the artifact (e.g., {"sha256": "..."}) This is useful for verifying unsigned artifacts.
+    pub digest: Option<HashMap<String, String>>,
+    /// Optional media type of the artifact (e.g., "application/vnd.in-toto+json").
+    pub media_type: Option<String>,
+    /// Optional expected identity for the artifact
+    pub expected_signer_identity: Option<String>,
+    /// Optional hint of whether the artifact can be fetched by anyone. Links without it are public.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access: Option<LinkAccess>,
+    /// Optional URL where access to a gated artifact can be requested (e.g., a trust center form).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_url: Option<String>,
+}
+
+/// Whether a linked artifact can be fetched by anyone, or only after authenticating or agreeing
+/// to terms such as an NDA, as is usual for SOC 2 reports.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkAccess {
+    Public,
+    Gated,
+}
+
+impl ArtifactLink {
+    /// Returns true if the artifact is access-controlled, so it should not be fetched automatically.
+    pub fn is_gated(&self) -> bool {
+        self.access == Some(LinkAccess::Gated)
+    }
+
+    /// Describes how to obtain a gated artifact, for output in place of a fetch.
+    pub fn access_instructions(&self) -> String {
+        match &self.request_url {
+            Some(request_url) => format!("'{}' is access-controlled; request access at {}", self.uri, request_url),
+            None => format!("'{}' is access-controlled; ask the supplier for access", self.uri),
+        }
+    }
+}
+
+// TODO: Figure out if Baseline will be a first class predicate type or not.
+/// Parses the predicate from an InTotoStatement based on its predicateType. Predicates written
+/// against an older schema version are upgraded to the current model first.
+pub fn parse_predicate(statement: &InTotoStatement) -> Result<ChainsightsPredicate> {
+    // const BASELINE: &str = "https://baseline.openssf.org/attestation/manual";
+
+    let mut predicate_value = statement.predicate.clone();
+    match upgrade_predicate(&statement.predicate_type, &mut predicate_value) {
+        CATALOG_V2 => {
+            let predicate: ChainsightsCatalogPredicate = serde_json::from_value(predicate_value)
+                .context(format!("Failed to parse predicate as {}", statement.predicate_type))?;
+            Ok(ChainsightsPredicate::Catalog(predicate))
+        }
+        COMPONENT_V2 => {
+            let predicate: ChainsightsComponentPredicate = serde_json::from_value(predicate_value)
+                .context(format!("Failed to parse predicate as {}", statement.predicate_type))?;
+            Ok(ChainsightsPredicate::Component(predicate))
+        }
+        RELEASE_V1 => {
+            let predicate: ChainsightsReleasePredicate = serde_json::from_value(predicate_value)
+                .context(format!("Failed to parse predicate as {}", RELEASE_V1))?;
+            Ok(ChainsightsPredicate::Release(predicate))
+        }
+        REVOCATION_V1 => {
+            let predicate: ChainsightsRevocationPredicate = serde_json::from_value(predicate_value)
+                .context(format!("Failed to parse predicate as {}", REVOCATION_V1))?;
+            Ok(ChainsightsPredicate::Revocation(predicate))
+        }
+        ENDORSEMENT_V1 => {
+            let predicate: ChainsightsEndorsementPredicate = serde_json::from_value(predicate_value)
+                .context(format!("Failed to parse predicate as {}", ENDORSEMENT_V1))?;
+            Ok(ChainsightsPredicate::Endorsement(predicate))
+        }
+        VERIFICATION_V3 => {
+            let predicate: ChainsightsVerificationPredicate = serde_json::from_value(predicate_value)
+                .context(format!("Failed to parse predicate as {}", statement.predicate_type))?;
+            Ok(ChainsightsPredicate::Verification(Box::new(predicate)))
+        }
+        FRESHNESS_V1 => {
+            let predicate: ChainsightsFreshnessPredicate = serde_json::from_value(predicate_value)
+                .context(format!("Failed to parse predicate as {}", FRESHNESS_V1))?;
+            Ok(ChainsightsPredicate::Freshness(predicate))
+        }
+        TRUST_SNAPSHOT_V1 => {
+            let predicate: ChainsightsTrustSnapshotPredicate = serde_json::from_value(predicate_value)
+                .context(format!("Failed to parse predicate as {}", TRUST_SNAPSHOT_V1))?;
+            Ok(ChainsightsPredicate::TrustSnapshot(Box::new(predicate)))
+        }
+        POLICY_V1 => {
+            let predicate: ChainsightsPolicyPredicate = serde_json::from_value(predicate_value)
+                .context(format!("Failed to parse predicate as {}", POLICY_V1))?;
+            Ok(ChainsightsPredicate::Policy(Box::new(predicate)))
+        }
+        /*BASELINE => {
+            let predicate: BaselinePredicate = serde_json::from_value(statement.predicate.clone())
+               .context(format!("Failed to parse predicate as {}", BASELINE))?;
+            Ok(ChainsightsPredicate::Baseline(predicate))
+        }*/
+        unknown_type => {
+            println!("WARN: Unrecognized predicateType: {}", unknown_type);
+            Ok(ChainsightsPredicate::Unknown {
+                predicate_type: unknown_type.to_string(),
+                predicate_value,
+            })
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use serde::de::DeserializeOwned;
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    /// Parses `document`, serializes it back and checks that nothing was lost and that a second
+    /// pass produces the same JSON.
+    fn round_trip<T: Serialize + DeserializeOwned>(document: Value) -> T {
+        let parsed: T = serde_json::from_value(document.clone()).unwrap();
+        let serialized = serde_json::to_value(&parsed).unwrap();
+        assert_contains(&serialized, &document, "$");
+        let reparsed: T = serde_json::from_value(serialized.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&reparsed).unwrap(), serialized);
+        reparsed
+    }
+
+    /// Asserts that every value in `expected` is present in `actual`. Extra keys in `actual` are
+    /// allowed, since unset optional fields may serialize as null.
+    fn assert_contains(actual: &Value, expected: &Value, path: &str) {
+        match (actual, expected) {
+            (Value::Object(actual), Value::Object(expected)) => {
+                for (key, value) in expected {
+                    let path = format!("{}.{}", path, key);
+                    let actual = actual.get(key).unwrap_or_else(|| panic!("{} was dropped", path));
+                    assert_contains(actual, value, &path);
+                }
+            }
+            (Value::Array(actual), Value::Array(expected)) => {
+                assert_eq!(actual.len(), expected.len(), "{} changed length", path);
+                for (i, (actual, expected)) in actual.iter().zip(expected).enumerate() {
+                    assert_contains(actual, expected, &format!("{}[{}]", path, i));
+                }
+            }
+            _ => assert_eq!(actual, expected, "{} changed", path),
+        }
+    }
+
+    fn link(uri: &str) -> Value {
+        json!({
+            "uri": uri,
+            "digest": {"sha256": "abc123"},
+            "mediaType": "application/vnd.dev.sigstore.bundle.v0.3+json",
+            "expectedSignerIdentity": "release@example.com"
+        })
+    }
+
+    #[test]
+    fn catalog_round_trips() {
+        let catalog: ChainsightsCatalogPredicate = round_trip(json!({
+            "generator": {"purl": "pkg:cargo/chainsights_client@0.1.0"},
+            "timestamp": "2025-04-20T05:05:22Z",
+            "expires": "2026-04-20T05:05:22Z",
+            "components": [{
+                "name": "My Component",
+                "description": "Does things",
+                "purl": "pkg:generic/example.com/my-component",
+                "componentAttestationLink": link("https://example.com/my-component.json"),
+                "labels": {"tier": "1"}
+            }],
+            "subCatalogs": [{
+                "name": "Team A",
+                "catalogAttestationLink": link("https://example.com/team-a.json")
+            }],
+            "metadataLinks": [{
+                "uri": "https://example.com/soc2.pdf",
+                "mediaType": "application/pdf",
+                "access": "gated",
+                "requestUrl": "https://trust.example.com/request"
+            }],
+            "revocationLink": link("https://example.com/revocations.json"),
+            "securityContact": {"email": "security@example.com", "securityTxt": "https://example.com/.well-known/security.txt"},
+            "nextPage": link("https://example.com/catalog-2.json"),
+            "componentShards": [link("https://example.com/shard-1.json")]
+        }));
+        assert_eq!(catalog.components[0].component_purl, "pkg:generic/example.com/my-component");
+        assert_eq!(catalog.sub_catalogs.unwrap()[0].name, "Team A");
+        let soc2 = &catalog.metadata_links.unwrap()[0];
+        assert!(soc2.is_gated());
+        assert_eq!(
+            soc2.access_instructions(),
+            "'https://example.com/soc2.pdf' is access-controlled; request access at https://trust.example.com/request"
+        );
+    }
+
+    #[test]
+    fn component_round_trips() {
+        let mut accepted = link("https://example.com/my-component@1.0.0.json");
+        accepted["acceptedSignerIdentities"] = json!([{
+            "identity": "old-release@example.com",
+            "notAfter": "2025-01-01T00:00:00Z"
+        }]);
+        let component: ChainsightsComponentPredicate = round_trip(json!({
+            "timestamp": "2025-04-20T05:05:22Z",
+            "purl": "pkg:generic/example.com/my-component",
+            "name": "My Component",
+            "aliases": ["mycomp"],
+            "aliasPurls": ["pkg:npm/my-component"],
+            "cpe": "cpe:2.3:a:example:my-component:*:*:*:*:*:*:*:*",
+            "license": "Apache-2.0 OR MIT",
+            "supplier": {"name": "Example Corp", "url": "https://example.com"},
+            "repositories": [{
+                "type": "git",
+                "uri": "https://github.com/example/my-component",
+                "paths": ["/services/auth"],
+                "primaryPath": "/services/auth"
+            }],
+            "subComponents": [{
+                "name": "Auth",
+                "purl": "pkg:generic/example.com/auth",
+                "componentAttestationLink": link("https://example.com/auth.json")
+            }],
+            "relationships": [{
+                "type": "depends-on",
+                "targetPurl": "pkg:generic/example.com/runtime",
+                "componentAttestationLink": link("https://example.com/runtime.json")
+            }],
+            "releaseAttestations": [accepted],
+            "supportStreams": [{"stream": "1", "lts": true, "supportEnd": "2026-01-01", "eol": "2027-01-01"}],
+            "service": {
+                "endpoints": [{"uri": "https://api.eu.example.com", "description": "REST API", "regions": ["eu-west-1"]}],
+                "regions": ["eu-west-1"],
+                "dataResidency": ["EU"],
+                "statusPageUri": "https://status.example.com",
+                "complianceReports": [{"uri": "https://example.com/iso27001.pdf", "access": "public"}]
+            }
+        }));
+        let relationship = &component.relationships.as_ref().unwrap()[0];
+        assert_eq!(relationship.relationship_type, RelationshipType::DependsOn);
+        assert_eq!(component.release_attestations[0].accepted_identities().len(), 2);
+        assert_eq!(
+            component.release_cpe("1.0.0").as_deref(),
+            Some("cpe:2.3:a:example:my-component:1.0.0:*:*:*:*:*:*:*")
+        );
+    }
+
+    #[test]
+    fn release_round_trips() {
+        let release: ChainsightsReleasePredicate = round_trip(json!({
+            "timestamp": "2025-04-20T05:05:22Z",
+            "purl": "pkg:generic/example.com/my-component@1.0.0",
+            "name": "v1.0.0",
+            "releaseDate": "2025-04-20",
+            "lifecyclePhase": "stable",
+            "status": {"state": "yanked", "reason": "broken build", "supersededBy": "1.0.1"},
+            "metadataLinks": [{"uri": "https://example.com/sbom.json", "digest": {"sha256": "abc123"}, "mediaType": "application/spdx+json"}],
+            "artifacts": [{"uri": "https://example.com/my-component.tar.gz", "digest": {"sha256": "def456"}}],
+            "artifactGroups": [{
+                "platform": {"os": "linux", "architecture": "arm64", "variant": "v8"},
+                "artifacts": [{"uri": "https://example.com/my-component-arm64", "digest": {"sha256": "789abc"}}]
+            }]
+        }));
+        let status = release.status.unwrap();
+        assert_eq!(status.state, ReleaseState::Yanked);
+        assert!(status.is_pulled());
+        assert_eq!(release.artifact_groups.unwrap()[0].platform.to_string(), "linux/arm64/v8");
+    }
+
+    #[test]
+    fn revocation_and_endorsement_round_trip() {
+        let revocations: ChainsightsRevocationPredicate = round_trip(json!({
+            "timestamp": "2025-04-20T05:05:22Z",
+            "revocations": [{
+                "uri": "https://example.com/my-component@0.9.0.json",
+                "digest": {"sha256": "abc123"},
+                "reason": "signed with a compromised key",
+                "revokedAt": "2025-04-19T00:00:00Z"
+            }]
+        }));
+        let link: AttestationLink = serde_json::from_value(link("https://example.com/elsewhere.json")).unwrap();
+        assert!(revocations.find(&link).is_some());
+
+        let endorsement: ChainsightsEndorsementPredicate = round_trip(json!({
+            "timestamp": "2025-04-20T05:05:22Z",
+            "endorser": {"name": "Audit Co"},
+            "endorsedStatement": {"uri": "https://example.com/my-component@1.0.0.json", "digest": {"sha256": "ABC123"}},
+            "assertion": "audited",
+            "comment": "No findings",
+            "evidenceUri": "https://audit.example/report.pdf"
+        }));
+        assert!(endorsement.endorsed_statement.matches_sha256("abc123"));
+    }
+
+    #[test]
+    fn verification_round_trips() {
+        let verification: ChainsightsVerificationPredicate = round_trip(json!({
+            "verifier": {"id": "https://verifier.example.org", "version": {"chainsights_client": "0.1.0"}},
+            "timeVerified": "2025-04-20T05:05:22Z",
+            "domain": "example.com",
+            "policy": {"uri": "https://verifier.example.org/policy", "digest": {"sha256": "abc123"}},
+            "verificationResult": "PASSED",
+            "results": {
+                "catalog": null,
+                "components": [],
+                "errors": [{"uri": "https://example.com/broken.json", "error": "HTTP Status 404"}]
+            }
+        }));
+        assert_eq!(verification.verification_result, VerificationResult::Passed);
+        assert_eq!(verification.results.errors.len(), 1);
+    }
+
+    #[test]
+    fn baseline_round_trips() {
+        round_trip::<crate::baseline::BaselinePredicate>(json!({
+            "verifiedTimestamp": "2025-04-20T05:05:22Z",
+            "verifier": {"id": "auditor@example.com", "comment": "manual review"},
+            "assessmentComment": "Meets level 1",
+            "controls": [{
+                "control": "OSPS-AC-01",
+                "implemented": true,
+                "evidence": [{"description": "MFA enforced", "uri": "https://example.com/mfa", "media_type": "text/html"}]
+            }]
+        }));
+    }
+
+    #[test]
+    fn upgrades_v1_statements() {
+        let catalog = json!({
+            "timestamp": "2025-04-20T05:05:22Z",
+            "components": [{
+                "name": "My Component",
+                "componentPurl": "pkg:generic/example.com/my-component",
+                "componentAttestationLink": link("https://example.com/my-component.json")
+            }],
+            "subCatalogs": null,
+            "metadataLinks": null
+        });
+        let statement = InTotoStatement::new("https://example.com/catalog.json", HashMap::new(), CATALOG_V1, catalog);
+        match parse_predicate(&statement).unwrap() {
+            ChainsightsPredicate::Catalog(catalog) => {
+                assert_eq!(catalog.components[0].component_purl, "pkg:generic/example.com/my-component")
+            }
+            other => panic!("expected a catalog, got {:?}", other),
+        }
+
+        // Current documents are parsed as they are.
+        let catalog = json!({
+            "timestamp": "2025-04-20T05:05:22Z",
+            "components": [{"name": "x", "purl": "pkg:generic/x", "componentAttestationLink": link("https://example.com/x.json")}],
+            "subCatalogs": null,
+            "metadataLinks": null
+        });
+        let statement = InTotoStatement::new("https://example.com/catalog.json", HashMap::new(), CATALOG_V2, catalog);
+        assert!(parse_predicate(&statement).is_ok());
+    }
+
+    #[test]
+    fn parses_predicates_by_type() {
+        let predicate = json!({"timestamp": "2025-04-20T05:05:22Z", "revocations": []});
+        let statement = InTotoStatement::new("https://example.com/revocations.json", HashMap::new(), REVOCATION_V1, predicate);
+        assert!(matches!(parse_predicate(&statement).unwrap(), ChainsightsPredicate::Revocation(_)));
+
+        let statement = InTotoStatement::new("https://example.com/x.json", HashMap::new(), RELEASE_V1, json!({}));
+        assert!(parse_predicate(&statement).is_err());
+
+        let statement = InTotoStatement::new("https://example.com/x.json", HashMap::new(), "https://slsa.dev/provenance/v1", json!({"a": 1}));
+        match parse_predicate(&statement).unwrap() {
+            ChainsightsPredicate::Unknown { predicate_type, predicate_value } => {
+                assert_eq!(predicate_type, "https://slsa.dev/provenance/v1");
+                assert_eq!(predicate_value, json!({"a": 1}));
+            }
+            other => panic!("expected an unknown predicate, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,296 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{Context, Result};
+use chainsights_core::statement::InTotoStatement;
+use chainsights_core::txt::ChainsightsRecord;
+use chainsights_predicates::{
+    ArtifactLink, AttestationLink, CATALOG_V2, COMPONENT_V2, CatalogComponentEntry, ChainsightsCatalogPredicate,
+    ChainsightsComponentPredicate, ChainsightsReleasePredicate, Generator, RELEASE_V1, RepositoryInfo,
+};
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::signer::EphemeralSigner;
+
+/// Path of the root catalog on the fixture server.
+pub const CATALOG_PATH: &str = "/catalog.json";
+
+/// Describes the tree to generate: the domain, the identity signing every statement, the
+/// components with their release versions, and how many catalog pages list them.
+#[derive(Debug, Clone)]
+pub struct FixtureSpec {
+    /// Domain the tree is published for (e.g., "example.test").
+    pub domain: String,
+    /// Identity every statement is signed by.
+    pub identity: String,
+    /// The components in the catalog.
+    pub components: Vec<ComponentSpec>,
+    /// Number of catalog statements the components are split across, each linking the next
+    /// through `nextPage`. The root catalog is the first page.
+    pub pages: usize,
+}
+
+/// One component of a [`FixtureSpec`].
+#[derive(Debug, Clone)]
+pub struct ComponentSpec {
+    /// Human-readable name; its slug names the component in PURLs and paths.
+    pub name: String,
+    /// Release versions, each with one artifact and an SPDX SBOM.
+    pub versions: Vec<String>,
+}
+
+impl FixtureSpec {
+    /// A spec for `domain`, signed by `identity`, without any components yet.
+    pub fn new(domain: &str, identity: &str) -> Self {
+        Self {
+            domain: domain.to_string(),
+            identity: identity.to_string(),
+            components: Vec::new(),
+            pages: 1,
+        }
+    }
+
+    /// A spec under `example.test` with `components` components named `component-N`, each with
+    /// `releases` releases versioned `1.0.N`. Useful for benchmarks and load tests.
+    pub fn synthetic(components: usize, releases: usize) -> Self {
+        let versions: Vec<String> = (0..releases).map(|n| format!("1.0.{}", n)).collect();
+        let versions: Vec<&str> = versions.iter().map(String::as_str).collect();
+        (0..components).fold(Self::new("example.test", "release@example.test"), |spec, n| {
+            spec.component(&format!("component-{}", n), &versions)
+        })
+    }
+
+    /// Splits the components across `pages` catalog pages (at least one).
+    pub fn pages(mut self, pages: usize) -> Self {
+        self.pages = pages.max(1);
+        self
+    }
+
+    /// Adds a component with the given release versions.
+    pub fn component(mut self, name: &str, versions: &[&str]) -> Self {
+        self.components.push(ComponentSpec {
+            name: name.to_string(),
+            versions: versions.iter().map(|v| v.to_string()).collect(),
+        });
+        self
+    }
+}
+
+impl Default for FixtureSpec {
+    /// Two components under `example.test`: `app` with two releases and `lib` with one.
+    fn default() -> Self {
+        Self::new("example.test", "release@example.test")
+            .component("app", &["1.0.0", "1.1.0"])
+            .component("lib", &["0.1.0"])
+    }
+}
+
+/// A generated and signed tree: every document to serve, keyed by URL path.
+pub struct FixtureTree {
+    /// Bundles, artifacts and SBOMs by path (e.g., "/components/app.json").
+    pub documents: BTreeMap<String, Vec<u8>>,
+    /// URI of the root catalog bundle.
+    pub root_uri: String,
+    /// Value of the domain's `_chainsights` TXT record.
+    pub txt_record: String,
+}
+
+impl FixtureTree {
+    /// Generates the tree for `spec` with every link pointing under `base_url`, signing each
+    /// statement with a fresh ephemeral key.
+    pub fn generate(spec: &FixtureSpec, base_url: &str) -> Result<Self> {
+        let base_url = base_url.trim_end_matches('/');
+        let signer = EphemeralSigner::new(&spec.identity)?;
+        let mut tree = Self {
+            documents: BTreeMap::new(),
+            root_uri: format!("{}{}", base_url, CATALOG_PATH),
+            txt_record: ChainsightsRecord {
+                uri: format!("{}{}", base_url, CATALOG_PATH),
+                identity: spec.identity.clone(),
+                key: None,
+            }
+            .to_string(),
+        };
+        let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        let generator = Some(Generator {
+            purl: format!("pkg:cargo/chainsights_testing@{}", env!("CARGO_PKG_VERSION")),
+        });
+
+        let mut entries = Vec::new();
+        for component in &spec.components {
+            let slug = slugify(&component.name);
+            let component_purl = format!("pkg:generic/{}/{}", spec.domain, slug);
+
+            let mut release_attestations = Vec::new();
+            for version in &component.versions {
+                let artifact_path = format!("/artifacts/{}/{}/{}-{}.tar.gz", slug, version, slug, version);
+                let artifact = format!("{} {} artifact\n", component.name, version).into_bytes();
+                let sbom_path = format!("/artifacts/{}/{}/{}-{}.spdx.json", slug, version, slug, version);
+                let sbom = spdx_sbom(&component.name, version)?;
+
+                let release = ChainsightsReleasePredicate {
+                    generator: generator.clone(),
+                    timestamp: timestamp.clone(),
+                    expires: None,
+                    purl: format!("{}@{}", component_purl, version),
+                    name: version.clone(),
+                    cpe: None,
+                    release_date: Some(timestamp.clone()),
+                    release_notes_uri: None,
+                    lifecycle_phase: Some("stable".to_string()),
+                    status: None,
+                    license: Some("Apache-2.0".to_string()),
+                    supplier: None,
+                    metadata_links: Some(vec![tree.add_unsigned(base_url, &sbom_path, sbom, "application/spdx+json")]),
+                    artifacts: Some(vec![tree.add_unsigned(base_url, &artifact_path, artifact, "application/gzip")]),
+                    artifact_groups: None,
+                };
+                let subject = format!("pkg:chainsights/{}/{}@{}", spec.domain, slug, version);
+                let path = format!("/releases/{}/{}.json", slug, version);
+                release_attestations.push(tree.add_signed(&signer, base_url, &path, &subject, RELEASE_V1, &release)?);
+            }
+
+            let predicate = ChainsightsComponentPredicate {
+                generator: generator.clone(),
+                timestamp: timestamp.clone(),
+                expires: None,
+                purl: component_purl.clone(),
+                name: component.name.clone(),
+                description: Some(format!("Test fixture component {}", component.name)),
+                aliases: None,
+                alias_purls: None,
+                cpe: None,
+                labels: None,
+                security_contact: None,
+                license: Some("Apache-2.0".to_string()),
+                supplier: None,
+                repositories: vec![RepositoryInfo {
+                    repo_type: "git".to_string(),
+                    uri: format!("https://git.{}/{}", spec.domain, slug),
+                    paths: None,
+                    primary_path: None,
+                }],
+                sub_components: None,
+                relationships: None,
+                release_attestations,
+                metadata_links: None,
+                support_streams: None,
+                service: None,
+            };
+            let subject = format!("pkg:chainsights/{}/{}", spec.domain, slug);
+            let path = format!("/components/{}.json", slug);
+            entries.push(CatalogComponentEntry {
+                name: component.name.clone(),
+                description: predicate.description.clone(),
+                component_purl,
+                component_attestation_link: tree.add_signed(&signer, base_url, &path, &subject, COMPONENT_V2, &predicate)?,
+                labels: None,
+            });
+        }
+
+        // Later pages are signed first, so that each page can pin the digest of the next one.
+        let per_page = entries.len().div_ceil(spec.pages.max(1)).max(1);
+        let mut pages: Vec<Vec<CatalogComponentEntry>> = entries.chunks(per_page).map(<[_]>::to_vec).collect();
+        if pages.is_empty() {
+            pages.push(Vec::new());
+        }
+        let subject = format!("pkg:chainsights/{}", spec.domain);
+        let mut next_page = None;
+        for (index, components) in pages.into_iter().enumerate().rev() {
+            let catalog = ChainsightsCatalogPredicate {
+                generator: generator.clone(),
+                timestamp: timestamp.clone(),
+                expires: None,
+                serial: None,
+                components,
+                sub_catalogs: None,
+                metadata_links: None,
+                revocation_link: None,
+                freshness_link: None,
+                security_contact: None,
+                next_page: next_page.take(),
+                component_shards: None,
+            };
+            let path = match index {
+                0 => CATALOG_PATH.to_string(),
+                _ => format!("/catalog/page-{}.json", index + 1),
+            };
+            next_page = Some(tree.add_signed(&signer, base_url, &path, &subject, CATALOG_V2, &catalog)?);
+        }
+        Ok(tree)
+    }
+
+    /// Signs a statement, stores its bundle at `path` and returns a link to it.
+    fn add_signed<T: Serialize>(
+        &mut self,
+        signer: &EphemeralSigner,
+        base_url: &str,
+        path: &str,
+        subject: &str,
+        predicate_type: &str,
+        predicate: &T,
+    ) -> Result<AttestationLink> {
+        let predicate = serde_json::to_value(predicate).context("Failed to serialize predicate")?;
+        let predicate_bytes = serde_json::to_vec(&predicate).context("Failed to serialize predicate")?;
+        let subject_digest = HashMap::from([("sha256".to_string(), sha256_hex(&predicate_bytes))]);
+        let statement = InTotoStatement::new(subject, subject_digest, predicate_type, predicate);
+        let (bundle, payload) = signer.sign(&statement)?;
+        self.documents.insert(path.to_string(), format!("{}\n", bundle).into_bytes());
+        Ok(AttestationLink {
+            uri: format!("{}{}", base_url, path),
+            digest: Some(HashMap::from([("sha256".to_string(), sha256_hex(&payload))])),
+            media_type: Some("application/vnd.dev.sigstore.bundle.v0.3+json".to_string()),
+            expected_signer_identity: signer.identity().to_string(),
+            accepted_signer_identities: None,
+            expected_pgp_fingerprints: None,
+            expected_public_keys: None,
+        })
+    }
+
+    /// Stores an unsigned document at `path` and returns a link to it pinned by its digest.
+    fn add_unsigned(&mut self, base_url: &str, path: &str, bytes: Vec<u8>, media_type: &str) -> ArtifactLink {
+        let link = ArtifactLink {
+            uri: format!("{}{}", base_url, path),
+            digest: Some(HashMap::from([("sha256".to_string(), sha256_hex(&bytes))])),
+            media_type: Some(media_type.to_string()),
+            expected_signer_identity: None,
+            access: None,
+            request_url: None,
+        };
+        self.documents.insert(path.to_string(), bytes);
+        link
+    }
+}
+
+/// A minimal SPDX 2.3 document describing one package.
+fn spdx_sbom(name: &str, version: &str) -> Result<Vec<u8>> {
+    serde_json::to_vec_pretty(&serde_json::json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": format!("{}-{}", name, version),
+        "packages": [{
+            "SPDXID": "SPDXRef-Package",
+            "name": name,
+            "versionInfo": version,
+            "downloadLocation": "NOASSERTION"
+        }]
+    }))
+    .context("Failed to serialize SBOM")
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Turns a display name into a lowercase, dash-separated identifier.
+fn slugify(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
@@ -0,0 +1,258 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use base64::{Engine as _, engine::general_purpose::STANDARD, engine::general_purpose::URL_SAFE_NO_PAD};
+use blake2::{Blake2b512, Digest};
+use chainsights_core::bundle::{DsseEnvelope, SignatureData, construct_pae};
+use ed25519_dalek::{Signer as _, SigningKey};
+use sha2::Sha512;
+
+use crate::signer::IN_TOTO_PAYLOAD_TYPE;
+
+/// The SSHSIG namespace Chainsights requires, as `ssh-keygen -Y sign -n` takes it.
+const SSH_SIGNATURE_NAMESPACE: &str = "chainsights";
+
+/// Signs documents like `ssh-keygen -Y sign -n chainsights` with a freshly generated Ed25519 key.
+pub struct SshSigner {
+    key: SigningKey,
+}
+
+impl Default for SshSigner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SshSigner {
+    pub fn new() -> Self {
+        Self { key: SigningKey::generate(&mut rand::rngs::OsRng) }
+    }
+
+    /// The OpenSSH public key line, as pinned in attestation links.
+    pub fn public_key(&self) -> String {
+        format!("ssh-ed25519 {} fixture@example.test", STANDARD.encode(self.public_key_blob()))
+    }
+
+    /// Makes an armored SSHSIG signature over `document`, hashed with SHA-512.
+    pub fn sign(&self, document: &[u8]) -> String {
+        let digest = Sha512::digest(document);
+        let mut signed = b"SSHSIG".to_vec();
+        for field in [SSH_SIGNATURE_NAMESPACE.as_bytes(), b"", b"sha512", &digest] {
+            put_string(&mut signed, field);
+        }
+        let mut signature = Vec::new();
+        put_string(&mut signature, b"ssh-ed25519");
+        put_string(&mut signature, &self.key.sign(&signed).to_bytes());
+
+        let mut blob = b"SSHSIG".to_vec();
+        blob.extend_from_slice(&1u32.to_be_bytes());
+        for field in [&self.public_key_blob(), SSH_SIGNATURE_NAMESPACE.as_bytes(), b"", b"sha512", &signature] {
+            put_string(&mut blob, field);
+        }
+        let encoded = STANDARD.encode(blob);
+        let lines: Vec<&str> = encoded.as_bytes().chunks(70).map(|line| std::str::from_utf8(line).unwrap()).collect();
+        format!("-----BEGIN SSH SIGNATURE-----\n{}\n-----END SSH SIGNATURE-----\n", lines.join("\n"))
+    }
+
+    fn public_key_blob(&self) -> Vec<u8> {
+        let mut blob = Vec::new();
+        put_string(&mut blob, b"ssh-ed25519");
+        put_string(&mut blob, self.key.verifying_key().as_bytes());
+        blob
+    }
+}
+
+/// Signs documents like `minisign -S` with a freshly generated key: prehashed signatures with a
+/// signed trusted comment.
+pub struct MinisignSigner {
+    key: SigningKey,
+    key_id: [u8; 8],
+}
+
+impl Default for MinisignSigner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MinisignSigner {
+    pub fn new() -> Self {
+        let key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let key_id = Sha512::digest(key.verifying_key().as_bytes())[..8].try_into().unwrap();
+        Self { key, key_id }
+    }
+
+    /// The base64 public key (`RWQ...`), as pinned in attestation links.
+    pub fn public_key(&self) -> String {
+        STANDARD.encode([b"Ed".as_slice(), &self.key_id, self.key.verifying_key().as_bytes()].concat())
+    }
+
+    /// Makes a `.minisig` signature file for `document`.
+    pub fn sign(&self, document: &[u8]) -> String {
+        let signature = self.key.sign(&Blake2b512::digest(document)).to_bytes();
+        let trusted_comment = "timestamp:0\tfile:statement.json\thashed";
+        let global_signature = self.key.sign(&[signature.as_slice(), trusted_comment.as_bytes()].concat());
+        format!(
+            "untrusted comment: signature from minisign secret key\n{}\ntrusted comment: {}\n{}\n",
+            STANDARD.encode([b"ED".as_slice(), &self.key_id, &signature].concat()),
+            trusted_comment,
+            STANDARD.encode(global_signature.to_bytes())
+        )
+    }
+}
+
+/// Signs statements as DSSE envelopes with a freshly generated Ed25519 key, for a `did:key` DID or
+/// a `did:web` DID whose document lists the key.
+pub struct DidSigner {
+    key: SigningKey,
+}
+
+impl Default for DidSigner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DidSigner {
+    pub fn new() -> Self {
+        Self { key: SigningKey::generate(&mut rand::rngs::OsRng) }
+    }
+
+    /// The `did:key` DID of the key (`did:key:z6Mk...`).
+    pub fn did_key(&self) -> String {
+        format!("did:key:z{}", encode_base58(&[&[0xed, 0x01], self.key.verifying_key().as_bytes().as_slice()].concat()))
+    }
+
+    /// A DID document for `did` with the key as its only assertion method, `#key-1`, given as a JWK.
+    pub fn did_document(&self, did: &str) -> String {
+        serde_json::json!({
+            "@context": ["https://www.w3.org/ns/did/v1", "https://w3id.org/security/suites/jws-2020/v1"],
+            "id": did,
+            "verificationMethod": [{
+                "id": format!("{}#key-1", did),
+                "type": "JsonWebKey2020",
+                "controller": did,
+                "publicKeyJwk": {
+                    "kty": "OKP",
+                    "crv": "Ed25519",
+                    "x": URL_SAFE_NO_PAD.encode(self.key.verifying_key().as_bytes()),
+                },
+            }],
+            "assertionMethod": ["#key-1"],
+        })
+        .to_string()
+    }
+
+    /// Makes a DSSE envelope of an in-toto statement, on a single line.
+    pub fn sign(&self, statement: &[u8]) -> String {
+        let signature = self.key.sign(&construct_pae(IN_TOTO_PAYLOAD_TYPE, statement));
+        serde_json::to_string(&DsseEnvelope {
+            payload: STANDARD.encode(statement),
+            payload_type: IN_TOTO_PAYLOAD_TYPE.to_string(),
+            signatures: vec![SignatureData { sig: STANDARD.encode(signature.to_bytes()) }],
+        })
+        .unwrap()
+    }
+}
+
+fn encode_base58(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    // Little-endian digits of the number, in base 58.
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in bytes {
+        let mut carry = byte as usize;
+        for digit in digits.iter_mut() {
+            carry += *digit as usize * 256;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+    std::iter::repeat_n('1', zeros).chain(digits.iter().rev().map(|&digit| ALPHABET[digit as usize] as char)).collect()
+}
+
+fn put_string(buffer: &mut Vec<u8>, value: &[u8]) {
+    buffer.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    buffer.extend_from_slice(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use chainsights_core::did::{DidVerifier, did_document_keys, did_key, did_web_document_url};
+    use chainsights_core::verifier::{PublicKey, Verifier, key_verifier, matches_key_pin, published_key_fingerprints};
+
+    use super::*;
+
+    #[test]
+    fn ssh_and_minisign_signatures_verify_for_pinned_keys_only() {
+        let document = r#"{"_type":"https://in-toto.io/Statement/v1"}"#;
+        let (ssh, minisign) = (SshSigner::new(), MinisignSigner::new());
+
+        let verifier = key_verifier(&[ssh.public_key()]).unwrap();
+        assert_eq!(verifier.signature_uri("https://example.test/x.json", document).unwrap(), "https://example.test/x.json.sig");
+        let verified = verifier.verify(document, Some(&ssh.sign(document.as_bytes()))).unwrap();
+        assert!(verified.signer_identity.starts_with("SHA256:"));
+        assert_eq!(verified.payload, document.as_bytes());
+        assert!(verifier.verify("{}", Some(&ssh.sign(document.as_bytes()))).is_err());
+        assert!(verifier.verify(document, Some(&SshSigner::new().sign(document.as_bytes()))).is_err());
+
+        let verifier = key_verifier(&[minisign.public_key()]).unwrap();
+        let signature = minisign.sign(document.as_bytes());
+        assert!(verifier.verify(document, Some(&signature)).unwrap().signer_identity.starts_with("minisign:"));
+        assert!(verifier.verify("{}", Some(&signature)).is_err());
+        let forged_comment = signature.replace("file:statement.json", "file:other.json");
+        assert!(verifier.verify(document, Some(&forged_comment)).is_err());
+
+        // DNS TXT records carry keys in a compact form.
+        let compact = ssh.public_key().split_whitespace().take(2).collect::<Vec<_>>().join(":");
+        assert_eq!(compact.parse::<PublicKey>().unwrap(), ssh.public_key().parse::<PublicKey>().unwrap());
+        assert!(format!("minisign:{}", minisign.public_key()).parse::<PublicKey>().is_ok());
+        assert!(key_verifier(&[ssh.public_key(), minisign.public_key()]).is_err());
+    }
+
+    #[test]
+    fn published_keys_match_the_pins_of_their_signatures() {
+        let document = "{}";
+        let (ssh, minisign) = (SshSigner::new(), MinisignSigner::new());
+        let ssh_signed = key_verifier(&[ssh.public_key()]).unwrap().verify(document, Some(&ssh.sign(b"{}"))).unwrap();
+        let minisign_signed =
+            key_verifier(&[minisign.public_key()]).unwrap().verify(document, Some(&minisign.sign(b"{}"))).unwrap();
+
+        let published = format!("# release keys\n{}\n", ssh.public_key());
+        let [fingerprint] = published_key_fingerprints(&published).unwrap().try_into().unwrap();
+        assert!(matches_key_pin(&fingerprint, &ssh_signed.key_fingerprints));
+        assert!(!matches_key_pin(&fingerprint, &minisign_signed.key_fingerprints));
+
+        let published = format!("untrusted comment: minisign public key\n{}\n", minisign.public_key());
+        let [key_id] = published_key_fingerprints(&published).unwrap().try_into().unwrap();
+        assert!(matches_key_pin(&format!("minisign:{}", key_id.to_lowercase()), &minisign_signed.key_fingerprints));
+        assert!(published_key_fingerprints("").is_err());
+    }
+
+    #[test]
+    fn dsse_envelopes_verify_against_did_keys() {
+        let statement = br#"{"_type":"https://in-toto.io/Statement/v1"}"#;
+        let signer = DidSigner::new();
+        let did = signer.did_key();
+        assert!(did.starts_with("did:key:z6Mk"), "{}", did);
+
+        let verified = DidVerifier::new(vec![did_key(&did).unwrap()]).verify(&signer.sign(statement), None).unwrap();
+        assert_eq!(verified.payload, statement);
+        assert!(verified.signer_identity.starts_with(&format!("{}#", did)));
+        assert!(verified.key_fingerprints[0].starts_with("SHA256:"));
+        let other = DidVerifier::new(vec![did_key(&DidSigner::new().did_key()).unwrap()]);
+        assert!(other.verify(&signer.sign(statement), None).is_err());
+
+        let did = "did:web:example.test%3A8443:teams:release";
+        assert_eq!(did_web_document_url(did).unwrap(), "https://example.test:8443/teams/release/did.json");
+        assert_eq!(did_web_document_url("did:web:example.test").unwrap(), "https://example.test/.well-known/did.json");
+        let keys = did_document_keys(did, &signer.did_document(did)).unwrap();
+        assert_eq!(keys[0].id, format!("{}#key-1", did));
+        let verified = DidVerifier::new(keys).verify(&signer.sign(statement), None).unwrap();
+        assert_eq!(verified.signer_identity, format!("{}#key-1", did));
+        assert!(did_document_keys("did:web:other.test", &signer.did_document(did)).is_err());
+    }
+}
@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fixtures and in-process mocks for end-to-end tests of Chainsights traversal and verification.
+//!
+//! [`FixtureTree`] generates a complete catalog (components, releases, artifacts and SBOMs)
+//! signed with an ephemeral key, and [`MockEnvironment`] serves it from a local HTTP server along
+//! with the `_chainsights` TXT records a stub resolver should answer with. Nothing touches the
+//! network or the public Sigstore infrastructure; [`PrivateSigstore`] stands in for a private
+//! instance where verification against a trusted root is under test, [`PgpSigner`] for
+//! PGP-signed statements, [`SshSigner`] and [`MinisignSigner`] for statements signed with
+//! plain public keys, and [`DidSigner`] for DSSE envelopes signed by a DID's key.
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! use chainsights_testing::{FixtureSpec, MockEnvironment};
+//!
+//! let env = MockEnvironment::start(FixtureSpec::default()).await?;
+//! println!("serving {} signed by {}", env.root_uri(), env.identity());
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod fixture;
+pub mod keys;
+pub mod pgp;
+pub mod server;
+pub mod signer;
+pub mod sigstore;
+
+pub use fixture::{ComponentSpec, FixtureSpec, FixtureTree};
+pub use keys::{DidSigner, MinisignSigner, SshSigner};
+pub use pgp::PgpSigner;
+pub use server::MockEnvironment;
+pub use signer::{EphemeralSigner, FIXTURE_ISSUER};
+pub use sigstore::PrivateSigstore;
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, anyhow};
+use chainsights_core::statement::InTotoStatement;
+use chrono::SubsecRound;
+use pgp::cleartext::CleartextSignedMessage;
+use pgp::crypto::hash::HashAlgorithm;
+use pgp::packet::{SignatureConfig, SignatureType, Subpacket, SubpacketData};
+use pgp::types::{PublicKeyTrait, SecretKeyTrait};
+use pgp::{KeyType, SecretKeyParamsBuilder, SignedSecretKey, StandaloneSignature};
+
+/// Signs statements with a freshly generated Ed25519 PGP key, as distributions and Apache
+/// projects sign with their release keys.
+pub struct PgpSigner {
+    key: SignedSecretKey,
+}
+
+impl PgpSigner {
+    /// Generates a signing key for `user_id` (e.g., `Release Manager <release@example.test>`).
+    pub fn new(user_id: &str) -> Result<Self> {
+        let mut rng = rand::thread_rng();
+        let params = SecretKeyParamsBuilder::default()
+            .key_type(KeyType::EdDSALegacy)
+            .can_certify(true)
+            .can_sign(true)
+            .primary_user_id(user_id.to_string())
+            .passphrase(None)
+            .build()
+            .context("Failed to build PGP key parameters")?;
+        let key = params
+            .generate(&mut rng)
+            .and_then(|key| key.sign(&mut rng, String::new))
+            .map_err(|e| anyhow!("Failed to generate a PGP key: {}", e))?;
+        Ok(Self { key })
+    }
+
+    /// Uppercase hex fingerprint of the key, as pinned in attestation links.
+    pub fn fingerprint(&self) -> String {
+        self.key.fingerprint().as_bytes().iter().map(|b| format!("{:02X}", b)).collect()
+    }
+
+    /// The armored public key, for a keyring.
+    pub fn armored_public_key(&self) -> Result<String> {
+        let public = self
+            .key
+            .public_key()
+            .sign(rand::thread_rng(), &self.key, String::new)
+            .map_err(|e| anyhow!("Failed to sign the PGP public key: {}", e))?;
+        public
+            .to_armored_string(Default::default())
+            .map_err(|e| anyhow!("Failed to armor the PGP public key: {}", e))
+    }
+
+    /// Serializes the statement and signs it as a cleartext-signed message.
+    pub fn sign_cleartext(&self, statement: &InTotoStatement) -> Result<String> {
+        let text = serde_json::to_string_pretty(statement).context("Failed to serialize statement")?;
+        CleartextSignedMessage::sign(rand::thread_rng(), &text, &self.key, String::new)
+            .and_then(|message| message.to_armored_string(Default::default()))
+            .map_err(|e| anyhow!("Failed to sign cleartext message: {}", e))
+    }
+
+    /// Makes an armored detached signature over `document`.
+    pub fn sign_detached(&self, document: &[u8]) -> Result<String> {
+        let mut config = SignatureConfig::v4(SignatureType::Binary, self.key.algorithm(), HashAlgorithm::SHA2_256);
+        config.hashed_subpackets = vec![
+            Subpacket::regular(SubpacketData::IssuerFingerprint(self.key.fingerprint())),
+            Subpacket::regular(SubpacketData::SignatureCreationTime(chrono::Utc::now().trunc_subsecs(0))),
+        ];
+        config.unhashed_subpackets = vec![Subpacket::regular(SubpacketData::Issuer(self.key.key_id()))];
+        config
+            .sign(&self.key, String::new, document)
+            .and_then(|signature| StandaloneSignature::new(signature).to_armored_string(Default::default()))
+            .map_err(|e| anyhow!("Failed to sign detached signature: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chainsights_core::pgp::{PgpKeyring, is_pgp_cleartext, verify_pgp_cleartext, verify_pgp_detached};
+
+    use super::*;
+
+    #[test]
+    fn cleartext_and_detached_signatures_verify_for_pinned_keys_only() {
+        let signer = PgpSigner::new("Release Manager <release@example.test>").unwrap();
+        let other = PgpSigner::new("Someone Else <other@example.test>").unwrap();
+        let keyring = PgpKeyring::from_armored(&format!(
+            "pub ed25519 Release Manager\n{}\npub ed25519 Someone Else\n{}",
+            signer.armored_public_key().unwrap(),
+            other.armored_public_key().unwrap()
+        ))
+        .unwrap();
+        assert_eq!(keyring.fingerprints(), [signer.fingerprint(), other.fingerprint()]);
+        let pinned = [signer.fingerprint().to_lowercase()];
+
+        let statement = InTotoStatement::new("https://example.test/x.json", HashMap::new(), "https://example.test/v1", serde_json::json!({"a": 1}));
+        let cleartext = signer.sign_cleartext(&statement).unwrap();
+        assert!(is_pgp_cleartext(&cleartext));
+        let verified = verify_pgp_cleartext(&cleartext, &keyring, &pinned).unwrap();
+        assert_eq!(verified.signer_identity, signer.fingerprint());
+        let parsed: serde_json::Value = serde_json::from_slice(&verified.payload).unwrap();
+        assert_eq!(parsed["predicate"]["a"], 1);
+        assert!(verify_pgp_cleartext(&cleartext.replace("\"a\": 1", "\"a\": 2"), &keyring, &pinned).is_err());
+
+        let document = serde_json::to_string(&statement).unwrap();
+        let signature = signer.sign_detached(document.as_bytes()).unwrap();
+        assert!(verify_pgp_detached(&document, &signature, &keyring, &pinned).is_ok());
+        assert!(verify_pgp_detached("{}", &signature, &keyring, &pinned).is_err());
+
+        // A key in the keyring that the link doesn't pin is not accepted.
+        let signature = other.sign_detached(document.as_bytes()).unwrap();
+        assert!(verify_pgp_detached(&document, &signature, &keyring, &pinned).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use anyhow::{Result, bail};
+use wiremock::matchers::{method, path as path_matcher};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::fixture::{FixtureSpec, FixtureTree};
+
+/// A fixture tree served from an in-process HTTP server, together with the TXT records a stub
+/// resolver needs to discover it. The server shuts down when the environment is dropped.
+pub struct MockEnvironment {
+    server: MockServer,
+    spec: FixtureSpec,
+    tree: FixtureTree,
+}
+
+impl MockEnvironment {
+    /// Generates the tree for `spec` and serves every document on a local port.
+    pub async fn start(spec: FixtureSpec) -> Result<Self> {
+        let server = MockServer::start().await;
+        let tree = FixtureTree::generate(&spec, &server.uri())?;
+        for (path, body) in &tree.documents {
+            Mock::given(method("GET"))
+                .and(path_matcher(path.as_str()))
+                .respond_with(ResponseTemplate::new(200).set_body_bytes(body.clone()))
+                .mount(&server)
+                .await;
+        }
+        Ok(Self { server, spec, tree })
+    }
+
+    /// Base URL of the server, without a trailing slash.
+    pub fn base_url(&self) -> String {
+        self.server.uri()
+    }
+
+    /// URI of the root catalog bundle.
+    pub fn root_uri(&self) -> &str {
+        &self.tree.root_uri
+    }
+
+    /// The domain the tree is published for.
+    pub fn domain(&self) -> &str {
+        &self.spec.domain
+    }
+
+    /// The identity every statement is signed by.
+    pub fn identity(&self) -> &str {
+        &self.spec.identity
+    }
+
+    /// The full URI of a served path.
+    pub fn uri(&self, path: &str) -> String {
+        format!("{}{}", self.server.uri(), path)
+    }
+
+    /// The document originally generated for `path`.
+    pub fn document(&self, path: &str) -> Option<&[u8]> {
+        self.tree.documents.get(path).map(Vec::as_slice)
+    }
+
+    /// TXT records announcing the tree, keyed by record name, for a stub resolver.
+    pub fn txt_records(&self) -> HashMap<String, Vec<String>> {
+        HashMap::from([(format!("_chainsights.{}", self.spec.domain), vec![self.tree.txt_record.clone()])])
+    }
+
+    /// Serves `body` at `path` from now on, e.g. to simulate a tampered or replaced document.
+    pub async fn replace(&self, path: &str, body: impl Into<Vec<u8>>) -> Result<()> {
+        if !self.tree.documents.contains_key(path) {
+            bail!("No fixture document at '{}'", path);
+        }
+        self.serve(path, body).await;
+        Ok(())
+    }
+
+    /// Serves `body` at `path` from now on, whether or not the fixture has a document there, e.g.
+    /// a detached signature next to a statement.
+    pub async fn serve(&self, path: &str, body: impl Into<Vec<u8>>) {
+        Mock::given(method("GET"))
+            .and(path_matcher(path))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(body.into()))
+            .with_priority(1)
+            .mount(&self.server)
+            .await;
+    }
+}
@@ -0,0 +1,239 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chainsights_core::bundle::{
+    CertificateData, DsseEnvelope, SignatureData, SigstoreBundleData, VerificationMaterial, construct_pae,
+};
+use chainsights_core::identity::FulcioExtensions;
+use chainsights_core::statement::InTotoStatement;
+use chrono::{Datelike, Duration, Utc};
+use p256::ecdsa::SigningKey;
+use p256::ecdsa::signature::Signer as _;
+use p256::pkcs8::DecodePrivateKey;
+use rcgen::{CertificateParams, CustomExtension, KeyPair, SanType, date_time_ymd};
+
+/// Payload type of the DSSE envelopes Chainsights statements are signed in.
+pub(crate) const IN_TOTO_PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+/// OIDC issuer named in every ephemeral certificate, as Fulcio would name the identity provider.
+pub const FIXTURE_ISSUER: &str = "https://issuer.example.test";
+
+/// Fulcio's certificate extensions (1.3.6.1.4.1.57264.1.*), each holding a DER UTF8String.
+const OID_FULCIO_ISSUER_V2: &[u64] = &[1, 3, 6, 1, 4, 1, 57264, 1, 8];
+const OID_FULCIO_BUILD_SIGNER_URI: &[u64] = &[1, 3, 6, 1, 4, 1, 57264, 1, 9];
+const OID_FULCIO_RUNNER_ENVIRONMENT: &[u64] = &[1, 3, 6, 1, 4, 1, 57264, 1, 11];
+const OID_FULCIO_SOURCE_REPOSITORY_URI: &[u64] = &[1, 3, 6, 1, 4, 1, 57264, 1, 12];
+const OID_FULCIO_SOURCE_REPOSITORY_DIGEST: &[u64] = &[1, 3, 6, 1, 4, 1, 57264, 1, 13];
+const OID_FULCIO_SOURCE_REPOSITORY_REF: &[u64] = &[1, 3, 6, 1, 4, 1, 57264, 1, 14];
+const OID_FULCIO_BUILD_TRIGGER: &[u64] = &[1, 3, 6, 1, 4, 1, 57264, 1, 20];
+
+/// Signs statements like a Fulcio-issued identity would: an ephemeral P-256 key with a
+/// self-signed certificate naming the identity as a SAN and [`FIXTURE_ISSUER`] as the OIDC
+/// issuer.
+pub struct EphemeralSigner {
+    identity: String,
+    key: SigningKey,
+    certificate_der: Vec<u8>,
+}
+
+impl EphemeralSigner {
+    /// Generates a key and a certificate for `identity`, valid from yesterday until the day
+    /// after tomorrow. Identities containing `://` are URI SANs, as for CI workflows; others are
+    /// email SANs.
+    pub fn new(identity: &str) -> Result<Self> {
+        Self::with_extensions(identity, &FulcioExtensions::default())
+    }
+
+    /// Like [`EphemeralSigner::new`], with the CI build context in `extensions` recorded in the
+    /// certificate. The issuer defaults to [`FIXTURE_ISSUER`].
+    pub fn with_extensions(identity: &str, extensions: &FulcioExtensions) -> Result<Self> {
+        let key_pair = KeyPair::generate().context("Failed to generate an ephemeral key")?;
+        let certificate = certificate_params(identity, extensions)?
+            .self_signed(&key_pair)
+            .context("Failed to issue the ephemeral certificate")?;
+        Self::from_certificate(identity, &key_pair, certificate.der().to_vec())
+    }
+
+    /// A signer for a certificate issued elsewhere to `key_pair`.
+    pub(crate) fn from_certificate(identity: &str, key_pair: &KeyPair, certificate_der: Vec<u8>) -> Result<Self> {
+        let key = SigningKey::from_pkcs8_der(&key_pair.serialize_der())
+            .context("Failed to load the ephemeral key for signing")?;
+        Ok(Self {
+            identity: identity.to_string(),
+            key,
+            certificate_der,
+        })
+    }
+
+    /// The identity named in the certificate.
+    pub fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    /// The signing certificate in PEM, e.g. to publish it for a DNS key pin.
+    pub fn certificate_pem(&self) -> String {
+        certificate_pem(&self.certificate_der)
+    }
+
+    /// Signs a statement and returns the Sigstore bundle JSON on a single line, together with
+    /// the serialized statement (the DSSE payload).
+    pub fn sign(&self, statement: &InTotoStatement) -> Result<(String, Vec<u8>)> {
+        let payload = serde_json::to_vec(statement).context("Failed to serialize statement")?;
+        let signature: p256::ecdsa::Signature = self.key.sign(&construct_pae(IN_TOTO_PAYLOAD_TYPE, &payload));
+        let bundle = SigstoreBundleData {
+            verification_material: VerificationMaterial {
+                certificate: CertificateData {
+                    raw_bytes: STANDARD.encode(&self.certificate_der),
+                },
+                tlog_entries: Vec::new(),
+                timestamp_verification_data: None,
+            },
+            dsse_envelope: DsseEnvelope {
+                payload: STANDARD.encode(&payload),
+                payload_type: IN_TOTO_PAYLOAD_TYPE.to_string(),
+                signatures: vec![SignatureData {
+                    sig: STANDARD.encode(signature.to_der().as_bytes()),
+                }],
+            },
+        };
+        let bundle = serde_json::to_string(&bundle).context("Failed to serialize bundle")?;
+        Ok((bundle, payload))
+    }
+}
+
+/// A DER certificate in PEM.
+pub(crate) fn certificate_pem(certificate_der: &[u8]) -> String {
+    let encoded = STANDARD.encode(certificate_der);
+    let lines: Vec<&str> = encoded.as_bytes().chunks(64).map(|line| std::str::from_utf8(line).unwrap()).collect();
+    format!("-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n", lines.join("\n"))
+}
+
+/// Parameters for a certificate like Fulcio issues: `identity` as the only SAN, valid from
+/// yesterday until the day after tomorrow, with `extensions` recorded.
+pub(crate) fn certificate_params(identity: &str, extensions: &FulcioExtensions) -> Result<CertificateParams> {
+    let mut params = CertificateParams::default();
+    let invalid = || format!("Invalid signer identity '{}'", identity);
+    params.subject_alt_names = vec![match identity.contains("://") {
+        true => SanType::URI(identity.try_into().with_context(invalid)?),
+        false => SanType::Rfc822Name(identity.try_into().with_context(invalid)?),
+    }];
+    let not_before = Utc::now() - Duration::days(1);
+    let not_after = Utc::now() + Duration::days(2);
+    params.not_before = date_time_ymd(not_before.year(), not_before.month() as u8, not_before.day() as u8);
+    params.not_after = date_time_ymd(not_after.year(), not_after.month() as u8, not_after.day() as u8);
+    let issuer = extensions.issuer.as_deref().unwrap_or(FIXTURE_ISSUER);
+    params.custom_extensions = [
+        (OID_FULCIO_ISSUER_V2, Some(issuer)),
+        (OID_FULCIO_BUILD_SIGNER_URI, extensions.build_signer_uri.as_deref()),
+        (OID_FULCIO_RUNNER_ENVIRONMENT, extensions.runner_environment.as_deref()),
+        (OID_FULCIO_SOURCE_REPOSITORY_URI, extensions.source_repository_uri.as_deref()),
+        (OID_FULCIO_SOURCE_REPOSITORY_DIGEST, extensions.source_repository_digest.as_deref()),
+        (OID_FULCIO_SOURCE_REPOSITORY_REF, extensions.source_repository_ref.as_deref()),
+        (OID_FULCIO_BUILD_TRIGGER, extensions.build_trigger.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(oid, value)| Some(CustomExtension::from_oid_content(oid, der_utf8_string(value?))))
+    .collect();
+    Ok(params)
+}
+
+/// Encodes `value` as a DER UTF8String.
+fn der_utf8_string(value: &str) -> Vec<u8> {
+    der_encode(0x0c, value.as_bytes())
+}
+
+/// DER-encodes an element with a single-byte tag and up to 64 KiB of content.
+pub(crate) fn der_encode(tag: u8, content: &[u8]) -> Vec<u8> {
+    let len = content.len();
+    let mut der = vec![tag];
+    match len {
+        0..=0x7f => der.push(len as u8),
+        0x80..=0xff => der.extend([0x81, len as u8]),
+        _ => der.extend([0x82, (len >> 8) as u8, len as u8]),
+    }
+    der.extend_from_slice(content);
+    der
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chainsights_core::identity::{BuildPolicy, SignerIdentity};
+    use chainsights_core::timestamp::TimeSource;
+    use chainsights_core::verify::{decode_bundle_unverified, verify_signature_with_pae};
+
+    use super::*;
+
+    #[test]
+    fn signed_bundles_verify() {
+        let signer = EphemeralSigner::new("release@example.test").unwrap();
+        let statement = InTotoStatement::new("https://example.test/x.json", HashMap::new(), "https://example.test/v1", serde_json::json!({"a": 1}));
+        let (bundle, payload) = signer.sign(&statement).unwrap();
+
+        let verified =
+            verify_signature_with_pae(&bundle, &[SignerIdentity::new("release@example.test")], TimeSource::Local, Duration::minutes(5))
+                .unwrap();
+        assert_eq!(verified.payload, payload);
+        assert_eq!(verified.signer_identity, "release@example.test");
+        assert_eq!(verified.extensions.issuer.as_deref(), Some(FIXTURE_ISSUER));
+
+        let wrong_identity =
+            verify_signature_with_pae(&bundle, &[SignerIdentity::new("other@example.test")], TimeSource::Local, Duration::minutes(5));
+        assert!(wrong_identity.is_err());
+    }
+
+    #[test]
+    fn unverified_decoding_reads_certificate_and_payload() {
+        let signer = EphemeralSigner::new("release@example.test").unwrap();
+        let statement = InTotoStatement::new("https://example.test/x.json", HashMap::new(), "https://example.test/v1", serde_json::json!({"a": 1}));
+        let (bundle, payload) = signer.sign(&statement).unwrap();
+
+        let decoded = decode_bundle_unverified(&bundle).unwrap();
+        assert_eq!(decoded.payload, payload);
+        assert_eq!(decoded.signatures, 1);
+        assert_eq!(decoded.identities, ["release@example.test"]);
+        assert_eq!(decoded.extensions.issuer.as_deref(), Some(FIXTURE_ISSUER));
+        assert!(decoded.validity.is_some());
+        assert!(decoded.certificate_error.is_none());
+    }
+
+    #[test]
+    fn workflow_certificates_carry_the_build_context() {
+        let workflow = "https://github.com/example/app/.github/workflows/release.yml@refs/tags/v1.2.0";
+        let build = FulcioExtensions {
+            issuer: Some("https://token.actions.githubusercontent.com".to_string()),
+            source_repository_uri: Some("https://github.com/example/app".to_string()),
+            source_repository_ref: Some("refs/tags/v1.2.0".to_string()),
+            build_signer_uri: Some(workflow.to_string()),
+            build_trigger: Some("push".to_string()),
+            ..Default::default()
+        };
+        let signer = EphemeralSigner::with_extensions(workflow, &build).unwrap();
+        let statement = InTotoStatement::new("https://example.test/x.json", HashMap::new(), "https://example.test/v1", serde_json::json!({"a": 1}));
+        let (bundle, _) = signer.sign(&statement).unwrap();
+
+        let verified = verify_signature_with_pae(&bundle, &[SignerIdentity::new(workflow)], TimeSource::Local, Duration::minutes(5)).unwrap();
+        assert_eq!(verified.signer_identity, workflow);
+        assert_eq!(verified.extensions, build);
+
+        // Refs and paths are case-sensitive, unlike the scheme and host.
+        let verify = |identity: &str| verify_signature_with_pae(&bundle, &[SignerIdentity::new(identity)], TimeSource::Local, Duration::minutes(5));
+        assert!(verify("HTTPS://GitHub.com/example/app/.github/workflows/release.yml@refs/tags/v1.2.0").is_ok());
+        assert!(verify("https://github.com/example/app/.github/workflows/release.yml@refs/tags/V1.2.0").is_err());
+        let branch = "https://github.com/example/app/.github/workflows/release.yml@refs/heads/main";
+        let (bundle, _) = EphemeralSigner::new(&branch.replace("main", "MAIN")).unwrap().sign(&statement).unwrap();
+        assert!(verify_signature_with_pae(&bundle, &[SignerIdentity::new(branch)], TimeSource::Local, Duration::minutes(5)).is_err());
+
+        let policy = |source_ref: &str| BuildPolicy {
+            source_repository: Some("https://github.com/example/app".to_string()),
+            source_ref: Some(source_ref.to_string()),
+            build_trigger: Some("push".to_string()),
+        };
+        assert!(policy("refs/tags/*").check(&verified.extensions).is_ok());
+        assert!(policy("refs/tags/v1.*.*").check(&verified.extensions).is_ok());
+        assert!(policy("refs/heads/*").check(&verified.extensions).is_err());
+        assert!(policy("refs/tags/*").check(&FulcioExtensions::default()).is_err());
+    }
+}
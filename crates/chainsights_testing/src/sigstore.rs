@@ -0,0 +1,433 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chainsights_core::bundle::{
+    CertificateData, InclusionPromise, KindVersion, LogId, Rfc3161Timestamp, SigstoreBundleData, TimestampVerificationData,
+    TlogEntry,
+};
+use chainsights_core::identity::FulcioExtensions;
+use chainsights_core::trust::{
+    CertificateAuthority, CertificateChain, TRUSTED_ROOT_MEDIA_TYPE, TransparencyLogInstance, TrustedPublicKey,
+    TrustedRoot,
+};
+use chrono::{DateTime, Utc};
+use p256::ecdsa::SigningKey;
+use p256::ecdsa::signature::Signer as _;
+use p256::pkcs8::DecodePrivateKey;
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams, CustomExtension, DnType, IsCa, KeyPair, PublicKeyData, SubjectPublicKeyInfo,
+};
+use sha2::{Digest, Sha256};
+
+use crate::signer::{EphemeralSigner, certificate_params, certificate_pem, der_encode};
+
+/// Certificate extension holding a TLS-encoded list of signed certificate timestamps.
+const OID_CT_SCT_LIST: &[u64] = &[1, 3, 6, 1, 4, 1, 11129, 2, 4, 2];
+
+/// DER-encoded OIDs of the RFC 3161 timestamp tokens the instance's timestamp authority issues.
+const OID_SIGNED_DATA: &[u8] = &[0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02];
+const OID_TST_INFO: &[u8] = &[0x06, 0x0b, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x10, 0x01, 0x04];
+const OID_MESSAGE_DIGEST: &[u8] = &[0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x04];
+const OID_SHA256: &[u8] = &[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+const OID_ECDSA_SHA256: &[u8] = &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+const OID_TEST_POLICY: &[u8] = &[0x06, 0x03, 0x2a, 0x03, 0x04];
+
+/// A private Sigstore instance, as sigstore-scaffolding deploys: a certificate authority standing
+/// in for Fulcio, the keys of a Rekor log and a CT log, and an RFC 3161 timestamp authority.
+/// [`PrivateSigstore::trusted_root`] describes it to verifiers.
+pub struct PrivateSigstore {
+    ca_key: KeyPair,
+    ca_certificate: Certificate,
+    rekor_key: KeyPair,
+    ct_key: KeyPair,
+    tsa_key: KeyPair,
+    tsa_certificate: Certificate,
+}
+
+impl PrivateSigstore {
+    /// Generates the instance's keys and its self-signed root certificate.
+    pub fn new() -> Result<Self> {
+        let ca_key = KeyPair::generate().context("Failed to generate the CA key")?;
+        let mut params = CertificateParams::default();
+        params.distinguished_name.push(DnType::CommonName, "chainsights-test-fulcio");
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca_certificate = params.self_signed(&ca_key).context("Failed to issue the CA certificate")?;
+        let tsa_key = KeyPair::generate().context("Failed to generate the timestamp authority key")?;
+        let mut params = CertificateParams::default();
+        params.distinguished_name.push(DnType::CommonName, "chainsights-test-tsa");
+        let tsa_certificate = params.self_signed(&tsa_key).context("Failed to issue the timestamp authority certificate")?;
+        Ok(Self {
+            ca_key,
+            ca_certificate,
+            rekor_key: KeyPair::generate().context("Failed to generate the Rekor key")?,
+            ct_key: KeyPair::generate().context("Failed to generate the CT log key")?,
+            tsa_key,
+            tsa_certificate,
+        })
+    }
+
+    /// Issues a certificate for `identity` from the instance's CA, with a signed certificate
+    /// timestamp from its CT log embedded as Fulcio does.
+    pub fn signer(&self, identity: &str) -> Result<EphemeralSigner> {
+        let key_pair = KeyPair::generate().context("Failed to generate an ephemeral key")?;
+        let certificate = self.issue(identity, &key_pair)?;
+        EphemeralSigner::from_certificate(identity, &key_pair, certificate.der().to_vec())
+    }
+
+    /// Issues a certificate for `identity` to a key held elsewhere, given as SubjectPublicKeyInfo
+    /// PEM, as Fulcio answers a signing certificate request. Returns the certificate as PEM.
+    pub fn certify(&self, identity: &str, public_key_pem: &str) -> Result<String> {
+        let public_key = SubjectPublicKeyInfo::from_pem(public_key_pem).context("Failed to parse the public key")?;
+        Ok(self.issue(identity, &public_key)?.pem())
+    }
+
+    fn issue(&self, identity: &str, public_key: &impl PublicKeyData) -> Result<Certificate> {
+        let mut params = certificate_params(identity, &FulcioExtensions::default())?;
+
+        // The CT log signs the certificate as it is before the SCT is embedded.
+        let precertificate = params
+            .clone()
+            .signed_by(public_key, &self.ca_certificate, &self.ca_key)
+            .context("Failed to issue the precertificate")?;
+        let sct = self.sign_certificate_timestamp(tbs_certificate(precertificate.der())?)?;
+        let mut list = (sct.len() as u16).to_be_bytes().to_vec();
+        list.extend_from_slice(&sct);
+        let mut list_with_length = (list.len() as u16).to_be_bytes().to_vec();
+        list_with_length.extend_from_slice(&list);
+        params
+            .custom_extensions
+            .push(CustomExtension::from_oid_content(OID_CT_SCT_LIST, der_encode(0x04, &list_with_length)));
+
+        params
+            .signed_by(public_key, &self.ca_certificate, &self.ca_key)
+            .context("Failed to issue the certificate")
+    }
+
+    /// Records a bundle in the instance's Rekor log: adds a `dsse` tlog entry, integrated now, with
+    /// a signed entry timestamp.
+    pub fn log(&self, bundle_json: &str) -> Result<String> {
+        self.log_at(bundle_json, Utc::now())
+    }
+
+    /// Like [`PrivateSigstore::log`], but integrated at `integrated_time`, e.g. to stand in for an
+    /// entry logged long ago.
+    pub fn log_at(&self, bundle_json: &str, integrated_time: DateTime<Utc>) -> Result<String> {
+        let mut bundle: SigstoreBundleData = serde_json::from_str(bundle_json).context("Failed to parse bundle JSON")?;
+        let log_id = Sha256::digest(self.rekor_key.public_key_der());
+        let integrated_time = integrated_time.timestamp();
+        let envelope = &bundle.dsse_envelope;
+        let payload = STANDARD.decode(&envelope.payload).context("Failed to decode the bundle payload")?;
+        let certificate = STANDARD
+            .decode(&bundle.verification_material.certificate.raw_bytes)
+            .context("Failed to decode the bundle certificate")?;
+        let sha256 = |bytes: &[u8]| serde_json::json!({ "algorithm": "sha256", "value": hex(&Sha256::digest(bytes)) });
+        // Keys of serde_json maps are sorted, so this is the canonical JSON Rekor stores.
+        let body = serde_json::json!({
+            "apiVersion": "0.0.1",
+            "kind": "dsse",
+            "spec": {
+                "envelopeHash": sha256(&serde_json::to_vec(envelope)?),
+                "payloadHash": sha256(&payload),
+                "signatures": envelope
+                    .signatures
+                    .iter()
+                    .map(|signature| serde_json::json!({ "signature": signature.sig, "verifier": STANDARD.encode(certificate_pem(&certificate)) }))
+                    .collect::<Vec<_>>(),
+            },
+        });
+        let body = STANDARD.encode(serde_json::to_vec(&body)?);
+        let signed = format!(
+            r#"{{"body":"{}","integratedTime":{},"logID":"{}","logIndex":0}}"#,
+            body,
+            integrated_time,
+            hex(&log_id)
+        );
+        let signature: p256::ecdsa::Signature = signing_key(&self.rekor_key)?.sign(signed.as_bytes());
+        bundle.verification_material.tlog_entries = vec![TlogEntry {
+            log_index: "0".to_string(),
+            log_id: LogId { key_id: STANDARD.encode(log_id) },
+            kind_version: KindVersion { kind: "dsse".to_string(), version: "0.0.1".to_string() },
+            integrated_time: integrated_time.to_string(),
+            inclusion_promise: Some(InclusionPromise {
+                signed_entry_timestamp: STANDARD.encode(signature.to_der().as_bytes()),
+            }),
+            inclusion_proof: None,
+            canonicalized_body: body,
+        }];
+        serde_json::to_string(&bundle).context("Failed to serialize bundle")
+    }
+
+    /// Timestamps a bundle's signature with the instance's timestamp authority: adds an RFC 3161
+    /// timestamp token, generated now, to its timestamp verification data.
+    pub fn timestamp(&self, bundle_json: &str) -> Result<String> {
+        let mut bundle: SigstoreBundleData = serde_json::from_str(bundle_json).context("Failed to parse bundle JSON")?;
+        let signature = bundle.dsse_envelope.signatures.first().context("Bundle has no signature")?;
+        let signature = STANDARD.decode(&signature.sig).context("Failed to decode the bundle signature")?;
+
+        let sha256 = der_encode(0x30, OID_SHA256);
+        let imprint = der_encode(0x30, &[sha256.clone(), der_encode(0x04, &Sha256::digest(&signature))].concat());
+        let gen_time = Utc::now().format("%Y%m%d%H%M%SZ").to_string();
+        let tst_info = der_encode(0x30, &[
+            der_encode(0x02, &[1]),
+            OID_TEST_POLICY.to_vec(),
+            imprint,
+            der_encode(0x02, &[1]),
+            der_encode(0x18, gen_time.as_bytes()),
+        ].concat());
+        let encap = der_encode(0x30, &[OID_TST_INFO.to_vec(), der_encode(0xa0, &der_encode(0x04, &tst_info))].concat());
+        let attributes = der_encode(0x30, &[
+            OID_MESSAGE_DIGEST.to_vec(),
+            der_encode(0x31, &der_encode(0x04, &Sha256::digest(&tst_info))),
+        ].concat());
+        // The signed attributes are signed as a SET, and carried with an implicit [0] tag.
+        let token_signature: p256::ecdsa::Signature = signing_key(&self.tsa_key)?.sign(&der_encode(0x31, &attributes));
+        let signer_info = der_encode(0x30, &[
+            der_encode(0x02, &[1]),
+            der_encode(0x80, &Sha256::digest(self.tsa_key.public_key_der())[..20]),
+            sha256.clone(),
+            der_encode(0xa0, &attributes),
+            der_encode(0x30, OID_ECDSA_SHA256),
+            der_encode(0x04, token_signature.to_der().as_bytes()),
+        ].concat());
+        let signed_data = der_encode(0x30, &[
+            der_encode(0x02, &[3]),
+            der_encode(0x31, &sha256),
+            encap,
+            der_encode(0x31, &signer_info),
+        ].concat());
+        let token = der_encode(0x30, &[OID_SIGNED_DATA.to_vec(), der_encode(0xa0, &signed_data)].concat());
+
+        bundle.verification_material.timestamp_verification_data = Some(TimestampVerificationData {
+            rfc3161_timestamps: vec![Rfc3161Timestamp { signed_timestamp: STANDARD.encode(token) }],
+        });
+        serde_json::to_string(&bundle).context("Failed to serialize bundle")
+    }
+
+    /// The instance's trust material in Sigstore's `trusted_root.json` format.
+    pub fn trusted_root(&self) -> TrustedRoot {
+        let log = |base_url: &str, key: &KeyPair| TransparencyLogInstance {
+            base_url: base_url.to_string(),
+            hash_algorithm: Some("SHA2_256".to_string()),
+            public_key: TrustedPublicKey {
+                raw_bytes: STANDARD.encode(key.public_key_der()),
+                key_details: Some("PKIX_ECDSA_P256_SHA_256".to_string()),
+                valid_for: None,
+            },
+            log_id: LogId { key_id: STANDARD.encode(Sha256::digest(key.public_key_der())) },
+        };
+        TrustedRoot {
+            media_type: TRUSTED_ROOT_MEDIA_TYPE.to_string(),
+            tlogs: vec![log("https://rekor.example.test", &self.rekor_key)],
+            certificate_authorities: vec![CertificateAuthority {
+                uri: Some("https://fulcio.example.test".to_string()),
+                cert_chain: CertificateChain {
+                    certificates: vec![CertificateData { raw_bytes: STANDARD.encode(self.ca_certificate.der()) }],
+                },
+                valid_for: None,
+            }],
+            ctlogs: vec![log("https://ctfe.example.test", &self.ct_key)],
+            timestamp_authorities: vec![CertificateAuthority {
+                uri: Some("https://tsa.example.test".to_string()),
+                cert_chain: CertificateChain {
+                    certificates: vec![CertificateData { raw_bytes: STANDARD.encode(self.tsa_certificate.der()) }],
+                },
+                valid_for: None,
+            }],
+        }
+    }
+
+    /// Signs an RFC 6962 precertificate entry for `tbs`, returning the TLS-encoded SCT.
+    fn sign_certificate_timestamp(&self, tbs: &[u8]) -> Result<Vec<u8>> {
+        let log_id = Sha256::digest(self.ct_key.public_key_der());
+        let timestamp = (Utc::now().timestamp_millis() as u64).to_be_bytes();
+        let mut signed = vec![0, 0];
+        signed.extend_from_slice(&timestamp);
+        signed.extend_from_slice(&[0, 1]);
+        signed.extend_from_slice(&Sha256::digest(self.ca_key.public_key_der()));
+        signed.extend_from_slice(&(tbs.len() as u32).to_be_bytes()[1..]);
+        signed.extend_from_slice(tbs);
+        signed.extend_from_slice(&[0, 0]);
+        let signature: p256::ecdsa::Signature = signing_key(&self.ct_key)?.sign(&signed);
+        let signature = signature.to_der();
+
+        let mut sct = vec![0];
+        sct.extend_from_slice(&log_id);
+        sct.extend_from_slice(&timestamp);
+        sct.extend_from_slice(&[0, 0]);
+        // SHA-256 with ECDSA
+        sct.extend_from_slice(&[4, 3]);
+        sct.extend_from_slice(&(signature.as_bytes().len() as u16).to_be_bytes());
+        sct.extend_from_slice(signature.as_bytes());
+        Ok(sct)
+    }
+}
+
+/// Lowercase hex, as Rekor writes hashes and log IDs.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn signing_key(key_pair: &KeyPair) -> Result<SigningKey> {
+    SigningKey::from_pkcs8_der(&key_pair.serialize_der()).context("Failed to load a P-256 key for signing")
+}
+
+/// The TBSCertificate element of a DER certificate.
+fn tbs_certificate(certificate: &[u8]) -> Result<&[u8]> {
+    // Skips a tag and length, returning the header length and the content length.
+    let header = |der: &[u8]| -> Result<(usize, usize)> {
+        match der.get(1) {
+            Some(&len) if len < 0x80 => Ok((2, len as usize)),
+            Some(&0x81) if der.len() > 2 => Ok((3, der[2] as usize)),
+            Some(&0x82) if der.len() > 3 => Ok((4, (der[2] as usize) << 8 | der[3] as usize)),
+            _ => bail!("Unsupported DER length"),
+        }
+    };
+    let (outer, _) = header(certificate)?;
+    let tbs = &certificate[outer..];
+    let (inner, len) = header(tbs)?;
+    tbs.get(..inner + len).context("Truncated TBSCertificate")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chainsights_core::identity::SignerIdentity;
+    use chainsights_core::statement::InTotoStatement;
+    use chainsights_core::timestamp::TimeSource;
+    use chainsights_core::verify::{verify_signature_with_pae, verify_signature_with_trusted_root};
+    use chrono::Duration;
+
+    use super::*;
+
+    fn signed_bundle(signer: &EphemeralSigner) -> String {
+        let statement = InTotoStatement::new("https://example.test/x.json", HashMap::new(), "https://example.test/v1", serde_json::json!({"a": 1}));
+        signer.sign(&statement).unwrap().0
+    }
+
+    #[test]
+    fn bundles_verify_against_their_instance_only() {
+        let instance = PrivateSigstore::new().unwrap();
+        let identities = [SignerIdentity::new("release@example.test")];
+        let verify = |bundle: &str, root: &TrustedRoot| {
+            verify_signature_with_trusted_root(bundle, &identities, TimeSource::Rekor, Duration::minutes(5), root)
+        };
+        let bundle = instance.log(&signed_bundle(&instance.signer("release@example.test").unwrap())).unwrap();
+        let verified = verify(&bundle, &instance.trusted_root()).unwrap();
+        assert!(verified.rekor_entry);
+
+        // Certificates from another CA, and Rekor entries from another log, are rejected.
+        let other = PrivateSigstore::new().unwrap();
+        assert!(verify(&bundle, &other.trusted_root()).is_err());
+        let self_signed = instance.log(&signed_bundle(&EphemeralSigner::new("release@example.test").unwrap())).unwrap();
+        assert!(verify(&self_signed, &instance.trusted_root()).is_err());
+        let foreign_log = other.log(&signed_bundle(&instance.signer("release@example.test").unwrap())).unwrap();
+        assert!(verify(&foreign_log, &instance.trusted_root()).is_err());
+
+        // The signed entry timestamp covers the integrated time.
+        let mut tampered: SigstoreBundleData = serde_json::from_str(&bundle).unwrap();
+        tampered.verification_material.tlog_entries[0].integrated_time = "1".to_string();
+        assert!(verify(&serde_json::to_string(&tampered).unwrap(), &instance.trusted_root()).is_err());
+    }
+
+    #[test]
+    fn trusted_times_need_signatures_from_the_trusted_root() {
+        let instance = PrivateSigstore::new().unwrap();
+        let identities = [SignerIdentity::new("release@example.test")];
+        let bundle = instance.timestamp(&instance.log(&signed_bundle(&instance.signer("release@example.test").unwrap())).unwrap()).unwrap();
+        let verify = |bundle: &str, time_source, root: &TrustedRoot| {
+            verify_signature_with_trusted_root(bundle, &identities, time_source, Duration::minutes(5), root)
+        };
+        for time_source in [TimeSource::Rekor, TimeSource::Rfc3161] {
+            let verified = verify(&bundle, time_source, &instance.trusted_root()).unwrap();
+            assert!(verified.trusted_time.is_some_and(|time| (Utc::now() - time).num_minutes().abs() < 5));
+            // Without a trusted root the bundle's timestamps cannot be checked, so they are not used.
+            assert!(verify_signature_with_pae(&bundle, &identities, time_source, Duration::minutes(5)).is_err());
+        }
+
+        // A timestamp from another authority is rejected.
+        let mut root = instance.trusted_root();
+        root.timestamp_authorities = PrivateSigstore::new().unwrap().trusted_root().timestamp_authorities;
+        let Err(error) = verify(&bundle, TimeSource::Rfc3161, &root) else {
+            panic!("verified a timestamp from an untrusted authority");
+        };
+        assert!(format!("{:#}", error).contains("timestamp authority"), "{:#}", error);
+        assert!(verify(&bundle, TimeSource::Local, &root).is_ok());
+    }
+
+    #[test]
+    fn identity_windows_ignore_unverified_integrated_times() {
+        let instance = PrivateSigstore::new().unwrap();
+        let retired = SignerIdentity { not_after: Some("2020-01-01T00:00:00Z".to_string()), ..SignerIdentity::new("release@example.test") };
+        let bundle = instance.log(&signed_bundle(&instance.signer("release@example.test").unwrap())).unwrap();
+        let mut forged: SigstoreBundleData = serde_json::from_str(&bundle).unwrap();
+        forged.verification_material.tlog_entries[0].integrated_time = "1546300800".to_string();
+        let forged = serde_json::to_string(&forged).unwrap();
+
+        // Without a trusted root the integrated time is unverified, so it cannot revive the identity.
+        let Err(error) = verify_signature_with_pae(&forged, std::slice::from_ref(&retired), TimeSource::Local, Duration::minutes(5)) else {
+            panic!("a forged integrated time revived a retired identity");
+        };
+        assert!(format!("{:#}", error).contains("not accepted"), "{:#}", error);
+        // With one, the signed entry timestamp no longer matches.
+        assert!(verify_signature_with_trusted_root(&forged, &[retired], TimeSource::Local, Duration::minutes(5), &instance.trusted_root()).is_err());
+    }
+
+    #[test]
+    fn integrated_times_count_only_for_entries_recording_the_bundle() {
+        let instance = PrivateSigstore::new().unwrap();
+        let retired = SignerIdentity { not_after: Some((Utc::now() - Duration::hours(1)).to_rfc3339()), ..SignerIdentity::new("release@example.test") };
+        let verify = |bundle: &str, identity: &SignerIdentity| {
+            verify_signature_with_trusted_root(bundle, std::slice::from_ref(identity), TimeSource::Local, Duration::minutes(5), &instance.trusted_root())
+        };
+        let sign = || signed_bundle(&instance.signer("release@example.test").unwrap());
+        let genuine = instance.log_at(&sign(), Utc::now() - Duration::hours(2)).unwrap();
+        assert!(verify(&genuine, &retired).is_ok());
+
+        // A genuine entry from while the identity was accepted cannot vouch for a bundle signed
+        // since with a fresh certificate.
+        let genuine: SigstoreBundleData = serde_json::from_str(&genuine).unwrap();
+        let unlogged = sign();
+        let mut forged: SigstoreBundleData = serde_json::from_str(&unlogged).unwrap();
+        forged.verification_material.tlog_entries = genuine.verification_material.tlog_entries;
+        let forged = serde_json::to_string(&forged).unwrap();
+        let Err(error) = verify(&forged, &retired) else {
+            panic!("an entry of another bundle revived a retired identity");
+        };
+        assert!(format!("{:#}", error).contains("does not record this bundle"), "{:#}", error);
+        // Nor can it give the bundle a signing time with --time-source rekor.
+        let identities = [SignerIdentity::new("release@example.test")];
+        assert!(verify_signature_with_trusted_root(&forged, &identities, TimeSource::Rekor, Duration::minutes(5), &instance.trusted_root()).is_err());
+        assert!(verify(&instance.log(&unlogged).unwrap(), &retired).is_err());
+
+        // An integrated time standing in for the certificate's notBefore must fall within its validity.
+        let backdated = instance.log_at(&sign(), Utc::now() - Duration::days(30)).unwrap();
+        let Err(error) = verify(&backdated, &SignerIdentity::new("release@example.test")) else {
+            panic!("verified an integrated time outside the certificate's validity");
+        };
+        assert!(format!("{:#}", error).contains("not valid at signing time"), "{:#}", error);
+    }
+
+    #[test]
+    fn certificates_need_a_timestamp_from_a_trusted_ct_log() {
+        let instance = PrivateSigstore::new().unwrap();
+        let identities = [SignerIdentity::new("release@example.test")];
+        let bundle = signed_bundle(&instance.signer("release@example.test").unwrap());
+        let mut root = instance.trusted_root();
+        let verify = |root: &TrustedRoot| {
+            verify_signature_with_trusted_root(&bundle, &identities, TimeSource::Local, Duration::minutes(5), root)
+        };
+        assert!(verify(&root).is_ok());
+
+        root.ctlogs = PrivateSigstore::new().unwrap().trusted_root().ctlogs;
+        let Err(error) = verify(&root) else {
+            panic!("verified without a trusted SCT");
+        };
+        assert!(format!("{:#}", error).contains("signed certificate timestamp"), "{:#}", error);
+
+        // Without CT logs in the trusted root, SCTs are not required.
+        root.ctlogs.clear();
+        assert!(verify(&root).is_ok());
+    }
+}